@@ -0,0 +1,12 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+// `parse` must return rather than panic on any input, and whatever it
+// accepts must serialize to something it accepts again.
+fuzz_target!(|data: &[u8]| {
+    if let Ok(v) = json_parser::parse(data) {
+        let s = v.to_json_string();
+        assert!(json_parser::parse(s.as_bytes()).is_ok(), "{}", s);
+    }
+});
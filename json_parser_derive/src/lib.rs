@@ -0,0 +1,589 @@
+//! `#[derive(FromJson)]` for `json_parser`, enabled by its `derive` feature.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::{
+    parse_macro_input, parse_quote, Attribute, Data, DataEnum, DeriveInput, ExprPath, Field,
+    Fields, FieldsNamed, GenericParam, Ident, LitStr, Token, Variant,
+};
+
+/// Decodes a struct with named fields from a JSON object, field by field.
+/// A missing key is an error unless the field is an `Option` or `Presence`.
+///
+/// Unknown keys follow `ParseOptions::unknown_fields`, unless a map field is
+/// marked `#[json(unknown)]` to collect them. Fields take attributes:
+///
+/// - `#[json(rename = "key")]` reads a field from a key other than its name;
+/// - `#[json(default)]` fills a missing field with `Default::default()`, and
+///   `#[json(default = "path::to::fn")]` with what that function returns;
+/// - `#[json(skip)]` never reads a field, setting it to its default (the
+///   key is then unknown);
+/// - `#[json(flatten)]` decodes a field (a struct or a map) from the same
+///   object, seeing only the keys the other fields don't read, including
+///   other flattened fields. A key a flattened map takes isn't unknown to
+///   the flattened structs beside it.
+///
+/// Enums are decoded as serde represents them, picked by an attribute on the
+/// enum:
+///
+/// - by default, externally tagged: `"Unit"` or `{"Variant": content}`;
+/// - `#[json(tag = "type")]`, internally tagged: `{"type": "Variant", ...}`
+///   with the variant's fields alongside the tag (no tuple variants);
+/// - `#[json(tag = "t", content = "c")]`, adjacently tagged:
+///   `{"t": "Variant", "c": content}`;
+/// - `#[json(untagged)]`: the first variant whose content decodes, with unit
+///   variants matching `null`.
+///
+/// Content is `null` for a unit variant, the value for a one-field tuple
+/// variant, an array for longer ones and an object for struct variants.
+/// `#[json(rename = "name")]` on a variant changes its tag.
+#[proc_macro_derive(FromJson, attributes(json))]
+pub fn derive_from_json(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match expand(input) {
+        Ok(tokens) => tokens.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+#[derive(Default)]
+struct FieldAttrs {
+    unknown: bool,
+    rename: Option<String>,
+    // the expression giving a missing field's value
+    default: Option<TokenStream2>,
+    skip: bool,
+    flatten: bool,
+}
+
+fn field_attrs(field: &Field) -> syn::Result<FieldAttrs> {
+    let mut attrs = FieldAttrs::default();
+    for attr in field.attrs.iter().filter(|a| a.path().is_ident("json")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("unknown") {
+                attrs.unknown = true;
+                Ok(())
+            } else if meta.path.is_ident("rename") {
+                attrs.rename = Some(meta.value()?.parse::<LitStr>()?.value());
+                Ok(())
+            } else if meta.path.is_ident("default") {
+                attrs.default = Some(if meta.input.peek(Token![=]) {
+                    let path: ExprPath = meta.value()?.parse::<LitStr>()?.parse()?;
+                    quote! { #path() }
+                } else {
+                    quote! { ::core::default::Default::default() }
+                });
+                Ok(())
+            } else if meta.path.is_ident("skip") {
+                attrs.skip = true;
+                Ok(())
+            } else if meta.path.is_ident("flatten") {
+                attrs.flatten = true;
+                Ok(())
+            } else {
+                Err(meta.error("unsupported json attribute"))
+            }
+        })?;
+    }
+    Ok(attrs)
+}
+
+// How an enum's variants are told apart in JSON.
+enum Tagging {
+    External,
+    Internal { tag: String },
+    Adjacent { tag: String, content: String },
+    Untagged,
+}
+
+fn tagging(attrs: &[Attribute]) -> syn::Result<Tagging> {
+    let mut tag = None;
+    let mut content = None;
+    let mut untagged = false;
+    for attr in attrs.iter().filter(|a| a.path().is_ident("json")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("tag") {
+                tag = Some(meta.value()?.parse::<LitStr>()?.value());
+                Ok(())
+            } else if meta.path.is_ident("content") {
+                content = Some(meta.value()?.parse::<LitStr>()?.value());
+                Ok(())
+            } else if meta.path.is_ident("untagged") {
+                untagged = true;
+                Ok(())
+            } else {
+                Err(meta.error("unsupported json attribute"))
+            }
+        })?;
+    }
+    let error = |msg| Err(syn::Error::new(proc_macro2::Span::call_site(), msg));
+    match (tag, content, untagged) {
+        (None, None, false) => Ok(Tagging::External),
+        (Some(tag), None, false) => Ok(Tagging::Internal { tag }),
+        (Some(tag), Some(content), false) => Ok(Tagging::Adjacent { tag, content }),
+        (None, None, true) => Ok(Tagging::Untagged),
+        (None, Some(_), _) => error("#[json(content)] needs #[json(tag)]"),
+        (_, _, true) => error("#[json(untagged)] can't have a tag"),
+    }
+}
+
+// The tag of a variant: its name, or `#[json(rename)]`.
+fn variant_name(variant: &Variant) -> syn::Result<String> {
+    let mut name = variant.ident.to_string();
+    for attr in variant.attrs.iter().filter(|a| a.path().is_ident("json")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("rename") {
+                name = meta.value()?.parse::<LitStr>()?.value();
+                Ok(())
+            } else {
+                Err(meta.error("unsupported json attribute"))
+            }
+        })?;
+    }
+    Ok(name)
+}
+
+fn expand(mut input: DeriveInput) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+    let mut flattened_keys = None;
+    let body = match &input.data {
+        Data::Struct(data) => {
+            let Fields::Named(fields) = &data.fields else {
+                return Err(syn::Error::new_spanned(
+                    name,
+                    "FromJson can only be derived for structs with named fields",
+                ));
+            };
+            flattened_keys = Some(struct_keys(fields)?);
+            decode_object(fields, quote! { Self }, &name.to_string(), None)?
+        }
+        Data::Enum(data) => expand_enum(name, data, tagging(&input.attrs)?)?,
+        Data::Union(_) => {
+            return Err(syn::Error::new_spanned(
+                name,
+                "FromJson can only be derived for structs and enums",
+            ))
+        }
+    };
+
+    for param in &mut input.generics.params {
+        if let GenericParam::Type(t) = param {
+            t.bounds.push(parse_quote!(::json_parser::FromJson));
+        }
+    }
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::json_parser::FromJson for #name #ty_generics #where_clause {
+            fn from_json(
+                de: &mut ::json_parser::Decoder,
+            ) -> ::json_parser::__private::eyre::Result<Self> {
+                #body
+            }
+
+            #flattened_keys
+        }
+    })
+}
+
+// `FromJson::flattened_keys` for a struct: its own keys and those of its
+// flattened fields, or `None` if any of them reads every key.
+fn struct_keys(fields: &FieldsNamed) -> syn::Result<TokenStream2> {
+    let mut keys = Vec::new();
+    let mut flattened = Vec::new();
+    for field in &fields.named {
+        let attrs = field_attrs(field)?;
+        if attrs.unknown {
+            return Ok(quote! {});
+        }
+        if attrs.skip {
+            continue;
+        }
+        if attrs.flatten {
+            flattened.push(&field.ty);
+            continue;
+        }
+        keys.push(field_key(field, attrs.rename));
+    }
+    Ok(quote! {
+        fn flattened_keys() -> ::core::option::Option<::std::vec::Vec<&'static str>> {
+            let mut keys = ::std::vec![#(#keys),*];
+            #(keys.extend(<#flattened as ::json_parser::FromJson>::flattened_keys()?);)*
+            ::core::option::Option::Some(keys)
+        }
+    })
+}
+
+// The key a field is read from: its name, or `#[json(rename)]`.
+fn field_key(field: &Field, rename: Option<String>) -> String {
+    rename.unwrap_or_else(|| {
+        let key = field.ident.as_ref().expect("named field").to_string();
+        key.strip_prefix("r#").unwrap_or(&key).to_string()
+    })
+}
+
+// Statements decoding an object into `ctor { fields }`, ending in the
+// `Result`. `what` names it in errors; the key `skip` (an internal tag) is
+// passed over.
+fn decode_object(
+    fields: &FieldsNamed,
+    ctor: TokenStream2,
+    what: &str,
+    skip: Option<&str>,
+) -> syn::Result<TokenStream2> {
+    let mut decls = Vec::new();
+    let mut arms = Vec::new();
+    let mut inits = Vec::new();
+    let mut unknown = None;
+    // keys this object reads itself, hidden from flattened fields
+    let mut keys = Vec::new();
+    let mut flattened = Vec::new();
+    if let Some(skip) = skip {
+        arms.push(quote! { #skip => de.skip()?, });
+        keys.push(skip.to_string());
+    }
+    for (i, field) in fields.named.iter().enumerate() {
+        let attrs = field_attrs(field)?;
+        let ident = field.ident.as_ref().expect("named field");
+        let ty = &field.ty;
+        // a local that can't clash with `de` or `key`
+        let slot = format_ident!("__field{}", i);
+
+        if attrs.skip {
+            let default = attrs
+                .default
+                .unwrap_or_else(|| quote! { ::core::default::Default::default() });
+            inits.push(quote! { #ident: #default });
+            continue;
+        }
+        if attrs.flatten {
+            flattened.push((ident, ty));
+            continue;
+        }
+        if attrs.unknown {
+            if unknown.is_some() {
+                return Err(syn::Error::new_spanned(
+                    ident,
+                    "only one field can be #[json(unknown)]",
+                ));
+            }
+            decls.push(quote! {
+                let mut #slot: #ty = ::core::default::Default::default();
+            });
+            unknown = Some(quote! {
+                {
+                    #slot.insert(::std::string::ToString::to_string(key), de.decode()?);
+                }
+            });
+            inits.push(quote! { #ident: #slot });
+            continue;
+        }
+
+        let key = field_key(field, attrs.rename);
+        let missing = format!("Missing field `{}` of {}", key, what);
+        let absent = attrs.default.unwrap_or_else(|| {
+            quote! {
+                return ::core::result::Result::Err(
+                    ::json_parser::__private::eyre::eyre!(#missing),
+                )
+            }
+        });
+        decls.push(quote! {
+            let mut #slot: ::core::option::Option<#ty> = ::core::option::Option::None;
+        });
+        arms.push(quote! {
+            #key => #slot = ::core::option::Option::Some(de.decode()?),
+        });
+        inits.push(quote! {
+            #ident: match #slot.or_else(<#ty as ::json_parser::FromJson>::absent) {
+                ::core::option::Option::Some(v) => v,
+                ::core::option::Option::None => #absent,
+            }
+        });
+        keys.push(key);
+    }
+
+    if flattened.is_empty() {
+        let unknown = unknown.unwrap_or_else(|| quote! { de.unknown_field(key)? });
+        return Ok(quote! {
+            #(#decls)*
+            de.object(|key, de| {
+                match key {
+                    #(#arms)*
+                    _ => #unknown,
+                }
+                ::core::result::Result::Ok(())
+            })?;
+            ::core::result::Result::Ok(#ctor { #(#inits,)* })
+        });
+    }
+    if unknown.is_some() {
+        return Err(syn::Error::new_spanned(
+            flattened[0].0,
+            "#[json(flatten)] can't be combined with #[json(unknown)]",
+        ));
+    }
+    // the object is read once for this struct's own fields, leaving other
+    // keys to the flattened fields, then again for each of those, passing
+    // over the keys the others read
+    let flat_inits = flattened.iter().enumerate().map(|(i, (ident, ty))| {
+        let siblings = flattened
+            .iter()
+            .enumerate()
+            .filter(|(j, _)| *j != i)
+            .map(|(_, (_, ty))| ty);
+        quote! {
+            #ident: {
+                let mut __hidden: ::std::vec::Vec<&str> = ::std::vec![#(#keys),*];
+                let mut __taken = false;
+                #(match <#siblings as ::json_parser::FromJson>::flattened_keys() {
+                    ::core::option::Option::Some(keys) => __hidden.extend(keys),
+                    ::core::option::Option::None => __taken = true,
+                })*
+                if __taken {
+                    de.replay_without_unknown(__start, &__hidden).decode::<#ty>()?
+                } else {
+                    de.replay_without(__start, &__hidden).decode::<#ty>()?
+                }
+            }
+        }
+    });
+    Ok(quote! {
+        let __start = de.skip_for_replay()?;
+        #(#decls)*
+        de.replay(__start).object(|key, de| {
+            match key {
+                #(#arms)*
+                _ => de.skip()?,
+            }
+            ::core::result::Result::Ok(())
+        })?;
+        ::core::result::Result::Ok(#ctor { #(#inits,)* #(#flat_inits,)* })
+    })
+}
+
+// An expression decoding a variant's content from `de` into a `Result`.
+fn decode_content(name: &Ident, variant: &Variant) -> syn::Result<TokenStream2> {
+    let ident = &variant.ident;
+    let what = format!("{}::{}", name, ident);
+    let body = match &variant.fields {
+        Fields::Unit => quote! {
+            de.null()?;
+            ::core::result::Result::Ok(Self::#ident)
+        },
+        Fields::Unnamed(fields) if fields.unnamed.len() == 1 => quote! {
+            ::core::result::Result::Ok(Self::#ident(de.decode()?))
+        },
+        Fields::Unnamed(fields) => {
+            let len = fields.unnamed.len();
+            let slots: Vec<_> = (0..len).map(|i| format_ident!("__elem{}", i)).collect();
+            let tys = fields.unnamed.iter().map(|f| &f.ty);
+            let indices = 0..len;
+            let wrong = format!("Expected {} elements for {}, found {{}}", len, what);
+            quote! {
+                #(let mut #slots: ::core::option::Option<#tys> = ::core::option::Option::None;)*
+                let mut __len = 0usize;
+                de.array(|de| {
+                    match __len {
+                        #(#indices => #slots = ::core::option::Option::Some(de.decode()?),)*
+                        _ => de.skip()?,
+                    }
+                    __len += 1;
+                    ::core::result::Result::Ok(())
+                })?;
+                match (#(#slots,)*) {
+                    (#(::core::option::Option::Some(#slots),)*) if __len == #len => {
+                        ::core::result::Result::Ok(Self::#ident(#(#slots),*))
+                    }
+                    _ => ::core::result::Result::Err(
+                        ::json_parser::__private::eyre::eyre!(::std::format!(#wrong, __len)),
+                    ),
+                }
+            }
+        }
+        Fields::Named(fields) => decode_object(fields, quote! { Self::#ident }, &what, None)?,
+    };
+    Ok(in_closure(body))
+}
+
+// Runs `body` in a closure over `de`, so its `?` and `return` stay inside.
+fn in_closure(body: TokenStream2) -> TokenStream2 {
+    quote! {
+        (|de: &mut ::json_parser::Decoder<'_>| -> ::json_parser::__private::eyre::Result<Self> {
+            #body
+        })(de)
+    }
+}
+
+fn expand_enum(name: &Ident, data: &DataEnum, tagging: Tagging) -> syn::Result<TokenStream2> {
+    let unknown = format!("Unknown variant `{{}}` of {} at byte {{}}", name);
+    let unknown = quote! {
+        ::json_parser::__private::eyre::bail!(::std::format!(#unknown, other, __start))
+    };
+    let mut names = Vec::new();
+    for variant in &data.variants {
+        names.push(variant_name(variant)?);
+    }
+    let variants = data.variants.iter().zip(&names);
+    match tagging {
+        Tagging::External => {
+            let mut units = Vec::new();
+            let mut arms = Vec::new();
+            for (variant, tag) in variants {
+                let ident = &variant.ident;
+                if let Fields::Unit = variant.fields {
+                    units.push(quote! { #tag => ::core::result::Result::Ok(Self::#ident), });
+                }
+                let content = decode_content(name, variant)?;
+                arms.push(quote! { #tag => #content?, });
+            }
+            let several = format!("Expected one key for {} at byte {{}}, found more", name);
+            let empty = format!(
+                "Expected a variant of {} at byte {{}}, found an empty object",
+                name
+            );
+            Ok(quote! {
+                let __start = de.position();
+                if de.peek()? == ::core::option::Option::Some(::json_parser::lexer::TokenKind::String) {
+                    let __tag = de.str()?;
+                    return match &*__tag {
+                        #(#units)*
+                        other => #unknown,
+                    };
+                }
+                let mut __out = ::core::option::Option::None;
+                de.object(|key, de| {
+                    if __out.is_some() {
+                        ::json_parser::__private::eyre::bail!(::std::format!(#several, __start))
+                    }
+                    __out = ::core::option::Option::Some(match key {
+                        #(#arms)*
+                        other => #unknown,
+                    });
+                    ::core::result::Result::Ok(())
+                })?;
+                __out.ok_or_else(|| {
+                    ::json_parser::__private::eyre::eyre!(::std::format!(#empty, __start))
+                })
+            })
+        }
+        Tagging::Internal { tag } => {
+            let mut arms = Vec::new();
+            for (variant, name_tag) in variants {
+                let ident = &variant.ident;
+                let what = format!("{}::{}", name, ident);
+                let decode = match &variant.fields {
+                    Fields::Unit => quote! {
+                        de.skip()?;
+                        ::core::result::Result::Ok(Self::#ident)
+                    },
+                    // the tag reaches the inner type as an unknown field
+                    Fields::Unnamed(fields) if fields.unnamed.len() == 1 => quote! {
+                        ::core::result::Result::Ok(Self::#ident(de.decode()?))
+                    },
+                    Fields::Unnamed(_) => {
+                        return Err(syn::Error::new_spanned(
+                            ident,
+                            "an internally tagged enum can't have tuple variants",
+                        ))
+                    }
+                    Fields::Named(fields) => {
+                        decode_object(fields, quote! { Self::#ident }, &what, Some(&tag))?
+                    }
+                };
+                let decode = in_closure(decode);
+                arms.push(quote! { #name_tag => #decode, });
+            }
+            let missing = format!("Missing tag `{}` of {} at byte {{}}", tag, name);
+            Ok(quote! {
+                let __start = de.skip_for_replay()?;
+                let mut __tag = ::core::option::Option::None;
+                de.replay(__start).object(|key, de| {
+                    if key == #tag && __tag.is_none() {
+                        __tag = ::core::option::Option::Some(de.str()?.into_owned());
+                    } else {
+                        de.skip()?;
+                    }
+                    ::core::result::Result::Ok(())
+                })?;
+                let ::core::option::Option::Some(__tag) = __tag else {
+                    ::json_parser::__private::eyre::bail!(::std::format!(#missing, __start))
+                };
+                let de = &mut de.replay(__start);
+                match __tag.as_str() {
+                    #(#arms)*
+                    other => #unknown,
+                }
+            })
+        }
+        Tagging::Adjacent { tag, content } => {
+            let missing = format!("Missing tag `{}` of {} at byte {{}}", tag, name);
+            let missing_content = format!("Missing content `{}` of {} at byte {{}}", content, name);
+            let mut arms = Vec::new();
+            for (variant, name_tag) in variants {
+                let ident = &variant.ident;
+                let decode = decode_content(name, variant)?;
+                // only a unit variant may leave the content out
+                let absent = match variant.fields {
+                    Fields::Unit => quote! { ::core::result::Result::Ok(Self::#ident) },
+                    _ => quote! {
+                        ::json_parser::__private::eyre::bail!(
+                            ::std::format!(#missing_content, __start)
+                        )
+                    },
+                };
+                arms.push(quote! {
+                    #name_tag => match __content {
+                        ::core::option::Option::Some(c) => {
+                            let de = &mut de.replay(c);
+                            #decode
+                        }
+                        ::core::option::Option::None => #absent,
+                    },
+                });
+            }
+            Ok(quote! {
+                let __start = de.skip_for_replay()?;
+                let mut __tag = ::core::option::Option::None;
+                let mut __content = ::core::option::Option::None;
+                de.replay(__start).object(|key, de| {
+                    if key == #tag {
+                        __tag = ::core::option::Option::Some(de.str()?.into_owned());
+                    } else if key == #content {
+                        __content = ::core::option::Option::Some(de.skip_for_replay()?);
+                    } else {
+                        de.unknown_field(key)?;
+                    }
+                    ::core::result::Result::Ok(())
+                })?;
+                let ::core::option::Option::Some(__tag) = __tag else {
+                    ::json_parser::__private::eyre::bail!(::std::format!(#missing, __start))
+                };
+                match __tag.as_str() {
+                    #(#arms)*
+                    other => #unknown,
+                }
+            })
+        }
+        Tagging::Untagged => {
+            let mut tries = Vec::new();
+            for variant in &data.variants {
+                let decode = decode_content(name, variant)?;
+                tries.push(quote! {
+                    if let ::core::result::Result::Ok(v) = {
+                        let de = &mut de.replay(__start);
+                        #decode
+                    } {
+                        return ::core::result::Result::Ok(v);
+                    }
+                });
+            }
+            let none = format!("No variant of {} matches the value at byte {{}}", name);
+            Ok(quote! {
+                let __start = de.skip_for_replay()?;
+                #(#tries)*
+                ::json_parser::__private::eyre::bail!(::std::format!(#none, __start))
+            })
+        }
+    }
+}
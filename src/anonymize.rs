@@ -0,0 +1,101 @@
+use crate::generate::Rng;
+use crate::{pointer, JSONValue, PathPattern};
+
+/// Knobs for `anonymize`.
+#[derive(Debug, Clone, Default)]
+pub struct AnonymizeOptions {
+    /// Replacements are a function of the seed and the original, so equal
+    /// values stay equal across documents anonymized with the same seed.
+    pub seed: u64,
+    /// Values (and everything inside them) to leave as they are, e.g.
+    /// `/**/type`.
+    pub keep: Vec<PathPattern>,
+}
+
+// FNV-1a, which unlike `DefaultHasher` is stable across Rust releases.
+fn fnv1a(seed: u64, bytes: &[u8]) -> u64 {
+    let mut h = 0xcbf2_9ce4_8422_2325 ^ seed;
+    for b in bytes {
+        h ^= *b as u64;
+        h = h.wrapping_mul(0x0100_0000_01b3);
+    }
+    h
+}
+
+fn fake_char(rng: &mut Rng, c: char) -> char {
+    let pick = |rng: &mut Rng, first: u8, n: u64| (first + rng.below(n) as u8) as char;
+    if c.is_ascii_digit() {
+        pick(rng, b'0', 10)
+    } else if c.is_uppercase() {
+        pick(rng, b'A', 26)
+    } else if c.is_alphabetic() {
+        pick(rng, b'a', 26)
+    } else {
+        // punctuation and spacing carry the format, e.g. in emails and dates
+        c
+    }
+}
+
+fn fake_string(s: &str, seed: u64) -> String {
+    let mut rng = Rng::new(fnv1a(seed, s.as_bytes()));
+    s.chars().map(|c| fake_char(&mut rng, c)).collect()
+}
+
+// Replaces each digit, keeping the sign, the number of digits on each side
+// of the point and where the first non-zero digit is, so the magnitude is
+// the same.
+fn fake_number(n: f64, seed: u64) -> f64 {
+    let text = n.to_string();
+    let mut rng = Rng::new(fnv1a(seed, text.as_bytes()));
+    let mut leading = true;
+    let fake: String = text
+        .chars()
+        .map(|c| {
+            if !c.is_ascii_digit() {
+                return c;
+            }
+            let d = match (leading, c) {
+                (true, '0') => '0',
+                (true, _) => (b'1' + rng.below(9) as u8) as char,
+                (false, c) => fake_char(&mut rng, c),
+            };
+            leading &= c == '0';
+            d
+        })
+        .collect();
+    fake.parse().unwrap_or(n)
+}
+
+/// Replaces every string and number in `value` with fake data of the same
+/// shape: strings keep their length, case and punctuation, numbers their
+/// sign and magnitude. Keys, booleans and nulls are left alone, so the
+/// result can be shared in bug reports without leaking the original.
+pub fn anonymize(value: &mut JSONValue, options: &AnonymizeOptions) {
+    walk(value, &mut String::new(), options)
+}
+
+fn walk(v: &mut JSONValue, ptr: &mut String, options: &AnonymizeOptions) {
+    if options.keep.iter().any(|p| p.matches(ptr)) {
+        return;
+    }
+    let len = ptr.len();
+    match v {
+        JSONValue::Str(s) => *s = fake_string(s, options.seed),
+        JSONValue::Num(n) => *n = fake_number(*n, options.seed),
+        JSONValue::Array(entries) => {
+            for (i, e) in entries.iter_mut().enumerate() {
+                pointer::push(ptr, &i.to_string());
+                walk(e, ptr, options);
+                ptr.truncate(len);
+            }
+        }
+        JSONValue::Dict(d) => {
+            for (k, e) in d.iter_mut() {
+                pointer::push(ptr, k);
+                walk(e, ptr, options);
+                ptr.truncate(len);
+            }
+        }
+        JSONValue::Null | JSONValue::Bool(_) => {}
+    }
+}
@@ -0,0 +1,24 @@
+use std::process::ExitCode;
+
+use eyre::OptionExt;
+
+use crate::load_schema;
+use crate::summary::Summary;
+
+pub fn run(args: &[String]) -> eyre::Result<ExitCode> {
+    let mut schema = None;
+    let mut name = "Root";
+    let mut summary = Summary::new("codegen");
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--schema" => schema = Some(args.next().ok_or_eyre("--schema needs a file")?),
+            "--name" => name = args.next().ok_or_eyre("--name needs a name")?,
+            a if summary.parse(a, &mut args)? => {}
+            a => eyre::bail!(format!("Unexpected argument {}", a)),
+        }
+    }
+    let schema = load_schema(schema.ok_or_eyre("codegen needs --schema SCHEMA")?)?;
+    print!("{}", schema.to_rust(name)?);
+    Ok(summary.finish())
+}
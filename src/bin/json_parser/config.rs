@@ -0,0 +1,204 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use json_parser::{parse, parse_with_options, JSONValue, ParseOptions};
+
+use crate::summary::Failure;
+
+/// Names of the project config file, looked for in this order in the working
+/// directory and then each directory above it.
+pub const CONFIG_FILES: [&str; 2] = [".jsonparser.toml", "json_parser.config.json"];
+
+/// Project defaults, from the nearest config file. Flags given on the
+/// command line win.
+#[derive(Debug, Default)]
+pub struct Config {
+    /// Spaces per level for `fmt`, `sort-keys` and `watch --fmt`.
+    pub indent: Option<usize>,
+    /// Have `fmt` and `watch --fmt` sort keys too.
+    pub sort_keys: bool,
+    /// How documents are read: comments, control characters, `NaN`, depth.
+    pub parse: ParseOptions,
+    /// Globs of files and directories skipped when walking directories, as
+    /// in a `.gitignore` in `root`.
+    pub ignore: Vec<String>,
+    /// The directory the config file is in.
+    pub root: PathBuf,
+}
+
+impl Config {
+    /// The config for the working directory, or the defaults if there's no
+    /// config file.
+    pub fn load() -> eyre::Result<Self> {
+        let cwd = std::env::current_dir()
+            .map_err(|e| eyre::eyre!(format!("Can't find the working directory: {}", e)))?;
+        for dir in cwd.ancestors() {
+            for name in CONFIG_FILES {
+                let path = dir.join(name);
+                if path.is_file() {
+                    return Self::from_file(&path);
+                }
+            }
+        }
+        Ok(Self {
+            root: cwd,
+            ..Self::default()
+        })
+    }
+
+    pub fn from_file(path: &Path) -> eyre::Result<Self> {
+        let bad = |e: eyre::Report| eyre::eyre!(format!("Bad config {}: {}", path.display(), e));
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| Failure::io(format!("Can't read {}: {}", path.display(), e)))?;
+        let settings = if path.extension().is_some_and(|ext| ext == "toml") {
+            parse_toml(&text)
+        } else {
+            let options = ParseOptions {
+                allow_comments: true,
+                ..ParseOptions::default()
+            };
+            parse_with_options(text.as_bytes(), &options)
+        };
+        let mut config = Self::from_value(settings.map_err(bad)?).map_err(bad)?;
+        config.root = path.parent().unwrap_or(Path::new(".")).to_path_buf();
+        Ok(config)
+    }
+
+    fn from_value(settings: JSONValue) -> eyre::Result<Self> {
+        let JSONValue::Dict(settings) = settings else {
+            eyre::bail!("expected an object of settings")
+        };
+        let mut config = Self::default();
+        for (key, value) in settings {
+            // `sort-keys` and `sort_keys` are both fine
+            let wrong = || eyre::eyre!(format!("{} can't be {}", key, value.to_json_string()));
+            match (key.replace('_', "-").as_str(), &value) {
+                ("indent", JSONValue::Num(n)) if n.fract() == 0.0 && *n >= 0.0 => {
+                    config.indent = Some(*n as usize)
+                }
+                ("sort-keys", JSONValue::Bool(b)) => config.sort_keys = *b,
+                ("allow-comments", JSONValue::Bool(b)) => config.parse.allow_comments = *b,
+                ("allow-control-chars", JSONValue::Bool(b)) => {
+                    config.parse.allow_control_chars = *b
+                }
+                ("allow-non-finite", JSONValue::Bool(b)) => config.parse.allow_non_finite = *b,
+                ("max-depth", JSONValue::Num(n)) if n.fract() == 0.0 && *n >= 0.0 => {
+                    config.parse.max_depth = Some(*n as usize)
+                }
+                ("ignore", JSONValue::Array(globs)) => {
+                    config.ignore = globs
+                        .iter()
+                        .map(|g| match g {
+                            JSONValue::Str(s) => Ok(s.clone()),
+                            _ => Err(wrong()),
+                        })
+                        .collect::<eyre::Result<_>>()?
+                }
+                (
+                    "indent"
+                    | "sort-keys"
+                    | "allow-comments"
+                    | "allow-control-chars"
+                    | "allow-non-finite"
+                    | "max-depth"
+                    | "ignore",
+                    _,
+                ) => return Err(wrong()),
+                _ => eyre::bail!(format!("unknown setting {}", key)),
+            }
+        }
+        Ok(config)
+    }
+}
+
+// Reads the flat subset of TOML a config needs: `key = value` lines whose
+// values are strings, numbers, booleans or arrays of them (which may span
+// lines), with `#` comments.
+fn parse_toml(text: &str) -> eyre::Result<JSONValue> {
+    let mut settings = HashMap::new();
+    let mut lines = text.lines().enumerate();
+    while let Some((i, line)) = lines.next() {
+        let line = strip_comment(line).trim();
+        if line.is_empty() {
+            continue;
+        }
+        if line.starts_with('[') {
+            eyre::bail!(format!(
+                "line {}: tables aren't supported, only `key = value`",
+                i + 1
+            ))
+        }
+        let (key, value) = line
+            .split_once('=')
+            .ok_or_else(|| eyre::eyre!(format!("line {}: expected `key = value`", i + 1)))?;
+        let mut value = value.trim().to_string();
+        while value.starts_with('[') && !value.ends_with(']') {
+            let (_, more) = lines
+                .next()
+                .ok_or_else(|| eyre::eyre!(format!("line {}: unclosed array", i + 1)))?;
+            value.push(' ');
+            value.push_str(strip_comment(more).trim());
+        }
+        let json = toml_to_json(&value);
+        let value = parse(json.as_bytes())
+            .map_err(|e| eyre::eyre!(format!("line {}: bad value for {}: {}", i + 1, key, e)))?;
+        settings.insert(key.trim().trim_matches('"').to_string(), value);
+    }
+    Ok(JSONValue::Dict(settings))
+}
+
+// `line` up to a `#` that isn't in a string.
+fn strip_comment(line: &str) -> &str {
+    let mut quote = None;
+    let mut escaped = false;
+    for (i, c) in line.char_indices() {
+        match (quote, c) {
+            (Some('"'), '\\') if !escaped => {
+                escaped = true;
+                continue;
+            }
+            (Some(q), c) if c == q && !escaped => quote = None,
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '#') => return &line[..i],
+            _ => {}
+        }
+        escaped = false;
+    }
+    line
+}
+
+// Rewrites the TOML-only parts of a value as JSON: 'literal' strings and a
+// trailing comma in arrays.
+fn toml_to_json(value: &str) -> String {
+    let mut out = String::new();
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => {
+                out.push(c);
+                while let Some(c) = chars.next() {
+                    out.push(c);
+                    match c {
+                        '\\' => out.extend(chars.next()),
+                        '"' => break,
+                        _ => {}
+                    }
+                }
+            }
+            '\'' => {
+                let literal: String = chars.by_ref().take_while(|c| *c != '\'').collect();
+                out.push_str(&JSONValue::Str(literal).to_json_string());
+            }
+            ']' => {
+                let trimmed = out.trim_end().len();
+                out.truncate(trimmed);
+                if out.ends_with(',') {
+                    out.pop();
+                }
+                out.push(c);
+            }
+            c => out.push(c),
+        }
+    }
+    out
+}
@@ -0,0 +1,114 @@
+// Lines of context around each change.
+const CONTEXT: usize = 3;
+// Past this many line pairs, a changed middle is shown as all removed and
+// all added instead of looking for common lines.
+const MAX_LCS: usize = 4_000_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Line<'a> {
+    Same(&'a str),
+    Removed(&'a str),
+    Added(&'a str),
+}
+
+// `before` turned into `after`, line by line.
+fn lines<'a>(before: &'a str, after: &'a str) -> Vec<Line<'a>> {
+    let a: Vec<_> = before.lines().collect();
+    let b: Vec<_> = after.lines().collect();
+    let prefix = a.iter().zip(&b).take_while(|(x, y)| x == y).count();
+    let suffix = a[prefix..]
+        .iter()
+        .rev()
+        .zip(b[prefix..].iter().rev())
+        .take_while(|(x, y)| x == y)
+        .count();
+    let (am, bm) = (&a[prefix..a.len() - suffix], &b[prefix..b.len() - suffix]);
+    let mut out: Vec<_> = a[..prefix].iter().map(|l| Line::Same(l)).collect();
+    if am.len() * bm.len() > MAX_LCS {
+        out.extend(am.iter().map(|l| Line::Removed(l)));
+        out.extend(bm.iter().map(|l| Line::Added(l)));
+    } else {
+        // lcs[i][j]: longest common subsequence of am[i..] and bm[j..]
+        let mut lcs = vec![vec![0usize; bm.len() + 1]; am.len() + 1];
+        for i in (0..am.len()).rev() {
+            for j in (0..bm.len()).rev() {
+                lcs[i][j] = if am[i] == bm[j] {
+                    lcs[i + 1][j + 1] + 1
+                } else {
+                    lcs[i + 1][j].max(lcs[i][j + 1])
+                };
+            }
+        }
+        let (mut i, mut j) = (0, 0);
+        while i < am.len() || j < bm.len() {
+            if i < am.len() && j < bm.len() && am[i] == bm[j] {
+                out.push(Line::Same(am[i]));
+                i += 1;
+                j += 1;
+            } else if j == bm.len() || i < am.len() && lcs[i + 1][j] >= lcs[i][j + 1] {
+                out.push(Line::Removed(am[i]));
+                i += 1;
+            } else {
+                out.push(Line::Added(bm[j]));
+                j += 1;
+            }
+        }
+    }
+    out.extend(a[a.len() - suffix..].iter().map(|l| Line::Same(l)));
+    out
+}
+
+/// A unified diff of `before` and `after`, both versions of `file`, or
+/// nothing if their lines are the same.
+pub fn unified(file: &str, before: &str, after: &str) -> String {
+    let lines = lines(before, after);
+    let changed: Vec<usize> = (0..lines.len())
+        .filter(|i| !matches!(lines[*i], Line::Same(_)))
+        .collect();
+    if changed.is_empty() {
+        return String::new();
+    }
+    let mut out = format!("--- {}\n+++ {}\n", file, file);
+    let mut k = 0;
+    while k < changed.len() {
+        // changes closer than twice the context share a hunk
+        let start = changed[k].saturating_sub(CONTEXT);
+        let mut last = changed[k];
+        while k + 1 < changed.len() && changed[k + 1] - last <= 2 * CONTEXT {
+            k += 1;
+            last = changed[k];
+        }
+        k += 1;
+        let end = (last + CONTEXT + 1).min(lines.len());
+        // line numbers where the hunk starts in each version
+        let old_start = 1 + lines[..start]
+            .iter()
+            .filter(|l| !matches!(l, Line::Added(_)))
+            .count();
+        let new_start = 1 + lines[..start]
+            .iter()
+            .filter(|l| !matches!(l, Line::Removed(_)))
+            .count();
+        let hunk = &lines[start..end];
+        let old_len = hunk.iter().filter(|l| !matches!(l, Line::Added(_))).count();
+        let new_len = hunk
+            .iter()
+            .filter(|l| !matches!(l, Line::Removed(_)))
+            .count();
+        out.push_str(&format!(
+            "@@ -{},{} +{},{} @@\n",
+            old_start, old_len, new_start, new_len
+        ));
+        for line in hunk {
+            let (mark, text) = match line {
+                Line::Same(t) => (' ', t),
+                Line::Removed(t) => ('-', t),
+                Line::Added(t) => ('+', t),
+            };
+            out.push(mark);
+            out.push_str(text);
+            out.push('\n');
+        }
+    }
+    out
+}
@@ -0,0 +1,336 @@
+use std::path::Path;
+use std::process::ExitCode;
+
+use eyre::OptionExt;
+use json_parser::{
+    parse, parse_with_options, ConfigEditor, Document, JSONValue, Node, NodeKind, ParseOptions,
+    SerializeOptions,
+};
+
+use crate::config::Config;
+use crate::diff::unified;
+use crate::files::{FileFilter, Walker};
+use crate::jobs::{jobs_arg, map_files};
+use crate::read;
+use crate::summary::{Failure, Status, Summary};
+
+// What the editing commands do with their result.
+#[derive(Debug, Default)]
+struct WriteMode {
+    // `Some` to write files back, with the backup suffix if any
+    in_place: Option<Option<String>>,
+    dry_run: bool,
+    // list the files that would change
+    check: bool,
+}
+
+impl WriteMode {
+    // Takes `arg` if it's one of the options shared by editing commands.
+    fn parse(&mut self, arg: &str) -> bool {
+        match arg {
+            "--in-place" => self.in_place = Some(None),
+            "--dry-run" => self.dry_run = true,
+            "--check" => self.check = true,
+            a => match a.strip_prefix("--in-place=") {
+                Some(suffix) => self.in_place = Some(Some(suffix.to_string())),
+                None => return false,
+            },
+        }
+        true
+    }
+
+    // Prints, diffs or writes back the edited `after` of `file`, and says
+    // whether it differs from `before`.
+    fn finish(&self, file: &str, before: &[u8], after: &str) -> eyre::Result<bool> {
+        let changed = before != after.as_bytes();
+        if self.check {
+            if changed {
+                println!("{}", file);
+            }
+            return Ok(changed);
+        }
+        if self.dry_run {
+            print!("{}", unified(file, &String::from_utf8_lossy(before), after));
+            return Ok(changed);
+        }
+        let Some(backup) = &self.in_place else {
+            print!("{}", after);
+            return Ok(changed);
+        };
+        if file == "-" {
+            return Err(Failure::usage(
+                "--in-place needs files, not standard input".to_string(),
+            ));
+        }
+        if changed {
+            if let Some(suffix) = backup {
+                let backup = format!("{}{}", file, suffix);
+                std::fs::copy(file, &backup)
+                    .map_err(|e| Failure::io(format!("Can't write {}: {}", backup, e)))?;
+            }
+            write_atomic(Path::new(file), after)
+                .map_err(|e| Failure::io(format!("Can't write {}: {}", file, e)))?;
+        }
+        Ok(changed)
+    }
+}
+
+// Replaces `path` by renaming a finished temporary file over it, so readers
+// see either the old contents or the new.
+pub fn write_atomic(path: &Path, contents: &str) -> std::io::Result<()> {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    let tmp = path.with_file_name(format!(".{}.{}.tmp", name, std::process::id()));
+    std::fs::write(&tmp, contents)?;
+    let res = std::fs::metadata(path)
+        .and_then(|meta| std::fs::set_permissions(&tmp, meta.permissions()))
+        .and_then(|_| std::fs::rename(&tmp, path));
+    if res.is_err() {
+        let _ = std::fs::remove_file(&tmp);
+    }
+    res
+}
+
+// Runs `edit` over each file, or those `walker` finds in directories, on up
+// to `jobs` threads, handling the shared options in file order. With --check
+// or --dry-run a file that would change is something found; files that
+// can't be read, parsed or written are reported and skipped.
+fn edit_files(
+    mode: &WriteMode,
+    jobs: usize,
+    walker: &Walker,
+    summary: Summary,
+    mut files: Vec<&str>,
+    edit: impl Fn(&str, &[u8]) -> eyre::Result<String> + Sync,
+) -> eyre::Result<ExitCode> {
+    if files.is_empty() {
+        files.push("-");
+    }
+    let files = walker.files(&files)?;
+    let files: Vec<&str> = files.iter().map(String::as_str).collect();
+    let results = map_files(&files, jobs, |file| {
+        let before = read(file)?;
+        let after = edit(file, &before)?;
+        Ok((before, after))
+    });
+    let (verb, status) = match mode.check || mode.dry_run {
+        true => ("would change", Status::Found),
+        false => ("changed", Status::Ok),
+    };
+    let mut summary = match mode.check || mode.dry_run || mode.in_place.is_some() {
+        true => summary.counting(&[verb, "failed"]),
+        false => summary,
+    };
+    for (file, result) in files.iter().zip(results) {
+        match result.and_then(|(before, after)| mode.finish(file, &before, &after)) {
+            Ok(true) => summary.add(file, verb, status),
+            Ok(false) => summary.add(file, "ok", Status::Ok),
+            Err(e) => summary.fail(file, "failed", &e),
+        }
+    }
+    Ok(summary.finish())
+}
+
+fn indent_arg(value: Option<&String>) -> eyre::Result<usize> {
+    value
+        .and_then(|n| n.parse().ok())
+        .ok_or_eyre("--indent needs a number of spaces")
+}
+
+fn utf8<'a>(file: &str, input: &'a [u8]) -> eyre::Result<&'a str> {
+    std::str::from_utf8(input).map_err(|e| eyre::eyre!(format!("Can't read {}: {}", file, e)))
+}
+
+/// `fmt`: re-indents documents, keeping keys in their order.
+pub fn fmt(args: &[String], config: &Config) -> eyre::Result<ExitCode> {
+    let mut mode = WriteMode::default();
+    let mut summary = Summary::new("fmt");
+    let mut indent = config.indent.unwrap_or(2);
+    let mut sort_keys = config.sort_keys;
+    let mut jobs = 1;
+    let mut filter = FileFilter::default();
+    let mut files = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--indent" => indent = indent_arg(args.next())?,
+            "--sort-keys" => sort_keys = true,
+            "--jobs" => jobs = jobs_arg(args.next())?,
+            a if mode.parse(a) => {}
+            a if filter.parse(a, &mut args)? => {}
+            a if summary.parse(a, &mut args)? => {}
+            a if a.starts_with("--") => eyre::bail!(format!("Unknown option {}", a)),
+            a => files.push(a),
+        }
+    }
+    let options = rewrite_options(config);
+    let walker = Walker::new(&filter, config)?;
+    edit_files(&mode, jobs, &walker, summary, files, |file, input| {
+        formatted(input, indent, sort_keys, &options)
+            .map_err(|e| eyre::eyre!(format!("Can't parse {}: {}", file, e)))
+    })
+}
+
+/// How the rewriting commands read documents: as configured, but without
+/// comments, which they would drop.
+pub fn rewrite_options(config: &Config) -> ParseOptions {
+    ParseOptions {
+        allow_comments: false,
+        ..config.parse.clone()
+    }
+}
+
+/// What `fmt` makes of `input`.
+pub fn formatted(
+    input: &[u8],
+    indent: usize,
+    sort_keys: bool,
+    options: &ParseOptions,
+) -> eyre::Result<String> {
+    let doc = Document::parse_with_options(input, options)?;
+    let mut out = String::new();
+    write_node(doc.root(), &" ".repeat(indent), sort_keys, 0, &mut out);
+    out.push('\n');
+    Ok(out)
+}
+
+// Writes `node` pretty-printed, with keys as ordered in the input unless
+// sorting them, and numbers as written there.
+fn write_node(node: Node<'_>, unit: &str, sort_keys: bool, depth: usize, out: &mut String) {
+    let (open, close) = match node.kind() {
+        NodeKind::Array => ('[', ']'),
+        NodeKind::Object => ('{', '}'),
+        NodeKind::String => {
+            let s = node.as_str().unwrap_or_default().into_owned();
+            out.push_str(&JSONValue::Str(s).to_json_string());
+            return;
+        }
+        _ => {
+            out.push_str(&String::from_utf8_lossy(node.raw()));
+            return;
+        }
+    };
+    out.push(open);
+    if node.is_empty() {
+        out.push(close);
+        return;
+    }
+    let inner = unit.repeat(depth + 1);
+    let mut entries: Vec<_> = match node.kind() {
+        NodeKind::Array => node.elements().map(|e| (None, e)).collect(),
+        _ => node.entries().map(|(k, v)| (Some(k), v)).collect(),
+    };
+    if sort_keys {
+        entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+    }
+    for (i, (key, value)) in entries.into_iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        out.push('\n');
+        out.push_str(&inner);
+        if let Some(key) = key {
+            out.push_str(&JSONValue::Str(key.into_owned()).to_json_string());
+            out.push_str(": ");
+        }
+        write_node(value, unit, sort_keys, depth + 1, out);
+    }
+    out.push('\n');
+    out.push_str(&unit.repeat(depth));
+    out.push(close);
+}
+
+/// `sort-keys`: rewrites documents with every object's keys sorted.
+pub fn sort_keys(args: &[String], config: &Config) -> eyre::Result<ExitCode> {
+    let mut mode = WriteMode::default();
+    let mut summary = Summary::new("sort-keys");
+    let mut indent = config.indent.unwrap_or(2);
+    let mut filter = FileFilter::default();
+    let mut files = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--indent" => indent = indent_arg(args.next())?,
+            a if mode.parse(a) => {}
+            a if filter.parse(a, &mut args)? => {}
+            a if summary.parse(a, &mut args)? => {}
+            a if a.starts_with("--") => eyre::bail!(format!("Unknown option {}", a)),
+            a => files.push(a),
+        }
+    }
+    let options = SerializeOptions {
+        indent: Some(indent),
+        sort_keys: true,
+        ..SerializeOptions::default()
+    };
+    let parse_options = rewrite_options(config);
+    let walker = Walker::new(&filter, config)?;
+    edit_files(&mode, 1, &walker, summary, files, |file, input| {
+        let doc = parse_with_options(input, &parse_options)
+            .map_err(|e| eyre::eyre!(format!("Can't parse {}: {}", file, e)))?;
+        Ok(format!("{}\n", doc.to_json_string_with(&options)))
+    })
+}
+
+/// `set PATH VALUE`: puts a JSON value at a path, leaving the rest of the
+/// file, comments included, as it was.
+pub fn set(args: &[String], config: &Config) -> eyre::Result<ExitCode> {
+    let mut mode = WriteMode::default();
+    let mut summary = Summary::new("set");
+    let mut filter = FileFilter::default();
+    let mut positional = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            a if mode.parse(a) => {}
+            a if filter.parse(a, &mut args)? => {}
+            a if summary.parse(a, &mut args)? => {}
+            a if a.starts_with("--") => eyre::bail!(format!("Unknown option {}", a)),
+            a => positional.push(a),
+        }
+    }
+    let [path, value, files @ ..] = positional.as_slice() else {
+        eyre::bail!("set needs a PATH and a VALUE")
+    };
+    let value = parse(value.as_bytes()).map_err(|e| {
+        eyre::eyre!(format!(
+            "VALUE must be JSON, e.g. '\"text\"' for a string: {}",
+            e
+        ))
+    })?;
+    let walker = Walker::new(&filter, config)?;
+    edit_files(&mode, 1, &walker, summary, files.to_vec(), |file, input| {
+        let mut config = ConfigEditor::new(utf8(file, input)?)
+            .map_err(|e| eyre::eyre!(format!("Can't parse {}: {}", file, e)))?;
+        config.set_field_preserving(path, value.clone())?;
+        Ok(config.into_string())
+    })
+}
+
+/// `del PATH`: removes a value and its key, leaving the rest of the file as
+/// it was.
+pub fn del(args: &[String], config: &Config) -> eyre::Result<ExitCode> {
+    let mut mode = WriteMode::default();
+    let mut summary = Summary::new("del");
+    let mut filter = FileFilter::default();
+    let mut positional = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            a if mode.parse(a) => {}
+            a if filter.parse(a, &mut args)? => {}
+            a if summary.parse(a, &mut args)? => {}
+            a if a.starts_with("--") => eyre::bail!(format!("Unknown option {}", a)),
+            a => positional.push(a),
+        }
+    }
+    let [path, files @ ..] = positional.as_slice() else {
+        eyre::bail!("del needs a PATH")
+    };
+    let walker = Walker::new(&filter, config)?;
+    edit_files(&mode, 1, &walker, summary, files.to_vec(), |file, input| {
+        let mut config = ConfigEditor::new(utf8(file, input)?)
+            .map_err(|e| eyre::eyre!(format!("Can't parse {}: {}", file, e)))?;
+        config.remove_field_preserving(path)?;
+        Ok(config.into_string())
+    })
+}
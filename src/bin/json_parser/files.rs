@@ -0,0 +1,271 @@
+use std::path::{Path, PathBuf};
+
+use eyre::OptionExt;
+
+use crate::config::Config;
+
+/// Files of globs to skip when walking the directory they're in and those
+/// below it.
+pub const IGNORE_FILES: [&str; 2] = [".gitignore", ".jsonparserignore"];
+
+/// `--include`/`--exclude` globs choosing the files found in directories.
+/// Globs without a `/` match names at any depth, others paths from the
+/// working directory.
+#[derive(Debug, Default)]
+pub struct FileFilter {
+    pub include: Vec<String>,
+    pub exclude: Vec<String>,
+}
+
+impl FileFilter {
+    /// Takes `arg`, and its glob from `args`, if it's one of the filter
+    /// options.
+    pub fn parse(
+        &mut self,
+        arg: &str,
+        args: &mut std::slice::Iter<'_, String>,
+    ) -> eyre::Result<bool> {
+        let globs = match arg {
+            "--include" => &mut self.include,
+            "--exclude" => &mut self.exclude,
+            _ => return Ok(false),
+        };
+        globs.push(
+            args.next()
+                .ok_or_eyre(format!("{} needs a glob", arg))?
+                .clone(),
+        );
+        Ok(true)
+    }
+}
+
+// A line of an ignore file, or a glob from the config or the command line,
+// matched against paths under `base`.
+#[derive(Debug, Clone)]
+struct Rule {
+    base: PathBuf,
+    glob: String,
+    // `!glob` takes a file back
+    negated: bool,
+    // `glob/` only matches directories
+    dir_only: bool,
+}
+
+impl Rule {
+    fn new(base: &Path, line: &str) -> Option<Self> {
+        let line = line.trim_end();
+        if line.is_empty() || line.starts_with('#') {
+            return None;
+        }
+        let (negated, line) = match line.strip_prefix('!') {
+            Some(rest) => (true, rest),
+            None => (false, line),
+        };
+        let (dir_only, line) = match line.strip_suffix('/') {
+            Some(rest) => (true, rest),
+            None => (false, line),
+        };
+        Some(Self {
+            base: base.to_path_buf(),
+            glob: line.to_string(),
+            negated,
+            dir_only,
+        })
+    }
+
+    fn matches(&self, path: &Path, is_dir: bool) -> bool {
+        if self.dir_only && !is_dir {
+            return false;
+        }
+        let Ok(relative) = path.strip_prefix(&self.base) else {
+            return false;
+        };
+        let relative = relative.to_string_lossy().replace('\\', "/");
+        match self.glob.strip_prefix('/') {
+            Some(anchored) => glob_match(anchored, &relative),
+            None if self.glob.contains('/') => glob_match(&self.glob, &relative),
+            None => relative
+                .rsplit('/')
+                .next()
+                .is_some_and(|name| glob_match(&self.glob, name)),
+        }
+    }
+}
+
+// Whether the last of `rules` to match `path` ignores it.
+fn ignored(rules: &[Rule], path: &Path, is_dir: bool) -> bool {
+    rules
+        .iter()
+        .rev()
+        .find(|rule| rule.matches(path, is_dir))
+        .is_some_and(|rule| !rule.negated)
+}
+
+// The rules in `dir`'s ignore files.
+fn ignore_rules(dir: &Path) -> Vec<Rule> {
+    IGNORE_FILES
+        .iter()
+        .filter_map(|name| std::fs::read_to_string(dir.join(name)).ok())
+        .flat_map(|text| {
+            text.lines()
+                .filter_map(|line| Rule::new(dir, line))
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+// The rules in the ignore files above `dir`, up to the root of the git
+// repository it's in, outermost first.
+fn rules_above(dir: &Path) -> Vec<Rule> {
+    let Some(top) = dir.ancestors().position(|a| a.join(".git").exists()) else {
+        return Vec::new();
+    };
+    let above: Vec<_> = dir.ancestors().skip(1).take(top).collect();
+    above.into_iter().rev().flat_map(ignore_rules).collect()
+}
+
+fn absolute(path: &Path) -> PathBuf {
+    std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// Finds the files to work on in directories given on the command line:
+/// the `.json` files, or those matching `--include`, that aren't hidden,
+/// ignored by an ignore file or the config, or `--exclude`d.
+#[derive(Debug)]
+pub struct Walker {
+    ignore: Vec<Rule>,
+    include: Vec<Rule>,
+    exclude: Vec<Rule>,
+}
+
+impl Walker {
+    pub fn new(filter: &FileFilter, config: &Config) -> eyre::Result<Self> {
+        let cwd = std::env::current_dir()
+            .map_err(|e| eyre::eyre!(format!("Can't find the working directory: {}", e)))?;
+        let rules = |base: &Path, globs: &[String]| {
+            globs.iter().filter_map(|g| Rule::new(base, g)).collect()
+        };
+        Ok(Self {
+            ignore: rules(&absolute(&config.root), &config.ignore),
+            include: rules(&cwd, &filter.include),
+            exclude: rules(&cwd, &filter.exclude),
+        })
+    }
+
+    /// The files named by `paths`: files as given, and those found in
+    /// directories and their subdirectories, in sorted order.
+    pub fn files(&self, paths: &[&str]) -> eyre::Result<Vec<String>> {
+        let mut files = Vec::new();
+        for path in paths {
+            if !Path::new(path).is_dir() {
+                files.push(path.to_string());
+                continue;
+            }
+            let dir = absolute(Path::new(path));
+            let mut rules = self.ignore.clone();
+            rules.extend(rules_above(&dir));
+            self.walk(Path::new(path), &dir, &mut rules, &mut files)
+                .map_err(|e| eyre::eyre!(format!("Can't read {}: {}", path, e)))?;
+        }
+        Ok(files)
+    }
+
+    // Adds the files in `dir`, which is `absolute`, to `files`.
+    fn walk(
+        &self,
+        dir: &Path,
+        absolute: &Path,
+        rules: &mut Vec<Rule>,
+        files: &mut Vec<String>,
+    ) -> std::io::Result<()> {
+        let outer = rules.len();
+        rules.extend(ignore_rules(absolute));
+        let mut names = std::fs::read_dir(dir)?
+            .map(|e| e.map(|e| e.file_name()))
+            .collect::<std::io::Result<Vec<_>>>()?;
+        names.sort();
+        for name in names {
+            let (path, absolute) = (dir.join(&name), absolute.join(&name));
+            let is_dir = path.is_dir();
+            if self.skips(rules, &absolute, is_dir) {
+                continue;
+            }
+            if is_dir {
+                self.walk(&path, &absolute, rules, files)?;
+            } else if self.includes(&absolute) {
+                files.push(path.display().to_string());
+            }
+        }
+        rules.truncate(outer);
+        Ok(())
+    }
+
+    fn skips(&self, rules: &[Rule], path: &Path, is_dir: bool) -> bool {
+        let hidden = path
+            .file_name()
+            .is_some_and(|name| name.to_string_lossy().starts_with('.'));
+        hidden || ignored(rules, path, is_dir) || ignored(&self.exclude, path, is_dir)
+    }
+
+    fn includes(&self, path: &Path) -> bool {
+        if self.include.is_empty() {
+            return path.extension().is_some_and(|ext| ext == "json");
+        }
+        self.include.iter().any(|rule| rule.matches(path, false))
+    }
+
+    /// Whether walking `root` finds `path`, or `path` is `root`.
+    #[cfg(feature = "watch")]
+    pub fn selects(&self, root: &Path, path: &Path) -> bool {
+        let (root, path) = (absolute(root), absolute(path));
+        let Ok(relative) = path.strip_prefix(&root) else {
+            return false;
+        };
+        if relative.as_os_str().is_empty() {
+            return true;
+        }
+        let mut rules = self.ignore.clone();
+        rules.extend(rules_above(&root));
+        let mut dir = root;
+        let mut components = relative.components().peekable();
+        while let Some(component) = components.next() {
+            let std::path::Component::Normal(name) = component else {
+                return false;
+            };
+            rules.extend(ignore_rules(&dir));
+            dir.push(name);
+            if self.skips(&rules, &dir, components.peek().is_some()) {
+                return false;
+            }
+        }
+        self.includes(&path)
+    }
+}
+
+/// Whether `text` matches `glob`, where `*` stands for any run of characters
+/// but `/`, `?` for one, and a `**` segment for any number of directories.
+pub fn glob_match(glob: &str, text: &str) -> bool {
+    fn segments(glob: &[&str], text: &[&str]) -> bool {
+        match glob.split_first() {
+            None => text.is_empty(),
+            Some((&"**", rest)) => (0..=text.len()).any(|i| segments(rest, &text[i..])),
+            Some((g, rest)) => text
+                .split_first()
+                .is_some_and(|(t, more)| segment(&chars(g), &chars(t)) && segments(rest, more)),
+        }
+    }
+    fn chars(s: &str) -> Vec<char> {
+        s.chars().collect()
+    }
+    fn segment(glob: &[char], text: &[char]) -> bool {
+        match glob.split_first() {
+            None => text.is_empty(),
+            Some(('*', rest)) => (0..=text.len()).any(|i| segment(rest, &text[i..])),
+            Some(('?', rest)) => !text.is_empty() && segment(rest, &text[1..]),
+            Some((g, rest)) => text.first() == Some(g) && segment(rest, &text[1..]),
+        }
+    }
+    let glob: Vec<_> = glob.split('/').collect();
+    let text: Vec<_> = text.split('/').collect();
+    segments(&glob, &text)
+}
@@ -0,0 +1,42 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+use eyre::OptionExt;
+
+/// The value of `--jobs`: a number of threads, or 0 for one per CPU.
+pub fn jobs_arg(value: Option<&String>) -> eyre::Result<usize> {
+    let jobs: usize = value
+        .and_then(|n| n.parse().ok())
+        .ok_or_eyre("--jobs needs a number of threads")?;
+    Ok(match jobs {
+        0 => std::thread::available_parallelism().map_or(1, |n| n.get()),
+        n => n,
+    })
+}
+
+/// `f` of each file, run on up to `jobs` threads, in the order of `files`.
+pub fn map_files<T: Send>(files: &[&str], jobs: usize, f: impl Fn(&str) -> T + Sync) -> Vec<T> {
+    if jobs <= 1 || files.len() <= 1 {
+        return files.iter().map(|file| f(file)).collect();
+    }
+    let next = AtomicUsize::new(0);
+    let results = Mutex::new((0..files.len()).map(|_| None).collect::<Vec<_>>());
+    std::thread::scope(|s| {
+        for _ in 0..jobs.min(files.len()) {
+            s.spawn(|| loop {
+                let i = next.fetch_add(1, Ordering::Relaxed);
+                let Some(file) = files.get(i) else {
+                    break;
+                };
+                let result = f(file);
+                results.lock().unwrap_or_else(|e| e.into_inner())[i] = Some(result);
+            });
+        }
+    });
+    results
+        .into_inner()
+        .unwrap_or_else(|e| e.into_inner())
+        .into_iter()
+        .flatten()
+        .collect()
+}
@@ -0,0 +1,56 @@
+use std::process::ExitCode;
+
+use eyre::OptionExt;
+use json_parser::{join, parse_with_options, JSONValue, MergeStrategy};
+
+use crate::config::Config;
+use crate::files::{FileFilter, Walker};
+use crate::output::{Output, OUTPUT_HELP};
+use crate::read;
+use crate::summary::{Failure, Status, Summary};
+
+pub fn run(args: &[String], config: &Config) -> eyre::Result<ExitCode> {
+    let mut strategy = MergeStrategy::Collect;
+    let mut output = Output::Json;
+    let mut filter = FileFilter::default();
+    let mut summary = Summary::new("join");
+    let mut files = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--concat" => strategy = MergeStrategy::Concat,
+            "--merge" => strategy = MergeStrategy::DeepMerge,
+            "--output" => output = Output::parse(args.next().ok_or_eyre(OUTPUT_HELP)?)?,
+            a if filter.parse(a, &mut args)? => {}
+            a if summary.parse(a, &mut args)? => {}
+            a if a.starts_with("--") => eyre::bail!(format!("Unknown option {}", a)),
+            a => files.push(a),
+        }
+    }
+    if files.is_empty() {
+        files.push("-");
+    }
+    let mut docs = Vec::new();
+    for file in Walker::new(&filter, config)?.files(&files)? {
+        let input = read(&file)?;
+        let doc = parse_with_options(&input, &config.parse)
+            .map_err(|e| Failure::found(format!("Can't parse {}: {}", file, e)))?;
+        // checked here so the error names the file
+        match (strategy, &doc) {
+            (MergeStrategy::Concat, JSONValue::Array(_)) => {}
+            (MergeStrategy::Concat, _) => {
+                return Err(Failure::found(format!("{} is not an array", file)))
+            }
+            (MergeStrategy::DeepMerge, JSONValue::Dict(_)) => {}
+            (MergeStrategy::DeepMerge, _) => {
+                return Err(Failure::found(format!("{} is not an object", file)))
+            }
+            _ => {}
+        }
+        summary.add(&file, "ok", Status::Ok);
+        docs.push(doc);
+    }
+    let joined = join(docs, strategy)?;
+    print!("{}", output.format(&[&joined]));
+    Ok(summary.finish())
+}
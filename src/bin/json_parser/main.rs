@@ -0,0 +1,187 @@
+//! The `json_parser` command line tool.
+
+mod codegen;
+mod config;
+mod diff;
+mod edit;
+mod files;
+mod jobs;
+mod join;
+mod output;
+mod query;
+mod sample;
+mod sort;
+mod stats;
+mod summary;
+mod validate;
+mod watch;
+
+use std::io::Read;
+use std::process::ExitCode;
+
+use json_parser::Schema;
+
+use crate::config::Config;
+use crate::summary::{error_json, status_of, Failure, Status, SummaryFormat};
+
+const USAGE: &str = "\
+Usage: json_parser <command> [options] [FILE...]
+
+Commands:
+  validate --schema SCHEMA [--ndjson] [--jobs N] [FILE...]
+      Check documents against a JSON Schema. With --ndjson, each line is a
+      separate document.
+  stats [--jobs N] [FILE...]
+      Print the size, number of values and nesting depth of each document,
+      and the totals.
+  codegen --schema SCHEMA [--name NAME]
+      Print Rust structs deriving FromJson for the schema's documents, the
+      outermost one named NAME (default `Root`).
+  sample --schema SCHEMA [--output FORMAT]
+      Print a document matching the schema, with plausible values.
+  query [--arg NAME VALUE] [--argjson NAME JSON] [--null-input] [--slurp]
+        [--output FORMAT] PATTERN [FILE...]
+      Print the values matching PATTERN, a JSON Pointer where a segment may
+      be `*` or `**`, one per line. A `$NAME` segment stands for a variable
+      given with --arg (a string) or --argjson (a string or index as JSON),
+      and a pattern starting with `$NAME` looks in the variable instead of
+      the input. With -n/--null-input the input is `null` and no files are
+      read; with -s/--slurp every document, including each line of NDJSON,
+      is gathered into one array that is queried once.
+  fmt [--indent N] [--sort-keys] [--check] [--jobs N] [FILE...]
+      Re-indent documents (2 spaces by default), keeping keys in order
+      unless given --sort-keys.
+      With --check, list the files that aren't formatted instead.
+  sort-keys [--indent N] [FILE...]
+      Rewrite documents with the keys of every object sorted.
+  set PATH VALUE [FILE...]
+      Put the JSON VALUE at PATH (`a.b.c` or a JSON Pointer), creating
+      objects on the way. Comments and formatting elsewhere are kept.
+  del PATH [FILE...]
+      Remove the value at PATH, keeping comments and formatting elsewhere.
+  join [--concat | --merge] [--output FORMAT] [FILE...]
+      Combine the documents into one array of them, or with --concat the
+      elements of arrays into one array, or with --merge objects into one,
+      merging the objects under a key they share and concatenating arrays.
+      Later files win on other keys.
+  sort-lines [--key POINTER] [--reverse] [--memory MB] [--temp-dir DIR]
+             [--progress] [FILE...]
+      Print the lines of NDJSON sorted by the value at POINTER (default the
+      whole record), as jq orders values. Beyond MB megabytes (default 64)
+      sorted runs are spilled to files in DIR and merged. With --progress,
+      print how far it has read to standard error every second.
+  watch [--schema SCHEMA] [--fmt [--indent N] [--sort-keys]] [PATH...]
+      Check the JSON files under PATH (default `.`) against SCHEMA, or that
+      they parse, then again each time one changes, printing `FILE: ok` or
+      what's wrong. With --fmt, valid files are also re-indented in place.
+      Needs the `watch` feature.
+
+fmt, sort-keys, set and del print the result, or with --in-place write it
+back to each file (through a temporary file, so it is replaced in one go),
+first copying the original to FILE.bak with --in-place=.bak. With --dry-run
+they print a diff of what would change instead, exiting with 1 if anything
+would.
+
+Defaults for validate, query, stats, join, sort-lines, fmt, sort-keys and
+watch come from the nearest .jsonparser.toml or json_parser.config.json in
+the working directory or above it, e.g.
+
+  indent = 4
+  sort-keys = true
+  allow-comments = true        # also allow-control-chars, allow-non-finite,
+                               # max-depth
+  ignore = ['build/**', '*.generated.json']
+
+Flags override them. `ignore` applies when walking directories; fmt and
+sort-keys still reject comments, which they would drop.
+
+Output formats: json (pretty, the default for sample), ndjson (the default
+for query), raw (ndjson with bare strings), csv (a row per value or array
+element, with a column per key of object rows), yaml.
+
+Files default to standard input, also read for `-`. A directory stands
+for the .json files in it and below it, except hidden ones and those
+matched by a .gitignore or .jsonparserignore (in it, below it, or above it
+up to the repository root) or the config's `ignore`. --include GLOB picks
+other files instead of .json ones and --exclude GLOB skips more; both can
+be repeated, and match names, or paths from the working directory if the
+glob has a `/`. Commands taking
+--jobs N work on N files at once (0 for one per CPU), still reporting in
+the order given, and end with a summary on standard error when given
+several files. A file that can't be read or parsed is reported and
+skipped.
+
+Every command takes --summary json to end with a line of JSON on standard
+error giving its exit status and each file's result (`ok`, `invalid`,
+`would change`, ...) with any error.
+
+Exit status, the worst that applies: 0 if all is well, 1 if something was
+found (invalid or unparseable input, files that would change, a query
+matching nothing), 2 for bad usage (options, schemas, config), 3 if a file
+couldn't be read or written.";
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let config = Config::load;
+    let result = match args.first().map(String::as_str) {
+        Some("validate") => config().and_then(|c| validate::run(&args[1..], &c)),
+        Some("codegen") => codegen::run(&args[1..]),
+        Some("sample") => sample::run(&args[1..]),
+        Some("query") => config().and_then(|c| query::run(&args[1..], &c)),
+        Some("stats") => config().and_then(|c| stats::run(&args[1..], &c)),
+        Some("join") => config().and_then(|c| join::run(&args[1..], &c)),
+        Some("sort-lines") => config().and_then(|c| sort::run(&args[1..], &c)),
+        Some("watch") => config().and_then(|c| watch::run(&args[1..], &c)),
+        Some("fmt") => config().and_then(|c| edit::fmt(&args[1..], &c)),
+        Some("sort-keys") => config().and_then(|c| edit::sort_keys(&args[1..], &c)),
+        Some("set") => config().and_then(|c| edit::set(&args[1..], &c)),
+        Some("del") => config().and_then(|c| edit::del(&args[1..], &c)),
+        Some("-h" | "--help") => {
+            println!("{}", USAGE);
+            return ExitCode::SUCCESS;
+        }
+        _ => {
+            eprintln!("{}", USAGE);
+            return Status::Usage.into();
+        }
+    };
+    match result {
+        Ok(code) => code,
+        Err(e) => {
+            eprintln!("json_parser: {}", e);
+            if SummaryFormat::requested(&args) == SummaryFormat::Json {
+                eprintln!("{}", error_json(&args[0], &e));
+            }
+            status_of(&e).into()
+        }
+    }
+}
+
+/// Reads `path`, or standard input for `-`.
+fn read(path: &str) -> eyre::Result<Vec<u8>> {
+    let mut buf = Vec::new();
+    let res = if path == "-" {
+        std::io::stdin().read_to_end(&mut buf).map(|_| ())
+    } else {
+        std::fs::read(path).map(|b| buf = b)
+    };
+    res.map_err(|e| Failure::io(format!("Can't read {}: {}", path, e)))?;
+    Ok(buf)
+}
+
+fn load_schema(path: &str) -> eyre::Result<Schema> {
+    Schema::parse(&read(path)?).map_err(|e| eyre::eyre!(format!("Bad schema {}: {}", path, e)))
+}
+
+/// 1-based line and column of byte `offset`.
+fn line_col(buf: &[u8], offset: usize) -> (usize, usize) {
+    let before = &buf[..offset.min(buf.len())];
+    let line = before.iter().filter(|b| **b == b'\n').count() + 1;
+    let col = offset
+        - before
+            .iter()
+            .rposition(|b| *b == b'\n')
+            .map_or(0, |i| i + 1)
+        + 1;
+    (line, col)
+}
@@ -0,0 +1,231 @@
+use std::collections::BTreeSet;
+use std::fmt::Write;
+
+use json_parser::{parse, JSONValue, SerializeOptions};
+
+/// How read-path commands print the values they produce.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Output {
+    /// Each value pretty-printed.
+    Json,
+    /// Each value compact, on its own line.
+    Ndjson,
+    /// Like `Ndjson`, but strings without quotes (`jq -r`).
+    Raw,
+    /// A table with a row per value, or per element of an array value, and
+    /// a column per key of object rows.
+    Csv,
+    /// A YAML document per value.
+    Yaml,
+}
+
+pub const OUTPUT_HELP: &str = "--output needs one of json, ndjson, raw, csv, yaml";
+
+impl Output {
+    pub fn parse(name: &str) -> eyre::Result<Self> {
+        Ok(match name {
+            "json" => Output::Json,
+            "ndjson" => Output::Ndjson,
+            "raw" => Output::Raw,
+            "csv" => Output::Csv,
+            "yaml" => Output::Yaml,
+            _ => eyre::bail!(OUTPUT_HELP),
+        })
+    }
+
+    pub fn format(self, values: &[&JSONValue]) -> String {
+        let mut out = String::new();
+        match self {
+            Output::Json => {
+                for v in values {
+                    out.push_str(&v.to_json_pretty());
+                    out.push('\n');
+                }
+            }
+            Output::Ndjson => {
+                for v in values {
+                    out.push_str(&compact(v));
+                    out.push('\n');
+                }
+            }
+            Output::Raw => {
+                for v in values {
+                    match v {
+                        JSONValue::Str(s) => out.push_str(s),
+                        v => out.push_str(&compact(v)),
+                    }
+                    out.push('\n');
+                }
+            }
+            Output::Csv => csv(values, &mut out),
+            Output::Yaml => {
+                for (i, v) in values.iter().enumerate() {
+                    if i > 0 {
+                        out.push_str("---\n");
+                    }
+                    yaml(v, 0, &mut out);
+                }
+            }
+        }
+        out
+    }
+}
+
+fn compact(v: &JSONValue) -> String {
+    v.to_json_string_with(&SerializeOptions {
+        sort_keys: true,
+        ..SerializeOptions::default()
+    })
+}
+
+// A CSV field: strings as they are, anything else as compact JSON.
+fn field(v: &JSONValue, out: &mut String) {
+    let text = match v {
+        JSONValue::Str(s) => s.clone(),
+        JSONValue::Null => String::new(),
+        v => compact(v),
+    };
+    if text.contains([',', '"', '\n', '\r']) {
+        let _ = write!(out, "\"{}\"", text.replace('"', "\"\""));
+    } else {
+        out.push_str(&text);
+    }
+}
+
+fn csv(values: &[&JSONValue], out: &mut String) {
+    let rows: Vec<&JSONValue> = values
+        .iter()
+        .flat_map(|v| match v {
+            JSONValue::Array(entries) => entries.iter().collect(),
+            v => vec![*v],
+        })
+        .collect();
+    let tabular = rows.iter().all(|r| matches!(r, JSONValue::Dict(_)));
+    if !tabular {
+        for row in rows {
+            match row {
+                JSONValue::Array(fields) => {
+                    for (i, f) in fields.iter().enumerate() {
+                        if i > 0 {
+                            out.push(',');
+                        }
+                        field(f, out);
+                    }
+                }
+                v => field(v, out),
+            }
+            out.push('\n');
+        }
+        return;
+    }
+    let columns: BTreeSet<&str> = rows
+        .iter()
+        .flat_map(|r| match r {
+            JSONValue::Dict(d) => d.keys().map(String::as_str).collect(),
+            _ => Vec::new(),
+        })
+        .collect();
+    if columns.is_empty() {
+        return;
+    }
+    let header: Vec<_> = columns
+        .iter()
+        .map(|c| JSONValue::Str(c.to_string()))
+        .collect();
+    for (i, c) in header.iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        field(c, out);
+    }
+    out.push('\n');
+    for row in rows {
+        let JSONValue::Dict(d) = row else {
+            continue;
+        };
+        for (i, c) in columns.iter().enumerate() {
+            if i > 0 {
+                out.push(',');
+            }
+            field(d.get(*c).unwrap_or(&JSONValue::Null), out);
+        }
+        out.push('\n');
+    }
+}
+
+// A string as a YAML scalar: plain when YAML would read it back as the same
+// string, else double-quoted, whose escapes JSON's are a subset of.
+fn yaml_str(s: &str) -> String {
+    let plain = !s.is_empty()
+        && s.trim() == s
+        && !s.starts_with(|c: char| "-?:,[]{}#&*!|>'\"%@`~".contains(c))
+        && !s.contains(": ")
+        && !s.contains(" #")
+        && !s.contains(|c: char| c.is_control())
+        && parse(s.as_bytes()).is_err()
+        && !matches!(
+            s.to_ascii_lowercase().as_str(),
+            "yes" | "no" | "on" | "off" | "y" | "n" | "true" | "false" | "null"
+        );
+    if plain {
+        s.to_string()
+    } else {
+        JSONValue::Str(s.to_string()).to_json_string()
+    }
+}
+
+fn yaml(v: &JSONValue, depth: usize, out: &mut String) {
+    let indent = "  ".repeat(depth);
+    match v {
+        JSONValue::Array(entries) if !entries.is_empty() => {
+            for e in entries {
+                out.push_str(&indent);
+                out.push('-');
+                yaml_item(e, depth + 1, out);
+            }
+        }
+        JSONValue::Dict(d) if !d.is_empty() => {
+            let mut entries: Vec<_> = d.iter().collect();
+            entries.sort_unstable_by_key(|(k, _)| *k);
+            for (k, e) in entries {
+                out.push_str(&indent);
+                out.push_str(&yaml_str(k));
+                out.push(':');
+                yaml_item(e, depth + 1, out);
+            }
+        }
+        v => {
+            out.push_str(&indent);
+            out.push_str(&yaml_scalar(v));
+            out.push('\n');
+        }
+    }
+}
+
+// What follows a `key:` or `-`: a scalar on the same line, or a nested
+// block below it.
+fn yaml_item(v: &JSONValue, depth: usize, out: &mut String) {
+    match v {
+        JSONValue::Array(entries) if !entries.is_empty() => {
+            out.push('\n');
+            yaml(v, depth, out);
+        }
+        JSONValue::Dict(d) if !d.is_empty() => {
+            out.push('\n');
+            yaml(v, depth, out);
+        }
+        v => {
+            out.push(' ');
+            out.push_str(&yaml_scalar(v));
+            out.push('\n');
+        }
+    }
+}
+
+fn yaml_scalar(v: &JSONValue) -> String {
+    match v {
+        JSONValue::Str(s) => yaml_str(s),
+        // `[]`, `{}` and JSON numbers, booleans and null read the same
+        v => v.to_json_string(),
+    }
+}
@@ -0,0 +1,146 @@
+use std::collections::HashMap;
+use std::process::ExitCode;
+
+use eyre::OptionExt;
+use json_parser::{parse, parse_with_options, JSONValue, ParseOptions, PathPattern};
+
+use crate::config::Config;
+use crate::files::{FileFilter, Walker};
+use crate::output::{Output, OUTPUT_HELP};
+use crate::read;
+use crate::summary::{Failure, Status, Summary};
+
+pub fn run(args: &[String], config: &Config) -> eyre::Result<ExitCode> {
+    let mut vars = HashMap::new();
+    let mut pattern = None;
+    let mut output = Output::Ndjson;
+    let mut null_input = false;
+    let mut slurp = false;
+    let mut filter = FileFilter::default();
+    let mut summary = Summary::new("query");
+    let mut files = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--arg" | "--argjson" => {
+                let name = args
+                    .next()
+                    .ok_or_eyre(format!("{} needs a name and a value", arg))?;
+                let value = args
+                    .next()
+                    .ok_or_eyre(format!("{} needs a name and a value", arg))?;
+                let value = match arg.as_str() {
+                    "--arg" => JSONValue::Str(value.clone()),
+                    _ => parse(value.as_bytes())
+                        .map_err(|e| eyre::eyre!(format!("Bad --argjson {}: {}", name, e)))?,
+                };
+                vars.insert(name.as_str(), value);
+            }
+            "-n" | "--null-input" => null_input = true,
+            "-s" | "--slurp" => slurp = true,
+            a if filter.parse(a, &mut args)? => {}
+            a if summary.parse(a, &mut args)? => {}
+            "--output" => output = Output::parse(args.next().ok_or_eyre(OUTPUT_HELP)?)?,
+            a if a.starts_with("--") => eyre::bail!(format!("Unknown option {}", a)),
+            a if pattern.is_none() => pattern = Some(a),
+            a => files.push(a),
+        }
+    }
+    let pattern = pattern.ok_or_eyre("query needs a PATTERN")?;
+    // `$name/..` looks in the variable instead of the input
+    let (var, pattern) = match pattern.strip_prefix('$') {
+        Some(rest) => {
+            let (name, rest) = rest.split_at(rest.find('/').unwrap_or(rest.len()));
+            let value = vars
+                .get(name)
+                .ok_or_eyre(format!("${} is not set, pass --arg {} VALUE", name, name))?;
+            (Some(value.clone()), rest)
+        }
+        None => (None, pattern),
+    };
+    let pattern = PathPattern::new(&substitute(pattern, &vars)?)?;
+
+    let mut docs = if null_input {
+        if !files.is_empty() {
+            eyre::bail!("--null-input reads no files")
+        }
+        vec![JSONValue::Null]
+    } else {
+        if files.is_empty() {
+            files.push("-");
+        }
+        let mut docs = Vec::new();
+        for file in Walker::new(&filter, config)?.files(&files)? {
+            let file = file.as_str();
+            let input = read(file)?;
+            if slurp {
+                docs.extend(documents(file, &input, &config.parse)?);
+            } else {
+                docs.push(
+                    parse_with_options(&input, &config.parse)
+                        .map_err(|e| Failure::found(format!("Can't parse {}: {}", file, e)))?,
+                );
+            }
+            summary.add(file, "ok", Status::Ok);
+        }
+        if slurp {
+            docs = vec![JSONValue::Array(docs)];
+        }
+        docs
+    };
+    if let Some(var) = var {
+        docs.iter_mut().for_each(|doc| *doc = var.clone());
+    }
+    let found: Vec<_> = docs
+        .iter()
+        .flat_map(|doc| pattern.find(doc))
+        .map(|(_, v)| v)
+        .collect();
+    print!("{}", output.format(&found));
+    if found.is_empty() {
+        summary.found(Status::Found);
+    }
+    Ok(summary.finish())
+}
+
+// Replaces each `$name` segment of `pattern` with the variable's value,
+// escaped as a reference token.
+fn substitute(pattern: &str, vars: &HashMap<&str, JSONValue>) -> eyre::Result<String> {
+    let segments = pattern
+        .split('/')
+        .map(|segment| {
+            let Some(name) = segment.strip_prefix('$') else {
+                return Ok(segment.to_string());
+            };
+            let token = match vars.get(name) {
+                Some(JSONValue::Str(s)) => s.clone(),
+                Some(JSONValue::Num(n)) if n.fract() == 0.0 && *n >= 0.0 => {
+                    format!("{}", *n as u64)
+                }
+                Some(_) => eyre::bail!(format!(
+                    "${} must be a string or an index to be used in a pattern",
+                    name
+                )),
+                None => eyre::bail!(format!("${} is not set, pass --arg {} VALUE", name, name)),
+            };
+            Ok(token.replace('~', "~0").replace('/', "~1"))
+        })
+        .collect::<eyre::Result<Vec<_>>>()?;
+    Ok(segments.join("/"))
+}
+
+// The documents in `input`: one, or one per line for NDJSON.
+fn documents(file: &str, input: &[u8], options: &ParseOptions) -> eyre::Result<Vec<JSONValue>> {
+    if let Ok(doc) = parse_with_options(input, options) {
+        return Ok(vec![doc]);
+    }
+    input
+        .split(|b| *b == b'\n')
+        .enumerate()
+        .filter(|(_, line)| !line.trim_ascii().is_empty())
+        .map(|(i, line)| {
+            parse_with_options(line, options)
+                .map_err(|e| Failure::found(format!("Can't parse {}:{}: {}", file, i + 1, e)))
+        })
+        .collect()
+}
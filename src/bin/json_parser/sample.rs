@@ -0,0 +1,25 @@
+use std::process::ExitCode;
+
+use eyre::OptionExt;
+
+use crate::load_schema;
+use crate::output::{Output, OUTPUT_HELP};
+use crate::summary::Summary;
+
+pub fn run(args: &[String]) -> eyre::Result<ExitCode> {
+    let mut schema = None;
+    let mut output = Output::Json;
+    let mut summary = Summary::new("sample");
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--schema" => schema = Some(args.next().ok_or_eyre("--schema needs a file")?),
+            "--output" => output = Output::parse(args.next().ok_or_eyre(OUTPUT_HELP)?)?,
+            a if summary.parse(a, &mut args)? => {}
+            a => eyre::bail!(format!("Unexpected argument {}", a)),
+        }
+    }
+    let schema = load_schema(schema.ok_or_eyre("sample needs --schema SCHEMA")?)?;
+    print!("{}", output.format(&[&schema.sample()]));
+    Ok(summary.finish())
+}
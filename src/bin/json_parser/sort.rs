@@ -0,0 +1,98 @@
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::process::ExitCode;
+
+use eyre::OptionExt;
+use json_parser::{sort_lines, sort_lines_with_progress, Progress, ProgressReport, SortOptions};
+
+use crate::config::Config;
+use crate::files::{FileFilter, Walker};
+use crate::summary::{Failure, Status, Summary};
+
+pub fn run(args: &[String], config: &Config) -> eyre::Result<ExitCode> {
+    let mut options = SortOptions::default();
+    let mut progress = false;
+    let mut filter = FileFilter::default();
+    let mut summary = Summary::new("sort-lines");
+    let mut files = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--key" => {
+                options.key = args
+                    .next()
+                    .ok_or_eyre("--key needs a JSON Pointer")?
+                    .clone()
+            }
+            "-r" | "--reverse" => options.descending = true,
+            "--progress" => progress = true,
+            "--memory" => {
+                let mb: usize = args
+                    .next()
+                    .and_then(|n| n.parse().ok())
+                    .filter(|mb| *mb > 0)
+                    .ok_or_eyre("--memory needs a number of megabytes")?;
+                options.memory = mb * 1024 * 1024;
+            }
+            "--temp-dir" => {
+                options.temp_dir = Some(
+                    args.next()
+                        .ok_or_eyre("--temp-dir needs a directory")?
+                        .into(),
+                )
+            }
+            a if filter.parse(a, &mut args)? => {}
+            a if summary.parse(a, &mut args)? => {}
+            a if a.starts_with("--") => eyre::bail!(format!("Unknown option {}", a)),
+            a => files.push(a),
+        }
+    }
+    if files.is_empty() {
+        files.push("-");
+    }
+    // the files are sorted together, read one after the other without
+    // loading them
+    let mut input: Box<dyn Read> = Box::new(std::io::empty());
+    // the size of the files, unless one is standard input
+    let mut total = Some(0);
+    for file in Walker::new(&filter, config)?.files(&files)? {
+        let reader: Box<dyn Read> = if file == "-" {
+            total = None;
+            Box::new(std::io::stdin())
+        } else {
+            let f = File::open(&file)
+                .map_err(|e| Failure::io(format!("Can't read {}: {}", file, e)))?;
+            total = total.zip(f.metadata().ok()).map(|(t, m)| t + m.len());
+            Box::new(f)
+        };
+        // a last line without a newline mustn't run into the next file's
+        input = Box::new(input.chain(reader).chain(&b"\n"[..]));
+        summary.add(&file, "ok", Status::Ok);
+    }
+    let stdout = std::io::stdout().lock();
+    let input = BufReader::new(input);
+    let sorted = if progress {
+        let mut report = ProgressReport::new(print_progress);
+        if let Some(total) = total {
+            report = report.total_bytes(total);
+        }
+        sort_lines_with_progress(input, stdout, &options, report)
+    } else {
+        sort_lines(input, stdout, &options)
+    };
+    sorted.map_err(|e| Failure::found(e.to_string()))?;
+    Ok(summary.finish())
+}
+
+// One line on standard error per second, which suits both a terminal and a
+// log of a long job.
+fn print_progress(progress: &Progress) {
+    let mut line = format!(
+        "sort-lines: {} bytes, {} records",
+        progress.bytes, progress.records
+    );
+    if let (Some(done), Some(eta)) = (progress.fraction(), progress.eta()) {
+        line.push_str(&format!(", {:.0}%, {}s left", done * 100.0, eta.as_secs()));
+    }
+    eprintln!("{}", line);
+}
@@ -0,0 +1,91 @@
+use std::process::ExitCode;
+
+use json_parser::{parse_with_options, JSONValue};
+
+use crate::config::Config;
+use crate::files::{FileFilter, Walker};
+use crate::jobs::{jobs_arg, map_files};
+use crate::read;
+use crate::summary::{Status, Summary};
+
+#[derive(Debug, Default, Clone, Copy)]
+struct Stats {
+    bytes: usize,
+    values: usize,
+    depth: usize,
+}
+
+impl Stats {
+    fn add(&mut self, other: Stats) {
+        self.bytes += other.bytes;
+        self.values += other.values;
+        self.depth = self.depth.max(other.depth);
+    }
+}
+
+// Counts `v` and everything in it, at nesting `depth`.
+fn count(v: &JSONValue, depth: usize, stats: &mut Stats) {
+    stats.values += 1;
+    stats.depth = stats.depth.max(depth);
+    match v {
+        JSONValue::Array(entries) => entries.iter().for_each(|e| count(e, depth + 1, stats)),
+        JSONValue::Dict(d) => d.values().for_each(|e| count(e, depth + 1, stats)),
+        _ => {}
+    }
+}
+
+pub fn run(args: &[String], config: &Config) -> eyre::Result<ExitCode> {
+    let mut jobs = 1;
+    let mut filter = FileFilter::default();
+    let mut summary = Summary::new("stats").counting(&["failed"]);
+    let mut files = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--jobs" => jobs = jobs_arg(args.next())?,
+            a if filter.parse(a, &mut args)? => {}
+            a if summary.parse(a, &mut args)? => {}
+            a if a.starts_with("--") => eyre::bail!(format!("Unknown option {}", a)),
+            a => files.push(a),
+        }
+    }
+    if files.is_empty() {
+        files.push("-");
+    }
+    let files = Walker::new(&filter, config)?.files(&files)?;
+    let files: Vec<&str> = files.iter().map(String::as_str).collect();
+
+    let results = map_files(&files, jobs, |file| -> eyre::Result<Stats> {
+        let input = read(file)?;
+        let doc = parse_with_options(&input, &config.parse)
+            .map_err(|e| eyre::eyre!(format!("Can't parse {}: {}", file, e)))?;
+        let mut stats = Stats {
+            bytes: input.len(),
+            ..Stats::default()
+        };
+        count(&doc, 0, &mut stats);
+        Ok(stats)
+    });
+    let mut total = Stats::default();
+    for (file, result) in files.iter().zip(results) {
+        match result {
+            Ok(stats) => {
+                print_stats(file, &stats);
+                total.add(stats);
+                summary.add(file, "ok", Status::Ok);
+            }
+            Err(e) => summary.fail(file, "failed", &e),
+        }
+    }
+    if files.len() > 1 {
+        print_stats("total", &total);
+    }
+    Ok(summary.finish())
+}
+
+fn print_stats(name: &str, stats: &Stats) {
+    println!(
+        "{}: {} bytes, {} values, depth {}",
+        name, stats.bytes, stats.values, stats.depth
+    );
+}
@@ -0,0 +1,236 @@
+use std::collections::HashMap;
+use std::process::ExitCode;
+
+use eyre::OptionExt;
+use json_parser::{JSONValue, SerializeOptions};
+
+/// Exit statuses, the same for every command so scripts and CI can rely on
+/// them. A run exits with the worst status of anything in it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Status {
+    Ok = 0,
+    /// Violations, differences, unparseable input or no match.
+    Found = 1,
+    /// Bad arguments, options or schemas.
+    Usage = 2,
+    /// A file couldn't be read or written.
+    Io = 3,
+}
+
+impl From<Status> for ExitCode {
+    fn from(status: Status) -> Self {
+        ExitCode::from(status as u8)
+    }
+}
+
+/// An error with the status it exits with. Other errors are usage errors,
+/// or something found when they concern one file of many.
+#[derive(Debug)]
+pub struct Failure {
+    pub status: Status,
+    pub message: String,
+}
+
+impl Failure {
+    pub fn io(message: String) -> eyre::Report {
+        eyre::Report::new(Self {
+            status: Status::Io,
+            message,
+        })
+    }
+
+    pub fn usage(message: String) -> eyre::Report {
+        eyre::Report::new(Self {
+            status: Status::Usage,
+            message,
+        })
+    }
+
+    pub fn found(message: String) -> eyre::Report {
+        eyre::Report::new(Self {
+            status: Status::Found,
+            message,
+        })
+    }
+}
+
+impl std::fmt::Display for Failure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for Failure {}
+
+/// The status `e` exits with: its own if it's a `Failure`, else usage.
+pub fn status_of(e: &eyre::Report) -> Status {
+    e.downcast_ref::<Failure>()
+        .map_or(Status::Usage, |f| f.status)
+}
+
+pub const SUMMARY_HELP: &str = "--summary needs a format: human or json";
+
+/// `--summary`: how the end of a run is reported on standard error.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum SummaryFormat {
+    /// A `N files: ...` line, when given several files.
+    #[default]
+    Human,
+    /// Always one JSON object with the status and each file's result.
+    Json,
+}
+
+impl SummaryFormat {
+    /// The format asked for anywhere in `args`, for reporting errors that
+    /// happen before a command gets to its own.
+    pub fn requested(args: &[String]) -> Self {
+        match args
+            .windows(2)
+            .any(|w| w[0] == "--summary" && w[1] == "json")
+        {
+            true => Self::Json,
+            false => Self::Human,
+        }
+    }
+}
+
+/// What happened to each file in a run, printed when it ends.
+#[derive(Debug, Clone)]
+pub struct Summary {
+    command: &'static str,
+    format: SummaryFormat,
+    // the results counted on the human line, in order
+    counted: Vec<&'static str>,
+    status: Status,
+    files: Vec<(String, &'static str, Option<String>)>,
+}
+
+impl Summary {
+    pub fn new(command: &'static str) -> Self {
+        Self {
+            command,
+            format: SummaryFormat::default(),
+            counted: Vec::new(),
+            status: Status::Ok,
+            files: Vec::new(),
+        }
+    }
+
+    /// Lists the results with counts on the human summary line.
+    pub fn counting(mut self, results: &[&'static str]) -> Self {
+        self.counted = results.to_vec();
+        self
+    }
+
+    /// Takes `arg`, and its format from `args`, if it's `--summary`.
+    pub fn parse(
+        &mut self,
+        arg: &str,
+        args: &mut std::slice::Iter<'_, String>,
+    ) -> eyre::Result<bool> {
+        if arg != "--summary" {
+            return Ok(false);
+        }
+        self.format = match args.next().ok_or_eyre(SUMMARY_HELP)?.as_str() {
+            "human" => SummaryFormat::Human,
+            "json" => SummaryFormat::Json,
+            _ => eyre::bail!(SUMMARY_HELP),
+        };
+        Ok(true)
+    }
+
+    /// Records `result` for `file`, e.g. `ok` or `invalid`.
+    pub fn add(&mut self, file: &str, result: &'static str, status: Status) {
+        self.status = self.status.max(status);
+        self.files.push((file.to_string(), result, None));
+    }
+
+    /// Records `e` as `result` for `file`, reporting it on standard error.
+    /// Other than failing to read or write, a file going wrong is something
+    /// found in it.
+    pub fn fail(&mut self, file: &str, result: &'static str, e: &eyre::Report) {
+        eprintln!("json_parser: {}", e);
+        let status = e
+            .downcast_ref::<Failure>()
+            .map_or(Status::Found, |f| f.status);
+        self.status = self.status.max(status);
+        self.files
+            .push((file.to_string(), result, Some(e.to_string())));
+    }
+
+    /// Raises the run's status to at least `status`.
+    pub fn found(&mut self, status: Status) {
+        self.status = self.status.max(status);
+    }
+
+    /// Prints the summary and gives the exit status.
+    pub fn finish(&self) -> ExitCode {
+        match self.format {
+            SummaryFormat::Human if self.files.len() > 1 && !self.counted.is_empty() => {
+                let counts: Vec<_> = self
+                    .counted
+                    .iter()
+                    .map(|result| {
+                        let n = self.files.iter().filter(|f| f.1 == *result).count();
+                        format!("{} {}", n, result)
+                    })
+                    .collect();
+                eprintln!("{} files: {}", self.files.len(), counts.join(", "));
+            }
+            SummaryFormat::Human => {}
+            SummaryFormat::Json => eprintln!("{}", self.to_json()),
+        }
+        self.status.into()
+    }
+
+    fn to_json(&self) -> String {
+        let mut counts = HashMap::new();
+        let files = self
+            .files
+            .iter()
+            .map(|(file, result, error)| {
+                let n = counts.entry(result.to_string()).or_insert(0.0);
+                *n += 1.0;
+                let mut entry = HashMap::from([
+                    ("file".to_string(), JSONValue::Str(file.clone())),
+                    ("result".to_string(), JSONValue::Str(result.to_string())),
+                ]);
+                if let Some(error) = error {
+                    entry.insert("error".to_string(), JSONValue::Str(error.clone()));
+                }
+                JSONValue::Dict(entry)
+            })
+            .collect();
+        let counts = counts.into_iter().map(|(k, n)| (k, JSONValue::Num(n)));
+        json_line(HashMap::from([
+            ("command".to_string(), JSONValue::Str(self.command.into())),
+            (
+                "status".to_string(),
+                JSONValue::Num(self.status as u8 as f64),
+            ),
+            ("counts".to_string(), JSONValue::Dict(counts.collect())),
+            ("files".to_string(), JSONValue::Array(files)),
+        ]))
+    }
+}
+
+/// The JSON summary of a command that failed with `e` before finishing.
+pub fn error_json(command: &str, e: &eyre::Report) -> String {
+    json_line(HashMap::from([
+        ("command".to_string(), JSONValue::Str(command.to_string())),
+        (
+            "status".to_string(),
+            JSONValue::Num(status_of(e) as u8 as f64),
+        ),
+        ("error".to_string(), JSONValue::Str(e.to_string())),
+    ]))
+}
+
+// `fields` as one line of JSON, keys sorted so it reads the same every run.
+fn json_line(fields: HashMap<String, JSONValue>) -> String {
+    let options = SerializeOptions {
+        sort_keys: true,
+        ..SerializeOptions::default()
+    };
+    JSONValue::Dict(fields).to_json_string_with(&options)
+}
@@ -0,0 +1,102 @@
+use std::fmt::Write;
+use std::process::ExitCode;
+
+use eyre::OptionExt;
+use json_parser::{ParseOptions, Schema};
+
+use crate::config::Config;
+use crate::files::{FileFilter, Walker};
+use crate::jobs::{jobs_arg, map_files};
+use crate::summary::{Status, Summary};
+use crate::{line_col, load_schema, read};
+
+pub fn run(args: &[String], config: &Config) -> eyre::Result<ExitCode> {
+    let mut schema = None;
+    let mut ndjson = false;
+    let mut jobs = 1;
+    let mut filter = FileFilter::default();
+    let mut summary = Summary::new("validate").counting(&["invalid", "unreadable"]);
+    let mut files = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--schema" => schema = Some(args.next().ok_or_eyre("--schema needs a file")?),
+            "--ndjson" => ndjson = true,
+            "--jobs" => jobs = jobs_arg(args.next())?,
+            a if filter.parse(a, &mut args)? => {}
+            a if summary.parse(a, &mut args)? => {}
+            a if a.starts_with("--") => eyre::bail!(format!("Unknown option {}", a)),
+            a => files.push(a),
+        }
+    }
+    let schema = schema.ok_or_eyre("validate needs --schema SCHEMA")?;
+    let schema = load_schema(schema)?;
+    if files.is_empty() {
+        files.push("-");
+    }
+    let files = Walker::new(&filter, config)?.files(&files)?;
+    let files: Vec<&str> = files.iter().map(String::as_str).collect();
+
+    let results = map_files(&files, jobs, |file| -> eyre::Result<(bool, String)> {
+        let input = read(file)?;
+        let mut report = String::new();
+        if !ndjson {
+            let valid = check(&schema, &config.parse, file, &input, 1, &mut report);
+            return Ok((valid, report));
+        }
+        let mut valid = true;
+        for (i, line) in input.split(|b| *b == b'\n').enumerate() {
+            if !line.trim_ascii().is_empty() {
+                valid &= check(&schema, &config.parse, file, line, i + 1, &mut report);
+            }
+        }
+        Ok((valid, report))
+    });
+
+    for (file, result) in files.iter().zip(&results) {
+        match result {
+            Ok((true, _)) => summary.add(file, "ok", Status::Ok),
+            Ok((false, report)) => {
+                print!("{}", report);
+                summary.add(file, "invalid", Status::Found);
+            }
+            Err(e) => summary.fail(file, "unreadable", e),
+        }
+    }
+    Ok(summary.finish())
+}
+
+// Adds a `file:line:col: pointer: message` line to `report` per violation in
+// `json`, which starts on line `first_line` of `file`.
+pub fn check(
+    schema: &Schema,
+    options: &ParseOptions,
+    file: &str,
+    json: &[u8],
+    first_line: usize,
+    report: &mut String,
+) -> bool {
+    let mut valid = true;
+    let res = schema.validate_with(json, options, |v| {
+        valid = false;
+        let (line, col) = line_col(json, v.offset());
+        let line = first_line + line - 1;
+        let _ = match v.pointer() {
+            "" => writeln!(report, "{}:{}:{}: {}", file, line, col, v.message()),
+            ptr => writeln!(
+                report,
+                "{}:{}:{}: {}: {}",
+                file,
+                line,
+                col,
+                ptr,
+                v.message()
+            ),
+        };
+    });
+    if let Err(e) = res {
+        let _ = writeln!(report, "{}:{}: {}", file, first_line, e);
+        valid = false;
+    }
+    valid
+}
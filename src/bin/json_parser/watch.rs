@@ -0,0 +1,171 @@
+use std::path::Path;
+use std::process::ExitCode;
+
+use eyre::OptionExt;
+use json_parser::{parse_with_options, Schema};
+
+use crate::config::Config;
+use crate::edit::{formatted, rewrite_options, write_atomic};
+use crate::files::{FileFilter, Walker};
+use crate::summary::{Status, Summary};
+use crate::validate::check;
+use crate::{load_schema, read};
+
+// What to do with each file as it changes.
+struct Watch<'c> {
+    schema: Option<Schema>,
+    // re-indent with this many spaces
+    fmt: Option<usize>,
+    sort_keys: bool,
+    config: &'c Config,
+    walker: Walker,
+    // copied for each batch of changes
+    summary: Summary,
+}
+
+impl Watch<'_> {
+    // Checks (and formats) `path`, printing one line per problem or `ok`.
+    fn run(&self, path: &Path, summary: &mut Summary) {
+        let file = path.display().to_string();
+        let input = match read(&file) {
+            Ok(input) => input,
+            Err(e) => {
+                summary.add(&file, "unreadable", Status::Io);
+                return println!("{}", e);
+            }
+        };
+        let mut report = String::new();
+        let valid = match &self.schema {
+            Some(schema) => check(schema, &self.config.parse, &file, &input, 1, &mut report),
+            None => parse_with_options(&input, &self.config.parse)
+                .map_err(|e| report = format!("{}: {}\n", file, e))
+                .is_ok(),
+        };
+        if !valid {
+            summary.add(&file, "invalid", Status::Found);
+            return print!("{}", report);
+        }
+        if let Some(indent) = self.fmt {
+            let options = rewrite_options(self.config);
+            match formatted(&input, indent, self.sort_keys, &options) {
+                Ok(out) if out.as_bytes() != input => match write_atomic(path, &out) {
+                    Ok(()) => {
+                        summary.add(&file, "formatted", Status::Ok);
+                        return println!("{}: formatted", file);
+                    }
+                    Err(e) => {
+                        summary.add(&file, "unwritable", Status::Io);
+                        return println!("{}: can't write: {}", file, e);
+                    }
+                },
+                Ok(_) => {}
+                Err(e) => {
+                    summary.add(&file, "invalid", Status::Found);
+                    return println!("{}: {}", file, e);
+                }
+            }
+        }
+        summary.add(&file, "ok", Status::Ok);
+        println!("{}: ok", file);
+    }
+
+    // Runs over `paths`, then prints the summary of the batch.
+    fn run_all(&self, paths: impl IntoIterator<Item = impl AsRef<Path>>) {
+        let mut summary = self.summary.clone();
+        for path in paths {
+            self.run(path.as_ref(), &mut summary);
+        }
+        summary.finish();
+    }
+}
+
+pub fn run(args: &[String], config: &Config) -> eyre::Result<ExitCode> {
+    let mut schema = None;
+    let mut fmt = false;
+    let mut indent = config.indent.unwrap_or(2);
+    let mut sort_keys = config.sort_keys;
+    let mut filter = FileFilter::default();
+    let mut summary = Summary::new("watch");
+    let mut paths = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            // checking is what watch always does
+            "--validate" => {}
+            "--schema" => schema = Some(args.next().ok_or_eyre("--schema needs a file")?),
+            "--fmt" => fmt = true,
+            "--sort-keys" => sort_keys = true,
+            "--indent" => {
+                indent = args
+                    .next()
+                    .and_then(|n| n.parse().ok())
+                    .ok_or_eyre("--indent needs a number of spaces")?
+            }
+            a if filter.parse(a, &mut args)? => {}
+            a if summary.parse(a, &mut args)? => {}
+            a if a.starts_with("--") => eyre::bail!(format!("Unknown option {}", a)),
+            a => paths.push(a),
+        }
+    }
+    if paths.is_empty() {
+        paths.push(".");
+    }
+    let watch = Watch {
+        schema: schema.map(|s| load_schema(s)).transpose()?,
+        fmt: fmt.then_some(indent),
+        sort_keys,
+        config,
+        walker: Walker::new(&filter, config)?,
+        summary,
+    };
+    watch_paths(&watch, &paths)
+}
+
+#[cfg(feature = "watch")]
+fn watch_paths(watch: &Watch, paths: &[&str]) -> eyre::Result<ExitCode> {
+    use std::collections::BTreeSet;
+    use std::time::Duration;
+
+    use notify::{EventKind, RecursiveMode, Watcher};
+
+    let (tx, rx) = std::sync::mpsc::channel();
+    let mut watcher = notify::recommended_watcher(tx)
+        .map_err(|e| eyre::eyre!(format!("Can't watch files: {}", e)))?;
+    for path in paths {
+        watcher
+            .watch(Path::new(path), RecursiveMode::Recursive)
+            .map_err(|e| eyre::eyre!(format!("Can't watch {}: {}", path, e)))?;
+    }
+    watch.run_all(watch.walker.files(paths)?);
+    // editors write a file in several steps, so changes arriving close
+    // together are handled once
+    while let Ok(event) = rx.recv() {
+        let mut changed = BTreeSet::new();
+        let mut event = Some(event);
+        while let Some(e) = event {
+            if let Ok(e) = e {
+                if matches!(e.kind, EventKind::Create(_) | EventKind::Modify(_)) {
+                    changed.extend(e.paths);
+                }
+            }
+            event = rx.recv_timeout(Duration::from_millis(100)).ok();
+        }
+        changed.retain(|path| {
+            let watched = paths
+                .iter()
+                .any(|root| watch.walker.selects(Path::new(root), path));
+            watched && path.is_file()
+        });
+        if !changed.is_empty() {
+            watch.run_all(changed);
+        }
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+#[cfg(not(feature = "watch"))]
+fn watch_paths(watch: &Watch, paths: &[&str]) -> eyre::Result<ExitCode> {
+    // check once, so the files still get looked at
+    watch.run_all(watch.walker.files(paths)?);
+    eyre::bail!("Watching needs json_parser built with the `watch` feature")
+}
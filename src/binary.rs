@@ -0,0 +1,172 @@
+use std::ops::Deref;
+
+use crate::JSONValue;
+
+const STANDARD: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Standard base64 with padding.
+pub(crate) fn encode_base64(data: &[u8]) -> String {
+    let mut out = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let b = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let n = (b[0] as u32) << 16 | (b[1] as u32) << 8 | b[2] as u32;
+        let chars = chunk.len() + 1;
+        for i in 0..4 {
+            if i < chars {
+                out.push(STANDARD[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+/// Decodes base64 in either the standard or URL-safe alphabet, with or
+/// without padding. One string can't mix the two alphabets, and padding,
+/// if any, must fill out the last group of four.
+pub(crate) fn decode_base64(s: &str) -> eyre::Result<Vec<u8>> {
+    let unpadded = s.trim_end_matches('=');
+    let padding = s.len() - unpadded.len();
+    if unpadded.len() % 4 == 1 || (padding > 0 && (padding > 2 || !s.len().is_multiple_of(4))) {
+        eyre::bail!("Invalid base64 length")
+    }
+    let mut out = Vec::with_capacity(unpadded.len() * 3 / 4);
+    let mut acc: u32 = 0;
+    let mut bits = 0;
+    // the alphabet fixed by the first `+/` or `-_` seen
+    let mut url_safe = None;
+    for c in unpadded.bytes() {
+        let v = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' | b'-' | b'/' | b'_' => {
+                let is_url_safe = matches!(c, b'-' | b'_');
+                if *url_safe.get_or_insert(is_url_safe) != is_url_safe {
+                    eyre::bail!("Base64 mixes the standard and URL-safe alphabets")
+                }
+                if matches!(c, b'+' | b'-') {
+                    62
+                } else {
+                    63
+                }
+            }
+            _ => eyre::bail!(format!("Invalid base64 character: '{}'", c as char)),
+        };
+        acc = acc << 6 | v as u32;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((acc >> bits) as u8);
+            acc &= (1 << bits) - 1;
+        }
+    }
+    Ok(out)
+}
+
+pub(crate) fn encode_hex(data: &[u8]) -> String {
+    const DIGITS: &[u8; 16] = b"0123456789abcdef";
+    let mut out = String::with_capacity(data.len() * 2);
+    for b in data {
+        out.push(DIGITS[(b >> 4) as usize] as char);
+        out.push(DIGITS[(b & 0xf) as usize] as char);
+    }
+    out
+}
+
+pub(crate) fn decode_hex(s: &str) -> eyre::Result<Vec<u8>> {
+    if !s.len().is_multiple_of(2) {
+        eyre::bail!("Invalid hex length")
+    }
+    s.as_bytes()
+        .chunks(2)
+        .map(|pair| {
+            let digit = |c: u8| {
+                (c as char)
+                    .to_digit(16)
+                    .ok_or_else(|| eyre::eyre!("Invalid hex character: '{}'", c as char))
+            };
+            Ok((digit(pair[0])? << 4 | digit(pair[1])?) as u8)
+        })
+        .collect()
+}
+
+impl JSONValue {
+    /// Decodes a base64 string value (standard or URL-safe alphabet).
+    pub fn as_base64_bytes(&self) -> Option<Vec<u8>> {
+        match self {
+            JSONValue::Str(s) => decode_base64(s).ok(),
+            _ => None,
+        }
+    }
+
+    /// Decodes a hex string value.
+    pub fn as_hex_bytes(&self) -> Option<Vec<u8>> {
+        match self {
+            JSONValue::Str(s) => decode_hex(s).ok(),
+            _ => None,
+        }
+    }
+
+    /// A string value holding `data` as padded standard base64.
+    pub fn from_bytes_base64(data: &[u8]) -> Self {
+        JSONValue::Str(encode_base64(data))
+    }
+
+    /// A string value holding `data` as lowercase hex.
+    pub fn from_bytes_hex(data: &[u8]) -> Self {
+        JSONValue::Str(encode_hex(data))
+    }
+}
+
+/// Binary data that is represented in JSON as a base64 string.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Bytes(pub Vec<u8>);
+
+impl Bytes {
+    pub fn into_inner(self) -> Vec<u8> {
+        self.0
+    }
+}
+
+impl Deref for Bytes {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl From<Vec<u8>> for Bytes {
+    fn from(v: Vec<u8>) -> Self {
+        Bytes(v)
+    }
+}
+
+impl From<&[u8]> for Bytes {
+    fn from(v: &[u8]) -> Self {
+        Bytes(v.to_vec())
+    }
+}
+
+impl From<Bytes> for JSONValue {
+    fn from(b: Bytes) -> Self {
+        JSONValue::from_bytes_base64(&b)
+    }
+}
+
+impl TryFrom<&JSONValue> for Bytes {
+    type Error = eyre::Report;
+
+    fn try_from(v: &JSONValue) -> eyre::Result<Self> {
+        match v {
+            JSONValue::Str(s) => Ok(Bytes(decode_base64(s)?)),
+            _ => eyre::bail!("Expected base64 string"),
+        }
+    }
+}
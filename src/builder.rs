@@ -0,0 +1,120 @@
+use std::collections::HashMap;
+
+use crate::JSONValue;
+
+/// Fluent construction of a `JSONValue::Dict`.
+#[derive(Debug, Default)]
+pub struct ObjectBuilder {
+    entries: HashMap<String, JSONValue>,
+}
+
+impl ObjectBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            entries: HashMap::with_capacity(capacity),
+        }
+    }
+
+    /// Sets `key` to `value`, replacing any earlier value for the same key.
+    pub fn field(mut self, key: impl Into<String>, value: impl Into<JSONValue>) -> Self {
+        self.entries.insert(key.into(), value.into());
+        self
+    }
+
+    /// Like `field`, but leaves the key out entirely when `value` is `None`.
+    pub fn field_opt<V: Into<JSONValue>>(self, key: impl Into<String>, value: Option<V>) -> Self {
+        match value {
+            Some(v) => self.field(key, v),
+            None => self,
+        }
+    }
+
+    pub fn build(self) -> JSONValue {
+        JSONValue::Dict(self.entries)
+    }
+}
+
+/// Fluent construction of a `JSONValue::Array`.
+#[derive(Debug, Default)]
+pub struct ArrayBuilder {
+    entries: Vec<JSONValue>,
+}
+
+impl ArrayBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            entries: Vec::with_capacity(capacity),
+        }
+    }
+
+    pub fn push(mut self, value: impl Into<JSONValue>) -> Self {
+        self.entries.push(value.into());
+        self
+    }
+
+    pub fn extend<V: Into<JSONValue>>(mut self, values: impl IntoIterator<Item = V>) -> Self {
+        self.entries.extend(values.into_iter().map(Into::into));
+        self
+    }
+
+    pub fn build(self) -> JSONValue {
+        JSONValue::Array(self.entries)
+    }
+}
+
+impl From<ObjectBuilder> for JSONValue {
+    fn from(b: ObjectBuilder) -> Self {
+        b.build()
+    }
+}
+
+impl From<ArrayBuilder> for JSONValue {
+    fn from(b: ArrayBuilder) -> Self {
+        b.build()
+    }
+}
+
+/// Builds a `JSONValue` from JSON-like syntax.
+///
+/// Keys must be string literals. Values that span more than one token
+/// (e.g. `-1` or `a + b`) need to be wrapped in parentheses.
+///
+/// ```
+/// use json_parser::{json, ArrayBuilder};
+///
+/// let tags = ArrayBuilder::new().push("a").push("b");
+/// let val = json!({"foo": [1, 2, (-3)], "bar": null, "tags": tags});
+/// ```
+#[macro_export]
+macro_rules! json {
+    (null) => {
+        $crate::JSONValue::Null
+    };
+    ([]) => {
+        $crate::JSONValue::Array(::std::vec::Vec::new())
+    };
+    ([ $($elem:tt),+ $(,)? ]) => {
+        $crate::ArrayBuilder::new()
+            $(.push($crate::json!($elem)))+
+            .build()
+    };
+    ({}) => {
+        $crate::JSONValue::Dict(::std::collections::HashMap::new())
+    };
+    ({ $($key:literal : $value:tt),+ $(,)? }) => {
+        $crate::ObjectBuilder::new()
+            $(.field($key, $crate::json!($value)))+
+            .build()
+    };
+    ($other:expr) => {
+        $crate::JSONValue::from($other)
+    };
+}
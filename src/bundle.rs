@@ -0,0 +1,147 @@
+use std::collections::HashMap;
+use std::ops::Range;
+
+use eyre::OptionExt;
+
+use crate::lexer::{Lexer, SpannedToken, Token};
+use crate::prefix::TreeBuilder;
+use crate::{parse, JSONValue};
+
+/// Writes a bundle: many named documents in one JSON file, readable one at
+/// a time with `Bundle`.
+///
+/// The file is an object whose `index` member maps each name to the byte
+/// range of its document inside the `members` object that follows, e.g.
+/// `{"index":{"a":[5,7]},"members":{"a":[]}}`.
+#[derive(Debug, Clone, Default)]
+pub struct BundleWriter {
+    members: Vec<(String, String)>,
+}
+
+impl BundleWriter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds `doc` as `name`, replacing any earlier member of that name.
+    pub fn add(&mut self, name: &str, doc: &JSONValue) -> &mut Self {
+        let text = doc.to_json_string();
+        match self.members.iter_mut().find(|(n, _)| n == name) {
+            Some((_, t)) => *t = text,
+            None => self.members.push((name.to_string(), text)),
+        }
+        self
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut members = String::from("{");
+        let mut index = HashMap::new();
+        for (i, (name, text)) in self.members.iter().enumerate() {
+            if i > 0 {
+                members.push(',');
+            }
+            members.push_str(&JSONValue::Str(name.clone()).to_json_string());
+            members.push(':');
+            let range = [members.len(), members.len() + text.len()];
+            index.insert(
+                name.clone(),
+                JSONValue::Array(range.map(|n| JSONValue::Num(n as f64)).to_vec()),
+            );
+            members.push_str(text);
+        }
+        members.push('}');
+        let index = JSONValue::Dict(index).to_json_string();
+        format!(r#"{{"index":{},"members":{}}}"#, index, members).into_bytes()
+    }
+}
+
+/// A bundle written by `BundleWriter`. Opening one reads only its index;
+/// each member is parsed when asked for.
+#[derive(Debug, Clone)]
+pub struct Bundle {
+    buf: Vec<u8>,
+    index: HashMap<String, Range<usize>>,
+}
+
+impl Bundle {
+    pub fn open(buf: impl Into<Vec<u8>>) -> eyre::Result<Self> {
+        let buf = buf.into();
+        let mut tokens = Lexer::new().tokens(&buf);
+        let mut next =
+            || -> eyre::Result<SpannedToken> { tokens.next().ok_or_eyre("Bundle ends early")? };
+        let expect_key = |t: Token, key: &str| match t {
+            Token::StringVal(i, j) if &buf[i..j] == key.as_bytes() => Ok(()),
+            _ => eyre::bail!(format!("Expected bundle member `{}`", key)),
+        };
+        if next()?.token != Token::LeftBrace {
+            eyre::bail!("Bundle must be an object")
+        }
+        expect_key(next()?.token, "index")?;
+        if next()?.token != Token::Colon {
+            eyre::bail!("Expected `:` after `index`")
+        }
+        let mut builder = TreeBuilder::new();
+        let index = loop {
+            if let Some(index) = builder.push(next()?.token, &buf)? {
+                break index;
+            }
+        };
+        if next()?.token != Token::Comma {
+            eyre::bail!("Expected `members` after the index")
+        }
+        expect_key(next()?.token, "members")?;
+        if next()?.token != Token::Colon {
+            eyre::bail!("Expected `:` after `members`")
+        }
+        let start = match next()? {
+            t if t.token == Token::LeftBrace => t.span.start,
+            _ => eyre::bail!("Bundle `members` must be an object"),
+        };
+        let JSONValue::Dict(index) = index else {
+            eyre::bail!("Bundle `index` must be an object")
+        };
+        let index = index
+            .into_iter()
+            .map(|(name, range)| {
+                let range = match &range {
+                    JSONValue::Array(r) => match r.as_slice() {
+                        [JSONValue::Num(from), JSONValue::Num(to)] => start
+                            .checked_add(*from as usize)
+                            .zip(start.checked_add(*to as usize))
+                            .map(|(from, to)| from..to),
+                        _ => None,
+                    },
+                    _ => None,
+                };
+                let range = range
+                    .filter(|r| r.start <= r.end && r.end <= buf.len())
+                    .ok_or_else(|| eyre::eyre!(format!("Invalid index entry for {:?}", name)))?;
+                Ok((name, range))
+            })
+            .collect::<eyre::Result<_>>()?;
+        Ok(Self { buf, index })
+    }
+
+    /// Member names, in no particular order.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.index.keys().map(String::as_str)
+    }
+
+    pub fn len(&self) -> usize {
+        self.index.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.index.is_empty()
+    }
+
+    /// The text of the member `name`, unparsed.
+    pub fn raw(&self, name: &str) -> Option<&[u8]> {
+        self.index.get(name).map(|r| &self.buf[r.clone()])
+    }
+
+    /// Parses the member `name`, or `None` if there isn't one.
+    pub fn get(&self, name: &str) -> Option<eyre::Result<JSONValue>> {
+        self.raw(name).map(parse)
+    }
+}
@@ -0,0 +1,105 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+
+use crate::{parse_with_options, JSONValue, ParseOptions};
+
+// Entries by input, each with the tick it was last used at, and the inputs
+// by that tick, oldest first.
+#[derive(Default)]
+struct Entries {
+    by_input: HashMap<Arc<[u8]>, (Arc<JSONValue>, u64)>,
+    by_use: BTreeMap<u64, Arc<[u8]>>,
+    bytes: usize,
+    tick: u64,
+}
+
+/// Remembers parsed documents by their input, so services that keep getting
+/// the same payload (webhook retries, polling) parse it once and share the
+/// result. Inputs are looked up by hash and compared byte for byte, so a hash
+/// collision can't return the wrong document.
+///
+/// The inputs kept add up to at most `max_bytes`; the least recently used
+/// are dropped to make room. Safe to share between threads.
+pub struct ParseCache {
+    entries: Mutex<Entries>,
+    max_bytes: usize,
+    options: ParseOptions,
+}
+
+impl ParseCache {
+    pub fn new(max_bytes: usize) -> Self {
+        Self::with_options(max_bytes, ParseOptions::default())
+    }
+
+    pub fn with_options(max_bytes: usize, options: ParseOptions) -> Self {
+        Self {
+            entries: Mutex::default(),
+            max_bytes,
+            options,
+        }
+    }
+
+    /// The document `json` parses to, parsing it only if it isn't cached.
+    /// Errors aren't cached.
+    pub fn get_or_parse(&self, json: &[u8]) -> eyre::Result<Arc<JSONValue>> {
+        if let Some(v) = self.get(json) {
+            return Ok(v);
+        }
+        // parsed unlocked, so a slow parse doesn't hold up other threads
+        let v = Arc::new(parse_with_options(json, &self.options)?);
+        if json.len() <= self.max_bytes {
+            self.insert(json, v.clone());
+        }
+        Ok(v)
+    }
+
+    /// The cached document for `json`, if any.
+    pub fn get(&self, json: &[u8]) -> Option<Arc<JSONValue>> {
+        let mut guard = self.entries.lock().unwrap();
+        let entries = &mut *guard;
+        let tick = entries.tick;
+        let (input, _) = entries.by_input.get_key_value(json)?;
+        let input = input.clone();
+        let (v, used) = entries.by_input.get_mut(json)?;
+        let old = std::mem::replace(used, tick);
+        let v = v.clone();
+        entries.by_use.remove(&old);
+        entries.by_use.insert(tick, input);
+        entries.tick += 1;
+        Some(v)
+    }
+
+    fn insert(&self, json: &[u8], v: Arc<JSONValue>) {
+        let mut guard = self.entries.lock().unwrap();
+        let entries = &mut *guard;
+        if entries.by_input.contains_key(json) {
+            // another thread got there first
+            return;
+        }
+        while entries.bytes + json.len() > self.max_bytes {
+            let Some((_, oldest)) = entries.by_use.pop_first() else {
+                break;
+            };
+            entries.bytes -= oldest.len();
+            entries.by_input.remove(&oldest);
+        }
+        let input: Arc<[u8]> = json.into();
+        entries.by_input.insert(input.clone(), (v, entries.tick));
+        entries.by_use.insert(entries.tick, input);
+        entries.bytes += json.len();
+        entries.tick += 1;
+    }
+
+    /// How many documents are cached.
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().by_input.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn clear(&self) {
+        *self.entries.lock().unwrap() = Entries::default();
+    }
+}
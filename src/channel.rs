@@ -0,0 +1,86 @@
+use std::io::BufRead;
+use std::sync::mpsc::{sync_channel, Receiver};
+use std::thread::{self, JoinHandle};
+
+use crate::{parse_auto, JSONValue};
+
+/// Records parsed on a background thread by `spawn_records`, received in
+/// order. Iteration ends after the last record or the first error.
+pub struct RecordReceiver {
+    rx: Receiver<eyre::Result<JSONValue>>,
+    reader: Option<JoinHandle<()>>,
+}
+
+/// Reads and parses the records of `reader` (see `parse_auto`) on a new
+/// thread, handing them over through a channel holding at most `capacity`
+/// of them. Once it is full the thread waits, so a slow consumer throttles
+/// reading instead of parsed records piling up. Dropping the receiver stops
+/// the thread after its current record.
+pub fn spawn_records<R: BufRead + Send + 'static>(reader: R, capacity: usize) -> RecordReceiver {
+    let (tx, rx) = sync_channel(capacity);
+    let reader = thread::spawn(move || send_records(reader, |r| tx.send(r).is_ok()));
+    RecordReceiver {
+        rx,
+        reader: Some(reader),
+    }
+}
+
+// Calls `send` with each record until it returns `false` or there's an error.
+fn send_records<R: BufRead>(reader: R, mut send: impl FnMut(eyre::Result<JSONValue>) -> bool) {
+    let records = match parse_auto(reader) {
+        Ok(records) => records,
+        Err(e) => {
+            send(Err(e));
+            return;
+        }
+    };
+    for record in records {
+        let failed = record.is_err();
+        if !send(record) || failed {
+            break;
+        }
+    }
+}
+
+impl Iterator for RecordReceiver {
+    type Item = eyre::Result<JSONValue>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.rx.recv() {
+            Ok(record) => Some(record),
+            Err(_) => {
+                // the reader is done; surface a panic on its thread
+                if let Some(Err(panic)) = self.reader.take().map(JoinHandle::join) {
+                    std::panic::resume_unwind(panic);
+                }
+                None
+            }
+        }
+    }
+}
+
+#[cfg(feature = "tokio")]
+mod tokio_support {
+    use std::io::BufRead;
+    use std::thread;
+
+    use tokio::sync::mpsc;
+
+    use super::send_records;
+    use crate::JSONValue;
+
+    /// Like `spawn_records`, but delivers to async code through a bounded
+    /// `tokio::sync::mpsc` channel. Reading still happens on its own
+    /// thread, so it never blocks the runtime.
+    pub fn spawn_records_tokio<R: BufRead + Send + 'static>(
+        reader: R,
+        capacity: usize,
+    ) -> mpsc::Receiver<eyre::Result<JSONValue>> {
+        let (tx, rx) = mpsc::channel(capacity);
+        thread::spawn(move || send_records(reader, |r| tx.blocking_send(r).is_ok()));
+        rx
+    }
+}
+
+#[cfg(feature = "tokio")]
+pub use tokio_support::spawn_records_tokio;
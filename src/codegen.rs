@@ -0,0 +1,165 @@
+use core::fmt::Write;
+
+use crate::schema::{Rules, Type};
+use crate::Schema;
+
+// Words Rust reserves, which need `r#` as field names.
+const KEYWORDS: &[&str] = &[
+    "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum", "extern",
+    "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub",
+    "ref", "return", "static", "struct", "trait", "true", "type", "unsafe", "use", "where",
+    "while", "abstract", "become", "box", "do", "final", "gen", "macro", "override", "priv", "try",
+    "typeof", "unsized", "virtual", "yield",
+];
+
+// Splits `key` into lowercase words at non-alphanumerics and camelCase humps.
+fn words(key: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut prev_lower = false;
+    for c in key.chars() {
+        if !c.is_alphanumeric() {
+            words.extend((!word.is_empty()).then(|| std::mem::take(&mut word)));
+            prev_lower = false;
+            continue;
+        }
+        if c.is_uppercase() && prev_lower {
+            words.push(std::mem::take(&mut word));
+        }
+        prev_lower = c.is_lowercase() || c.is_numeric();
+        word.extend(c.to_lowercase());
+    }
+    words.extend((!word.is_empty()).then_some(word));
+    words
+}
+
+fn field_name(key: &str) -> String {
+    let name = words(key).join("_");
+    match name.chars().next() {
+        None => "field".to_string(),
+        Some(c) if c.is_numeric() => format!("_{}", name),
+        Some(_) if name == "self" || name == "super" => format!("{}_", name),
+        Some(_) if KEYWORDS.contains(&name.as_str()) => format!("r#{}", name),
+        Some(_) => name,
+    }
+}
+
+fn type_name(key: &str) -> String {
+    let name: String = words(key)
+        .iter()
+        .map(|w| {
+            let mut cs = w.chars();
+            cs.next()
+                .map(|c| c.to_uppercase().chain(cs).collect())
+                .unwrap_or_default()
+        })
+        .collect::<Vec<String>>()
+        .concat();
+    match name.chars().next() {
+        None => "Value".to_string(),
+        Some(c) if c.is_numeric() => format!("T{}", name),
+        Some(_) => name,
+    }
+}
+
+struct Codegen {
+    // finished struct definitions, root first
+    structs: Vec<(String, String)>,
+}
+
+impl Codegen {
+    fn unique(&self, name: String) -> String {
+        let taken = |n: &str| self.structs.iter().any(|(s, _)| s == n);
+        if !taken(&name) {
+            return name;
+        }
+        (2..)
+            .map(|i| format!("{}{}", name, i))
+            .find(|n| !taken(n))
+            .expect("some suffix is free")
+    }
+
+    // The Rust type for values matching `rules`, found at key `key`.
+    fn rust_type(&mut self, rules: &Rules, key: &str) -> String {
+        let mut types = rules.types();
+        let nullable = types.len() > 1 && types.contains(&Type::Null);
+        types.retain(|t| !nullable || *t != Type::Null);
+        let ty = match types[..] {
+            [Type::Null] => "()".to_string(),
+            [Type::Boolean] => "bool".to_string(),
+            [Type::Integer] => "i64".to_string(),
+            [Type::Number] | [Type::Integer, Type::Number] | [Type::Number, Type::Integer] => {
+                "f64".to_string()
+            }
+            [Type::String] => "String".to_string(),
+            [Type::Array] => match &rules.items {
+                Some(items) => format!("Vec<{}>", self.rust_type(items, key)),
+                None => "Vec<json_parser::JSONValue>".to_string(),
+            },
+            [Type::Object] if !rules.properties.is_empty() => self.define(rules, type_name(key)),
+            [Type::Object] => match &rules.additional_properties {
+                Some(values) if !values.never => format!(
+                    "std::collections::HashMap<String, {}>",
+                    self.rust_type(values, key)
+                ),
+                _ => "std::collections::HashMap<String, json_parser::JSONValue>".to_string(),
+            },
+            _ => "json_parser::JSONValue".to_string(),
+        };
+        if nullable {
+            format!("Option<{}>", ty)
+        } else {
+            ty
+        }
+    }
+
+    // Writes a struct for an object with `rules.properties`, returning its
+    // name.
+    fn define(&mut self, rules: &Rules, name: String) -> String {
+        let name = self.unique(name);
+        // reserve the name before nested structs are defined
+        let slot = self.structs.len();
+        self.structs.push((name.clone(), String::new()));
+
+        let mut keys: Vec<_> = rules.properties.keys().collect();
+        keys.sort();
+        let mut out = String::from("#[derive(Debug, Clone, PartialEq, json_parser::FromJson)]\n");
+        writeln!(out, "pub struct {} {{", name).unwrap();
+        for key in keys {
+            let property = &rules.properties[key];
+            if property.never {
+                continue;
+            }
+            let mut ty = self.rust_type(property, key);
+            if !rules.required.contains(key) && !ty.starts_with("Option<") {
+                ty = format!("Option<{}>", ty);
+            }
+            let field = field_name(key);
+            if field.trim_start_matches("r#") != key {
+                writeln!(out, "    #[json(rename = {:?})]", key).unwrap();
+            }
+            writeln!(out, "    pub {}: {},", field, ty).unwrap();
+        }
+        out.push('}');
+        self.structs[slot].1 = out;
+        name
+    }
+}
+
+impl Schema {
+    /// Rust structs that `#[derive(FromJson)]` for documents matching the
+    /// schema, the root one named `name`. Needs an object schema with
+    /// `properties`.
+    pub fn to_rust(&self, name: &str) -> eyre::Result<String> {
+        let rules = self.rules();
+        if !rules.types().contains(&Type::Object) || rules.properties.is_empty() {
+            eyre::bail!("Only object schemas with properties can become structs")
+        }
+        let mut codegen = Codegen {
+            structs: Vec::new(),
+        };
+        codegen.define(rules, type_name(name));
+        let structs: Vec<_> = codegen.structs.into_iter().map(|(_, s)| s).collect();
+        Ok(structs.join("\n\n") + "\n")
+    }
+}
@@ -0,0 +1,57 @@
+use crate::JSONValue;
+
+// Lenient counterparts of the `as_*` accessors, for APIs that send numbers
+// as strings or booleans as 0/1. Kept apart so strict code can't slip into
+// them by accident.
+impl JSONValue {
+    /// A whole number, also from a string like `"42"` or `" -7 "` and from a
+    /// boolean (`true` is 1). `None` for fractions, values out of range and
+    /// anything else.
+    pub fn coerce_i64(&self) -> Option<i64> {
+        let n = match self {
+            JSONValue::Bool(b) => return Some(i64::from(*b)),
+            JSONValue::Str(s) => {
+                let s = s.trim();
+                if let Ok(n) = s.parse::<i64>() {
+                    return Some(n);
+                }
+                parse_finite(s)?
+            }
+            JSONValue::Num(n) => *n,
+            _ => return None,
+        };
+        // `as` saturates; the bounds keep saturated values out
+        (n.fract() == 0.0 && n >= i64::MIN as f64 && n < i64::MAX as f64).then_some(n as i64)
+    }
+
+    /// A number, also from a string like `"2.5"` or `"1e3"` and from a
+    /// boolean. Strings spelling `NaN` or infinity give `None`.
+    pub fn coerce_f64(&self) -> Option<f64> {
+        match self {
+            JSONValue::Num(n) => Some(*n),
+            JSONValue::Bool(b) => Some(f64::from(u8::from(*b))),
+            JSONValue::Str(s) => parse_finite(s.trim()),
+            _ => None,
+        }
+    }
+
+    /// A boolean, also from the numbers 0 and 1 and from the strings
+    /// `"true"`/`"false"`, `"yes"`/`"no"` and `"1"`/`"0"` in any case.
+    pub fn coerce_bool(&self) -> Option<bool> {
+        match self {
+            JSONValue::Bool(b) => Some(*b),
+            JSONValue::Num(n) if *n == 0.0 => Some(false),
+            JSONValue::Num(n) if *n == 1.0 => Some(true),
+            JSONValue::Str(s) => match s.trim().to_ascii_lowercase().as_str() {
+                "true" | "yes" | "1" => Some(true),
+                "false" | "no" | "0" => Some(false),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
+
+fn parse_finite(s: &str) -> Option<f64> {
+    s.parse::<f64>().ok().filter(|n| n.is_finite())
+}
@@ -0,0 +1,192 @@
+use std::collections::HashMap;
+
+use crate::schema::Type;
+use crate::JSONValue;
+
+/// The values of one column, typed from what the records held. Rows where
+/// the column is null or missing hold a default here and are marked in
+/// `Column::validity`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ColumnValues {
+    /// Every row was null or missing.
+    Null,
+    Bool(Vec<bool>),
+    /// Whole numbers that all fit in an `i64`.
+    Int(Vec<i64>),
+    Float(Vec<f64>),
+    Str(Vec<String>),
+    /// Arrays, objects, or a mix of types, kept as they were.
+    Json(Vec<JSONValue>),
+}
+
+/// One column of a `ColumnBatch`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Column {
+    name: String,
+    values: ColumnValues,
+    validity: Vec<bool>,
+}
+
+impl Column {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn values(&self) -> &ColumnValues {
+        &self.values
+    }
+
+    /// Whether each row has a value, as Arrow's validity bitmap wants.
+    pub fn validity(&self) -> &[bool] {
+        &self.validity
+    }
+
+    pub fn null_count(&self) -> usize {
+        self.validity.iter().filter(|valid| !**valid).count()
+    }
+}
+
+/// Flat object records turned into one typed array per key, the shape
+/// Arrow and Parquet writers take.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ColumnBatch {
+    rows: usize,
+    columns: Vec<Column>,
+}
+
+impl ColumnBatch {
+    /// Collects `records` into a batch; see `BatchBuilder::push`.
+    pub fn from_records<I: IntoIterator<Item = JSONValue>>(records: I) -> eyre::Result<Self> {
+        let mut builder = BatchBuilder::new();
+        for record in records {
+            builder.push(record)?;
+        }
+        Ok(builder.finish())
+    }
+
+    /// Number of rows.
+    pub fn len(&self) -> usize {
+        self.rows
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows == 0
+    }
+
+    /// Columns in key order.
+    pub fn columns(&self) -> &[Column] {
+        &self.columns
+    }
+
+    pub fn column(&self, name: &str) -> Option<&Column> {
+        self.columns.iter().find(|c| c.name == name)
+    }
+}
+
+/// Builds a `ColumnBatch` one record at a time, e.g. while reading NDJSON,
+/// so a long stream can be cut into batches of a fixed size.
+#[derive(Debug, Clone, Default)]
+pub struct BatchBuilder {
+    rows: usize,
+    columns: HashMap<String, Vec<JSONValue>>,
+}
+
+impl BatchBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a row. `record` must be an object; a key it lacks is null in
+    /// that row, and a key not seen before becomes a new column, null in
+    /// the rows before.
+    pub fn push(&mut self, record: JSONValue) -> eyre::Result<()> {
+        let JSONValue::Dict(fields) = record else {
+            eyre::bail!(format!(
+                "Expected record {} to be an object, found {}",
+                self.rows,
+                Type::of(&record).name()
+            ))
+        };
+        for (name, value) in fields {
+            let column = self
+                .columns
+                .entry(name)
+                .or_insert_with(|| vec![JSONValue::Null; self.rows]);
+            column.push(value);
+        }
+        self.rows += 1;
+        for column in self.columns.values_mut() {
+            column.resize(self.rows, JSONValue::Null);
+        }
+        Ok(())
+    }
+
+    /// Rows pushed so far.
+    pub fn len(&self) -> usize {
+        self.rows
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows == 0
+    }
+
+    /// Infers each column's type and returns the batch, leaving the builder
+    /// empty for the next one.
+    pub fn finish(&mut self) -> ColumnBatch {
+        let rows = std::mem::take(&mut self.rows);
+        let mut columns: Vec<Column> = std::mem::take(&mut self.columns)
+            .into_iter()
+            .map(|(name, values)| column(name, values))
+            .collect();
+        columns.sort_unstable_by(|a, b| a.name.cmp(&b.name));
+        ColumnBatch { rows, columns }
+    }
+}
+
+fn column(name: String, values: Vec<JSONValue>) -> Column {
+    let validity: Vec<bool> = values.iter().map(|v| *v != JSONValue::Null).collect();
+    let present = || values.iter().filter(|v| **v != JSONValue::Null);
+    let all = |f: fn(&JSONValue) -> bool| present().all(f);
+    let values = if present().next().is_none() {
+        ColumnValues::Null
+    } else if all(|v| matches!(v, JSONValue::Bool(_))) {
+        ColumnValues::Bool(
+            values
+                .iter()
+                .map(|v| matches!(v, JSONValue::Bool(true)))
+                .collect(),
+        )
+    } else if all(|v| matches!(v, JSONValue::Num(n) if is_int(*n))) {
+        ColumnValues::Int(values.iter().map(|v| number(v) as i64).collect())
+    } else if all(|v| matches!(v, JSONValue::Num(_))) {
+        ColumnValues::Float(values.iter().map(number).collect())
+    } else if all(|v| matches!(v, JSONValue::Str(_))) {
+        ColumnValues::Str(
+            values
+                .into_iter()
+                .map(|v| match v {
+                    JSONValue::Str(s) => s,
+                    _ => String::new(),
+                })
+                .collect(),
+        )
+    } else {
+        ColumnValues::Json(values)
+    };
+    Column {
+        name,
+        values,
+        validity,
+    }
+}
+
+fn is_int(n: f64) -> bool {
+    n.fract() == 0.0 && n.abs() < i64::MAX as f64
+}
+
+fn number(v: &JSONValue) -> f64 {
+    match v {
+        JSONValue::Num(n) => *n,
+        _ => 0.0,
+    }
+}
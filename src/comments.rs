@@ -0,0 +1,185 @@
+use std::ops::Range;
+
+use crate::lexer::{Lexer, SpannedToken, Token};
+use crate::{escape, pointer, JSONValue, ParseOptions, RecursiveParser, ValuePool};
+
+/// A comment found while parsing in comment-tolerant mode.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Comment {
+    /// The comment as written, including its `//` or `/* */` delimiters.
+    pub text: String,
+    /// Byte range of the comment in the input.
+    pub span: Range<usize>,
+    pub placement: CommentPlacement,
+}
+
+impl Comment {
+    pub fn is_block(&self) -> bool {
+        self.text.starts_with("/*")
+    }
+}
+
+/// Where a comment sits relative to the values of the document, each variant
+/// holding the JSON Pointer of the value it is attached to.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CommentPlacement {
+    /// Before the member or element at the pointer.
+    Leading(String),
+    /// After the value at the pointer, typically on the same line.
+    Trailing(String),
+    /// Inside the empty container at the pointer.
+    Dangling(String),
+}
+
+pub(crate) struct RawComment {
+    span: Range<usize>,
+    next_token: usize,
+    same_line: bool,
+}
+
+impl RawComment {
+    pub(crate) fn new(buf: &[u8], span: Range<usize>, next_token: usize) -> Self {
+        let mut same_line = false;
+        for c in buf[..span.start].iter().rev() {
+            match c {
+                b'\n' => break,
+                b' ' | b'\t' | b'\r' => continue,
+                _ => {
+                    same_line = true;
+                    break;
+                }
+            }
+        }
+        Self {
+            span,
+            next_token,
+            same_line,
+        }
+    }
+}
+
+/// Returns the end of the comment starting at `buf[i] == '/'`. Line comments
+/// end before their newline.
+pub(crate) fn comment_end(buf: &[u8], i: usize) -> eyre::Result<usize> {
+    match buf.get(i + 1) {
+        Some(b'/') => Ok(buf[i..]
+            .iter()
+            .position(|c| *c == b'\n')
+            .map_or(buf.len(), |n| i + n)),
+        Some(b'*') => match buf[i + 2..].windows(2).position(|w| w == b"*/") {
+            Some(n) => Ok(i + 2 + n + 2),
+            None => eyre::bail!("Unterminated block comment"),
+        },
+        _ => eyre::bail!("Unexpected value: '/'"),
+    }
+}
+
+/// Parses `json` with comments allowed (regardless of
+/// `options.allow_comments`), returning the comments alongside the value.
+pub fn parse_with_comments(
+    json: &[u8],
+    options: &ParseOptions,
+) -> eyre::Result<(JSONValue, Vec<Comment>)> {
+    let mut options = options.clone();
+    options.allow_comments = true;
+    let lexer = Lexer::with_options(&options);
+    let (tokens, raw) = lexer.lex_with_comments(json)?;
+    let mut pool = ValuePool::new();
+    let value = RecursiveParser::new(json, &mut pool, None, &options).parse_tokens(&tokens)?;
+    let marks = mark_tokens(&tokens, json);
+    let comments = raw
+        .into_iter()
+        .map(|c| Comment {
+            text: String::from_utf8_lossy(&json[c.span.clone()]).into_owned(),
+            placement: place(&c, &tokens, &marks),
+            span: c.span,
+        })
+        .collect();
+    Ok((value, comments))
+}
+
+#[derive(Default, Clone)]
+struct Marks {
+    // pointer of the member/element that begins at this token
+    starts: Option<String>,
+    // pointer of the value that ends at this token
+    ends: Option<String>,
+}
+
+enum Frame {
+    Array { ptr: String, idx: usize },
+    Object { ptr: String, key: Option<String> },
+}
+
+// Expects tokens that already parsed successfully.
+fn mark_tokens(tokens: &[SpannedToken], buf: &[u8]) -> Vec<Marks> {
+    let mut marks = vec![Marks::default(); tokens.len()];
+    let mut stack: Vec<Frame> = Vec::new();
+    for (k, t) in tokens.iter().map(|t| &t.token).enumerate() {
+        match t {
+            Token::Colon => continue,
+            Token::Comma => {
+                if let Some(Frame::Array { idx, .. }) = stack.last_mut() {
+                    *idx += 1;
+                }
+                continue;
+            }
+            Token::RightBrace | Token::RightBracket => {
+                marks[k].ends = match stack.pop() {
+                    Some(Frame::Array { ptr, .. }) | Some(Frame::Object { ptr, .. }) => Some(ptr),
+                    None => None,
+                };
+                continue;
+            }
+            _ => {}
+        }
+        // object keys
+        if let (Some(Frame::Object { ptr, key }), Token::StringVal(i, j)) = (stack.last_mut(), t) {
+            if key.is_none() {
+                let name = escape::lossy(&buf[*i..*j]);
+                marks[k].starts = Some(pointer::child(ptr, &name));
+                *key = Some(name);
+                continue;
+            }
+        }
+        // values
+        let ptr = match stack.last_mut() {
+            None => String::new(),
+            Some(Frame::Array { ptr, idx }) => pointer::child(ptr, &idx.to_string()),
+            Some(Frame::Object { ptr, key }) => {
+                pointer::child(ptr, &key.take().unwrap_or_default())
+            }
+        };
+        marks[k].starts = Some(ptr.clone());
+        match t {
+            Token::LeftBrace => stack.push(Frame::Object { ptr, key: None }),
+            Token::LeftBracket => stack.push(Frame::Array { ptr, idx: 0 }),
+            _ => marks[k].ends = Some(ptr),
+        }
+    }
+    marks
+}
+
+fn place(c: &RawComment, tokens: &[SpannedToken], marks: &[Marks]) -> CommentPlacement {
+    // the value before the comment, looking past a separating comma
+    let mut prev_idx = c.next_token.checked_sub(1);
+    if let Some(p) = prev_idx {
+        if tokens[p].token == Token::Comma {
+            prev_idx = p.checked_sub(1);
+        }
+    }
+    let prev = prev_idx.and_then(|p| marks[p].ends.clone());
+    let next = marks.get(c.next_token).and_then(|m| m.starts.clone());
+    match (prev, next) {
+        (Some(prev), _) if c.same_line => CommentPlacement::Trailing(prev),
+        (_, Some(next)) => CommentPlacement::Leading(next),
+        (Some(prev), None) => CommentPlacement::Trailing(prev),
+        (None, None) => {
+            let container = marks
+                .get(c.next_token)
+                .and_then(|m| m.ends.clone())
+                .unwrap_or_default();
+            CommentPlacement::Dangling(container)
+        }
+    }
+}
@@ -0,0 +1,396 @@
+use std::collections::HashMap;
+use std::ops::Range;
+
+use crate::document::{Document, Node, NodeKind};
+use crate::{pointer, JSONValue, ParseOptions, SerializeOptions};
+
+/// Surgical edits to a hand-maintained JSON or JSONC file such as
+/// `package.json` or `tsconfig.json`. Only the text of the value being
+/// changed is rewritten, so comments, indentation and key order everywhere
+/// else stay as they were.
+///
+/// Paths are dotted (`scripts.build`, `files.0`) or, to reach keys with
+/// dots in them, JSON Pointers (`/compilerOptions/paths/@app~1*`).
+///
+/// ```
+/// use json_parser::ConfigEditor;
+///
+/// let mut pkg = ConfigEditor::new("{\n  // build steps\n  \"scripts\": {}\n}\n")?;
+/// pkg.set_field_preserving("scripts.build", "tsc")?;
+/// assert_eq!(
+///     pkg.as_str(),
+///     "{\n  // build steps\n  \"scripts\": {\n    \"build\": \"tsc\"\n  }\n}\n"
+/// );
+/// # Ok::<(), eyre::Report>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct ConfigEditor {
+    text: String,
+    // one level of indentation, or `None` for a file without any
+    unit: Option<String>,
+    newline: &'static str,
+}
+
+// Where an entry of an array or object starts (at its key, for objects),
+// and its value.
+struct Item<'d> {
+    start: usize,
+    value: Node<'d>,
+}
+
+fn items<'d>(container: &Node<'d>) -> Vec<Item<'d>> {
+    match container.kind() {
+        NodeKind::Object => container
+            .key_spans()
+            .map(|(key, value)| Item {
+                start: key.start,
+                value,
+            })
+            .collect(),
+        _ => container
+            .elements()
+            .map(|value| Item {
+                start: value.span().start,
+                value,
+            })
+            .collect(),
+    }
+}
+
+fn segments(path: &str) -> eyre::Result<Vec<String>> {
+    if path.is_empty() || path.starts_with('/') {
+        return pointer::tokens(path);
+    }
+    Ok(path.split('.').map(str::to_string).collect())
+}
+
+// The nodes along `segments` from the root, as far as they exist.
+fn walk<'d>(doc: &'d Document, segments: &[String]) -> Vec<Node<'d>> {
+    let mut nodes = vec![doc.root()];
+    for segment in segments {
+        let node = nodes[nodes.len() - 1];
+        let next = match node.kind() {
+            NodeKind::Object => node.get(segment),
+            NodeKind::Array => match pointer::index(segment, node.len()) {
+                Some(i) => node.index(i),
+                None => None,
+            },
+            _ => None,
+        };
+        match next {
+            Some(next) => nodes.push(next),
+            None => break,
+        }
+    }
+    nodes
+}
+
+impl ConfigEditor {
+    /// Fails unless `text` is JSON, with or without comments.
+    pub fn new(text: impl Into<String>) -> eyre::Result<Self> {
+        let text = text.into();
+        parse(&text)?;
+        let unit = text
+            .lines()
+            .skip(1)
+            .map(|line| {
+                let content = line.trim_start_matches([' ', '\t']);
+                &line[..line.len() - content.len()]
+            })
+            .find(|indent| !indent.is_empty())
+            .map(str::to_string);
+        let newline = if text.contains("\r\n") { "\r\n" } else { "\n" };
+        Ok(Self {
+            text,
+            unit,
+            newline,
+        })
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+
+    pub fn into_string(self) -> String {
+        self.text
+    }
+
+    /// The value at `path`, if there is one.
+    pub fn get(&self, path: &str) -> eyre::Result<Option<JSONValue>> {
+        let segments = segments(path)?;
+        let doc = parse(&self.text)?;
+        let nodes = walk(&doc, &segments);
+        Ok((nodes.len() == segments.len() + 1).then(|| nodes[nodes.len() - 1].to_value()))
+    }
+
+    /// Puts `value` at `path`. An existing value is replaced where it
+    /// stands; a new key goes after the last entry of its object, indented
+    /// like it, with any missing objects on the way created. In an array,
+    /// `-` appends.
+    pub fn set_field_preserving(
+        &mut self,
+        path: &str,
+        value: impl Into<JSONValue>,
+    ) -> eyre::Result<()> {
+        let segments = segments(path)?;
+        let doc = parse(&self.text)?;
+        let nodes = walk(&doc, &segments);
+        let node = nodes[nodes.len() - 1];
+        let mut value = value.into();
+        let edits = if nodes.len() == segments.len() + 1 {
+            // an array or object written on one line, or anything in a
+            // container written on one line, stays on one line
+            let one_line = |n: &Node<'_>| !self.text[n.span().range()].contains('\n');
+            let inline = one_line(&node)
+                && (!node.is_empty() || nodes.len() > 1 && one_line(&nodes[nodes.len() - 2]));
+            let indent = self.line_indent(node.span().start);
+            vec![(node.span().range(), self.write(&value, &indent, inline))]
+        } else {
+            let segment = &segments[nodes.len() - 1];
+            for key in segments[nodes.len()..].iter().rev() {
+                value = JSONValue::Dict(HashMap::from([(key.clone(), value)]));
+            }
+            let key = match node.kind() {
+                NodeKind::Object => Some(segment.as_str()),
+                NodeKind::Array if pointer::index(segment, node.len()) == Some(node.len()) => None,
+                NodeKind::Array => {
+                    eyre::bail!(format!("No element {} in the array at {:?}", segment, path))
+                }
+                _ => eyre::bail!(format!(
+                    "Expected an object or array to hold {:?} in {:?}",
+                    segment, path
+                )),
+            };
+            self.insert(&node, key, &value)
+        };
+        self.apply(edits)
+    }
+
+    /// Removes the value at `path` along with its key, its comma and a
+    /// comment after it on the same line, and returns it. Does nothing if
+    /// there is no such value.
+    pub fn remove_field_preserving(&mut self, path: &str) -> eyre::Result<Option<JSONValue>> {
+        let segments = segments(path)?;
+        if segments.is_empty() {
+            eyre::bail!("Can't remove the whole document")
+        }
+        let doc = parse(&self.text)?;
+        let nodes = walk(&doc, &segments);
+        if nodes.len() != segments.len() + 1 {
+            return Ok(None);
+        }
+        let node = nodes[nodes.len() - 1];
+        let container = nodes[nodes.len() - 2];
+        let items = items(&container);
+        let i = items
+            .iter()
+            .position(|item| item.value.span() == node.span())
+            .unwrap_or_default();
+        let item = &items[i];
+        let text = self.text.as_str();
+        let edits = if items.len() == 1 {
+            let span = container.span();
+            vec![(span.start + 1..span.end - 1, String::new())]
+        } else if i + 1 < items.len() {
+            // up to and including the comma, and the rest of its line if
+            // nothing else follows on it
+            let comma = skip_trivia(text, node.span().end);
+            let end = self.line_comment_end(skip_blanks(text, comma + 1));
+            let edit = match newline_end(text, end) {
+                Some(end) => (self.line_start(item.start)..end, String::new()),
+                None => (item.start..end, String::new()),
+            };
+            vec![edit]
+        } else {
+            // the comma before it, and the line break and indentation too
+            let comma = skip_trivia(text, items[i - 1].value.span().end);
+            let mut start = text[..item.start].trim_end_matches([' ', '\t']).len();
+            if text[..start].ends_with('\n') {
+                start = text[..start - 1].trim_end_matches('\r').len();
+            }
+            let end = self.line_comment_end(skip_blanks(text, node.span().end));
+            vec![
+                (comma..comma + 1, String::new()),
+                (start..end, String::new()),
+            ]
+        };
+        let removed = node.to_value();
+        self.apply(edits)?;
+        Ok(Some(removed))
+    }
+
+    // Adds `value` as the last entry of `container`, under `key` in an object.
+    fn insert(
+        &self,
+        container: &Node<'_>,
+        key: Option<&str>,
+        value: &JSONValue,
+    ) -> Vec<(Range<usize>, String)> {
+        let text = self.text.as_str();
+        let span = container.span();
+        let entry = |indent: &str, inline: bool| {
+            let value = self.write(value, indent, inline);
+            match key {
+                Some(key) if self.unit.is_some() => {
+                    format!(
+                        "{}: {}",
+                        JSONValue::Str(key.to_string()).to_json_string(),
+                        value
+                    )
+                }
+                Some(key) => format!(
+                    "{}:{}",
+                    JSONValue::Str(key.to_string()).to_json_string(),
+                    value
+                ),
+                None => value,
+            }
+        };
+        let items = items(container);
+        let Some(last) = items.last() else {
+            let inner_end = span.start + 1 + text[span.start + 1..span.end - 1].trim_end().len();
+            let edit = match &self.unit {
+                Some(unit) => {
+                    let outer = self.line_indent(span.start);
+                    let inner = format!("{}{}", outer, unit);
+                    format!(
+                        "{}{}{}{}{}",
+                        self.newline,
+                        inner,
+                        entry(&inner, false),
+                        self.newline,
+                        outer
+                    )
+                }
+                None => entry("", true),
+            };
+            return vec![(inner_end..span.end - 1, edit)];
+        };
+        let end = last.value.span().end;
+        if !text[span.range()].contains('\n') {
+            let separator = if self.unit.is_some() { ", " } else { "," };
+            return vec![(end..end, format!("{}{}", separator, entry("", true)))];
+        }
+        // after a comment trailing the last entry, on a line of its own
+        let indent = self.line_indent(last.start);
+        let at = self.line_comment_end(skip_blanks(text, end));
+        let at = if at == skip_blanks(text, end) {
+            end
+        } else {
+            at
+        };
+        vec![(
+            end..at,
+            format!(
+                ",{}{}{}{}",
+                &text[end..at],
+                self.newline,
+                indent,
+                entry(&indent, false)
+            ),
+        )]
+    }
+
+    // `value` as text for a line indented with `indent`.
+    fn write(&self, value: &JSONValue, indent: &str, inline: bool) -> String {
+        let Some(unit) = self.unit.as_deref().filter(|_| !inline) else {
+            return value.to_json_string();
+        };
+        let width = unit.len();
+        let pretty = value.to_json_string_with(&SerializeOptions {
+            indent: Some(width),
+            sort_keys: true,
+            ..SerializeOptions::default()
+        });
+        let mut out = String::with_capacity(pretty.len());
+        for (i, line) in pretty.split('\n').enumerate() {
+            if i > 0 {
+                out.push_str(self.newline);
+                out.push_str(indent);
+                let content = line.trim_start_matches(' ');
+                out.push_str(&unit.repeat((line.len() - content.len()) / width));
+                out.push_str(content);
+            } else {
+                out.push_str(line);
+            }
+        }
+        out
+    }
+
+    // The whitespace starting the line `pos` is on.
+    fn line_indent(&self, pos: usize) -> String {
+        let start = self.text[..pos].rfind('\n').map_or(0, |i| i + 1);
+        let line = &self.text[start..pos];
+        line[..line.len() - line.trim_start_matches([' ', '\t']).len()].to_string()
+    }
+
+    // The start of the line `pos` is on if only whitespace comes before it
+    // there, else `pos`.
+    fn line_start(&self, pos: usize) -> usize {
+        let start = self.text[..pos].trim_end_matches([' ', '\t']).len();
+        if start == 0 || self.text[..start].ends_with('\n') {
+            start
+        } else {
+            pos
+        }
+    }
+
+    // Past a `//` comment starting at `pos`, up to the line break.
+    fn line_comment_end(&self, pos: usize) -> usize {
+        if !self.text[pos..].starts_with("//") {
+            return pos;
+        }
+        pos + self.text[pos..]
+            .find(['\r', '\n'])
+            .unwrap_or(self.text.len() - pos)
+    }
+
+    // Makes the edits, which mustn't overlap, checking the result parses.
+    fn apply(&mut self, mut edits: Vec<(Range<usize>, String)>) -> eyre::Result<()> {
+        edits.sort_by_key(|(range, _)| std::cmp::Reverse(range.start));
+        let mut text = self.text.clone();
+        for (range, replacement) in edits {
+            text.replace_range(range, &replacement);
+        }
+        parse(&text).map_err(|e| eyre::eyre!(format!("Edit would break the file: {}", e)))?;
+        self.text = text;
+        Ok(())
+    }
+}
+
+fn parse(text: &str) -> eyre::Result<Document> {
+    let options = ParseOptions {
+        allow_comments: true,
+        ..ParseOptions::default()
+    };
+    Document::parse_with_options(text, &options)
+}
+
+// Past spaces and tabs from `pos`.
+fn skip_blanks(text: &str, pos: usize) -> usize {
+    pos + (text[pos..].len() - text[pos..].trim_start_matches([' ', '\t']).len())
+}
+
+// Past whitespace and comments from `pos`.
+fn skip_trivia(text: &str, mut pos: usize) -> usize {
+    loop {
+        let rest = &text[pos..];
+        let trimmed = rest.trim_start();
+        pos += rest.len() - trimmed.len();
+        if trimmed.starts_with("//") {
+            pos += trimmed.find('\n').unwrap_or(trimmed.len());
+        } else if let Some(comment) = trimmed.strip_prefix("/*") {
+            pos += comment.find("*/").map_or(trimmed.len(), |i| i + 4);
+        } else {
+            return pos;
+        }
+    }
+}
+
+// Past the line break at `pos`, if there is one.
+fn newline_end(text: &str, pos: usize) -> Option<usize> {
+    let rest = &text[pos..];
+    rest.strip_prefix("\r\n")
+        .or_else(|| rest.strip_prefix('\n'))
+        .map(|after| pos + rest.len() - after.len())
+}
@@ -0,0 +1,44 @@
+/// Limits on number values, checked by the parser as each number is read
+/// (see `ParseHooks::constrain_numbers`).
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub struct NumberConstraint {
+    integer: bool,
+    min: Option<f64>,
+    max: Option<f64>,
+}
+
+impl NumberConstraint {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Rejects numbers with a fractional part.
+    pub fn integer_only(mut self) -> Self {
+        self.integer = true;
+        self
+    }
+
+    /// Rejects numbers below `min`.
+    pub fn min(mut self, min: f64) -> Self {
+        self.min = Some(min);
+        self
+    }
+
+    /// Rejects numbers above `max`.
+    pub fn max(mut self, max: f64) -> Self {
+        self.max = Some(max);
+        self
+    }
+
+    /// Why `n` breaks the constraint, e.g. "is above the maximum of 10".
+    pub(crate) fn violation(&self, n: f64) -> Option<String> {
+        if self.integer && n.fract() != 0.0 {
+            return Some("is not an integer".to_string());
+        }
+        match (self.min, self.max) {
+            (Some(min), _) if n < min => Some(format!("is below the minimum of {}", min)),
+            (_, Some(max)) if n > max => Some(format!("is above the maximum of {}", max)),
+            _ => None,
+        }
+    }
+}
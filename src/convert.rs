@@ -0,0 +1,68 @@
+use std::collections::HashMap;
+
+use crate::JSONValue;
+
+impl From<bool> for JSONValue {
+    fn from(b: bool) -> Self {
+        JSONValue::Bool(b)
+    }
+}
+
+macro_rules! from_num {
+    ($($t:ty),*) => {
+        $(
+            impl From<$t> for JSONValue {
+                fn from(n: $t) -> Self {
+                    JSONValue::Num(n as f64)
+                }
+            }
+        )*
+    };
+}
+
+from_num!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize, f32, f64);
+
+impl From<&str> for JSONValue {
+    fn from(s: &str) -> Self {
+        JSONValue::Str(s.to_string())
+    }
+}
+
+impl From<String> for JSONValue {
+    fn from(s: String) -> Self {
+        JSONValue::Str(s)
+    }
+}
+
+impl From<()> for JSONValue {
+    fn from(_: ()) -> Self {
+        JSONValue::Null
+    }
+}
+
+impl<T: Into<JSONValue>> From<Option<T>> for JSONValue {
+    fn from(v: Option<T>) -> Self {
+        match v {
+            Some(v) => v.into(),
+            None => JSONValue::Null,
+        }
+    }
+}
+
+impl<T: Into<JSONValue>> From<Vec<T>> for JSONValue {
+    fn from(v: Vec<T>) -> Self {
+        JSONValue::Array(v.into_iter().map(Into::into).collect())
+    }
+}
+
+impl From<HashMap<String, JSONValue>> for JSONValue {
+    fn from(m: HashMap<String, JSONValue>) -> Self {
+        JSONValue::Dict(m)
+    }
+}
+
+impl<T: Into<JSONValue>> FromIterator<T> for JSONValue {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        JSONValue::Array(iter.into_iter().map(Into::into).collect())
+    }
+}
@@ -0,0 +1,463 @@
+use std::collections::{BTreeMap, HashMap};
+
+use eyre::OptionExt;
+
+use crate::{pointer, JSONValue};
+
+/// Orders every write: a Lamport clock reading, ties broken by replica.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+struct Stamp {
+    counter: u64,
+    replica: u64,
+}
+
+impl Stamp {
+    // as text, since JSON numbers can't hold every u64
+    fn to_json(self) -> JSONValue {
+        JSONValue::Str(format!("{}:{}", self.counter, self.replica))
+    }
+
+    fn from_json(v: &JSONValue) -> eyre::Result<Self> {
+        let parsed = match v {
+            JSONValue::Str(s) => s
+                .split_once(':')
+                .and_then(|(c, r)| Some((c.parse().ok()?, r.parse().ok()?))),
+            _ => None,
+        };
+        let (counter, replica) = parsed.ok_or_eyre("Invalid CRDT stamp")?;
+        Ok(Self { counter, replica })
+    }
+}
+
+// A value written at `stamp`, or a tombstone if `node` is `None`.
+#[derive(Debug, Clone, PartialEq)]
+struct Slot {
+    stamp: Stamp,
+    node: Option<Node>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Element {
+    // fixed when the element is inserted, whatever is later written to it
+    id: Stamp,
+    // elements are ordered by position, then id
+    position: Vec<u32>,
+    slot: Slot,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    // null, a boolean, a number or a string
+    Leaf(JSONValue),
+    Object(BTreeMap<String, Slot>),
+    Array(Vec<Element>),
+}
+
+/// A `JSONValue` that replicas can edit independently and merge in any
+/// order, always ending up with the same document: object members keep the
+/// last write (by Lamport clock, then replica id), and array elements have
+/// stable identities and positions, so concurrent inserts all survive.
+/// Removals leave tombstones.
+///
+/// `to_json` and `from_json` carry the whole state, stamps included,
+/// between replicas.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CrdtValue {
+    root: Slot,
+    replica: u64,
+    clock: u64,
+}
+
+impl CrdtValue {
+    /// Starts a document on the replica `replica`, which must be unique
+    /// among the replicas that will be merged.
+    pub fn new(value: &JSONValue, replica: u64) -> Self {
+        let mut crdt = Self {
+            root: Slot {
+                stamp: Stamp {
+                    counter: 0,
+                    replica,
+                },
+                node: None,
+            },
+            replica,
+            clock: 0,
+        };
+        crdt.root = crdt.slot(value);
+        crdt
+    }
+
+    /// A copy of this state to be edited on the replica `replica`.
+    pub fn fork(&self, replica: u64) -> Self {
+        Self {
+            replica,
+            ..self.clone()
+        }
+    }
+
+    pub fn replica(&self) -> u64 {
+        self.replica
+    }
+
+    /// The current document, without the bookkeeping.
+    pub fn to_value(&self) -> JSONValue {
+        self.root
+            .node
+            .as_ref()
+            .map_or(JSONValue::Null, Node::to_value)
+    }
+
+    fn tick(&mut self) -> Stamp {
+        self.clock += 1;
+        Stamp {
+            counter: self.clock,
+            replica: self.replica,
+        }
+    }
+
+    fn slot(&mut self, value: &JSONValue) -> Slot {
+        let stamp = self.tick();
+        let node = self.node(value);
+        Slot {
+            stamp,
+            node: Some(node),
+        }
+    }
+
+    fn node(&mut self, value: &JSONValue) -> Node {
+        match value {
+            JSONValue::Dict(d) => {
+                Node::Object(d.iter().map(|(k, v)| (k.clone(), self.slot(v))).collect())
+            }
+            JSONValue::Array(a) => {
+                let mut position = Vec::new();
+                let mut elements = Vec::with_capacity(a.len());
+                for v in a {
+                    position = between(&position, None);
+                    elements.push(Element {
+                        id: self.tick(),
+                        position: position.clone(),
+                        slot: self.slot(v),
+                    });
+                }
+                Node::Array(elements)
+            }
+            v => Node::Leaf(v.clone()),
+        }
+    }
+
+    /// Puts `value` at the JSON Pointer `ptr`, replacing what was there. In
+    /// an array, `-` appends.
+    pub fn set(&mut self, ptr: &str, value: &JSONValue) -> eyre::Result<()> {
+        self.write(ptr, value, false)
+    }
+
+    /// Like `set`, but in an array inserts before the element at `ptr`.
+    pub fn insert(&mut self, ptr: &str, value: &JSONValue) -> eyre::Result<()> {
+        self.write(ptr, value, true)
+    }
+
+    fn write(&mut self, ptr: &str, value: &JSONValue, insert: bool) -> eyre::Result<()> {
+        let slot = self.slot(value);
+        let (parent, last) = pointer::split(ptr)?;
+        let Some(last) = last else {
+            self.root = slot;
+            return Ok(());
+        };
+        let id = self.tick();
+        match find(&mut self.root, &parent)? {
+            Node::Object(d) => {
+                d.insert(last, slot);
+            }
+            Node::Array(elements) => {
+                let live = live(elements);
+                let i = pointer::index(&last, live.len())
+                    .filter(|i| *i <= live.len())
+                    .ok_or_else(|| {
+                        eyre::eyre!(format!("Index {:?} out of range at {:?}", last, parent))
+                    })?;
+                if !insert && i < live.len() {
+                    elements[live[i]].slot = slot;
+                    return Ok(());
+                }
+                let after = i
+                    .checked_sub(1)
+                    .map(|p| elements[live[p]].position.as_slice());
+                let before = live.get(i).map(|e| elements[*e].position.as_slice());
+                let position = between(after.unwrap_or_default(), before);
+                elements.push(Element { id, position, slot });
+                sort(elements);
+            }
+            Node::Leaf(_) => eyre::bail!(format!("{:?} is not an array or object", parent)),
+        }
+        Ok(())
+    }
+
+    /// Removes the value at `ptr`.
+    pub fn remove(&mut self, ptr: &str) -> eyre::Result<()> {
+        let stamp = self.tick();
+        let (parent, last) = pointer::split(ptr)?;
+        let last = last.ok_or_eyre("Can't remove the whole document")?;
+        let slot = match find(&mut self.root, &parent)? {
+            Node::Object(d) => d.get_mut(&last).filter(|s| s.node.is_some()),
+            Node::Array(elements) => {
+                let live = live(elements);
+                let i = pointer::index(&last, live.len()).filter(|i| *i < live.len());
+                i.map(|i| &mut elements[live[i]].slot)
+            }
+            Node::Leaf(_) => None,
+        };
+        let slot = slot.ok_or_else(|| eyre::eyre!(format!("No value at {:?}", ptr)))?;
+        *slot = Slot { stamp, node: None };
+        Ok(())
+    }
+
+    /// Folds in `other`'s edits. Merging is commutative, associative and
+    /// idempotent, so replicas that have merged the same edits agree.
+    pub fn merge(&mut self, other: &CrdtValue) {
+        self.clock = self.clock.max(other.clock);
+        merge_slot(&mut self.root, &other.root);
+    }
+
+    /// The full state, for sending to another replica.
+    pub fn to_json(&self) -> JSONValue {
+        let mut d = HashMap::new();
+        d.insert(
+            "replica".to_string(),
+            JSONValue::Str(self.replica.to_string()),
+        );
+        d.insert("clock".to_string(), JSONValue::Str(self.clock.to_string()));
+        d.insert("root".to_string(), self.root.to_json());
+        JSONValue::Dict(d)
+    }
+
+    pub fn from_json(state: &JSONValue) -> eyre::Result<Self> {
+        let number = |key: &str| match field(state, key)? {
+            JSONValue::Str(s) => s
+                .parse()
+                .map_err(|_| eyre::eyre!(format!("Invalid CRDT `{}`", key))),
+            _ => eyre::bail!(format!("Invalid CRDT `{}`", key)),
+        };
+        Ok(Self {
+            replica: number("replica")?,
+            clock: number("clock")?,
+            root: Slot::from_json(field(state, "root")?)?,
+        })
+    }
+}
+
+impl Node {
+    fn to_value(&self) -> JSONValue {
+        match self {
+            Node::Leaf(v) => v.clone(),
+            Node::Object(d) => JSONValue::Dict(
+                d.iter()
+                    .filter_map(|(k, s)| Some((k.clone(), s.node.as_ref()?.to_value())))
+                    .collect(),
+            ),
+            Node::Array(elements) => JSONValue::Array(
+                elements
+                    .iter()
+                    .filter_map(|e| Some(e.slot.node.as_ref()?.to_value()))
+                    .collect(),
+            ),
+        }
+    }
+
+    fn to_json(&self) -> JSONValue {
+        let (tag, v) = match self {
+            Node::Leaf(v) => ("value", v.clone()),
+            Node::Object(d) => (
+                "object",
+                JSONValue::Dict(d.iter().map(|(k, s)| (k.clone(), s.to_json())).collect()),
+            ),
+            Node::Array(elements) => (
+                "array",
+                JSONValue::Array(
+                    elements
+                        .iter()
+                        .map(|e| {
+                            let position = e
+                                .position
+                                .iter()
+                                .map(|p| JSONValue::Num(*p as f64))
+                                .collect();
+                            let mut d = HashMap::new();
+                            d.insert("id".to_string(), e.id.to_json());
+                            d.insert("position".to_string(), JSONValue::Array(position));
+                            d.insert("slot".to_string(), e.slot.to_json());
+                            JSONValue::Dict(d)
+                        })
+                        .collect(),
+                ),
+            ),
+        };
+        JSONValue::Dict(HashMap::from([(tag.to_string(), v)]))
+    }
+
+    fn from_json(v: &JSONValue) -> eyre::Result<Self> {
+        let JSONValue::Dict(d) = v else {
+            eyre::bail!("Invalid CRDT node")
+        };
+        let node = match (d.len(), d.iter().next()) {
+            (1, Some((tag, JSONValue::Array(_) | JSONValue::Dict(_)))) if tag == "value" => {
+                eyre::bail!("Invalid CRDT node")
+            }
+            (1, Some((tag, v))) if tag == "value" => Node::Leaf(v.clone()),
+            (1, Some((tag, JSONValue::Dict(d)))) if tag == "object" => Node::Object(
+                d.iter()
+                    .map(|(k, s)| Ok((k.clone(), Slot::from_json(s)?)))
+                    .collect::<eyre::Result<_>>()?,
+            ),
+            (1, Some((tag, JSONValue::Array(a)))) if tag == "array" => {
+                let mut elements = a
+                    .iter()
+                    .map(|e| {
+                        let JSONValue::Array(position) = field(e, "position")? else {
+                            eyre::bail!("Invalid CRDT position")
+                        };
+                        let position = position
+                            .iter()
+                            .map(|p| match p {
+                                JSONValue::Num(n)
+                                    if n.fract() == 0.0 && (0.0..=u32::MAX as f64).contains(n) =>
+                                {
+                                    Ok(*n as u32)
+                                }
+                                _ => eyre::bail!("Invalid CRDT position"),
+                            })
+                            .collect::<eyre::Result<_>>()?;
+                        Ok(Element {
+                            id: Stamp::from_json(field(e, "id")?)?,
+                            position,
+                            slot: Slot::from_json(field(e, "slot")?)?,
+                        })
+                    })
+                    .collect::<eyre::Result<Vec<_>>>()?;
+                sort(&mut elements);
+                Node::Array(elements)
+            }
+            _ => eyre::bail!("Invalid CRDT node"),
+        };
+        Ok(node)
+    }
+}
+
+impl Slot {
+    fn to_json(&self) -> JSONValue {
+        let mut d = HashMap::new();
+        d.insert("stamp".to_string(), self.stamp.to_json());
+        if let Some(node) = &self.node {
+            d.insert("node".to_string(), node.to_json());
+        }
+        JSONValue::Dict(d)
+    }
+
+    fn from_json(v: &JSONValue) -> eyre::Result<Self> {
+        let node = match v {
+            JSONValue::Dict(d) => d.get("node").map(Node::from_json).transpose()?,
+            _ => None,
+        };
+        Ok(Self {
+            stamp: Stamp::from_json(field(v, "stamp")?)?,
+            node,
+        })
+    }
+}
+
+fn field<'v>(v: &'v JSONValue, key: &str) -> eyre::Result<&'v JSONValue> {
+    match v {
+        JSONValue::Dict(d) => d.get(key),
+        _ => None,
+    }
+    .ok_or_else(|| eyre::eyre!(format!("CRDT state is missing `{}`", key)))
+}
+
+fn merge_slot(mine: &mut Slot, theirs: &Slot) {
+    if theirs.stamp > mine.stamp {
+        *mine = theirs.clone();
+        return;
+    }
+    if theirs.stamp < mine.stamp {
+        return;
+    }
+    // the same write, maybe edited further inside on either side
+    match (&mut mine.node, &theirs.node) {
+        (Some(Node::Object(mine)), Some(Node::Object(theirs))) => {
+            for (k, s) in theirs {
+                match mine.get_mut(k) {
+                    Some(m) => merge_slot(m, s),
+                    None => {
+                        mine.insert(k.clone(), s.clone());
+                    }
+                }
+            }
+        }
+        (Some(Node::Array(mine)), Some(Node::Array(theirs))) => {
+            let index: HashMap<Stamp, usize> =
+                mine.iter().enumerate().map(|(i, e)| (e.id, i)).collect();
+            for e in theirs {
+                match index.get(&e.id) {
+                    Some(i) => merge_slot(&mut mine[*i].slot, &e.slot),
+                    None => mine.push(e.clone()),
+                }
+            }
+            sort(mine);
+        }
+        _ => {}
+    }
+}
+
+// The container at `ptr`, which must exist.
+fn find<'s>(slot: &'s mut Slot, ptr: &str) -> eyre::Result<&'s mut Node> {
+    let missing = || eyre::eyre!(format!("No value at {:?}", ptr));
+    let mut node = slot.node.as_mut().ok_or_else(missing)?;
+    for token in pointer::tokens(ptr)? {
+        let slot = match node {
+            Node::Object(d) => d.get_mut(&token),
+            Node::Array(elements) => {
+                let live = live(elements);
+                let i = pointer::index(&token, live.len()).and_then(|i| live.get(i));
+                i.map(|i| &mut elements[*i].slot)
+            }
+            Node::Leaf(_) => None,
+        };
+        node = slot.and_then(|s| s.node.as_mut()).ok_or_else(missing)?;
+    }
+    Ok(node)
+}
+
+// Indexes of the elements not removed.
+fn live(elements: &[Element]) -> Vec<usize> {
+    (0..elements.len())
+        .filter(|i| elements[*i].slot.node.is_some())
+        .collect()
+}
+
+fn sort(elements: &mut [Element]) {
+    elements.sort_by(|a, b| (&a.position, a.id).cmp(&(&b.position, b.id)));
+}
+
+// A position after `low` and before `high` (or anywhere after `low`),
+// ending in a non-zero digit so there is always room before it.
+fn between(low: &[u32], high: Option<&[u32]>) -> Vec<u32> {
+    let mut out = Vec::new();
+    let mut bounded = high.is_some();
+    for i in 0.. {
+        let l = low.get(i).copied().unwrap_or(0) as u64;
+        let h = match high {
+            Some(high) if bounded => high.get(i).copied().unwrap_or(0) as u64,
+            _ => 1 << 32,
+        };
+        if h > l + 1 {
+            out.push(((l + h) / 2) as u32);
+            break;
+        }
+        out.push(l as u32);
+        if h == l + 1 {
+            // anything after this digit is below `high`
+            bounded = false;
+        }
+    }
+    out
+}
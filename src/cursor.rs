@@ -0,0 +1,215 @@
+use crate::{pointer, JSONValue};
+
+#[derive(Debug, Clone, PartialEq)]
+enum Step {
+    Key(String),
+    Index(usize),
+}
+
+/// A movable focus inside a value, for tools that walk and rewrite a tree
+/// step by step. Object entries are visited in key order.
+///
+/// Moves return `false`, leaving the focus where it was, when there is
+/// nowhere to go.
+pub struct Cursor<'v> {
+    root: &'v mut JSONValue,
+    path: Vec<Step>,
+}
+
+impl JSONValue {
+    /// A cursor focused on this value.
+    pub fn cursor(&mut self) -> Cursor<'_> {
+        Cursor {
+            root: self,
+            path: Vec::new(),
+        }
+    }
+}
+
+fn child<'a>(v: &'a JSONValue, step: &Step) -> Option<&'a JSONValue> {
+    match (v, step) {
+        (JSONValue::Dict(d), Step::Key(k)) => d.get(k),
+        (JSONValue::Array(a), Step::Index(i)) => a.get(*i),
+        _ => None,
+    }
+}
+
+fn child_mut<'a>(v: &'a mut JSONValue, step: &Step) -> Option<&'a mut JSONValue> {
+    match (v, step) {
+        (JSONValue::Dict(d), Step::Key(k)) => d.get_mut(k),
+        (JSONValue::Array(a), Step::Index(i)) => a.get_mut(*i),
+        _ => None,
+    }
+}
+
+fn sorted_keys(v: &JSONValue) -> Vec<&String> {
+    let mut keys: Vec<_> = match v {
+        JSONValue::Dict(d) => d.keys().collect(),
+        _ => Vec::new(),
+    };
+    keys.sort_unstable();
+    keys
+}
+
+impl<'v> Cursor<'v> {
+    pub fn focus(&self) -> &JSONValue {
+        self.at(self.path.len())
+    }
+
+    pub fn focus_mut(&mut self) -> &mut JSONValue {
+        let mut v = &mut *self.root;
+        for step in &self.path {
+            v = child_mut(v, step).expect("cursor path stays valid");
+        }
+        v
+    }
+
+    // The value `depth` steps down the path.
+    fn at(&self, depth: usize) -> &JSONValue {
+        let mut v = &*self.root;
+        for step in &self.path[..depth] {
+            v = child(v, step).expect("cursor path stays valid");
+        }
+        v
+    }
+
+    fn parent(&self) -> Option<&JSONValue> {
+        let depth = self.path.len().checked_sub(1)?;
+        Some(self.at(depth))
+    }
+
+    /// JSON Pointer of the focus.
+    pub fn pointer(&self) -> String {
+        let mut ptr = String::new();
+        for step in &self.path {
+            match step {
+                Step::Key(k) => pointer::push(&mut ptr, k),
+                Step::Index(i) => pointer::push(&mut ptr, &i.to_string()),
+            }
+        }
+        ptr
+    }
+
+    /// 0 at the root.
+    pub fn depth(&self) -> usize {
+        self.path.len()
+    }
+
+    /// The key of the focus, if its parent is an object.
+    pub fn key(&self) -> Option<&str> {
+        match self.path.last()? {
+            Step::Key(k) => Some(k),
+            Step::Index(_) => None,
+        }
+    }
+
+    /// The index of the focus, if its parent is an array.
+    pub fn index(&self) -> Option<usize> {
+        match self.path.last()? {
+            Step::Index(i) => Some(*i),
+            Step::Key(_) => None,
+        }
+    }
+
+    /// Moves to the first element or entry of the focus.
+    pub fn down(&mut self) -> bool {
+        let step = match self.focus() {
+            JSONValue::Array(a) if !a.is_empty() => Step::Index(0),
+            v @ JSONValue::Dict(_) => match sorted_keys(v).first() {
+                Some(k) => Step::Key(k.to_string()),
+                None => return false,
+            },
+            _ => return false,
+        };
+        self.path.push(step);
+        true
+    }
+
+    /// Moves to the entry `key` of the focused object.
+    pub fn down_key(&mut self, key: &str) -> bool {
+        let step = Step::Key(key.to_string());
+        if child(self.focus(), &step).is_none() {
+            return false;
+        }
+        self.path.push(step);
+        true
+    }
+
+    /// Moves to element `i` of the focused array.
+    pub fn down_index(&mut self, i: usize) -> bool {
+        let step = Step::Index(i);
+        if child(self.focus(), &step).is_none() {
+            return false;
+        }
+        self.path.push(step);
+        true
+    }
+
+    pub fn up(&mut self) -> bool {
+        self.path.pop().is_some()
+    }
+
+    pub fn next_sibling(&mut self) -> bool {
+        self.sibling(true)
+    }
+
+    pub fn prev_sibling(&mut self) -> bool {
+        self.sibling(false)
+    }
+
+    fn sibling(&mut self, forward: bool) -> bool {
+        let (Some(parent), Some(step)) = (self.parent(), self.path.last()) else {
+            return false;
+        };
+        let next = match (parent, step) {
+            (JSONValue::Array(a), Step::Index(i)) => {
+                let j = if forward {
+                    i.checked_add(1)
+                } else {
+                    i.checked_sub(1)
+                };
+                j.filter(|j| *j < a.len()).map(Step::Index)
+            }
+            (JSONValue::Dict(_), Step::Key(k)) => {
+                let keys = sorted_keys(parent);
+                let pos = keys.binary_search(&k).unwrap_or_else(|p| p);
+                let j = if forward {
+                    pos.checked_add(1)
+                } else {
+                    pos.checked_sub(1)
+                };
+                j.and_then(|j| keys.get(j))
+                    .map(|k| Step::Key(k.to_string()))
+            }
+            _ => None,
+        };
+        match next {
+            Some(next) => {
+                *self.path.last_mut().expect("checked above") = next;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Replaces the focus, returning the old value.
+    pub fn replace(&mut self, value: JSONValue) -> JSONValue {
+        std::mem::replace(self.focus_mut(), value)
+    }
+
+    /// Removes the focus from its parent and moves up to the parent. Returns
+    /// `None` at the root, which can't be removed.
+    pub fn remove(&mut self) -> Option<JSONValue> {
+        let step = self.path.pop()?;
+        match (self.focus_mut(), step) {
+            (JSONValue::Dict(d), Step::Key(k)) => d.remove(&k),
+            (JSONValue::Array(a), Step::Index(i)) => Some(a.remove(i)),
+            _ => None,
+        }
+    }
+
+    /// Gives back the root once editing is done.
+    pub fn into_root(self) -> &'v mut JSONValue {
+        self.root
+    }
+}
@@ -0,0 +1,318 @@
+use core::fmt;
+use std::collections::HashMap;
+
+use crate::{JSONValue, ParseHooks};
+
+/// Key used when tagging RFC 3339 strings, as in MongoDB Extended JSON.
+pub const DATE_TAG: &str = "$date";
+
+/// A timestamp parsed from an RFC 3339 string, e.g. `2024-01-31T12:00:00.5+02:00`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Rfc3339 {
+    pub year: u16,
+    pub month: u8,
+    pub day: u8,
+    pub hour: u8,
+    pub minute: u8,
+    pub second: u8,
+    pub nanosecond: u32,
+    /// Offset from UTC in minutes, `0` for `Z`.
+    pub offset_minutes: i16,
+}
+
+impl Rfc3339 {
+    pub fn parse(s: &str) -> Option<Self> {
+        let b = s.as_bytes();
+        if b.len() < 20 {
+            return None;
+        }
+        let num = |i: usize, n: usize| -> Option<u32> {
+            let digits = b.get(i..i + n)?;
+            digits.iter().try_fold(0u32, |acc, d| {
+                d.is_ascii_digit().then(|| acc * 10 + (d - b'0') as u32)
+            })
+        };
+        if b[4] != b'-' || b[7] != b'-' || b[13] != b':' || b[16] != b':' {
+            return None;
+        }
+        if !matches!(b[10], b'T' | b't' | b' ') {
+            return None;
+        }
+        let year = num(0, 4)? as u16;
+        let month = num(5, 2)? as u8;
+        let day = num(8, 2)? as u8;
+        let hour = num(11, 2)? as u8;
+        let minute = num(14, 2)? as u8;
+        let second = num(17, 2)? as u8;
+        // fractional seconds
+        let mut i = 19;
+        let mut nanosecond = 0;
+        if b[i] == b'.' {
+            let start = i + 1;
+            i = start;
+            while i < b.len() && b[i].is_ascii_digit() {
+                if i - start < 9 {
+                    nanosecond = nanosecond * 10 + (b[i] - b'0') as u32;
+                }
+                i += 1;
+            }
+            if i == start {
+                return None;
+            }
+            for _ in (i - start)..9 {
+                nanosecond *= 10;
+            }
+        }
+        // offset
+        let offset_minutes = match b.get(i)? {
+            b'Z' | b'z' if i + 1 == b.len() => 0,
+            sign @ (b'+' | b'-') if i + 6 == b.len() && b[i + 3] == b':' => {
+                let (h, m) = (num(i + 1, 2)?, num(i + 4, 2)?);
+                if h > 23 || m > 59 {
+                    return None;
+                }
+                let off = (h * 60 + m) as i16;
+                if *sign == b'-' {
+                    -off
+                } else {
+                    off
+                }
+            }
+            _ => return None,
+        };
+        if !(1..=12).contains(&month)
+            || day == 0
+            || day > days_in_month(year, month)
+            || hour > 23
+            || minute > 59
+            || second > 60
+        {
+            return None;
+        }
+        Some(Self {
+            year,
+            month,
+            day,
+            hour,
+            minute,
+            second,
+            nanosecond,
+            offset_minutes,
+        })
+    }
+
+    /// Seconds since the Unix epoch; a leap second is folded into the next one.
+    pub fn unix_timestamp(&self) -> i64 {
+        let days = days_from_civil(self.year as i64, self.month as i64, self.day as i64);
+        days * 86_400 + self.hour as i64 * 3600 + self.minute as i64 * 60 + self.second as i64
+            - self.offset_minutes as i64 * 60
+    }
+
+    /// The instant `secs` seconds after the Unix epoch, in UTC. Fails outside
+    /// the years 0000 to 9999, which RFC 3339 can't write, or if `nanosecond`
+    /// isn't below a second.
+    pub fn from_unix_timestamp(secs: i64, nanosecond: u32) -> eyre::Result<Self> {
+        if nanosecond >= 1_000_000_000 {
+            eyre::bail!(format!("Nanosecond {} is a second or more", nanosecond))
+        }
+        let days = secs.div_euclid(86_400);
+        let rem = secs.rem_euclid(86_400);
+        let (year, month, day) = civil_from_days(days);
+        if !(0..=9999).contains(&year) {
+            eyre::bail!(format!(
+                "Timestamp {} is outside the years 0000 to 9999",
+                secs
+            ))
+        }
+        Ok(Self {
+            year: year as u16,
+            month: month as u8,
+            day: day as u8,
+            hour: (rem / 3600) as u8,
+            minute: (rem % 3600 / 60) as u8,
+            second: (rem % 60) as u8,
+            nanosecond,
+            offset_minutes: 0,
+        })
+    }
+
+    /// The same instant expressed in UTC. Fails if that falls outside the
+    /// years 0000 to 9999, e.g. for `0000-01-01T00:00:00+01:00`.
+    pub fn to_utc(&self) -> eyre::Result<Self> {
+        Self::from_unix_timestamp(self.unix_timestamp(), self.nanosecond)
+    }
+}
+
+impl fmt::Display for Rfc3339 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+            self.year, self.month, self.day, self.hour, self.minute, self.second
+        )?;
+        if self.nanosecond > 0 {
+            let frac = format!("{:09}", self.nanosecond);
+            write!(f, ".{}", frac.trim_end_matches('0'))?;
+        }
+        if self.offset_minutes == 0 {
+            return write!(f, "Z");
+        }
+        let sign = if self.offset_minutes < 0 { '-' } else { '+' };
+        let off = self.offset_minutes.unsigned_abs();
+        write!(f, "{}{:02}:{:02}", sign, off / 60, off % 60)
+    }
+}
+
+fn is_leap_year(year: u16) -> bool {
+    year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400))
+}
+
+fn days_in_month(year: u16, month: u8) -> u8 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+// Howard Hinnant's days_from_civil / civil_from_days
+fn days_from_civil(y: i64, m: i64, d: i64) -> i64 {
+    let y = if m <= 2 { y - 1 } else { y };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let doy = (153 * (m + if m > 2 { -3 } else { 9 }) + 2) / 5 + d - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+fn civil_from_days(z: i64) -> (i64, i64, i64) {
+    let z = z + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = doy - (153 * mp + 2) / 5 + 1;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 };
+    (yoe + era * 400 + if m <= 2 { 1 } else { 0 }, m, d)
+}
+
+impl JSONValue {
+    /// Parses a string value (or a `{"$date": ...}` tagged value) as RFC 3339.
+    pub fn as_rfc3339(&self) -> Option<Rfc3339> {
+        match self {
+            JSONValue::Str(s) => Rfc3339::parse(s),
+            JSONValue::Dict(d) if d.len() == 1 => d.get(DATE_TAG)?.as_rfc3339(),
+            _ => None,
+        }
+    }
+
+    /// Whole seconds since the Unix epoch, from either an integral number or
+    /// an RFC 3339 string.
+    pub fn as_unix_timestamp(&self) -> Option<i64> {
+        match self {
+            JSONValue::Num(n) if n.fract() == 0.0 && n.abs() < i64::MAX as f64 => Some(*n as i64),
+            _ => self.as_rfc3339().map(|t| t.unix_timestamp()),
+        }
+    }
+}
+
+impl From<Rfc3339> for JSONValue {
+    fn from(t: Rfc3339) -> Self {
+        JSONValue::Str(t.to_string())
+    }
+}
+
+impl<'h> ParseHooks<'h> {
+    /// Replaces every RFC 3339 string value with `{"$date": "<utc timestamp>"}`
+    /// so timestamps can be told apart from other strings after parsing.
+    /// Timestamps with no UTC form in years 0000 to 9999 stay strings.
+    pub fn tag_rfc3339(self) -> Self {
+        self.on_string(|s| {
+            Ok(match Rfc3339::parse(&s).and_then(|t| t.to_utc().ok()) {
+                Some(t) => {
+                    let mut tagged = HashMap::with_capacity(1);
+                    tagged.insert(DATE_TAG.to_string(), t.into());
+                    JSONValue::Dict(tagged)
+                }
+                None => JSONValue::Str(s),
+            })
+        })
+    }
+}
+
+#[cfg(feature = "chrono")]
+mod chrono_support {
+    use super::Rfc3339;
+    use crate::JSONValue;
+
+    impl<Tz: chrono::TimeZone> From<chrono::DateTime<Tz>> for JSONValue
+    where
+        Tz::Offset: core::fmt::Display,
+    {
+        fn from(t: chrono::DateTime<Tz>) -> Self {
+            JSONValue::Str(t.to_rfc3339())
+        }
+    }
+
+    impl JSONValue {
+        pub fn as_chrono(&self) -> Option<chrono::DateTime<chrono::FixedOffset>> {
+            let t = self.as_rfc3339()?;
+            let offset = chrono::FixedOffset::east_opt(t.offset_minutes as i32 * 60)?;
+            chrono::DateTime::from_timestamp(t.unix_timestamp(), t.nanosecond)
+                .map(|utc| utc.with_timezone(&offset))
+        }
+    }
+
+    impl TryFrom<Rfc3339> for chrono::DateTime<chrono::Utc> {
+        type Error = eyre::Report;
+
+        /// Fails for fields chrono can't represent, e.g. a nanosecond of a
+        /// second or more.
+        fn try_from(t: Rfc3339) -> eyre::Result<Self> {
+            chrono::DateTime::from_timestamp(t.unix_timestamp(), t.nanosecond)
+                .ok_or_else(|| eyre::eyre!(format!("{} is out of chrono's range", t)))
+        }
+    }
+}
+
+#[cfg(feature = "time")]
+mod time_support {
+    use super::Rfc3339;
+    use crate::JSONValue;
+
+    impl TryFrom<time::OffsetDateTime> for JSONValue {
+        type Error = eyre::Report;
+
+        /// Fails outside the years 0000 to 9999, which RFC 3339 can't write.
+        fn try_from(t: time::OffsetDateTime) -> eyre::Result<Self> {
+            let year = u16::try_from(t.year())
+                .ok()
+                .filter(|year| *year <= 9999)
+                .ok_or_else(|| eyre::eyre!(format!("Year {} is outside 0000 to 9999", t.year())))?;
+            let r = Rfc3339 {
+                year,
+                month: t.month() as u8,
+                day: t.day(),
+                hour: t.hour(),
+                minute: t.minute(),
+                second: t.second(),
+                nanosecond: t.nanosecond(),
+                offset_minutes: t.offset().whole_minutes(),
+            };
+            Ok(r.into())
+        }
+    }
+
+    impl JSONValue {
+        pub fn as_offset_datetime(&self) -> Option<time::OffsetDateTime> {
+            let t = self.as_rfc3339()?;
+            let offset = time::UtcOffset::from_whole_seconds(t.offset_minutes as i32 * 60).ok()?;
+            let nanos = t.unix_timestamp() as i128 * 1_000_000_000 + t.nanosecond as i128;
+            time::OffsetDateTime::from_unix_timestamp_nanos(nanos)
+                .ok()
+                .map(|utc| utc.to_offset(offset))
+        }
+    }
+}
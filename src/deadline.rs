@@ -0,0 +1,31 @@
+use core::fmt;
+use std::time::{Duration, Instant};
+
+use crate::{JSONValue, ParseOptions, RecursiveParser, ValuePool};
+
+/// Returned (inside the `eyre::Report`) when `parse_with_deadline` runs out
+/// of time.
+#[derive(Debug)]
+pub struct TimedOut {
+    pub timeout: Duration,
+}
+
+impl fmt::Display for TimedOut {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Parsing took longer than {:?}", self.timeout)
+    }
+}
+
+impl std::error::Error for TimedOut {}
+
+/// Parses `json`, giving up with `TimedOut` once `timeout` has passed, so a
+/// valid but enormous input can't hold up a request handler. The clock is
+/// checked every few hundred tokens, so it may overrun slightly.
+pub fn parse_with_deadline(json: &[u8], timeout: Duration) -> eyre::Result<JSONValue> {
+    let mut pool = ValuePool::new();
+    let options = ParseOptions::default();
+    let mut parser = RecursiveParser::new(json, &mut pool, None, &options);
+    // a timeout too long to represent is no timeout
+    parser.deadline = Instant::now().checked_add(timeout).map(|at| (at, timeout));
+    parser.parse()
+}
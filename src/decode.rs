@@ -0,0 +1,595 @@
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap};
+
+use crate::lexer::{Lexer, SpannedToken, Token, TokenKind, Tokens};
+use crate::syntax::SyntaxState;
+use crate::{escape, FromJsonNumber, JSONValue, ParseOptions};
+
+/// Types that can be decoded straight from the token stream, without
+/// building a `JSONValue` first. Implement it by calling the `Decoder`
+/// method for the expected shape, or derive it with the `derive` feature.
+pub trait FromJson: Sized {
+    fn from_json(de: &mut Decoder) -> eyre::Result<Self>;
+
+    /// The value for a missing object field, if it may be missing. `Option`
+    /// fields default to `None`.
+    fn absent() -> Option<Self> {
+        None
+    }
+
+    /// The object keys this type reads when flattened into a derived struct,
+    /// which the struct's other flattened fields then pass over. `None` if it
+    /// reads any key, as a map does.
+    fn flattened_keys() -> Option<Vec<&'static str>> {
+        None
+    }
+}
+
+/// What typed decoding does with an object key the target type has no field
+/// for. A derived type can instead collect such entries into a map field
+/// marked `#[json(unknown)]`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum UnknownFields {
+    /// Skip the value without building it.
+    #[default]
+    Ignore,
+    Error,
+}
+
+/// A field that tells a missing key apart from an explicit `null`, e.g. for
+/// PATCH requests where `null` clears a value and a missing key leaves it.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum Presence<T> {
+    #[default]
+    Absent,
+    Null,
+    Present(T),
+}
+
+impl<T> Presence<T> {
+    pub fn is_absent(&self) -> bool {
+        matches!(self, Self::Absent)
+    }
+
+    pub fn is_null(&self) -> bool {
+        matches!(self, Self::Null)
+    }
+
+    pub fn as_ref(&self) -> Presence<&T> {
+        match self {
+            Self::Absent => Presence::Absent,
+            Self::Null => Presence::Null,
+            Self::Present(v) => Presence::Present(v),
+        }
+    }
+
+    /// The value, losing the difference between absent and `null`.
+    pub fn into_option(self) -> Option<T> {
+        match self {
+            Self::Present(v) => Some(v),
+            Self::Absent | Self::Null => None,
+        }
+    }
+}
+
+/// Decodes one document into `T`.
+pub fn decode<T: FromJson>(json: &[u8]) -> eyre::Result<T> {
+    decode_with_options(json, &ParseOptions::default())
+}
+
+pub fn decode_with_options<T: FromJson>(json: &[u8], options: &ParseOptions) -> eyre::Result<T> {
+    if let Some(max_size) = options.max_size {
+        if json.len() > max_size {
+            eyre::bail!(format!(
+                "Input of {} bytes exceeds maximum size of {}",
+                json.len(),
+                max_size
+            ))
+        }
+    }
+    let mut de = Decoder::new(json, options);
+    let value = T::from_json(&mut de)?;
+    if let Some(t) = de.next()? {
+        eyre::bail!(format!(
+            "Invalid JSON contains extra content at byte {}",
+            t.span.start
+        ))
+    }
+    Ok(value)
+}
+
+/// Parses a document that must be an object into its entries, for callers
+/// that would otherwise match on `JSONValue::Dict`.
+pub fn parse_object(json: &[u8]) -> eyre::Result<BTreeMap<String, JSONValue>> {
+    decode(json)
+}
+
+/// Parses a document that must be an array, decoding each element into `T`.
+pub fn parse_array_of<T: FromJson>(json: &[u8]) -> eyre::Result<Vec<T>> {
+    decode(json)
+}
+
+/// Reads a document token by token for `FromJson` implementations. Every
+/// token is checked against the grammar, including those skipped.
+pub struct Decoder<'b> {
+    buf: &'b [u8],
+    tokens: Tokens<'b>,
+    peeked: Option<SpannedToken>,
+    // tokens handed out so far, to catch implementations that consume none
+    taken: usize,
+    // where the innermost object key being decoded starts
+    key_start: usize,
+    syntax: SyntaxState,
+    options: &'b ParseOptions,
+    // keys of the top-level object `object` passes over, and where that
+    // object starts, for `replay_without`
+    hidden: Option<(usize, Vec<String>)>,
+    // whether that object's unknown keys are skipped whatever the options
+    // say, for `replay_without_unknown`
+    skip_unknown: bool,
+}
+
+impl<'b> Decoder<'b> {
+    fn new(buf: &'b [u8], options: &'b ParseOptions) -> Self {
+        Self {
+            buf,
+            tokens: Lexer::with_options(options).tokens(buf),
+            peeked: None,
+            taken: 0,
+            key_start: 0,
+            syntax: SyntaxState::new(),
+            options,
+            hidden: None,
+            skip_unknown: false,
+        }
+    }
+
+    fn next(&mut self) -> eyre::Result<Option<SpannedToken>> {
+        let t = match self.peeked.take() {
+            Some(t) => Some(t),
+            None => self.pull()?,
+        };
+        self.taken += t.is_some() as usize;
+        Ok(t)
+    }
+
+    fn pull(&mut self) -> eyre::Result<Option<SpannedToken>> {
+        loop {
+            let Some(t) = self.tokens.next().transpose()? else {
+                return Ok(None);
+            };
+            if t.token == Token::Comment {
+                continue;
+            }
+            self.syntax.feed(t.token).map_err(|e| {
+                eyre::eyre!(format!("Syntax error at byte {}: {}", t.span.start, e))
+            })?;
+            if matches!(t.token, Token::LeftBrace | Token::LeftBracket) {
+                if let Some(max_depth) = self.options.max_depth {
+                    if self.syntax.stack.len() > max_depth {
+                        eyre::bail!(format!("Nesting exceeds maximum depth of {}", max_depth))
+                    }
+                }
+            }
+            return Ok(Some(t));
+        }
+    }
+
+    /// Byte offset of the next token, for error messages.
+    pub fn position(&mut self) -> usize {
+        match self.peek_token() {
+            Ok(Some(t)) => t.span.start,
+            _ => self.tokens.position(),
+        }
+    }
+
+    fn peek_token(&mut self) -> eyre::Result<Option<SpannedToken>> {
+        if self.peeked.is_none() {
+            self.peeked = self.pull()?;
+        }
+        Ok(self.peeked)
+    }
+
+    /// The kind of the next token, or `None` at the end of the input.
+    pub fn peek(&mut self) -> eyre::Result<Option<TokenKind>> {
+        Ok(self.peek_token()?.map(|t| t.kind()))
+    }
+
+    fn expect(&mut self, what: &str) -> eyre::Result<SpannedToken> {
+        self.next()?
+            .ok_or_else(|| eyre::eyre!(format!("Expected {}, found end of input", what)))
+    }
+
+    fn mismatch<T>(&self, what: &str, t: SpannedToken) -> eyre::Result<T> {
+        eyre::bail!(format!(
+            "Expected {} at byte {}, found {:?}",
+            what,
+            t.span.start,
+            t.kind()
+        ))
+    }
+
+    /// Decodes the next value as `T`.
+    pub fn decode<T: FromJson>(&mut self) -> eyre::Result<T> {
+        T::from_json(self)
+    }
+
+    pub fn null(&mut self) -> eyre::Result<()> {
+        let t = self.expect("null")?;
+        match t.token {
+            Token::NullVal => Ok(()),
+            _ => self.mismatch("null", t),
+        }
+    }
+
+    /// Consumes a `null` if that is what comes next.
+    pub fn take_null(&mut self) -> eyre::Result<bool> {
+        if self.peek()? == Some(TokenKind::Null) {
+            self.next()?;
+            return Ok(true);
+        }
+        Ok(false)
+    }
+
+    pub fn bool(&mut self) -> eyre::Result<bool> {
+        let t = self.expect("boolean")?;
+        match t.token {
+            Token::BoolVal(b) => Ok(b),
+            _ => self.mismatch("boolean", t),
+        }
+    }
+
+    /// A number, converted from its text as written.
+    pub fn number<N: FromJsonNumber>(&mut self) -> eyre::Result<N> {
+        let t = self.expect("number")?;
+        let Token::NumVal(n) = t.token else {
+            return self.mismatch("number", t);
+        };
+        let lexeme = std::str::from_utf8(&self.buf[t.span.range()])?;
+        N::from_json_number(lexeme, n)
+            .map_err(|e| eyre::eyre!(format!("{} at byte {}", e, t.span.start)))
+    }
+
+    fn decode_str(&self, i: usize, j: usize) -> eyre::Result<Cow<'b, str>> {
+        let raw = &self.buf[i..j];
+        if raw.contains(&b'\\') {
+            escape::unescape_str(raw, self.options.lone_surrogates, String::new()).map(Cow::Owned)
+        } else {
+            Ok(Cow::Borrowed(std::str::from_utf8(raw)?))
+        }
+    }
+
+    /// A string, borrowed from the input unless it contains escapes.
+    pub fn str(&mut self) -> eyre::Result<Cow<'b, str>> {
+        let t = self.expect("string")?;
+        match t.token {
+            Token::StringVal(i, j) => self.decode_str(i, j),
+            _ => self.mismatch("string", t),
+        }
+    }
+
+    /// Calls `f` to decode each element of an array.
+    pub fn array(&mut self, mut f: impl FnMut(&mut Self) -> eyre::Result<()>) -> eyre::Result<()> {
+        let t = self.expect("array")?;
+        if t.token != Token::LeftBracket {
+            return self.mismatch("array", t);
+        }
+        loop {
+            if self.peek()? == Some(TokenKind::RightBracket) {
+                self.next()?;
+                return Ok(());
+            }
+            self.each(&mut f)?;
+            if self.peek()? == Some(TokenKind::Comma) {
+                self.next()?;
+            }
+        }
+    }
+
+    /// Calls `f` with each key of an object; it must decode or `skip` the
+    /// value that follows.
+    pub fn object(
+        &mut self,
+        mut f: impl FnMut(&str, &mut Self) -> eyre::Result<()>,
+    ) -> eyre::Result<()> {
+        let t = self.expect("object")?;
+        if t.token != Token::LeftBrace {
+            return self.mismatch("object", t);
+        }
+        loop {
+            let t = self.expect("key")?;
+            let key = match t.token {
+                Token::RightBrace => return Ok(()),
+                Token::StringVal(i, j) => self.decode_str(i, j)?,
+                _ => return self.mismatch("key", t),
+            };
+            self.expect("`:`")?;
+            if self.hides(&key) {
+                self.skip()?;
+            } else {
+                self.each(|de| {
+                    de.key_start = t.span.start;
+                    f(&key, de)
+                })?;
+            }
+            if self.peek()? == Some(TokenKind::Comma) {
+                self.next()?;
+            }
+        }
+    }
+
+    // Runs `f` on one element or entry, which it must consume.
+    fn each(&mut self, f: impl FnOnce(&mut Self) -> eyre::Result<()>) -> eyre::Result<()> {
+        let taken = self.taken;
+        f(self)?;
+        if self.taken == taken {
+            eyre::bail!(format!(
+                "Nothing was decoded or skipped at byte {}",
+                self.position()
+            ))
+        }
+        Ok(())
+    }
+
+    // Nesting depth before the next token (the grammar state is already past
+    // a peeked one).
+    fn depth(&self) -> usize {
+        let len = self.syntax.stack.len();
+        match self.peeked.map(|t| t.token) {
+            Some(Token::LeftBrace | Token::LeftBracket) => len - 1,
+            Some(Token::RightBrace | Token::RightBracket) => len + 1,
+            _ => len,
+        }
+    }
+
+    /// Skips the next value, returning where it starts so `replay` can
+    /// decode it again, e.g. to find an enum's tag before its fields or to
+    /// try each variant of an untagged enum.
+    pub fn skip_for_replay(&mut self) -> eyre::Result<usize> {
+        let start = self.position();
+        self.skip()?;
+        Ok(start)
+    }
+
+    /// A decoder over the value `skip_for_replay` skipped at `start`, with
+    /// the same options. Errors keep byte offsets into the whole input.
+    pub fn replay(&self, start: usize) -> Decoder<'b> {
+        let hidden = self.hidden.clone().filter(|(at, _)| *at == start);
+        Self {
+            tokens: Lexer::with_options(self.options).tokens_from(self.buf, start),
+            skip_unknown: self.skip_unknown && hidden.is_some(),
+            hidden,
+            ..Self::new(self.buf, self.options)
+        }
+    }
+
+    /// Like `replay` for an object, passing over `keys` (and any this
+    /// decoder already hides) as if they weren't there, e.g. for a
+    /// flattened field, which mustn't see the fields around it.
+    pub fn replay_without(&self, start: usize, keys: &[&str]) -> Decoder<'b> {
+        let mut de = self.replay(start);
+        let (_, hidden) = de.hidden.get_or_insert_with(|| (start, Vec::new()));
+        hidden.extend(keys.iter().map(|k| k.to_string()));
+        de
+    }
+
+    /// Like `replay_without`, also skipping the object's other unknown keys
+    /// whatever `ParseOptions::unknown_fields` says, e.g. for a flattened
+    /// field beside a flattened map, which takes them.
+    pub fn replay_without_unknown(&self, start: usize, keys: &[&str]) -> Decoder<'b> {
+        let mut de = self.replay_without(start, keys);
+        de.skip_unknown = true;
+        de
+    }
+
+    // Whether `key` is hidden, in the top-level object.
+    fn hides(&self, key: &str) -> bool {
+        self.syntax.stack.len() == 1
+            && self
+                .hidden
+                .as_ref()
+                .is_some_and(|(_, keys)| keys.iter().any(|k| k == key))
+    }
+
+    /// Skips the next value without decoding it.
+    pub fn skip(&mut self) -> eyre::Result<()> {
+        let depth = self.depth();
+        loop {
+            self.expect("value")?;
+            if self.syntax.stack.len() <= depth {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Handles the value of an object key the caller doesn't know, following
+    /// `ParseOptions::unknown_fields`.
+    pub fn unknown_field(&mut self, key: &str) -> eyre::Result<()> {
+        if self.skip_unknown && self.syntax.stack.len() == 1 {
+            return self.skip();
+        }
+        match self.options.unknown_fields {
+            UnknownFields::Ignore => self.skip(),
+            UnknownFields::Error => eyre::bail!(format!(
+                "Unknown field `{}` at byte {}",
+                key, self.key_start
+            )),
+        }
+    }
+
+    /// Decodes the next value into a `JSONValue`.
+    pub fn value(&mut self) -> eyre::Result<JSONValue> {
+        let Some(t) = self.peek_token()? else {
+            return self.expect("value").map(|_| JSONValue::Null);
+        };
+        Ok(match t.token {
+            Token::LeftBracket => {
+                let mut entries = Vec::new();
+                self.array(|de| {
+                    entries.push(de.value()?);
+                    Ok(())
+                })?;
+                JSONValue::Array(entries)
+            }
+            Token::LeftBrace => {
+                let mut entries = HashMap::new();
+                self.object(|key, de| {
+                    entries.insert(key.to_string(), de.value()?);
+                    Ok(())
+                })?;
+                JSONValue::Dict(entries)
+            }
+            Token::StringVal(..) => JSONValue::Str(self.str()?.into_owned()),
+            Token::NumVal(_) => JSONValue::Num(self.number()?),
+            Token::BoolVal(b) => {
+                self.next()?;
+                JSONValue::Bool(b)
+            }
+            Token::NullVal => {
+                self.next()?;
+                JSONValue::Null
+            }
+            _ => {
+                self.next()?;
+                return self.mismatch("value", t);
+            }
+        })
+    }
+}
+
+impl FromJson for JSONValue {
+    fn from_json(de: &mut Decoder) -> eyre::Result<Self> {
+        de.value()
+    }
+}
+
+impl FromJson for () {
+    fn from_json(de: &mut Decoder) -> eyre::Result<Self> {
+        de.null()
+    }
+}
+
+impl FromJson for bool {
+    fn from_json(de: &mut Decoder) -> eyre::Result<Self> {
+        de.bool()
+    }
+}
+
+impl FromJson for String {
+    fn from_json(de: &mut Decoder) -> eyre::Result<Self> {
+        de.str().map(Cow::into_owned)
+    }
+}
+
+impl FromJson for f64 {
+    fn from_json(de: &mut Decoder) -> eyre::Result<Self> {
+        de.number()
+    }
+}
+
+impl FromJson for f32 {
+    fn from_json(de: &mut Decoder) -> eyre::Result<Self> {
+        de.number::<f64>().map(|n| n as f32)
+    }
+}
+
+macro_rules! from_json_int {
+    ($via:ty: $($t:ty),*) => {
+        $(
+            impl FromJson for $t {
+                fn from_json(de: &mut Decoder) -> eyre::Result<Self> {
+                    let pos = de.position();
+                    let n = de.number::<$via>()?;
+                    <$t>::try_from(n).map_err(|_| {
+                        eyre::eyre!(format!(
+                            "Number {} at byte {} is out of range for {}",
+                            n,
+                            pos,
+                            stringify!($t)
+                        ))
+                    })
+                }
+            }
+        )*
+    };
+}
+
+from_json_int!(i64: i8, i16, i32, isize);
+from_json_int!(u64: u8, u16, u32, usize);
+
+impl FromJson for i64 {
+    fn from_json(de: &mut Decoder) -> eyre::Result<Self> {
+        de.number()
+    }
+}
+
+impl FromJson for u64 {
+    fn from_json(de: &mut Decoder) -> eyre::Result<Self> {
+        de.number()
+    }
+}
+
+impl<T: FromJson> FromJson for Option<T> {
+    fn from_json(de: &mut Decoder) -> eyre::Result<Self> {
+        if de.take_null()? {
+            return Ok(None);
+        }
+        T::from_json(de).map(Some)
+    }
+
+    fn absent() -> Option<Self> {
+        Some(None)
+    }
+}
+
+impl<T: FromJson> FromJson for Presence<T> {
+    fn from_json(de: &mut Decoder) -> eyre::Result<Self> {
+        if de.take_null()? {
+            return Ok(Self::Null);
+        }
+        T::from_json(de).map(Self::Present)
+    }
+
+    fn absent() -> Option<Self> {
+        Some(Self::Absent)
+    }
+}
+
+impl<T: FromJson> FromJson for Box<T> {
+    fn from_json(de: &mut Decoder) -> eyre::Result<Self> {
+        T::from_json(de).map(Box::new)
+    }
+}
+
+impl<T: FromJson> FromJson for Vec<T> {
+    fn from_json(de: &mut Decoder) -> eyre::Result<Self> {
+        let mut entries = Vec::new();
+        de.array(|de| {
+            entries.push(T::from_json(de)?);
+            Ok(())
+        })?;
+        Ok(entries)
+    }
+}
+
+impl<T: FromJson> FromJson for HashMap<String, T> {
+    fn from_json(de: &mut Decoder) -> eyre::Result<Self> {
+        let mut entries = HashMap::new();
+        de.object(|key, de| {
+            entries.insert(key.to_string(), T::from_json(de)?);
+            Ok(())
+        })?;
+        Ok(entries)
+    }
+}
+
+impl<T: FromJson> FromJson for BTreeMap<String, T> {
+    fn from_json(de: &mut Decoder) -> eyre::Result<Self> {
+        let mut entries = BTreeMap::new();
+        de.object(|key, de| {
+            entries.insert(key.to_string(), T::from_json(de)?);
+            Ok(())
+        })?;
+        Ok(entries)
+    }
+}
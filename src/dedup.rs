@@ -0,0 +1,94 @@
+use std::collections::{HashSet, VecDeque};
+use std::io::BufRead;
+
+use crate::profile::canonical_hash;
+use crate::{parse_auto, patch, pointer, JSONValue, Records};
+
+/// A record from `dedup_lines`, and whether an earlier one had its key.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DedupRecord {
+    pub value: JSONValue,
+    pub duplicate: bool,
+}
+
+/// Records from `dedup_lines`, each flagged if it's a duplicate.
+pub struct Dedup<R> {
+    records: Records<R>,
+    key: Option<String>,
+    seen: HashSet<u64>,
+    // keys in the order first seen, when only a window of them is kept
+    order: VecDeque<u64>,
+    window: Option<usize>,
+}
+
+/// Streams the records of `reader` (framed as `parse_auto` sees it),
+/// flagging each whose key was already seen: the value at the JSON Pointer
+/// `key`, or with `None` the whole record. Keys compare as values, so key
+/// order and formatting don't matter. Records without the key are never
+/// duplicates.
+///
+/// Only a 64-bit hash of each key is kept, so memory grows by a few bytes
+/// per distinct key; `window` bounds it for endless streams.
+pub fn dedup_lines<R: BufRead>(reader: R, key: Option<&str>) -> eyre::Result<Dedup<R>> {
+    if let Some(key) = key {
+        pointer::tokens(key)?;
+    }
+    Ok(Dedup {
+        records: parse_auto(reader)?,
+        key: key.map(str::to_string),
+        seen: HashSet::new(),
+        order: VecDeque::new(),
+        window: None,
+    })
+}
+
+impl<R: BufRead> Dedup<R> {
+    /// Remembers only the last `n` distinct keys, so a duplicate is caught
+    /// only if its first record came less than `n` keys before.
+    pub fn window(mut self, n: usize) -> Self {
+        self.window = Some(n);
+        self
+    }
+
+    /// Only the first record with each key, dropping duplicates.
+    pub fn unique(self) -> impl Iterator<Item = eyre::Result<JSONValue>> {
+        self.filter_map(|record| match record {
+            Ok(DedupRecord {
+                duplicate: true, ..
+            }) => None,
+            record => Some(record.map(|r| r.value)),
+        })
+    }
+
+    // Whether `hash` was seen, remembering it if not.
+    fn seen(&mut self, hash: u64) -> bool {
+        if !self.seen.insert(hash) {
+            return true;
+        }
+        if let Some(window) = self.window {
+            self.order.push_back(hash);
+            while self.order.len() > window {
+                let oldest = self.order.pop_front().expect("not empty");
+                self.seen.remove(&oldest);
+            }
+        }
+        false
+    }
+}
+
+impl<R: BufRead> Iterator for Dedup<R> {
+    type Item = eyre::Result<DedupRecord>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let value = match self.records.next()? {
+            Ok(value) => value,
+            Err(e) => return Some(Err(e)),
+        };
+        let hash = match &self.key {
+            Some(key) => patch::get(&value, key).ok().map(canonical_hash),
+            None => Some(canonical_hash(&value)),
+        };
+        let duplicate = hash.is_some_and(|hash| self.seen(hash));
+        Some(Ok(DedupRecord { value, duplicate }))
+    }
+}
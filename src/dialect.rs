@@ -0,0 +1,281 @@
+use core::fmt::{self, Write};
+
+use crate::format::{JsonFormat, PrettyFormat};
+use crate::{pointer, Comment, CommentPlacement, JSONValue};
+
+/// JSON5 output: identifier keys unquoted, single-quoted strings,
+/// `Infinity`/`NaN` for non-finite numbers, and trailing commas when
+/// pretty-printing.
+#[derive(Debug, Clone, Copy)]
+pub struct Json5Format {
+    /// Spaces per level, or `None` for compact output.
+    pub indent: Option<usize>,
+    pub trailing_commas: bool,
+}
+
+impl Default for Json5Format {
+    fn default() -> Self {
+        Self {
+            indent: Some(2),
+            trailing_commas: true,
+        }
+    }
+}
+
+fn is_identifier(key: &str) -> bool {
+    let mut chars = key.chars();
+    let Some(first) = chars.next() else {
+        return false;
+    };
+    (first.is_alphabetic() || first == '_' || first == '$')
+        && chars.all(|c| c.is_alphanumeric() || c == '_' || c == '$')
+}
+
+fn write_single_quoted<W: ?Sized + Write>(out: &mut W, s: &str) -> fmt::Result {
+    out.write_char('\'')?;
+    for c in s.chars() {
+        match c {
+            '\'' => out.write_str("\\'")?,
+            '\\' => out.write_str("\\\\")?,
+            '\n' => out.write_str("\\n")?,
+            '\r' => out.write_str("\\r")?,
+            '\t' => out.write_str("\\t")?,
+            c if c < ' ' => write!(out, "\\u{:04x}", c as u32)?,
+            c => out.write_char(c)?,
+        }
+    }
+    out.write_char('\'')
+}
+
+impl JsonFormat for Json5Format {
+    fn write_number<W: ?Sized + Write>(&mut self, out: &mut W, n: f64) -> fmt::Result {
+        match n {
+            n if n.is_nan() => out.write_str("NaN"),
+            f64::INFINITY => out.write_str("Infinity"),
+            f64::NEG_INFINITY => out.write_str("-Infinity"),
+            n => crate::serialize::write_number(out, n),
+        }
+    }
+
+    fn write_string<W: ?Sized + Write>(&mut self, out: &mut W, s: &str) -> fmt::Result {
+        write_single_quoted(out, s)
+    }
+
+    fn write_key<W: ?Sized + Write>(&mut self, out: &mut W, key: &str) -> fmt::Result {
+        if is_identifier(key) {
+            out.write_str(key)
+        } else {
+            write_single_quoted(out, key)
+        }
+    }
+
+    fn write_colon<W: ?Sized + Write>(&mut self, out: &mut W) -> fmt::Result {
+        out.write_str(if self.indent.is_some() { ": " } else { ":" })
+    }
+
+    fn after_last_entry<W: ?Sized + Write>(&mut self, out: &mut W) -> fmt::Result {
+        if self.trailing_commas && self.indent.is_some() {
+            out.write_char(',')?;
+        }
+        Ok(())
+    }
+
+    fn indent<W: ?Sized + Write>(&mut self, out: &mut W, depth: usize) -> fmt::Result {
+        match self.indent {
+            Some(indent) => PrettyFormat { indent }.indent(out, depth),
+            None => Ok(()),
+        }
+    }
+
+    fn order_entries(&mut self, entries: &mut [(&String, &JSONValue)]) {
+        entries.sort_unstable_by(|a, b| a.0.cmp(b.0));
+    }
+}
+
+struct Frame {
+    ptr: String,
+    // pointer of the entry being written
+    entry: String,
+    // next array index, `None` for objects
+    idx: Option<usize>,
+    empty: bool,
+}
+
+/// Pretty-printed JSON with comments from `parse_with_comments` written back
+/// next to the values they were attached to.
+pub struct JsoncFormat<'c> {
+    comments: &'c [Comment],
+    indent: usize,
+    stack: Vec<Frame>,
+}
+
+impl<'c> JsoncFormat<'c> {
+    pub fn new(comments: &'c [Comment]) -> Self {
+        Self {
+            comments,
+            indent: 2,
+            stack: Vec::new(),
+        }
+    }
+
+    pub fn indent(mut self, indent: usize) -> Self {
+        self.indent = indent;
+        self
+    }
+
+    fn newline<W: ?Sized + Write>(&self, out: &mut W, depth: usize) -> fmt::Result {
+        PrettyFormat {
+            indent: self.indent,
+        }
+        .indent(out, depth)
+    }
+
+    // pointer of the value about to be written
+    fn current(&self) -> String {
+        self.stack
+            .last()
+            .map(|f| f.entry.clone())
+            .unwrap_or_default()
+    }
+
+    fn leading<W: ?Sized + Write>(&self, out: &mut W, ptr: &str) -> fmt::Result {
+        for c in self.comments {
+            if matches!(&c.placement, CommentPlacement::Leading(p) if p == ptr) {
+                out.write_str(&c.text)?;
+                self.newline(out, self.stack.len())?;
+            }
+        }
+        Ok(())
+    }
+
+    fn trailing<W: ?Sized + Write>(&self, out: &mut W) -> fmt::Result {
+        let Some(frame) = self.stack.last() else {
+            return Ok(());
+        };
+        for c in self.comments {
+            if matches!(&c.placement, CommentPlacement::Trailing(p) if *p == frame.entry) {
+                out.write_char(' ')?;
+                out.write_str(&c.text)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn begin<W: ?Sized + Write>(&mut self, out: &mut W, open: char, array: bool) -> fmt::Result {
+        let ptr = self.current();
+        self.stack.push(Frame {
+            entry: ptr.clone(),
+            ptr,
+            idx: array.then_some(0),
+            empty: true,
+        });
+        out.write_char(open)
+    }
+
+    fn end<W: ?Sized + Write>(&mut self, out: &mut W, close: char) -> fmt::Result {
+        let frame = self.stack.pop().expect("end without begin");
+        if frame.empty {
+            let dangling: Vec<_> = self
+                .comments
+                .iter()
+                .filter(
+                    |c| matches!(&c.placement, CommentPlacement::Dangling(p) if *p == frame.ptr),
+                )
+                .collect();
+            if !dangling.is_empty() {
+                for c in dangling {
+                    self.newline(out, self.stack.len() + 1)?;
+                    out.write_str(&c.text)?;
+                }
+                self.newline(out, self.stack.len())?;
+            }
+        }
+        out.write_char(close)
+    }
+}
+
+impl JsonFormat for JsoncFormat<'_> {
+    fn write_key<W: ?Sized + Write>(&mut self, out: &mut W, key: &str) -> fmt::Result {
+        if let Some(frame) = self.stack.last_mut() {
+            frame.entry = pointer::child(&frame.ptr, key);
+        }
+        self.leading(out, &self.current())?;
+        self.write_string(out, key)
+    }
+
+    fn begin_array<W: ?Sized + Write>(&mut self, out: &mut W) -> fmt::Result {
+        self.begin(out, '[', true)
+    }
+
+    fn end_array<W: ?Sized + Write>(&mut self, out: &mut W) -> fmt::Result {
+        self.end(out, ']')
+    }
+
+    fn begin_object<W: ?Sized + Write>(&mut self, out: &mut W) -> fmt::Result {
+        self.begin(out, '{', false)
+    }
+
+    fn end_object<W: ?Sized + Write>(&mut self, out: &mut W) -> fmt::Result {
+        self.end(out, '}')
+    }
+
+    fn write_comma<W: ?Sized + Write>(&mut self, out: &mut W) -> fmt::Result {
+        out.write_char(',')?;
+        self.trailing(out)
+    }
+
+    fn write_colon<W: ?Sized + Write>(&mut self, out: &mut W) -> fmt::Result {
+        out.write_str(": ")
+    }
+
+    fn after_last_entry<W: ?Sized + Write>(&mut self, out: &mut W) -> fmt::Result {
+        self.trailing(out)
+    }
+
+    fn indent<W: ?Sized + Write>(&mut self, out: &mut W, depth: usize) -> fmt::Result {
+        self.newline(out, depth)?;
+        let entry_depth = self.stack.len();
+        let Some(frame) = self.stack.last_mut() else {
+            return Ok(());
+        };
+        // before an entry rather than the closing bracket
+        if depth == entry_depth {
+            frame.empty = false;
+            if let Some(idx) = frame.idx.as_mut() {
+                frame.entry = pointer::child(&frame.ptr, &idx.to_string());
+                *idx += 1;
+                self.leading(out, &self.current())?;
+            }
+        }
+        Ok(())
+    }
+
+    fn order_entries(&mut self, entries: &mut [(&String, &JSONValue)]) {
+        entries.sort_unstable_by(|a, b| a.0.cmp(b.0));
+    }
+}
+
+impl JSONValue {
+    /// JSON5 text for this value, see `Json5Format`.
+    pub fn to_json5(&self) -> String {
+        self.to_string_formatted(&mut Json5Format::default())
+    }
+
+    /// Pretty-printed JSONC, writing `comments` (as returned by
+    /// `parse_with_comments`) back in place.
+    pub fn to_jsonc(&self, comments: &[Comment]) -> String {
+        let mut format = JsoncFormat::new(comments);
+        let mut out = String::new();
+        format
+            .leading(&mut out, "")
+            .and_then(|_| self.write_formatted(&mut out, &mut format))
+            .expect("writing to a String doesn't fail");
+        for c in comments {
+            if matches!(&c.placement, CommentPlacement::Trailing(p) if p.is_empty()) {
+                out.push(' ');
+                out.push_str(&c.text);
+            }
+        }
+        out
+    }
+}
@@ -0,0 +1,379 @@
+use core::fmt;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::ops::Deref;
+
+use crate::lexer::{Lexer, Span, Token};
+use crate::syntax::{Expect, SyntaxState};
+use crate::{escape, JSONValue, ParseOptions};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum NodeKind {
+    Null,
+    Bool,
+    Number,
+    String,
+    Array,
+    Object,
+}
+
+// One value (or object key) in document order. Containers are followed by
+// their entries, keys by their values.
+#[derive(Debug, Copy, Clone)]
+enum Slot {
+    Null,
+    Bool(bool),
+    Num(f64),
+    // contents between the quotes, and whether they contain escapes
+    Str(Span, bool),
+    // `end` is the index of the first slot after the container's entries
+    Array { end: usize, len: usize },
+    Object { end: usize, len: usize },
+}
+
+#[derive(Debug, Clone)]
+enum Buffer {
+    Vec(Vec<u8>),
+    #[cfg(feature = "bytes")]
+    Bytes(bytes::Bytes),
+}
+
+impl Deref for Buffer {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match self {
+            Buffer::Vec(v) => v,
+            #[cfg(feature = "bytes")]
+            Buffer::Bytes(b) => b,
+        }
+    }
+}
+
+/// A parsed document that owns its input and hands out `Node`s borrowing
+/// from it, so strings without escapes are never copied.
+#[derive(Debug, Clone)]
+pub struct Document {
+    buf: Buffer,
+    slots: Vec<Slot>,
+    spans: Vec<Span>,
+}
+
+impl Document {
+    pub fn parse(buf: impl Into<Vec<u8>>) -> eyre::Result<Self> {
+        Self::parse_with_options(buf, &ParseOptions::default())
+    }
+
+    pub fn parse_with_options(
+        buf: impl Into<Vec<u8>>,
+        options: &ParseOptions,
+    ) -> eyre::Result<Self> {
+        Self::build(Buffer::Vec(buf.into()), options)
+    }
+
+    /// Parses a network buffer without copying it; see `Node::str_bytes`.
+    #[cfg(feature = "bytes")]
+    pub fn from_bytes(buf: bytes::Bytes) -> eyre::Result<Self> {
+        Self::from_bytes_with_options(buf, &ParseOptions::default())
+    }
+
+    #[cfg(feature = "bytes")]
+    pub fn from_bytes_with_options(
+        buf: bytes::Bytes,
+        options: &ParseOptions,
+    ) -> eyre::Result<Self> {
+        Self::build(Buffer::Bytes(buf), options)
+    }
+
+    fn build(buf: Buffer, options: &ParseOptions) -> eyre::Result<Self> {
+        if let Some(max_size) = options.max_size {
+            if buf.len() > max_size {
+                eyre::bail!(format!(
+                    "Input of {} bytes exceeds maximum size of {}",
+                    buf.len(),
+                    max_size
+                ))
+            }
+        }
+        let mut slots = Vec::new();
+        let mut spans = Vec::new();
+        let mut syntax = SyntaxState::new();
+        // open containers, by slot index
+        let mut stack: Vec<usize> = Vec::new();
+        for t in Lexer::with_options(options).tokens(&buf) {
+            let t = t?;
+            let expect = syntax.expect;
+            syntax.feed(t.token)?;
+            let is_key = matches!(expect, Expect::Key | Expect::KeyOrEndObject);
+            let slot = match t.token {
+                Token::Comment | Token::Colon | Token::Comma => continue,
+                Token::LeftBrace | Token::LeftBracket => {
+                    if let Some(max_depth) = options.max_depth {
+                        if stack.len() >= max_depth {
+                            eyre::bail!(format!("Nesting exceeds maximum depth of {}", max_depth))
+                        }
+                    }
+                    count_entry(&mut slots, &stack, false);
+                    stack.push(slots.len());
+                    slots.push(match t.token {
+                        Token::LeftBrace => Slot::Object { end: 0, len: 0 },
+                        _ => Slot::Array { end: 0, len: 0 },
+                    });
+                    spans.push(t.span);
+                    continue;
+                }
+                Token::RightBrace | Token::RightBracket => {
+                    if let Some(open) = stack.pop() {
+                        let end = slots.len();
+                        if let Slot::Array { end: e, .. } | Slot::Object { end: e, .. } =
+                            &mut slots[open]
+                        {
+                            *e = end;
+                        }
+                        spans[open].end = t.span.end;
+                    }
+                    continue;
+                }
+                Token::StringVal(i, j) => {
+                    // decoded now so that reading it back can't fail
+                    let raw = &buf[i..j];
+                    let escaped = raw.contains(&b'\\');
+                    if escaped {
+                        escape::unescape_str(raw, options.lone_surrogates, String::new())?;
+                    } else {
+                        std::str::from_utf8(raw)?;
+                    }
+                    Slot::Str(Span::new(i, j), escaped)
+                }
+                Token::NumVal(n) => Slot::Num(n),
+                Token::BoolVal(b) => Slot::Bool(b),
+                Token::NullVal => Slot::Null,
+            };
+            count_entry(&mut slots, &stack, is_key);
+            slots.push(slot);
+            spans.push(t.span);
+        }
+        if !syntax.is_complete() {
+            eyre::bail!(format!("Unexpected end of input, {}", syntax.describe()))
+        }
+        Ok(Self { buf, slots, spans })
+    }
+
+    pub fn root(&self) -> Node<'_> {
+        Node { doc: self, idx: 0 }
+    }
+
+    /// The input the document was parsed from.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.buf
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        match self.buf {
+            Buffer::Vec(v) => v,
+            #[cfg(feature = "bytes")]
+            Buffer::Bytes(b) => b.into(),
+        }
+    }
+
+    // Index of the slot after the value at `idx`.
+    fn skip(&self, idx: usize) -> usize {
+        match self.slots[idx] {
+            Slot::Array { end, .. } | Slot::Object { end, .. } => end,
+            _ => idx + 1,
+        }
+    }
+
+    fn str_at(&self, idx: usize) -> Option<Cow<'_, str>> {
+        let Slot::Str(span, escaped) = self.slots[idx] else {
+            return None;
+        };
+        let raw = &self.buf[span.range()];
+        if escaped {
+            escape::unescape_str(raw, escape::LoneSurrogates::Replace, String::new())
+                .ok()
+                .map(Cow::Owned)
+        } else {
+            std::str::from_utf8(raw).ok().map(Cow::Borrowed)
+        }
+    }
+}
+
+// Counts a new entry of the innermost container; object entries are counted
+// at their key.
+fn count_entry(slots: &mut [Slot], stack: &[usize], is_key: bool) {
+    let Some(&open) = stack.last() else {
+        return;
+    };
+    match &mut slots[open] {
+        Slot::Array { len, .. } => *len += 1,
+        Slot::Object { len, .. } if is_key => *len += 1,
+        _ => {}
+    }
+}
+
+/// A value inside a `Document`.
+#[derive(Copy, Clone)]
+pub struct Node<'d> {
+    doc: &'d Document,
+    idx: usize,
+}
+
+impl fmt::Debug for Node<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Node")
+            .field("kind", &self.kind())
+            .field("span", &self.span())
+            .finish()
+    }
+}
+
+impl<'d> Node<'d> {
+    pub fn kind(&self) -> NodeKind {
+        match self.doc.slots[self.idx] {
+            Slot::Null => NodeKind::Null,
+            Slot::Bool(_) => NodeKind::Bool,
+            Slot::Num(_) => NodeKind::Number,
+            Slot::Str(..) => NodeKind::String,
+            Slot::Array { .. } => NodeKind::Array,
+            Slot::Object { .. } => NodeKind::Object,
+        }
+    }
+
+    /// The value's text in the input.
+    pub fn raw(&self) -> &'d [u8] {
+        &self.doc.buf[self.doc.spans[self.idx].range()]
+    }
+
+    pub fn span(&self) -> Span {
+        self.doc.spans[self.idx]
+    }
+
+    pub fn is_null(&self) -> bool {
+        matches!(self.doc.slots[self.idx], Slot::Null)
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self.doc.slots[self.idx] {
+            Slot::Bool(b) => Some(b),
+            _ => None,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self.doc.slots[self.idx] {
+            Slot::Num(n) => Some(n),
+            _ => None,
+        }
+    }
+
+    /// The string, borrowed from the input unless it contains escapes.
+    pub fn as_str(&self) -> Option<Cow<'d, str>> {
+        self.doc.str_at(self.idx)
+    }
+
+    /// The string as `Bytes` holding UTF-8. For a document made with
+    /// `Document::from_bytes` this is a slice of the input unless the string
+    /// contains escapes; otherwise it is a copy.
+    #[cfg(feature = "bytes")]
+    pub fn str_bytes(&self) -> Option<bytes::Bytes> {
+        match (&self.doc.buf, self.doc.slots[self.idx]) {
+            (Buffer::Bytes(b), Slot::Str(span, false)) => Some(b.slice(span.range())),
+            _ => self.as_str().map(|s| bytes::Bytes::from(s.into_owned())),
+        }
+    }
+
+    /// Number of elements or entries of an array or object.
+    pub fn len(&self) -> usize {
+        match self.doc.slots[self.idx] {
+            Slot::Array { len, .. } | Slot::Object { len, .. } => len,
+            _ => 0,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Elements of an array; empty for anything else.
+    pub fn elements(&self) -> impl Iterator<Item = Node<'d>> + 'd {
+        let doc = self.doc;
+        let (mut idx, end) = match doc.slots[self.idx] {
+            Slot::Array { end, .. } => (self.idx + 1, end),
+            _ => (0, 0),
+        };
+        std::iter::from_fn(move || {
+            if idx >= end {
+                return None;
+            }
+            let node = Node { doc, idx };
+            idx = doc.skip(idx);
+            Some(node)
+        })
+    }
+
+    /// Entries of an object in document order; empty for anything else.
+    pub fn entries(&self) -> impl Iterator<Item = (Cow<'d, str>, Node<'d>)> + 'd {
+        let doc = self.doc;
+        let (mut idx, end) = match doc.slots[self.idx] {
+            Slot::Object { end, .. } => (self.idx + 1, end),
+            _ => (0, 0),
+        };
+        std::iter::from_fn(move || {
+            if idx >= end {
+                return None;
+            }
+            let key = doc.str_at(idx).unwrap_or_default();
+            let node = Node { doc, idx: idx + 1 };
+            idx = doc.skip(idx + 1);
+            Some((key, node))
+        })
+    }
+
+    // Entries of an object with the span of each key, quotes included.
+    pub(crate) fn key_spans(&self) -> impl Iterator<Item = (Span, Node<'d>)> + 'd {
+        let doc = self.doc;
+        let (mut idx, end) = match doc.slots[self.idx] {
+            Slot::Object { end, .. } => (self.idx + 1, end),
+            _ => (0, 0),
+        };
+        std::iter::from_fn(move || {
+            if idx >= end {
+                return None;
+            }
+            let span = doc.spans[idx];
+            let node = Node { doc, idx: idx + 1 };
+            idx = doc.skip(idx + 1);
+            Some((span, node))
+        })
+    }
+
+    /// The value of `key` in an object. Like `parse`, the last of duplicate
+    /// keys wins.
+    pub fn get(&self, key: &str) -> Option<Node<'d>> {
+        self.entries()
+            .filter(|(k, _)| k == key)
+            .last()
+            .map(|(_, v)| v)
+    }
+
+    pub fn index(&self, i: usize) -> Option<Node<'d>> {
+        self.elements().nth(i)
+    }
+
+    /// Copies the value out of the document.
+    pub fn to_value(&self) -> JSONValue {
+        match self.doc.slots[self.idx] {
+            Slot::Null => JSONValue::Null,
+            Slot::Bool(b) => JSONValue::Bool(b),
+            Slot::Num(n) => JSONValue::Num(n),
+            Slot::Str(..) => JSONValue::Str(self.as_str().unwrap_or_default().into_owned()),
+            Slot::Array { .. } => JSONValue::Array(self.elements().map(|e| e.to_value()).collect()),
+            Slot::Object { .. } => JSONValue::Dict(
+                self.entries()
+                    .map(|(k, v)| (k.into_owned(), v.to_value()))
+                    .collect::<HashMap<_, _>>(),
+            ),
+        }
+    }
+}
@@ -0,0 +1,192 @@
+use std::collections::BTreeMap;
+
+use crate::{parse, JSONValue};
+
+// Nested name parts are joined with this, leaving `_` free within a part.
+const SEPARATOR: &str = "__";
+
+// A name part: uppercase, with each run of anything but letters and digits
+// as one `_`, and none at either end, so parts never contain `SEPARATOR`.
+fn env_name(segment: &str) -> String {
+    let mut name = String::with_capacity(segment.len());
+    for c in segment.chars() {
+        if c.is_ascii_alphanumeric() {
+            name.push(c.to_ascii_uppercase());
+        } else if !name.is_empty() && !name.ends_with('_') {
+            name.push('_');
+        }
+    }
+    if name.ends_with('_') {
+        name.pop();
+    }
+    name
+}
+
+fn flatten(
+    v: &JSONValue,
+    name: String,
+    root: bool,
+    vars: &mut Vec<(String, String)>,
+) -> eyre::Result<()> {
+    let join = |segment: &str| {
+        let part = env_name(segment);
+        if part.is_empty() {
+            eyre::bail!(format!(
+                "Key {:?} under {:?} has no letters or digits",
+                segment, name
+            ))
+        }
+        Ok(match (name.as_str(), root) {
+            ("", _) => part,
+            (name, true) => format!("{}_{}", name, part),
+            (name, false) => format!("{}{}{}", name, SEPARATOR, part),
+        })
+    };
+    match v {
+        JSONValue::Array(entries) if !entries.is_empty() => {
+            for (i, e) in entries.iter().enumerate() {
+                flatten(e, join(&i.to_string())?, false, vars)?;
+            }
+        }
+        JSONValue::Dict(d) if !d.is_empty() => {
+            for (k, e) in d {
+                flatten(e, join(k)?, false, vars)?;
+            }
+        }
+        JSONValue::Str(s) if parse(s.as_bytes()).is_err() => vars.push((name, s.clone())),
+        v => vars.push((name, v.to_json_string())),
+    }
+    Ok(())
+}
+
+// Variables gathered by name part before it's known which objects are
+// arrays.
+enum Tree {
+    Leaf(JSONValue),
+    Node(BTreeMap<String, Tree>),
+}
+
+impl Tree {
+    fn insert(&mut self, segments: &[String], value: JSONValue) -> bool {
+        let Tree::Node(children) = self else {
+            return false;
+        };
+        match segments.split_first() {
+            None if children.is_empty() => {
+                *self = Tree::Leaf(value);
+                true
+            }
+            None => false,
+            Some((first, rest)) => children
+                .entry(first.clone())
+                .or_insert_with(|| Tree::Node(BTreeMap::new()))
+                .insert(rest, value),
+        }
+    }
+
+    // Objects keyed `0` to `n - 1` become arrays.
+    fn into_json(self) -> JSONValue {
+        let children = match self {
+            Tree::Leaf(v) => return v,
+            Tree::Node(children) => children,
+        };
+        // in BTreeMap order `10` comes before `2`
+        let mut entries: Vec<_> = children
+            .keys()
+            .filter_map(|k| k.parse::<usize>().ok().filter(|i| i.to_string() == *k))
+            .collect();
+        entries.sort_unstable();
+        let is_array = !entries.is_empty()
+            && entries.len() == children.len()
+            && entries.iter().enumerate().all(|(i, k)| i == *k);
+        if is_array {
+            let mut entries: Vec<_> = children
+                .into_iter()
+                .map(|(k, v)| (k.parse::<usize>().unwrap_or_default(), v.into_json()))
+                .collect();
+            entries.sort_unstable_by_key(|(i, _)| *i);
+            return JSONValue::Array(entries.into_iter().map(|(_, v)| v).collect());
+        }
+        JSONValue::Dict(
+            children
+                .into_iter()
+                .map(|(k, v)| (k, v.into_json()))
+                .collect(),
+        )
+    }
+}
+
+impl JSONValue {
+    /// Flattens the value into environment variables named after the path
+    /// to each scalar: the prefix, then the keys uppercased and joined with
+    /// `__`, so `{"db": {"max_connections": 5}}` with prefix `app` gives
+    /// `APP_DB__MAX_CONNECTIONS=5`. Other punctuation in keys becomes `_`.
+    /// Strings are written as they are unless they would read back as
+    /// another value, as `"5432"` would, in which case they're quoted. Sorted
+    /// by name.
+    ///
+    /// Fails if two paths give the same name, as `max-connections` and
+    /// `max_connections` (or `Host` and `host`) would, or if a key has no
+    /// letters or digits to name it by.
+    pub fn to_env_vars(&self, prefix: &str) -> eyre::Result<Vec<(String, String)>> {
+        let mut vars = Vec::new();
+        flatten(self, env_name(prefix), true, &mut vars)?;
+        vars.sort_unstable();
+        if let Some(w) = vars.windows(2).find(|w| w[0].0 == w[1].0) {
+            eyre::bail!(format!("More than one value would be named {}", w[0].0))
+        }
+        Ok(vars)
+    }
+
+    /// Nests the variables starting with `PREFIX_` by the `__`-separated
+    /// parts of their names, lowercased. Values that are JSON (`5432`,
+    /// `true`, `["a"]`) are read as such and anything else as a string;
+    /// objects keyed `0`, `1`, .. become arrays. Fails if a variable is both
+    /// a value and the parent of others, as `APP_DB` and `APP_DB__PORT`
+    /// would be.
+    ///
+    /// This reads back what `to_env_vars` writes for keys that are lowercase
+    /// letters, digits and single `_`s; any other key comes back lowercased
+    /// and with its punctuation as `_`, since names don't keep either.
+    pub fn from_env_vars(
+        prefix: &str,
+        vars: impl IntoIterator<Item = (String, String)>,
+    ) -> eyre::Result<JSONValue> {
+        let prefix = env_name(prefix);
+        let mut vars: Vec<_> = vars
+            .into_iter()
+            .filter_map(|(name, value)| {
+                let rest = match name.strip_prefix(prefix.as_str())? {
+                    rest if prefix.is_empty() || rest.is_empty() => rest,
+                    rest => rest.strip_prefix('_')?,
+                };
+                let segments: Vec<String> = match rest {
+                    "" => Vec::new(),
+                    rest => rest.split(SEPARATOR).map(str::to_lowercase).collect(),
+                };
+                Some((segments, name, value))
+            })
+            .collect();
+        vars.sort_unstable();
+        let mut tree = Tree::Node(BTreeMap::new());
+        for (segments, name, value) in vars {
+            if segments.iter().any(String::is_empty) {
+                eyre::bail!(format!("Empty name part in {}", name))
+            }
+            let value = parse(value.as_bytes()).unwrap_or(JSONValue::Str(value));
+            if !tree.insert(&segments, value) {
+                eyre::bail!(format!("{} conflicts with another variable", name))
+            }
+        }
+        Ok(tree.into_json())
+    }
+
+    /// `from_env_vars` over this process's environment. Variables that
+    /// aren't Unicode are ignored.
+    pub fn from_env(prefix: &str) -> eyre::Result<JSONValue> {
+        let vars = std::env::vars_os().filter_map(|(name, value)| {
+            Some((name.into_string().ok()?, value.into_string().ok()?))
+        });
+        Self::from_env_vars(prefix, vars)
+    }
+}
@@ -0,0 +1,156 @@
+use eyre::OptionExt;
+
+/// What to do with a `\uD800`-style escape that is not part of a surrogate
+/// pair.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum LoneSurrogates {
+    #[default]
+    Reject,
+    /// Replace it with U+FFFD.
+    Replace,
+    /// Keep it, encoded as WTF-8. Only `unescape` can return such strings;
+    /// parsing into a `JSONValue` fails since its strings must be UTF-8.
+    Preserve,
+}
+
+/// Finds the closing quote of the string whose opening quote is at `start`.
+/// Returns `None` if the input ends first.
+pub(crate) fn scan_string(
+    buf: &[u8],
+    start: usize,
+    allow_control_chars: bool,
+) -> eyre::Result<Option<usize>> {
+    let mut j = start + 1;
+    while j < buf.len() {
+        match buf[j] {
+            b'"' => return Ok(Some(j)),
+            b'\n' if !allow_control_chars => eyre::bail!(format!(
+                "Unescaped newline at byte {} in the string starting at byte {}: \
+                 write it as \\n, or close the string before the line ends",
+                j, start
+            )),
+            c @ 0x00..=0x1f if !allow_control_chars => eyre::bail!(format!(
+                "Unescaped control character 0x{:02X} in string at byte {}",
+                c, j
+            )),
+            b'\\' => {
+                let Some(&c) = buf.get(j + 1) else {
+                    return Ok(None);
+                };
+                match c {
+                    b'"' | b'\\' | b'/' | b'b' | b'f' | b'n' | b'r' | b't' => j += 2,
+                    b'u' => {
+                        let digits = &buf[j + 2..buf.len().min(j + 6)];
+                        if let Some(bad) = digits.iter().find(|c| !c.is_ascii_hexdigit()) {
+                            eyre::bail!(format!("Invalid unicode escape digit: '{}'", *bad as char))
+                        }
+                        j += 6;
+                    }
+                    _ => eyre::bail!(format!("Invalid escape: '\\{}'", c as char)),
+                }
+            }
+            _ => j += 1,
+        }
+    }
+    Ok(None)
+}
+
+fn hex4(raw: &[u8], i: usize) -> eyre::Result<u32> {
+    let digits = raw.get(i..i + 4).ok_or_eyre("Incomplete unicode escape")?;
+    let digits = std::str::from_utf8(digits)?;
+    Ok(u32::from_str_radix(digits, 16)?)
+}
+
+fn push_char(out: &mut Vec<u8>, c: char) {
+    out.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes());
+}
+
+fn unescape_into(raw: &[u8], policy: LoneSurrogates, out: &mut Vec<u8>) -> eyre::Result<()> {
+    let mut i = 0;
+    while let Some(n) = raw[i..].iter().position(|c| *c == b'\\') {
+        out.extend_from_slice(&raw[i..i + n]);
+        i += n + 1;
+        let c = *raw.get(i).ok_or_eyre("Incomplete escape")?;
+        i += 1;
+        let simple = match c {
+            b'"' | b'\\' | b'/' => c,
+            b'b' => 0x08,
+            b'f' => 0x0c,
+            b'n' => b'\n',
+            b'r' => b'\r',
+            b't' => b'\t',
+            b'u' => {
+                let hi = hex4(raw, i)?;
+                i += 4;
+                let cp = match hi {
+                    0xD800..=0xDBFF if raw.get(i..i + 2) == Some(b"\\u") => {
+                        let lo = hex4(raw, i + 2)?;
+                        if (0xDC00..=0xDFFF).contains(&lo) {
+                            i += 6;
+                            Some(0x10000 + ((hi - 0xD800) << 10) + (lo - 0xDC00))
+                        } else {
+                            None
+                        }
+                    }
+                    0xD800..=0xDFFF => None,
+                    _ => Some(hi),
+                };
+                match (cp.and_then(char::from_u32), policy) {
+                    (Some(c), _) => push_char(out, c),
+                    (None, LoneSurrogates::Reject) => {
+                        eyre::bail!(format!("Lone surrogate in escape: '\\u{:04X}'", hi))
+                    }
+                    (None, LoneSurrogates::Replace) => push_char(out, '\u{FFFD}'),
+                    (None, LoneSurrogates::Preserve) => out.extend_from_slice(&[
+                        0xE0 | (hi >> 12) as u8,
+                        0x80 | (hi >> 6 & 0x3f) as u8,
+                        0x80 | (hi & 0x3f) as u8,
+                    ]),
+                }
+                continue;
+            }
+            _ => eyre::bail!(format!("Invalid escape: '\\{}'", c as char)),
+        };
+        out.push(simple);
+    }
+    out.extend_from_slice(&raw[i..]);
+    Ok(())
+}
+
+/// Decodes the escapes in the raw contents of a string token (the bytes
+/// between the quotes). The result is UTF-8 unless `policy` is `Preserve`
+/// and a lone surrogate was found, in which case it is WTF-8.
+pub fn unescape(raw: &[u8], policy: LoneSurrogates) -> eyre::Result<Vec<u8>> {
+    std::str::from_utf8(raw)?;
+    let mut out = Vec::with_capacity(raw.len());
+    unescape_into(raw, policy, &mut out)?;
+    Ok(out)
+}
+
+/// Best-effort decoding for keys in tools that must not fail on them.
+pub(crate) fn lossy(raw: &[u8]) -> String {
+    let mut out = Vec::with_capacity(raw.len());
+    match unescape_into(raw, LoneSurrogates::Replace, &mut out) {
+        Ok(()) => String::from_utf8_lossy(&out).into_owned(),
+        Err(_) => String::from_utf8_lossy(raw).into_owned(),
+    }
+}
+
+/// Like `unescape`, appending to `out` (which may be a recycled buffer).
+pub(crate) fn unescape_str(
+    raw: &[u8],
+    policy: LoneSurrogates,
+    out: String,
+) -> eyre::Result<String> {
+    let mut out = out;
+    let s = std::str::from_utf8(raw)?;
+    if !raw.contains(&b'\\') {
+        out.push_str(s);
+        return Ok(out);
+    }
+    let mut bytes = out.into_bytes();
+    unescape_into(raw, policy, &mut bytes)?;
+    String::from_utf8(bytes).map_err(|_| {
+        eyre::eyre!("Lone surrogates can't be preserved in a string value, use `unescape`")
+    })
+}
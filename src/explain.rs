@@ -0,0 +1,310 @@
+use std::collections::VecDeque;
+
+use crate::lexer::{Lexer, SpannedToken, Token};
+use crate::pointer;
+use crate::syntax::{Container, Expect, SyntaxState};
+
+/// Explains why `input` failed to parse with `err`, as a few lines of prose:
+/// the error, where it happened with the line marked, the tokens just
+/// before it, the containers it is inside and what was expected instead,
+/// with a hint for common mistakes. The input is scanned again (without
+/// comments) to find this, so `err` is only repeated at the top; if the
+/// input turns out to be well-formed, that's said instead.
+pub fn explain_error(input: &[u8], err: &eyre::Report) -> String {
+    let mut out = format!("{}\n", err);
+    let failure = match find_failure(input) {
+        Some(failure) => failure,
+        None => {
+            out.push_str(
+                "\nThe input is well-formed JSON, so the error comes from a limit or \
+                 option (such as max_depth) rather than its syntax.\n",
+            );
+            return out;
+        }
+    };
+    let (line, col) = line_col(input, failure.at);
+    out.push_str(&format!("\nAt line {}, column {}:\n", line, col));
+    out.push_str(&snippet(input, failure.at));
+
+    let mut sentences = Vec::new();
+    if !failure.recent.is_empty() {
+        let recent: Vec<_> = failure.recent.iter().map(|s| format!("`{}`", s)).collect();
+        sentences.push(format!("After {}", list(&recent)));
+    }
+    match failure.frames.last() {
+        Some(frame) => {
+            let (line, col) = line_col(input, frame.open);
+            let kind = match frame.container {
+                Container::Object => "object",
+                Container::Array => "array",
+            };
+            let mut ptr = String::new();
+            for f in &failure.frames {
+                match (f.container, &f.key) {
+                    (Container::Object, Some(key)) => pointer::push(&mut ptr, key),
+                    (Container::Object, None) => {}
+                    (Container::Array, _) => pointer::push(&mut ptr, &f.index.to_string()),
+                }
+            }
+            let at = match ptr.is_empty() {
+                true => String::new(),
+                false => format!(", at {}", ptr),
+            };
+            sentences.push(format!(
+                "inside the {} opened at line {}, column {}{}",
+                kind, line, col, at
+            ));
+        }
+        None if failure.found.is_some() => sentences.push("at the top level".to_string()),
+        None => {}
+    }
+    let mut narrative = sentences.join(", ");
+    if !narrative.is_empty() {
+        narrative.push_str(", the parser ");
+    } else {
+        narrative.push_str("The parser ");
+    }
+    match &failure.found {
+        Some(found) => narrative.push_str(&format!("{}, but found {}.", failure.expected, found)),
+        None => narrative.push_str(&format!("{}, but the input ended.", failure.expected)),
+    }
+    out.push('\n');
+    out.push_str(&capitalize(&narrative));
+    out.push('\n');
+    if let Some(hint) = failure.hint {
+        out.push_str(&hint);
+        out.push('\n');
+    }
+    out
+}
+
+// An open container while scanning.
+struct Frame {
+    container: Container,
+    // byte offset of its `{` or `[`
+    open: usize,
+    // the key being read, in an object
+    key: Option<String>,
+    // elements before the current one, in an array
+    index: usize,
+}
+
+// Where and how scanning went wrong.
+struct Failure {
+    at: usize,
+    frames: Vec<Frame>,
+    recent: VecDeque<String>,
+    expected: String,
+    // what was found instead; `None` at the end of input
+    found: Option<String>,
+    hint: Option<String>,
+}
+
+// How many tokens before the error to show.
+const RECENT: usize = 3;
+
+// Scans `input` the way `parse` does, stopping at the first problem.
+fn find_failure(input: &[u8]) -> Option<Failure> {
+    let lexer = Lexer::new();
+    let mut state = SyntaxState::new();
+    let mut frames: Vec<Frame> = Vec::new();
+    let mut recent = VecDeque::new();
+    let mut last = None;
+    let mut pos = 0;
+    loop {
+        let expect = state.expect;
+        let t = match lexer.next_token(input, &mut pos) {
+            Ok(Some(t)) => t,
+            Ok(None) if state.is_complete() => return None,
+            Ok(None) => {
+                let hint = frames.last().map(|f| {
+                    let (line, col) = line_col(input, f.open);
+                    format!(
+                        "{} still open; the innermost opens at line {}, column {}.",
+                        match frames.len() {
+                            1 => "One container is".to_string(),
+                            n => format!("{} containers are", n),
+                        },
+                        line,
+                        col
+                    )
+                });
+                return Some(Failure {
+                    at: input.len(),
+                    frames,
+                    recent,
+                    expected: state.describe(),
+                    found: None,
+                    hint,
+                });
+            }
+            Err(e) => {
+                return Some(Failure {
+                    at: pos,
+                    frames,
+                    recent,
+                    expected: state.describe(),
+                    found: Some(match input[pos] {
+                        b'"' => format!("a string that isn't valid ({})", e),
+                        _ => {
+                            let len = input[pos..]
+                                .iter()
+                                .position(|b| b.is_ascii_whitespace() || b",:[]{}".contains(b))
+                                .unwrap_or(input.len() - pos)
+                                .max(1);
+                            format!("`{}` ({})", text(input, pos, pos + len), e)
+                        }
+                    }),
+                    hint: None,
+                });
+            }
+        };
+        if state.feed(t.token).is_err() {
+            let found = describe(input, &t);
+            let hint = match (expect, t.token) {
+                (Expect::CommaOrEndArray | Expect::CommaOrEndObject, token)
+                    if !matches!(
+                        token,
+                        Token::Colon | Token::Comma | Token::RightBrace | Token::RightBracket
+                    ) =>
+                {
+                    Some("Perhaps a `,` is missing before it.")
+                }
+                (Expect::Value | Expect::Key, Token::RightBrace | Token::RightBracket)
+                    if last == Some(Token::Comma) =>
+                {
+                    Some("JSON doesn't allow a trailing comma; remove the `,` before it.")
+                }
+                (Expect::Colon, _) => Some("Each key must be followed by `:` and a value."),
+                (Expect::End, _) => Some(
+                    "Only one value is allowed at the top level; wrap several in an array, \
+                     or read them as NDJSON.",
+                ),
+                _ => None,
+            };
+            return Some(Failure {
+                at: t.span.start,
+                frames,
+                recent,
+                expected: state.describe(),
+                found: Some(found),
+                hint: hint.map(str::to_string),
+            });
+        }
+        match t.token {
+            Token::LeftBrace | Token::LeftBracket => frames.push(Frame {
+                container: match t.token {
+                    Token::LeftBrace => Container::Object,
+                    _ => Container::Array,
+                },
+                open: t.span.start,
+                key: None,
+                index: 0,
+            }),
+            Token::RightBrace | Token::RightBracket => {
+                frames.pop();
+            }
+            Token::StringVal(start, end) if expect_is_key(expect) => {
+                if let Some(frame) = frames.last_mut() {
+                    frame.key = Some(String::from_utf8_lossy(&input[start..end]).into_owned());
+                }
+            }
+            Token::Comma => {
+                if let Some(frame) = frames.last_mut() {
+                    frame.index += 1;
+                    frame.key = None;
+                }
+            }
+            _ => {}
+        }
+        if recent.len() == RECENT {
+            recent.pop_front();
+        }
+        recent.push_back(text(input, t.span.start, t.span.end));
+        last = Some(t.token);
+    }
+}
+
+fn expect_is_key(expect: Expect) -> bool {
+    matches!(expect, Expect::Key | Expect::KeyOrEndObject)
+}
+
+// e.g. "the string \"b\"" or "`]`".
+fn describe(input: &[u8], t: &SpannedToken) -> String {
+    let text = text(input, t.span.start, t.span.end);
+    match t.token {
+        Token::StringVal(..) => format!("the string {}", text),
+        Token::NumVal(_) => format!("the number {}", text),
+        _ => format!("`{}`", text),
+    }
+}
+
+// `input[start..end]` as text, shortened if long.
+fn text(input: &[u8], start: usize, end: usize) -> String {
+    let s = String::from_utf8_lossy(&input[start..end.min(input.len())]);
+    match s.char_indices().nth(20) {
+        Some((i, _)) => format!("{}..", &s[..i]),
+        None => s.into_owned(),
+    }
+}
+
+// "a", "a and b", "a, b and c".
+fn list(items: &[String]) -> String {
+    match items {
+        [] => String::new(),
+        [one] => one.clone(),
+        [rest @ .., last] => format!("{} and {}", rest.join(", "), last),
+    }
+}
+
+fn capitalize(s: &str) -> String {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(c) => c.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+// 1-based line and column (in chars) of byte `offset`.
+fn line_col(input: &[u8], offset: usize) -> (usize, usize) {
+    let before = &input[..offset.min(input.len())];
+    let line = before.iter().filter(|b| **b == b'\n').count() + 1;
+    let start = before
+        .iter()
+        .rposition(|b| *b == b'\n')
+        .map_or(0, |i| i + 1);
+    let col = String::from_utf8_lossy(&before[start..]).chars().count() + 1;
+    (line, col)
+}
+
+// The line holding byte `offset` with a caret under it, cut down to about
+// 60 chars around the caret.
+fn snippet(input: &[u8], offset: usize) -> String {
+    let offset = offset.min(input.len());
+    let start = input[..offset]
+        .iter()
+        .rposition(|b| *b == b'\n')
+        .map_or(0, |i| i + 1);
+    let end = input[offset..]
+        .iter()
+        .position(|b| *b == b'\n')
+        .map_or(input.len(), |i| offset + i);
+    let before: Vec<char> = String::from_utf8_lossy(&input[start..offset])
+        .chars()
+        .collect();
+    let after: Vec<char> = String::from_utf8_lossy(&input[offset..end])
+        .chars()
+        .collect();
+    let skip = before.len().saturating_sub(30);
+    let mut line: String = before[skip..].iter().collect();
+    let caret = before.len() - skip + usize::from(skip > 0) * 2;
+    if skip > 0 {
+        line.insert_str(0, "..");
+    }
+    line.extend(after.iter().take(30));
+    if after.len() > 30 {
+        line.push_str("..");
+    }
+    let line = line.replace('\t', " ").replace('\r', "");
+    format!("    {}\n    {}^\n", line, " ".repeat(caret))
+}
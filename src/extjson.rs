@@ -0,0 +1,337 @@
+use std::collections::HashMap;
+
+use bson::oid::ObjectId;
+use bson::spec::BinarySubtype;
+use bson::{
+    Binary, Bson, DateTime, Decimal128, Document, JavaScriptCodeWithScope, Regex, Timestamp,
+};
+use eyre::OptionExt;
+
+use crate::binary::{decode_base64, decode_hex, encode_base64, encode_hex};
+use crate::JSONValue;
+
+// Dates in this range, years 1970 through 9999, are ISO-8601 strings in
+// relaxed Extended JSON.
+const ISO_DATES: std::ops::RangeInclusive<i64> = 0..=253_402_300_799_999;
+// Integers up to this size fit in an `f64` exactly.
+const SAFE_INTEGER: i64 = 1 << 53;
+
+/// How `JSONValue::from_bson` writes the types JSON lacks, following
+/// MongoDB Extended JSON v2.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum ExtJsonMode {
+    /// Numbers as plain JSON numbers and dates from 1970 to 9999 as
+    /// ISO-8601 strings, for people and tools that don't know BSON. 64-bit
+    /// integers too big for an `f64` stay `{"$numberLong": ".."}`.
+    #[default]
+    Relaxed,
+    /// Every number and date in a type wrapper, e.g. `{"$numberInt": "1"}`,
+    /// so converting back gives exactly the same BSON.
+    Canonical,
+}
+
+impl JSONValue {
+    /// This value as BSON. Objects that are Extended JSON type wrappers, in
+    /// either mode, become the type they stand for, e.g. `{"$oid": ".."}`
+    /// an `ObjectId` and `{"$date": ".."}` a `DateTime`. Whole numbers
+    /// become `Int32` or `Int64` when they fit, other numbers `Double`, and
+    /// object keys are written in sorted order.
+    pub fn to_bson(&self) -> eyre::Result<Bson> {
+        Ok(match self {
+            JSONValue::Null => Bson::Null,
+            JSONValue::Bool(b) => Bson::Boolean(*b),
+            JSONValue::Num(n) => number(*n),
+            JSONValue::Str(s) => Bson::String(s.clone()),
+            JSONValue::Array(entries) => Bson::Array(
+                entries
+                    .iter()
+                    .map(JSONValue::to_bson)
+                    .collect::<eyre::Result<_>>()?,
+            ),
+            JSONValue::Dict(d) => match wrapped(d)? {
+                Some(bson) => bson,
+                None => Bson::Document(document(d)?),
+            },
+        })
+    }
+
+    /// Like `to_bson`, for an object, giving a document ready for
+    /// `Document::to_writer`.
+    pub fn to_bson_document(&self) -> eyre::Result<Document> {
+        match self.to_bson()? {
+            Bson::Document(doc) => Ok(doc),
+            other => eyre::bail!(format!(
+                "Expected an object, found {:?}",
+                other.element_type()
+            )),
+        }
+    }
+
+    /// `value` as Extended JSON in the given mode.
+    pub fn from_bson(value: &Bson, mode: ExtJsonMode) -> JSONValue {
+        let canonical = mode == ExtJsonMode::Canonical;
+        match value {
+            Bson::Null => JSONValue::Null,
+            Bson::Boolean(b) => JSONValue::Bool(*b),
+            Bson::String(s) => JSONValue::Str(s.clone()),
+            Bson::Double(n) if n.is_finite() && !canonical => JSONValue::Num(*n),
+            Bson::Double(n) => wrap("$numberDouble", JSONValue::Str(double(*n))),
+            Bson::Int32(n) if !canonical => JSONValue::Num(*n as f64),
+            Bson::Int32(n) => wrap("$numberInt", JSONValue::Str(n.to_string())),
+            Bson::Int64(n) if !canonical && n.abs() <= SAFE_INTEGER => JSONValue::Num(*n as f64),
+            Bson::Int64(n) => wrap("$numberLong", JSONValue::Str(n.to_string())),
+            Bson::Decimal128(d) => wrap("$numberDecimal", JSONValue::Str(d.to_string())),
+            Bson::Array(entries) => JSONValue::Array(
+                entries
+                    .iter()
+                    .map(|e| JSONValue::from_bson(e, mode))
+                    .collect(),
+            ),
+            Bson::Document(doc) => JSONValue::Dict(
+                doc.iter()
+                    .map(|(k, v)| (k.clone(), JSONValue::from_bson(v, mode)))
+                    .collect(),
+            ),
+            Bson::ObjectId(id) => wrap("$oid", JSONValue::Str(id.to_hex())),
+            Bson::DateTime(date) => {
+                let ms = date.timestamp_millis();
+                let iso = date.try_to_rfc3339_string().ok();
+                match iso.filter(|_| !canonical && ISO_DATES.contains(&ms)) {
+                    Some(iso) => wrap("$date", JSONValue::Str(iso)),
+                    None => wrap("$date", wrap("$numberLong", JSONValue::Str(ms.to_string()))),
+                }
+            }
+            Bson::Binary(binary) => wrap(
+                "$binary",
+                object([
+                    ("base64", JSONValue::Str(encode_base64(&binary.bytes))),
+                    (
+                        "subType",
+                        JSONValue::Str(encode_hex(&[u8::from(binary.subtype)])),
+                    ),
+                ]),
+            ),
+            Bson::RegularExpression(regex) => wrap(
+                "$regularExpression",
+                object([
+                    ("pattern", JSONValue::Str(regex.pattern.clone())),
+                    ("options", JSONValue::Str(regex.options.clone())),
+                ]),
+            ),
+            Bson::Timestamp(ts) => wrap(
+                "$timestamp",
+                object([
+                    ("t", JSONValue::Num(ts.time as f64)),
+                    ("i", JSONValue::Num(ts.increment as f64)),
+                ]),
+            ),
+            Bson::JavaScriptCode(code) => wrap("$code", JSONValue::Str(code.clone())),
+            Bson::JavaScriptCodeWithScope(code) => object([
+                ("$code", JSONValue::Str(code.code.clone())),
+                (
+                    "$scope",
+                    JSONValue::from_bson(&Bson::Document(code.scope.clone()), mode),
+                ),
+            ]),
+            Bson::Symbol(s) => wrap("$symbol", JSONValue::Str(s.clone())),
+            Bson::MinKey => wrap("$minKey", JSONValue::Num(1.0)),
+            Bson::MaxKey => wrap("$maxKey", JSONValue::Num(1.0)),
+            Bson::Undefined => wrap("$undefined", JSONValue::Bool(true)),
+            Bson::DbPointer(_) => match db_pointer_parts(value) {
+                Some((namespace, id)) => wrap(
+                    "$dbPointer",
+                    object([
+                        ("$ref", JSONValue::Str(namespace)),
+                        ("$id", wrap("$oid", JSONValue::Str(id.to_hex()))),
+                    ]),
+                ),
+                None => JSONValue::Null,
+            },
+        }
+    }
+}
+
+fn object<const N: usize>(entries: [(&str, JSONValue); N]) -> JSONValue {
+    JSONValue::Dict(entries.map(|(k, v)| (k.to_string(), v)).into())
+}
+
+fn wrap(key: &str, value: JSONValue) -> JSONValue {
+    object([(key, value)])
+}
+
+fn number(n: f64) -> Bson {
+    if n.fract() != 0.0 || !n.is_finite() || (n == 0.0 && n.is_sign_negative()) {
+        Bson::Double(n)
+    } else if n >= i32::MIN as f64 && n <= i32::MAX as f64 {
+        Bson::Int32(n as i32)
+    } else if n.abs() < i64::MAX as f64 {
+        Bson::Int64(n as i64)
+    } else {
+        Bson::Double(n)
+    }
+}
+
+// Extended JSON's spelling of a double: `1.0`, `-0.0`, `Infinity`, `NaN`.
+fn double(n: f64) -> String {
+    match n {
+        f64::INFINITY => "Infinity".to_string(),
+        f64::NEG_INFINITY => "-Infinity".to_string(),
+        n if n.is_nan() => "NaN".to_string(),
+        n => format!("{:?}", n),
+    }
+}
+
+fn document(d: &HashMap<String, JSONValue>) -> eyre::Result<Document> {
+    let mut keys: Vec<_> = d.keys().collect();
+    keys.sort_unstable();
+    let mut doc = Document::new();
+    for k in keys {
+        doc.insert(k.clone(), d[k].to_bson()?);
+    }
+    Ok(doc)
+}
+
+fn string<'v>(v: &'v JSONValue, what: &str) -> eyre::Result<&'v str> {
+    match v {
+        JSONValue::Str(s) => Ok(s),
+        _ => eyre::bail!(format!("Expected {} to be a string", what)),
+    }
+}
+
+fn field<'v>(v: &'v JSONValue, key: &str) -> eyre::Result<&'v JSONValue> {
+    match v {
+        JSONValue::Dict(d) => d
+            .get(key)
+            .ok_or_else(|| eyre::eyre!(format!("Missing {:?}", key))),
+        _ => eyre::bail!(format!("Expected an object with {:?}", key)),
+    }
+}
+
+fn u32_field(v: &JSONValue, key: &str) -> eyre::Result<u32> {
+    match field(v, key)? {
+        JSONValue::Num(n) if n.fract() == 0.0 && (0.0..=u32::MAX as f64).contains(n) => {
+            Ok(*n as u32)
+        }
+        _ => eyre::bail!(format!(
+            "Expected {:?} to be a 32-bit unsigned integer",
+            key
+        )),
+    }
+}
+
+// The BSON an Extended JSON type wrapper stands for, or `None` for an
+// ordinary object.
+fn wrapped(d: &HashMap<String, JSONValue>) -> eyre::Result<Option<Bson>> {
+    let mut keys: Vec<&str> = d.keys().map(String::as_str).collect();
+    keys.sort_unstable();
+    let Some(first) = keys.first().copied() else {
+        return Ok(None);
+    };
+    unwrap(&keys, d).map_err(|e| eyre::eyre!(format!("Invalid {}: {}", first, e)))
+}
+
+fn unwrap(keys: &[&str], d: &HashMap<String, JSONValue>) -> eyre::Result<Option<Bson>> {
+    let first = keys[0];
+    let value = &d[first];
+    let bson = match keys {
+        ["$oid"] => Bson::ObjectId(ObjectId::parse_str(string(value, first)?)?),
+        ["$numberInt"] => Bson::Int32(string(value, first)?.parse()?),
+        ["$numberLong"] => Bson::Int64(string(value, first)?.parse()?),
+        ["$numberDouble"] => Bson::Double(match string(value, first)? {
+            "Infinity" => f64::INFINITY,
+            "-Infinity" => f64::NEG_INFINITY,
+            "NaN" => f64::NAN,
+            s => s.parse()?,
+        }),
+        ["$numberDecimal"] => Bson::Decimal128(string(value, first)?.parse::<Decimal128>()?),
+        ["$date"] => Bson::DateTime(date(value)?),
+        ["$binary"] => Bson::Binary(Binary {
+            subtype: match decode_hex(string(field(value, "subType")?, "subType")?)?[..] {
+                [subtype] => BinarySubtype::from(subtype),
+                _ => eyre::bail!("Expected subType to be one byte of hex"),
+            },
+            bytes: decode_base64(string(field(value, "base64")?, "base64")?)?,
+        }),
+        ["$uuid"] => {
+            let bytes = decode_hex(&string(value, first)?.replace('-', ""))?;
+            if bytes.len() != 16 {
+                eyre::bail!("Expected $uuid to be 16 bytes")
+            }
+            Bson::Binary(Binary {
+                subtype: BinarySubtype::Uuid,
+                bytes,
+            })
+        }
+        ["$regularExpression"] => {
+            let mut options: Vec<char> = string(field(value, "options")?, "options")?
+                .chars()
+                .collect();
+            options.sort_unstable();
+            Bson::RegularExpression(Regex {
+                pattern: string(field(value, "pattern")?, "pattern")?.to_string(),
+                options: options.into_iter().collect(),
+            })
+        }
+        ["$timestamp"] => Bson::Timestamp(Timestamp {
+            time: u32_field(value, "t")?,
+            increment: u32_field(value, "i")?,
+        }),
+        ["$code"] => Bson::JavaScriptCode(string(value, first)?.to_string()),
+        ["$code", "$scope"] => Bson::JavaScriptCodeWithScope(JavaScriptCodeWithScope {
+            code: string(value, first)?.to_string(),
+            scope: d["$scope"].to_bson_document()?,
+        }),
+        ["$symbol"] => Bson::Symbol(string(value, first)?.to_string()),
+        ["$minKey"] => Bson::MinKey,
+        ["$maxKey"] => Bson::MaxKey,
+        ["$undefined"] => Bson::Undefined,
+        ["$dbPointer"] => {
+            let namespace = string(field(value, "$ref")?, "$ref")?;
+            match field(value, "$id")?.to_bson()? {
+                Bson::ObjectId(id) => db_pointer(namespace, id)?,
+                _ => eyre::bail!("Expected $id to be an $oid"),
+            }
+        }
+        _ => return Ok(None),
+    };
+    Ok(Some(bson))
+}
+
+fn date(v: &JSONValue) -> eyre::Result<DateTime> {
+    Ok(match v {
+        JSONValue::Str(iso) => DateTime::parse_rfc3339_str(iso)?,
+        // the legacy form, milliseconds as a plain number
+        JSONValue::Num(ms) if ms.fract() == 0.0 => DateTime::from_millis(*ms as i64),
+        v => DateTime::from_millis(string(field(v, "$numberLong")?, "$numberLong")?.parse()?),
+    })
+}
+
+// `DbPointer` keeps its parts private, so they go through its encoding in a
+// one-element document: the document's size, the element type, an empty
+// name, then the namespace as a length-prefixed string and the 12-byte id.
+fn db_pointer(namespace: &str, id: ObjectId) -> eyre::Result<Bson> {
+    let mut bytes = vec![0; 4];
+    bytes.extend([0x0c, 0]);
+    bytes.extend((namespace.len() as i32 + 1).to_le_bytes());
+    bytes.extend(namespace.as_bytes());
+    bytes.push(0);
+    bytes.extend(id.bytes());
+    bytes.push(0);
+    let size = bytes.len() as i32;
+    bytes[..4].copy_from_slice(&size.to_le_bytes());
+    Document::from_reader(bytes.as_slice())?
+        .remove("")
+        .ok_or_eyre("Invalid $dbPointer")
+}
+
+fn db_pointer_parts(pointer: &Bson) -> Option<(String, ObjectId)> {
+    let mut doc = Document::new();
+    doc.insert("", pointer.clone());
+    let mut bytes = Vec::new();
+    doc.to_writer(&mut bytes).ok()?;
+    let rest = bytes.get(6..)?;
+    let len = i32::from_le_bytes(rest.get(..4)?.try_into().ok()?) as usize;
+    let namespace = std::str::from_utf8(rest.get(4..(4 + len).checked_sub(1)?)?).ok()?;
+    let id: [u8; 12] = rest.get(4 + len..4 + len + 12)?.try_into().ok()?;
+    Some((namespace.to_string(), ObjectId::from_bytes(id)))
+}
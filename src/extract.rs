@@ -0,0 +1,270 @@
+use core::fmt;
+use std::collections::{BTreeMap, HashMap};
+
+use crate::{patch, pointer, JSONValue};
+
+/// Types a parsed `JSONValue` converts to, for `as_vec_of` and the other
+/// typed accessors. Numbers convert to integer types only when whole and in
+/// range.
+pub trait FromValue: Sized {
+    fn from_value(v: &JSONValue) -> Result<Self, ValueMismatch>;
+}
+
+/// Why a value didn't convert: the JSON Pointer (relative to the value
+/// converted) of the part that didn't fit, what was expected there and what
+/// was found.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValueMismatch {
+    pub pointer: String,
+    pub expected: String,
+    pub found: String,
+}
+
+impl ValueMismatch {
+    /// `found` where `expected` (e.g. "a string") was wanted, for
+    /// `FromValue` implementations.
+    pub fn new(expected: impl Into<String>, found: &JSONValue) -> Self {
+        Self {
+            pointer: String::new(),
+            expected: expected.into(),
+            found: describe(found),
+        }
+    }
+
+    /// The same mismatch seen from the container holding it at `token` (a
+    /// key or an index).
+    pub fn within(mut self, token: &str) -> Self {
+        let mut ptr = String::new();
+        pointer::push(&mut ptr, token);
+        self.pointer.insert_str(0, &ptr);
+        self
+    }
+}
+
+impl fmt::Display for ValueMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.pointer.is_empty() {
+            write!(f, "expected {}, found {}", self.expected, self.found)
+        } else {
+            write!(
+                f,
+                "expected {} at {}, found {}",
+                self.expected, self.pointer, self.found
+            )
+        }
+    }
+}
+
+impl std::error::Error for ValueMismatch {}
+
+// Scalars as written, containers by kind.
+fn describe(v: &JSONValue) -> String {
+    match v {
+        JSONValue::Array(_) => "an array".to_string(),
+        JSONValue::Dict(_) => "an object".to_string(),
+        v => v.to_json_string(),
+    }
+}
+
+impl FromValue for JSONValue {
+    fn from_value(v: &JSONValue) -> Result<Self, ValueMismatch> {
+        Ok(v.clone())
+    }
+}
+
+impl FromValue for bool {
+    fn from_value(v: &JSONValue) -> Result<Self, ValueMismatch> {
+        match v {
+            JSONValue::Bool(b) => Ok(*b),
+            v => Err(ValueMismatch::new("a boolean", v)),
+        }
+    }
+}
+
+impl FromValue for String {
+    fn from_value(v: &JSONValue) -> Result<Self, ValueMismatch> {
+        match v {
+            JSONValue::Str(s) => Ok(s.clone()),
+            v => Err(ValueMismatch::new("a string", v)),
+        }
+    }
+}
+
+impl FromValue for f64 {
+    fn from_value(v: &JSONValue) -> Result<Self, ValueMismatch> {
+        match v {
+            JSONValue::Num(n) => Ok(*n),
+            v => Err(ValueMismatch::new("a number", v)),
+        }
+    }
+}
+
+impl FromValue for f32 {
+    fn from_value(v: &JSONValue) -> Result<Self, ValueMismatch> {
+        f64::from_value(v).map(|n| n as f32)
+    }
+}
+
+macro_rules! from_value_int {
+    ($($t:ty),*) => {
+        $(
+            impl FromValue for $t {
+                fn from_value(v: &JSONValue) -> Result<Self, ValueMismatch> {
+                    let expected = concat!("an integer in ", stringify!($t), " range");
+                    match v {
+                        // `as` saturates, so huge numbers still fail `try_from`
+                        JSONValue::Num(n) if n.is_finite() && n.fract() == 0.0 => {
+                            <$t>::try_from(*n as i128)
+                                .map_err(|_| ValueMismatch::new(expected, v))
+                        }
+                        v => Err(ValueMismatch::new(expected, v)),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+from_value_int!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+
+impl<T: FromValue> FromValue for Option<T> {
+    fn from_value(v: &JSONValue) -> Result<Self, ValueMismatch> {
+        match v {
+            JSONValue::Null => Ok(None),
+            v => T::from_value(v).map(Some),
+        }
+    }
+}
+
+impl<T: FromValue> FromValue for Vec<T> {
+    fn from_value(v: &JSONValue) -> Result<Self, ValueMismatch> {
+        match v {
+            JSONValue::Array(a) => a
+                .iter()
+                .enumerate()
+                .map(|(i, v)| T::from_value(v).map_err(|e| e.within(&i.to_string())))
+                .collect(),
+            v => Err(ValueMismatch::new("an array", v)),
+        }
+    }
+}
+
+impl<T: FromValue> FromValue for HashMap<String, T> {
+    fn from_value(v: &JSONValue) -> Result<Self, ValueMismatch> {
+        match v {
+            JSONValue::Dict(d) => d
+                .iter()
+                .map(|(k, v)| Ok((k.clone(), T::from_value(v).map_err(|e| e.within(k))?)))
+                .collect(),
+            v => Err(ValueMismatch::new("an object", v)),
+        }
+    }
+}
+
+impl<T: FromValue> FromValue for BTreeMap<String, T> {
+    fn from_value(v: &JSONValue) -> Result<Self, ValueMismatch> {
+        match v {
+            JSONValue::Dict(d) => d
+                .iter()
+                .map(|(k, v)| Ok((k.clone(), T::from_value(v).map_err(|e| e.within(k))?)))
+                .collect(),
+            v => Err(ValueMismatch::new("an object", v)),
+        }
+    }
+}
+
+impl JSONValue {
+    /// The value of `key` in an object; `None` for a missing key or a value
+    /// that isn't an object.
+    pub fn get(&self, key: &str) -> Option<&JSONValue> {
+        match self {
+            JSONValue::Dict(d) => d.get(key),
+            _ => None,
+        }
+    }
+
+    /// The value at the JSON Pointer `ptr`, e.g. `/servers/0/port`; `None` if
+    /// there is none or `ptr` isn't a valid pointer.
+    pub fn pointer(&self, ptr: &str) -> Option<&JSONValue> {
+        patch::get(self, ptr).ok()
+    }
+
+    /// The value down `path` (object keys, or indices into arrays)
+    /// converted to `T`, or `None` if any step is missing or it doesn't
+    /// convert. `v.try_get::<u16>(&["server", "port"])` saves a chain of
+    /// `and_then`s.
+    pub fn try_get<T: FromValue>(&self, path: &[&str]) -> Option<T> {
+        let mut v = self;
+        for step in path {
+            v = match v {
+                JSONValue::Dict(d) => d.get(*step)?,
+                JSONValue::Array(a) => a.get(pointer::index(step, a.len())?)?,
+                _ => return None,
+            };
+        }
+        T::from_value(v).ok()
+    }
+
+    /// The value of `key` converted to `T`, or `default` if it is missing,
+    /// `null` or doesn't convert.
+    pub fn get_or<T: FromValue>(&self, key: &str, default: T) -> T {
+        self.try_get(&[key]).unwrap_or(default)
+    }
+
+    /// Like `get_or`, for the value at the JSON Pointer `ptr`.
+    pub fn pointer_or<T: FromValue>(&self, ptr: &str, default: T) -> T {
+        self.pointer(ptr)
+            .and_then(|v| T::from_value(v).ok())
+            .unwrap_or(default)
+    }
+
+    /// The string, or `fallback` for any other value.
+    pub fn as_str_or<'a>(&'a self, fallback: &'a str) -> &'a str {
+        match self {
+            JSONValue::Str(s) => s,
+            _ => fallback,
+        }
+    }
+
+    /// The number, or `fallback` for any other value.
+    pub fn as_f64_or(&self, fallback: f64) -> f64 {
+        match self {
+            JSONValue::Num(n) => *n,
+            _ => fallback,
+        }
+    }
+
+    /// The number if it is a whole `i64`, or `fallback`.
+    pub fn as_i64_or(&self, fallback: i64) -> i64 {
+        i64::from_value(self).unwrap_or(fallback)
+    }
+
+    /// The boolean, or `fallback` for any other value.
+    pub fn as_bool_or(&self, fallback: bool) -> bool {
+        match self {
+            JSONValue::Bool(b) => *b,
+            _ => fallback,
+        }
+    }
+
+    /// The elements of an array converted to `T`, e.g.
+    /// `v.as_vec_of::<f64>()`. The error names the index and pointer of
+    /// the first element that doesn't convert.
+    pub fn as_vec_of<T: FromValue>(&self) -> eyre::Result<Vec<T>> {
+        let JSONValue::Array(a) = self else {
+            eyre::bail!(format!("Expected an array, found {}", describe(self)))
+        };
+        a.iter()
+            .enumerate()
+            .map(|(i, v)| {
+                T::from_value(v).map_err(|e| {
+                    let e = e.within(&i.to_string());
+                    eyre::eyre!(format!(
+                        "Element {} at {}: expected {}, found {}",
+                        i, e.pointer, e.expected, e.found
+                    ))
+                })
+            })
+            .collect()
+    }
+}
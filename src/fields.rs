@@ -0,0 +1,100 @@
+use crate::{JSONValue, ValueMismatch};
+
+/// Structs whose fields `map_into` can set by name, usually implemented
+/// with `field_set!`.
+pub trait FieldSet {
+    /// Sets the field `key` names from `value`, returning `Ok(false)` if no
+    /// field matches.
+    fn set_field(&mut self, key: &str, value: &JSONValue) -> Result<bool, ValueMismatch>;
+}
+
+/// Whether the object key `key` names the field `field`, ignoring case and
+/// `_`/`-`, so `hostName`, `host-name` and `HOST_NAME` all match `host_name`.
+pub fn field_matches(field: &str, key: &str) -> bool {
+    let fold = |s: &str| {
+        s.chars()
+            .filter(|c| *c != '_' && *c != '-')
+            .flat_map(char::to_lowercase)
+            .collect::<String>()
+    };
+    fold(field) == fold(key)
+}
+
+/// Builds a `T` from an object, starting from `T::default()` and setting the
+/// field each member names; members without a field are skipped, and
+/// fields without a member keep their default. Lighter than deriving
+/// `FromJson` when a struct only needs filling in from a parsed value.
+pub fn map_into<T: Default + FieldSet>(value: &JSONValue) -> eyre::Result<T> {
+    map_fields(value).map_err(|e| match e.pointer.is_empty() {
+        true => eyre::eyre!(format!("Expected {}, found {}", e.expected, e.found)),
+        false => eyre::eyre!(format!(
+            "Member at {}: expected {}, found {}",
+            e.pointer, e.expected, e.found
+        )),
+    })
+}
+
+/// Like `map_into`, with the error as a `ValueMismatch`; `field_set!` uses
+/// it so mapped structs nest.
+pub fn map_fields<T: Default + FieldSet>(value: &JSONValue) -> Result<T, ValueMismatch> {
+    let JSONValue::Dict(d) = value else {
+        return Err(ValueMismatch::new("an object", value));
+    };
+    let mut out = T::default();
+    // in key order, so which of two matching keys wins doesn't depend on
+    // hashing
+    let mut entries: Vec<_> = d.iter().collect();
+    entries.sort_unstable_by(|a, b| a.0.cmp(b.0));
+    for (key, v) in entries {
+        out.set_field(key, v).map_err(|e| e.within(key))?;
+    }
+    Ok(out)
+}
+
+/// Implements `FieldSet` (and `FromValue`, so such structs nest) for a
+/// struct, listing the fields to fill. Each field's type must implement
+/// `FromValue`.
+///
+/// ```
+/// use json_parser::{field_set, map_into, parse};
+///
+/// #[derive(Default)]
+/// struct Server {
+///     host_name: String,
+///     port: u16,
+///     tags: Vec<String>,
+/// }
+/// field_set!(Server { host_name, port, tags });
+///
+/// let server: Server = map_into(&parse(br#"{"hostName": "a", "port": 80}"#)?)?;
+/// assert_eq!((server.host_name.as_str(), server.port), ("a", 80));
+/// # Ok::<(), eyre::Report>(())
+/// ```
+#[macro_export]
+macro_rules! field_set {
+    ($t:ty { $($field:ident),* $(,)? }) => {
+        impl $crate::FieldSet for $t {
+            fn set_field(
+                &mut self,
+                key: &str,
+                value: &$crate::JSONValue,
+            ) -> ::core::result::Result<bool, $crate::ValueMismatch> {
+                $(
+                    if $crate::field_matches(::core::stringify!($field), key) {
+                        self.$field = $crate::FromValue::from_value(value)?;
+                        return ::core::result::Result::Ok(true);
+                    }
+                )*
+                ::core::result::Result::Ok(false)
+            }
+        }
+
+        impl $crate::FromValue for $t {
+            fn from_value(
+                value: &$crate::JSONValue,
+            ) -> ::core::result::Result<Self, $crate::ValueMismatch> {
+                $crate::map_fields(value)
+            }
+        }
+    };
+}
@@ -0,0 +1,178 @@
+use core::fmt::{self, Write};
+
+use crate::serialize::{self, SerializeOptions};
+use crate::JSONValue;
+
+/// Hooks driven by `JSONValue::write_formatted`, one per piece of output.
+/// Every method has a default producing compact JSON, so an implementation
+/// only overrides what its style changes.
+pub trait JsonFormat {
+    fn write_null<W: ?Sized + Write>(&mut self, out: &mut W) -> fmt::Result {
+        out.write_str("null")
+    }
+
+    fn write_bool<W: ?Sized + Write>(&mut self, out: &mut W, b: bool) -> fmt::Result {
+        out.write_str(if b { "true" } else { "false" })
+    }
+
+    fn write_number<W: ?Sized + Write>(&mut self, out: &mut W, n: f64) -> fmt::Result {
+        serialize::write_number(out, n)
+    }
+
+    fn write_string<W: ?Sized + Write>(&mut self, out: &mut W, s: &str) -> fmt::Result {
+        serialize::write_string(out, s, &SerializeOptions::default())
+    }
+
+    fn write_key<W: ?Sized + Write>(&mut self, out: &mut W, key: &str) -> fmt::Result {
+        self.write_string(out, key)
+    }
+
+    fn begin_array<W: ?Sized + Write>(&mut self, out: &mut W) -> fmt::Result {
+        out.write_char('[')
+    }
+
+    fn end_array<W: ?Sized + Write>(&mut self, out: &mut W) -> fmt::Result {
+        out.write_char(']')
+    }
+
+    fn begin_object<W: ?Sized + Write>(&mut self, out: &mut W) -> fmt::Result {
+        out.write_char('{')
+    }
+
+    fn end_object<W: ?Sized + Write>(&mut self, out: &mut W) -> fmt::Result {
+        out.write_char('}')
+    }
+
+    /// Between two entries of an array or object.
+    fn write_comma<W: ?Sized + Write>(&mut self, out: &mut W) -> fmt::Result {
+        out.write_char(',')
+    }
+
+    /// Between a key and its value.
+    fn write_colon<W: ?Sized + Write>(&mut self, out: &mut W) -> fmt::Result {
+        out.write_char(':')
+    }
+
+    /// After the last entry of a non-empty array or object, e.g. for a
+    /// trailing comma.
+    fn after_last_entry<W: ?Sized + Write>(&mut self, out: &mut W) -> fmt::Result {
+        let _ = out;
+        Ok(())
+    }
+
+    /// Before each entry of a non-empty array or object (at `depth` = its
+    /// nesting level) and before its closing bracket (at the container's).
+    fn indent<W: ?Sized + Write>(&mut self, out: &mut W, depth: usize) -> fmt::Result {
+        let _ = (out, depth);
+        Ok(())
+    }
+
+    /// Puts object entries in the order they are written; hash map order by
+    /// default.
+    fn order_entries(&mut self, entries: &mut [(&String, &JSONValue)]) {
+        let _ = entries;
+    }
+}
+
+/// Compact JSON, the trait's defaults.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CompactFormat;
+
+impl JsonFormat for CompactFormat {}
+
+/// One entry per line, indented by `indent` spaces per level, with sorted
+/// keys.
+#[derive(Debug, Clone, Copy)]
+pub struct PrettyFormat {
+    pub indent: usize,
+}
+
+impl Default for PrettyFormat {
+    fn default() -> Self {
+        Self { indent: 2 }
+    }
+}
+
+impl JsonFormat for PrettyFormat {
+    fn write_colon<W: ?Sized + Write>(&mut self, out: &mut W) -> fmt::Result {
+        out.write_str(": ")
+    }
+
+    fn indent<W: ?Sized + Write>(&mut self, out: &mut W, depth: usize) -> fmt::Result {
+        out.write_char('\n')?;
+        for _ in 0..depth * self.indent {
+            out.write_char(' ')?;
+        }
+        Ok(())
+    }
+
+    fn order_entries(&mut self, entries: &mut [(&String, &JSONValue)]) {
+        entries.sort_unstable_by(|a, b| a.0.cmp(b.0));
+    }
+}
+
+impl JSONValue {
+    /// Writes this value, calling back into `format` for every piece.
+    pub fn write_formatted<W: Write, F: JsonFormat>(
+        &self,
+        out: &mut W,
+        format: &mut F,
+    ) -> fmt::Result {
+        write_formatted(self, out, format, 0)
+    }
+
+    pub fn to_string_formatted<F: JsonFormat>(&self, format: &mut F) -> String {
+        let mut out = String::new();
+        self.write_formatted(&mut out, format)
+            .expect("writing to a String doesn't fail");
+        out
+    }
+}
+
+fn write_formatted<W: Write, F: JsonFormat>(
+    v: &JSONValue,
+    out: &mut W,
+    format: &mut F,
+    depth: usize,
+) -> fmt::Result {
+    match v {
+        JSONValue::Null => format.write_null(out),
+        JSONValue::Bool(b) => format.write_bool(out, *b),
+        JSONValue::Num(n) => format.write_number(out, *n),
+        JSONValue::Str(s) => format.write_string(out, s),
+        JSONValue::Array(entries) => {
+            format.begin_array(out)?;
+            for (i, e) in entries.iter().enumerate() {
+                if i > 0 {
+                    format.write_comma(out)?;
+                }
+                format.indent(out, depth + 1)?;
+                write_formatted(e, out, format, depth + 1)?;
+            }
+            if !entries.is_empty() {
+                format.after_last_entry(out)?;
+                format.indent(out, depth)?;
+            }
+            format.end_array(out)
+        }
+        JSONValue::Dict(d) => {
+            let mut entries: Vec<_> = d.iter().collect();
+            format.order_entries(&mut entries);
+            format.begin_object(out)?;
+            for (i, (k, e)) in entries.iter().enumerate() {
+                if i > 0 {
+                    format.write_comma(out)?;
+                }
+                format.indent(out, depth + 1)?;
+                format.write_key(out, k)?;
+                format.write_colon(out)?;
+                write_formatted(e, out, format, depth + 1)?;
+            }
+            if !entries.is_empty() {
+                format.after_last_entry(out)?;
+                format.indent(out, depth)?;
+            }
+            format.end_object(out)
+        }
+    }
+}
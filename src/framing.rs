@@ -0,0 +1,266 @@
+use std::io::{BufRead, ErrorKind, Read};
+
+use crate::{parse, IncrementalParser, JSONValue};
+
+/// One Server-Sent Events message whose `data:` payload was parsed as JSON.
+#[derive(Debug)]
+pub struct SseEvent {
+    pub event: Option<String>,
+    pub id: Option<String>,
+    pub data: JSONValue,
+}
+
+/// Reads Server-Sent Events from `reader`, parsing the (joined) `data:` lines
+/// of each event as a JSON document. Each line is fed to an
+/// `IncrementalParser` as it is read, so a payload that can't be JSON fails
+/// at the line that breaks it. Events without data are skipped.
+pub struct SseReader<R> {
+    reader: R,
+    stop_at: Option<String>,
+    done: bool,
+}
+
+// The `data:` lines of one event so far.
+struct Payload {
+    parser: IncrementalParser,
+    // lines held back while they could still be the sentinel
+    held: Option<String>,
+    lines: usize,
+}
+
+impl Payload {
+    fn new(sentinel: Option<&str>) -> Self {
+        Self {
+            parser: IncrementalParser::new(),
+            held: sentinel.map(|_| String::new()),
+            lines: 0,
+        }
+    }
+
+    fn push_line(&mut self, line: &str, sentinel: Option<&str>) -> eyre::Result<()> {
+        let sep = if self.lines > 0 { "\n" } else { "" };
+        self.lines += 1;
+        match &mut self.held {
+            Some(held) => {
+                held.push_str(sep);
+                held.push_str(line);
+                if !sentinel.is_some_and(|s| s.starts_with(held.as_str())) {
+                    let held = self.held.take().unwrap_or_default();
+                    self.parser.feed(held.as_bytes())?;
+                }
+            }
+            None => {
+                self.parser.feed(sep.as_bytes())?;
+                self.parser.feed(line.as_bytes())?;
+            }
+        }
+        Ok(())
+    }
+
+    fn is_sentinel(&self, sentinel: Option<&str>) -> bool {
+        self.held.is_some() && self.held.as_deref() == sentinel
+    }
+
+    fn finish(mut self) -> eyre::Result<JSONValue> {
+        if let Some(held) = self.held.take() {
+            self.parser.feed(held.as_bytes())?;
+        }
+        // the event ends here, so end any number cut off at the last line
+        self.parser.feed(b"\n")?;
+        if !self.parser.is_done() {
+            eyre::bail!("Event data ends before the JSON document does")
+        }
+        if !self.parser.buffer().iter().all(u8::is_ascii_whitespace) {
+            eyre::bail!("Unexpected data after the JSON document in an event")
+        }
+        Ok(self.parser.finish().expect("checked is_done"))
+    }
+}
+
+impl<R: BufRead> SseReader<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            stop_at: None,
+            done: false,
+        }
+    }
+
+    /// Ends the stream at the first event whose data equals `sentinel`
+    /// (e.g. `[DONE]`) instead of trying to parse it.
+    pub fn stop_at(mut self, sentinel: impl Into<String>) -> Self {
+        self.stop_at = Some(sentinel.into());
+        self
+    }
+
+    fn next_event(&mut self) -> eyre::Result<Option<SseEvent>> {
+        let mut event = None;
+        let mut id = None;
+        let mut data: Option<Payload> = None;
+        let mut line = String::new();
+        loop {
+            line.clear();
+            let eof = self.reader.read_line(&mut line)? == 0;
+            let l = line.trim_end_matches(['\n', '\r']);
+            if eof || l.is_empty() {
+                if let Some(data) = data.take() {
+                    if data.is_sentinel(self.stop_at.as_deref()) {
+                        return Ok(None);
+                    }
+                    let data = data.finish()?;
+                    return Ok(Some(SseEvent { event, id, data }));
+                }
+                if eof {
+                    return Ok(None);
+                }
+                event = None;
+                id = None;
+                continue;
+            }
+            // comment line
+            if l.starts_with(':') {
+                continue;
+            }
+            let (field, value) = match l.split_once(':') {
+                Some((f, v)) => (f, v.strip_prefix(' ').unwrap_or(v)),
+                None => (l, ""),
+            };
+            match field {
+                "data" => {
+                    let sentinel = self.stop_at.as_deref();
+                    data.get_or_insert_with(|| Payload::new(sentinel))
+                        .push_line(value, sentinel)?;
+                }
+                "event" => event = Some(value.to_string()),
+                "id" => id = Some(value.to_string()),
+                _ => {}
+            }
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for SseReader<R> {
+    type Item = eyre::Result<SseEvent>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let res = self.next_event().transpose();
+        if !matches!(res, Some(Ok(_))) {
+            self.done = true;
+        }
+        res
+    }
+}
+
+/// Width and byte order of the length prefix in front of every frame.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum LengthPrefix {
+    U16Be,
+    U16Le,
+    #[default]
+    U32Be,
+    U32Le,
+    U64Be,
+    U64Le,
+}
+
+impl LengthPrefix {
+    fn width(&self) -> usize {
+        match self {
+            LengthPrefix::U16Be | LengthPrefix::U16Le => 2,
+            LengthPrefix::U32Be | LengthPrefix::U32Le => 4,
+            LengthPrefix::U64Be | LengthPrefix::U64Le => 8,
+        }
+    }
+
+    fn decode(&self, b: &[u8]) -> u64 {
+        let mut bytes = [0u8; 8];
+        match self {
+            LengthPrefix::U16Be | LengthPrefix::U32Be | LengthPrefix::U64Be => {
+                bytes[8 - b.len()..].copy_from_slice(b);
+                u64::from_be_bytes(bytes)
+            }
+            _ => {
+                bytes[..b.len()].copy_from_slice(b);
+                u64::from_le_bytes(bytes)
+            }
+        }
+    }
+}
+
+/// Reads frames of `<length><payload>` from `reader`, parsing each payload as
+/// a JSON document.
+pub struct LengthPrefixedReader<R> {
+    reader: R,
+    prefix: LengthPrefix,
+    max_frame_len: usize,
+    buf: Vec<u8>,
+    done: bool,
+}
+
+impl<R: Read> LengthPrefixedReader<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            prefix: LengthPrefix::default(),
+            max_frame_len: 64 * 1024 * 1024,
+            buf: Vec::new(),
+            done: false,
+        }
+    }
+
+    pub fn prefix(mut self, prefix: LengthPrefix) -> Self {
+        self.prefix = prefix;
+        self
+    }
+
+    /// Frames announcing a larger payload are rejected before anything is
+    /// allocated for them.
+    pub fn max_frame_len(mut self, max: usize) -> Self {
+        self.max_frame_len = max;
+        self
+    }
+
+    fn next_frame(&mut self) -> eyre::Result<Option<JSONValue>> {
+        let width = self.prefix.width();
+        let mut len_buf = [0u8; 8];
+        // a clean EOF is only allowed before the first prefix byte
+        let mut read = 0;
+        while read < width {
+            match self.reader.read(&mut len_buf[read..width]) {
+                Ok(0) if read == 0 => return Ok(None),
+                Ok(0) => eyre::bail!("Truncated length prefix"),
+                Ok(n) => read += n,
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e.into()),
+            }
+        }
+        let len = self.prefix.decode(&len_buf[..width]);
+        if len > self.max_frame_len as u64 {
+            eyre::bail!(format!(
+                "Frame of {} bytes exceeds limit of {}",
+                len, self.max_frame_len
+            ));
+        }
+        self.buf.resize(len as usize, 0);
+        self.reader.read_exact(&mut self.buf)?;
+        parse(&self.buf).map(Some)
+    }
+}
+
+impl<R: Read> Iterator for LengthPrefixedReader<R> {
+    type Item = eyre::Result<JSONValue>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let res = self.next_frame().transpose();
+        if !matches!(res, Some(Ok(_))) {
+            self.done = true;
+        }
+        res
+    }
+}
@@ -0,0 +1,298 @@
+use std::collections::HashMap;
+
+use crate::schema::{Rules, Type};
+use crate::{JSONValue, Schema};
+
+/// SplitMix64: small, fast and good enough for test data. Not for secrets.
+#[derive(Debug, Clone)]
+pub(crate) struct Rng(u64);
+
+impl Rng {
+    pub(crate) fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in `0..n`; `n` must not be 0.
+    pub(crate) fn below(&mut self, n: u64) -> u64 {
+        self.next_u64() % n
+    }
+
+    /// Uniform in `low..=high`.
+    pub(crate) fn between(&mut self, low: u64, high: u64) -> u64 {
+        low + self.below(high - low + 1)
+    }
+
+    /// Uniform in `[0, 1)`.
+    pub(crate) fn unit(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    pub(crate) fn chance(&mut self, p: f64) -> bool {
+        self.unit() < p
+    }
+}
+
+/// How often each kind of value is picked, relative to the others. Arrays
+/// and objects are never picked at `GenerateOptions::max_depth`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct TypeWeights {
+    pub null: u32,
+    pub bool: u32,
+    pub number: u32,
+    pub string: u32,
+    pub array: u32,
+    pub object: u32,
+}
+
+impl Default for TypeWeights {
+    fn default() -> Self {
+        Self {
+            null: 1,
+            bool: 1,
+            number: 2,
+            string: 2,
+            array: 1,
+            object: 1,
+        }
+    }
+}
+
+/// Knobs for `Generator`.
+#[derive(Debug, Clone)]
+pub struct GenerateOptions {
+    /// The same seed and options always produce the same values.
+    pub seed: u64,
+    /// Arrays and objects nest at most this deep.
+    pub max_depth: usize,
+    /// Most elements or entries in one array or object.
+    pub max_len: usize,
+    /// Most characters in one string or key.
+    pub max_string_len: usize,
+    pub weights: TypeWeights,
+}
+
+impl Default for GenerateOptions {
+    fn default() -> Self {
+        Self {
+            seed: 0,
+            max_depth: 4,
+            max_len: 8,
+            max_string_len: 16,
+            weights: TypeWeights::default(),
+        }
+    }
+}
+
+// Picked from when generating strings: plain text plus characters that need
+// escaping or more than one UTF-8 byte.
+const CHARS: &[char] = &[
+    'a', 'b', 'c', 'x', 'y', 'z', 'A', 'Q', '0', '7', ' ', '-', '_', '.', '"', '\\', '/', '\n',
+    '\t', '\u{1}', '\u{7f}', 'é', 'ß', 'Ж', '中', '\u{2028}', '😀',
+];
+
+/// Produces arbitrary `JSONValue`s from a seed, e.g. for fuzzing downstream
+/// systems, load tests and property tests.
+#[derive(Debug, Clone)]
+pub struct Generator {
+    rng: Rng,
+    options: GenerateOptions,
+}
+
+impl Generator {
+    pub fn new(options: GenerateOptions) -> Self {
+        Self {
+            rng: Rng::new(options.seed),
+            options,
+        }
+    }
+
+    /// Any value, shaped by the options.
+    pub fn value(&mut self) -> JSONValue {
+        self.any(0)
+    }
+
+    /// A value that `schema` accepts. Sizes and depth the schema leaves open
+    /// follow the options.
+    pub fn value_for(&mut self, schema: &Schema) -> JSONValue {
+        self.conforming(schema.rules(), 0)
+    }
+
+    fn any(&mut self, depth: usize) -> JSONValue {
+        let w = self.options.weights;
+        let nested = depth < self.options.max_depth;
+        let weights = [
+            w.null,
+            w.bool,
+            w.number,
+            w.string,
+            if nested { w.array } else { 0 },
+            if nested { w.object } else { 0 },
+        ];
+        let total: u64 = weights.iter().map(|w| *w as u64).sum();
+        if total == 0 {
+            return JSONValue::Null;
+        }
+        let mut pick = self.rng.below(total);
+        let kind = weights
+            .iter()
+            .position(|w| match pick.checked_sub(*w as u64) {
+                Some(rest) => {
+                    pick = rest;
+                    false
+                }
+                None => true,
+            })
+            .expect("pick is below the total");
+        match kind {
+            0 => JSONValue::Null,
+            1 => JSONValue::Bool(self.rng.chance(0.5)),
+            2 => JSONValue::Num(self.number()),
+            3 => JSONValue::Str(self.string(0, self.options.max_string_len)),
+            4 => {
+                let len = self.rng.between(0, self.options.max_len as u64);
+                JSONValue::Array((0..len).map(|_| self.any(depth + 1)).collect())
+            }
+            _ => {
+                let len = self.rng.between(0, self.options.max_len as u64);
+                let mut d = HashMap::new();
+                for _ in 0..len {
+                    let key = self.string(0, self.options.max_string_len);
+                    d.insert(key, self.any(depth + 1));
+                }
+                JSONValue::Dict(d)
+            }
+        }
+    }
+
+    fn number(&mut self) -> f64 {
+        match self.rng.below(3) {
+            0 => self.rng.between(0, 2000) as f64 - 1000.0,
+            1 => (self.rng.unit() - 0.5) * 2e6,
+            // anything finite, including huge and tiny magnitudes
+            _ => loop {
+                let n = f64::from_bits(self.rng.next_u64());
+                if n.is_finite() {
+                    break n;
+                }
+            },
+        }
+    }
+
+    fn string(&mut self, min: usize, max: usize) -> String {
+        let len = self.rng.between(min as u64, max.max(min) as u64);
+        (0..len)
+            .map(|_| CHARS[self.rng.below(CHARS.len() as u64) as usize])
+            .collect()
+    }
+
+    fn conforming(&mut self, rules: &Rules, depth: usize) -> JSONValue {
+        if let Some(constant) = &rules.constant {
+            return constant.clone();
+        }
+        if let Some(values) = rules.one_of_values.as_ref().filter(|v| !v.is_empty()) {
+            return values[self.rng.below(values.len() as u64) as usize].clone();
+        }
+        let types = rules.types();
+        if types.is_empty() {
+            return self.any(depth);
+        }
+        match types[self.rng.below(types.len() as u64) as usize] {
+            Type::Null => JSONValue::Null,
+            Type::Boolean => JSONValue::Bool(self.rng.chance(0.5)),
+            Type::Integer => JSONValue::Num(self.number_in(rules, true)),
+            Type::Number => JSONValue::Num(self.number_in(rules, false)),
+            Type::String => {
+                let min = rules.min_length.unwrap_or(0);
+                let max = rules
+                    .max_length
+                    .unwrap_or(min + self.options.max_string_len);
+                JSONValue::Str(self.string(min, max))
+            }
+            Type::Array => {
+                let min = rules.min_items.unwrap_or(0);
+                let max = rules.max_items.unwrap_or(min + self.options.max_len);
+                let len = self.rng.between(min as u64, max.max(min) as u64);
+                let items = (0..len)
+                    .map(|_| match &rules.items {
+                        Some(items) => self.conforming(items, depth + 1),
+                        None => self.any(depth + 1),
+                    })
+                    .collect();
+                JSONValue::Array(items)
+            }
+            Type::Object => self.object_in(rules, depth),
+        }
+    }
+
+    fn number_in(&mut self, rules: &Rules, integer: bool) -> f64 {
+        let low = [rules.minimum, rules.exclusive_minimum.map(|n| n + 1.0)]
+            .into_iter()
+            .flatten()
+            .reduce(f64::max)
+            .unwrap_or(-1000.0);
+        let high = [rules.maximum, rules.exclusive_maximum.map(|n| n - 1.0)]
+            .into_iter()
+            .flatten()
+            .reduce(f64::min)
+            .unwrap_or(low.max(0.0) + 1000.0);
+        let step = match rules.multiple_of {
+            Some(step) => step,
+            None if integer => 1.0,
+            None => return low + self.rng.unit() * (high - low),
+        };
+        let (first, last) = ((low / step).ceil(), (high / step).floor());
+        if last < first {
+            return first * step;
+        }
+        let k = first + self.rng.below((last - first) as u64 + 1) as f64;
+        k * step
+    }
+
+    fn object_in(&mut self, rules: &Rules, depth: usize) -> JSONValue {
+        let mut d = HashMap::new();
+        let mut keys: Vec<_> = rules.properties.keys().collect();
+        keys.sort();
+        for key in keys {
+            let property = &rules.properties[key];
+            let room = rules.max_properties.is_none_or(|max| d.len() < max);
+            let wanted = rules.required.contains(key) || (room && self.rng.chance(0.5));
+            if property.never || !wanted {
+                continue;
+            }
+            d.insert(key.clone(), self.conforming(property, depth + 1));
+        }
+        for key in &rules.required {
+            if !d.contains_key(key) {
+                let value = match rules.property(key) {
+                    Some(rules) => self.conforming(rules, depth + 1),
+                    None => self.any(depth + 1),
+                };
+                d.insert(key.clone(), value);
+            }
+        }
+        let extra = rules.additional_properties.as_deref();
+        if extra.is_none_or(|r| !r.never) {
+            let min = rules.min_properties.unwrap_or(0);
+            while d.len() < min {
+                let key = self.string(1, self.options.max_string_len.max(1));
+                if rules.properties.contains_key(&key) || d.contains_key(&key) {
+                    continue;
+                }
+                let value = match extra {
+                    Some(rules) => self.conforming(rules, depth + 1),
+                    None => self.any(depth + 1),
+                };
+                d.insert(key, value);
+            }
+        }
+        JSONValue::Dict(d)
+    }
+}
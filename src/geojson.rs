@@ -0,0 +1,351 @@
+use std::collections::HashMap;
+
+use crate::lexer::TokenKind;
+use crate::{Decoder, FromJson, JSONValue};
+
+/// `[longitude, latitude]`, optionally followed by an altitude.
+pub type Position = Vec<f64>;
+
+/// A GeoJSON geometry (RFC 7946). Decoding checks the shape of the
+/// coordinates for the type: positions have at least two numbers, line
+/// strings at least two positions, and polygon rings are closed with at
+/// least four.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Geometry {
+    Point(Position),
+    MultiPoint(Vec<Position>),
+    LineString(Vec<Position>),
+    MultiLineString(Vec<Vec<Position>>),
+    Polygon(Vec<Vec<Position>>),
+    MultiPolygon(Vec<Vec<Vec<Position>>>),
+    GeometryCollection(Vec<Geometry>),
+}
+
+/// A GeoJSON feature: a geometry, which may be missing, with arbitrary
+/// properties.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Feature {
+    /// A string or number, if given.
+    pub id: Option<JSONValue>,
+    pub geometry: Option<Geometry>,
+    pub properties: HashMap<String, JSONValue>,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct FeatureCollection {
+    pub features: Vec<Feature>,
+}
+
+/// Any GeoJSON document, for when the top-level type isn't known ahead.
+#[derive(Debug, Clone, PartialEq)]
+pub enum GeoJson {
+    Geometry(Geometry),
+    Feature(Feature),
+    FeatureCollection(FeatureCollection),
+}
+
+impl Geometry {
+    /// The `type` member, e.g. `"Point"`.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Geometry::Point(_) => "Point",
+            Geometry::MultiPoint(_) => "MultiPoint",
+            Geometry::LineString(_) => "LineString",
+            Geometry::MultiLineString(_) => "MultiLineString",
+            Geometry::Polygon(_) => "Polygon",
+            Geometry::MultiPolygon(_) => "MultiPolygon",
+            Geometry::GeometryCollection(_) => "GeometryCollection",
+        }
+    }
+
+    /// Every position, in order.
+    pub fn positions(&self) -> Vec<&Position> {
+        match self {
+            Geometry::Point(p) => vec![p],
+            Geometry::MultiPoint(ps) | Geometry::LineString(ps) => ps.iter().collect(),
+            Geometry::MultiLineString(lines) | Geometry::Polygon(lines) => {
+                lines.iter().flatten().collect()
+            }
+            Geometry::MultiPolygon(polygons) => polygons.iter().flatten().flatten().collect(),
+            Geometry::GeometryCollection(geometries) => {
+                geometries.iter().flat_map(Geometry::positions).collect()
+            }
+        }
+    }
+
+    /// `[west, south, east, north]` around every position, or `None` for an
+    /// empty geometry. Doesn't account for the antimeridian.
+    pub fn bbox(&self) -> Option<[f64; 4]> {
+        self.positions().into_iter().fold(None, |bbox, p| {
+            let [w, s, e, n] = bbox.unwrap_or([p[0], p[1], p[0], p[1]]);
+            Some([w.min(p[0]), s.min(p[1]), e.max(p[0]), n.max(p[1])])
+        })
+    }
+
+    pub fn to_json(&self) -> JSONValue {
+        let position = |p: &Position| p.iter().map(|n| JSONValue::Num(*n)).collect();
+        let line = |ps: &Vec<Position>| ps.iter().map(position).collect();
+        let polygon = |rings: &Vec<Vec<Position>>| rings.iter().map(line).collect();
+        let (key, value) = match self {
+            Geometry::Point(p) => ("coordinates", position(p)),
+            Geometry::MultiPoint(ps) | Geometry::LineString(ps) => ("coordinates", line(ps)),
+            Geometry::MultiLineString(lines) | Geometry::Polygon(lines) => {
+                ("coordinates", polygon(lines))
+            }
+            Geometry::MultiPolygon(polygons) => {
+                ("coordinates", polygons.iter().map(polygon).collect())
+            }
+            Geometry::GeometryCollection(geometries) => (
+                "geometries",
+                geometries.iter().map(Geometry::to_json).collect(),
+            ),
+        };
+        typed(self.type_name(), [(key, value)])
+    }
+}
+
+impl Feature {
+    pub fn property(&self, key: &str) -> Option<&JSONValue> {
+        self.properties.get(key)
+    }
+
+    pub fn to_json(&self) -> JSONValue {
+        let mut feature = typed(
+            "Feature",
+            [
+                (
+                    "geometry",
+                    self.geometry
+                        .as_ref()
+                        .map_or(JSONValue::Null, Geometry::to_json),
+                ),
+                ("properties", JSONValue::Dict(self.properties.clone())),
+            ],
+        );
+        if let (Some(id), JSONValue::Dict(d)) = (&self.id, &mut feature) {
+            d.insert("id".to_string(), id.clone());
+        }
+        feature
+    }
+}
+
+impl FeatureCollection {
+    pub fn to_json(&self) -> JSONValue {
+        typed(
+            "FeatureCollection",
+            [(
+                "features",
+                self.features.iter().map(Feature::to_json).collect(),
+            )],
+        )
+    }
+}
+
+impl GeoJson {
+    pub fn to_json(&self) -> JSONValue {
+        match self {
+            GeoJson::Geometry(g) => g.to_json(),
+            GeoJson::Feature(f) => f.to_json(),
+            GeoJson::FeatureCollection(fc) => fc.to_json(),
+        }
+    }
+}
+
+fn typed<const N: usize>(type_name: &str, members: [(&str, JSONValue); N]) -> JSONValue {
+    let mut d: HashMap<_, _> = members
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v))
+        .collect();
+    d.insert("type".to_string(), JSONValue::Str(type_name.to_string()));
+    JSONValue::Dict(d)
+}
+
+// Coordinates as written, before `type` (which may come after them) says
+// what shape they must have.
+enum Coords {
+    Num(f64),
+    List(Vec<Coords>),
+}
+
+impl FromJson for Coords {
+    fn from_json(de: &mut Decoder) -> eyre::Result<Self> {
+        match de.peek()? {
+            Some(TokenKind::LeftBracket) => de.decode().map(Coords::List),
+            _ => de.number().map(Coords::Num),
+        }
+    }
+}
+
+impl Coords {
+    fn list(&self, what: &str) -> eyre::Result<&[Coords]> {
+        match self {
+            Coords::List(items) => Ok(items),
+            Coords::Num(_) => eyre::bail!(format!("Expected {} to be an array", what)),
+        }
+    }
+
+    fn position(&self) -> eyre::Result<Position> {
+        let numbers = self
+            .list("a position")?
+            .iter()
+            .map(|c| match c {
+                Coords::Num(n) => Ok(*n),
+                Coords::List(_) => eyre::bail!("Expected a position to hold numbers"),
+            })
+            .collect::<eyre::Result<Position>>()?;
+        if numbers.len() < 2 {
+            eyre::bail!("Expected a position to have at least 2 numbers")
+        }
+        Ok(numbers)
+    }
+
+    fn positions(&self, min: usize) -> eyre::Result<Vec<Position>> {
+        let positions = self
+            .list("a list of positions")?
+            .iter()
+            .map(Coords::position)
+            .collect::<eyre::Result<Vec<_>>>()?;
+        if positions.len() < min {
+            eyre::bail!(format!("Expected at least {} positions", min))
+        }
+        Ok(positions)
+    }
+
+    fn rings(&self) -> eyre::Result<Vec<Vec<Position>>> {
+        self.list("a polygon")?
+            .iter()
+            .map(|ring| {
+                let ring = ring.positions(4)?;
+                if ring.first() != ring.last() {
+                    eyre::bail!("Expected a polygon ring to end where it starts")
+                }
+                Ok(ring)
+            })
+            .collect()
+    }
+
+    fn geometry(&self, type_name: &str) -> eyre::Result<Geometry> {
+        Ok(match type_name {
+            "Point" => Geometry::Point(self.position()?),
+            "MultiPoint" => Geometry::MultiPoint(self.positions(0)?),
+            "LineString" => Geometry::LineString(self.positions(2)?),
+            "MultiLineString" => Geometry::MultiLineString(
+                self.list(type_name)?
+                    .iter()
+                    .map(|line| line.positions(2))
+                    .collect::<eyre::Result<_>>()?,
+            ),
+            "Polygon" => Geometry::Polygon(self.rings()?),
+            "MultiPolygon" => Geometry::MultiPolygon(
+                self.list(type_name)?
+                    .iter()
+                    .map(Coords::rings)
+                    .collect::<eyre::Result<_>>()?,
+            ),
+            _ => eyre::bail!(format!("Unknown geometry type `{}`", type_name)),
+        })
+    }
+}
+
+// The members of any GeoJSON object, gathered before `type` decides which
+// it is.
+#[derive(Default)]
+struct Members {
+    type_name: Option<String>,
+    coordinates: Option<Coords>,
+    geometries: Option<Vec<Geometry>>,
+    id: Option<JSONValue>,
+    geometry: Option<Option<Geometry>>,
+    properties: Option<Option<HashMap<String, JSONValue>>>,
+    features: Option<Vec<Feature>>,
+}
+
+impl Members {
+    fn read(de: &mut Decoder) -> eyre::Result<(usize, Self)> {
+        let start = de.position();
+        let mut m = Members::default();
+        de.object(|key, de| {
+            match key {
+                "type" => m.type_name = Some(de.decode()?),
+                "coordinates" => m.coordinates = Some(de.decode()?),
+                "geometries" => m.geometries = Some(de.decode()?),
+                "id" => m.id = Some(de.decode()?),
+                "geometry" => m.geometry = Some(de.decode()?),
+                "properties" => m.properties = Some(de.decode()?),
+                "features" => m.features = Some(de.decode()?),
+                // bbox and foreign members
+                _ => de.skip()?,
+            }
+            Ok(())
+        })?;
+        Ok((start, m))
+    }
+
+    fn into_geojson(self, start: usize) -> eyre::Result<GeoJson> {
+        let Some(type_name) = self.type_name else {
+            eyre::bail!(format!("GeoJSON object at byte {} has no `type`", start))
+        };
+        let invalid = |e: eyre::Report| {
+            eyre::eyre!(format!("Invalid {} at byte {}: {}", type_name, start, e))
+        };
+        let missing = |member: &str| {
+            eyre::eyre!(format!(
+                "{} at byte {} has no `{}`",
+                type_name, start, member
+            ))
+        };
+        Ok(match type_name.as_str() {
+            "Feature" => GeoJson::Feature(Feature {
+                id: self.id,
+                geometry: self.geometry.ok_or_else(|| missing("geometry"))?,
+                properties: self.properties.flatten().unwrap_or_default(),
+            }),
+            "FeatureCollection" => GeoJson::FeatureCollection(FeatureCollection {
+                features: self.features.ok_or_else(|| missing("features"))?,
+            }),
+            "GeometryCollection" => GeoJson::Geometry(Geometry::GeometryCollection(
+                self.geometries.ok_or_else(|| missing("geometries"))?,
+            )),
+            _ => {
+                let coordinates = self.coordinates.ok_or_else(|| missing("coordinates"))?;
+                GeoJson::Geometry(coordinates.geometry(&type_name).map_err(invalid)?)
+            }
+        })
+    }
+}
+
+impl FromJson for GeoJson {
+    fn from_json(de: &mut Decoder) -> eyre::Result<Self> {
+        let (start, members) = Members::read(de)?;
+        members.into_geojson(start)
+    }
+}
+
+// Decodes any GeoJSON object and requires it to be of the `GeoJson` variant
+// named after the type.
+macro_rules! from_json_geojson {
+    ($($t:ident),*) => {
+        $(
+            impl FromJson for $t {
+                fn from_json(de: &mut Decoder) -> eyre::Result<Self> {
+                    let (start, members) = Members::read(de)?;
+                    match members.into_geojson(start)? {
+                        GeoJson::$t(v) => Ok(v),
+                        other => eyre::bail!(format!(
+                            "Expected a {} at byte {}, found a {}",
+                            stringify!($t),
+                            start,
+                            match other {
+                                GeoJson::Geometry(g) => g.type_name(),
+                                GeoJson::Feature(_) => "Feature",
+                                GeoJson::FeatureCollection(_) => "FeatureCollection",
+                            }
+                        )),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+from_json_geojson!(Geometry, Feature, FeatureCollection);
@@ -0,0 +1,73 @@
+use std::ops::Range;
+
+use crate::lexer::{Lexer, Token};
+
+/// Highlighting class of a span of input.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum TokenClass {
+    /// A string used as an object key.
+    Key,
+    /// A string used as a value.
+    String,
+    Number,
+    /// `true`, `false` and `null`.
+    Keyword,
+    Punctuation,
+    Comment,
+    /// Bytes the lexer could not make sense of.
+    Error,
+}
+
+/// Classifies every token of `input` for syntax highlighting. Comments are
+/// accepted, and lexing resumes after anything invalid so that a single typo
+/// doesn't leave the rest of the document uncoloured.
+pub fn highlight(input: &[u8]) -> Vec<(Range<usize>, TokenClass)> {
+    let lexer = Lexer::new().allow_comments(true);
+    let mut spans: Vec<(Range<usize>, Option<Token>)> = Vec::new();
+    let mut pos = 0;
+    loop {
+        match lexer.next_token(input, &mut pos) {
+            Ok(Some(t)) => spans.push((t.span.range(), Some(t.token))),
+            Ok(None) => break,
+            Err(_) => {
+                let end = error_end(input, pos);
+                spans.push((pos..end, None));
+                pos = end;
+            }
+        }
+    }
+    let mut classes = Vec::with_capacity(spans.len());
+    for (k, (range, token)) in spans.iter().enumerate() {
+        let class = match token {
+            None => TokenClass::Error,
+            Some(Token::StringVal(..)) => {
+                let next = spans[k + 1..]
+                    .iter()
+                    .find(|(_, t)| *t != Some(Token::Comment));
+                match next {
+                    Some((_, Some(Token::Colon))) => TokenClass::Key,
+                    _ => TokenClass::String,
+                }
+            }
+            Some(Token::NumVal(_)) => TokenClass::Number,
+            Some(Token::BoolVal(_)) | Some(Token::NullVal) => TokenClass::Keyword,
+            Some(Token::Comment) => TokenClass::Comment,
+            Some(_) => TokenClass::Punctuation,
+        };
+        classes.push((range.clone(), class));
+    }
+    classes
+}
+
+// An unterminated string is an error up to the end of its line, anything else
+// up to the next whitespace or structural character.
+fn error_end(input: &[u8], pos: usize) -> usize {
+    let rest = &input[pos + 1..];
+    let stop = if input[pos] == b'"' {
+        rest.iter().position(|c| *c == b'\n')
+    } else {
+        rest.iter()
+            .position(|c| c.is_ascii_whitespace() || b"{}[],:\"".contains(c))
+    };
+    stop.map_or(input.len(), |n| pos + 1 + n)
+}
@@ -0,0 +1,78 @@
+use crate::{JSONValue, NumberConstraint, Warning};
+
+type KeyHook<'h> = Box<dyn FnMut(String) -> eyre::Result<String> + 'h>;
+type StringHook<'h> = Box<dyn FnMut(String) -> eyre::Result<JSONValue> + 'h>;
+type NumberHook<'h> = Box<dyn FnMut(f64) -> eyre::Result<JSONValue> + 'h>;
+type WarningHook<'h> = Box<dyn FnMut(Warning) + 'h>;
+
+/// Callbacks run on keys and primitive values as they are parsed, so values
+/// can be rewritten or rejected without a second pass over the result.
+///
+/// Returning an error from a hook aborts the parse with that error.
+#[derive(Default)]
+pub struct ParseHooks<'h> {
+    pub(crate) on_key: Option<KeyHook<'h>>,
+    pub(crate) on_string: Option<StringHook<'h>>,
+    pub(crate) on_number: Option<NumberHook<'h>>,
+    pub(crate) on_warning: Option<WarningHook<'h>>,
+    // each with the JSON Pointer it applies to, or `None` for everywhere
+    pub(crate) constraints: Vec<(Option<String>, NumberConstraint)>,
+}
+
+impl<'h> ParseHooks<'h> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Called with every object key before it is inserted.
+    pub fn on_key(mut self, f: impl FnMut(String) -> eyre::Result<String> + 'h) -> Self {
+        self.on_key = Some(Box::new(f));
+        self
+    }
+
+    /// Called with every string value (not keys); the returned value is used
+    /// in its place.
+    pub fn on_string(mut self, f: impl FnMut(String) -> eyre::Result<JSONValue> + 'h) -> Self {
+        self.on_string = Some(Box::new(f));
+        self
+    }
+
+    /// Called with every number value; the returned value is used in its place.
+    pub fn on_number(mut self, f: impl FnMut(f64) -> eyre::Result<JSONValue> + 'h) -> Self {
+        self.on_number = Some(Box::new(f));
+        self
+    }
+
+    /// Called with each `Warning`: duplicate keys, rounded numbers, a
+    /// skipped byte order mark or very deep nesting.
+    pub fn on_warning(mut self, f: impl FnMut(Warning) + 'h) -> Self {
+        self.on_warning = Some(Box::new(f));
+        self
+    }
+
+    /// Rejects any number that breaks `constraint`, failing the parse with
+    /// its position. Checked before `on_number`.
+    pub fn constrain_numbers(mut self, constraint: NumberConstraint) -> Self {
+        self.constraints.push((None, constraint));
+        self
+    }
+
+    /// Like `constrain_numbers`, but only for the number at the JSON Pointer
+    /// `ptr`, e.g. `/items/0/price`.
+    pub fn constrain_number_at(mut self, ptr: &str, constraint: NumberConstraint) -> Self {
+        self.constraints.push((Some(ptr.to_string()), constraint));
+        self
+    }
+
+    pub(crate) fn tracks_pointers(&self) -> bool {
+        self.on_warning.is_some() || self.constraints.iter().any(|(ptr, _)| ptr.is_some())
+    }
+
+    // The first constraint `n` at `ptr` breaks, and why.
+    pub(crate) fn check_number(&self, ptr: &str, n: f64) -> Option<String> {
+        self.constraints
+            .iter()
+            .filter(|(p, _)| p.as_deref().is_none_or(|p| p == ptr))
+            .find_map(|(_, c)| c.violation(n))
+    }
+}
@@ -0,0 +1,195 @@
+use core::fmt;
+
+use crate::ParseOptions;
+
+/// Body size limit used when no `max_size` is configured, matching axum's.
+pub const DEFAULT_BODY_LIMIT: usize = 2 * 1024 * 1024;
+/// Nesting limit used when no `max_depth` is configured.
+pub const DEFAULT_DEPTH_LIMIT: usize = 128;
+
+/// Options with the size and depth limits applied to HTTP bodies.
+pub fn body_options() -> ParseOptions {
+    ParseOptions {
+        max_size: Some(DEFAULT_BODY_LIMIT),
+        max_depth: Some(DEFAULT_DEPTH_LIMIT),
+        ..ParseOptions::default()
+    }
+}
+
+/// Returned (inside the `eyre::Report`) when a body is over `max_size`.
+#[derive(Debug)]
+pub struct BodyTooLarge {
+    pub limit: usize,
+}
+
+impl fmt::Display for BodyTooLarge {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Body exceeds limit of {} bytes", self.limit)
+    }
+}
+
+impl std::error::Error for BodyTooLarge {}
+
+#[cfg(feature = "hyper")]
+mod hyper_support {
+    use http_body_util::{BodyExt, LengthLimitError, Limited};
+
+    use super::BodyTooLarge;
+    use crate::{parse_with_options, Document, JSONValue, ParseOptions};
+
+    async fn collect<B>(body: B, options: &ParseOptions) -> eyre::Result<bytes::Bytes>
+    where
+        B: http_body::Body,
+        B::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+    {
+        let limit = options.max_size.unwrap_or(usize::MAX);
+        let collected = Limited::new(body, limit).collect().await.map_err(|e| {
+            if e.is::<LengthLimitError>() {
+                eyre::Report::new(BodyTooLarge { limit })
+            } else {
+                eyre::Report::msg(e)
+            }
+        })?;
+        Ok(collected.to_bytes())
+    }
+
+    /// Collects and parses any `http_body::Body` (hyper, axum, tonic, ...),
+    /// stopping early once `options.max_size` is exceeded.
+    pub async fn parse_body<B>(body: B, options: &ParseOptions) -> eyre::Result<JSONValue>
+    where
+        B: http_body::Body,
+        B::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+    {
+        parse_with_options(&collect(body, options).await?, options)
+    }
+
+    /// Like `parse_body`, but keeps the collected body as a `Document`
+    /// without copying it, so string values can be read as slices of it.
+    pub async fn parse_body_document<B>(body: B, options: &ParseOptions) -> eyre::Result<Document>
+    where
+        B: http_body::Body,
+        B::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+    {
+        Document::from_bytes_with_options(collect(body, options).await?, options)
+    }
+}
+
+#[cfg(feature = "hyper")]
+pub use hyper_support::{parse_body, parse_body_document};
+
+#[cfg(feature = "reqwest")]
+mod reqwest_support {
+    use super::BodyTooLarge;
+    use crate::{parse_with_options, JSONValue, ParseOptions};
+
+    /// Reads and parses a response body, stopping early once
+    /// `options.max_size` is exceeded.
+    pub async fn parse_response(
+        mut resp: reqwest::Response,
+        options: &ParseOptions,
+    ) -> eyre::Result<JSONValue> {
+        let limit = options.max_size.unwrap_or(usize::MAX);
+        if resp.content_length().is_some_and(|len| len > limit as u64) {
+            return Err(BodyTooLarge { limit }.into());
+        }
+        let mut buf = Vec::new();
+        while let Some(chunk) = resp.chunk().await? {
+            if buf.len() + chunk.len() > limit {
+                return Err(BodyTooLarge { limit }.into());
+            }
+            buf.extend_from_slice(&chunk);
+        }
+        parse_with_options(&buf, options)
+    }
+}
+
+#[cfg(feature = "reqwest")]
+pub use reqwest_support::parse_response;
+
+#[cfg(feature = "axum")]
+mod axum_support {
+    use core::fmt;
+
+    use axum_core::extract::{FromRequest, Request};
+    use axum_core::response::{IntoResponse, Response};
+    use http::{header, HeaderMap, StatusCode};
+
+    use super::{body_options, parse_body, BodyTooLarge};
+    use crate::{JSONValue, ParseOptions};
+
+    /// Extractor parsing the request body with this crate. Limits come from a
+    /// `ParseOptions` request extension if present, else `body_options()`.
+    #[derive(Debug)]
+    pub struct Json<T = JSONValue>(pub T);
+
+    /// Why the `Json` extractor refused a request.
+    #[derive(Debug)]
+    pub struct JsonRejection {
+        pub status: StatusCode,
+        pub message: String,
+    }
+
+    impl fmt::Display for JsonRejection {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "{}", self.message)
+        }
+    }
+
+    impl IntoResponse for JsonRejection {
+        fn into_response(self) -> Response {
+            (self.status, self.message).into_response()
+        }
+    }
+
+    fn is_json_content_type(headers: &HeaderMap) -> bool {
+        let Some(content_type) = headers.get(header::CONTENT_TYPE) else {
+            return false;
+        };
+        let Ok(content_type) = content_type.to_str() else {
+            return false;
+        };
+        let mime = content_type.split(';').next().unwrap_or("").trim();
+        mime.eq_ignore_ascii_case("application/json") || mime.ends_with("+json")
+    }
+
+    impl<T, S> FromRequest<S> for Json<T>
+    where
+        T: TryFrom<JSONValue> + Send,
+        T::Error: fmt::Display,
+        S: Send + Sync,
+    {
+        type Rejection = JsonRejection;
+
+        async fn from_request(req: Request, _state: &S) -> Result<Self, Self::Rejection> {
+            if !is_json_content_type(req.headers()) {
+                return Err(JsonRejection {
+                    status: StatusCode::UNSUPPORTED_MEDIA_TYPE,
+                    message: "Expected request with `Content-Type: application/json`".into(),
+                });
+            }
+            let options = req
+                .extensions()
+                .get::<ParseOptions>()
+                .cloned()
+                .unwrap_or_else(body_options);
+            let value = parse_body(req.into_body(), &options)
+                .await
+                .map_err(|e| JsonRejection {
+                    status: if e.is::<BodyTooLarge>() {
+                        StatusCode::PAYLOAD_TOO_LARGE
+                    } else {
+                        StatusCode::BAD_REQUEST
+                    },
+                    message: e.to_string(),
+                })?;
+            let value = T::try_from(value).map_err(|e| JsonRejection {
+                status: StatusCode::UNPROCESSABLE_ENTITY,
+                message: e.to_string(),
+            })?;
+            Ok(Json(value))
+        }
+    }
+}
+
+#[cfg(feature = "axum")]
+pub use axum_support::{Json, JsonRejection};
@@ -0,0 +1,201 @@
+use crate::lexer::{Lexer, Token};
+use crate::prefix::TreeBuilder;
+use crate::syntax::{self, Expect};
+use crate::{binary, escape, JSONValue, LoneSurrogates, ParseOptions};
+
+/// Parses a document that arrives in pieces (e.g. streamed model output),
+/// able to produce a best-effort value at any point.
+///
+/// Complete tokens are consumed as they arrive and their bytes dropped, so each
+/// `feed` only lexes the new bytes (plus any token that was cut off last time)
+/// and memory stays bounded by the values built. Nesting is limited
+/// to `DEFAULT_MAX_DEPTH`, or `ParseOptions::max_depth` with `with_options`.
+pub struct IncrementalParser {
+    // input not yet consumed as complete tokens, from `pos` on
+    buf: Vec<u8>,
+    pos: usize,
+    // bytes consumed and dropped before `buf` starts
+    base: usize,
+    lexer: Lexer,
+    builder: TreeBuilder,
+    done: Option<JSONValue>,
+}
+
+impl Default for IncrementalParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl IncrementalParser {
+    pub fn new() -> Self {
+        Self::with_options(&ParseOptions::default())
+    }
+
+    /// A parser nesting at most `options.max_depth` deep. The other options
+    /// don't apply: the input is always strict JSON.
+    pub fn with_options(options: &ParseOptions) -> Self {
+        Self {
+            buf: Vec::new(),
+            pos: 0,
+            base: 0,
+            lexer: Lexer::new(),
+            builder: TreeBuilder::with_options(options),
+            done: None,
+        }
+    }
+
+    /// Appends `chunk` and consumes every token it completes. Fails as soon as
+    /// the input can no longer become valid JSON. Bytes after the end of the
+    /// document are ignored.
+    pub fn feed(&mut self, chunk: &[u8]) -> eyre::Result<()> {
+        self.buf.extend_from_slice(chunk);
+        while self.done.is_none() {
+            let mut pos = self.pos;
+            let t = match self.lexer.next_token(&self.buf, &mut pos) {
+                Ok(Some(t)) => t,
+                Ok(None) => break,
+                Err(e) => {
+                    syntax::partial_token(&self.buf[pos..]).ok_or(e)?;
+                    break;
+                }
+            };
+            if matches!(t.token, Token::NumVal(_)) && t.span.end == self.buf.len() {
+                self.builder.syntax().clone().feed(t.token)?;
+                break;
+            }
+            self.done = self.builder.push(t.token, &self.buf)?;
+            self.pos = t.span.end;
+        }
+        self.buf.drain(..self.pos);
+        self.base += self.pos;
+        self.pos = 0;
+        Ok(())
+    }
+
+    /// Whether a complete document has been parsed.
+    pub fn is_done(&self) -> bool {
+        self.done.is_some()
+    }
+
+    /// Bytes fed but not yet consumed: a token cut off at the end of the
+    /// input so far, with any whitespace before it.
+    pub fn buffer(&self) -> &[u8] {
+        &self.buf[self.pos..]
+    }
+
+    /// Bytes of the input consumed as complete tokens, counting from the
+    /// start of the stream even after resuming.
+    pub fn offset(&self) -> usize {
+        self.base + self.pos
+    }
+
+    /// The parser's state as JSON: the values built so far in every open
+    /// container, what the grammar expects next, any cut-off token, and
+    /// `offset`. A long-running job can save it and, after a restart,
+    /// `resume` and carry on feeding from where the input was cut off
+    /// instead of from the beginning.
+    pub fn checkpoint(&self) -> JSONValue {
+        let mut state = match self.builder.to_json() {
+            JSONValue::Dict(d) => d,
+            _ => unreachable!("builder state is an object"),
+        };
+        let pending = binary::encode_base64(&self.buf[self.pos..]);
+        state.insert("pending".to_string(), JSONValue::Str(pending));
+        state.insert("offset".to_string(), JSONValue::Num(self.offset() as f64));
+        if let Some(done) = &self.done {
+            state.insert("done".to_string(), done.clone());
+        }
+        JSONValue::Dict(state)
+    }
+
+    /// A parser in the state `checkpoint` saved. Feed it the input from
+    /// `offset() + pending.len()`, i.e. the bytes the saved parser never saw.
+    pub fn resume(checkpoint: &JSONValue) -> eyre::Result<Self> {
+        Self::resume_with_options(checkpoint, &ParseOptions::default())
+    }
+
+    /// Like `resume`, for a parser made with `with_options`.
+    pub fn resume_with_options(
+        checkpoint: &JSONValue,
+        options: &ParseOptions,
+    ) -> eyre::Result<Self> {
+        let invalid = || eyre::eyre!("Invalid parser state");
+        let JSONValue::Dict(d) = checkpoint else {
+            return Err(invalid());
+        };
+        let buf = match d.get("pending") {
+            Some(JSONValue::Str(s)) => binary::decode_base64(s)?,
+            _ => return Err(invalid()),
+        };
+        let base = match d.get("offset") {
+            Some(JSONValue::Num(n)) if n.fract() == 0.0 && *n >= 0.0 => *n as usize,
+            _ => return Err(invalid()),
+        };
+        let builder = TreeBuilder::from_json(checkpoint, options)?;
+        let done = d.get("done").cloned();
+        if done.is_some() != builder.syntax().is_complete() {
+            return Err(invalid());
+        }
+        Ok(Self {
+            buf,
+            pos: 0,
+            base,
+            lexer: Lexer::new(),
+            builder,
+            done,
+        })
+    }
+
+    /// The document as it stands, with an unterminated string, number or
+    /// keyword at the end taken as it is and every open container closed.
+    /// Keys still waiting for a value are left out.
+    pub fn current(&self) -> Option<JSONValue> {
+        if let Some(done) = &self.done {
+            return Some(done.clone());
+        }
+        let tail = match self.builder.syntax().expect {
+            Expect::Value | Expect::ValueOrEndArray => partial_value(&self.buf[self.pos..]),
+            _ => None,
+        };
+        self.builder.snapshot(tail)
+    }
+
+    /// Ends the stream, returning the best-effort value if the document was
+    /// never completed.
+    pub fn finish(self) -> Option<JSONValue> {
+        self.current()
+    }
+}
+
+// Best guess at the value an incomplete trailing token will become.
+pub(crate) fn partial_value(rest: &[u8]) -> Option<JSONValue> {
+    let start = rest.iter().position(|c| !c.is_ascii_whitespace())?;
+    let rest = &rest[start..];
+    match rest[0] {
+        b'"' => {
+            // drop a multi-byte character or an escape that is only partly there
+            let s = &rest[1..];
+            let valid = std::str::from_utf8(s).map_or_else(|e| e.valid_up_to(), |s| s.len());
+            let s = &s[..valid];
+            (s.len().saturating_sub(11)..=s.len())
+                .rev()
+                .find_map(|n| {
+                    escape::unescape_str(&s[..n], LoneSurrogates::Replace, String::new()).ok()
+                })
+                .map(JSONValue::Str)
+        }
+        b't' if b"true".starts_with(rest) => Some(JSONValue::Bool(true)),
+        b'f' if b"false".starts_with(rest) => Some(JSONValue::Bool(false)),
+        b'n' if b"null".starts_with(rest) => Some(JSONValue::Null),
+        b'-' | b'0'..=b'9' => {
+            // longest prefix that is a valid number, e.g. `1.` -> 1, `-` -> none
+            let s = std::str::from_utf8(rest).ok()?;
+            (1..=s.len())
+                .rev()
+                .find_map(|n| s[..n].parse::<f64>().ok())
+                .map(JSONValue::Num)
+        }
+        _ => None,
+    }
+}
@@ -0,0 +1,402 @@
+use core::fmt;
+use std::collections::HashMap;
+use std::io::{BufRead, Write};
+
+use crate::schema::Type;
+use crate::{parse, JSONValue};
+
+pub const PARSE_ERROR: i64 = -32700;
+pub const INVALID_REQUEST: i64 = -32600;
+pub const METHOD_NOT_FOUND: i64 = -32601;
+pub const INVALID_PARAMS: i64 = -32602;
+pub const INTERNAL_ERROR: i64 = -32603;
+
+/// Identifies a request and the response to it.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Id {
+    Num(i64),
+    Str(String),
+    /// Only in responses to requests whose id couldn't be read.
+    Null,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Request {
+    pub id: Id,
+    pub method: String,
+    /// An array or object, if given.
+    pub params: Option<JSONValue>,
+}
+
+/// A request that wants no response.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Notification {
+    pub method: String,
+    pub params: Option<JSONValue>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Response {
+    pub id: Id,
+    pub result: Result<JSONValue, Error>,
+}
+
+/// The `error` of a failed response. Also what reading a message gives
+/// when it breaks the spec, ready to send back.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Error {
+    pub code: i64,
+    pub message: String,
+    pub data: Option<JSONValue>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Message {
+    Request(Request),
+    Notification(Notification),
+    Response(Response),
+}
+
+/// What one frame holds: a single message or a batch. A batch keeps the
+/// position of each element, with an error for those that aren't valid
+/// messages, which the spec says to answer with an error response each.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Payload {
+    Single(Message),
+    Batch(Vec<Result<Message, Error>>),
+}
+
+impl Id {
+    pub fn to_json(&self) -> JSONValue {
+        match self {
+            Id::Num(n) => JSONValue::Num(*n as f64),
+            Id::Str(s) => JSONValue::Str(s.clone()),
+            Id::Null => JSONValue::Null,
+        }
+    }
+
+    fn from_json(v: &JSONValue) -> Option<Self> {
+        match v {
+            JSONValue::Num(n) if n.fract() == 0.0 && n.abs() < i64::MAX as f64 => {
+                Some(Id::Num(*n as i64))
+            }
+            JSONValue::Str(s) => Some(Id::Str(s.clone())),
+            JSONValue::Null => Some(Id::Null),
+            _ => None,
+        }
+    }
+}
+
+impl Error {
+    pub fn new(code: i64, message: &str) -> Self {
+        Self {
+            code,
+            message: message.to_string(),
+            data: None,
+        }
+    }
+
+    pub fn with_data(mut self, data: JSONValue) -> Self {
+        self.data = Some(data);
+        self
+    }
+
+    pub fn parse_error(detail: &str) -> Self {
+        Self::new(PARSE_ERROR, "Parse error").with_data(JSONValue::Str(detail.to_string()))
+    }
+
+    pub fn invalid_request(detail: &str) -> Self {
+        Self::new(INVALID_REQUEST, "Invalid Request").with_data(JSONValue::Str(detail.to_string()))
+    }
+
+    pub fn method_not_found(method: &str) -> Self {
+        Self::new(METHOD_NOT_FOUND, "Method not found")
+            .with_data(JSONValue::Str(method.to_string()))
+    }
+
+    pub fn invalid_params(detail: &str) -> Self {
+        Self::new(INVALID_PARAMS, "Invalid params").with_data(JSONValue::Str(detail.to_string()))
+    }
+
+    pub fn internal_error(detail: &str) -> Self {
+        Self::new(INTERNAL_ERROR, "Internal error").with_data(JSONValue::Str(detail.to_string()))
+    }
+
+    pub fn to_json(&self) -> JSONValue {
+        let mut d = HashMap::from([
+            ("code".to_string(), JSONValue::Num(self.code as f64)),
+            ("message".to_string(), JSONValue::Str(self.message.clone())),
+        ]);
+        if let Some(data) = &self.data {
+            d.insert("data".to_string(), data.clone());
+        }
+        JSONValue::Dict(d)
+    }
+
+    fn from_json(v: &JSONValue) -> Option<Self> {
+        let JSONValue::Dict(d) = v else {
+            return None;
+        };
+        let code = match d.get("code")? {
+            JSONValue::Num(n) if n.fract() == 0.0 => *n as i64,
+            _ => return None,
+        };
+        let JSONValue::Str(message) = d.get("message")? else {
+            return None;
+        };
+        Some(Self {
+            code,
+            message: message.clone(),
+            data: d.get("data").cloned(),
+        })
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({})", self.message, self.code)?;
+        match &self.data {
+            Some(JSONValue::Str(detail)) => write!(f, ": {}", detail),
+            Some(data) => write!(f, ": {}", data.to_json_string()),
+            None => Ok(()),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl Response {
+    /// A failed response. Use `Id::Null` for a request whose id couldn't
+    /// be read.
+    pub fn error(id: Id, error: Error) -> Self {
+        Self {
+            id,
+            result: Err(error),
+        }
+    }
+}
+
+fn envelope(members: Vec<(&str, JSONValue)>) -> JSONValue {
+    let mut d: HashMap<_, _> = members
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v))
+        .collect();
+    d.insert("jsonrpc".to_string(), JSONValue::Str("2.0".to_string()));
+    JSONValue::Dict(d)
+}
+
+impl Message {
+    /// Checks `v` against the spec: a `"jsonrpc": "2.0"` member, a string
+    /// method with array or object params for requests and notifications,
+    /// and exactly one of `result` or `error` for responses.
+    pub fn from_json(v: &JSONValue) -> Result<Self, Error> {
+        let JSONValue::Dict(d) = v else {
+            return Err(Error::invalid_request(&format!(
+                "Expected an object, found {}",
+                Type::of(v).name()
+            )));
+        };
+        if d.get("jsonrpc") != Some(&JSONValue::Str("2.0".to_string())) {
+            return Err(Error::invalid_request("Expected \"jsonrpc\": \"2.0\""));
+        }
+        let id = match d.get("id") {
+            Some(id) => Some(Id::from_json(id).ok_or_else(|| {
+                Error::invalid_request("Expected id to be a string, integer or null")
+            })?),
+            None => None,
+        };
+        if let Some(method) = d.get("method") {
+            let JSONValue::Str(method) = method else {
+                return Err(Error::invalid_request("Expected method to be a string"));
+            };
+            let params = d.get("params").cloned();
+            if params
+                .as_ref()
+                .is_some_and(|p| !matches!(p, JSONValue::Array(_) | JSONValue::Dict(_)))
+            {
+                return Err(Error::invalid_request(
+                    "Expected params to be an array or object",
+                ));
+            }
+            let method = method.clone();
+            return Ok(match id {
+                Some(id) => Message::Request(Request { id, method, params }),
+                None => Message::Notification(Notification { method, params }),
+            });
+        }
+        let Some(id) = id else {
+            return Err(Error::invalid_request("Expected a method or an id"));
+        };
+        let result = match (d.get("result"), d.get("error")) {
+            (Some(result), None) => Ok(result.clone()),
+            (None, Some(error)) => Err(Error::from_json(error).ok_or_else(|| {
+                Error::invalid_request("Expected error to have an integer code and a message")
+            })?),
+            _ => {
+                return Err(Error::invalid_request(
+                    "Expected a response to have exactly one of result and error",
+                ))
+            }
+        };
+        Ok(Message::Response(Response { id, result }))
+    }
+
+    pub fn to_json(&self) -> JSONValue {
+        let method = |m: &str| ("method", JSONValue::Str(m.to_string()));
+        match self {
+            Message::Request(r) => {
+                let mut members = vec![("id", r.id.to_json()), method(&r.method)];
+                members.extend(r.params.clone().map(|p| ("params", p)));
+                envelope(members)
+            }
+            Message::Notification(n) => {
+                let mut members = vec![method(&n.method)];
+                members.extend(n.params.clone().map(|p| ("params", p)));
+                envelope(members)
+            }
+            Message::Response(r) => envelope(vec![
+                ("id", r.id.to_json()),
+                match &r.result {
+                    Ok(result) => ("result", result.clone()),
+                    Err(error) => ("error", error.to_json()),
+                },
+            ]),
+        }
+    }
+}
+
+impl From<Message> for Payload {
+    fn from(m: Message) -> Self {
+        Payload::Single(m)
+    }
+}
+
+impl Payload {
+    /// Reads one frame. The error, for JSON that doesn't parse, a message
+    /// breaking the spec or an empty batch, is the one to answer with.
+    pub fn parse(json: &[u8]) -> Result<Self, Error> {
+        let v = parse(json).map_err(|e| Error::parse_error(&e.to_string()))?;
+        Self::from_json(&v)
+    }
+
+    pub fn from_json(v: &JSONValue) -> Result<Self, Error> {
+        match v {
+            JSONValue::Array(entries) if entries.is_empty() => {
+                Err(Error::invalid_request("Expected a batch to be non-empty"))
+            }
+            JSONValue::Array(entries) => Ok(Payload::Batch(
+                entries.iter().map(Message::from_json).collect(),
+            )),
+            v => Message::from_json(v).map(Payload::Single),
+        }
+    }
+
+    /// Batch elements that weren't valid messages are written as error
+    /// responses with a `null` id.
+    pub fn to_json(&self) -> JSONValue {
+        match self {
+            Payload::Single(m) => m.to_json(),
+            Payload::Batch(entries) => entries
+                .iter()
+                .map(|e| match e {
+                    Ok(m) => m.to_json(),
+                    Err(error) => {
+                        Message::Response(Response::error(Id::Null, error.clone())).to_json()
+                    }
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Reads `Content-Length`-framed payloads, as LSP sends them. Items are
+/// `Err` for I/O or framing problems, after which the stream is over, and
+/// `Ok(Err(..))` for a frame that isn't a valid payload, which should be
+/// answered with that error.
+pub struct MessageReader<R> {
+    reader: R,
+    max_len: usize,
+    buf: Vec<u8>,
+    done: bool,
+}
+
+impl<R: BufRead> MessageReader<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            max_len: 64 * 1024 * 1024,
+            buf: Vec::new(),
+            done: false,
+        }
+    }
+
+    /// Frames announcing a larger body are rejected before anything is
+    /// allocated for them.
+    pub fn max_len(mut self, max: usize) -> Self {
+        self.max_len = max;
+        self
+    }
+
+    fn next_frame(&mut self) -> eyre::Result<Option<Result<Payload, Error>>> {
+        let mut len = None;
+        let mut line = String::new();
+        let mut first = true;
+        loop {
+            line.clear();
+            if self.reader.read_line(&mut line)? == 0 {
+                if first {
+                    return Ok(None);
+                }
+                eyre::bail!("Input ends inside a header")
+            }
+            first = false;
+            let l = line.trim_end_matches(['\n', '\r']);
+            if l.is_empty() {
+                break;
+            }
+            let Some((name, value)) = l.split_once(':') else {
+                eyre::bail!(format!("Invalid header: {:?}", l))
+            };
+            if name.trim().eq_ignore_ascii_case("content-length") {
+                let value = value.trim();
+                len =
+                    Some(value.parse::<usize>().map_err(|_| {
+                        eyre::eyre!(format!("Invalid Content-Length: {:?}", value))
+                    })?);
+            }
+        }
+        let len = len.ok_or_else(|| eyre::eyre!("Missing Content-Length header"))?;
+        if len > self.max_len {
+            eyre::bail!(format!(
+                "Message of {} bytes exceeds limit of {}",
+                len, self.max_len
+            ))
+        }
+        self.buf.resize(len, 0);
+        self.reader.read_exact(&mut self.buf)?;
+        Ok(Some(Payload::parse(&self.buf)))
+    }
+}
+
+impl<R: BufRead> Iterator for MessageReader<R> {
+    type Item = eyre::Result<Result<Payload, Error>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let res = self.next_frame().transpose();
+        if !matches!(res, Some(Ok(_))) {
+            self.done = true;
+        }
+        res
+    }
+}
+
+/// Writes `payload` with a `Content-Length` header, for `MessageReader` on
+/// the other end.
+pub fn write_message<W: Write>(w: &mut W, payload: &Payload) -> std::io::Result<()> {
+    let body = payload.to_json().to_json_string();
+    write!(w, "Content-Length: {}\r\n\r\n{}", body.len(), body)?;
+    w.flush()
+}
@@ -0,0 +1,41 @@
+use crate::binary::decode_base64;
+use crate::{parse, JSONValue};
+
+// The header and claims of a compact JWS, `header.claims.signature`.
+fn segments(token: &str) -> eyre::Result<[JSONValue; 2]> {
+    let parts: Vec<&str> = token.trim().split('.').collect();
+    let [header, claims, _signature] = parts[..] else {
+        if parts.len() == 5 {
+            eyre::bail!("JWT is encrypted (JWE), so its claims can't be read")
+        }
+        eyre::bail!(format!(
+            "Expected a JWT to have 3 segments, found {}",
+            parts.len()
+        ))
+    };
+    let decode = |segment: &str, what: &str| -> eyre::Result<JSONValue> {
+        let json = decode_base64(segment)
+            .map_err(|e| eyre::eyre!(format!("Invalid JWT {}: {}", what, e)))?;
+        match parse(&json) {
+            Ok(v @ JSONValue::Dict(_)) => Ok(v),
+            Ok(_) => eyre::bail!(format!("Expected the JWT {} to be an object", what)),
+            Err(e) => eyre::bail!(format!("Invalid JWT {}: {}", what, e)),
+        }
+    };
+    Ok([decode(header, "header")?, decode(claims, "claims")?])
+}
+
+/// The claims (payload) of a JWT, e.g. `sub` and `exp`, for a quick look.
+/// The signature is NOT verified, so nothing here can be trusted for
+/// authentication.
+pub fn decode_jwt_claims(token: &str) -> eyre::Result<JSONValue> {
+    let [_, claims] = segments(token)?;
+    Ok(claims)
+}
+
+/// The header of a JWT, e.g. `alg` and `kid`. Like `decode_jwt_claims`, it
+/// doesn't verify anything.
+pub fn decode_jwt_header(token: &str) -> eyre::Result<JSONValue> {
+    let [header, _] = segments(token)?;
+    Ok(header)
+}
@@ -0,0 +1,616 @@
+use core::fmt;
+use std::ops::Range;
+
+use crate::comments::{self, RawComment};
+use crate::{escape, ParseOptions, BOM};
+use eyre::OptionExt;
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum Token {
+    LeftBrace,
+    RightBrace,
+    LeftBracket,
+    RightBracket,
+    Comma,
+    Colon,
+    NullVal,
+    /// Byte range of the string's contents, excluding the quotes.
+    StringVal(usize, usize),
+    NumVal(f64),
+    BoolVal(bool),
+    /// Only produced when comments are allowed.
+    Comment,
+}
+
+impl Token {
+    pub fn kind(&self) -> TokenKind {
+        use Token::*;
+        match self {
+            LeftBrace => TokenKind::LeftBrace,
+            RightBrace => TokenKind::RightBrace,
+            LeftBracket => TokenKind::LeftBracket,
+            RightBracket => TokenKind::RightBracket,
+            Comma => TokenKind::Comma,
+            Colon => TokenKind::Colon,
+            NullVal => TokenKind::Null,
+            StringVal(..) => TokenKind::String,
+            NumVal(_) => TokenKind::Number,
+            BoolVal(_) => TokenKind::Bool,
+            Comment => TokenKind::Comment,
+        }
+    }
+}
+
+impl fmt::Debug for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use Token::*;
+        let s = match self {
+            LeftBrace => "{",
+            RightBrace => "}",
+            LeftBracket => "[",
+            RightBracket => "]",
+            Comma => ",",
+            Colon => ":",
+            NullVal => "null",
+            StringVal(i, j) => return write!(f, "Token(str_range:[{}:{}])", i, j),
+            BoolVal(b) => {
+                if *b {
+                    "true"
+                } else {
+                    "false"
+                }
+            }
+            NumVal(n) => return write!(f, "Token('{}')", n),
+            Comment => "comment",
+        };
+        write!(f, "Token('{}')", s)
+    }
+}
+
+/// Token type without the payload, for matching and classification.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum TokenKind {
+    LeftBrace,
+    RightBrace,
+    LeftBracket,
+    RightBracket,
+    Comma,
+    Colon,
+    Null,
+    String,
+    Number,
+    Bool,
+    Comment,
+}
+
+/// Half-open byte range `start..end` into the input.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    pub fn new(start: usize, end: usize) -> Self {
+        Self { start, end }
+    }
+
+    pub fn range(&self) -> Range<usize> {
+        self.start..self.end
+    }
+
+    pub fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+}
+
+/// A token together with the bytes of the input it was lexed from.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct SpannedToken {
+    pub token: Token,
+    pub span: Span,
+}
+
+impl SpannedToken {
+    pub fn kind(&self) -> TokenKind {
+        self.token.kind()
+    }
+}
+
+pub struct Lexer {
+    whitespace: Vec<u8>,
+    single_char_symbols: (Vec<u8>, Vec<Token>), // mapping
+    multi_char_symbols: (Vec<Vec<u8>>, Vec<Token>), // mapping
+    num_chars: Vec<u8>,
+    allow_comments: bool,
+    allow_control_chars: bool,
+    allow_non_finite: bool,
+    strip_bom: bool,
+    max_tokens: Option<usize>,
+}
+
+impl Default for Lexer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Lexer {
+    pub fn new() -> Self {
+        let whitespace: Vec<u8> = [' ', '\t', '\r', '\n']
+            .into_iter()
+            .map(|v| v as u8)
+            .collect();
+        use Token::*;
+        let single_char_symbols = (
+            ['{', '}', '[', ']', ',', ':']
+                .into_iter()
+                .map(|v| v as u8)
+                .collect(),
+            vec![
+                LeftBrace,
+                RightBrace,
+                LeftBracket,
+                RightBracket,
+                Comma,
+                Colon,
+            ],
+        );
+        let multi_char_symbols = (
+            ["null", "true", "fals"]
+                .into_iter()
+                .map(|s| s.as_bytes().to_vec())
+                .collect(),
+            vec![NullVal, BoolVal(true), BoolVal(false)],
+        );
+        let num_chars: Vec<u8> = "0123456789.eE+-".chars().map(|v| v as u8).collect();
+        Self {
+            whitespace,
+            single_char_symbols,
+            multi_char_symbols,
+            num_chars,
+            allow_comments: false,
+            allow_control_chars: false,
+            allow_non_finite: false,
+            strip_bom: false,
+            max_tokens: None,
+        }
+    }
+
+    pub(crate) fn with_options(options: &ParseOptions) -> Self {
+        Self::new()
+            .allow_comments(options.allow_comments)
+            .allow_control_chars(options.allow_control_chars)
+            .allow_non_finite(options.allow_non_finite)
+            .strip_bom(options.strip_bom)
+            .max_tokens(options.max_tokens)
+    }
+
+    /// Accept `//` and `/* */` comments, producing `Token::Comment` for them.
+    pub fn allow_comments(mut self, allow: bool) -> Self {
+        self.allow_comments = allow;
+        self
+    }
+
+    /// Accept raw control characters (tabs, newlines, ...) inside strings,
+    /// which RFC 8259 requires to be escaped.
+    pub fn allow_control_chars(mut self, allow: bool) -> Self {
+        self.allow_control_chars = allow;
+        self
+    }
+
+    /// Accept `NaN`, `Infinity` and `-Infinity` as numbers.
+    pub fn allow_non_finite(mut self, allow: bool) -> Self {
+        self.allow_non_finite = allow;
+        self
+    }
+
+    /// Skip a UTF-8 byte order mark at the start of the input. Spans stay
+    /// offsets into the whole input.
+    pub fn strip_bom(mut self, strip: bool) -> Self {
+        self.strip_bom = strip;
+        self
+    }
+
+    // Where lexing `input` starts: past a byte order mark if it's skipped.
+    fn start(&self, input: &[u8]) -> usize {
+        if self.strip_bom && input.starts_with(BOM) {
+            BOM.len()
+        } else {
+            0
+        }
+    }
+
+    /// Fail once an input has more than `max` tokens, not counting comments.
+    pub fn max_tokens(mut self, max: Option<usize>) -> Self {
+        self.max_tokens = max;
+        self
+    }
+
+    /// Fails if `count` tokens are more than `max_tokens` allows.
+    pub(crate) fn check_count(&self, count: usize) -> eyre::Result<()> {
+        if let Some(max) = self.max_tokens {
+            if count > max {
+                eyre::bail!(format!("Input exceeds maximum of {} tokens", max))
+            }
+        }
+        Ok(())
+    }
+
+    /// Lazily lexes `input` one token at a time.
+    pub fn tokens(self, input: &[u8]) -> Tokens<'_> {
+        Tokens {
+            pos: self.start(input),
+            lexer: self,
+            buf: input,
+            done: false,
+            count: 0,
+        }
+    }
+
+    /// Like `tokens`, starting at byte `pos` of `input`.
+    pub(crate) fn tokens_from(self, input: &[u8], pos: usize) -> Tokens<'_> {
+        Tokens {
+            pos,
+            ..self.tokens(input)
+        }
+    }
+
+    /// Lexes all of `buf`, leaving out comments.
+    pub fn lex(&self, buf: &[u8]) -> eyre::Result<Vec<SpannedToken>> {
+        self.lex_into(buf, None)
+    }
+
+    pub(crate) fn lex_with_comments(
+        &self,
+        buf: &[u8],
+    ) -> eyre::Result<(Vec<SpannedToken>, Vec<RawComment>)> {
+        let mut comments = Vec::new();
+        let tokens = self.lex_into(buf, Some(&mut comments))?;
+        Ok((tokens, comments))
+    }
+
+    fn lex_into(
+        &self,
+        buf: &[u8],
+        mut comments: Option<&mut Vec<RawComment>>,
+    ) -> eyre::Result<Vec<SpannedToken>> {
+        let mut tokens = Vec::new();
+        let mut i = self.start(buf);
+        while let Some(t) = self.next_token(buf, &mut i)? {
+            if t.token == Token::Comment {
+                if let Some(comments) = comments.as_mut() {
+                    comments.push(RawComment::new(buf, t.span.range(), tokens.len()));
+                }
+                continue;
+            }
+            tokens.push(t);
+            self.check_count(tokens.len())?;
+        }
+        Ok(tokens)
+    }
+
+    /// Lexes the token starting at or after `*pos` (skipping whitespace),
+    /// advancing `*pos` past it.
+    pub(crate) fn next_token(
+        &self,
+        buf: &[u8],
+        pos: &mut usize,
+    ) -> eyre::Result<Option<SpannedToken>> {
+        let mut i = *pos;
+        // ignore whitespace
+        while i < buf.len() && self.whitespace.contains(&buf[i]) {
+            i += 1;
+        }
+        *pos = i;
+        if i >= buf.len() {
+            return Ok(None);
+        }
+        let (token, end) = self.lex_one(buf, i)?;
+        *pos = end;
+        Ok(Some(SpannedToken {
+            token,
+            span: Span::new(i, end),
+        }))
+    }
+
+    fn lex_one(&self, buf: &[u8], i: usize) -> eyre::Result<(Token, usize)> {
+        let quote_sym: u8 = b'"';
+        let minus_sym: u8 = b'-';
+        let buf_len = buf.len();
+        let c = buf[i];
+
+        // handle comments
+        if self.allow_comments && c == b'/' {
+            return Ok((Token::Comment, comments::comment_end(buf, i)?));
+        }
+
+        // handle single len symbols
+        if let Some(si) = self.single_char_symbols.0.iter().position(|v| *v == c) {
+            return Ok((self.single_char_symbols.1[si], i + 1));
+        }
+        // handle strings
+        if c == quote_sym {
+            let j = escape::scan_string(buf, i, self.allow_control_chars)?.ok_or_eyre(format!(
+                "Missing end quote for the string starting at byte {}",
+                i
+            ))?;
+            return Ok((Token::StringVal(i + 1, j), j + 1));
+        }
+
+        // handle null and bools
+        let end = std::cmp::min(i + 4, buf_len);
+        let s = &buf[i..end];
+        for j in 0..self.multi_char_symbols.0.len() {
+            if s == self.multi_char_symbols.0[j] {
+                let t = self.multi_char_symbols.1[j];
+                if t == Token::BoolVal(false) {
+                    if end < buf_len && buf[end] == b'e' {
+                        return Ok((t, end + 1));
+                    } else {
+                        return Err(unexpected(buf, i));
+                    }
+                }
+                return Ok((t, end));
+            }
+        }
+        if self.allow_non_finite {
+            for (word, n) in [
+                (&b"NaN"[..], f64::NAN),
+                (b"Infinity", f64::INFINITY),
+                (b"-Infinity", f64::NEG_INFINITY),
+            ] {
+                if buf[i..].starts_with(word) {
+                    return Ok((Token::NumVal(n), i + word.len()));
+                }
+            }
+        }
+        if buf[i..].starts_with(b"-Infinity") {
+            return Err(unexpected(buf, i));
+        }
+        // handle numbers
+        if c == minus_sym || c.is_ascii_digit() {
+            let mut j = i + 1;
+            while j < buf_len && self.num_chars.contains(&buf[j]) {
+                j += 1;
+            }
+            let lexeme = &buf[i..j];
+            if !is_number(lexeme) {
+                eyre::bail!(format!(
+                    "Invalid number `{}` at byte {}",
+                    String::from_utf8_lossy(lexeme),
+                    i
+                ))
+            }
+            let num: f64 = std::str::from_utf8(lexeme)?.parse()?;
+            return Ok((Token::NumVal(num), j));
+        }
+
+        Err(unexpected(buf, i))
+    }
+}
+
+// Whether `s` is a JSON number: `-?(0|[1-9][0-9]*)(\.[0-9]+)?([eE][+-]?[0-9]+)?`.
+pub(crate) fn is_number(s: &[u8]) -> bool {
+    fn digits(s: &[u8]) -> usize {
+        s.iter().take_while(|b| b.is_ascii_digit()).count()
+    }
+    let s = s.strip_prefix(b"-").unwrap_or(s);
+    let int = digits(s);
+    if int == 0 || (int > 1 && s[0] == b'0') {
+        return false;
+    }
+    let mut s = &s[int..];
+    if let Some(rest) = s.strip_prefix(b".") {
+        let frac = digits(rest);
+        if frac == 0 {
+            return false;
+        }
+        s = &rest[frac..];
+    }
+    if let Some(rest) = s.strip_prefix(b"e").or_else(|| s.strip_prefix(b"E")) {
+        let rest = rest
+            .strip_prefix(b"+")
+            .or_else(|| rest.strip_prefix(b"-"))
+            .unwrap_or(rest);
+        let exp = digits(rest);
+        if exp == 0 {
+            return false;
+        }
+        s = &rest[exp..];
+    }
+    s.is_empty()
+}
+
+/// Describes what is wrong with the text at `buf[i]`, which starts no token,
+/// suggesting what was probably meant: `true` for `True`, `null` for `None`,
+/// double quotes for single ones.
+pub(crate) fn unexpected(buf: &[u8], i: usize) -> eyre::Report {
+    let c = buf[i];
+    match c {
+        b'\'' => {
+            return eyre::eyre!(format!(
+                "Unexpected `'` at byte {}: strings and keys need double quotes",
+                i
+            ))
+        }
+        b'/' => {
+            return eyre::eyre!(format!(
+                "Unexpected `/` at byte {}: comments aren't JSON, set `allow_comments` \
+                 to accept them",
+                i
+            ))
+        }
+        _ => {}
+    }
+    let len = buf[i..]
+        .iter()
+        .position(|b| !(b.is_ascii_alphanumeric() || matches!(b, b'_' | b'-' | b'.')))
+        .unwrap_or(buf.len() - i);
+    let word = String::from_utf8_lossy(&buf[i..i + len]);
+    let meant = match word.as_ref() {
+        "NaN" | "Infinity" | "-Infinity" => {
+            return eyre::eyre!(format!(
+                "Unexpected `{}` at byte {}: JSON has no non-finite numbers, \
+                 set `allow_non_finite` to accept them",
+                word, i
+            ))
+        }
+        "None" | "nil" | "undefined" => Some("null"),
+        w if ["true", "false", "null"].contains(&w.to_lowercase().as_str()) => {
+            return eyre::eyre!(format!(
+                "Unexpected `{}` at byte {}, did you mean `{}`?",
+                w,
+                i,
+                w.to_lowercase()
+            ))
+        }
+        w if !w.is_empty() => ["true", "false", "null"]
+            .into_iter()
+            .find(|full| full.starts_with(w) || w.starts_with(full)),
+        _ => None,
+    };
+    match meant {
+        Some(meant) => eyre::eyre!(format!(
+            "Unexpected `{}` at byte {}, did you mean `{}`?",
+            word, i, meant
+        )),
+        None if len > 0 && c.is_ascii_alphabetic() => eyre::eyre!(format!(
+            "Unexpected `{}` at byte {}: strings need double quotes",
+            word, i
+        )),
+        None => eyre::eyre!(format!(
+            "Unexpected character {:?} at byte {}",
+            String::from_utf8_lossy(&buf[i..buf.len().min(i + 4)])
+                .chars()
+                .next()
+                .unwrap_or(c as char),
+            i
+        )),
+    }
+}
+
+/// Replacement of the bytes `range` of the old input with `new_len` bytes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextEdit {
+    pub range: Range<usize>,
+    pub new_len: usize,
+}
+
+impl TextEdit {
+    fn delta(&self) -> isize {
+        self.new_len as isize - self.range.len() as isize
+    }
+}
+
+fn shift(t: SpannedToken, delta: isize) -> SpannedToken {
+    let mv = |p: usize| (p as isize + delta) as usize;
+    let token = match t.token {
+        Token::StringVal(i, j) => Token::StringVal(mv(i), mv(j)),
+        token => token,
+    };
+    SpannedToken {
+        token,
+        span: Span::new(mv(t.span.start), mv(t.span.end)),
+    }
+}
+
+impl Lexer {
+    /// Updates `tokens` (previously produced by `tokens()` from the old input)
+    /// to match `new_input`, the result of applying `edit`. Only the region
+    /// around the edit is re-lexed; once a token lines up with an old one
+    /// again the remaining old tokens are reused, shifted by the edit.
+    ///
+    /// On error `tokens` is left untouched.
+    pub fn relex(
+        &self,
+        tokens: &mut Vec<SpannedToken>,
+        new_input: &[u8],
+        edit: &TextEdit,
+    ) -> eyre::Result<()> {
+        let delta = edit.delta();
+        // tokens ending before the edit are unaffected, but one touching its
+        // start may grow (e.g. typing after a number)
+        let first = tokens.partition_point(|t| t.span.end < edit.range.start);
+        let mut pos = if first > 0 {
+            tokens[first - 1].span.end
+        } else {
+            0
+        };
+        // old tokens entirely after the edit are candidates for resyncing
+        let mut old = tokens.partition_point(|t| t.span.start < edit.range.end);
+        let mut relexed = Vec::new();
+        loop {
+            let Some(t) = self.next_token(new_input, &mut pos)? else {
+                old = tokens.len();
+                break;
+            };
+            while old < tokens.len()
+                && (tokens[old].span.start as isize + delta) < t.span.start as isize
+            {
+                old += 1;
+            }
+            if old < tokens.len()
+                && (tokens[old].span.start as isize + delta) == t.span.start as isize
+            {
+                break;
+            }
+            relexed.push(t);
+        }
+        for t in &mut tokens[old..] {
+            *t = shift(*t, delta);
+        }
+        tokens.splice(first..old, relexed);
+        Ok(())
+    }
+}
+
+/// Iterator over the tokens of an input, see `Lexer::tokens`. Stops after the
+/// first error.
+pub struct Tokens<'a> {
+    lexer: Lexer,
+    buf: &'a [u8],
+    pos: usize,
+    done: bool,
+    // tokens so far, not counting comments
+    count: usize,
+}
+
+impl Tokens<'_> {
+    /// Byte offset the next token will be lexed from.
+    pub fn position(&self) -> usize {
+        self.pos
+    }
+}
+
+impl Iterator for Tokens<'_> {
+    type Item = eyre::Result<SpannedToken>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let mut res = self.lexer.next_token(self.buf, &mut self.pos).transpose();
+        if let Some(Ok(t)) = &res {
+            if t.token != Token::Comment {
+                self.count += 1;
+                if let Err(e) = self.lexer.check_count(self.count) {
+                    res = Some(Err(e));
+                }
+            }
+        }
+        if !matches!(res, Some(Ok(_))) {
+            self.done = true;
+        }
+        res
+    }
+}
+
+/// Lazily lexes strict JSON `input`.
+pub fn tokens(input: &[u8]) -> Tokens<'_> {
+    Lexer::new().tokens(input)
+}
@@ -1,305 +1,548 @@
-#![allow(dead_code, unused_variables)]
-
 use core::fmt;
+use std::time::{Duration, Instant};
+
 use eyre::{Ok, OptionExt};
-use std::collections::HashMap;
-
-#[derive(Copy, Clone, PartialEq)]
-enum Token {
-    LeftBrace,
-    RightBrace,
-    LeftBracket,
-    RightBracket,
-    Comma,
-    Colon,
-    NullVal,
-    StringVal(usize, usize),
-    NumVal(f64),
-    BoolVal(bool),
-}
 
-impl fmt::Debug for Token {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        use Token::*;
-        let s = match self {
-            LeftBrace => "{",
-            RightBrace => "}",
-            LeftBracket => "[",
-            RightBracket => "]",
-            Comma => ",",
-            Colon => ":",
-            NullVal => "null",
-            StringVal(i, j) => return write!(f, "Token(str_range:[{}:{}])", i, j),
-            BoolVal(b) => {
-                if *b {
-                    "true"
-                } else {
-                    "false"
-                }
-            }
-            NumVal(n) => return write!(f, "Token('{}')", n),
-        };
-        write!(f, "Token('{}')", s)
-    }
+use lexer::{Lexer, Span, SpannedToken, Token};
+
+mod anonymize;
+mod binary;
+mod builder;
+mod bundle;
+mod cache;
+mod channel;
+mod codegen;
+mod coerce;
+mod columnar;
+mod comments;
+mod config_edit;
+mod constraints;
+mod convert;
+mod crdt;
+mod cursor;
+mod datetime;
+mod deadline;
+mod decode;
+mod dedup;
+mod dialect;
+mod document;
+mod env;
+mod escape;
+mod explain;
+#[cfg(feature = "bson")]
+mod extjson;
+mod extract;
+mod fields;
+mod format;
+mod framing;
+mod generate;
+#[cfg(feature = "geojson")]
+mod geojson;
+mod highlight;
+mod hooks;
+pub mod http;
+mod incremental;
+pub mod jsonrpc;
+mod jwt;
+pub mod lexer;
+mod media;
+mod merge;
+mod metrics;
+mod normalize;
+mod number;
+mod observe;
+mod openapi;
+mod options;
+mod outline;
+mod patch;
+mod pattern;
+#[cfg(feature = "im")]
+mod persistent;
+mod pointer;
+mod pool;
+mod prefix;
+mod profile;
+mod progress;
+mod protojson;
+mod records;
+mod reformat;
+mod sample;
+mod schema;
+mod serialize;
+mod session;
+mod shards;
+mod shared;
+mod sort;
+mod split;
+mod step;
+mod syntax;
+mod tree;
+mod truncate;
+mod value;
+mod visit;
+mod warnings;
+
+pub use anonymize::{anonymize, AnonymizeOptions};
+pub use binary::Bytes;
+pub use builder::{ArrayBuilder, ObjectBuilder};
+pub use bundle::{Bundle, BundleWriter};
+pub use cache::ParseCache;
+#[cfg(feature = "tokio")]
+pub use channel::spawn_records_tokio;
+pub use channel::{spawn_records, RecordReceiver};
+pub use columnar::{BatchBuilder, Column, ColumnBatch, ColumnValues};
+pub use comments::{parse_with_comments, Comment, CommentPlacement};
+pub use config_edit::ConfigEditor;
+pub use constraints::NumberConstraint;
+pub use crdt::CrdtValue;
+pub use cursor::Cursor;
+pub use datetime::{Rfc3339, DATE_TAG};
+pub use deadline::{parse_with_deadline, TimedOut};
+pub use decode::{
+    decode, decode_with_options, parse_array_of, parse_object, Decoder, FromJson, Presence,
+    UnknownFields,
+};
+pub use dedup::{dedup_lines, Dedup, DedupRecord};
+pub use dialect::{Json5Format, JsoncFormat};
+pub use document::{Document, Node, NodeKind};
+pub use escape::{unescape, LoneSurrogates};
+pub use explain::explain_error;
+#[cfg(feature = "bson")]
+pub use extjson::ExtJsonMode;
+pub use extract::{FromValue, ValueMismatch};
+pub use fields::{field_matches, map_fields, map_into, FieldSet};
+pub use format::{CompactFormat, JsonFormat, PrettyFormat};
+pub use framing::{LengthPrefix, LengthPrefixedReader, SseEvent, SseReader};
+pub use generate::{GenerateOptions, Generator, TypeWeights};
+#[cfg(feature = "geojson")]
+pub use geojson::{Feature, FeatureCollection, GeoJson, Geometry, Position};
+pub use highlight::{highlight, TokenClass};
+pub use hooks::ParseHooks;
+pub use incremental::IncrementalParser;
+#[cfg(feature = "derive")]
+pub use json_parser_derive::FromJson;
+pub use jwt::{decode_jwt_claims, decode_jwt_header};
+pub use media::{HalLink, HalResource, Problem, HAL_JSON, PROBLEM_JSON};
+pub use merge::{merge3, Conflict, Merged};
+pub use metrics::{parse_with_metrics, ParseMetrics, ParseStats};
+#[cfg(feature = "unicode-normalization")]
+pub use normalize::UnicodeForm;
+pub use number::{BigNumbers, Number};
+pub use observe::{Change, ObservableDocument, SubscriptionId};
+pub use openapi::{OpenApi, Operation};
+pub use options::{ParseOptions, Profile, DEFAULT_MAX_DEPTH};
+pub use outline::{outline, Region, RegionKind};
+pub use patch::{Patch, PatchOp};
+pub use pattern::{transform_at, PathPattern};
+#[cfg(feature = "im")]
+pub use persistent::PersistentValue;
+pub use pool::ValuePool;
+pub use prefix::{parse_prefix, parse_prefix_with_options, PrefixParse, PrefixStatus};
+pub use profile::{profile_records, profile_records_with_progress, PathProfile, Profiler};
+pub use progress::{Progress, ProgressReport};
+pub use protojson::{proto_json_name, ProtoAny, ANY_TYPE};
+pub use records::{parse_auto, parse_auto_with_progress, sample_lines, InputFormat, Records};
+pub use reformat::{reformat, ReformatStyle};
+pub use schema::{Schema, Violation};
+pub use serialize::SerializeOptions;
+pub use session::EditSession;
+pub use shards::{join, parse_shard_files, parse_shards, MergeStrategy};
+pub use shared::SharedValue;
+pub use sort::{sort_lines, sort_lines_with_progress, SortOptions};
+pub use split::{split, split_with_progress};
+pub use step::{Parser, StepResult};
+pub use syntax::{expected_next, Completion};
+pub use truncate::{truncate, TruncateOptions, TRUNCATED};
+pub use value::{
+    BTreeMapKind, FromJsonNumber, HashMapKind, MapKey, MapKind, MapOps, ToJsonNumber, Value,
+    DEBUG_DEPTH, DEBUG_WIDTH,
+};
+pub use visit::{VisitAction, VisitContext, Visitor};
+pub use warnings::{parse_with_warnings, Warning, WarningKind};
+
+#[cfg(feature = "derive")]
+#[doc(hidden)]
+pub mod __private {
+    pub use eyre;
 }
 
-struct Lexer {
-    whitespace: Vec<u8>,
-    single_char_symbols: (Vec<u8>, Vec<Token>), // mapping
-    multi_char_symbols: (Vec<Vec<u8>>, Vec<Token>), // mapping
-    num_chars: Vec<u8>,
+// Tokens lexed or values parsed between looks at the clock.
+const DEADLINE_CHECK_EVERY: usize = 256;
+
+const BOM: &[u8] = b"\xEF\xBB\xBF";
+
+/// The default value model: `String`s, `f64` numbers and `HashMap` objects.
+pub type JSONValue = Value;
+
+struct RecursiveParser<'b, 'p, 'h> {
+    buf: &'b [u8],
+    pool: &'p mut ValuePool,
+    hooks: Option<&'p mut ParseHooks<'h>>,
+    options: &'p ParseOptions,
+    depth: usize,
+    // JSON Pointer of the value being parsed, only tracked when a hook needs it
+    path: Option<String>,
+    // how many tokens `parse` lexed
+    tokens: usize,
+    // when to give up, and the timeout it came from
+    deadline: Option<(Instant, Duration)>,
+    steps: usize,
+    // whether `WarningKind::DeepNesting` was given
+    warned_depth: bool,
 }
 
-impl Lexer {
-    fn new() -> Self {
-        let whitespace: Vec<u8> = [' ', '\t', '\r', '\n']
-            .into_iter()
-            .map(|v| v as u8)
-            .collect();
-        use Token::*;
-        let single_char_symbols = (
-            ['{', '}', '[', ']', ',', ':']
-                .into_iter()
-                .map(|v| v as u8)
-                .collect(),
-            vec![
-                LeftBrace,
-                RightBrace,
-                LeftBracket,
-                RightBracket,
-                Comma,
-                Colon,
-            ],
-        );
-        let multi_char_symbols = (
-            ["null", "true", "fals"]
-                .into_iter()
-                .map(|s| s.as_bytes().to_vec())
-                .collect(),
-            vec![NullVal, BoolVal(true), BoolVal(false)],
-        );
-        let num_chars: Vec<u8> = "0123456789.e".chars().map(|v| v as u8).collect();
+impl<'b, 'p, 'h> RecursiveParser<'b, 'p, 'h> {
+    fn new(
+        buf: &'b [u8],
+        pool: &'p mut ValuePool,
+        hooks: Option<&'p mut ParseHooks<'h>>,
+        options: &'p ParseOptions,
+    ) -> Self {
+        let path = hooks
+            .as_ref()
+            .filter(|h| h.tracks_pointers())
+            .map(|_| String::new());
         Self {
-            whitespace: whitespace,
-            single_char_symbols: single_char_symbols,
-            multi_char_symbols: multi_char_symbols,
-            num_chars: num_chars,
+            buf,
+            pool,
+            hooks,
+            options,
+            depth: 0,
+            path,
+            tokens: 0,
+            deadline: None,
+            steps: 0,
+            warned_depth: false,
         }
     }
-    fn lex(&self, buf: &[u8]) -> eyre::Result<Vec<Token>> {
-        let mut tokens = Vec::new();
-        let quote_sym: u8 = '"' as u8;
-        let minus_sym: u8 = '-' as u8;
-        let mut i = 0;
-        let buf_len = buf.len();
-        'outer: loop {
-            if i >= buf_len {
-                break;
+
+    fn warns(&self) -> bool {
+        self.hooks.as_ref().is_some_and(|h| h.on_warning.is_some())
+    }
+
+    fn warn(&mut self, kind: WarningKind, pointer: &str, offset: usize, message: String) {
+        if let Some(on_warning) = self.hooks.as_mut().and_then(|h| h.on_warning.as_mut()) {
+            on_warning(Warning::new(kind, pointer, offset, message));
+        }
+    }
+
+    // Appends `token` to the tracked pointer, returning the length to pop
+    // back to.
+    fn push_path(&mut self, token: &dyn fmt::Display) -> usize {
+        match self.path.as_mut() {
+            Some(path) => {
+                let len = path.len();
+                pointer::push(path, &token.to_string());
+                len
             }
+            None => 0,
+        }
+    }
 
-            let c = buf[i];
+    fn pop_path(&mut self, len: usize) {
+        if let Some(path) = self.path.as_mut() {
+            path.truncate(len);
+        }
+    }
 
-            // ignore whitespace
-            if self.whitespace.contains(&c) {
-                i += 1;
-                continue 'outer;
+    fn enter(&mut self, offset: usize) -> eyre::Result<()> {
+        self.depth += 1;
+        if let Some(max_depth) = self.options.max_depth {
+            if self.depth > max_depth {
+                eyre::bail!(format!("Nesting exceeds maximum depth of {}", max_depth))
             }
+        }
+        let limit = self.options.max_depth.unwrap_or(DEFAULT_MAX_DEPTH);
+        if !self.warned_depth && self.depth > limit / 2 && self.warns() {
+            self.warn_depth(offset, limit);
+        }
+        Ok(())
+    }
+
+    // Out of line so `parse_value`'s frame stays small.
+    #[inline(never)]
+    fn warn_depth(&mut self, offset: usize, limit: usize) {
+        self.warned_depth = true;
+        let ptr = self.path.clone().unwrap_or_default();
+        let message = format!(
+            "Nesting reaches depth {}, over half the limit of {}",
+            self.depth, limit
+        );
+        self.warn(WarningKind::DeepNesting, &ptr, offset, message);
+    }
 
-            // handle single len symbols
-            if let Some(si) = self.single_char_symbols.0.iter().position(|v| *v == c) {
-                tokens.push(self.single_char_symbols.1[si]);
-                i += 1;
-                continue 'outer;
+    fn tick(&mut self) -> eyre::Result<()> {
+        self.steps += 1;
+        if let Some((at, timeout)) = self.deadline {
+            if self.steps.is_multiple_of(DEADLINE_CHECK_EVERY) && Instant::now() >= at {
+                return Err(eyre::Report::new(TimedOut { timeout }));
             }
-            // handle strings
-            if c == quote_sym {
-                let mut j = i + 1;
-                loop {
-                    if j >= buf_len {
-                        eyre::bail!("Missing end quote for string");
-                    }
-                    if buf[j] == quote_sym {
-                        tokens.push(Token::StringVal(i + 1, j));
-                        i = j + 1;
-                        continue 'outer;
-                    }
-                    j += 1;
-                }
+        }
+        Ok(())
+    }
+
+    fn lex(&mut self) -> eyre::Result<Vec<SpannedToken>> {
+        let lexer = Lexer::with_options(self.options);
+        let bom = self.options.strip_bom && self.buf.starts_with(BOM);
+        if bom {
+            let message = "Skipped a byte order mark at the start of the input".to_string();
+            self.warn(WarningKind::BomStripped, "", 0, message);
+        } else if self.deadline.is_none() {
+            return lexer.lex(self.buf);
+        }
+        let mut tokens = Vec::new();
+        // offsets stay those of the whole input
+        let mut pos = if bom { BOM.len() } else { 0 };
+        while let Some(t) = lexer.next_token(self.buf, &mut pos)? {
+            self.tick()?;
+            if t.token != Token::Comment {
+                tokens.push(t);
+                lexer.check_count(tokens.len())?;
             }
+        }
+        Ok(tokens)
+    }
 
-            // handle null and bools
-            let end = std::cmp::min(i + 4, buf_len);
-            let s = &buf[i..end];
-            for j in 0..self.multi_char_symbols.0.len() {
-                if s == &self.multi_char_symbols.0[j] {
-                    let t = self.multi_char_symbols.1[j];
-                    if t == Token::BoolVal(false) {
-                        if end < buf_len && buf[end] == 'e' as u8 {
-                            i = end + 1;
-                        } else {
-                            eyre::bail!("Incomplete false value");
-                        }
-                    } else {
-                        i = end;
-                    }
-                    tokens.push(t);
-                    continue 'outer;
+    fn parse_array<'a>(
+        &mut self,
+        tokens: &'a [SpannedToken],
+    ) -> eyre::Result<(JSONValue, &'a [SpannedToken])> {
+        let mut entries = self.pool.take_vec();
+        // handle empty array
+        let t = tokens.first().ok_or_eyre("Expected value")?.token;
+        if t == Token::RightBracket {
+            return Ok((JSONValue::Array(entries), &tokens[1..]));
+        }
+        // handle non-empty
+        let mut tokens = tokens;
+        loop {
+            let len = self.push_path(&entries.len());
+            let (val, rest) = self.parse_value(tokens)?;
+            self.pop_path(len);
+            entries.push(val);
+            tokens = rest;
+            let token = tokens.first().ok_or_eyre("Expected value")?.token;
+            match token {
+                Token::RightBracket => {
+                    return Ok((JSONValue::Array(entries), &tokens[1..]));
                 }
-            }
-            // handle numbers
-            if c == minus_sym || (c >= 48 && c <= 57) {
-                let mut j = i + 1;
-                while j < buf_len {
-                    if !self.num_chars.contains(&buf[j]) {
-                        let num_as_buf = &buf[i..j];
-                        let num: f64 = std::str::from_utf8(num_as_buf)?.parse()?;
-                        tokens.push(Token::NumVal(num));
-                        i = j;
-                        continue 'outer;
-                    }
-                    j += 1;
+                Token::Comma => {
+                    tokens = &tokens[1..];
+                    continue;
                 }
+                _ => eyre::bail!("Unexpected value for array"),
             }
-
-            // error
-            eyre::bail!(format!("Unexpected value: '{}'", c as char));
         }
-        return Ok(tokens);
     }
 
-    fn lex_multichar_symbol(&self, lexeme: &[u8]) -> Option<Token> {
-        for i in 0..self.multi_char_symbols.0.len() {
-            if lexeme == &self.multi_char_symbols.0[i] {
-                return Some(self.multi_char_symbols.1[i]);
+    fn parse_string(&mut self, i: usize, j: usize, is_key: bool) -> eyre::Result<String> {
+        let s = escape::unescape_str(
+            &self.buf[i..j],
+            self.options.lone_surrogates,
+            self.pool.take_string(),
+        )?;
+        Ok(normalize::apply(s, self.options, is_key))
+    }
+
+    // Kept out of `parse_value` so its stack frame, paid once per nesting
+    // level, stays small.
+    fn parse_number(&mut self, span: Span, n: f64) -> eyre::Result<JSONValue> {
+        let lexeme = self
+            .buf
+            .get(span.range())
+            .ok_or_eyre("Number span outside the input")?;
+        let lexeme = std::str::from_utf8(lexeme)?;
+        let ptr = self.path.as_deref().unwrap_or_default();
+        if let Some(why) = self.hooks.as_ref().and_then(|h| h.check_number(ptr, n)) {
+            match &self.path {
+                Some(ptr) => eyre::bail!(format!(
+                    "Number {} at {} (byte {}) {}",
+                    lexeme, ptr, span.start, why
+                )),
+                None => eyre::bail!(format!("Number {} at byte {} {}", lexeme, span.start, why)),
             }
         }
-        return None;
+        if self.options.big_numbers == BigNumbers::LoseToF64
+            && self.warns()
+            // not `NaN` or `Infinity`, which are exact
+            && lexeme.bytes().any(|b| b.is_ascii_digit())
+            && number::f64_loses_precision(lexeme, n)
+        {
+            let ptr = self.path.clone().unwrap_or_default();
+            let message = format!("Number {} was rounded to {}", lexeme, n);
+            self.warn(WarningKind::PrecisionLoss, &ptr, span.start, message);
+        }
+        let v = match number::convert(lexeme, n, self.options.big_numbers)? {
+            None => JSONValue::Str(lexeme.to_string()),
+            Some(n) => match self.hooks.as_mut().and_then(|h| h.on_number.as_mut()) {
+                Some(on_number) => on_number(n)?,
+                None => JSONValue::Num(n),
+            },
+        };
+        Ok(v)
     }
-}
 
-#[derive(Debug)]
-pub enum JSONValue {
-    Null,
-    Bool(bool),
-    Str(String),
-    Num(f64),
-    Array(Vec<JSONValue>),
-    Dict(HashMap<String, JSONValue>),
-}
+    fn parse_dict_entry<'a>(
+        &mut self,
+        tokens: &'a [SpannedToken],
+    ) -> eyre::Result<((String, JSONValue), &'a [SpannedToken])> {
+        if tokens.len() < 3 {
+            eyre::bail!("Object entry incomplete")
+        }
+        // get key
+        let mut key: String;
+        if let Token::StringVal(i, j) = tokens[0].token {
+            key = self.parse_string(i, j, true)?;
+        } else {
+            eyre::bail!("Expected string for key")
+        }
+        if let Some(on_key) = self.hooks.as_mut().and_then(|h| h.on_key.as_mut()) {
+            key = on_key(key)?;
+        }
+        // handle colon
+        if tokens[1].token != Token::Colon {
+            eyre::bail!("Expected colon")
+        }
+        // get val
+        let len = self.push_path(&key);
+        let (val, rest) = self.parse_value(&tokens[2..])?;
+        self.pop_path(len);
+        Ok(((key, val), rest))
+    }
 
-fn parse_array<'a, 'b>(
-    tokens: &'a [Token],
-    buf: &'b [u8],
-) -> eyre::Result<(JSONValue, &'a [Token])> {
-    let mut entries = Vec::new();
-    // handle empty array
-    let t = *tokens.get(0).ok_or_eyre("Expected value")?;
-    if t == Token::RightBracket {
-        return Ok((JSONValue::Array(entries), &tokens[1..]));
+    // Out of line like `warn_depth`.
+    #[inline(never)]
+    fn warn_duplicate(&mut self, key: &str, offset: usize) {
+        let mut ptr = self.path.clone().unwrap_or_default();
+        pointer::push(&mut ptr, key);
+        let message = "Duplicate key; the last value is kept".to_string();
+        self.warn(WarningKind::DuplicateKey, &ptr, offset, message);
     }
-    // handle non-empty
-    let mut tokens = tokens;
-    loop {
-        let (val, rest) = parse_value(tokens, buf)?;
-        entries.push(val);
-        tokens = rest;
-        let token = *tokens.get(0).ok_or_eyre("Expected value")?;
-        match token {
-            Token::RightBracket => {
-                return Ok((JSONValue::Array(entries), &tokens[1..]));
+
+    fn parse_dict<'a>(
+        &mut self,
+        tokens: &'a [SpannedToken],
+    ) -> eyre::Result<(JSONValue, &'a [SpannedToken])> {
+        let mut entries = self.pool.take_map();
+        // handle empty dict
+        let t = tokens.first().ok_or_eyre("Expected value")?.token;
+        if t == Token::RightBrace {
+            return Ok((JSONValue::Dict(entries), &tokens[1..]));
+        }
+        // handle rest
+        let mut tokens = tokens;
+        loop {
+            let ((key, val), rest) = self.parse_dict_entry(tokens)?;
+            if self.warns() && entries.contains_key(&key) {
+                self.warn_duplicate(&key, tokens[0].span.start);
             }
-            Token::Comma => {
-                tokens = &tokens[1..];
-                continue;
+            entries.insert(key, val);
+            tokens = rest;
+            let token = tokens.first().ok_or_eyre("Expected value")?.token;
+            match token {
+                Token::RightBrace => {
+                    return Ok((JSONValue::Dict(entries), &tokens[1..]));
+                }
+                Token::Comma => {
+                    tokens = &tokens[1..];
+                    continue;
+                }
+                _ => eyre::bail!("Unexpected value for dict"),
             }
-            _ => eyre::bail!("Unexpected value for array"),
         }
     }
-}
 
-fn parse_dict_entry<'a, 'b>(
-    tokens: &'a [Token],
-    buf: &'b [u8],
-) -> eyre::Result<((String, JSONValue), &'a [Token])> {
-    if tokens.len() < 3 {
-        eyre::bail!("Object entry incomplete")
-    }
-    // get key
-    let key: String;
-    if let Token::StringVal(i, j) = tokens[0] {
-        key = String::from_utf8((&buf[i..j]).to_vec())?;
-    } else {
-        eyre::bail!("Expected string for key")
-    }
-    // handle colon
-    if tokens[1] != Token::Colon {
-        eyre::bail!("Expected colon")
+    fn parse_value<'a>(
+        &mut self,
+        tokens: &'a [SpannedToken],
+    ) -> eyre::Result<(JSONValue, &'a [SpannedToken])> {
+        self.tick()?;
+        let t = tokens.first().ok_or_eyre("Expected value")?.token;
+        let rest = &tokens[1..];
+        let v = match t {
+            Token::BoolVal(b) => JSONValue::Bool(b),
+            Token::NullVal => JSONValue::Null,
+            Token::NumVal(n) => self.parse_number(tokens[0].span, n)?,
+            Token::StringVal(i, j) => {
+                let s = self.parse_string(i, j, false)?;
+                match self.hooks.as_mut().and_then(|h| h.on_string.as_mut()) {
+                    Some(on_string) => on_string(s)?,
+                    None => JSONValue::Str(s),
+                }
+            }
+            Token::LeftBrace | Token::LeftBracket => {
+                self.enter(tokens[0].span.start)?;
+                let res = if t == Token::LeftBrace {
+                    self.parse_dict(rest)
+                } else {
+                    self.parse_array(rest)
+                };
+                self.depth -= 1;
+                return res;
+            }
+            Token::RightBrace
+            | Token::RightBracket
+            | Token::Comma
+            | Token::Colon
+            | Token::Comment => eyre::bail!(format!("Expected value, found {:?}", t)),
+        };
+        Ok((v, rest))
     }
-    // get val
-    let (val, rest) = parse_value(&tokens[2..], buf)?;
-    return Ok(((key, val), rest));
-}
 
-fn parse_dict<'a, 'b>(
-    tokens: &'a [Token],
-    buf: &'b [u8],
-) -> eyre::Result<(JSONValue, &'a [Token])> {
-    let mut entries = HashMap::new();
-    // handle empty dict
-    let t = *tokens.get(0).ok_or_eyre("Expected value")?;
-    if t == Token::RightBracket {
-        return Ok((JSONValue::Dict(entries), &tokens[1..]));
-    }
-    // handle rest
-    let mut tokens = tokens;
-    loop {
-        let ((key, val), rest) = parse_dict_entry(tokens, buf)?;
-        entries.insert(key, val);
-        tokens = rest;
-        let token = *tokens.get(0).ok_or_eyre("Expected value")?;
-        match token {
-            Token::RightBrace => {
-                return Ok((JSONValue::Dict(entries), &tokens[1..]));
-            }
-            Token::Comma => {
-                tokens = &tokens[1..];
-                continue;
+    fn parse(&mut self) -> eyre::Result<JSONValue> {
+        if let Some(max_size) = self.options.max_size {
+            if self.buf.len() > max_size {
+                eyre::bail!(format!(
+                    "Input of {} bytes exceeds maximum size of {}",
+                    self.buf.len(),
+                    max_size
+                ))
             }
-            _ => eyre::bail!("Unexpected value for dict"),
         }
+        let tokens = self.lex()?;
+        self.tokens = tokens.len();
+        self.parse_tokens(&tokens)
     }
-}
 
-fn parse_value<'a, 'b>(
-    tokens: &'a [Token],
-    buf: &'b [u8],
-) -> eyre::Result<(JSONValue, &'a [Token])> {
-    let t = tokens.get(0).ok_or_eyre("Expected value")?;
-    let rest = &tokens[1..];
-    let v = match t {
-        Token::BoolVal(b) => JSONValue::Bool(*b),
-        Token::NullVal => JSONValue::Null,
-        Token::NumVal(n) => JSONValue::Num(*n),
-        Token::StringVal(i, j) => {
-            let s = String::from_utf8((&buf[*i..*j]).to_vec())?;
-            JSONValue::Str(s)
-        }
-        Token::LeftBrace => return parse_dict(rest, buf),
-        Token::LeftBracket => return parse_array(rest, buf),
-        _ => {
-            println!("bozo tok: {:?}", t);
-            todo!("parse gen")
+    fn parse_many(&mut self) -> eyre::Result<Vec<JSONValue>> {
+        let lexer = Lexer::with_options(self.options);
+        let tokens = lexer.lex(self.buf)?;
+        let mut tokens = &tokens[..];
+        let mut values = Vec::new();
+        while !tokens.is_empty() {
+            let (val, rest) = self.parse_value(tokens)?;
+            values.push(val);
+            tokens = rest;
         }
-    };
-    Ok((v, rest))
+        Ok(values)
+    }
+
+    fn parse_tokens(&mut self, tokens: &[SpannedToken]) -> eyre::Result<JSONValue> {
+        let (json_val, rest) = self.parse_value(tokens)?;
+        if !rest.is_empty() {
+            eyre::bail!("Invalid JSON contains extra content")
+        };
+        Ok(json_val)
+    }
 }
 
+/// Parses one JSON document. Malformed input of any kind is an error, never a
+/// panic.
 pub fn parse(json: &[u8]) -> eyre::Result<JSONValue> {
-    let lexer = Lexer::new();
-    let tokens = lexer.lex(json)?;
-    let (json_val, rest) = parse_value(&tokens, json)?;
-    if rest.len() > 0 {
-        eyre::bail!("Invalid JSON contains extra content")
-    };
-    return Ok(json_val);
+    parse_with_pool(json, &mut ValuePool::new())
+}
+
+/// Parses `json`, running `hooks` on keys and values as they are encountered.
+pub fn parse_with_hooks(json: &[u8], hooks: &mut ParseHooks) -> eyre::Result<JSONValue> {
+    let mut pool = ValuePool::new();
+    RecursiveParser::new(json, &mut pool, Some(hooks), &ParseOptions::default()).parse()
+}
+
+pub(crate) fn parse_with_pool(json: &[u8], pool: &mut ValuePool) -> eyre::Result<JSONValue> {
+    RecursiveParser::new(json, pool, None, &ParseOptions::default()).parse()
+}
+
+pub fn parse_with_options(json: &[u8], options: &ParseOptions) -> eyre::Result<JSONValue> {
+    let mut pool = ValuePool::new();
+    RecursiveParser::new(json, &mut pool, None, options).parse()
 }
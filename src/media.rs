@@ -0,0 +1,322 @@
+use std::collections::HashMap;
+
+use crate::JSONValue;
+
+/// Content type of `Problem` bodies.
+pub const PROBLEM_JSON: &str = "application/problem+json";
+/// Content type of `HalResource` bodies.
+pub const HAL_JSON: &str = "application/hal+json";
+
+// The standard reason phrase of common statuses, the title RFC 9457 asks
+// for when the problem type is `about:blank`.
+fn reason(status: u16) -> Option<&'static str> {
+    Some(match status {
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        402 => "Payment Required",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        406 => "Not Acceptable",
+        408 => "Request Timeout",
+        409 => "Conflict",
+        410 => "Gone",
+        411 => "Length Required",
+        412 => "Precondition Failed",
+        413 => "Content Too Large",
+        415 => "Unsupported Media Type",
+        422 => "Unprocessable Content",
+        428 => "Precondition Required",
+        429 => "Too Many Requests",
+        500 => "Internal Server Error",
+        501 => "Not Implemented",
+        502 => "Bad Gateway",
+        503 => "Service Unavailable",
+        504 => "Gateway Timeout",
+        _ => return None,
+    })
+}
+
+/// Builds an RFC 9457 (formerly 7807) problem details object, the body of
+/// a `application/problem+json` error response.
+///
+/// ```
+/// use json_parser::Problem;
+///
+/// let body = Problem::new(404)
+///     .detail("No order 42")
+///     .extension("order_id", 42.0)
+///     .build();
+/// ```
+#[derive(Debug, Clone)]
+pub struct Problem {
+    type_uri: Option<String>,
+    title: Option<String>,
+    status: u16,
+    detail: Option<String>,
+    instance: Option<String>,
+    extensions: HashMap<String, JSONValue>,
+}
+
+impl Problem {
+    pub fn new(status: u16) -> Self {
+        Self {
+            type_uri: None,
+            title: None,
+            status,
+            detail: None,
+            instance: None,
+            extensions: HashMap::new(),
+        }
+    }
+
+    /// A URI naming the kind of problem. Without one the type is
+    /// `about:blank` and the title defaults to the status's reason phrase.
+    pub fn type_uri(mut self, uri: impl Into<String>) -> Self {
+        self.type_uri = Some(uri.into());
+        self
+    }
+
+    /// A short summary, the same for every occurrence of this type.
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    /// What went wrong this time.
+    pub fn detail(mut self, detail: impl Into<String>) -> Self {
+        self.detail = Some(detail.into());
+        self
+    }
+
+    /// A URI for this occurrence.
+    pub fn instance(mut self, uri: impl Into<String>) -> Self {
+        self.instance = Some(uri.into());
+        self
+    }
+
+    /// A member of your own, e.g. the invalid fields. One named like a
+    /// standard member is dropped; set those through their methods.
+    pub fn extension(mut self, key: impl Into<String>, value: impl Into<JSONValue>) -> Self {
+        self.extensions.insert(key.into(), value.into());
+        self
+    }
+
+    pub fn build(self) -> JSONValue {
+        let title = match (&self.title, &self.type_uri) {
+            (Some(title), _) => Some(title.as_str()),
+            (None, None) => reason(self.status),
+            (None, Some(_)) => None,
+        };
+        let mut d = self.extensions;
+        let members = [
+            ("type", self.type_uri.as_deref()),
+            ("title", title),
+            ("detail", self.detail.as_deref()),
+            ("instance", self.instance.as_deref()),
+        ];
+        for (key, value) in members {
+            match value {
+                Some(v) => d.insert(key.to_string(), JSONValue::Str(v.to_string())),
+                None => d.remove(key),
+            };
+        }
+        d.insert("status".to_string(), JSONValue::Num(self.status as f64));
+        JSONValue::Dict(d)
+    }
+}
+
+impl From<Problem> for JSONValue {
+    fn from(p: Problem) -> Self {
+        p.build()
+    }
+}
+
+/// A HAL link object. Converts from a `&str` or `String` href.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HalLink {
+    href: String,
+    templated: bool,
+    title: Option<String>,
+    name: Option<String>,
+    type_hint: Option<String>,
+}
+
+impl HalLink {
+    pub fn new(href: impl Into<String>) -> Self {
+        Self {
+            href: href.into(),
+            templated: false,
+            title: None,
+            name: None,
+            type_hint: None,
+        }
+    }
+
+    /// Marks `href` as a URI template, e.g. `/orders{?page}`.
+    pub fn templated(mut self) -> Self {
+        self.templated = true;
+        self
+    }
+
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    /// Tells apart links of the same relation.
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// The media type expected at `href`.
+    pub fn type_hint(mut self, media_type: impl Into<String>) -> Self {
+        self.type_hint = Some(media_type.into());
+        self
+    }
+
+    pub fn build(self) -> JSONValue {
+        let mut d = HashMap::from([("href".to_string(), JSONValue::Str(self.href))]);
+        if self.templated {
+            d.insert("templated".to_string(), JSONValue::Bool(true));
+        }
+        let members = [
+            ("title", self.title),
+            ("name", self.name),
+            ("type", self.type_hint),
+        ];
+        for (key, value) in members {
+            if let Some(v) = value {
+                d.insert(key.to_string(), JSONValue::Str(v));
+            }
+        }
+        JSONValue::Dict(d)
+    }
+}
+
+impl From<&str> for HalLink {
+    fn from(href: &str) -> Self {
+        Self::new(href)
+    }
+}
+
+impl From<String> for HalLink {
+    fn from(href: String) -> Self {
+        Self::new(href)
+    }
+}
+
+impl From<HalLink> for JSONValue {
+    fn from(l: HalLink) -> Self {
+        l.build()
+    }
+}
+
+// Links or embedded resources under one relation: a single one is written
+// as an object unless the relation was declared a list.
+#[derive(Debug, Clone, Default)]
+struct Relation {
+    entries: Vec<JSONValue>,
+    list: bool,
+}
+
+impl Relation {
+    fn build(self) -> JSONValue {
+        match <[JSONValue; 1]>::try_from(self.entries) {
+            Ok([one]) if !self.list => one,
+            Ok(one) => JSONValue::Array(one.into()),
+            Err(entries) => JSONValue::Array(entries),
+        }
+    }
+}
+
+/// Builds a HAL (`application/hal+json`) resource: its own fields plus
+/// `_links` and `_embedded`.
+///
+/// ```
+/// use json_parser::HalResource;
+///
+/// let order = HalResource::new()
+///     .link("self", "/orders/42")
+///     .field("total", 30.0)
+///     .build();
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct HalResource {
+    fields: HashMap<String, JSONValue>,
+    links: HashMap<String, Relation>,
+    embedded: HashMap<String, Relation>,
+}
+
+impl HalResource {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn field(mut self, key: impl Into<String>, value: impl Into<JSONValue>) -> Self {
+        self.fields.insert(key.into(), value.into());
+        self
+    }
+
+    /// Adds a link. A relation with one link is written as an object and
+    /// one with more as an array, except `curies`, which is always an
+    /// array.
+    pub fn link(mut self, rel: impl Into<String>, link: impl Into<HalLink>) -> Self {
+        let rel = rel.into();
+        let relation = self.links.entry(rel.clone()).or_default();
+        relation.list |= rel == "curies";
+        relation.entries.push(link.into().build());
+        self
+    }
+
+    /// Declares `rel` as holding a list of links, so it's written as an
+    /// array even with one or none.
+    pub fn link_list(mut self, rel: impl Into<String>) -> Self {
+        self.links.entry(rel.into()).or_default().list = true;
+        self
+    }
+
+    /// Embeds a resource, written as an object or, once there are more
+    /// under `rel`, an array.
+    pub fn embed(mut self, rel: impl Into<String>, resource: HalResource) -> Self {
+        let relation = self.embedded.entry(rel.into()).or_default();
+        relation.entries.push(resource.build());
+        self
+    }
+
+    /// Embeds a collection under `rel`, always written as an array.
+    pub fn embed_list(
+        mut self,
+        rel: impl Into<String>,
+        resources: impl IntoIterator<Item = HalResource>,
+    ) -> Self {
+        let relation = self.embedded.entry(rel.into()).or_default();
+        relation.list = true;
+        relation
+            .entries
+            .extend(resources.into_iter().map(HalResource::build));
+        self
+    }
+
+    pub fn build(self) -> JSONValue {
+        let mut d = self.fields;
+        let sections = [("_links", self.links), ("_embedded", self.embedded)];
+        for (key, relations) in sections {
+            if !relations.is_empty() {
+                let section = relations
+                    .into_iter()
+                    .map(|(rel, relation)| (rel, relation.build()))
+                    .collect();
+                d.insert(key.to_string(), JSONValue::Dict(section));
+            }
+        }
+        JSONValue::Dict(d)
+    }
+}
+
+impl From<HalResource> for JSONValue {
+    fn from(r: HalResource) -> Self {
+        r.build()
+    }
+}
@@ -0,0 +1,146 @@
+use core::fmt;
+use std::collections::{HashMap, HashSet};
+
+use crate::{pointer, JSONValue};
+
+/// A place both sides changed differently. `None` means the value was
+/// absent on that side, e.g. deleted.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Conflict {
+    pointer: String,
+    base: Option<JSONValue>,
+    mine: Option<JSONValue>,
+    theirs: Option<JSONValue>,
+}
+
+impl Conflict {
+    /// JSON Pointer of the conflicting value.
+    pub fn pointer(&self) -> &str {
+        &self.pointer
+    }
+
+    pub fn base(&self) -> Option<&JSONValue> {
+        self.base.as_ref()
+    }
+
+    pub fn mine(&self) -> Option<&JSONValue> {
+        self.mine.as_ref()
+    }
+
+    pub fn theirs(&self) -> Option<&JSONValue> {
+        self.theirs.as_ref()
+    }
+}
+
+impl fmt::Display for Conflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let show = |v: &Option<JSONValue>| match v {
+            Some(v) => v.to_json_string(),
+            None => "(absent)".to_string(),
+        };
+        let at = if self.pointer.is_empty() {
+            "root"
+        } else {
+            &self.pointer
+        };
+        write!(
+            f,
+            "{}: base {}, mine {}, theirs {}",
+            at,
+            show(&self.base),
+            show(&self.mine),
+            show(&self.theirs)
+        )
+    }
+}
+
+/// The result of `merge3`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Merged {
+    /// The merged document, with `mine` at every conflict.
+    pub value: JSONValue,
+    pub conflicts: Vec<Conflict>,
+}
+
+impl Merged {
+    pub fn is_clean(&self) -> bool {
+        self.conflicts.is_empty()
+    }
+}
+
+/// Merges two edited copies of `base` structurally: a change made on only
+/// one side is kept, objects are merged key by key, and arrays of unchanged
+/// length element by element. Where both sides changed the same value
+/// differently, a `Conflict` records all three versions.
+pub fn merge3(base: &JSONValue, mine: &JSONValue, theirs: &JSONValue) -> Merged {
+    let mut conflicts = Vec::new();
+    let value = merge(
+        Some(base),
+        Some(mine),
+        Some(theirs),
+        &mut String::new(),
+        &mut conflicts,
+    )
+    .expect("present on every side, so present when merged");
+    Merged { value, conflicts }
+}
+
+fn merge(
+    base: Option<&JSONValue>,
+    mine: Option<&JSONValue>,
+    theirs: Option<&JSONValue>,
+    ptr: &mut String,
+    conflicts: &mut Vec<Conflict>,
+) -> Option<JSONValue> {
+    if mine == theirs || theirs == base {
+        return mine.cloned();
+    }
+    if mine == base {
+        return theirs.cloned();
+    }
+    let len = ptr.len();
+    match (base, mine, theirs) {
+        (Some(JSONValue::Dict(b)), Some(JSONValue::Dict(m)), Some(JSONValue::Dict(t))) => {
+            let mut keys: Vec<_> = b
+                .keys()
+                .chain(m.keys())
+                .chain(t.keys())
+                .collect::<HashSet<_>>()
+                .into_iter()
+                .collect();
+            // so conflicts come out in a stable order
+            keys.sort_unstable();
+            let mut merged = HashMap::new();
+            for k in keys {
+                pointer::push(ptr, k);
+                let v = merge(b.get(k), m.get(k), t.get(k), ptr, conflicts);
+                ptr.truncate(len);
+                if let Some(v) = v {
+                    merged.insert(k.clone(), v);
+                }
+            }
+            Some(JSONValue::Dict(merged))
+        }
+        (Some(JSONValue::Array(b)), Some(JSONValue::Array(m)), Some(JSONValue::Array(t)))
+            if b.len() == m.len() && b.len() == t.len() =>
+        {
+            let mut merged = Vec::with_capacity(b.len());
+            for (i, ((b, m), t)) in b.iter().zip(m).zip(t).enumerate() {
+                pointer::push(ptr, &i.to_string());
+                let v = merge(Some(b), Some(m), Some(t), ptr, conflicts);
+                ptr.truncate(len);
+                merged.push(v.expect("present on every side, so present when merged"));
+            }
+            Some(JSONValue::Array(merged))
+        }
+        _ => {
+            conflicts.push(Conflict {
+                pointer: ptr.clone(),
+                base: base.cloned(),
+                mine: mine.cloned(),
+                theirs: theirs.cloned(),
+            });
+            mine.cloned()
+        }
+    }
+}
@@ -0,0 +1,46 @@
+use std::time::{Duration, Instant};
+
+use crate::{JSONValue, ParseOptions, RecursiveParser, ValuePool};
+
+/// What one call to `parse_with_metrics` did.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ParseStats {
+    /// Length of the input.
+    pub bytes: usize,
+    /// Tokens the lexer produced, or 0 if lexing failed.
+    pub tokens: usize,
+    /// Time spent lexing and parsing.
+    pub elapsed: Duration,
+}
+
+/// Receives a `ParseStats` after every parse, e.g. to feed Prometheus or
+/// StatsD counters and histograms. Methods take `&self` so one recorder can
+/// be shared between threads; both do nothing by default.
+pub trait ParseMetrics {
+    fn parsed(&self, _stats: &ParseStats) {}
+
+    fn failed(&self, _stats: &ParseStats, _error: &eyre::Report) {}
+}
+
+/// Like `parse_with_options`, reporting to `metrics` whether it succeeds or
+/// fails.
+pub fn parse_with_metrics(
+    json: &[u8],
+    options: &ParseOptions,
+    metrics: &dyn ParseMetrics,
+) -> eyre::Result<JSONValue> {
+    let start = Instant::now();
+    let mut pool = ValuePool::new();
+    let mut parser = RecursiveParser::new(json, &mut pool, None, options);
+    let res = parser.parse();
+    let stats = ParseStats {
+        bytes: json.len(),
+        tokens: parser.tokens,
+        elapsed: start.elapsed(),
+    };
+    match &res {
+        Ok(_) => metrics.parsed(&stats),
+        Err(e) => metrics.failed(&stats, e),
+    }
+    res
+}
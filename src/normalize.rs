@@ -0,0 +1,76 @@
+use crate::{JSONValue, ParseOptions};
+
+/// Applies the normalization and case folding `options` ask for to a decoded
+/// string or key.
+pub(crate) fn apply(s: String, options: &ParseOptions, is_key: bool) -> String {
+    let mut s = s;
+    #[cfg(feature = "unicode-normalization")]
+    {
+        let form = if is_key {
+            options.normalize_keys
+        } else {
+            options.normalize_strings
+        };
+        if let Some(form) = form {
+            s = normalize(s, form);
+        }
+    }
+    if is_key && options.fold_key_case {
+        s = fold_case(s);
+    }
+    s
+}
+
+/// Lowercases `s`, leaving it untouched (and unallocated) if it has no
+/// uppercase characters.
+pub(crate) fn fold_case(s: String) -> String {
+    if s.chars().any(char::is_uppercase) {
+        s.to_lowercase()
+    } else {
+        s
+    }
+}
+
+impl JSONValue {
+    /// Looks up `key` in an object, ignoring case.
+    pub fn get_ignore_case(&self, key: &str) -> Option<&JSONValue> {
+        let JSONValue::Dict(d) = self else {
+            return None;
+        };
+        d.get(key).or_else(|| {
+            let key = key.to_lowercase();
+            d.iter()
+                .find(|(k, _)| k.to_lowercase() == key)
+                .map(|(_, v)| v)
+        })
+    }
+}
+
+#[cfg(feature = "unicode-normalization")]
+mod forms {
+    use unicode_normalization::{is_nfc, is_nfd, is_nfkc, is_nfkd, UnicodeNormalization};
+
+    /// Unicode normalization form applied to strings while parsing.
+    #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+    pub enum UnicodeForm {
+        Nfc,
+        Nfd,
+        Nfkc,
+        Nfkd,
+    }
+
+    pub(crate) fn normalize(s: String, form: UnicodeForm) -> String {
+        match form {
+            UnicodeForm::Nfc if !is_nfc(&s) => s.nfc().collect(),
+            UnicodeForm::Nfd if !is_nfd(&s) => s.nfd().collect(),
+            UnicodeForm::Nfkc if !is_nfkc(&s) => s.nfkc().collect(),
+            UnicodeForm::Nfkd if !is_nfkd(&s) => s.nfkd().collect(),
+            _ => s,
+        }
+    }
+}
+
+#[cfg(feature = "unicode-normalization")]
+pub(crate) use forms::normalize;
+#[cfg(feature = "unicode-normalization")]
+pub use forms::UnicodeForm;
@@ -0,0 +1,168 @@
+use core::fmt;
+
+use crate::{FromJsonNumber, ToJsonNumber};
+
+/// What to do with a number the target number type can't hold exactly, e.g.
+/// an integer above 2^53 parsed into an `f64`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum BigNumbers {
+    /// Round it to the nearest value, as `f64` parsing always has.
+    #[default]
+    LoseToF64,
+    Error,
+    /// Keep the number as written, as a string value.
+    KeepAsString,
+    /// Keep integers as `Number::BigInt`; other numbers are an error. Needs
+    /// a number type that can hold them, i.e. `Value<_, Number>`.
+    #[cfg(feature = "bignum")]
+    KeepAsBigInt,
+}
+
+/// Converts a number according to `policy`. `Ok(None)` means it should be
+/// kept as a string.
+pub(crate) fn convert<N: FromJsonNumber>(
+    lexeme: &str,
+    value: f64,
+    policy: BigNumbers,
+) -> eyre::Result<Option<N>> {
+    if policy == BigNumbers::LoseToF64 || !N::loses_precision(lexeme, value) {
+        return N::from_json_number(lexeme, value).map(Some);
+    }
+    match policy {
+        BigNumbers::LoseToF64 | BigNumbers::Error => eyre::bail!(format!(
+            "Number {} can't be represented without losing precision",
+            lexeme
+        )),
+        BigNumbers::KeepAsString => Ok(None),
+        #[cfg(feature = "bignum")]
+        BigNumbers::KeepAsBigInt => N::from_big_integer(lexeme).map(Some),
+    }
+}
+
+// Significant digits (no leading or trailing zeros) and the decimal exponent
+// of the first one, so "120" and "1.2e2" both give ("12", 2).
+fn significant(number: &str) -> Option<(String, i64)> {
+    let number = number.trim_start_matches(['-', '+']);
+    let (mantissa, exp) = match number.find(['e', 'E']) {
+        Some(i) => (&number[..i], number[i + 1..].parse::<i64>().ok()?),
+        None => (number, 0),
+    };
+    let (int, frac) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    let digits: String = int.chars().chain(frac.chars()).collect();
+    let Some(first) = digits.find(|c| c != '0') else {
+        return Some((String::new(), 0));
+    };
+    let exp = exp.checked_add(int.len() as i64 - first as i64 - 1)?;
+    Some((digits[first..].trim_end_matches('0').to_string(), exp))
+}
+
+/// Whether `value` (the nearest `f64`) differs from the number written as
+/// `lexeme`.
+pub(crate) fn f64_loses_precision(lexeme: &str, value: f64) -> bool {
+    !value.is_finite() || significant(lexeme) != significant(&format!("{:e}", value))
+}
+
+/// A number backend for `Value` that, with the `i128` feature, keeps
+/// integers exact: integers in `i128` range parse to `I128`, larger positive
+/// ones to `U128`, and everything else to `Float`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Number {
+    Float(f64),
+    #[cfg(feature = "i128")]
+    I128(i128),
+    #[cfg(feature = "i128")]
+    U128(u128),
+    /// Only produced under `BigNumbers::KeepAsBigInt`.
+    #[cfg(feature = "bignum")]
+    BigInt(num_bigint::BigInt),
+}
+
+impl Number {
+    /// The nearest `f64`, which may lose precision for large integers.
+    pub fn as_f64(&self) -> f64 {
+        match *self {
+            Number::Float(n) => n,
+            #[cfg(feature = "i128")]
+            Number::I128(n) => n as f64,
+            #[cfg(feature = "i128")]
+            Number::U128(n) => n as f64,
+            #[cfg(feature = "bignum")]
+            Number::BigInt(ref n) => n.to_string().parse().unwrap_or(f64::NAN),
+        }
+    }
+}
+
+impl From<f64> for Number {
+    fn from(n: f64) -> Self {
+        Number::Float(n)
+    }
+}
+
+#[cfg(feature = "i128")]
+impl From<i128> for Number {
+    fn from(n: i128) -> Self {
+        Number::I128(n)
+    }
+}
+
+#[cfg(feature = "i128")]
+impl From<u128> for Number {
+    fn from(n: u128) -> Self {
+        Number::U128(n)
+    }
+}
+
+impl FromJsonNumber for Number {
+    // only `i128` looks at the lexeme
+    #[cfg_attr(not(feature = "i128"), allow(unused_variables))]
+    fn from_json_number(lexeme: &str, value: f64) -> eyre::Result<Self> {
+        #[cfg(feature = "i128")]
+        if !lexeme.contains(['.', 'e', 'E']) {
+            if let Ok(n) = lexeme.parse::<i128>() {
+                return Ok(Number::I128(n));
+            }
+            if let Ok(n) = lexeme.parse::<u128>() {
+                return Ok(Number::U128(n));
+            }
+        }
+        Ok(Number::Float(value))
+    }
+
+    fn loses_precision(lexeme: &str, value: f64) -> bool {
+        #[cfg(feature = "i128")]
+        if !lexeme.contains(['.', 'e', 'E'])
+            && (lexeme.parse::<i128>().is_ok() || lexeme.parse::<u128>().is_ok())
+        {
+            return false;
+        }
+        f64_loses_precision(lexeme, value)
+    }
+
+    #[cfg(feature = "bignum")]
+    fn from_big_integer(lexeme: &str) -> eyre::Result<Self> {
+        lexeme
+            .parse()
+            .map(Number::BigInt)
+            .map_err(|_| eyre::eyre!(format!("Number {} is not an integer", lexeme)))
+    }
+}
+
+impl ToJsonNumber for Number {
+    fn write_json_number<W: ?Sized + fmt::Write>(&self, out: &mut W) -> fmt::Result {
+        match self {
+            Number::Float(n) => n.write_json_number(out),
+            #[cfg(feature = "i128")]
+            Number::I128(n) => write!(out, "{}", n),
+            #[cfg(feature = "i128")]
+            Number::U128(n) => write!(out, "{}", n),
+            #[cfg(feature = "bignum")]
+            Number::BigInt(n) => write!(out, "{}", n),
+        }
+    }
+}
+
+impl fmt::Display for Number {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write_json_number(f)
+    }
+}
@@ -0,0 +1,152 @@
+use crate::patch::{self, PatchOp};
+use crate::{pointer, JSONValue, Patch};
+
+/// One edit to an `ObservableDocument`. `None` means there was no value
+/// there before, or is none after.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Change {
+    pub pointer: String,
+    pub old: Option<JSONValue>,
+    pub new: Option<JSONValue>,
+}
+
+/// Identifies a subscriber, for `ObservableDocument::unsubscribe`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct SubscriptionId(u64);
+
+type Subscriber<'o> = Box<dyn FnMut(&Change) + 'o>;
+
+/// A document that tells subscribers about every edit made through it, so
+/// UIs and replication layers can react to changes without diffing
+/// snapshots. Subscribers run after each edit succeeds, in the order they
+/// subscribed.
+pub struct ObservableDocument<'o> {
+    value: JSONValue,
+    subscribers: Vec<(SubscriptionId, Subscriber<'o>)>,
+    next_id: u64,
+}
+
+impl<'o> ObservableDocument<'o> {
+    pub fn new(value: JSONValue) -> Self {
+        Self {
+            value,
+            subscribers: Vec::new(),
+            next_id: 0,
+        }
+    }
+
+    pub fn value(&self) -> &JSONValue {
+        &self.value
+    }
+
+    pub fn into_value(self) -> JSONValue {
+        self.value
+    }
+
+    pub fn subscribe(&mut self, f: impl FnMut(&Change) + 'o) -> SubscriptionId {
+        let id = SubscriptionId(self.next_id);
+        self.next_id += 1;
+        self.subscribers.push((id, Box::new(f)));
+        id
+    }
+
+    /// Stops calling a subscriber, returning `false` if it was already gone.
+    pub fn unsubscribe(&mut self, id: SubscriptionId) -> bool {
+        let len = self.subscribers.len();
+        self.subscribers.retain(|(i, _)| *i != id);
+        self.subscribers.len() < len
+    }
+
+    /// Puts `value` at the JSON Pointer `ptr`, replacing what was there. In
+    /// an array, `-` appends.
+    pub fn set(&mut self, ptr: &str, value: JSONValue) -> eyre::Result<()> {
+        let op = if patch::get(&self.value, ptr).is_ok() {
+            PatchOp::Replace {
+                path: ptr.to_string(),
+                value,
+            }
+        } else {
+            PatchOp::Add {
+                path: ptr.to_string(),
+                value,
+            }
+        };
+        self.apply(&Patch::new(vec![op]))
+    }
+
+    /// Like `set`, but in an array inserts before the element at `ptr`.
+    pub fn insert(&mut self, ptr: &str, value: JSONValue) -> eyre::Result<()> {
+        let path = ptr.to_string();
+        self.apply(&Patch::new(vec![PatchOp::Add { path, value }]))
+    }
+
+    /// Removes and returns the value at `ptr`.
+    pub fn remove(&mut self, ptr: &str) -> eyre::Result<JSONValue> {
+        let removed = patch::remove(&mut self.value, ptr)?;
+        self.notify(&[Change {
+            pointer: ptr.to_string(),
+            old: Some(removed.clone()),
+            new: None,
+        }]);
+        Ok(removed)
+    }
+
+    /// Applies `patch` all or nothing, then reports each change it made.
+    pub fn apply(&mut self, patch: &Patch) -> eyre::Result<()> {
+        let mut doc = self.value.clone();
+        let mut changes = Vec::new();
+        for (i, op) in patch.ops().iter().enumerate() {
+            changes_of(&doc, op, &mut changes);
+            patch::apply_op(&mut doc, op)
+                .map_err(|e| eyre::eyre!(format!("Operation {}: {}", i, e)))?;
+        }
+        self.value = doc;
+        self.notify(&changes);
+        Ok(())
+    }
+
+    fn notify(&mut self, changes: &[Change]) {
+        for change in changes {
+            for (_, f) in &mut self.subscribers {
+                f(change);
+            }
+        }
+    }
+}
+
+// Appends the changes `op` makes to `doc`, assuming it succeeds.
+fn changes_of(doc: &JSONValue, op: &PatchOp, changes: &mut Vec<Change>) {
+    let old = |ptr: &str| patch::get(doc, ptr).ok().cloned();
+    let change = |pointer: &str, old, new: Option<&JSONValue>| Change {
+        pointer: pointer.to_string(),
+        old,
+        new: new.cloned(),
+    };
+    match op {
+        PatchOp::Add { path, value } => match resolve_insert(doc, path) {
+            // inserting into an array shifts rather than replaces
+            Some(index) => changes.push(change(&index, None, Some(value))),
+            None => changes.push(change(path, old(path), Some(value))),
+        },
+        PatchOp::Remove { path } => changes.push(change(path, old(path), None)),
+        PatchOp::Replace { path, value } => changes.push(change(path, old(path), Some(value))),
+        PatchOp::Move { from, path } => {
+            let moved = old(from);
+            changes.push(change(from, moved.clone(), None));
+            changes.push(change(path, old(path), moved.as_ref()));
+        }
+        PatchOp::Copy { from, path } => changes.push(change(path, old(path), old(from).as_ref())),
+        PatchOp::Test { .. } => {}
+    }
+}
+
+// The pointer to the element an add at `ptr` inserts, with `-` spelled out,
+// if `ptr` is in an array.
+fn resolve_insert(doc: &JSONValue, ptr: &str) -> Option<String> {
+    let (parent, last) = pointer::split(ptr).ok()?;
+    let JSONValue::Array(a) = patch::get(doc, &parent).ok()? else {
+        return None;
+    };
+    let i = pointer::index(&last?, a.len())?;
+    Some(pointer::child(&parent, &i.to_string()))
+}
@@ -0,0 +1,243 @@
+use std::collections::HashMap;
+
+use crate::patch::get;
+use crate::{parse, pointer, JSONValue};
+
+// The operations a path item can hold, in the order the spec lists them.
+const METHODS: &[&str] = &[
+    "get", "put", "post", "delete", "options", "head", "patch", "trace",
+];
+
+/// An OpenAPI 3.x or Swagger 2.0 document, with its internal `$ref`s
+/// followed on request.
+#[derive(Debug, Clone)]
+pub struct OpenApi {
+    doc: JSONValue,
+}
+
+/// One operation: a method under a path.
+#[derive(Debug, Clone, Copy)]
+pub struct Operation<'a> {
+    path: &'a str,
+    method: &'a str,
+    path_item: &'a JSONValue,
+    value: &'a JSONValue,
+}
+
+impl<'a> Operation<'a> {
+    /// e.g. `/pets/{id}`
+    pub fn path(&self) -> &'a str {
+        self.path
+    }
+
+    /// Lowercase, e.g. `get`.
+    pub fn method(&self) -> &'a str {
+        self.method
+    }
+
+    pub fn operation_id(&self) -> Option<&'a str> {
+        match self.value {
+            JSONValue::Dict(d) => match d.get("operationId")? {
+                JSONValue::Str(id) => Some(id),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// The operation object.
+    pub fn value(&self) -> &'a JSONValue {
+        self.value
+    }
+
+    /// The path item the operation is in, e.g. for parameters shared by
+    /// every method.
+    pub fn path_item(&self) -> &'a JSONValue {
+        self.path_item
+    }
+
+    /// JSON Pointer to the operation, e.g. `/paths/~1pets/get`.
+    pub fn pointer(&self) -> String {
+        let mut ptr = pointer::child("/paths", self.path);
+        pointer::push(&mut ptr, self.method);
+        ptr
+    }
+}
+
+// Percent-decodes a URI fragment.
+fn decode_fragment(fragment: &str) -> eyre::Result<String> {
+    let bytes = fragment.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = fragment
+                .get(i + 1..i + 3)
+                .and_then(|h| u8::from_str_radix(h, 16).ok())
+                .ok_or_else(|| eyre::eyre!(format!("Invalid escape in {:?}", fragment)))?;
+            out.push(hex);
+            i += 3;
+        } else {
+            out.push(bytes[i]);
+            i += 1;
+        }
+    }
+    Ok(String::from_utf8(out)?)
+}
+
+// The `$ref` of a reference object.
+fn reference(v: &JSONValue) -> Option<&str> {
+    match v {
+        JSONValue::Dict(d) => match d.get("$ref")? {
+            JSONValue::Str(r) => Some(r),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+impl OpenApi {
+    pub fn parse(json: &[u8]) -> eyre::Result<Self> {
+        Self::from_value(parse(json)?)
+    }
+
+    /// Fails unless `doc` is an object with an `openapi` or `swagger`
+    /// version string.
+    pub fn from_value(doc: JSONValue) -> eyre::Result<Self> {
+        let api = Self { doc };
+        if api.version().is_none() {
+            eyre::bail!("Expected an `openapi` or `swagger` version string")
+        }
+        Ok(api)
+    }
+
+    pub fn document(&self) -> &JSONValue {
+        &self.doc
+    }
+
+    /// e.g. `3.1.0`, or `2.0` for Swagger.
+    pub fn version(&self) -> Option<&str> {
+        let JSONValue::Dict(d) = &self.doc else {
+            return None;
+        };
+        match d.get("openapi").or_else(|| d.get("swagger"))? {
+            JSONValue::Str(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// What the internal reference `reference` (e.g.
+    /// `#/components/schemas/Pet`) points to, following further `$ref`s.
+    /// References to other files aren't supported.
+    pub fn lookup(&self, reference: &str) -> eyre::Result<&JSONValue> {
+        let target = self.target(reference)?;
+        self.resolve(target)
+    }
+
+    /// `v` itself, or if it is a reference object, what it points to.
+    pub fn resolve<'v>(&'v self, v: &'v JSONValue) -> eyre::Result<&'v JSONValue> {
+        let mut v = v;
+        let mut seen = Vec::new();
+        while let Some(r) = reference(v) {
+            if seen.contains(&r) {
+                eyre::bail!(format!("$ref {:?} refers to itself", r))
+            }
+            seen.push(r);
+            v = self.target(r)?;
+        }
+        Ok(v)
+    }
+
+    fn target(&self, reference: &str) -> eyre::Result<&JSONValue> {
+        let Some(fragment) = reference.strip_prefix('#') else {
+            eyre::bail!(format!(
+                "$ref {:?} is not internal to the document",
+                reference
+            ))
+        };
+        get(&self.doc, &decode_fragment(fragment)?)
+            .map_err(|_| eyre::eyre!(format!("$ref {:?} points to nothing", reference)))
+    }
+
+    /// Every operation, by path and then in the spec's method order. Path
+    /// items that are themselves references are followed.
+    pub fn operations(&self) -> eyre::Result<Vec<Operation<'_>>> {
+        let JSONValue::Dict(d) = &self.doc else {
+            return Ok(Vec::new());
+        };
+        let Some(JSONValue::Dict(paths)) = d.get("paths") else {
+            return Ok(Vec::new());
+        };
+        let mut paths: Vec<_> = paths.iter().collect();
+        paths.sort_unstable_by_key(|(path, _)| *path);
+        let mut operations = Vec::new();
+        for (path, item) in paths {
+            let path_item = self.resolve(item)?;
+            let JSONValue::Dict(methods) = path_item else {
+                continue;
+            };
+            for method in METHODS {
+                if let Some(value) = methods.get(*method) {
+                    operations.push(Operation {
+                        path,
+                        method,
+                        path_item,
+                        value,
+                    });
+                }
+            }
+        }
+        Ok(operations)
+    }
+
+    /// Named schemas, from `components/schemas` (or `definitions` in
+    /// Swagger 2.0), sorted by name.
+    pub fn schemas(&self) -> Vec<(&str, &JSONValue)> {
+        let schemas =
+            get(&self.doc, "/components/schemas").or_else(|_| get(&self.doc, "/definitions"));
+        let Ok(JSONValue::Dict(schemas)) = schemas else {
+            return Vec::new();
+        };
+        let mut schemas: Vec<_> = schemas.iter().map(|(k, v)| (k.as_str(), v)).collect();
+        schemas.sort_unstable_by_key(|(name, _)| *name);
+        schemas
+    }
+
+    /// A copy of `v` with every internal `$ref` replaced by what it points
+    /// to, all the way down, e.g. for generators that want a schema in one
+    /// piece. A reference back into a schema being expanded, as in a tree
+    /// type, is left as a `$ref`.
+    pub fn dereference(&self, v: &JSONValue) -> eyre::Result<JSONValue> {
+        self.expand(v, &mut Vec::new())
+    }
+
+    fn expand<'v>(
+        &'v self,
+        v: &'v JSONValue,
+        expanding: &mut Vec<&'v str>,
+    ) -> eyre::Result<JSONValue> {
+        if let Some(r) = reference(v) {
+            if expanding.contains(&r) {
+                return Ok(v.clone());
+            }
+            expanding.push(r);
+            let expanded = self.expand(self.target(r)?, expanding);
+            expanding.pop();
+            return expanded;
+        }
+        Ok(match v {
+            JSONValue::Array(entries) => JSONValue::Array(
+                entries
+                    .iter()
+                    .map(|e| self.expand(e, expanding))
+                    .collect::<eyre::Result<_>>()?,
+            ),
+            JSONValue::Dict(d) => JSONValue::Dict(
+                d.iter()
+                    .map(|(k, e)| Ok((k.clone(), self.expand(e, expanding)?)))
+                    .collect::<eyre::Result<HashMap<_, _>>>()?,
+            ),
+            v => v.clone(),
+        })
+    }
+}
@@ -0,0 +1,105 @@
+use crate::http::{DEFAULT_BODY_LIMIT, DEFAULT_DEPTH_LIMIT};
+use crate::{BigNumbers, LoneSurrogates, UnknownFields};
+
+/// Nesting limit in `ParseOptions::default()`, low enough that parsing can't
+/// overflow a 2 MiB thread stack.
+pub const DEFAULT_MAX_DEPTH: usize = 512;
+
+/// Knobs controlling what the parser accepts. The default is strict JSON,
+/// nested at most `DEFAULT_MAX_DEPTH` deep.
+#[derive(Debug, Clone)]
+pub struct ParseOptions {
+    /// Accept `// line` and `/* block */` comments wherever whitespace is
+    /// allowed.
+    pub allow_comments: bool,
+    /// Accept raw control characters inside strings, e.g. from scraped logs.
+    pub allow_control_chars: bool,
+    /// Accept `NaN`, `Infinity` and `-Infinity`, as JavaScript and Python
+    /// write them.
+    pub allow_non_finite: bool,
+    /// Skip a UTF-8 byte order mark at the start of the input, as Windows
+    /// tools often write one. Byte offsets still count it.
+    pub strip_bom: bool,
+    /// Reject inputs longer than this many bytes.
+    pub max_size: Option<usize>,
+    /// Reject documents with objects/arrays nested deeper than this. `None`
+    /// removes the limit, letting deeply nested input overflow the stack.
+    pub max_depth: Option<usize>,
+    /// Reject inputs of more than this many tokens (punctuation, keys and
+    /// values), which bounds the memory a small but dense input can take.
+    pub max_tokens: Option<usize>,
+    /// How to handle `\uD800`-style escapes outside a surrogate pair.
+    pub lone_surrogates: LoneSurrogates,
+    /// What to do with numbers that can't be parsed without rounding.
+    pub big_numbers: BigNumbers,
+    /// What typed decoding does with object keys the target has no field for.
+    pub unknown_fields: UnknownFields,
+    /// Lowercase object keys, so lookups can ignore case.
+    pub fold_key_case: bool,
+    /// Normalize object keys to this Unicode form (applied before
+    /// `fold_key_case`).
+    #[cfg(feature = "unicode-normalization")]
+    pub normalize_keys: Option<crate::UnicodeForm>,
+    /// Normalize string values to this Unicode form.
+    #[cfg(feature = "unicode-normalization")]
+    pub normalize_strings: Option<crate::UnicodeForm>,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self {
+            allow_comments: false,
+            allow_control_chars: false,
+            allow_non_finite: false,
+            strip_bom: false,
+            max_size: None,
+            max_depth: Some(DEFAULT_MAX_DEPTH),
+            max_tokens: None,
+            lone_surrogates: LoneSurrogates::default(),
+            big_numbers: BigNumbers::default(),
+            unknown_fields: UnknownFields::default(),
+            fold_key_case: false,
+            #[cfg(feature = "unicode-normalization")]
+            normalize_keys: None,
+            #[cfg(feature = "unicode-normalization")]
+            normalize_strings: None,
+        }
+    }
+}
+
+/// A named set of limits for `ParseOptions::profile`, so input from
+/// untrusted sources is bounded without picking each limit by hand.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Profile {
+    /// Request bodies: 2 MiB, nested 128 deep, 250,000 tokens, as
+    /// `http::body_options` plus a token limit.
+    WebApi,
+    /// Files from a job you run: 1 GiB, nested `DEFAULT_MAX_DEPTH` deep, any
+    /// number of tokens.
+    Batch,
+    /// No limits at all, for trusted input only: deep nesting can overflow
+    /// the stack.
+    Unlimited,
+}
+
+impl ParseOptions {
+    /// The default options with the limits of `profile`; set other fields
+    /// with `..ParseOptions::profile(Profile::WebApi)`.
+    pub fn profile(profile: Profile) -> Self {
+        let (max_size, max_depth, max_tokens) = match profile {
+            Profile::WebApi => (
+                Some(DEFAULT_BODY_LIMIT),
+                Some(DEFAULT_DEPTH_LIMIT),
+                Some(250_000),
+            ),
+            Profile::Batch => (Some(1 << 30), Some(DEFAULT_MAX_DEPTH), None),
+            Profile::Unlimited => (None, None, None),
+        };
+        Self {
+            max_size,
+            max_depth,
+            max_tokens,
+            ..Self::default()
+        }
+    }
+}
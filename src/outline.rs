@@ -0,0 +1,116 @@
+use std::ops::Range;
+
+use crate::lexer::{Lexer, Token};
+use crate::{escape, pointer, DEFAULT_MAX_DEPTH};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum RegionKind {
+    Object,
+    Array,
+}
+
+/// An object or array of the document, from its opening to its closing
+/// bracket.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Region {
+    pub kind: RegionKind,
+    /// Byte range including both brackets.
+    pub span: Range<usize>,
+    /// JSON Pointer of the container.
+    pub pointer: String,
+    /// 0 for the top-level container.
+    pub depth: usize,
+}
+
+struct Open {
+    region: usize,
+    // next array index, or the key whose value comes next
+    idx: usize,
+    key: Option<String>,
+}
+
+/// Lists the containers of `input` in document order, for folding ranges and
+/// tree views. Comments are accepted. Containers still open at the end of the
+/// input (e.g. while the user is typing) end there. Nesting deeper than
+/// `DEFAULT_MAX_DEPTH` is an error, as each region's pointer grows with its
+/// depth.
+pub fn outline(input: &[u8]) -> eyre::Result<Vec<Region>> {
+    let mut regions: Vec<Region> = Vec::new();
+    let mut stack: Vec<Open> = Vec::new();
+    for t in Lexer::new().allow_comments(true).tokens(input) {
+        let t = t?;
+        match t.token {
+            Token::LeftBrace | Token::LeftBracket => {
+                if stack.len() == DEFAULT_MAX_DEPTH {
+                    eyre::bail!(format!(
+                        "Nesting exceeds maximum depth of {}",
+                        DEFAULT_MAX_DEPTH
+                    ))
+                }
+                let ptr = match stack.last_mut() {
+                    None => String::new(),
+                    Some(open) => {
+                        let parent = &regions[open.region];
+                        match parent.kind {
+                            RegionKind::Array => {
+                                pointer::child(&parent.pointer, &open.idx.to_string())
+                            }
+                            RegionKind::Object => pointer::child(
+                                &parent.pointer,
+                                open.key.as_deref().unwrap_or_default(),
+                            ),
+                        }
+                    }
+                };
+                let kind = if t.token == Token::LeftBrace {
+                    RegionKind::Object
+                } else {
+                    RegionKind::Array
+                };
+                stack.push(Open {
+                    region: regions.len(),
+                    idx: 0,
+                    key: None,
+                });
+                regions.push(Region {
+                    kind,
+                    span: t.span.start..input.len(),
+                    pointer: ptr,
+                    depth: stack.len() - 1,
+                });
+            }
+            Token::RightBrace | Token::RightBracket => {
+                let open = stack.pop().ok_or_else(|| {
+                    eyre::eyre!("Unmatched closing bracket at byte {}", t.span.start)
+                })?;
+                let region = &mut regions[open.region];
+                let expected = match region.kind {
+                    RegionKind::Object => Token::RightBrace,
+                    RegionKind::Array => Token::RightBracket,
+                };
+                if t.token != expected {
+                    eyre::bail!(format!(
+                        "Mismatched closing bracket at byte {}",
+                        t.span.start
+                    ))
+                }
+                region.span.end = t.span.end;
+            }
+            Token::Comma => {
+                if let Some(open) = stack.last_mut() {
+                    open.idx += 1;
+                    open.key = None;
+                }
+            }
+            Token::StringVal(i, j) => {
+                if let Some(open) = stack.last_mut() {
+                    if regions[open.region].kind == RegionKind::Object && open.key.is_none() {
+                        open.key = Some(escape::lossy(&input[i..j]));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(regions)
+}
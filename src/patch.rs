@@ -0,0 +1,383 @@
+use std::collections::HashMap;
+
+use eyre::OptionExt;
+
+use crate::{parse, pointer, JSONValue};
+
+/// One operation of an RFC 6902 JSON Patch. Paths are JSON Pointers.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PatchOp {
+    /// Inserts into an array (`-` appends) or sets an object member.
+    Add {
+        path: String,
+        value: JSONValue,
+    },
+    Remove {
+        path: String,
+    },
+    /// Like `Add`, but the target must already exist.
+    Replace {
+        path: String,
+        value: JSONValue,
+    },
+    Move {
+        from: String,
+        path: String,
+    },
+    Copy {
+        from: String,
+        path: String,
+    },
+    /// Fails the patch unless the target equals `value`.
+    Test {
+        path: String,
+        value: JSONValue,
+    },
+}
+
+impl PatchOp {
+    pub fn path(&self) -> &str {
+        match self {
+            PatchOp::Add { path, .. }
+            | PatchOp::Remove { path }
+            | PatchOp::Replace { path, .. }
+            | PatchOp::Move { path, .. }
+            | PatchOp::Copy { path, .. }
+            | PatchOp::Test { path, .. } => path,
+        }
+    }
+
+    /// The operation as a patch document element, e.g.
+    /// `{"op": "remove", "path": "/a"}`.
+    pub fn to_json(&self) -> JSONValue {
+        let (op, from, value) = match self {
+            PatchOp::Add { value, .. } => ("add", None, Some(value)),
+            PatchOp::Remove { .. } => ("remove", None, None),
+            PatchOp::Replace { value, .. } => ("replace", None, Some(value)),
+            PatchOp::Move { from, .. } => ("move", Some(from), None),
+            PatchOp::Copy { from, .. } => ("copy", Some(from), None),
+            PatchOp::Test { value, .. } => ("test", None, Some(value)),
+        };
+        let mut d = HashMap::new();
+        d.insert("op".to_string(), JSONValue::Str(op.to_string()));
+        d.insert("path".to_string(), JSONValue::Str(self.path().to_string()));
+        if let Some(from) = from {
+            d.insert("from".to_string(), JSONValue::Str(from.clone()));
+        }
+        if let Some(value) = value {
+            d.insert("value".to_string(), value.clone());
+        }
+        JSONValue::Dict(d)
+    }
+
+    pub fn from_json(op: &JSONValue) -> eyre::Result<Self> {
+        let JSONValue::Dict(d) = op else {
+            eyre::bail!("Patch operation must be an object")
+        };
+        let string = |key: &str| match d.get(key) {
+            Some(JSONValue::Str(s)) => Ok(s.clone()),
+            Some(_) => eyre::bail!(format!("Patch operation's `{}` must be a string", key)),
+            None => eyre::bail!(format!("Patch operation is missing `{}`", key)),
+        };
+        let value = || {
+            d.get("value")
+                .cloned()
+                .ok_or_eyre("Patch operation is missing `value`")
+        };
+        let path = string("path")?;
+        let op = match string("op")?.as_str() {
+            "add" => PatchOp::Add {
+                path,
+                value: value()?,
+            },
+            "remove" => PatchOp::Remove { path },
+            "replace" => PatchOp::Replace {
+                path,
+                value: value()?,
+            },
+            "move" => PatchOp::Move {
+                from: string("from")?,
+                path,
+            },
+            "copy" => PatchOp::Copy {
+                from: string("from")?,
+                path,
+            },
+            "test" => PatchOp::Test {
+                path,
+                value: value()?,
+            },
+            other => eyre::bail!(format!("Unknown patch operation {:?}", other)),
+        };
+        Ok(op)
+    }
+}
+
+/// An RFC 6902 JSON Patch: operations applied in order, all or nothing.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Patch {
+    ops: Vec<PatchOp>,
+}
+
+impl From<Vec<PatchOp>> for Patch {
+    fn from(ops: Vec<PatchOp>) -> Self {
+        Self { ops }
+    }
+}
+
+impl Patch {
+    pub fn new(ops: Vec<PatchOp>) -> Self {
+        Self { ops }
+    }
+
+    pub fn ops(&self) -> &[PatchOp] {
+        &self.ops
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ops.is_empty()
+    }
+
+    /// Reads a patch document, an array of operation objects.
+    pub fn parse(json: &[u8]) -> eyre::Result<Self> {
+        Self::from_json(&parse(json)?)
+    }
+
+    pub fn from_json(patch: &JSONValue) -> eyre::Result<Self> {
+        let JSONValue::Array(ops) = patch else {
+            eyre::bail!("Patch must be an array")
+        };
+        let ops = ops
+            .iter()
+            .map(PatchOp::from_json)
+            .collect::<Result<_, _>>()?;
+        Ok(Self { ops })
+    }
+
+    pub fn to_json(&self) -> JSONValue {
+        JSONValue::Array(self.ops.iter().map(PatchOp::to_json).collect())
+    }
+
+    /// Applies every operation to `doc`. If one fails, `doc` is left as it
+    /// was and the error says which.
+    pub fn apply(&self, doc: &mut JSONValue) -> eyre::Result<()> {
+        let mut patched = doc.clone();
+        for (i, op) in self.ops.iter().enumerate() {
+            apply_op(&mut patched, op)
+                .map_err(|e| eyre::eyre!(format!("Operation {}: {}", i, e)))?;
+        }
+        *doc = patched;
+        Ok(())
+    }
+
+    /// The patch that undoes this one once it has been applied to `base`,
+    /// e.g. to roll back a change. Fails if this patch doesn't apply to
+    /// `base`.
+    pub fn invert(&self, base: &JSONValue) -> eyre::Result<Patch> {
+        let mut doc = base.clone();
+        let mut undo = Vec::new();
+        for (i, op) in self.ops.iter().enumerate() {
+            let res = inverse(&doc, op).and_then(|inverse| {
+                apply_op(&mut doc, op)?;
+                Ok(inverse)
+            });
+            let inverse = res.map_err(|e| eyre::eyre!(format!("Operation {}: {}", i, e)))?;
+            undo.extend(inverse.into_iter().rev());
+        }
+        undo.reverse();
+        Ok(Patch::new(undo))
+    }
+
+    /// One patch doing what this one and then `next` do, with consecutive
+    /// operations on the same path squashed where that can't change the
+    /// result, e.g. two replaces into one.
+    pub fn compose(&self, next: &Patch) -> Patch {
+        let mut ops: Vec<PatchOp> = Vec::with_capacity(self.ops.len() + next.ops.len());
+        for op in self.ops.iter().chain(&next.ops) {
+            let squashed = match (ops.last(), op) {
+                (
+                    Some(PatchOp::Replace { path: p, .. }),
+                    PatchOp::Replace { path, .. } | PatchOp::Remove { path },
+                ) if p == path => Some(op.clone()),
+                // after an append, `-` names the next slot rather than the
+                // element just added, so the replace isn't of that element
+                (Some(PatchOp::Add { path: p, .. }), PatchOp::Replace { path, value })
+                    if p == path && !path.ends_with("/-") =>
+                {
+                    Some(PatchOp::Add {
+                        path: path.clone(),
+                        value: value.clone(),
+                    })
+                }
+                _ => None,
+            };
+            match squashed {
+                Some(squashed) => *ops.last_mut().expect("matched a last op") = squashed,
+                None => ops.push(op.clone()),
+            }
+        }
+        Patch::new(ops)
+    }
+}
+
+// Operations that undo `op`, in the order to apply them, given the document
+// `op` is about to be applied to.
+fn inverse(doc: &JSONValue, op: &PatchOp) -> eyre::Result<Vec<PatchOp>> {
+    if pointer::tokens(op.path())?.is_empty() {
+        // anything done to the root is undone by putting it back
+        return Ok(vec![PatchOp::Replace {
+            path: String::new(),
+            value: doc.clone(),
+        }]);
+    }
+    let ops = match op {
+        PatchOp::Add { path, .. } | PatchOp::Copy { path, .. } => vec![undo_add(doc, path)?],
+        PatchOp::Remove { path } => vec![PatchOp::Add {
+            path: path.clone(),
+            value: get(doc, path)?.clone(),
+        }],
+        PatchOp::Replace { path, .. } => vec![PatchOp::Replace {
+            path: path.clone(),
+            value: get(doc, path)?.clone(),
+        }],
+        PatchOp::Move { from, path } => {
+            let mut moved = doc.clone();
+            remove(&mut moved, from)?;
+            // move it back rather than remove it
+            match undo_add(&moved, path)? {
+                PatchOp::Replace { path, value } => vec![
+                    PatchOp::Move {
+                        from: path.clone(),
+                        path: from.clone(),
+                    },
+                    PatchOp::Add { path, value },
+                ],
+                undo => vec![PatchOp::Move {
+                    from: undo.path().to_string(),
+                    path: from.clone(),
+                }],
+            }
+        }
+        PatchOp::Test { .. } => vec![op.clone()],
+    };
+    Ok(ops)
+}
+
+// Undoes adding a value at `path` to `doc`: removes it from an array or a
+// new object member, or puts back the member it overwrote.
+fn undo_add(doc: &JSONValue, path: &str) -> eyre::Result<PatchOp> {
+    let (parent, last) = pointer::split(path)?;
+    let last = last.ok_or_eyre("The root has no parent")?;
+    let op = match get(doc, &parent)? {
+        JSONValue::Array(a) => {
+            let i = pointer::index(&last, a.len())
+                .filter(|i| *i <= a.len())
+                .ok_or_else(|| {
+                    eyre::eyre!(format!("Index {:?} out of range at {:?}", last, parent))
+                })?;
+            PatchOp::Remove {
+                path: pointer::child(&parent, &i.to_string()),
+            }
+        }
+        JSONValue::Dict(d) => match d.get(&last) {
+            Some(old) => PatchOp::Replace {
+                path: path.to_string(),
+                value: old.clone(),
+            },
+            None => PatchOp::Remove {
+                path: path.to_string(),
+            },
+        },
+        _ => eyre::bail!(format!("{:?} is not an array or object", parent)),
+    };
+    Ok(op)
+}
+
+pub(crate) fn apply_op(doc: &mut JSONValue, op: &PatchOp) -> eyre::Result<()> {
+    match op {
+        PatchOp::Add { path, value } => add(doc, path, value.clone()),
+        PatchOp::Remove { path } => remove(doc, path).map(|_| ()),
+        PatchOp::Replace { path, value } => {
+            *get_mut(doc, path)? = value.clone();
+            Ok(())
+        }
+        PatchOp::Move { from, path } => {
+            if path.starts_with(from.as_str()) && path[from.len()..].starts_with('/') {
+                eyre::bail!(format!("Can't move {:?} into itself", from))
+            }
+            let v = remove(doc, from)?;
+            add(doc, path, v)
+        }
+        PatchOp::Copy { from, path } => {
+            let v = get(doc, from)?.clone();
+            add(doc, path, v)
+        }
+        PatchOp::Test { path, value } => {
+            if get(doc, path)? != value {
+                eyre::bail!(format!("Value at {:?} is not the one expected", path))
+            }
+            Ok(())
+        }
+    }
+}
+
+pub(crate) fn get<'v>(doc: &'v JSONValue, ptr: &str) -> eyre::Result<&'v JSONValue> {
+    let mut v = doc;
+    for token in pointer::tokens(ptr)? {
+        let child = match v {
+            JSONValue::Dict(d) => d.get(&token),
+            JSONValue::Array(a) => pointer::index(&token, a.len()).and_then(|i| a.get(i)),
+            _ => None,
+        };
+        v = child.ok_or_else(|| eyre::eyre!(format!("No value at {:?}", ptr)))?;
+    }
+    Ok(v)
+}
+
+pub(crate) fn get_mut<'v>(doc: &'v mut JSONValue, ptr: &str) -> eyre::Result<&'v mut JSONValue> {
+    let mut v = doc;
+    for token in pointer::tokens(ptr)? {
+        let child = match v {
+            JSONValue::Dict(d) => d.get_mut(&token),
+            JSONValue::Array(a) => match pointer::index(&token, a.len()) {
+                Some(i) => a.get_mut(i),
+                None => None,
+            },
+            _ => None,
+        };
+        v = child.ok_or_else(|| eyre::eyre!(format!("No value at {:?}", ptr)))?;
+    }
+    Ok(v)
+}
+
+pub(crate) fn add(doc: &mut JSONValue, ptr: &str, value: JSONValue) -> eyre::Result<()> {
+    let (parent, last) = pointer::split(ptr)?;
+    let Some(last) = last else {
+        *doc = value;
+        return Ok(());
+    };
+    match get_mut(doc, &parent)? {
+        JSONValue::Dict(d) => {
+            d.insert(last, value);
+        }
+        JSONValue::Array(a) => match pointer::index(&last, a.len()) {
+            Some(i) if i <= a.len() => a.insert(i, value),
+            _ => eyre::bail!(format!("Index {:?} out of range at {:?}", last, parent)),
+        },
+        _ => eyre::bail!(format!("{:?} is not an array or object", parent)),
+    }
+    Ok(())
+}
+
+pub(crate) fn remove(doc: &mut JSONValue, ptr: &str) -> eyre::Result<JSONValue> {
+    let (parent, last) = pointer::split(ptr)?;
+    let last = last.ok_or_eyre("Can't remove the whole document")?;
+    let removed = match get_mut(doc, &parent)? {
+        JSONValue::Dict(d) => d.remove(&last),
+        JSONValue::Array(a) => pointer::index(&last, a.len())
+            .filter(|i| *i < a.len())
+            .map(|i| a.remove(i)),
+        _ => None,
+    };
+    removed.ok_or_else(|| eyre::eyre!(format!("No value at {:?}", ptr)))
+}
@@ -0,0 +1,175 @@
+use crate::{pointer, JSONValue};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Key(String),
+    // `*`: any one key or index
+    Any,
+    // `**`: any number of levels, including none
+    Recursive,
+}
+
+/// A JSON Pointer where a segment may be `*` (any key or index) or `**` (any
+/// number of levels), e.g. `/users/*/email` or `/**/password`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PathPattern {
+    segments: Vec<Segment>,
+}
+
+impl PathPattern {
+    pub fn new(pattern: &str) -> eyre::Result<Self> {
+        if pattern.is_empty() {
+            return Ok(Self {
+                segments: Vec::new(),
+            });
+        }
+        let Some(rest) = pattern.strip_prefix('/') else {
+            eyre::bail!(format!("Pattern must start with '/': {:?}", pattern))
+        };
+        let segments = rest
+            .split('/')
+            .map(|s| match s {
+                "*" => Segment::Any,
+                "**" => Segment::Recursive,
+                s => Segment::Key(s.replace("~1", "/").replace("~0", "~")),
+            })
+            .collect();
+        Ok(Self { segments })
+    }
+
+    // Adds `state` and the states reachable from it without consuming a
+    // level (skipping `**`).
+    fn add(&self, states: &mut Vec<usize>, state: usize) {
+        if states.contains(&state) {
+            return;
+        }
+        states.push(state);
+        if self.segments.get(state) == Some(&Segment::Recursive) {
+            self.add(states, state + 1);
+        }
+    }
+
+    fn start(&self) -> Vec<usize> {
+        let mut states = Vec::new();
+        self.add(&mut states, 0);
+        states
+    }
+
+    fn step(&self, states: &[usize], key: &str) -> Vec<usize> {
+        let mut next = Vec::new();
+        for &s in states {
+            match self.segments.get(s) {
+                Some(Segment::Key(k)) if k == key => self.add(&mut next, s + 1),
+                Some(Segment::Key(_)) | None => {}
+                Some(Segment::Any) => self.add(&mut next, s + 1),
+                Some(Segment::Recursive) => self.add(&mut next, s),
+            }
+        }
+        next
+    }
+
+    fn is_match(&self, states: &[usize]) -> bool {
+        states.contains(&self.segments.len())
+    }
+
+    /// Whether the JSON Pointer `ptr` matches.
+    pub fn matches(&self, ptr: &str) -> bool {
+        let mut states = self.start();
+        if let Some(rest) = ptr.strip_prefix('/') {
+            for token in rest.split('/') {
+                let token = token.replace("~1", "/").replace("~0", "~");
+                states = self.step(&states, &token);
+            }
+        } else if !ptr.is_empty() {
+            return false;
+        }
+        self.is_match(&states)
+    }
+
+    /// The values in `value` that match, with their JSON Pointers. Parents
+    /// come before what's inside them, and object keys in sorted order.
+    pub fn find<'v>(&self, value: &'v JSONValue) -> Vec<(String, &'v JSONValue)> {
+        let mut found = Vec::new();
+        self.find_in(value, &self.start(), &mut String::new(), &mut found);
+        found
+    }
+
+    fn find_in<'v>(
+        &self,
+        v: &'v JSONValue,
+        states: &[usize],
+        ptr: &mut String,
+        found: &mut Vec<(String, &'v JSONValue)>,
+    ) {
+        if self.is_match(states) {
+            found.push((ptr.clone(), v));
+        }
+        let children: Vec<(String, &JSONValue)> = match v {
+            JSONValue::Array(entries) => entries
+                .iter()
+                .enumerate()
+                .map(|(i, e)| (i.to_string(), e))
+                .collect(),
+            JSONValue::Dict(d) => {
+                let mut entries: Vec<_> = d.iter().map(|(k, e)| (k.clone(), e)).collect();
+                entries.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+                entries
+            }
+            _ => return,
+        };
+        for (key, e) in children {
+            let next = self.step(states, &key);
+            if !next.is_empty() {
+                let len = ptr.len();
+                pointer::push(ptr, &key);
+                self.find_in(e, &next, ptr, found);
+                ptr.truncate(len);
+            }
+        }
+    }
+}
+
+/// Calls `f` on every value in `value` matched by `pattern`, in one
+/// traversal, and returns how many there were. A match is transformed after
+/// any matches inside it.
+pub fn transform_at(
+    value: &mut JSONValue,
+    pattern: &PathPattern,
+    mut f: impl FnMut(&mut JSONValue),
+) -> usize {
+    let states = pattern.start();
+    transform(value, pattern, &states, &mut f)
+}
+
+fn transform(
+    v: &mut JSONValue,
+    pattern: &PathPattern,
+    states: &[usize],
+    f: &mut impl FnMut(&mut JSONValue),
+) -> usize {
+    let mut count = 0;
+    match v {
+        JSONValue::Array(entries) => {
+            for (i, e) in entries.iter_mut().enumerate() {
+                let next = pattern.step(states, &i.to_string());
+                if !next.is_empty() {
+                    count += transform(e, pattern, &next, f);
+                }
+            }
+        }
+        JSONValue::Dict(d) => {
+            for (k, e) in d.iter_mut() {
+                let next = pattern.step(states, k);
+                if !next.is_empty() {
+                    count += transform(e, pattern, &next, f);
+                }
+            }
+        }
+        _ => {}
+    }
+    if pattern.is_match(states) {
+        f(v);
+        count += 1;
+    }
+    count
+}
@@ -0,0 +1,163 @@
+use std::sync::Arc;
+
+use crate::{pointer, JSONValue};
+
+/// An immutable `JSONValue` built on `im`'s persistent vectors and maps:
+/// every edit returns a new version sharing most of its structure with the
+/// old one, in time and memory logarithmic in the size of what changed, so
+/// keeping every version around for undo/redo is cheap.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PersistentValue {
+    Null,
+    Bool(bool),
+    Num(f64),
+    Str(Arc<str>),
+    Array(im::Vector<PersistentValue>),
+    Dict(im::HashMap<String, PersistentValue>),
+}
+
+impl From<JSONValue> for PersistentValue {
+    fn from(v: JSONValue) -> Self {
+        match v {
+            JSONValue::Null => PersistentValue::Null,
+            JSONValue::Bool(b) => PersistentValue::Bool(b),
+            JSONValue::Num(n) => PersistentValue::Num(n),
+            JSONValue::Str(s) => PersistentValue::Str(s.into()),
+            JSONValue::Array(entries) => {
+                PersistentValue::Array(entries.into_iter().map(Self::from).collect())
+            }
+            JSONValue::Dict(d) => {
+                PersistentValue::Dict(d.into_iter().map(|(k, v)| (k, Self::from(v))).collect())
+            }
+        }
+    }
+}
+
+impl From<&PersistentValue> for JSONValue {
+    fn from(v: &PersistentValue) -> Self {
+        match v {
+            PersistentValue::Null => JSONValue::Null,
+            PersistentValue::Bool(b) => JSONValue::Bool(*b),
+            PersistentValue::Num(n) => JSONValue::Num(*n),
+            PersistentValue::Str(s) => JSONValue::Str(s.to_string()),
+            PersistentValue::Array(entries) => {
+                JSONValue::Array(entries.iter().map(JSONValue::from).collect())
+            }
+            PersistentValue::Dict(d) => JSONValue::Dict(
+                d.iter()
+                    .map(|(k, v)| (k.clone(), JSONValue::from(v)))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+impl PersistentValue {
+    /// A plain copy.
+    pub fn to_value(&self) -> JSONValue {
+        JSONValue::from(self)
+    }
+
+    /// The value at the JSON Pointer `ptr`, e.g. `/items/0`.
+    pub fn get(&self, ptr: &str) -> Option<&PersistentValue> {
+        let mut v = self;
+        for token in pointer::tokens(ptr).ok()? {
+            v = match v {
+                PersistentValue::Dict(d) => d.get(&token)?,
+                PersistentValue::Array(a) => a.get(pointer::index(&token, a.len())?)?,
+                _ => return None,
+            };
+        }
+        Some(v)
+    }
+
+    /// A new version with `value` at `ptr`, replacing what was there. The
+    /// parent must exist; in an array, `-` or the length appends.
+    pub fn with(&self, ptr: &str, value: impl Into<PersistentValue>) -> eyre::Result<Self> {
+        let tokens = pointer::tokens(ptr)?;
+        self.with_at(&tokens, 0, value.into())
+    }
+
+    fn with_at(&self, tokens: &[String], depth: usize, value: Self) -> eyre::Result<Self> {
+        let Some(token) = tokens.get(depth) else {
+            return Ok(value);
+        };
+        let last = depth + 1 == tokens.len();
+        let missing = || {
+            if last {
+                let parent = path(&tokens[..depth]);
+                eyre::eyre!(format!("Index {:?} out of range at {:?}", token, parent))
+            } else {
+                eyre::eyre!(format!("No value at {:?}", path(&tokens[..=depth])))
+            }
+        };
+        match self {
+            PersistentValue::Dict(d) => {
+                let child = match d.get(token) {
+                    _ if last => value,
+                    Some(child) => child.with_at(tokens, depth + 1, value)?,
+                    None => return Err(missing()),
+                };
+                Ok(PersistentValue::Dict(d.update(token.clone(), child)))
+            }
+            PersistentValue::Array(a) => {
+                let i = pointer::index(token, a.len()).ok_or_else(missing)?;
+                if last && i == a.len() {
+                    let mut a = a.clone();
+                    a.push_back(value);
+                    return Ok(PersistentValue::Array(a));
+                }
+                let child = match a.get(i) {
+                    Some(_) if last => value,
+                    Some(child) => child.with_at(tokens, depth + 1, value)?,
+                    None => return Err(missing()),
+                };
+                Ok(PersistentValue::Array(a.update(i, child)))
+            }
+            _ => eyre::bail!(format!(
+                "{:?} is not an array or object",
+                path(&tokens[..depth])
+            )),
+        }
+    }
+
+    /// A new version without the value at `ptr`, later array elements
+    /// shifted down, or `None` if there's nothing there.
+    pub fn without(&self, ptr: &str) -> Option<Self> {
+        let tokens = pointer::tokens(ptr).ok()?;
+        self.without_at(&tokens)
+    }
+
+    fn without_at(&self, tokens: &[String]) -> Option<Self> {
+        let (token, rest) = tokens.split_first()?;
+        match self {
+            PersistentValue::Dict(d) if rest.is_empty() => d
+                .contains_key(token)
+                .then(|| PersistentValue::Dict(d.without(token))),
+            PersistentValue::Dict(d) => {
+                let child = d.get(token)?.without_at(rest)?;
+                Some(PersistentValue::Dict(d.update(token.clone(), child)))
+            }
+            PersistentValue::Array(a) => {
+                let i = pointer::index(token, a.len()).filter(|i| *i < a.len())?;
+                let mut a = a.clone();
+                if rest.is_empty() {
+                    a.remove(i);
+                } else {
+                    let child = a[i].without_at(rest)?;
+                    a.set(i, child);
+                }
+                Some(PersistentValue::Array(a))
+            }
+            _ => None,
+        }
+    }
+}
+
+fn path(tokens: &[String]) -> String {
+    let mut p = String::new();
+    for t in tokens {
+        pointer::push(&mut p, t);
+    }
+    p
+}
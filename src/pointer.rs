@@ -0,0 +1,59 @@
+/// Appends `token` to the JSON Pointer `ptr`, escaping `~` and `/`.
+pub(crate) fn push(ptr: &mut String, token: &str) {
+    ptr.push('/');
+    for c in token.chars() {
+        match c {
+            '~' => ptr.push_str("~0"),
+            '/' => ptr.push_str("~1"),
+            c => ptr.push(c),
+        }
+    }
+}
+
+pub(crate) fn child(ptr: &str, token: &str) -> String {
+    let mut p = String::with_capacity(ptr.len() + token.len() + 1);
+    p.push_str(ptr);
+    push(&mut p, token);
+    p
+}
+
+/// The unescaped reference tokens of the JSON Pointer `ptr`.
+pub(crate) fn tokens(ptr: &str) -> eyre::Result<Vec<String>> {
+    if ptr.is_empty() {
+        return Ok(Vec::new());
+    }
+    let rest = match ptr.strip_prefix('/') {
+        Some(rest) => rest,
+        None => eyre::bail!(format!("JSON Pointer {:?} must start with /", ptr)),
+    };
+    Ok(rest
+        .split('/')
+        .map(|t| t.replace("~1", "/").replace("~0", "~"))
+        .collect())
+}
+
+/// Where an array reference token points: an index, or the end for `-`.
+pub(crate) fn index(token: &str, len: usize) -> Option<usize> {
+    if token == "-" {
+        return Some(len);
+    }
+    // no signs, no leading zeros
+    if token.is_empty() || (token.len() > 1 && token.starts_with('0')) {
+        return None;
+    }
+    if !token.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    token.parse().ok()
+}
+
+// The parent of `ptr` and its last reference token, or `None` for the root.
+pub(crate) fn split(ptr: &str) -> eyre::Result<(String, Option<String>)> {
+    let mut tokens = tokens(ptr)?;
+    let last = tokens.pop();
+    let mut parent = String::new();
+    for t in &tokens {
+        push(&mut parent, t);
+    }
+    Ok((parent, last))
+}
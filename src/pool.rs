@@ -0,0 +1,100 @@
+use std::collections::HashMap;
+
+use crate::JSONValue;
+
+const DEFAULT_MAX_BUFFERS: usize = 1024;
+
+/// Recycles the `Vec`/`HashMap`/`String` buffers of dropped `JSONValue` trees
+/// so that later parses can reuse them instead of hitting the allocator.
+pub struct ValuePool {
+    arrays: Vec<Vec<JSONValue>>,
+    dicts: Vec<HashMap<String, JSONValue>>,
+    strings: Vec<String>,
+    max_buffers: usize,
+}
+
+impl Default for ValuePool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ValuePool {
+    pub fn new() -> Self {
+        Self::with_max_buffers(DEFAULT_MAX_BUFFERS)
+    }
+
+    /// Creates a pool that keeps at most `max_buffers` buffers of each kind,
+    /// anything beyond that is dropped as usual.
+    pub fn with_max_buffers(max_buffers: usize) -> Self {
+        Self {
+            arrays: Vec::new(),
+            dicts: Vec::new(),
+            strings: Vec::new(),
+            max_buffers,
+        }
+    }
+
+    pub fn parse(&mut self, json: &[u8]) -> eyre::Result<JSONValue> {
+        crate::parse_with_pool(json, self)
+    }
+
+    /// Takes apart `value`, keeping its buffers for subsequent parses.
+    pub fn recycle(&mut self, value: JSONValue) {
+        match value {
+            JSONValue::Str(s) => self.put_string(s),
+            JSONValue::Array(mut entries) => {
+                for v in entries.drain(..) {
+                    self.recycle(v);
+                }
+                if self.arrays.len() < self.max_buffers {
+                    self.arrays.push(entries);
+                }
+            }
+            JSONValue::Dict(mut entries) => {
+                for (k, v) in entries.drain() {
+                    self.put_string(k);
+                    self.recycle(v);
+                }
+                if self.dicts.len() < self.max_buffers {
+                    self.dicts.push(entries);
+                }
+            }
+            JSONValue::Null | JSONValue::Bool(_) | JSONValue::Num(_) => {}
+        }
+    }
+
+    /// Number of buffers currently held, across all kinds.
+    pub fn len(&self) -> usize {
+        self.arrays.len() + self.dicts.len() + self.strings.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn clear(&mut self) {
+        self.arrays.clear();
+        self.dicts.clear();
+        self.strings.clear();
+    }
+
+    fn put_string(&mut self, mut s: String) {
+        if self.strings.len() < self.max_buffers {
+            s.clear();
+            self.strings.push(s);
+        }
+    }
+
+    pub(crate) fn take_vec(&mut self) -> Vec<JSONValue> {
+        self.arrays.pop().unwrap_or_default()
+    }
+
+    pub(crate) fn take_map(&mut self) -> HashMap<String, JSONValue> {
+        self.dicts.pop().unwrap_or_default()
+    }
+
+    pub(crate) fn take_string(&mut self) -> String {
+        self.strings.pop().unwrap_or_default()
+    }
+}
@@ -0,0 +1,300 @@
+use std::collections::HashMap;
+
+use eyre::OptionExt;
+
+use crate::incremental::partial_value;
+use crate::lexer::{Lexer, Token};
+use crate::syntax::{self, Container, Expect, SyntaxState};
+use crate::{escape, JSONValue, LoneSurrogates, ParseOptions};
+
+#[derive(Clone)]
+enum Open {
+    Array(Vec<JSONValue>),
+    Object(HashMap<String, JSONValue>, Option<String>),
+}
+
+/// Builds a value from tokens fed one at a time, validating them against
+/// the grammar as it goes.
+#[derive(Clone)]
+pub(crate) struct TreeBuilder {
+    syntax: SyntaxState,
+    stack: Vec<Open>,
+    max_depth: Option<usize>,
+}
+
+impl TreeBuilder {
+    /// A builder nesting at most `DEFAULT_MAX_DEPTH` deep.
+    pub(crate) fn new() -> Self {
+        Self::with_options(&ParseOptions::default())
+    }
+
+    /// A builder with the nesting limit of `options`.
+    pub(crate) fn with_options(options: &ParseOptions) -> Self {
+        Self {
+            syntax: SyntaxState::new(),
+            stack: Vec::new(),
+            max_depth: options.max_depth,
+        }
+    }
+
+    pub(crate) fn syntax(&self) -> &SyntaxState {
+        &self.syntax
+    }
+
+    /// Feeds the next token, returning the document once it is complete. On
+    /// error nothing is changed.
+    pub(crate) fn push(&mut self, t: Token, buf: &[u8]) -> eyre::Result<Option<JSONValue>> {
+        if matches!(t, Token::LeftBrace | Token::LeftBracket) {
+            self.check_depth(self.stack.len() + 1)?;
+        }
+        let expect = self.syntax.expect;
+        let string = match t {
+            Token::StringVal(i, j) => {
+                let raw = buf.get(i..j).ok_or_eyre("String range outside the input")?;
+                Some(escape::unescape_str(
+                    raw,
+                    LoneSurrogates::default(),
+                    String::new(),
+                )?)
+            }
+            _ => None,
+        };
+        self.syntax.feed(t)?;
+        let value = match t {
+            Token::Comment | Token::Colon | Token::Comma => return Ok(None),
+            Token::LeftBrace => {
+                self.stack.push(Open::Object(HashMap::new(), None));
+                return Ok(None);
+            }
+            Token::LeftBracket => {
+                self.stack.push(Open::Array(Vec::new()));
+                return Ok(None);
+            }
+            Token::RightBrace | Token::RightBracket => match self.stack.pop() {
+                Some(Open::Array(entries)) => JSONValue::Array(entries),
+                Some(Open::Object(entries, _)) => JSONValue::Dict(entries),
+                None => unreachable!("syntax state checks brackets"),
+            },
+            Token::StringVal(..) => {
+                let s = string.unwrap_or_default();
+                if matches!(expect, Expect::Key | Expect::KeyOrEndObject) {
+                    if let Some(Open::Object(_, key)) = self.stack.last_mut() {
+                        *key = Some(s);
+                    }
+                    return Ok(None);
+                }
+                JSONValue::Str(s)
+            }
+            Token::NumVal(n) => JSONValue::Num(n),
+            Token::BoolVal(b) => JSONValue::Bool(b),
+            Token::NullVal => JSONValue::Null,
+        };
+        Ok(self.attach(value))
+    }
+
+    fn check_depth(&self, depth: usize) -> eyre::Result<()> {
+        if let Some(max_depth) = self.max_depth {
+            if depth > max_depth {
+                eyre::bail!(format!("Nesting exceeds maximum depth of {}", max_depth))
+            }
+        }
+        Ok(())
+    }
+
+    fn attach(&mut self, value: JSONValue) -> Option<JSONValue> {
+        match self.stack.last_mut() {
+            None => return Some(value),
+            Some(Open::Array(entries)) => entries.push(value),
+            Some(Open::Object(entries, key)) => {
+                if let Some(key) = key.take() {
+                    entries.insert(key, value);
+                }
+            }
+        }
+        None
+    }
+
+    /// The builder's state, for `IncrementalParser::checkpoint`.
+    pub(crate) fn to_json(&self) -> JSONValue {
+        let stack = self
+            .stack
+            .iter()
+            .map(|open| {
+                let mut d = HashMap::new();
+                match open {
+                    Open::Array(entries) => {
+                        d.insert("array".to_string(), JSONValue::Array(entries.clone()));
+                    }
+                    Open::Object(entries, key) => {
+                        d.insert("object".to_string(), JSONValue::Dict(entries.clone()));
+                        if let Some(key) = key {
+                            d.insert("key".to_string(), JSONValue::Str(key.clone()));
+                        }
+                    }
+                }
+                JSONValue::Dict(d)
+            })
+            .collect();
+        let mut d = HashMap::new();
+        d.insert(
+            "expect".to_string(),
+            JSONValue::Str(self.syntax.expect.name()),
+        );
+        d.insert("stack".to_string(), JSONValue::Array(stack));
+        JSONValue::Dict(d)
+    }
+
+    pub(crate) fn from_json(state: &JSONValue, options: &ParseOptions) -> eyre::Result<Self> {
+        let invalid = || eyre::eyre!("Invalid parser state");
+        let JSONValue::Dict(d) = state else {
+            return Err(invalid());
+        };
+        let expect = match d.get("expect") {
+            Some(JSONValue::Str(name)) => Expect::from_name(name).ok_or_else(invalid)?,
+            _ => return Err(invalid()),
+        };
+        let Some(JSONValue::Array(opens)) = d.get("stack") else {
+            return Err(invalid());
+        };
+        let mut builder = Self::with_options(options);
+        builder.check_depth(opens.len())?;
+        for open in opens {
+            let JSONValue::Dict(open) = open else {
+                return Err(invalid());
+            };
+            let (open, container) = match (open.get("array"), open.get("object"), open.get("key")) {
+                (Some(JSONValue::Array(entries)), None, None) => {
+                    (Open::Array(entries.clone()), Container::Array)
+                }
+                (None, Some(JSONValue::Dict(entries)), key) => {
+                    let key = match key {
+                        Some(JSONValue::Str(key)) => Some(key.clone()),
+                        None => None,
+                        Some(_) => return Err(invalid()),
+                    };
+                    (Open::Object(entries.clone(), key), Container::Object)
+                }
+                _ => return Err(invalid()),
+            };
+            builder.stack.push(open);
+            builder.syntax.stack.push(container);
+        }
+        if !expect.fits(builder.syntax.stack.last().copied()) {
+            return Err(invalid());
+        }
+        builder.syntax.expect = expect;
+        Ok(builder)
+    }
+
+    /// Like `finish`, but leaves `self` untouched and first attaches `tail`
+    /// (a value still being typed) where the next value would go.
+    pub(crate) fn snapshot(&self, tail: Option<JSONValue>) -> Option<JSONValue> {
+        let mut b = self.clone();
+        if let Some(tail) = tail {
+            if let Some(root) = b.attach(tail) {
+                return Some(root);
+            }
+        }
+        b.finish()
+    }
+
+    /// Closes every open container, returning what has been built so far.
+    /// A key still waiting for its value is dropped.
+    pub(crate) fn finish(mut self) -> Option<JSONValue> {
+        let mut value = None;
+        while let Some(open) = self.stack.pop() {
+            value = Some(match open {
+                Open::Array(mut entries) => {
+                    entries.extend(value);
+                    JSONValue::Array(entries)
+                }
+                Open::Object(mut entries, key) => {
+                    if let (Some(key), Some(v)) = (key, value) {
+                        entries.insert(key, v);
+                    }
+                    JSONValue::Dict(entries)
+                }
+            });
+        }
+        value
+    }
+}
+
+/// Whether `parse_prefix` saw a whole document.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PrefixStatus {
+    /// A complete document ends at `consumed`.
+    Done,
+    /// The input ends before the document does.
+    NeedMore,
+}
+
+/// Result of `parse_prefix`.
+#[derive(Debug)]
+pub struct PrefixParse {
+    /// The document so far, with open containers closed and a string,
+    /// number or keyword cut off at the end taken as it is, e.g. `{"a":
+    /// [1, "hel` gives `{"a": [1, "hel"]}`. A key still waiting for its value
+    /// is left out. `None` until a value has begun.
+    pub value: Option<JSONValue>,
+    /// Bytes covered by complete tokens. When more input arrives, parsing can
+    /// be restarted from the beginning with the longer buffer.
+    pub consumed: usize,
+    pub status: PrefixStatus,
+}
+
+/// Parses as much of a possibly incomplete document as is available.
+///
+/// Tokens cut off by the end of the input (an unterminated string, a partial
+/// keyword, or a number that could still gain digits) are left unconsumed,
+/// but are part of `value` as far as they go.
+/// Anything that can no longer become valid JSON is an error. Parsing stops
+/// after the first complete document. Nesting is limited to
+/// `DEFAULT_MAX_DEPTH`.
+pub fn parse_prefix(input: &[u8]) -> eyre::Result<PrefixParse> {
+    parse_prefix_with_options(input, &ParseOptions::default())
+}
+
+/// Like `parse_prefix`, nesting at most `options.max_depth` deep. The other
+/// options don't apply.
+pub fn parse_prefix_with_options(
+    input: &[u8],
+    options: &ParseOptions,
+) -> eyre::Result<PrefixParse> {
+    let lexer = Lexer::new();
+    let mut builder = TreeBuilder::with_options(options);
+    let mut pos = 0;
+    let mut consumed = 0;
+    loop {
+        let t = match lexer.next_token(input, &mut pos) {
+            Ok(Some(t)) => t,
+            Ok(None) => break,
+            Err(e) => {
+                syntax::partial_token(&input[pos..]).ok_or(e)?;
+                break;
+            }
+        };
+        if matches!(t.token, Token::NumVal(_)) && t.span.end == input.len() {
+            // may still gain digits, but must be allowed here regardless
+            builder.syntax().clone().feed(t.token)?;
+            break;
+        }
+        if let Some(value) = builder.push(t.token, input)? {
+            return Ok(PrefixParse {
+                value: Some(value),
+                consumed: t.span.end,
+                status: PrefixStatus::Done,
+            });
+        }
+        consumed = t.span.end;
+    }
+    let tail = match builder.syntax().expect {
+        Expect::Value | Expect::ValueOrEndArray => partial_value(&input[consumed..]),
+        _ => None,
+    };
+    Ok(PrefixParse {
+        value: builder.snapshot(tail),
+        consumed,
+        status: PrefixStatus::NeedMore,
+    })
+}
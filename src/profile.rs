@@ -0,0 +1,240 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashMap};
+use std::hash::{Hash, Hasher};
+use std::io::BufRead;
+
+use crate::progress::Counting;
+use crate::schema::Type;
+use crate::{parse_auto, pointer, JSONValue, ProgressReport, SerializeOptions};
+
+/// What was seen at one key path across a dataset.
+#[derive(Debug, Clone, Default)]
+pub struct PathProfile {
+    /// Values seen at the path, `null`s included.
+    pub count: usize,
+    /// How many values had each type (`string`, `integer`, `null`, ..).
+    pub types: BTreeMap<&'static str, usize>,
+    /// Length of the shortest string (in chars), array or object seen.
+    pub min_length: Option<usize>,
+    /// Length of the longest.
+    pub max_length: Option<usize>,
+    distinct: Hll,
+}
+
+impl PathProfile {
+    /// The share of values that were `null`, from 0 to 1.
+    pub fn null_rate(&self) -> f64 {
+        match self.count {
+            0 => 0.0,
+            n => self.types.get("null").copied().unwrap_or(0) as f64 / n as f64,
+        }
+    }
+
+    /// An estimate of the number of distinct values, within a few percent.
+    pub fn distinct(&self) -> f64 {
+        self.distinct.estimate()
+    }
+
+    fn add(&mut self, value: &JSONValue) {
+        self.count += 1;
+        *self.types.entry(Type::of(value).name()).or_insert(0) += 1;
+        let len = match value {
+            JSONValue::Str(s) => Some(s.chars().count()),
+            JSONValue::Array(a) => Some(a.len()),
+            JSONValue::Dict(d) => Some(d.len()),
+            _ => None,
+        };
+        if let Some(len) = len {
+            self.min_length = Some(self.min_length.map_or(len, |m| m.min(len)));
+            self.max_length = Some(self.max_length.map_or(len, |m| m.max(len)));
+        }
+        self.distinct.add(canonical_hash(value));
+    }
+}
+
+/// Profiles the records of a dataset one at a time, keeping per key path
+/// the types seen, the null rate, an estimate of distinct values and the
+/// range of lengths. Memory depends on the number of paths, not records.
+/// Paths are JSON Pointers, with `*` for any array element.
+#[derive(Debug, Clone, Default)]
+pub struct Profiler {
+    records: usize,
+    paths: BTreeMap<String, PathProfile>,
+}
+
+impl Profiler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the values in `record` to the profile.
+    pub fn add(&mut self, record: &JSONValue) {
+        self.records += 1;
+        let mut path = String::new();
+        self.visit(record, &mut path);
+    }
+
+    fn visit(&mut self, value: &JSONValue, path: &mut String) {
+        match self.paths.get_mut(path.as_str()) {
+            Some(profile) => profile.add(value),
+            None => {
+                let mut profile = PathProfile::default();
+                profile.add(value);
+                self.paths.insert(path.clone(), profile);
+            }
+        }
+        let len = path.len();
+        match value {
+            JSONValue::Array(entries) => {
+                path.push_str("/*");
+                for entry in entries {
+                    self.visit(entry, path);
+                }
+            }
+            JSONValue::Dict(entries) => {
+                for (key, entry) in entries {
+                    pointer::push(path, key);
+                    self.visit(entry, path);
+                    path.truncate(len);
+                }
+            }
+            _ => {}
+        }
+        path.truncate(len);
+    }
+
+    /// The number of records added.
+    pub fn records(&self) -> usize {
+        self.records
+    }
+
+    /// The profile of each path, in order of path.
+    pub fn paths(&self) -> impl Iterator<Item = (&str, &PathProfile)> {
+        self.paths
+            .iter()
+            .map(|(path, profile)| (path.as_str(), profile))
+    }
+
+    /// The profile of `path`, if anything was seen there.
+    pub fn path(&self, path: &str) -> Option<&PathProfile> {
+        self.paths.get(path)
+    }
+
+    /// The profile as a document, e.g. to feed a dashboard:
+    /// `{"records": n, "paths": {path: {"count", "types", "null_rate",
+    /// "distinct", "min_length", "max_length"}}}`.
+    pub fn to_json(&self) -> JSONValue {
+        let num = |n: usize| JSONValue::Num(n as f64);
+        let paths = self
+            .paths
+            .iter()
+            .map(|(path, p)| {
+                let types = p
+                    .types
+                    .iter()
+                    .map(|(t, n)| (t.to_string(), num(*n)))
+                    .collect();
+                let mut fields = HashMap::from([
+                    ("count".to_string(), num(p.count)),
+                    ("types".to_string(), JSONValue::Dict(types)),
+                    ("null_rate".to_string(), JSONValue::Num(p.null_rate())),
+                    ("distinct".to_string(), JSONValue::Num(p.distinct().round())),
+                ]);
+                if let (Some(min), Some(max)) = (p.min_length, p.max_length) {
+                    fields.insert("min_length".to_string(), num(min));
+                    fields.insert("max_length".to_string(), num(max));
+                }
+                (path.clone(), JSONValue::Dict(fields))
+            })
+            .collect();
+        JSONValue::Dict(HashMap::from([
+            ("records".to_string(), num(self.records)),
+            ("paths".to_string(), JSONValue::Dict(paths)),
+        ]))
+    }
+}
+
+/// Profiles every record of `reader`, framed as `parse_auto` sees it.
+pub fn profile_records<R: BufRead>(reader: R) -> eyre::Result<Profiler> {
+    let mut profiler = Profiler::new();
+    for record in parse_auto(reader)? {
+        profiler.add(&record?);
+    }
+    Ok(profiler)
+}
+
+/// Like `profile_records`, reporting the bytes read and records profiled
+/// to `report`.
+pub fn profile_records_with_progress<R: BufRead>(
+    reader: R,
+    mut report: ProgressReport<'_>,
+) -> eyre::Result<Profiler> {
+    let (reader, read) = Counting::new(reader);
+    let mut profiler = Profiler::new();
+    for record in parse_auto(reader)? {
+        profiler.add(&record?);
+        report.advance(read.get(), 1);
+    }
+    report.finish();
+    Ok(profiler)
+}
+
+/// A hash that is the same for equal values, whatever order their keys are
+/// in.
+pub(crate) fn canonical_hash(value: &JSONValue) -> u64 {
+    let mut h = DefaultHasher::new();
+    match value {
+        JSONValue::Null => 0u8.hash(&mut h),
+        JSONValue::Bool(b) => b.hash(&mut h),
+        JSONValue::Num(n) => n.to_bits().hash(&mut h),
+        JSONValue::Str(s) => s.hash(&mut h),
+        v => v
+            .to_json_string_with(&SerializeOptions {
+                sort_keys: true,
+                ..SerializeOptions::default()
+            })
+            .hash(&mut h),
+    }
+    std::mem::discriminant(value).hash(&mut h);
+    h.finish()
+}
+
+// Bits of the hash picking a register: 2^12 registers, about 1.6% error.
+const HLL_BITS: u32 = 12;
+
+// HyperLogLog: counts distinct hashes in fixed space.
+#[derive(Debug, Clone)]
+struct Hll {
+    registers: Vec<u8>,
+}
+
+impl Default for Hll {
+    fn default() -> Self {
+        Self {
+            registers: vec![0; 1 << HLL_BITS],
+        }
+    }
+}
+
+impl Hll {
+    fn add(&mut self, hash: u64) {
+        let register = (hash >> (64 - HLL_BITS)) as usize;
+        let rank = ((hash << HLL_BITS) | (1 << (HLL_BITS - 1))).leading_zeros() as u8 + 1;
+        let r = &mut self.registers[register];
+        *r = (*r).max(rank);
+    }
+
+    fn estimate(&self) -> f64 {
+        let m = self.registers.len() as f64;
+        let alpha = 0.7213 / (1.0 + 1.079 / m);
+        let sum: f64 = self.registers.iter().map(|r| 0.5f64.powi(*r as i32)).sum();
+        let raw = alpha * m * m / sum;
+        let zeros = self.registers.iter().filter(|r| **r == 0).count();
+        // few values: count them by the registers still empty
+        if raw <= 2.5 * m && zeros > 0 {
+            m * (m / zeros as f64).ln()
+        } else {
+            raw
+        }
+    }
+}
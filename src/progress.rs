@@ -0,0 +1,141 @@
+use std::cell::Cell;
+use std::io::{BufRead, Read};
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+/// How far a long-running operation has got.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Progress {
+    /// Input read so far.
+    pub bytes: u64,
+    /// Size of the whole input, if given to `ProgressReport::total_bytes`.
+    pub total_bytes: Option<u64>,
+    /// Records (lines, array elements) done so far.
+    pub records: u64,
+    pub elapsed: Duration,
+}
+
+impl Progress {
+    /// The share of the input read, from 0 to 1, if its size is known.
+    pub fn fraction(&self) -> Option<f64> {
+        match self.total_bytes? {
+            0 => Some(1.0),
+            total => Some((self.bytes as f64 / total as f64).min(1.0)),
+        }
+    }
+
+    /// The time left at the rate so far, if the input's size is known.
+    pub fn eta(&self) -> Option<Duration> {
+        let done = self.fraction()?;
+        if done == 0.0 {
+            return None;
+        }
+        Some(self.elapsed.mul_f64((1.0 - done) / done))
+    }
+}
+
+/// A progress callback for the `_with_progress` functions, e.g. to draw a
+/// progress bar or log a heartbeat. It is called at most once per interval
+/// (a second by default) and once more when the operation ends.
+pub struct ProgressReport<'a> {
+    callback: Box<dyn FnMut(&Progress) + 'a>,
+    interval: Duration,
+    total_bytes: Option<u64>,
+    start: Instant,
+    last: Instant,
+    bytes: u64,
+    records: u64,
+}
+
+impl<'a> ProgressReport<'a> {
+    pub fn new(callback: impl FnMut(&Progress) + 'a) -> Self {
+        let now = Instant::now();
+        Self {
+            callback: Box::new(callback),
+            interval: Duration::from_secs(1),
+            total_bytes: None,
+            start: now,
+            last: now,
+            bytes: 0,
+            records: 0,
+        }
+    }
+
+    /// The size of the input, e.g. from the file's metadata, so progress
+    /// has a fraction and an ETA.
+    pub fn total_bytes(mut self, bytes: u64) -> Self {
+        self.total_bytes = Some(bytes);
+        self
+    }
+
+    /// How often the callback may be called; `Duration::ZERO` for every
+    /// record.
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Notes that input up to `bytes` has been read and `records` more are
+    /// done, calling back if the interval has passed.
+    pub(crate) fn advance(&mut self, bytes: u64, records: u64) {
+        self.bytes = self.bytes.max(bytes);
+        self.records += records;
+        if self.last.elapsed() >= self.interval {
+            self.last = Instant::now();
+            self.call();
+        }
+    }
+
+    /// Calls back a last time, when the operation has succeeded.
+    pub(crate) fn finish(&mut self) {
+        self.call();
+    }
+
+    fn call(&mut self) {
+        let progress = Progress {
+            bytes: self.bytes,
+            total_bytes: self.total_bytes,
+            records: self.records,
+            elapsed: self.start.elapsed(),
+        };
+        (self.callback)(&progress);
+    }
+}
+
+/// A reader counting the bytes read through it, for functions that hand
+/// their reader on and still want to report progress.
+pub(crate) struct Counting<R> {
+    inner: R,
+    count: Rc<Cell<u64>>,
+}
+
+impl<R> Counting<R> {
+    /// `inner`, and the count of bytes read from it so far.
+    pub(crate) fn new(inner: R) -> (Self, Rc<Cell<u64>>) {
+        let count = Rc::new(Cell::new(0));
+        let reader = Self {
+            inner,
+            count: count.clone(),
+        };
+        (reader, count)
+    }
+}
+
+impl<R: Read> Read for Counting<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.count.set(self.count.get() + n as u64);
+        Ok(n)
+    }
+}
+
+impl<R: BufRead> BufRead for Counting<R> {
+    fn fill_buf(&mut self) -> std::io::Result<&[u8]> {
+        self.inner.fill_buf()
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.count.set(self.count.get() + amt as u64);
+        self.inner.consume(amt)
+    }
+}
@@ -0,0 +1,208 @@
+use std::collections::HashMap;
+
+use crate::schema::Type;
+use crate::JSONValue;
+
+/// The key the proto3 JSON mapping uses for an `Any`'s type URL.
+pub const ANY_TYPE: &str = "@type";
+
+// Well-known types whose JSON form is not an object, so an `Any` holding
+// one carries it in a `value` field.
+const WRAPPED_IN_ANY: &[&str] = &[
+    "google.protobuf.BoolValue",
+    "google.protobuf.BytesValue",
+    "google.protobuf.DoubleValue",
+    "google.protobuf.Duration",
+    "google.protobuf.FieldMask",
+    "google.protobuf.FloatValue",
+    "google.protobuf.Int32Value",
+    "google.protobuf.Int64Value",
+    "google.protobuf.ListValue",
+    "google.protobuf.StringValue",
+    "google.protobuf.Struct",
+    "google.protobuf.Timestamp",
+    "google.protobuf.UInt32Value",
+    "google.protobuf.UInt64Value",
+    "google.protobuf.Value",
+];
+
+/// The JSON name protoc gives a field, e.g. `user_id` becomes `userId`.
+pub fn proto_json_name(field: &str) -> String {
+    let mut out = String::with_capacity(field.len());
+    let mut upper = false;
+    for c in field.chars() {
+        if c == '_' {
+            upper = true;
+        } else if upper {
+            out.extend(c.to_uppercase());
+            upper = false;
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+impl JSONValue {
+    /// A field of a message object, looked up by its JSON name and then its
+    /// proto name as parsers must accept both. `None` when the field is
+    /// missing or `null`, which proto3 both read as the default value.
+    pub fn proto_field(&self, field: &str) -> Option<&JSONValue> {
+        let JSONValue::Dict(d) = self else {
+            return None;
+        };
+        d.get(&proto_json_name(field))
+            .or_else(|| d.get(field))
+            .filter(|v| **v != JSONValue::Null)
+    }
+
+    /// Whether a field holding this value would be left out by a proto3
+    /// encoder that skips defaults: `null`, `0`, `false`, `""`, `[]` or
+    /// `{}`.
+    pub fn is_proto_default(&self) -> bool {
+        match self {
+            JSONValue::Null => true,
+            JSONValue::Bool(b) => !b,
+            JSONValue::Num(n) => *n == 0.0,
+            JSONValue::Str(s) => s.is_empty(),
+            JSONValue::Array(entries) => entries.is_empty(),
+            JSONValue::Dict(d) => d.is_empty(),
+        }
+    }
+
+    /// An `int64`, `sint64` or `sfixed64` field, which proto3 writes as a
+    /// decimal string but also accepts as a number.
+    pub fn as_proto_int64(&self) -> Option<i64> {
+        match self {
+            JSONValue::Str(s) => s.parse().ok(),
+            JSONValue::Num(n) if n.fract() == 0.0 && n.abs() < i64::MAX as f64 => Some(*n as i64),
+            _ => None,
+        }
+    }
+
+    /// Like `as_proto_int64`, for `uint64` and `fixed64`.
+    pub fn as_proto_uint64(&self) -> Option<u64> {
+        match self {
+            JSONValue::Str(s) => s.parse().ok(),
+            JSONValue::Num(n) if n.fract() == 0.0 && (0.0..u64::MAX as f64).contains(n) => {
+                Some(*n as u64)
+            }
+            _ => None,
+        }
+    }
+
+    /// A `double` or `float` field: a number, a numeric string, or
+    /// `"NaN"`, `"Infinity"` and `"-Infinity"`.
+    pub fn as_proto_double(&self) -> Option<f64> {
+        match self {
+            JSONValue::Num(n) => Some(*n),
+            JSONValue::Str(s) => match s.as_str() {
+                "NaN" => Some(f64::NAN),
+                "Infinity" => Some(f64::INFINITY),
+                "-Infinity" => Some(f64::NEG_INFINITY),
+                s => s.parse().ok().filter(|n: &f64| n.is_finite()),
+            },
+            _ => None,
+        }
+    }
+
+    /// A 64-bit integer the way proto3 writes it, as a string, since
+    /// JavaScript numbers can't hold all of them.
+    pub fn from_proto_int64(n: i64) -> Self {
+        JSONValue::Str(n.to_string())
+    }
+
+    pub fn from_proto_uint64(n: u64) -> Self {
+        JSONValue::Str(n.to_string())
+    }
+
+    /// A number, or for values JSON numbers can't hold the strings `"NaN"`,
+    /// `"Infinity"` and `"-Infinity"`.
+    pub fn from_proto_double(n: f64) -> Self {
+        match n {
+            f64::INFINITY => JSONValue::Str("Infinity".to_string()),
+            f64::NEG_INFINITY => JSONValue::Str("-Infinity".to_string()),
+            n if n.is_nan() => JSONValue::Str("NaN".to_string()),
+            n => JSONValue::Num(n),
+        }
+    }
+}
+
+/// A `google.protobuf.Any` in the proto3 JSON mapping: the message's own
+/// JSON with an `"@type"` URL added, or for well-known types such as
+/// `Timestamp` whose JSON isn't an object, `{"@type": .., "value": ..}`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProtoAny {
+    type_url: String,
+    message: JSONValue,
+}
+
+impl ProtoAny {
+    pub fn new(type_url: &str, message: JSONValue) -> Self {
+        Self {
+            type_url: type_url.to_string(),
+            message,
+        }
+    }
+
+    pub fn from_json(json: &JSONValue) -> eyre::Result<Self> {
+        let JSONValue::Dict(d) = json else {
+            eyre::bail!(format!(
+                "Expected Any to be an object, found {}",
+                Type::of(json).name()
+            ))
+        };
+        let type_url = match d.get(ANY_TYPE) {
+            Some(JSONValue::Str(url)) => url.clone(),
+            Some(_) => eyre::bail!("Expected Any's @type to be a string"),
+            None => eyre::bail!("Any is missing @type"),
+        };
+        let mut any = Self {
+            type_url,
+            message: JSONValue::Null,
+        };
+        any.message = if any.wraps_value() {
+            d.get("value").cloned().unwrap_or(JSONValue::Null)
+        } else {
+            let fields: HashMap<_, _> = d
+                .iter()
+                .filter(|(k, _)| *k != ANY_TYPE)
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect();
+            JSONValue::Dict(fields)
+        };
+        Ok(any)
+    }
+
+    pub fn to_json(&self) -> JSONValue {
+        let mut d = match &self.message {
+            JSONValue::Dict(fields) if !self.wraps_value() => fields.clone(),
+            message => HashMap::from([("value".to_string(), message.clone())]),
+        };
+        d.insert(ANY_TYPE.to_string(), JSONValue::Str(self.type_url.clone()));
+        JSONValue::Dict(d)
+    }
+
+    /// e.g. `type.googleapis.com/google.protobuf.Duration`
+    pub fn type_url(&self) -> &str {
+        &self.type_url
+    }
+
+    /// The fully qualified message name, the part of the URL after its
+    /// last `/`, e.g. `google.protobuf.Duration`.
+    pub fn type_name(&self) -> &str {
+        match self.type_url.rsplit_once('/') {
+            Some((_, name)) => name,
+            None => &self.type_url,
+        }
+    }
+
+    /// The message's JSON, without `@type`.
+    pub fn message(&self) -> &JSONValue {
+        &self.message
+    }
+
+    fn wraps_value(&self) -> bool {
+        WRAPPED_IN_ANY.contains(&self.type_name())
+    }
+}
@@ -0,0 +1,207 @@
+use std::io::BufRead;
+
+use eyre::WrapErr;
+
+use crate::generate::Rng;
+use crate::progress::Counting;
+use crate::{parse, JSONValue, ParseOptions, ProgressReport, RecursiveParser, ValuePool};
+
+/// How the records of an input to `parse_auto` were framed.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum InputFormat {
+    /// One document that is not an array; it is the only record.
+    Single,
+    /// One top-level array; each element is a record.
+    Array,
+    /// One document per line.
+    Ndjson,
+    /// Several documents back to back, not split on line boundaries.
+    Concatenated,
+}
+
+/// Records produced by `parse_auto`.
+pub struct Records<R> {
+    format: InputFormat,
+    inner: Inner<R>,
+}
+
+enum Inner<R> {
+    Buffered(std::vec::IntoIter<JSONValue>),
+    Lines {
+        reader: R,
+        line_no: usize,
+        // records already parsed while sniffing
+        sniffed: std::vec::IntoIter<JSONValue>,
+        done: bool,
+    },
+}
+
+impl<R> Records<R> {
+    pub fn format(&self) -> InputFormat {
+        self.format
+    }
+}
+
+/// Reads the next non-blank line into `line`, returning its 1-based number.
+fn next_line<R: BufRead>(
+    reader: &mut R,
+    line: &mut String,
+    line_no: &mut usize,
+) -> eyre::Result<bool> {
+    loop {
+        line.clear();
+        if reader.read_line(line)? == 0 {
+            return Ok(false);
+        }
+        *line_no += 1;
+        if !line.trim().is_empty() {
+            return Ok(true);
+        }
+    }
+}
+
+/// Sniffs whether `reader` holds a single document, a top-level array,
+/// NDJSON or concatenated documents, and yields its records uniformly.
+///
+/// NDJSON is recognised by its first line being a complete document
+/// followed by more content, and is then read lazily one line at a time.
+/// Everything else is read into memory before being split.
+pub fn parse_auto<R: BufRead>(mut reader: R) -> eyre::Result<Records<R>> {
+    let mut line = String::new();
+    let mut line_no = 0;
+    if !next_line(&mut reader, &mut line, &mut line_no)? {
+        return Ok(buffered(InputFormat::Concatenated, Vec::new()));
+    }
+    let mut buf = line.clone().into_bytes();
+    if let Ok(first) = parse(line.as_bytes()) {
+        if !next_line(&mut reader, &mut line, &mut line_no)? {
+            return Ok(single_or_array(first));
+        }
+        // a second line that is a document of its own means NDJSON
+        if let Ok(second) = parse(line.as_bytes()) {
+            return Ok(Records {
+                format: InputFormat::Ndjson,
+                inner: Inner::Lines {
+                    reader,
+                    line_no,
+                    sniffed: vec![first, second].into_iter(),
+                    done: false,
+                },
+            });
+        }
+        buf.extend_from_slice(line.as_bytes());
+    }
+    reader.read_to_end(&mut buf)?;
+    let mut pool = ValuePool::new();
+    let mut values =
+        RecursiveParser::new(&buf, &mut pool, None, &ParseOptions::default()).parse_many()?;
+    match values.pop() {
+        Some(val) if values.is_empty() => Ok(single_or_array(val)),
+        Some(val) => {
+            values.push(val);
+            Ok(buffered(InputFormat::Concatenated, values))
+        }
+        None => Ok(buffered(InputFormat::Concatenated, values)),
+    }
+}
+
+/// Like `parse_auto`, reporting the bytes read and records yielded to
+/// `report`, and once more after the last record.
+pub fn parse_auto_with_progress<'a, R: BufRead + 'a>(
+    reader: R,
+    mut report: ProgressReport<'a>,
+) -> eyre::Result<impl Iterator<Item = eyre::Result<JSONValue>> + 'a> {
+    let (reader, read) = Counting::new(reader);
+    let mut records = parse_auto(reader)?;
+    let mut done = false;
+    Ok(std::iter::from_fn(move || {
+        if done {
+            return None;
+        }
+        match records.next() {
+            Some(record) => {
+                report.advance(read.get(), 1);
+                Some(record)
+            }
+            None => {
+                done = true;
+                report.finish();
+                None
+            }
+        }
+    }))
+}
+
+fn buffered<R>(format: InputFormat, values: Vec<JSONValue>) -> Records<R> {
+    Records {
+        format,
+        inner: Inner::Buffered(values.into_iter()),
+    }
+}
+
+fn single_or_array<R>(val: JSONValue) -> Records<R> {
+    match val {
+        JSONValue::Array(entries) => buffered(InputFormat::Array, entries),
+        val => buffered(InputFormat::Single, vec![val]),
+    }
+}
+
+impl<R: BufRead> Iterator for Records<R> {
+    type Item = eyre::Result<JSONValue>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match &mut self.inner {
+            Inner::Buffered(values) => values.next().map(Ok),
+            Inner::Lines {
+                reader,
+                line_no,
+                sniffed,
+                done,
+            } => {
+                if let Some(val) = sniffed.next() {
+                    return Some(Ok(val));
+                }
+                if *done {
+                    return None;
+                }
+                let mut line = String::new();
+                let res = match next_line(reader, &mut line, line_no) {
+                    Ok(true) => parse(line.as_bytes())
+                        .wrap_err_with(|| format!("Invalid record on line {}", line_no)),
+                    Ok(false) => {
+                        *done = true;
+                        return None;
+                    }
+                    Err(e) => Err(e),
+                };
+                if res.is_err() {
+                    *done = true;
+                }
+                Some(res)
+            }
+        }
+    }
+}
+
+/// Picks `k` records of `reader` (framed as `parse_auto` sees it) uniformly
+/// at random with reservoir sampling, so NDJSON of any length is read once
+/// while holding only `k` records. The same seed picks the same records,
+/// which are returned in the order they were read.
+pub fn sample_lines<R: BufRead>(reader: R, k: usize, seed: u64) -> eyre::Result<Vec<JSONValue>> {
+    let mut rng = Rng::new(seed);
+    let mut reservoir: Vec<(usize, JSONValue)> = Vec::with_capacity(k);
+    for (i, record) in parse_auto(reader)?.enumerate() {
+        let record = record?;
+        if i < k {
+            reservoir.push((i, record));
+        } else {
+            // record `i` stays with probability k / (i + 1)
+            let j = rng.below(i as u64 + 1) as usize;
+            if j < k {
+                reservoir[j] = (i, record);
+            }
+        }
+    }
+    reservoir.sort_by_key(|(i, _)| *i);
+    Ok(reservoir.into_iter().map(|(_, record)| record).collect())
+}
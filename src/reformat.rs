@@ -0,0 +1,193 @@
+use std::io::{ErrorKind, Read, Write};
+
+use crate::lexer::{self, Token};
+use crate::syntax::SyntaxState;
+
+/// Layout for `reformat`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReformatStyle {
+    /// Spaces per level, or `None` for compact output on one line.
+    pub indent: Option<usize>,
+}
+
+impl Default for ReformatStyle {
+    fn default() -> Self {
+        Self { indent: Some(2) }
+    }
+}
+
+/// Re-indents the document read from `reader` into `writer` straight from
+/// its tokens: keys stay in order and strings and numbers are copied as
+/// written. Nothing is held but a buffer and the nesting, so documents of
+/// any size can be formatted. The input is checked as it goes, but output
+/// may already have been written when an error is found.
+pub fn reformat<R: Read, W: Write>(
+    mut reader: R,
+    writer: W,
+    style: &ReformatStyle,
+) -> eyre::Result<()> {
+    let mut f = Reformatter {
+        writer,
+        out: Vec::with_capacity(BUF_SIZE),
+        style: *style,
+        syntax: SyntaxState::new(),
+        open: false,
+        scalar: Vec::new(),
+        string: false,
+        escaped: false,
+        offset: 0,
+    };
+    let mut buf = vec![0; BUF_SIZE];
+    loop {
+        let n = match reader.read(&mut buf) {
+            Ok(0) => break,
+            Ok(n) => n,
+            Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+            Err(e) => eyre::bail!(format!("Can't read input: {}", e)),
+        };
+        for &b in &buf[..n] {
+            f.byte(b)?;
+            f.offset += 1;
+        }
+        f.flush(false)?;
+    }
+    f.finish()
+}
+
+const BUF_SIZE: usize = 8 * 1024;
+
+struct Reformatter<W> {
+    writer: W,
+    out: Vec<u8>,
+    style: ReformatStyle,
+    syntax: SyntaxState,
+    // just wrote `{` or `[`, so a closing bracket next makes `{}`/`[]`
+    open: bool,
+    // the number or literal being read
+    scalar: Vec<u8>,
+    string: bool,
+    escaped: bool,
+    offset: usize,
+}
+
+impl<W: Write> Reformatter<W> {
+    fn byte(&mut self, b: u8) -> eyre::Result<()> {
+        if self.string {
+            match b {
+                _ if self.escaped => self.escaped = false,
+                b'\\' => self.escaped = true,
+                b'"' => self.string = false,
+                b if b < 0x20 => eyre::bail!(format!(
+                    "Control character in string at byte {}",
+                    self.offset
+                )),
+                _ => {}
+            }
+            self.out.push(b);
+            return Ok(());
+        }
+        if b.is_ascii_alphanumeric() || matches!(b, b'-' | b'+' | b'.') {
+            self.scalar.push(b);
+            return Ok(());
+        }
+        self.end_scalar()?;
+        match b {
+            b' ' | b'\t' | b'\n' | b'\r' => Ok(()),
+            b'{' => self.token(Token::LeftBrace, b"{"),
+            b'}' => self.token(Token::RightBrace, b"}"),
+            b'[' => self.token(Token::LeftBracket, b"["),
+            b']' => self.token(Token::RightBracket, b"]"),
+            b',' => self.token(Token::Comma, b","),
+            b':' => self.token(Token::Colon, b":"),
+            b'"' => {
+                self.string = true;
+                self.token(Token::StringVal(0, 0), b"\"")
+            }
+            b => eyre::bail!(format!(
+                "Unexpected character {:?} at byte {}",
+                b as char, self.offset
+            )),
+        }
+    }
+
+    // Writes out the number or literal just read, if any.
+    fn end_scalar(&mut self) -> eyre::Result<()> {
+        if self.scalar.is_empty() {
+            return Ok(());
+        }
+        let scalar = std::mem::take(&mut self.scalar);
+        let start = self.offset - scalar.len();
+        let token = match scalar.as_slice() {
+            b"true" => Token::BoolVal(true),
+            b"false" => Token::BoolVal(false),
+            b"null" => Token::NullVal,
+            s if lexer::is_number(s) => Token::NumVal(0.0),
+            s => eyre::bail!(format!(
+                "Invalid value {} at byte {}",
+                String::from_utf8_lossy(s),
+                start
+            )),
+        };
+        self.token(token, &scalar)?;
+        self.scalar = scalar;
+        self.scalar.clear();
+        Ok(())
+    }
+
+    fn token(&mut self, token: Token, text: &[u8]) -> eyre::Result<()> {
+        let depth = self.syntax.stack.len();
+        self.syntax
+            .feed(token)
+            .map_err(|e| eyre::eyre!(format!("{} at byte {}", e, self.offset)))?;
+        let closing = matches!(token, Token::RightBrace | Token::RightBracket);
+        match (std::mem::take(&mut self.open), closing) {
+            (true, false) => self.newline(depth),
+            (false, true) => self.newline(depth - 1),
+            _ => {}
+        }
+        self.out.extend_from_slice(text);
+        match token {
+            Token::Comma => self.newline(depth),
+            Token::Colon if self.style.indent.is_some() => self.out.push(b' '),
+            Token::LeftBrace | Token::LeftBracket => self.open = true,
+            _ => {}
+        }
+        Ok(())
+    }
+
+    // Starts a line indented `depth` levels.
+    fn newline(&mut self, depth: usize) {
+        if let Some(indent) = self.style.indent {
+            self.out.push(b'\n');
+            let width = indent * depth;
+            self.out.resize(self.out.len() + width, b' ');
+        }
+    }
+
+    fn flush(&mut self, all: bool) -> eyre::Result<()> {
+        if all || self.out.len() >= BUF_SIZE {
+            self.writer
+                .write_all(&self.out)
+                .map_err(|e| eyre::eyre!(format!("Can't write output: {}", e)))?;
+            self.out.clear();
+        }
+        Ok(())
+    }
+
+    fn finish(mut self) -> eyre::Result<()> {
+        self.end_scalar()?;
+        if self.string {
+            eyre::bail!("Unterminated string at end of input")
+        }
+        if !self.syntax.is_complete() {
+            eyre::bail!(format!(
+                "Unexpected end of input, {}",
+                self.syntax.describe()
+            ))
+        }
+        self.flush(true)?;
+        self.writer
+            .flush()
+            .map_err(|e| eyre::eyre!(format!("Can't write output: {}", e)))
+    }
+}
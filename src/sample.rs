@@ -0,0 +1,110 @@
+use std::collections::HashMap;
+
+use crate::schema::{Rules, Type};
+use crate::{JSONValue, Schema};
+
+// A string that looks like what a key named `key` usually holds.
+fn plausible_string(key: &str) -> &'static str {
+    let key = key.to_lowercase();
+    let hints = [
+        ("email", "user@example.com"),
+        ("url", "https://example.com"),
+        ("uri", "https://example.com"),
+        ("link", "https://example.com"),
+        ("date", "2024-01-01"),
+        ("time", "2024-01-01T00:00:00Z"),
+        ("uuid", "123e4567-e89b-12d3-a456-426614174000"),
+        ("name", "Example"),
+        ("id", "abc123"),
+    ];
+    hints
+        .iter()
+        .find(|(hint, _)| key.contains(hint))
+        .map_or("example", |(_, s)| s)
+}
+
+fn sample_string(rules: &Rules, key: &str) -> String {
+    let mut s = plausible_string(key).to_string();
+    if let Some(min) = rules.min_length {
+        let len = s.chars().count();
+        s.extend(std::iter::repeat_n('x', min.saturating_sub(len)));
+    }
+    if let Some(max) = rules.max_length {
+        s = s.chars().take(max).collect();
+    }
+    s
+}
+
+fn sample_number(rules: &Rules, integer: bool) -> f64 {
+    let low = [rules.minimum, rules.exclusive_minimum.map(|n| n + 1.0)]
+        .into_iter()
+        .flatten()
+        .reduce(f64::max);
+    let high = [rules.maximum, rules.exclusive_maximum.map(|n| n - 1.0)]
+        .into_iter()
+        .flatten()
+        .reduce(f64::min);
+    let mut n = match (low, high) {
+        (Some(low), Some(high)) => (low + high) / 2.0,
+        (Some(low), None) => low,
+        (None, Some(high)) => high.min(1.0),
+        (None, None) => 1.0,
+    };
+    if let Some(step) = rules.multiple_of {
+        n = (n / step).ceil() * step;
+    }
+    if integer {
+        n = n.ceil();
+    }
+    n
+}
+
+fn sample(rules: &Rules, key: &str) -> JSONValue {
+    if let Some(constant) = &rules.constant {
+        return constant.clone();
+    }
+    if let Some(first) = rules.one_of_values.as_ref().and_then(|v| v.first()) {
+        return first.clone();
+    }
+    let types = rules.types();
+    // prefer anything over null
+    let ty = types
+        .iter()
+        .find(|t| **t != Type::Null)
+        .or(types.first())
+        .copied();
+    match ty {
+        None | Some(Type::Null) => JSONValue::Null,
+        Some(Type::Boolean) => JSONValue::Bool(true),
+        Some(Type::Integer) => JSONValue::Num(sample_number(rules, true)),
+        Some(Type::Number) => JSONValue::Num(sample_number(rules, false)),
+        Some(Type::String) => JSONValue::Str(sample_string(rules, key)),
+        Some(Type::Array) => {
+            let len = rules.min_items.unwrap_or(0).max(1);
+            let len = len.min(rules.max_items.unwrap_or(len));
+            let item = match &rules.items {
+                Some(items) => sample(items, key),
+                None => JSONValue::Null,
+            };
+            JSONValue::Array(vec![item; len])
+        }
+        Some(Type::Object) => {
+            let mut d = HashMap::new();
+            for (k, property) in &rules.properties {
+                if !property.never {
+                    d.insert(k.clone(), sample(property, k));
+                }
+            }
+            JSONValue::Dict(d)
+        }
+    }
+}
+
+impl Schema {
+    /// A document matching the schema, with plausible values: bounds are
+    /// respected, string contents are guessed from their keys, and every
+    /// property is filled in.
+    pub fn sample(&self) -> JSONValue {
+        sample(self.rules(), "")
+    }
+}
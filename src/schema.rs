@@ -0,0 +1,635 @@
+use core::fmt;
+use std::collections::HashMap;
+
+use crate::lexer::{Lexer, Token};
+use crate::syntax::{Container, Expect, SyntaxState};
+use crate::{escape, parse_with_options, pointer, JSONValue, ParseOptions};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) enum Type {
+    Null,
+    Boolean,
+    Integer,
+    Number,
+    String,
+    Array,
+    Object,
+}
+
+impl Type {
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            Type::Null => "null",
+            Type::Boolean => "boolean",
+            Type::Integer => "integer",
+            Type::Number => "number",
+            Type::String => "string",
+            Type::Array => "array",
+            Type::Object => "object",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        [
+            Type::Null,
+            Type::Boolean,
+            Type::Integer,
+            Type::Number,
+            Type::String,
+            Type::Array,
+            Type::Object,
+        ]
+        .into_iter()
+        .find(|t| t.name() == name)
+    }
+
+    pub(crate) fn of(value: &JSONValue) -> Self {
+        match value {
+            JSONValue::Null => Type::Null,
+            JSONValue::Bool(_) => Type::Boolean,
+            JSONValue::Num(n) if n.fract() == 0.0 => Type::Integer,
+            JSONValue::Num(_) => Type::Number,
+            JSONValue::Str(_) => Type::String,
+            JSONValue::Array(_) => Type::Array,
+            JSONValue::Dict(_) => Type::Object,
+        }
+    }
+
+    // Whether a value of type `self` satisfies `"type": wanted`.
+    fn satisfies(self, wanted: Type) -> bool {
+        self == wanted || (self == Type::Integer && wanted == Type::Number)
+    }
+}
+
+// Keywords that only describe a schema and never fail validation.
+const ANNOTATIONS: [&str; 12] = [
+    "$schema",
+    "$id",
+    "$comment",
+    "$defs",
+    "definitions",
+    "title",
+    "description",
+    "default",
+    "examples",
+    "deprecated",
+    "readOnly",
+    "writeOnly",
+];
+
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Rules {
+    // the `false` schema
+    pub(crate) never: bool,
+    pub(crate) types: Option<Vec<Type>>,
+    pub(crate) one_of_values: Option<Vec<JSONValue>>,
+    pub(crate) constant: Option<JSONValue>,
+    pub(crate) minimum: Option<f64>,
+    pub(crate) maximum: Option<f64>,
+    pub(crate) exclusive_minimum: Option<f64>,
+    pub(crate) exclusive_maximum: Option<f64>,
+    pub(crate) multiple_of: Option<f64>,
+    pub(crate) min_length: Option<usize>,
+    pub(crate) max_length: Option<usize>,
+    pub(crate) min_items: Option<usize>,
+    pub(crate) max_items: Option<usize>,
+    pub(crate) items: Option<Box<Rules>>,
+    pub(crate) min_properties: Option<usize>,
+    pub(crate) max_properties: Option<usize>,
+    pub(crate) properties: HashMap<String, Rules>,
+    pub(crate) required: Vec<String>,
+    pub(crate) additional_properties: Option<Box<Rules>>,
+}
+
+impl Rules {
+    fn compile(schema: &JSONValue, ptr: &str) -> eyre::Result<Self> {
+        let d = match schema {
+            JSONValue::Bool(b) => {
+                return Ok(Self {
+                    never: !b,
+                    ..Self::default()
+                })
+            }
+            JSONValue::Dict(d) => d,
+            _ => eyre::bail!(format!(
+                "Schema at {:?} must be an object or a boolean",
+                ptr
+            )),
+        };
+        let mut rules = Self::default();
+        for (k, v) in d {
+            let at = pointer::child(ptr, k);
+            match k.as_str() {
+                "type" => {
+                    let names = match v {
+                        JSONValue::Array(names) => names.iter().collect(),
+                        v => vec![v],
+                    };
+                    let types = names
+                        .into_iter()
+                        .map(|n| match n {
+                            JSONValue::Str(s) => Type::from_name(s),
+                            _ => None,
+                        })
+                        .collect::<Option<_>>();
+                    match types {
+                        Some(types) => rules.types = Some(types),
+                        None => eyre::bail!(format!("Unknown type at {:?}", at)),
+                    }
+                }
+                "enum" => match v {
+                    JSONValue::Array(values) => rules.one_of_values = Some(values.clone()),
+                    _ => eyre::bail!(format!("Expected an array at {:?}", at)),
+                },
+                "const" => rules.constant = Some(v.clone()),
+                "minimum" => rules.minimum = Some(number(v, &at)?),
+                "maximum" => rules.maximum = Some(number(v, &at)?),
+                "exclusiveMinimum" => rules.exclusive_minimum = Some(number(v, &at)?),
+                "exclusiveMaximum" => rules.exclusive_maximum = Some(number(v, &at)?),
+                "multipleOf" => rules.multiple_of = Some(number(v, &at)?),
+                "minLength" => rules.min_length = Some(count(v, &at)?),
+                "maxLength" => rules.max_length = Some(count(v, &at)?),
+                "minItems" => rules.min_items = Some(count(v, &at)?),
+                "maxItems" => rules.max_items = Some(count(v, &at)?),
+                "minProperties" => rules.min_properties = Some(count(v, &at)?),
+                "maxProperties" => rules.max_properties = Some(count(v, &at)?),
+                "items" => rules.items = Some(Box::new(Self::compile(v, &at)?)),
+                "properties" => match v {
+                    JSONValue::Dict(props) => {
+                        for (name, s) in props {
+                            let s = Self::compile(s, &pointer::child(&at, name))?;
+                            rules.properties.insert(name.clone(), s);
+                        }
+                    }
+                    _ => eyre::bail!(format!("Expected an object at {:?}", at)),
+                },
+                "required" => {
+                    let names = match v {
+                        JSONValue::Array(names) => names
+                            .iter()
+                            .map(|n| match n {
+                                JSONValue::Str(s) => Some(s.clone()),
+                                _ => None,
+                            })
+                            .collect(),
+                        _ => None,
+                    };
+                    rules.required = names.ok_or_else(|| {
+                        eyre::eyre!(format!("Expected an array of strings at {:?}", at))
+                    })?;
+                }
+                "additionalProperties" => {
+                    rules.additional_properties = Some(Box::new(Self::compile(v, &at)?))
+                }
+                k if ANNOTATIONS.contains(&k) => {}
+                k => eyre::bail!(format!("Unsupported schema keyword `{}` at {:?}", k, ptr)),
+            }
+        }
+        Ok(rules)
+    }
+
+    // The rules for the value of property `name`.
+    pub(crate) fn property(&self, name: &str) -> Option<&Rules> {
+        self.properties
+            .get(name)
+            .or(self.additional_properties.as_deref())
+    }
+
+    /// The types a value may have: those listed under `type`, or else those
+    /// implied by `const`, `enum` or the other keywords, if any.
+    pub(crate) fn types(&self) -> Vec<Type> {
+        if let Some(types) = &self.types {
+            return types.clone();
+        }
+        let values = self
+            .constant
+            .iter()
+            .chain(self.one_of_values.iter().flatten());
+        let mut of_values = Vec::new();
+        for t in values.map(Type::of) {
+            if !of_values.contains(&t) {
+                of_values.push(t);
+            }
+        }
+        if !of_values.is_empty() {
+            return of_values;
+        }
+        let implied = if !self.properties.is_empty()
+            || !self.required.is_empty()
+            || self.additional_properties.is_some()
+        {
+            Type::Object
+        } else if self.items.is_some() {
+            Type::Array
+        } else if self.min_length.is_some() || self.max_length.is_some() {
+            Type::String
+        } else if self.minimum.is_some() || self.maximum.is_some() {
+            Type::Number
+        } else {
+            return Vec::new();
+        };
+        vec![implied]
+    }
+
+    // Whether `enum` or `const` needs the whole value to be compared.
+    fn compares_values(&self) -> bool {
+        self.one_of_values.is_some() || self.constant.is_some()
+    }
+}
+
+fn number(v: &JSONValue, at: &str) -> eyre::Result<f64> {
+    match v {
+        JSONValue::Num(n) => Ok(*n),
+        _ => eyre::bail!(format!("Expected a number at {:?}", at)),
+    }
+}
+
+// Whether `n` is a whole number of `step`s. Decimal steps like 0.1 aren't
+// exact in binary, so the quotient only has to be within rounding error of
+// an integer.
+fn is_multiple(n: f64, step: f64) -> bool {
+    let q = n / step;
+    (q - q.round()).abs() <= 1e-9 * q.abs().max(1.0)
+}
+
+fn count(v: &JSONValue, at: &str) -> eyre::Result<usize> {
+    match v {
+        JSONValue::Num(n) if *n >= 0.0 && n.fract() == 0.0 => Ok(*n as usize),
+        _ => eyre::bail!(format!("Expected a non-negative integer at {:?}", at)),
+    }
+}
+
+/// A compiled JSON Schema, for validating documents as they are read.
+///
+/// Supports the keywords that can be checked without backtracking: `type`,
+/// `enum`, `const`, the numeric, length and size bounds, `items`,
+/// `properties`, `required` and `additionalProperties`. Compiling a schema
+/// that uses any other keyword (e.g. `$ref` or `anyOf`) fails rather than
+/// ignoring it.
+#[derive(Debug, Clone)]
+pub struct Schema {
+    root: Rules,
+}
+
+/// A place where a document breaks its schema.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Violation {
+    pointer: String,
+    offset: usize,
+    message: String,
+}
+
+impl Violation {
+    /// JSON Pointer of the offending value.
+    pub fn pointer(&self) -> &str {
+        &self.pointer
+    }
+
+    /// Byte offset where the offending value starts.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.pointer.is_empty() {
+            write!(f, "byte {}: {}", self.offset, self.message)
+        } else {
+            write!(
+                f,
+                "{} (byte {}): {}",
+                self.pointer, self.offset, self.message
+            )
+        }
+    }
+}
+
+struct Frame<'s> {
+    rules: Option<&'s Rules>,
+    container: Container,
+    start: usize,
+    // pointer length to go back to once the container ends
+    parent_len: usize,
+    count: usize,
+    // which of `rules.required` have been seen
+    seen: Vec<bool>,
+}
+
+struct Check<'s, 'b, F> {
+    buf: &'b [u8],
+    options: &'b ParseOptions,
+    ptr: String,
+    stack: Vec<Frame<'s>>,
+    key: String,
+    report: F,
+}
+
+impl<'s, F: FnMut(Violation)> Check<'s, '_, F> {
+    fn report(&mut self, offset: usize, message: String) {
+        (self.report)(Violation {
+            pointer: self.ptr.clone(),
+            offset,
+            message,
+        })
+    }
+
+    fn string(&self, i: usize, j: usize) -> eyre::Result<String> {
+        escape::unescape_str(&self.buf[i..j], self.options.lone_surrogates, String::new())
+    }
+
+    // Type, `enum` and `const` checks shared by all values. `value` is
+    // `None` for arrays and objects, which are compared once they end.
+    fn check_value(
+        &mut self,
+        rules: &Rules,
+        found: Type,
+        value: Option<&JSONValue>,
+        offset: usize,
+    ) {
+        if let Some(types) = &rules.types {
+            if !types.iter().any(|t| found.satisfies(*t)) {
+                let names: Vec<_> = types.iter().map(|t| t.name()).collect();
+                self.report(
+                    offset,
+                    format!("Expected {}, found {}", names.join(" or "), found.name()),
+                );
+            }
+        }
+        if let Some(value) = value {
+            self.compare(rules, value, offset);
+        }
+    }
+
+    fn compare(&mut self, rules: &Rules, value: &JSONValue, offset: usize) {
+        if let Some(values) = &rules.one_of_values {
+            if !values.contains(value) {
+                self.report(offset, "Value is not one of the allowed values".to_string());
+            }
+        }
+        if let Some(constant) = &rules.constant {
+            if constant != value {
+                self.report(offset, "Value is not the required constant".to_string());
+            }
+        }
+    }
+
+    fn check_number(&mut self, rules: &Rules, n: f64, lexeme: &str, offset: usize) {
+        let mut fail = |bound: Option<f64>, broken: fn(f64, f64) -> bool, what: &str| {
+            if let Some(b) = bound.filter(|b| broken(n, *b)) {
+                self.report(offset, format!("Number {} is {} {}", lexeme, what, b));
+            }
+        };
+        fail(rules.minimum, |n, b| n < b, "below the minimum of");
+        fail(rules.maximum, |n, b| n > b, "above the maximum of");
+        fail(rules.exclusive_minimum, |n, b| n <= b, "not above");
+        fail(rules.exclusive_maximum, |n, b| n >= b, "not below");
+        fail(
+            rules.multiple_of,
+            |n, b| !is_multiple(n, b),
+            "not a multiple of",
+        );
+    }
+
+    fn check_size(
+        &mut self,
+        (what, unit): (&str, &str),
+        len: usize,
+        min: Option<usize>,
+        max: Option<usize>,
+        offset: usize,
+    ) {
+        if let Some(min) = min.filter(|min| len < *min) {
+            self.report(
+                offset,
+                format!("{} has {} {}, fewer than {}", what, len, unit, min),
+            );
+        }
+        if let Some(max) = max.filter(|max| len > *max) {
+            self.report(
+                offset,
+                format!("{} has {} {}, more than {}", what, len, unit, max),
+            );
+        }
+    }
+
+    // Checks a value starting with token `t`, whose pointer is `self.ptr`.
+    fn start(
+        &mut self,
+        rules: Option<&'s Rules>,
+        t: Token,
+        offset: usize,
+        end: usize,
+        parent_len: usize,
+    ) -> eyre::Result<()> {
+        let rules = rules.filter(|r| {
+            !r.never || {
+                self.report(offset, "Value is not allowed here".to_string());
+                false
+            }
+        });
+        let (found, value) = match t {
+            Token::LeftBrace | Token::LeftBracket => {
+                let (found, container) = if t == Token::LeftBrace {
+                    (Type::Object, Container::Object)
+                } else {
+                    (Type::Array, Container::Array)
+                };
+                if let Some(rules) = rules {
+                    self.check_value(rules, found, None, offset);
+                }
+                let seen = vec![false; rules.map_or(0, |r| r.required.len())];
+                self.stack.push(Frame {
+                    rules,
+                    container,
+                    start: offset,
+                    parent_len,
+                    count: 0,
+                    seen,
+                });
+                return Ok(());
+            }
+            Token::NullVal => (Type::Null, JSONValue::Null),
+            Token::BoolVal(b) => (Type::Boolean, JSONValue::Bool(b)),
+            Token::NumVal(n) => {
+                let found = if n.fract() == 0.0 {
+                    Type::Integer
+                } else {
+                    Type::Number
+                };
+                (found, JSONValue::Num(n))
+            }
+            Token::StringVal(i, j) => (Type::String, JSONValue::Str(self.string(i, j)?)),
+            _ => unreachable!("only values start values"),
+        };
+        let Some(rules) = rules else {
+            self.ptr.truncate(parent_len);
+            return Ok(());
+        };
+        self.check_value(rules, found, Some(&value), offset);
+        match &value {
+            JSONValue::Num(n) => {
+                let lexeme = std::str::from_utf8(&self.buf[offset..end])?;
+                self.check_number(rules, *n, lexeme, offset);
+            }
+            JSONValue::Str(s) => {
+                let len = s.chars().count();
+                self.check_size(
+                    ("String", "characters"),
+                    len,
+                    rules.min_length,
+                    rules.max_length,
+                    offset,
+                );
+            }
+            _ => {}
+        }
+        self.ptr.truncate(parent_len);
+        Ok(())
+    }
+
+    fn end(&mut self, end: usize) -> eyre::Result<()> {
+        let frame = self.stack.pop().expect("grammar checked the container");
+        if let Some(rules) = frame.rules {
+            match frame.container {
+                Container::Object => {
+                    for (name, seen) in rules.required.iter().zip(&frame.seen) {
+                        if !seen {
+                            self.report(
+                                frame.start,
+                                format!("Missing required property {:?}", name),
+                            );
+                        }
+                    }
+                    let (min, max) = (rules.min_properties, rules.max_properties);
+                    self.check_size(("Object", "properties"), frame.count, min, max, frame.start);
+                }
+                Container::Array => {
+                    let (min, max) = (rules.min_items, rules.max_items);
+                    self.check_size(("Array", "items"), frame.count, min, max, frame.start);
+                }
+            }
+            if rules.compares_values() {
+                // only this subtree is built
+                let value = parse_with_options(&self.buf[frame.start..end], self.options)?;
+                self.compare(rules, &value, frame.start);
+            }
+        }
+        self.ptr.truncate(frame.parent_len);
+        Ok(())
+    }
+
+    fn run(&mut self, root: &'s Rules) -> eyre::Result<()> {
+        let mut syntax = SyntaxState::new();
+        for t in Lexer::with_options(self.options).tokens(self.buf) {
+            let t = t?;
+            let expect = syntax.expect;
+            syntax.feed(t.token).map_err(|e| {
+                eyre::eyre!(format!("Syntax error at byte {}: {}", t.span.start, e))
+            })?;
+            if let Some(max_depth) = self.options.max_depth {
+                if syntax.stack.len() > max_depth {
+                    eyre::bail!(format!("Nesting exceeds maximum depth of {}", max_depth))
+                }
+            }
+            match t.token {
+                Token::Comma | Token::Colon | Token::Comment => {}
+                Token::RightBrace | Token::RightBracket => self.end(t.span.end)?,
+                Token::StringVal(i, j)
+                    if matches!(expect, Expect::Key | Expect::KeyOrEndObject) =>
+                {
+                    self.key = self.string(i, j)?;
+                    let frame = self.stack.last_mut().expect("keys are inside objects");
+                    if let Some(rules) = frame.rules {
+                        for (name, seen) in rules.required.iter().zip(&mut frame.seen) {
+                            *seen |= *name == self.key;
+                        }
+                    }
+                }
+                token => {
+                    let parent_len = self.ptr.len();
+                    let rules = match self.stack.last_mut() {
+                        None => Some(root),
+                        Some(frame) => {
+                            frame.count += 1;
+                            match frame.container {
+                                Container::Array => {
+                                    pointer::push(&mut self.ptr, &(frame.count - 1).to_string());
+                                    frame.rules.and_then(|r| r.items.as_deref())
+                                }
+                                Container::Object => {
+                                    pointer::push(&mut self.ptr, &self.key);
+                                    frame.rules.and_then(|r| r.property(&self.key))
+                                }
+                            }
+                        }
+                    };
+                    self.start(rules, token, t.span.start, t.span.end, parent_len)?;
+                }
+            }
+        }
+        if !syntax.is_complete() {
+            eyre::bail!(format!("Unexpected end of input, {}", syntax.describe()))
+        }
+        Ok(())
+    }
+}
+
+impl Schema {
+    pub(crate) fn rules(&self) -> &Rules {
+        &self.root
+    }
+
+    /// Compiles a schema document.
+    pub fn compile(schema: &JSONValue) -> eyre::Result<Self> {
+        Ok(Self {
+            root: Rules::compile(schema, "")?,
+        })
+    }
+
+    pub fn parse(schema: &[u8]) -> eyre::Result<Self> {
+        Self::compile(&crate::parse(schema)?)
+    }
+
+    /// Checks `json` against the schema, returning every violation. Malformed
+    /// JSON is an error.
+    pub fn validate(&self, json: &[u8]) -> eyre::Result<Vec<Violation>> {
+        let mut violations = Vec::new();
+        self.validate_with(json, &ParseOptions::default(), |v| violations.push(v))?;
+        Ok(violations)
+    }
+
+    /// Checks `json` token by token, calling `report` on each violation as
+    /// soon as it is found. No tree is built, except for subtrees compared
+    /// against `enum` or `const`, so memory use doesn't grow with the
+    /// document.
+    pub fn validate_with(
+        &self,
+        json: &[u8],
+        options: &ParseOptions,
+        report: impl FnMut(Violation),
+    ) -> eyre::Result<()> {
+        if let Some(max_size) = options.max_size {
+            if json.len() > max_size {
+                eyre::bail!(format!(
+                    "Input of {} bytes exceeds maximum size of {}",
+                    json.len(),
+                    max_size
+                ))
+            }
+        }
+        Check {
+            buf: json,
+            options,
+            ptr: String::new(),
+            stack: Vec::new(),
+            key: String::new(),
+            report,
+        }
+        .run(&self.root)
+    }
+}
@@ -0,0 +1,351 @@
+use core::fmt::{self, Write};
+
+use crate::{MapKey, MapKind, MapOps, ToJsonNumber, Value};
+
+/// Knobs controlling how values are written. The default is compact JSON
+/// with only the escapes RFC 8259 requires.
+#[derive(Debug, Clone, Default)]
+pub struct SerializeOptions {
+    /// Escape every non-ASCII character as `\uXXXX`, for legacy consumers.
+    pub ascii_only: bool,
+    /// Escape `<`, `>`, `&`, U+2028 and U+2029 so the output can be embedded
+    /// in HTML `<script>` tags and JavaScript source.
+    pub html_safe: bool,
+    /// Write object keys in sorted order instead of hash map order.
+    pub sort_keys: bool,
+    /// Pretty-print with this many spaces per level instead of compactly.
+    pub indent: Option<usize>,
+    /// When pretty-printing, keep an array or object on one line if it fits
+    /// within this many columns.
+    pub max_inline_width: Option<usize>,
+    /// When pretty-printing an array of scalars that doesn't fit on one line,
+    /// fill lines up to `max_inline_width` instead of one element per line.
+    pub pack_arrays: bool,
+}
+
+impl SerializeOptions {
+    /// Two-space indentation with sorted keys, keeping anything that fits in
+    /// 80 columns on one line (like prettier).
+    pub fn pretty() -> Self {
+        Self {
+            sort_keys: true,
+            indent: Some(2),
+            max_inline_width: Some(80),
+            ..Self::default()
+        }
+    }
+}
+
+impl<S, N, M> Value<S, N, M>
+where
+    S: MapKey + AsRef<str>,
+    N: ToJsonNumber,
+    M: MapKind,
+{
+    /// Compact JSON text for this value.
+    pub fn to_json_string(&self) -> String {
+        self.to_json_string_with(&SerializeOptions::default())
+    }
+
+    /// Indented JSON text for this value, see `SerializeOptions::pretty`.
+    pub fn to_json_pretty(&self) -> String {
+        self.to_json_string_with(&SerializeOptions::pretty())
+    }
+
+    pub fn to_json_string_with(&self, options: &SerializeOptions) -> String {
+        let mut out = String::new();
+        self.write_json(&mut out, options)
+            .expect("writing to a String doesn't fail");
+        out
+    }
+
+    pub fn write_json<W: Write>(&self, out: &mut W, options: &SerializeOptions) -> fmt::Result {
+        let mut w = Writer {
+            out: Column { out, column: 0 },
+            options,
+            depth: 0,
+        };
+        w.write_value(self)
+    }
+}
+
+/// `{}` writes compact JSON, `{:#}` pretty-prints it.
+impl<S, N, M> fmt::Display for Value<S, N, M>
+where
+    S: MapKey + AsRef<str>,
+    N: ToJsonNumber,
+    M: MapKind,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if f.alternate() {
+            self.write_json(f, &SerializeOptions::pretty())
+        } else {
+            self.write_json(f, &SerializeOptions::default())
+        }
+    }
+}
+
+// Tracks the column the next character will be written at.
+struct Column<W> {
+    out: W,
+    column: usize,
+}
+
+impl<W: Write> Write for Column<W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        match s.rfind('\n') {
+            Some(i) => self.column = s[i + 1..].chars().count(),
+            None => self.column += s.chars().count(),
+        }
+        self.out.write_str(s)
+    }
+}
+
+// Buffers output, failing once it is longer than `limit` characters.
+struct Bounded {
+    buf: String,
+    len: usize,
+    limit: usize,
+}
+
+impl Write for Bounded {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.len += s.chars().count();
+        if self.len > self.limit {
+            return Err(fmt::Error);
+        }
+        self.buf.push_str(s);
+        Ok(())
+    }
+}
+
+struct Writer<'o, W> {
+    out: Column<W>,
+    options: &'o SerializeOptions,
+    depth: usize,
+}
+
+impl<W: Write> Writer<'_, W> {
+    fn write_value<S, N, M>(&mut self, v: &Value<S, N, M>) -> fmt::Result
+    where
+        S: MapKey + AsRef<str>,
+        N: ToJsonNumber,
+        M: MapKind,
+    {
+        let Some(indent) = self.options.indent else {
+            return write_flat(&mut self.out, v, self.options, ",", ":");
+        };
+        let empty = match v {
+            Value::Array(entries) => entries.is_empty(),
+            Value::Dict(entries) => entries.is_empty(),
+            _ => true,
+        };
+        if empty {
+            return write_flat(&mut self.out, v, self.options, ",", ":");
+        }
+        if let Some(inline) = self.try_inline(v) {
+            return self.out.write_str(&inline);
+        }
+        self.depth += 1;
+        match v {
+            Value::Array(entries) => {
+                self.out.write_char('[')?;
+                if self.options.pack_arrays && entries.iter().all(is_scalar) {
+                    self.write_packed(entries, indent)?;
+                } else {
+                    for (i, e) in entries.iter().enumerate() {
+                        if i > 0 {
+                            self.out.write_char(',')?;
+                        }
+                        self.newline(indent)?;
+                        self.write_value(e)?;
+                    }
+                }
+                self.depth -= 1;
+                self.newline(indent)?;
+                self.out.write_char(']')
+            }
+            Value::Dict(_) => {
+                self.out.write_char('{')?;
+                for (i, (k, e)) in sorted_entries(v, self.options).into_iter().enumerate() {
+                    if i > 0 {
+                        self.out.write_char(',')?;
+                    }
+                    self.newline(indent)?;
+                    write_string(&mut self.out, k.as_ref(), self.options)?;
+                    self.out.write_str(": ")?;
+                    self.write_value(e)?;
+                }
+                self.depth -= 1;
+                self.newline(indent)?;
+                self.out.write_char('}')
+            }
+            _ => unreachable!("scalars are always inline"),
+        }
+    }
+
+    // The value on a single line, if it fits in what's left of this one.
+    fn try_inline<S, N, M>(&self, v: &Value<S, N, M>) -> Option<String>
+    where
+        S: MapKey + AsRef<str>,
+        N: ToJsonNumber,
+        M: MapKind,
+    {
+        let width = self.options.max_inline_width?;
+        let mut b = Bounded {
+            buf: String::new(),
+            len: 0,
+            limit: width.checked_sub(self.out.column)?,
+        };
+        write_flat(&mut b, v, self.options, ", ", ": ").ok()?;
+        Some(b.buf)
+    }
+
+    fn write_packed<S, N, M>(&mut self, entries: &[Value<S, N, M>], indent: usize) -> fmt::Result
+    where
+        S: MapKey + AsRef<str>,
+        N: ToJsonNumber,
+        M: MapKind,
+    {
+        let width = self.options.max_inline_width.unwrap_or(0);
+        self.newline(indent)?;
+        for (i, e) in entries.iter().enumerate() {
+            let mut s = String::new();
+            write_flat(&mut s, e, self.options, ",", ":")?;
+            if i > 0 {
+                // room for ", " + element + trailing ","
+                if self.out.column + s.chars().count() + 3 > width {
+                    self.out.write_char(',')?;
+                    self.newline(indent)?;
+                } else {
+                    self.out.write_str(", ")?;
+                }
+            }
+            self.out.write_str(&s)?;
+        }
+        Ok(())
+    }
+
+    fn newline(&mut self, indent: usize) -> fmt::Result {
+        self.out.write_char('\n')?;
+        for _ in 0..self.depth * indent {
+            self.out.write_char(' ')?;
+        }
+        Ok(())
+    }
+}
+
+fn is_scalar<S: MapKey, N, M: MapKind>(v: &Value<S, N, M>) -> bool {
+    !matches!(v, Value::Array(_) | Value::Dict(_))
+}
+
+fn sorted_entries<'v, S, N, M>(
+    v: &'v Value<S, N, M>,
+    options: &SerializeOptions,
+) -> Vec<(&'v S, &'v Value<S, N, M>)>
+where
+    S: MapKey + AsRef<str>,
+    M: MapKind,
+{
+    let Value::Dict(d) = v else {
+        return Vec::new();
+    };
+    let mut entries: Vec<_> = d.entries().collect();
+    if options.sort_keys {
+        entries.sort_unstable_by(|a, b| a.0.as_ref().cmp(b.0.as_ref()));
+    }
+    entries
+}
+
+// Writes `v` on one line, with `comma` and `colon` as separators.
+fn write_flat<W: Write, S, N, M>(
+    out: &mut W,
+    v: &Value<S, N, M>,
+    options: &SerializeOptions,
+    comma: &str,
+    colon: &str,
+) -> fmt::Result
+where
+    S: MapKey + AsRef<str>,
+    N: ToJsonNumber,
+    M: MapKind,
+{
+    match v {
+        Value::Null => out.write_str("null"),
+        Value::Bool(b) => write!(out, "{}", b),
+        Value::Num(n) => n.write_json_number(out),
+        Value::Str(s) => write_string(out, s.as_ref(), options),
+        Value::Array(entries) => {
+            out.write_char('[')?;
+            for (i, e) in entries.iter().enumerate() {
+                if i > 0 {
+                    out.write_str(comma)?;
+                }
+                write_flat(out, e, options, comma, colon)?;
+            }
+            out.write_char(']')
+        }
+        Value::Dict(_) => {
+            out.write_char('{')?;
+            for (i, (k, e)) in sorted_entries(v, options).into_iter().enumerate() {
+                if i > 0 {
+                    out.write_str(comma)?;
+                }
+                write_string(out, k.as_ref(), options)?;
+                out.write_str(colon)?;
+                write_flat(out, e, options, comma, colon)?;
+            }
+            out.write_char('}')
+        }
+    }
+}
+
+// JSON has no NaN or infinities; like `JSON.stringify` they become null.
+pub(crate) fn write_number<W: ?Sized + Write>(out: &mut W, n: f64) -> fmt::Result {
+    if !n.is_finite() {
+        out.write_str("null")
+    } else if n.fract() == 0.0 && n.abs() < 1e16 {
+        write!(out, "{}", n)
+    } else {
+        write!(out, "{:?}", n)
+    }
+}
+
+pub(crate) fn write_string<W: ?Sized + Write>(
+    out: &mut W,
+    s: &str,
+    options: &SerializeOptions,
+) -> fmt::Result {
+    out.write_char('"')?;
+    let mut start = 0;
+    for (i, c) in s.char_indices() {
+        let escape = match c {
+            '"' => Some("\\\""),
+            '\\' => Some("\\\\"),
+            '\n' => Some("\\n"),
+            '\r' => Some("\\r"),
+            '\t' => Some("\\t"),
+            '\u{08}' => Some("\\b"),
+            '\u{0c}' => Some("\\f"),
+            _ => None,
+        };
+        let hex = c < ' '
+            || (options.ascii_only && !c.is_ascii())
+            || (options.html_safe && matches!(c, '<' | '>' | '&' | '\u{2028}' | '\u{2029}'));
+        if escape.is_none() && !hex {
+            continue;
+        }
+        out.write_str(&s[start..i])?;
+        start = i + c.len_utf8();
+        match escape {
+            Some(e) => out.write_str(e)?,
+            None => {
+                for unit in c.encode_utf16(&mut [0; 2]) {
+                    write!(out, "\\u{:04x}", unit)?;
+                }
+            }
+        }
+    }
+    out.write_str(&s[start..])?;
+    out.write_char('"')
+}
@@ -0,0 +1,104 @@
+use crate::{JSONValue, Patch, PatchOp, SharedValue};
+
+/// Edits to a document with undo and redo, e.g. behind a config editor.
+/// Every version is a `SharedValue` fork, so keeping them all costs little
+/// more than the changes themselves.
+#[derive(Debug, Clone)]
+pub struct EditSession {
+    // `versions[i + 1]` is `versions[i]` with `ops[i]` applied
+    versions: Vec<SharedValue>,
+    ops: Vec<PatchOp>,
+    current: usize,
+}
+
+impl EditSession {
+    pub fn new(doc: JSONValue) -> Self {
+        Self {
+            versions: vec![SharedValue::from(doc)],
+            ops: Vec::new(),
+            current: 0,
+        }
+    }
+
+    /// The document as of the last edit not undone.
+    pub fn value(&self) -> &SharedValue {
+        &self.versions[self.current]
+    }
+
+    /// Puts `value` at the JSON Pointer `ptr`, replacing what was there. In
+    /// an array, `-` appends.
+    pub fn set(&mut self, ptr: &str, value: JSONValue) -> eyre::Result<()> {
+        let mut next = self.value().fork();
+        let existed = next.get(ptr).is_some();
+        next.set(ptr, value.clone())?;
+        let path = ptr.to_string();
+        let op = if existed {
+            PatchOp::Replace { path, value }
+        } else {
+            PatchOp::Add { path, value }
+        };
+        self.push(next, op);
+        Ok(())
+    }
+
+    /// Like `set`, but in an array inserts before the element at `ptr`.
+    pub fn insert(&mut self, ptr: &str, value: JSONValue) -> eyre::Result<()> {
+        let mut next = self.value().fork();
+        next.insert(ptr, value.clone())?;
+        let path = ptr.to_string();
+        self.push(next, PatchOp::Add { path, value });
+        Ok(())
+    }
+
+    /// Removes and returns the value at `ptr`.
+    pub fn remove(&mut self, ptr: &str) -> eyre::Result<SharedValue> {
+        let mut next = self.value().fork();
+        let Some(removed) = next.remove(ptr) else {
+            eyre::bail!(format!("No value at {:?}", ptr))
+        };
+        let path = ptr.to_string();
+        self.push(next, PatchOp::Remove { path });
+        Ok(removed)
+    }
+
+    fn push(&mut self, next: SharedValue, op: PatchOp) {
+        // a new edit drops whatever could have been redone
+        self.versions.truncate(self.current + 1);
+        self.ops.truncate(self.current);
+        self.versions.push(next);
+        self.ops.push(op);
+        self.current += 1;
+    }
+
+    pub fn can_undo(&self) -> bool {
+        self.current > 0
+    }
+
+    pub fn can_redo(&self) -> bool {
+        self.current < self.ops.len()
+    }
+
+    /// Steps back one edit, returning `false` if there was none.
+    pub fn undo(&mut self) -> bool {
+        let can = self.can_undo();
+        if can {
+            self.current -= 1;
+        }
+        can
+    }
+
+    /// Reapplies the last undone edit, returning `false` if there was none.
+    pub fn redo(&mut self) -> bool {
+        let can = self.can_redo();
+        if can {
+            self.current += 1;
+        }
+        can
+    }
+
+    /// The edits from the starting document to the current one, as a JSON
+    /// Patch.
+    pub fn to_patch(&self) -> Patch {
+        Patch::new(self.ops[..self.current].to_vec())
+    }
+}
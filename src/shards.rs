@@ -0,0 +1,150 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+use eyre::WrapErr;
+
+use crate::schema::Type;
+use crate::{parse_with_options, JSONValue, ParseOptions};
+
+/// How `parse_shards` combines the documents of a dataset split across
+/// several inputs. Shards are always combined in the order given, whatever
+/// order they finish parsing in.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum MergeStrategy {
+    /// Every shard is an array; the result is their elements, in order.
+    #[default]
+    Concat,
+    /// Every shard is an object; the result has all their entries, with
+    /// later shards winning on duplicate keys.
+    Union,
+    /// Like `Union`, but objects under the same key are merged recursively
+    /// too, and arrays under the same key concatenated.
+    DeepMerge,
+    /// The result is an array holding each shard's document.
+    Collect,
+}
+
+impl MergeStrategy {
+    // Adds `next`, the input called `what` in errors, to `acc`.
+    fn merge(self, acc: &mut JSONValue, next: JSONValue, what: &str) -> eyre::Result<()> {
+        match (self, acc, next) {
+            (MergeStrategy::Collect, JSONValue::Array(acc), next) => acc.push(next),
+            (MergeStrategy::Concat, JSONValue::Array(acc), JSONValue::Array(next)) => {
+                acc.extend(next)
+            }
+            (MergeStrategy::Union, JSONValue::Dict(acc), JSONValue::Dict(next)) => acc.extend(next),
+            (MergeStrategy::DeepMerge, JSONValue::Dict(acc), JSONValue::Dict(next)) => {
+                deep_merge(acc, next)
+            }
+            (_, _, next) => {
+                let wanted = if self == MergeStrategy::Concat {
+                    "array"
+                } else {
+                    "object"
+                };
+                eyre::bail!(format!(
+                    "Expected {} to be an {}, found {}",
+                    what,
+                    wanted,
+                    Type::of(&next).name()
+                ))
+            }
+        }
+        Ok(())
+    }
+
+    fn empty(self) -> JSONValue {
+        match self {
+            MergeStrategy::Concat | MergeStrategy::Collect => JSONValue::Array(Vec::new()),
+            MergeStrategy::Union | MergeStrategy::DeepMerge => JSONValue::Dict(HashMap::new()),
+        }
+    }
+}
+
+fn deep_merge(acc: &mut HashMap<String, JSONValue>, next: HashMap<String, JSONValue>) {
+    for (k, v) in next {
+        match (acc.get_mut(&k), v) {
+            (Some(JSONValue::Dict(a)), JSONValue::Dict(b)) => deep_merge(a, b),
+            (Some(JSONValue::Array(a)), JSONValue::Array(b)) => a.extend(b),
+            (_, v) => {
+                acc.insert(k, v);
+            }
+        }
+    }
+}
+
+/// Combines documents already parsed with `strategy`, in the order given:
+/// into one array of them, or the union or deep merge of objects. Errors
+/// name the document, counting from 0.
+pub fn join(
+    docs: impl IntoIterator<Item = JSONValue>,
+    strategy: MergeStrategy,
+) -> eyre::Result<JSONValue> {
+    let mut acc = strategy.empty();
+    for (i, doc) in docs.into_iter().enumerate() {
+        strategy.merge(&mut acc, doc, &format!("document {}", i))?;
+    }
+    Ok(acc)
+}
+
+/// Parses `shards` of one logical dataset (e.g. `part-0001.json` to
+/// `part-0042.json`, already read) on all available cores and merges the
+/// results with `strategy`. Errors name the shard, counting from 0.
+pub fn parse_shards<T: AsRef<[u8]> + Sync>(
+    shards: &[T],
+    strategy: MergeStrategy,
+    options: &ParseOptions,
+) -> eyre::Result<JSONValue> {
+    in_parallel(shards.len(), strategy, |i| {
+        parse_with_options(shards[i].as_ref(), options)
+    })
+}
+
+/// Like `parse_shards`, reading each file on the worker that parses it.
+pub fn parse_shard_files<P: AsRef<Path> + Sync>(
+    paths: &[P],
+    strategy: MergeStrategy,
+    options: &ParseOptions,
+) -> eyre::Result<JSONValue> {
+    in_parallel(paths.len(), strategy, |i| {
+        let path = paths[i].as_ref();
+        let buf = std::fs::read(path).wrap_err_with(|| format!("Reading {}", path.display()))?;
+        parse_with_options(&buf, options).wrap_err_with(|| format!("In {}", path.display()))
+    })
+}
+
+fn in_parallel(
+    n: usize,
+    strategy: MergeStrategy,
+    parse: impl Fn(usize) -> eyre::Result<JSONValue> + Sync,
+) -> eyre::Result<JSONValue> {
+    let workers = thread::available_parallelism()
+        .map_or(1, |n| n.get())
+        .min(n);
+    let next = AtomicUsize::new(0);
+    let parsed: Mutex<Vec<Option<eyre::Result<JSONValue>>>> =
+        Mutex::new((0..n).map(|_| None).collect());
+    thread::scope(|s| {
+        for _ in 0..workers {
+            s.spawn(|| loop {
+                let i = next.fetch_add(1, Ordering::Relaxed);
+                if i >= n {
+                    break;
+                }
+                let res = parse(i);
+                parsed.lock().unwrap()[i] = Some(res);
+            });
+        }
+    });
+    let mut acc = strategy.empty();
+    for (i, res) in parsed.into_inner().unwrap().into_iter().enumerate() {
+        let v = res
+            .expect("every shard is parsed")
+            .wrap_err_with(|| format!("Shard {}", i))?;
+        strategy.merge(&mut acc, v, &format!("shard {}", i))?;
+    }
+    Ok(acc)
+}
@@ -0,0 +1,175 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::{pointer, JSONValue};
+
+/// A `JSONValue` whose strings, arrays and objects are reference counted, so
+/// `fork` is O(1) and a fork shares every subtree it hasn't changed with the
+/// value it came from. Writes copy only the path down to what they change,
+/// so many variants of a large base document can be kept cheaply.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SharedValue {
+    Null,
+    Bool(bool),
+    Num(f64),
+    Str(Arc<str>),
+    Array(Arc<Vec<SharedValue>>),
+    Dict(Arc<HashMap<String, SharedValue>>),
+}
+
+impl From<JSONValue> for SharedValue {
+    fn from(v: JSONValue) -> Self {
+        match v {
+            JSONValue::Null => SharedValue::Null,
+            JSONValue::Bool(b) => SharedValue::Bool(b),
+            JSONValue::Num(n) => SharedValue::Num(n),
+            JSONValue::Str(s) => SharedValue::Str(s.into()),
+            JSONValue::Array(entries) => {
+                SharedValue::Array(Arc::new(entries.into_iter().map(Self::from).collect()))
+            }
+            JSONValue::Dict(d) => SharedValue::Dict(Arc::new(
+                d.into_iter().map(|(k, v)| (k, Self::from(v))).collect(),
+            )),
+        }
+    }
+}
+
+impl From<&SharedValue> for JSONValue {
+    fn from(v: &SharedValue) -> Self {
+        match v {
+            SharedValue::Null => JSONValue::Null,
+            SharedValue::Bool(b) => JSONValue::Bool(*b),
+            SharedValue::Num(n) => JSONValue::Num(*n),
+            SharedValue::Str(s) => JSONValue::Str(s.to_string()),
+            SharedValue::Array(entries) => {
+                JSONValue::Array(entries.iter().map(JSONValue::from).collect())
+            }
+            SharedValue::Dict(d) => JSONValue::Dict(
+                d.iter()
+                    .map(|(k, v)| (k.clone(), JSONValue::from(v)))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+impl SharedValue {
+    /// A logical copy that can be changed without affecting `self`.
+    pub fn fork(&self) -> Self {
+        self.clone()
+    }
+
+    /// A plain, unshared copy.
+    pub fn to_value(&self) -> JSONValue {
+        JSONValue::from(self)
+    }
+
+    /// Whether `self` and `other` are the same allocation, i.e. an unchanged
+    /// subtree shared between forks. Always `false` for null, booleans and
+    /// numbers, which aren't reference counted.
+    pub fn shares(&self, other: &Self) -> bool {
+        match (self, other) {
+            (SharedValue::Str(a), SharedValue::Str(b)) => Arc::ptr_eq(a, b),
+            (SharedValue::Array(a), SharedValue::Array(b)) => Arc::ptr_eq(a, b),
+            (SharedValue::Dict(a), SharedValue::Dict(b)) => Arc::ptr_eq(a, b),
+            _ => false,
+        }
+    }
+
+    /// The value at the JSON Pointer `ptr`, e.g. `/items/0`.
+    pub fn get(&self, ptr: &str) -> Option<&SharedValue> {
+        let mut v = self;
+        for token in pointer::tokens(ptr).ok()? {
+            v = match v {
+                SharedValue::Dict(d) => d.get(&token)?,
+                SharedValue::Array(a) => a.get(pointer::index(&token, a.len())?)?,
+                _ => return None,
+            };
+        }
+        Some(v)
+    }
+
+    /// The value at `ptr`, copying the arrays and objects on the way to it
+    /// that are shared with a fork.
+    pub fn get_mut(&mut self, ptr: &str) -> Option<&mut SharedValue> {
+        // checked first so a miss copies nothing
+        self.get(ptr)?;
+        let mut v = self;
+        for token in pointer::tokens(ptr).ok()? {
+            v = match v {
+                SharedValue::Dict(d) => Arc::make_mut(d).get_mut(&token)?,
+                SharedValue::Array(a) => {
+                    let i = pointer::index(&token, a.len())?;
+                    Arc::make_mut(a).get_mut(i)?
+                }
+                _ => return None,
+            };
+        }
+        Some(v)
+    }
+
+    /// Puts `value` at `ptr`, replacing what was there. The parent must
+    /// exist; in an array, `-` or the length appends.
+    pub fn set(&mut self, ptr: &str, value: impl Into<SharedValue>) -> eyre::Result<()> {
+        let (parent, last) = pointer::split(ptr)?;
+        let Some(v) = self.get_mut(&parent) else {
+            eyre::bail!(format!("No value at {:?}", parent))
+        };
+        let Some(last) = last else {
+            *v = value.into();
+            return Ok(());
+        };
+        match v {
+            SharedValue::Dict(d) => {
+                Arc::make_mut(d).insert(last, value.into());
+            }
+            SharedValue::Array(a) => match pointer::index(&last, a.len()) {
+                Some(i) if i < a.len() => Arc::make_mut(a)[i] = value.into(),
+                Some(i) if i == a.len() => Arc::make_mut(a).push(value.into()),
+                _ => eyre::bail!(format!("Index {:?} out of range at {:?}", last, parent)),
+            },
+            _ => eyre::bail!(format!("{:?} is not an array or object", parent)),
+        }
+        Ok(())
+    }
+
+    /// Puts `value` at `ptr` like `set`, except that in an array it goes in
+    /// before the element at that index instead of replacing it.
+    pub fn insert(&mut self, ptr: &str, value: impl Into<SharedValue>) -> eyre::Result<()> {
+        let (parent, last) = pointer::split(ptr)?;
+        let Some(last) = last else {
+            *self = value.into();
+            return Ok(());
+        };
+        let Some(v) = self.get_mut(&parent) else {
+            eyre::bail!(format!("No value at {:?}", parent))
+        };
+        match v {
+            SharedValue::Dict(d) => {
+                Arc::make_mut(d).insert(last, value.into());
+            }
+            SharedValue::Array(a) => match pointer::index(&last, a.len()) {
+                Some(i) if i <= a.len() => Arc::make_mut(a).insert(i, value.into()),
+                _ => eyre::bail!(format!("Index {:?} out of range at {:?}", last, parent)),
+            },
+            _ => eyre::bail!(format!("{:?} is not an array or object", parent)),
+        }
+        Ok(())
+    }
+
+    /// Takes out the value at `ptr`, shifting later array elements down.
+    pub fn remove(&mut self, ptr: &str) -> Option<SharedValue> {
+        let (parent, last) = pointer::split(ptr).ok()?;
+        let last = last?;
+        // checked first so a miss copies nothing
+        self.get(ptr)?;
+        match self.get_mut(&parent)? {
+            SharedValue::Dict(d) => Arc::make_mut(d).remove(&last),
+            SharedValue::Array(a) => {
+                let i = pointer::index(&last, a.len())?;
+                Some(Arc::make_mut(a).remove(i))
+            }
+            _ => None,
+        }
+    }
+}
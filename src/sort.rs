@@ -0,0 +1,310 @@
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
+
+use crate::{parse, patch, pointer, JSONValue, ProgressReport};
+
+/// Options for `sort_lines`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SortOptions {
+    /// JSON Pointer to the value records are ordered by; `""` for the whole
+    /// record.
+    pub key: String,
+    /// Largest first.
+    pub descending: bool,
+    /// Bytes of lines held before a sorted run is spilled to a file.
+    pub memory: usize,
+    /// Where spill files go; the system's temporary directory if `None`.
+    pub temp_dir: Option<PathBuf>,
+}
+
+impl Default for SortOptions {
+    fn default() -> Self {
+        Self {
+            key: String::new(),
+            descending: false,
+            memory: 64 * 1024 * 1024,
+            temp_dir: None,
+        }
+    }
+}
+
+/// Sorts the NDJSON read from `reader` by the value at `options.key` into
+/// `writer`, copying each line as it was. Values order as in jq: missing
+/// keys and `null`, then `false`, `true`, numbers, strings, arrays and
+/// objects; records with equal keys keep their order. Input bigger than
+/// `options.memory` is sorted in runs spilled to temporary files and merged,
+/// so memory stays bounded. Returns the number of records; blank lines are
+/// dropped.
+pub fn sort_lines<R: BufRead, W: Write>(
+    reader: R,
+    writer: W,
+    options: &SortOptions,
+) -> eyre::Result<usize> {
+    sort(reader, writer, options, None)
+}
+
+/// Like `sort_lines`, reporting the bytes and records read to `report`.
+/// Merging spilled runs comes after, and is reported only as it ends.
+pub fn sort_lines_with_progress<R: BufRead, W: Write>(
+    reader: R,
+    writer: W,
+    options: &SortOptions,
+    mut report: ProgressReport<'_>,
+) -> eyre::Result<usize> {
+    let records = sort(reader, writer, options, Some(&mut report))?;
+    report.finish();
+    Ok(records)
+}
+
+fn sort<R: BufRead, W: Write>(
+    mut reader: R,
+    writer: W,
+    options: &SortOptions,
+    mut report: Option<&mut ProgressReport<'_>>,
+) -> eyre::Result<usize> {
+    pointer::tokens(&options.key)?;
+    let sorter = Sorter { options };
+    let mut writer = BufWriter::new(writer);
+    let mut runs = Runs::default();
+    let mut batch = Vec::new();
+    let mut batch_bytes = 0;
+    let mut records = 0;
+    let mut line_no = 0;
+    let mut read = 0;
+    loop {
+        let mut line = String::new();
+        let n = reader
+            .read_line(&mut line)
+            .map_err(|e| eyre::eyre!(format!("Can't read input: {}", e)))?;
+        if n == 0 {
+            break;
+        }
+        line_no += 1;
+        read += n as u64;
+        let line = line.trim_end_matches(['\n', '\r']);
+        if line.trim().is_empty() {
+            continue;
+        }
+        let key = sorter
+            .key(line)
+            .map_err(|e| eyre::eyre!(format!("Invalid record on line {}: {}", line_no, e)))?;
+        batch_bytes += line.len();
+        batch.push((key, line.to_string()));
+        records += 1;
+        if let Some(report) = report.as_deref_mut() {
+            report.advance(read, 1);
+        }
+        if batch_bytes >= options.memory {
+            runs.spill(&sorter, &mut batch)?;
+            batch_bytes = 0;
+        }
+    }
+    if runs.paths.is_empty() {
+        sorter.sort(&mut batch);
+        for (_, line) in &batch {
+            writeln!(writer, "{}", line).map_err(write_error)?;
+        }
+    } else {
+        if !batch.is_empty() {
+            runs.spill(&sorter, &mut batch)?;
+        }
+        sorter.merge(&runs, &mut writer)?;
+    }
+    writer.flush().map_err(write_error)?;
+    Ok(records)
+}
+
+fn write_error(e: std::io::Error) -> eyre::Report {
+    eyre::eyre!(format!("Can't write output: {}", e))
+}
+
+struct Sorter<'o> {
+    options: &'o SortOptions,
+}
+
+impl Sorter<'_> {
+    // The sort key of a record's line; `None` if it has no value there.
+    fn key(&self, line: &str) -> eyre::Result<Option<JSONValue>> {
+        let mut record = parse(line.as_bytes())?;
+        if self.options.key.is_empty() {
+            return Ok(Some(record));
+        }
+        Ok(patch::get_mut(&mut record, &self.options.key)
+            .ok()
+            .map(|v| std::mem::replace(v, JSONValue::Null)))
+    }
+
+    fn cmp(&self, a: &Option<JSONValue>, b: &Option<JSONValue>) -> Ordering {
+        let order = compare(a.as_ref(), b.as_ref());
+        match self.options.descending {
+            true => order.reverse(),
+            false => order,
+        }
+    }
+
+    fn sort(&self, batch: &mut [(Option<JSONValue>, String)]) {
+        batch.sort_by(|a, b| self.cmp(&a.0, &b.0));
+    }
+
+    // Merges the sorted runs into `writer`, taking the earliest run first
+    // among equal keys so the sort stays stable.
+    fn merge(&self, runs: &Runs, writer: &mut impl Write) -> eyre::Result<()> {
+        let mut readers = Vec::new();
+        let mut heap = BinaryHeap::new();
+        for (run, path) in runs.paths.iter().enumerate() {
+            let file = File::open(path)
+                .map_err(|e| eyre::eyre!(format!("Can't read {}: {}", path.display(), e)))?;
+            readers.push(BufReader::new(file).lines());
+            if let Some(head) = self.next_head(&mut readers[run], run)? {
+                heap.push(head);
+            }
+        }
+        while let Some(Head { line, run, .. }) = heap.pop() {
+            writeln!(writer, "{}", line).map_err(write_error)?;
+            if let Some(head) = self.next_head(&mut readers[run], run)? {
+                heap.push(head);
+            }
+        }
+        Ok(())
+    }
+
+    fn next_head<'s>(
+        &'s self,
+        lines: &mut std::io::Lines<BufReader<File>>,
+        run: usize,
+    ) -> eyre::Result<Option<Head<'s>>> {
+        let Some(line) = lines.next() else {
+            return Ok(None);
+        };
+        let line = line.map_err(|e| eyre::eyre!(format!("Can't read a sorted run: {}", e)))?;
+        Ok(Some(Head {
+            key: self.key(&line)?,
+            line,
+            run,
+            sorter: self,
+        }))
+    }
+}
+
+// The next line of a run, ordered so `BinaryHeap` pops the smallest.
+struct Head<'s> {
+    key: Option<JSONValue>,
+    line: String,
+    run: usize,
+    sorter: &'s Sorter<'s>,
+}
+
+impl Ord for Head<'_> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.sorter
+            .cmp(&self.key, &other.key)
+            .then(self.run.cmp(&other.run))
+            .reverse()
+    }
+}
+
+impl PartialOrd for Head<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Head<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Head<'_> {}
+
+// Spill files, removed when dropped.
+#[derive(Default)]
+struct Runs {
+    paths: Vec<PathBuf>,
+}
+
+static RUNS: AtomicUsize = AtomicUsize::new(0);
+
+impl Runs {
+    // Sorts `batch` and writes it to a new spill file, emptying it.
+    fn spill(
+        &mut self,
+        sorter: &Sorter,
+        batch: &mut Vec<(Option<JSONValue>, String)>,
+    ) -> eyre::Result<()> {
+        sorter.sort(batch);
+        let dir = sorter
+            .options
+            .temp_dir
+            .clone()
+            .unwrap_or_else(std::env::temp_dir);
+        let path = dir.join(format!(
+            "json_parser-sort-{}-{}",
+            std::process::id(),
+            RUNS.fetch_add(1, AtomicOrdering::Relaxed)
+        ));
+        let file = File::create(&path)
+            .map_err(|e| eyre::eyre!(format!("Can't create {}: {}", path.display(), e)))?;
+        self.paths.push(path.clone());
+        let mut out = BufWriter::new(file);
+        for (_, line) in batch.drain(..) {
+            writeln!(out, "{}", line)
+                .map_err(|e| eyre::eyre!(format!("Can't write {}: {}", path.display(), e)))?;
+        }
+        out.flush()
+            .map_err(|e| eyre::eyre!(format!("Can't write {}: {}", path.display(), e)))
+    }
+}
+
+impl Drop for Runs {
+    fn drop(&mut self) {
+        for path in &self.paths {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+// jq's order: missing and null, false, true, numbers, strings, arrays
+// element by element, then objects by their sorted keys and then values.
+fn compare(a: Option<&JSONValue>, b: Option<&JSONValue>) -> Ordering {
+    fn rank(v: Option<&JSONValue>) -> u8 {
+        match v {
+            None | Some(JSONValue::Null) => 0,
+            Some(JSONValue::Bool(false)) => 1,
+            Some(JSONValue::Bool(true)) => 2,
+            Some(JSONValue::Num(_)) => 3,
+            Some(JSONValue::Str(_)) => 4,
+            Some(JSONValue::Array(_)) => 5,
+            Some(JSONValue::Dict(_)) => 6,
+        }
+    }
+    match (a, b) {
+        (Some(JSONValue::Num(a)), Some(JSONValue::Num(b))) => a.total_cmp(b),
+        (Some(JSONValue::Str(a)), Some(JSONValue::Str(b))) => a.cmp(b),
+        (Some(JSONValue::Array(a)), Some(JSONValue::Array(b))) => a
+            .iter()
+            .zip(b)
+            .map(|(a, b)| compare(Some(a), Some(b)))
+            .find(|o| o.is_ne())
+            .unwrap_or(a.len().cmp(&b.len())),
+        (Some(JSONValue::Dict(a)), Some(JSONValue::Dict(b))) => {
+            let mut a: Vec<_> = a.iter().collect();
+            let mut b: Vec<_> = b.iter().collect();
+            a.sort_by(|x, y| x.0.cmp(y.0));
+            b.sort_by(|x, y| x.0.cmp(y.0));
+            let keys = a.iter().map(|e| e.0).cmp(b.iter().map(|e| e.0));
+            keys.then_with(|| {
+                a.iter()
+                    .zip(&b)
+                    .map(|(x, y)| compare(Some(x.1), Some(y.1)))
+                    .find(|o| o.is_ne())
+                    .unwrap_or(Ordering::Equal)
+            })
+        }
+        _ => rank(a).cmp(&rank(b)),
+    }
+}
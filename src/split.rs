@@ -0,0 +1,96 @@
+use std::io::{ErrorKind, Read};
+
+use crate::{pointer, Document, Node, NodeKind, ProgressReport};
+
+/// Breaks the array at `pointer` in the document read from `reader` into
+/// chunks of up to `chunk_size` elements. `sink` gets each chunk's number and
+/// a copy of the whole document with only that chunk's elements in the
+/// array, so every piece keeps the surrounding envelope; everything else is
+/// copied byte for byte. Returns the number of chunks, none for an empty
+/// array. The input is held in memory, but only as spans, not values.
+pub fn split<R: Read>(
+    reader: R,
+    pointer: &str,
+    chunk_size: usize,
+    sink: impl FnMut(usize, &[u8]) -> eyre::Result<()>,
+) -> eyre::Result<usize> {
+    split_chunks(reader, pointer, chunk_size, sink, None)
+}
+
+/// Like `split`, reporting the bytes read while reading the input, then the
+/// elements written as each chunk is handed over, to `report`.
+pub fn split_with_progress<R: Read>(
+    reader: R,
+    pointer: &str,
+    chunk_size: usize,
+    sink: impl FnMut(usize, &[u8]) -> eyre::Result<()>,
+    mut report: ProgressReport<'_>,
+) -> eyre::Result<usize> {
+    let chunks = split_chunks(reader, pointer, chunk_size, sink, Some(&mut report))?;
+    report.finish();
+    Ok(chunks)
+}
+
+fn split_chunks<R: Read>(
+    mut reader: R,
+    pointer: &str,
+    chunk_size: usize,
+    mut sink: impl FnMut(usize, &[u8]) -> eyre::Result<()>,
+    mut report: Option<&mut ProgressReport<'_>>,
+) -> eyre::Result<usize> {
+    if chunk_size == 0 {
+        eyre::bail!("Chunks must hold at least one element")
+    }
+    let mut buf = Vec::new();
+    let mut block = vec![0; 64 * 1024];
+    loop {
+        match reader.read(&mut block) {
+            Ok(0) => break,
+            Ok(n) => buf.extend_from_slice(&block[..n]),
+            Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+            Err(e) => eyre::bail!(format!("Can't read input: {}", e)),
+        }
+        if let Some(report) = report.as_deref_mut() {
+            report.advance(buf.len() as u64, 0);
+        }
+    }
+    let doc = Document::parse(buf)?;
+    let array = node_at(doc.root(), pointer)?;
+    if array.kind() != NodeKind::Array {
+        eyre::bail!(format!("{:?} is not an array", pointer))
+    }
+    let spans: Vec<_> = array.elements().map(|e| e.span()).collect();
+    let (Some(first), Some(last)) = (spans.first(), spans.last()) else {
+        return Ok(0);
+    };
+    let bytes = doc.as_bytes();
+    let (prefix, suffix) = (&bytes[..first.start], &bytes[last.end..]);
+    let mut out = Vec::new();
+    let mut chunks = 0;
+    for (i, chunk) in spans.chunks(chunk_size).enumerate() {
+        let (start, end) = (chunk[0].start, chunk[chunk.len() - 1].end);
+        out.clear();
+        out.extend_from_slice(prefix);
+        out.extend_from_slice(&bytes[start..end]);
+        out.extend_from_slice(suffix);
+        sink(i, &out)?;
+        chunks += 1;
+        if let Some(report) = report.as_deref_mut() {
+            report.advance(bytes.len() as u64, chunk.len() as u64);
+        }
+    }
+    Ok(chunks)
+}
+
+fn node_at<'d>(root: Node<'d>, ptr: &str) -> eyre::Result<Node<'d>> {
+    let mut node = root;
+    for token in pointer::tokens(ptr)? {
+        let child = match node.kind() {
+            NodeKind::Object => node.get(&token),
+            NodeKind::Array => pointer::index(&token, node.len()).and_then(|i| node.index(i)),
+            _ => None,
+        };
+        node = child.ok_or_else(|| eyre::eyre!(format!("No value at {:?}", ptr)))?;
+    }
+    Ok(node)
+}
@@ -0,0 +1,83 @@
+use crate::lexer::{Token, TokenKind};
+use crate::prefix::TreeBuilder;
+use crate::syntax::Expect;
+use crate::{JSONValue, ParseOptions};
+
+/// What feeding a token to `Parser::step` did.
+#[derive(Debug, Clone, PartialEq)]
+pub enum StepResult {
+    /// A `,`, `:` or comment was accepted; nothing was built.
+    Continue,
+    /// `{` or `[` opened a container.
+    Open,
+    /// An object key was read; its value comes next.
+    Key,
+    /// A value inside a container was completed: a scalar, or a container
+    /// that was just closed.
+    Value,
+    /// The top-level value is complete. Any further token is an error.
+    Done(JSONValue),
+}
+
+/// The JSON grammar as a state machine, fed one token at a time by the
+/// caller. Tokens can come from `lexer` or any other tokenizer that produces
+/// `Token`s, so the parser can be embedded in custom runtimes. It keeps its
+/// own stack rather than recursing, and fails on containers nested deeper
+/// than `DEFAULT_MAX_DEPTH` (or `ParseOptions::max_depth`) so the value it
+/// builds can still be dropped.
+#[derive(Clone)]
+pub struct Parser {
+    tree: TreeBuilder,
+}
+
+impl Default for Parser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Parser {
+    pub fn new() -> Self {
+        Self::with_options(&ParseOptions::default())
+    }
+
+    /// A parser nesting at most `options.max_depth` deep. The other options
+    /// concern lexing, which is up to the caller.
+    pub fn with_options(options: &ParseOptions) -> Self {
+        Self {
+            tree: TreeBuilder::with_options(options),
+        }
+    }
+
+    /// Feeds the next token. `buf` is the input that the ranges of
+    /// `Token::StringVal` point into. On error the parser is left as it was
+    /// before the token.
+    pub fn step(&mut self, token: Token, buf: &[u8]) -> eyre::Result<StepResult> {
+        let key = matches!(
+            self.tree.syntax().expect,
+            Expect::Key | Expect::KeyOrEndObject
+        );
+        let done = self.tree.push(token, buf)?;
+        Ok(match (done, token) {
+            (Some(value), _) => StepResult::Done(value),
+            (None, Token::Comma | Token::Colon | Token::Comment) => StepResult::Continue,
+            (None, Token::LeftBrace | Token::LeftBracket) => StepResult::Open,
+            (None, Token::StringVal(..)) if key => StepResult::Key,
+            (None, _) => StepResult::Value,
+        })
+    }
+
+    /// Kinds of token that `step` would accept next.
+    pub fn expected(&self) -> Vec<TokenKind> {
+        self.tree.syntax().expected()
+    }
+
+    /// Number of containers still open.
+    pub fn depth(&self) -> usize {
+        self.tree.syntax().stack.len()
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.tree.syntax().is_complete()
+    }
+}
@@ -0,0 +1,275 @@
+use crate::escape;
+use crate::lexer::{Lexer, Token, TokenKind};
+
+const VALUE_KINDS: [TokenKind; 6] = [
+    TokenKind::LeftBrace,
+    TokenKind::LeftBracket,
+    TokenKind::String,
+    TokenKind::Number,
+    TokenKind::Bool,
+    TokenKind::Null,
+];
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) enum Expect {
+    Value,
+    ValueOrEndArray,
+    CommaOrEndArray,
+    KeyOrEndObject,
+    Key,
+    Colon,
+    CommaOrEndObject,
+    End,
+}
+
+impl Expect {
+    const ALL: [Expect; 8] = [
+        Expect::Value,
+        Expect::ValueOrEndArray,
+        Expect::CommaOrEndArray,
+        Expect::KeyOrEndObject,
+        Expect::Key,
+        Expect::Colon,
+        Expect::CommaOrEndObject,
+        Expect::End,
+    ];
+
+    /// e.g. "CommaOrEndArray", for saving parser state.
+    pub(crate) fn name(self) -> String {
+        format!("{:?}", self)
+    }
+
+    pub(crate) fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|e| e.name() == name)
+    }
+
+    // Whether this can be expected with `top` as the innermost open
+    // container.
+    pub(crate) fn fits(self, top: Option<Container>) -> bool {
+        match top {
+            None => matches!(self, Expect::Value | Expect::End),
+            Some(Container::Array) => matches!(
+                self,
+                Expect::Value | Expect::ValueOrEndArray | Expect::CommaOrEndArray
+            ),
+            Some(Container::Object) => matches!(
+                self,
+                Expect::Value
+                    | Expect::KeyOrEndObject
+                    | Expect::Key
+                    | Expect::Colon
+                    | Expect::CommaOrEndObject
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) enum Container {
+    Object,
+    Array,
+}
+
+/// Tracks where in the JSON grammar a token stream is, one token at a time.
+#[derive(Debug, Clone)]
+pub(crate) struct SyntaxState {
+    pub(crate) stack: Vec<Container>,
+    pub(crate) expect: Expect,
+}
+
+impl SyntaxState {
+    pub(crate) fn new() -> Self {
+        Self {
+            stack: Vec::new(),
+            expect: Expect::Value,
+        }
+    }
+
+    pub(crate) fn expected(&self) -> Vec<TokenKind> {
+        let mut kinds = Vec::new();
+        match self.expect {
+            Expect::Value => kinds.extend(VALUE_KINDS),
+            Expect::ValueOrEndArray => {
+                kinds.extend(VALUE_KINDS);
+                kinds.push(TokenKind::RightBracket);
+            }
+            Expect::CommaOrEndArray => kinds.extend([TokenKind::Comma, TokenKind::RightBracket]),
+            Expect::KeyOrEndObject => kinds.extend([TokenKind::String, TokenKind::RightBrace]),
+            Expect::Key => kinds.push(TokenKind::String),
+            Expect::Colon => kinds.push(TokenKind::Colon),
+            Expect::CommaOrEndObject => kinds.extend([TokenKind::Comma, TokenKind::RightBrace]),
+            Expect::End => {}
+        }
+        kinds
+    }
+
+    /// e.g. "expected value or `]`"
+    pub(crate) fn describe(&self) -> String {
+        let what = match self.expect {
+            Expect::Value => "value",
+            Expect::ValueOrEndArray => "value or `]`",
+            Expect::CommaOrEndArray => "`,` or `]`",
+            Expect::KeyOrEndObject => "key or `}`",
+            Expect::Key => "key",
+            Expect::Colon => "`:`",
+            Expect::CommaOrEndObject => "`,` or `}`",
+            Expect::End => "end of input",
+        };
+        format!("expected {}", what)
+    }
+
+    pub(crate) fn is_complete(&self) -> bool {
+        self.expect == Expect::End
+    }
+
+    fn after_value(&mut self) {
+        self.expect = match self.stack.last() {
+            None => Expect::End,
+            Some(Container::Array) => Expect::CommaOrEndArray,
+            Some(Container::Object) => Expect::CommaOrEndObject,
+        };
+    }
+
+    /// Advances past `token`, failing if it is not allowed here. Comments are
+    /// ignored.
+    pub(crate) fn feed(&mut self, token: Token) -> eyre::Result<()> {
+        let kind = token.kind();
+        if kind == TokenKind::Comment {
+            return Ok(());
+        }
+        match (self.expect, kind) {
+            (Expect::Value | Expect::ValueOrEndArray, TokenKind::LeftBrace) => {
+                self.stack.push(Container::Object);
+                self.expect = Expect::KeyOrEndObject;
+            }
+            (Expect::Value | Expect::ValueOrEndArray, TokenKind::LeftBracket) => {
+                self.stack.push(Container::Array);
+                self.expect = Expect::ValueOrEndArray;
+            }
+            (Expect::Value | Expect::ValueOrEndArray, k) if VALUE_KINDS.contains(&k) => {
+                self.after_value();
+            }
+            (Expect::ValueOrEndArray | Expect::CommaOrEndArray, TokenKind::RightBracket)
+            | (Expect::KeyOrEndObject | Expect::CommaOrEndObject, TokenKind::RightBrace) => {
+                self.stack.pop();
+                self.after_value();
+            }
+            (Expect::KeyOrEndObject | Expect::Key, TokenKind::String) => {
+                self.expect = Expect::Colon;
+            }
+            (Expect::Colon, TokenKind::Colon) => self.expect = Expect::Value,
+            (Expect::CommaOrEndArray, TokenKind::Comma) => self.expect = Expect::Value,
+            (Expect::CommaOrEndObject, TokenKind::Comma) => self.expect = Expect::Key,
+            _ => eyre::bail!(format!("{}, found {:?}", self.describe(), token)),
+        }
+        Ok(())
+    }
+}
+
+/// What may follow a document prefix, see `expected_next`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Completion {
+    /// Kinds of token that would be valid next. Empty once the document is
+    /// complete.
+    pub expected: Vec<TokenKind>,
+    /// Set when the prefix stops in the middle of a token of this kind
+    /// (an unterminated string, a partial `true`/`false`/`null`, or a number
+    /// such as `1e` that can't end yet). Also set to `Number` when the prefix
+    /// ends in a number that more digits could extend, as `[1, 2` does;
+    /// `expected` then lists what may follow the number as it stands.
+    pub partial: Option<TokenKind>,
+    /// Human readable form of `expected`, e.g. "expected `,` or `]`".
+    pub description: String,
+    /// Number of containers still open.
+    pub depth: usize,
+}
+
+/// Reports which tokens could come next after `prefix`, the start of a
+/// document. Fails if the prefix itself is already invalid. Comments are
+/// accepted.
+pub fn expected_next(prefix: &[u8]) -> eyre::Result<Completion> {
+    let lexer = Lexer::new().allow_comments(true);
+    let mut state = SyntaxState::new();
+    let mut pos = 0;
+    let mut partial = None;
+    loop {
+        match lexer.next_token(prefix, &mut pos) {
+            Ok(Some(t)) => {
+                if t.token.kind() == TokenKind::Number && t.span.end == prefix.len() {
+                    partial = Some(TokenKind::Number);
+                }
+                state.feed(t.token)?
+            }
+            Ok(None) => break,
+            Err(e) => {
+                partial = Some(partial_token(&prefix[pos..]).ok_or(e)?);
+                break;
+            }
+        }
+    }
+    Ok(Completion {
+        expected: state.expected(),
+        partial,
+        description: state.describe(),
+        depth: state.stack.len(),
+    })
+}
+
+// Whether `rest`, which failed to lex, is a token cut short by the end of input.
+pub(crate) fn partial_token(rest: &[u8]) -> Option<TokenKind> {
+    if rest.first() == Some(&b'"') {
+        return matches!(escape::scan_string(rest, 0, false), Ok(None))
+            .then_some(TokenKind::String);
+    }
+    for (word, kind) in [
+        (&b"true"[..], TokenKind::Bool),
+        (b"false", TokenKind::Bool),
+        (b"null", TokenKind::Null),
+    ] {
+        if word.starts_with(rest) {
+            return Some(kind);
+        }
+    }
+    is_number_prefix(rest).then_some(TokenKind::Number)
+}
+
+// Whether `s` can be extended into a number, e.g. `-`, `1.` or `2e+`.
+fn is_number_prefix(s: &[u8]) -> bool {
+    let mut i = usize::from(s.first() == Some(&b'-'));
+    let digits = |i: &mut usize| {
+        let start = *i;
+        while s.get(*i).is_some_and(u8::is_ascii_digit) {
+            *i += 1;
+        }
+        *i - start
+    };
+    let int = digits(&mut i);
+    // no digit can follow a leading zero
+    if int > 1 && s[usize::from(s[0] == b'-')] == b'0' {
+        return false;
+    }
+    if i == s.len() {
+        return true;
+    }
+    if int == 0 {
+        return false;
+    }
+    if s[i] == b'.' {
+        i += 1;
+        if digits(&mut i) == 0 {
+            return i == s.len();
+        }
+    }
+    if i == s.len() {
+        return true;
+    }
+    if !matches!(s[i], b'e' | b'E') {
+        return false;
+    }
+    i += 1;
+    if matches!(s.get(i), Some(b'+' | b'-')) {
+        i += 1;
+    }
+    digits(&mut i);
+    i == s.len()
+}
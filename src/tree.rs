@@ -0,0 +1,92 @@
+use core::fmt::Write;
+
+use crate::JSONValue;
+
+// Characters of a string shown after its size.
+const PREVIEW_CHARS: usize = 40;
+
+/// e.g. `512B`, `5.2KB`
+fn human_size(bytes: usize) -> String {
+    const UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
+    if bytes < 1024 {
+        return format!("{}B", bytes);
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit + 1 < UNITS.len() {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1}{}", size, UNITS[unit])
+}
+
+// The annotation shown for `v`, e.g. `object(3)` or `string(5B) "hello"`.
+fn describe(v: &JSONValue) -> String {
+    match v {
+        JSONValue::Null => "null".to_string(),
+        JSONValue::Bool(b) => format!("bool {}", b),
+        JSONValue::Num(n) => format!("number {}", JSONValue::Num(*n)),
+        JSONValue::Str(s) => {
+            let mut preview: String = s.chars().take(PREVIEW_CHARS).collect();
+            if preview.len() < s.len() {
+                preview.push('…');
+            }
+            format!("string({}) {:?}", human_size(s.len()), preview)
+        }
+        JSONValue::Array(a) => format!("array({})", a.len()),
+        JSONValue::Dict(d) => format!("object({})", d.len()),
+    }
+}
+
+impl JSONValue {
+    /// An indented tree with each value's type and size, for eyeballing
+    /// large documents:
+    ///
+    /// ```text
+    /// object(2)
+    /// ├── "ids": array(1000)
+    /// │   ├── [0]: number 1
+    /// ...
+    /// └── "name": string(5B) "hello"
+    /// ```
+    ///
+    /// Object entries are in key order.
+    pub fn to_tree_string(&self) -> String {
+        let mut out = describe(self);
+        out.push('\n');
+        children(self, &mut String::new(), &mut out);
+        out
+    }
+}
+
+fn children(v: &JSONValue, indent: &mut String, out: &mut String) {
+    let entries: Vec<(String, &JSONValue)> = match v {
+        JSONValue::Array(a) => a
+            .iter()
+            .enumerate()
+            .map(|(i, e)| (format!("[{}]", i), e))
+            .collect(),
+        JSONValue::Dict(d) => {
+            let mut entries: Vec<_> = d.iter().collect();
+            entries.sort_unstable_by_key(|(k, _)| *k);
+            entries
+                .into_iter()
+                .map(|(k, e)| (format!("{:?}", k), e))
+                .collect()
+        }
+        _ => return,
+    };
+    let last = entries.len().saturating_sub(1);
+    for (i, (label, e)) in entries.into_iter().enumerate() {
+        let (branch, extend) = if i == last {
+            ("└── ", "    ")
+        } else {
+            ("├── ", "│   ")
+        };
+        writeln!(out, "{}{}{}: {}", indent, branch, label, describe(e)).unwrap();
+        let len = indent.len();
+        indent.push_str(extend);
+        children(e, indent, out);
+        indent.truncate(len);
+    }
+}
@@ -0,0 +1,86 @@
+use std::collections::HashMap;
+
+use crate::JSONValue;
+
+/// Marks where `truncate` left something out.
+pub const TRUNCATED: &str = "…truncated";
+
+/// Limits for `truncate`. `None` removes a limit.
+#[derive(Debug, Clone)]
+pub struct TruncateOptions {
+    /// Elements kept from the start of each array.
+    pub max_array_len: Option<usize>,
+    /// Entries kept from each object, in key order.
+    pub max_object_len: Option<usize>,
+    /// Characters kept from each string.
+    pub max_string_len: Option<usize>,
+    /// Arrays and objects nested deeper than this are replaced.
+    pub max_depth: Option<usize>,
+}
+
+impl Default for TruncateOptions {
+    fn default() -> Self {
+        Self {
+            max_array_len: Some(10),
+            max_object_len: Some(20),
+            max_string_len: Some(100),
+            max_depth: Some(8),
+        }
+    }
+}
+
+/// A size-bounded preview of `value` for logs and UIs. The same input always
+/// gives the same preview.
+///
+/// A cut array ends with a `"…truncated"` element, a cut object gets a
+/// `"…truncated"` entry holding how many entries were left out, a clipped
+/// string ends with `…`, and an array or object past the depth limit becomes
+/// the string `"…truncated"`.
+pub fn truncate(value: &JSONValue, options: &TruncateOptions) -> JSONValue {
+    preview(value, 0, options)
+}
+
+fn preview(v: &JSONValue, depth: usize, options: &TruncateOptions) -> JSONValue {
+    let nested = matches!(v, JSONValue::Array(_) | JSONValue::Dict(_));
+    if nested && options.max_depth.is_some_and(|max| depth >= max) {
+        return JSONValue::Str(TRUNCATED.to_string());
+    }
+    match v {
+        JSONValue::Str(s) => match options.max_string_len {
+            Some(max) if s.chars().count() > max => {
+                let mut s: String = s.chars().take(max).collect();
+                s.push('…');
+                JSONValue::Str(s)
+            }
+            _ => v.clone(),
+        },
+        JSONValue::Array(entries) => {
+            let keep = options.max_array_len.unwrap_or(entries.len());
+            let mut out: Vec<_> = entries
+                .iter()
+                .take(keep)
+                .map(|e| preview(e, depth + 1, options))
+                .collect();
+            if entries.len() > keep {
+                out.push(JSONValue::Str(TRUNCATED.to_string()));
+            }
+            JSONValue::Array(out)
+        }
+        JSONValue::Dict(d) => {
+            let mut keys: Vec<_> = d.keys().collect();
+            keys.sort_unstable();
+            let keep = options.max_object_len.unwrap_or(keys.len());
+            let mut out: HashMap<_, _> = keys
+                .iter()
+                .take(keep)
+                .map(|k| (k.to_string(), preview(&d[*k], depth + 1, options)))
+                .collect();
+            if keys.len() > keep {
+                let left_out = (keys.len() - keep) as f64;
+                out.insert(TRUNCATED.to_string(), JSONValue::Num(left_out));
+            }
+            JSONValue::Dict(out)
+        }
+        JSONValue::Null | JSONValue::Bool(_) | JSONValue::Num(_) => v.clone(),
+    }
+}
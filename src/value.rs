@@ -0,0 +1,535 @@
+use core::fmt;
+use core::hash::Hash;
+use std::collections::{BTreeMap, HashMap};
+
+use eyre::OptionExt;
+
+use crate::lexer::{Lexer, Token};
+use crate::syntax::{Expect, SyntaxState};
+use crate::{escape, normalize, number, serialize, ParseOptions};
+
+/// A JSON value with pluggable representations: strings (and keys) are `S`,
+/// numbers `N`, and objects the map type chosen by `M`. `JSONValue` is this
+/// type with the defaults.
+pub enum Value<S: MapKey = String, N = f64, M: MapKind = HashMapKind> {
+    Null,
+    Bool(bool),
+    Str(S),
+    Num(N),
+    Array(Vec<Value<S, N, M>>),
+    Dict(M::Map<S, Value<S, N, M>>),
+}
+
+/// What an object key needs to be usable with every `MapKind`.
+pub trait MapKey: Eq + Ord + Hash {}
+
+impl<T: Eq + Ord + Hash> MapKey for T {}
+
+/// Picks the map type objects are stored in, e.g. `BTreeMapKind` to keep
+/// keys ordered.
+pub trait MapKind {
+    type Map<K: MapKey, V>: MapOps<K, V>;
+}
+
+/// The operations `Value` needs from an object's map.
+pub trait MapOps<K, V>: Default + FromIterator<(K, V)> + IntoIterator<Item = (K, V)> {
+    fn get(&self, key: &K) -> Option<&V>;
+    /// Inserts an entry, returning the value it replaced.
+    fn insert(&mut self, key: K, value: V) -> Option<V>;
+    fn len(&self) -> usize;
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    fn entries(&self) -> Box<dyn Iterator<Item = (&K, &V)> + '_>;
+}
+
+/// Objects as `HashMap`s, the default.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct HashMapKind;
+
+impl MapKind for HashMapKind {
+    type Map<K: MapKey, V> = HashMap<K, V>;
+}
+
+impl<K: Eq + Hash, V> MapOps<K, V> for HashMap<K, V> {
+    fn get(&self, key: &K) -> Option<&V> {
+        HashMap::get(self, key)
+    }
+
+    fn insert(&mut self, key: K, value: V) -> Option<V> {
+        HashMap::insert(self, key, value)
+    }
+
+    fn len(&self) -> usize {
+        HashMap::len(self)
+    }
+
+    fn entries(&self) -> Box<dyn Iterator<Item = (&K, &V)> + '_> {
+        Box::new(self.iter())
+    }
+}
+
+/// Objects as `BTreeMap`s, iterating in key order.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct BTreeMapKind;
+
+impl MapKind for BTreeMapKind {
+    type Map<K: MapKey, V> = BTreeMap<K, V>;
+}
+
+impl<K: Ord, V> MapOps<K, V> for BTreeMap<K, V> {
+    fn get(&self, key: &K) -> Option<&V> {
+        BTreeMap::get(self, key)
+    }
+
+    fn insert(&mut self, key: K, value: V) -> Option<V> {
+        BTreeMap::insert(self, key, value)
+    }
+
+    fn len(&self) -> usize {
+        BTreeMap::len(self)
+    }
+
+    fn entries(&self) -> Box<dyn Iterator<Item = (&K, &V)> + '_> {
+        Box::new(self.iter())
+    }
+}
+
+impl<S: MapKey + Clone, N: Clone, M: MapKind> Clone for Value<S, N, M> {
+    fn clone(&self) -> Self {
+        match self {
+            Value::Null => Value::Null,
+            Value::Bool(b) => Value::Bool(*b),
+            Value::Str(s) => Value::Str(s.clone()),
+            Value::Num(n) => Value::Num(n.clone()),
+            Value::Array(entries) => Value::Array(entries.clone()),
+            Value::Dict(d) => {
+                Value::Dict(d.entries().map(|(k, v)| (k.clone(), v.clone())).collect())
+            }
+        }
+    }
+}
+
+impl<S: MapKey, N: PartialEq, M: MapKind> PartialEq for Value<S, N, M> {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Value::Null, Value::Null) => true,
+            (Value::Bool(a), Value::Bool(b)) => a == b,
+            (Value::Str(a), Value::Str(b)) => a == b,
+            (Value::Num(a), Value::Num(b)) => a == b,
+            (Value::Array(a), Value::Array(b)) => a == b,
+            (Value::Dict(a), Value::Dict(b)) => {
+                a.len() == b.len() && a.entries().all(|(k, v)| b.get(k) == Some(v))
+            }
+            _ => false,
+        }
+    }
+}
+
+/// How deep `{:?}` shows a value when the format doesn't say, e.g. with
+/// `{:.3?}`. Deeper arrays and objects are shown as `[..]` and `{..}`.
+pub const DEBUG_DEPTH: usize = 16;
+/// How many elements or entries `{:?}` shows per array or object when the
+/// format doesn't say, e.g. with `{:10?}`. The rest are shown as `..`.
+pub const DEBUG_WIDTH: usize = 100;
+// Characters `{:?}` shows per string.
+const DEBUG_STRING_CHARS: usize = 1000;
+
+// Debug that ignores the formatter's precision and width, which `Limited`
+// uses for its own purposes.
+struct Plain<T>(T);
+
+impl<T: fmt::Debug> fmt::Debug for Plain<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if f.alternate() {
+            write!(f, "{:#?}", self.0)
+        } else {
+            write!(f, "{:?}", self.0)
+        }
+    }
+}
+
+// Debug cut off after `DEBUG_STRING_CHARS` characters of output, for
+// strings of any `MapKey` type.
+struct Clipped<T>(T);
+
+// Passes writes through until `left` characters have been written, then
+// fails to stop the formatting.
+struct Budget<'a, 'f> {
+    out: &'a mut fmt::Formatter<'f>,
+    left: usize,
+}
+
+impl fmt::Write for Budget<'_, '_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for c in s.chars() {
+            if self.left == 0 {
+                return Err(fmt::Error);
+            }
+            self.left -= 1;
+            self.out.write_char(c)?;
+        }
+        Ok(())
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Clipped<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut budget = Budget {
+            out: f,
+            left: DEBUG_STRING_CHARS,
+        };
+        match fmt::Write::write_fmt(&mut budget, format_args!("{:?}", self.0)) {
+            Err(_) if budget.left == 0 => f.write_str(".."),
+            res => res,
+        }
+    }
+}
+
+// Debug for a value with `depth` more levels to show, at most `width`
+// children each.
+struct Limited<'v, S: MapKey, N, M: MapKind> {
+    value: &'v Value<S, N, M>,
+    depth: usize,
+    width: usize,
+}
+
+impl<'v, S: MapKey, N, M: MapKind> Limited<'v, S, N, M> {
+    fn child(&self, value: &'v Value<S, N, M>) -> Self {
+        Self {
+            value,
+            depth: self.depth - 1,
+            width: self.width,
+        }
+    }
+}
+
+impl<S: MapKey + fmt::Debug, N: fmt::Debug, M: MapKind> fmt::Debug for Limited<'_, S, N, M> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.value {
+            Value::Null => f.write_str("Null"),
+            Value::Bool(b) => f.debug_tuple("Bool").field(&Plain(b)).finish(),
+            Value::Str(s) => f.debug_tuple("Str").field(&Clipped(s)).finish(),
+            Value::Num(n) => f.debug_tuple("Num").field(&Plain(n)).finish(),
+            Value::Array(entries) => f
+                .debug_tuple("Array")
+                .field(&Elements(self, entries))
+                .finish(),
+            Value::Dict(d) => f.debug_tuple("Dict").field(&Entries(self, d)).finish(),
+        }
+    }
+}
+
+// The elements of the array `.0` shows.
+struct Elements<'l, 'v, S: MapKey, N, M: MapKind>(&'l Limited<'v, S, N, M>, &'v [Value<S, N, M>]);
+
+impl<S: MapKey + fmt::Debug, N: fmt::Debug, M: MapKind> fmt::Debug for Elements<'_, '_, S, N, M> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (limits, entries) = (self.0, self.1);
+        let mut list = f.debug_list();
+        let shown = if limits.depth == 0 { 0 } else { limits.width };
+        list.entries(entries.iter().take(shown).map(|e| limits.child(e)));
+        if entries.len() > shown {
+            list.finish_non_exhaustive()
+        } else {
+            list.finish()
+        }
+    }
+}
+
+// The entries of the object `.0` shows.
+struct Entries<'l, 'v, S: MapKey, N, M: MapKind>(
+    &'l Limited<'v, S, N, M>,
+    &'v M::Map<S, Value<S, N, M>>,
+);
+
+impl<S: MapKey + fmt::Debug, N: fmt::Debug, M: MapKind> fmt::Debug for Entries<'_, '_, S, N, M> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (limits, d) = (self.0, self.1);
+        let mut map = f.debug_map();
+        let shown = if limits.depth == 0 { 0 } else { limits.width };
+        for (k, v) in d.entries().take(shown) {
+            map.entry(&Plain(k), &limits.child(v));
+        }
+        if d.len() > shown {
+            map.finish_non_exhaustive()
+        } else {
+            map.finish()
+        }
+    }
+}
+
+/// Like a derived `Debug`, except that arrays and objects past a depth, and
+/// elements, entries and characters past a count, are elided, so logging a
+/// huge document stays cheap. `{:.N?}` shows `N` levels (default
+/// `DEBUG_DEPTH`) and `{:N?}` `N` children per level (default
+/// `DEBUG_WIDTH`).
+impl<S: MapKey + fmt::Debug, N: fmt::Debug, M: MapKind> fmt::Debug for Value<S, N, M> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Limited {
+            value: self,
+            depth: f.precision().unwrap_or(DEBUG_DEPTH),
+            width: f.width().unwrap_or(DEBUG_WIDTH),
+        }
+        .fmt(f)
+    }
+}
+
+/// Number types a `Value` can be parsed into.
+pub trait FromJsonNumber: Sized {
+    /// `lexeme` is the number as written in the input, `value` its nearest
+    /// `f64`.
+    fn from_json_number(lexeme: &str, value: f64) -> eyre::Result<Self>;
+
+    /// Whether converting `lexeme` rounds it, which `ParseOptions::big_numbers`
+    /// decides what to do about.
+    fn loses_precision(lexeme: &str, value: f64) -> bool {
+        let _ = (lexeme, value);
+        false
+    }
+
+    /// For `BigNumbers::KeepAsBigInt`.
+    #[cfg(feature = "bignum")]
+    fn from_big_integer(lexeme: &str) -> eyre::Result<Self> {
+        eyre::bail!(format!(
+            "Number {} needs a number type that can hold big integers",
+            lexeme
+        ))
+    }
+}
+
+impl FromJsonNumber for f64 {
+    fn from_json_number(_lexeme: &str, value: f64) -> eyre::Result<Self> {
+        Ok(value)
+    }
+
+    fn loses_precision(lexeme: &str, value: f64) -> bool {
+        number::f64_loses_precision(lexeme, value)
+    }
+}
+
+impl FromJsonNumber for i64 {
+    fn from_json_number(lexeme: &str, _value: f64) -> eyre::Result<Self> {
+        lexeme
+            .parse()
+            .map_err(|_| eyre::eyre!(format!("Number {} is not a 64-bit integer", lexeme)))
+    }
+}
+
+impl FromJsonNumber for u64 {
+    fn from_json_number(lexeme: &str, _value: f64) -> eyre::Result<Self> {
+        lexeme.parse().map_err(|_| {
+            eyre::eyre!(format!(
+                "Number {} is not an unsigned 64-bit integer",
+                lexeme
+            ))
+        })
+    }
+}
+
+/// Keeps the number exactly as written.
+impl FromJsonNumber for String {
+    fn from_json_number(lexeme: &str, _value: f64) -> eyre::Result<Self> {
+        Ok(lexeme.to_string())
+    }
+}
+
+/// Number types a `Value` can be serialized from.
+pub trait ToJsonNumber {
+    fn write_json_number<W: ?Sized + fmt::Write>(&self, out: &mut W) -> fmt::Result;
+}
+
+impl ToJsonNumber for f64 {
+    fn write_json_number<W: ?Sized + fmt::Write>(&self, out: &mut W) -> fmt::Result {
+        serialize::write_number(out, *self)
+    }
+}
+
+impl ToJsonNumber for i64 {
+    fn write_json_number<W: ?Sized + fmt::Write>(&self, out: &mut W) -> fmt::Result {
+        write!(out, "{}", self)
+    }
+}
+
+impl ToJsonNumber for u64 {
+    fn write_json_number<W: ?Sized + fmt::Write>(&self, out: &mut W) -> fmt::Result {
+        write!(out, "{}", self)
+    }
+}
+
+/// Writes the number verbatim, as kept by `FromJsonNumber for String`.
+impl ToJsonNumber for String {
+    fn write_json_number<W: ?Sized + fmt::Write>(&self, out: &mut W) -> fmt::Result {
+        out.write_str(self)
+    }
+}
+
+enum Open<S: MapKey, N, M: MapKind> {
+    Array(Vec<Value<S, N, M>>),
+    Object(M::Map<S, Value<S, N, M>>, Option<S>),
+}
+
+impl<S, N, M> Value<S, N, M>
+where
+    S: MapKey + From<String>,
+    N: FromJsonNumber,
+    M: MapKind,
+{
+    /// Parses one JSON document straight into this representation, e.g.
+    /// `Value::<Arc<str>, String, BTreeMapKind>::parse(json)`.
+    pub fn parse(json: &[u8]) -> eyre::Result<Self> {
+        Self::parse_with_options(json, &ParseOptions::default())
+    }
+
+    pub fn parse_with_options(json: &[u8], options: &ParseOptions) -> eyre::Result<Self> {
+        if let Some(max_size) = options.max_size {
+            if json.len() > max_size {
+                eyre::bail!(format!(
+                    "Input of {} bytes exceeds maximum size of {}",
+                    json.len(),
+                    max_size
+                ))
+            }
+        }
+        let mut syntax = SyntaxState::new();
+        let mut stack: Vec<Open<S, N, M>> = Vec::new();
+        let mut root = None;
+        for t in Lexer::with_options(options).tokens(json) {
+            let t = t?;
+            let expect = syntax.expect;
+            syntax.feed(t.token)?;
+            let value = match t.token {
+                Token::Comment | Token::Colon | Token::Comma => continue,
+                Token::LeftBrace | Token::LeftBracket => {
+                    if let Some(max_depth) = options.max_depth {
+                        if stack.len() >= max_depth {
+                            eyre::bail!(format!("Nesting exceeds maximum depth of {}", max_depth))
+                        }
+                    }
+                    stack.push(match t.token {
+                        Token::LeftBrace => Open::Object(Default::default(), None),
+                        _ => Open::Array(Vec::new()),
+                    });
+                    continue;
+                }
+                Token::RightBrace | Token::RightBracket => {
+                    match stack.pop().ok_or_eyre("Unbalanced brackets")? {
+                        Open::Array(entries) => Value::Array(entries),
+                        Open::Object(entries, _) => Value::Dict(entries),
+                    }
+                }
+                Token::StringVal(i, j) => {
+                    let is_key = matches!(expect, Expect::Key | Expect::KeyOrEndObject);
+                    let raw = json
+                        .get(i..j)
+                        .ok_or_eyre("String range outside the input")?;
+                    let s = escape::unescape_str(raw, options.lone_surrogates, String::new())?;
+                    let s = S::from(normalize::apply(s, options, is_key));
+                    if is_key {
+                        if let Some(Open::Object(_, key)) = stack.last_mut() {
+                            *key = Some(s);
+                        }
+                        continue;
+                    }
+                    Value::Str(s)
+                }
+                Token::NumVal(n) => {
+                    let lexeme = std::str::from_utf8(&json[t.span.range()])?;
+                    match number::convert(lexeme, n, options.big_numbers)? {
+                        Some(n) => Value::Num(n),
+                        None => Value::Str(S::from(lexeme.to_string())),
+                    }
+                }
+                Token::BoolVal(b) => Value::Bool(b),
+                Token::NullVal => Value::Null,
+            };
+            match stack.last_mut() {
+                None => root = Some(value),
+                Some(Open::Array(entries)) => entries.push(value),
+                Some(Open::Object(entries, key)) => {
+                    if let Some(key) = key.take() {
+                        entries.insert(key, value);
+                    }
+                }
+            }
+        }
+        root.ok_or_eyre("Expected value")
+    }
+}
+
+#[cfg(feature = "rust_decimal")]
+mod decimal_support {
+    use rust_decimal::Decimal;
+
+    use super::{FromJsonNumber, ToJsonNumber};
+
+    /// Exact decimal numbers, e.g. for money: parse with
+    /// `Value::<String, Decimal>::parse`. Numbers with more precision than
+    /// `Decimal` holds are an error rather than being rounded.
+    impl FromJsonNumber for Decimal {
+        fn from_json_number(lexeme: &str, _value: f64) -> eyre::Result<Self> {
+            let invalid = |e: &dyn core::fmt::Display| {
+                eyre::eyre!(format!("Number {} is not a decimal: {}", lexeme, e))
+            };
+            let (mantissa, exp) = match lexeme.split_once(['e', 'E']) {
+                Some((m, e)) => (m, e.parse::<i64>().map_err(|e| invalid(&e))?),
+                None => (lexeme, 0),
+            };
+            let mut d = Decimal::from_str_exact(mantissa).map_err(|e| invalid(&e))?;
+            if d.is_zero() {
+                return Ok(Decimal::ZERO);
+            }
+            // move the point by `exp` without rounding: `from_scientific`
+            // would round away digits past what `Decimal` holds
+            let scale = d.scale() as i64 - exp;
+            if scale >= 0 {
+                let scale = u32::try_from(scale).unwrap_or(u32::MAX);
+                d.set_scale(scale).map_err(|e| invalid(&e))?;
+                return Ok(d);
+            }
+            d.set_scale(0).map_err(|e| invalid(&e))?;
+            u32::try_from(-scale)
+                .ok()
+                .and_then(|shift| 10i128.checked_pow(shift))
+                .and_then(|factor| {
+                    d.checked_mul(Decimal::try_from_i128_with_scale(factor, 0).ok()?)
+                })
+                .ok_or_else(|| invalid(&"too large"))
+        }
+    }
+
+    impl ToJsonNumber for Decimal {
+        fn write_json_number<W: ?Sized + core::fmt::Write>(
+            &self,
+            out: &mut W,
+        ) -> core::fmt::Result {
+            write!(out, "{}", self)
+        }
+    }
+}
+
+#[cfg(feature = "valuable")]
+mod valuable_support {
+    use valuable::{Valuable, Value as Structured, Visit};
+
+    use crate::JSONValue;
+
+    /// Lets a document be recorded as a structured field, e.g. by `tracing`
+    /// with its `valuable` support, rather than as a string.
+    impl Valuable for JSONValue {
+        fn as_value(&self) -> Structured<'_> {
+            match self {
+                JSONValue::Null => Structured::Unit,
+                JSONValue::Bool(b) => Structured::Bool(*b),
+                JSONValue::Num(n) => Structured::F64(*n),
+                JSONValue::Str(s) => Structured::String(s),
+                JSONValue::Array(entries) => Structured::Listable(entries),
+                JSONValue::Dict(d) => Structured::Mappable(d),
+            }
+        }
+
+        fn visit(&self, visit: &mut dyn Visit) {
+            visit.visit_value(self.as_value())
+        }
+    }
+}
@@ -0,0 +1,114 @@
+use crate::{pointer, JSONValue};
+
+/// What a `Visitor` wants to happen after seeing a value.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum VisitAction {
+    #[default]
+    Continue,
+    /// Don't descend into this array or object.
+    SkipSubtree,
+    /// End the whole traversal.
+    Stop,
+}
+
+/// Where in the document a visited value is.
+#[derive(Debug)]
+pub struct VisitContext<'a> {
+    depth: usize,
+    pointer: &'a str,
+    key: Option<&'a str>,
+}
+
+impl VisitContext<'_> {
+    /// 0 for the value `visit` was called on.
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// JSON Pointer relative to the value `visit` was called on.
+    pub fn pointer(&self) -> &str {
+        self.pointer
+    }
+
+    /// The key of the value, if it is an object entry.
+    pub fn key(&self) -> Option<&str> {
+        self.key
+    }
+}
+
+/// Callbacks for a depth-first walk over a value, see `JSONValue::visit`.
+pub trait Visitor {
+    /// Called on every value before its children.
+    fn enter(&mut self, value: &JSONValue, cx: &VisitContext) -> VisitAction;
+
+    /// Called on an array or object after its children, unless they were
+    /// skipped.
+    fn leave(&mut self, value: &JSONValue, cx: &VisitContext) {
+        let _ = (value, cx);
+    }
+}
+
+impl<F: FnMut(&JSONValue, &VisitContext) -> VisitAction> Visitor for F {
+    fn enter(&mut self, value: &JSONValue, cx: &VisitContext) -> VisitAction {
+        self(value, cx)
+    }
+}
+
+impl JSONValue {
+    /// Walks this value depth first, object entries in hash map order.
+    /// Returns `false` if the visitor stopped the walk.
+    pub fn visit<V: Visitor>(&self, visitor: &mut V) -> bool {
+        let mut ptr = String::new();
+        walk(self, None, 0, &mut ptr, visitor)
+    }
+}
+
+fn walk<V: Visitor>(
+    v: &JSONValue,
+    key: Option<&str>,
+    depth: usize,
+    ptr: &mut String,
+    visitor: &mut V,
+) -> bool {
+    let cx = VisitContext {
+        depth,
+        pointer: ptr,
+        key,
+    };
+    match visitor.enter(v, &cx) {
+        VisitAction::Stop => return false,
+        VisitAction::SkipSubtree => return true,
+        VisitAction::Continue => {}
+    }
+    let len = ptr.len();
+    match v {
+        JSONValue::Array(entries) => {
+            for (i, e) in entries.iter().enumerate() {
+                pointer::push(ptr, &i.to_string());
+                let go_on = walk(e, None, depth + 1, ptr, visitor);
+                ptr.truncate(len);
+                if !go_on {
+                    return false;
+                }
+            }
+        }
+        JSONValue::Dict(d) => {
+            for (k, e) in d {
+                pointer::push(ptr, k);
+                let go_on = walk(e, Some(k), depth + 1, ptr, visitor);
+                ptr.truncate(len);
+                if !go_on {
+                    return false;
+                }
+            }
+        }
+        _ => return true,
+    }
+    let cx = VisitContext {
+        depth,
+        pointer: ptr,
+        key,
+    };
+    visitor.leave(v, &cx);
+    true
+}
@@ -0,0 +1,85 @@
+use core::fmt;
+
+use crate::{JSONValue, ParseHooks, ParseOptions, RecursiveParser, ValuePool};
+
+/// What a `Warning` is about.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum WarningKind {
+    /// A key appeared again in the same object; the last value was kept.
+    DuplicateKey,
+    /// A number was rounded to the nearest `f64` under
+    /// `BigNumbers::LoseToF64`.
+    PrecisionLoss,
+    /// A UTF-8 byte order mark at the start of the input was skipped, under
+    /// `ParseOptions::strip_bom`.
+    BomStripped,
+    /// Nesting went past half of `max_depth`; given once per parse.
+    DeepNesting,
+}
+
+/// Something odd about the input that didn't stop it parsing, e.g. for a
+/// strict pipeline to log.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Warning {
+    kind: WarningKind,
+    pointer: String,
+    offset: usize,
+    message: String,
+}
+
+impl Warning {
+    pub(crate) fn new(kind: WarningKind, pointer: &str, offset: usize, message: String) -> Self {
+        Self {
+            kind,
+            pointer: pointer.to_string(),
+            offset,
+            message,
+        }
+    }
+
+    pub fn kind(&self) -> WarningKind {
+        self.kind
+    }
+
+    /// JSON Pointer of the value concerned.
+    pub fn pointer(&self) -> &str {
+        &self.pointer
+    }
+
+    /// Byte offset in the input where it was noticed.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.pointer.is_empty() {
+            write!(f, "byte {}: {}", self.offset, self.message)
+        } else {
+            write!(
+                f,
+                "{} (byte {}): {}",
+                self.pointer, self.offset, self.message
+            )
+        }
+    }
+}
+
+/// Like `parse_with_options`, also returning the warnings met on the way,
+/// in input order. Use `ParseHooks::on_warning` to get them as they happen.
+pub fn parse_with_warnings(
+    json: &[u8],
+    options: &ParseOptions,
+) -> eyre::Result<(JSONValue, Vec<Warning>)> {
+    let mut warnings = Vec::new();
+    let mut hooks = ParseHooks::new().on_warning(|w| warnings.push(w));
+    let mut pool = ValuePool::new();
+    let value = RecursiveParser::new(json, &mut pool, Some(&mut hooks), options).parse();
+    drop(hooks);
+    Ok((value?, warnings))
+}
@@ -0,0 +1,134 @@
+use json_parser::{anonymize, parse, AnonymizeOptions, JSONValue, PathPattern};
+use proptest::prelude::*;
+
+mod common;
+use common::arb_value;
+
+// Same structure and keys, strings of the same length, numbers of the same
+// sign.
+fn same_shape(a: &JSONValue, b: &JSONValue) -> bool {
+    match (a, b) {
+        (JSONValue::Str(a), JSONValue::Str(b)) => a.chars().count() == b.chars().count(),
+        (JSONValue::Num(a), JSONValue::Num(b)) => a.signum() == b.signum(),
+        (JSONValue::Array(a), JSONValue::Array(b)) => {
+            a.len() == b.len() && a.iter().zip(b).all(|(a, b)| same_shape(a, b))
+        }
+        (JSONValue::Dict(a), JSONValue::Dict(b)) => {
+            a.len() == b.len()
+                && a.iter()
+                    .all(|(k, a)| b.get(k).is_some_and(|b| same_shape(a, b)))
+        }
+        (a, b) => a == b,
+    }
+}
+
+// Each character's kind: digit, upper or lower case letter, or itself.
+fn kinds(s: &str) -> String {
+    s.chars()
+        .map(|c| match c {
+            c if c.is_ascii_digit() => '9',
+            c if c.is_uppercase() => 'A',
+            c if c.is_alphabetic() => 'a',
+            c => c,
+        })
+        .collect()
+}
+
+#[test]
+fn anonymized_record() {
+    let original = parse(
+        br#"{"id": 12345, "email": "Jane.Doe@example.com", "active": true, "manager": null,
+            "type": "admin", "score": -0.25, "tags": ["x1", "x1"], "alias": "Jane.Doe@example.com"}"#,
+    )
+    .unwrap();
+    let options = AnonymizeOptions {
+        seed: 42,
+        keep: vec![PathPattern::new("/type").unwrap()],
+    };
+    let mut v = original.clone();
+    anonymize(&mut v, &options);
+    assert!(same_shape(&v, &original), "{}", v);
+    let JSONValue::Dict(d) = &v else {
+        panic!("expected an object, got {}", v);
+    };
+
+    // booleans, nulls and kept paths are as they were
+    assert_eq!(d["active"], JSONValue::Bool(true));
+    assert_eq!(d["manager"], JSONValue::Null);
+    assert_eq!(d["type"], JSONValue::Str("admin".to_string()));
+
+    // strings keep their case and punctuation, but not their letters
+    let JSONValue::Str(email) = &d["email"] else {
+        panic!("expected a string, got {}", d["email"]);
+    };
+    assert_eq!(kinds(email), "Aaaa.Aaa@aaaaaaa.aaa");
+    assert_ne!(email, "Jane.Doe@example.com");
+    // equal values stay equal
+    assert_eq!(d["alias"], d["email"]);
+    let JSONValue::Array(tags) = &d["tags"] else {
+        panic!("expected an array, got {}", d["tags"]);
+    };
+    assert_eq!(tags[0], tags[1]);
+    assert!(matches!(&tags[0], JSONValue::Str(t) if kinds(t) == "a9"));
+
+    // numbers keep their sign and magnitude
+    let JSONValue::Num(id) = d["id"] else {
+        panic!("expected a number, got {}", d["id"]);
+    };
+    assert!(
+        (10000.0..100000.0).contains(&id) && id.fract() == 0.0,
+        "{}",
+        id
+    );
+    let JSONValue::Num(score) = d["score"] else {
+        panic!("expected a number, got {}", d["score"]);
+    };
+    assert!((-1.0..=-0.1).contains(&score), "{}", score);
+
+    // the seed decides the replacements
+    let mut again = original.clone();
+    anonymize(&mut again, &options);
+    assert_eq!(again, v);
+    let mut reseeded = original.clone();
+    anonymize(
+        &mut reseeded,
+        &AnonymizeOptions {
+            seed: 43,
+            ..options
+        },
+    );
+    assert_ne!(reseeded, v);
+}
+
+#[test]
+fn kept_subtrees() {
+    let mut v = parse(br#"{"users": [{"name": "Ann", "meta": {"role": "ops", "n": 3}}]}"#).unwrap();
+    let options = AnonymizeOptions {
+        seed: 1,
+        keep: vec![PathPattern::new("/**/meta").unwrap()],
+    };
+    anonymize(&mut v, &options);
+    let JSONValue::Dict(d) = &v else {
+        panic!("expected an object, got {}", v);
+    };
+    let JSONValue::Array(users) = &d["users"] else {
+        panic!("expected an array, got {}", d["users"]);
+    };
+    let JSONValue::Dict(user) = &users[0] else {
+        panic!("expected an object, got {}", users[0]);
+    };
+    assert_eq!(user["meta"], parse(br#"{"role": "ops", "n": 3}"#).unwrap());
+    assert!(matches!(&user["name"], JSONValue::Str(s) if kinds(s) == "Aaa" && s != "Ann"));
+}
+
+proptest! {
+    #[test]
+    fn anonymized(v in arb_value(), seed in any::<u64>()) {
+        let options = AnonymizeOptions { seed, ..AnonymizeOptions::default() };
+        let (mut a, mut b) = (v.clone(), v.clone());
+        anonymize(&mut a, &options);
+        anonymize(&mut b, &options);
+        prop_assert_eq!(&a, &b);
+        prop_assert!(same_shape(&a, &v), "{} vs {}", a, v);
+    }
+}
@@ -0,0 +1,68 @@
+use json_parser::{Bytes, JSONValue};
+
+#[test]
+fn base64() {
+    for (data, encoded) in [
+        (&b""[..], ""),
+        (b"f", "Zg=="),
+        (b"fo", "Zm8="),
+        (b"foo", "Zm9v"),
+        (b"\xfb\xff\xbf", "+/+/"),
+    ] {
+        let v = JSONValue::from_bytes_base64(data);
+        assert_eq!(v, JSONValue::Str(encoded.to_string()));
+        assert_eq!(v.as_base64_bytes().unwrap(), data);
+        assert_eq!(JSONValue::from(Bytes::from(data)), v);
+        assert_eq!(*Bytes::try_from(&v).unwrap(), *data);
+    }
+    // unpadded and URL-safe input is accepted too
+    for encoded in ["Zg", "Zm8", "-_-_", "+/+/"] {
+        assert!(JSONValue::Str(encoded.to_string())
+            .as_base64_bytes()
+            .is_some());
+    }
+}
+
+#[test]
+fn base64_strict() {
+    for (bad, message) in [
+        ("+_", "Base64 mixes the standard and URL-safe alphabets"),
+        ("-/-/", "Base64 mixes the standard and URL-safe alphabets"),
+        ("Z", "Invalid base64 length"),
+        ("Zg=", "Invalid base64 length"),
+        ("Zm8==", "Invalid base64 length"),
+        ("Zg===", "Invalid base64 length"),
+        ("Zm9v====", "Invalid base64 length"),
+        ("Zg==Zg==", "Invalid base64 character: '='"),
+        ("Zm 9", "Invalid base64 character: ' '"),
+    ] {
+        let v = JSONValue::Str(bad.to_string());
+        assert_eq!(v.as_base64_bytes(), None, "{}", bad);
+        assert_eq!(
+            Bytes::try_from(&v).unwrap_err().to_string(),
+            message,
+            "{}",
+            bad
+        );
+    }
+    assert!(Bytes::try_from(&JSONValue::Num(1.0)).is_err());
+}
+
+#[test]
+fn hex() {
+    let v = JSONValue::from_bytes_hex(b"\x00\xab\xff");
+    assert_eq!(v, JSONValue::Str("00abff".to_string()));
+    assert_eq!(v.as_hex_bytes().unwrap(), b"\x00\xab\xff");
+    assert_eq!(
+        JSONValue::Str("00ABFF".to_string()).as_hex_bytes().unwrap(),
+        b"\x00\xab\xff"
+    );
+    for bad in ["abc", "0g", "+1"] {
+        assert_eq!(
+            JSONValue::Str(bad.to_string()).as_hex_bytes(),
+            None,
+            "{}",
+            bad
+        );
+    }
+}
@@ -0,0 +1,65 @@
+#![cfg(feature = "bson")]
+
+use json_parser::{parse, JSONValue};
+
+#[test]
+fn bson() {
+    use bson::{Bson, Document as BsonDocument};
+    use json_parser::ExtJsonMode;
+
+    let doc = parse(
+        br#"{
+            "_id": {"$oid": "65f1c0ffee00000000000001"},
+            "at": {"$date": "2024-05-01T12:00:00Z"},
+            "big": {"$numberLong": "9007199254740993"},
+            "bin": {"$binary": {"base64": "AQI=", "subType": "00"}},
+            "ints": [3, -70000000000],
+            "tags": ["a", null, true, 1.5]
+        }"#,
+    )
+    .unwrap();
+    let bson = doc.to_bson_document().unwrap();
+    assert!(matches!(bson.get("_id"), Some(Bson::ObjectId(_))));
+    assert!(matches!(bson.get("at"), Some(Bson::DateTime(_))));
+    assert_eq!(bson.get("big"), Some(&Bson::Int64(9007199254740993)));
+    assert_eq!(
+        bson.get("ints"),
+        Some(&Bson::Array(vec![
+            Bson::Int32(3),
+            Bson::Int64(-70000000000)
+        ]))
+    );
+    let mut bytes = Vec::new();
+    bson.to_writer(&mut bytes).unwrap();
+    let read = Bson::Document(BsonDocument::from_reader(bytes.as_slice()).unwrap());
+    assert_eq!(JSONValue::from_bson(&read, ExtJsonMode::Relaxed), doc);
+    let canonical = JSONValue::from_bson(&read, ExtJsonMode::Canonical);
+    let JSONValue::Dict(fields) = &canonical else {
+        panic!("expected an object")
+    };
+    assert_eq!(
+        fields["ints"],
+        parse(br#"[{"$numberInt": "3"}, {"$numberLong": "-70000000000"}]"#).unwrap()
+    );
+    assert_eq!(canonical.to_bson().unwrap(), read);
+
+    for wrapped in [
+        r#"{"$dbPointer": {"$ref": "db.users", "$id": {"$oid": "65f1c0ffee00000000000001"}}}"#,
+        r#"{"$regularExpression": {"pattern": "^a", "options": "im"}}"#,
+        r#"{"$timestamp": {"t": 1700000000, "i": 2}}"#,
+        r#"{"$numberDouble": "-Infinity"}"#,
+        r#"{"$code": "f()", "$scope": {"x": {"$numberInt": "1"}}}"#,
+    ] {
+        let v = parse(wrapped.as_bytes()).unwrap();
+        let bson = v.to_bson().unwrap();
+        assert_eq!(JSONValue::from_bson(&bson, ExtJsonMode::Canonical), v);
+    }
+    for bad in [
+        r#"{"$oid": "xyz"}"#,
+        r#"{"$numberInt": "1.5"}"#,
+        r#"{"$date": true}"#,
+    ] {
+        assert!(parse(bad.as_bytes()).unwrap().to_bson().is_err(), "{}", bad);
+    }
+    assert!(JSONValue::Num(1.0).to_bson_document().is_err());
+}
@@ -0,0 +1,51 @@
+use json_parser::{json, parse, ArrayBuilder, JSONValue, ObjectBuilder};
+
+#[test]
+fn built() {
+    let v = ObjectBuilder::with_capacity(4)
+        .field("name", "x")
+        .field("n", 1.5)
+        .field("n", 2.0)
+        .field_opt("missing", None::<bool>)
+        .field_opt("present", Some(true))
+        .field(
+            "tags",
+            ArrayBuilder::new()
+                .push("a")
+                .extend(["b", "c"])
+                .push(JSONValue::Null),
+        )
+        .build();
+    assert_eq!(
+        v,
+        parse(br#"{"name": "x", "n": 2, "present": true, "tags": ["a", "b", "c", null]}"#).unwrap()
+    );
+    assert_eq!(ObjectBuilder::new().build(), parse(b"{}").unwrap());
+    assert_eq!(
+        ArrayBuilder::with_capacity(1).build(),
+        parse(b"[]").unwrap()
+    );
+}
+
+#[test]
+fn json_macro() {
+    let tags = ArrayBuilder::new().push("a").push("b");
+    let count = 2.0;
+    let v = json!({
+        "foo": [1.0, 2.0, (-3.0)],
+        "bar": null,
+        "empty": [[], {}],
+        "nested": {"ok": true, "count": count},
+        "tags": tags,
+    });
+    assert_eq!(
+        v,
+        parse(
+            br#"{"foo": [1, 2, -3], "bar": null, "empty": [[], {}],
+                 "nested": {"ok": true, "count": 2}, "tags": ["a", "b"]}"#
+        )
+        .unwrap()
+    );
+    assert_eq!(json!("s"), JSONValue::Str("s".to_string()));
+    assert_eq!(json!(null), JSONValue::Null);
+}
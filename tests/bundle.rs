@@ -0,0 +1,75 @@
+use json_parser::{parse, Bundle, BundleWriter, JSONValue};
+use proptest::prelude::*;
+
+mod common;
+use common::arb_value;
+
+fn json(text: &str) -> JSONValue {
+    parse(text.as_bytes()).unwrap()
+}
+
+#[test]
+fn packed() {
+    let mut writer = BundleWriter::new();
+    writer.add("a", &json("[]"));
+    let bytes = writer.to_bytes();
+    // member ranges are relative to the start of `members`
+    assert_eq!(bytes, br#"{"index":{"a":[5,7]},"members":{"a":[]}}"#);
+    let bundle = Bundle::open(bytes).unwrap();
+    assert_eq!(bundle.names().collect::<Vec<_>>(), ["a"]);
+    assert_eq!(bundle.raw("a"), Some(&b"[]"[..]));
+}
+
+#[test]
+fn unpacked() {
+    let mut writer = BundleWriter::new();
+    writer
+        .add("users.json", &json(r#"[{"id": 1}]"#))
+        .add("config", &json(r#"{"debug": true}"#))
+        .add("users.json", &json(r#"[{"id": 2}]"#));
+    let bundle = Bundle::open(writer.to_bytes()).unwrap();
+    assert_eq!(bundle.len(), 2);
+    let mut names: Vec<_> = bundle.names().collect();
+    names.sort_unstable();
+    assert_eq!(names, ["config", "users.json"]);
+    // a later `add` replaces the member
+    assert_eq!(bundle.raw("users.json"), Some(&br#"[{"id":2}]"#[..]));
+    assert_eq!(
+        bundle.get("config").unwrap().unwrap(),
+        json(r#"{"debug": true}"#)
+    );
+    assert!(bundle.get("missing").is_none());
+
+    let empty = Bundle::open(BundleWriter::new().to_bytes()).unwrap();
+    assert!(empty.is_empty());
+}
+
+#[test]
+fn invalid_bundles() {
+    let err = |text: &str| Bundle::open(text.as_bytes()).unwrap_err().to_string();
+    assert_eq!(err("[]"), "Bundle must be an object");
+    assert_eq!(err(r#"{"members": {}}"#), "Expected bundle member `index`");
+    assert_eq!(
+        err(r#"{"index": {"a": [0, 9]}, "members": {}}"#),
+        "Invalid index entry for \"a\""
+    );
+    assert_eq!(err(r#"{"index": {}"#), "Bundle ends early");
+}
+
+proptest! {
+    #[test]
+    fn bundled(members in prop::collection::hash_map(".*", arb_value(), 0..8)) {
+        let mut writer = BundleWriter::new();
+        for (name, doc) in &members {
+            writer.add(name, doc);
+        }
+        let bytes = writer.to_bytes();
+        prop_assert!(parse(&bytes).is_ok());
+        let bundle = Bundle::open(bytes).unwrap();
+        prop_assert_eq!(bundle.len(), members.len());
+        for (name, doc) in &members {
+            prop_assert_eq!(&bundle.get(name).unwrap().unwrap(), doc);
+        }
+    }
+
+}
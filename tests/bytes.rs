@@ -0,0 +1,74 @@
+#![cfg(feature = "bytes")]
+
+use bytes::Bytes;
+use json_parser::{Document, ParseOptions};
+
+// Whether `part` points into `whole`'s buffer.
+fn shares(whole: &Bytes, part: &Bytes) -> bool {
+    let range = whole.as_ptr_range();
+    range.contains(&part.as_ptr()) && part.as_ptr_range().end <= range.end
+}
+
+#[test]
+fn zero_copy_strings() {
+    let body =
+        Bytes::from_static(br#"{"user": "ann", "bio": "line\nbreak", "tags": ["a", "\u00e9"]}"#);
+    let doc = Document::from_bytes(body.clone()).unwrap();
+    let root = doc.root();
+
+    // unescaped strings are slices of the input
+    let user = root.get("user").unwrap().str_bytes().unwrap();
+    assert_eq!(user, "ann");
+    assert!(shares(&body, &user));
+    let tag = root
+        .get("tags")
+        .unwrap()
+        .index(0)
+        .unwrap()
+        .str_bytes()
+        .unwrap();
+    assert_eq!(tag, "a");
+    assert!(shares(&body, &tag));
+
+    // escaped ones are decoded into a buffer of their own
+    let bio = root.get("bio").unwrap().str_bytes().unwrap();
+    assert_eq!(bio, "line\nbreak");
+    assert!(!shares(&body, &bio));
+    let accented = root
+        .get("tags")
+        .unwrap()
+        .index(1)
+        .unwrap()
+        .str_bytes()
+        .unwrap();
+    assert_eq!(accented, "é");
+
+    // non-strings have no string bytes
+    assert_eq!(root.get("tags").unwrap().str_bytes(), None);
+    assert_eq!(doc.as_bytes(), &body[..]);
+}
+
+#[test]
+fn owned_input() {
+    // a document parsed from a `Vec` has no `Bytes` to share, so it copies
+    let doc = Document::parse(r#"["x"]"#).unwrap();
+    let x = doc.root().index(0).unwrap().str_bytes().unwrap();
+    assert_eq!(x, "x");
+}
+
+#[test]
+fn options_and_errors() {
+    let options = ParseOptions {
+        allow_comments: true,
+        ..ParseOptions::default()
+    };
+    let body = Bytes::from(r#"/* note */ {"k": "v"}"#.as_bytes().to_vec());
+    let doc = Document::from_bytes_with_options(body.clone(), &options).unwrap();
+    let v = doc.root().get("k").unwrap().str_bytes().unwrap();
+    assert!(shares(&body, &v));
+    assert!(Document::from_bytes(body).is_err());
+    assert!(Document::from_bytes(Bytes::from_static(br#"{"k": "#)).is_err());
+    // the buffer can be taken back
+    let doc = Document::from_bytes(Bytes::from_static(b"[1]")).unwrap();
+    assert_eq!(doc.into_bytes(), b"[1]");
+}
@@ -0,0 +1,21 @@
+use std::sync::Arc;
+
+use json_parser::ParseCache;
+
+#[test]
+fn cached() {
+    let cache = ParseCache::new(12);
+    let a = cache.get_or_parse(b"[1, 2]").unwrap();
+    assert!(Arc::ptr_eq(&a, &cache.get_or_parse(b"[1, 2]").unwrap()));
+    assert!(cache.get_or_parse(b"[1,").is_err());
+    cache.get_or_parse(b"[3, 4]").unwrap();
+    // `[1, 2]` was used more recently than `[3, 4]`, so stays
+    cache.get_or_parse(b"[1, 2]").unwrap();
+    cache.get_or_parse(b"[5, 6]").unwrap();
+    assert!(cache.get(b"[1, 2]").is_some());
+    assert!(cache.get(b"[3, 4]").is_none());
+    assert_eq!(cache.len(), 2);
+    // too big to keep
+    cache.get_or_parse(b"[7, 8, 9, 10]").unwrap();
+    assert_eq!(cache.len(), 2);
+}
@@ -0,0 +1,16 @@
+use json_parser::{parse, spawn_records};
+
+#[test]
+fn channeled() {
+    let lines: String = (0..100).map(|i| format!("{{\"n\": {}}}\n", i)).collect();
+    let received: Vec<_> = spawn_records(std::io::Cursor::new(lines.clone()), 1)
+        .map(|r| r.unwrap())
+        .collect();
+    assert_eq!(received.len(), 100);
+    assert_eq!(received[99], parse(br#"{"n": 99}"#).unwrap());
+    // stops at the first bad record
+    let bad = format!("{}{{\n", lines);
+    let results: Vec<_> = spawn_records(std::io::Cursor::new(bad), 4).collect();
+    assert_eq!(results.len(), 101);
+    assert!(results[100].is_err());
+}
@@ -0,0 +1,66 @@
+use json_parser::{parse, JSONValue};
+
+#[test]
+fn coerced() {
+    let v =
+        parse(br#"[42, " -7 ", "42.0", "1e3", 2.5, "2.5", true, "x", null, 1e30, "NaN"]"#).unwrap();
+    let JSONValue::Array(items) = v else {
+        panic!("not an array")
+    };
+    let ints: Vec<_> = items.iter().map(|v| v.coerce_i64()).collect();
+    assert_eq!(
+        ints,
+        [
+            Some(42),
+            Some(-7),
+            Some(42),
+            Some(1000),
+            None,
+            None,
+            Some(1),
+            None,
+            None,
+            None,
+            None
+        ]
+    );
+    let floats: Vec<_> = items.iter().map(|v| v.coerce_f64()).collect();
+    assert_eq!(
+        floats,
+        [
+            Some(42.0),
+            Some(-7.0),
+            Some(42.0),
+            Some(1000.0),
+            Some(2.5),
+            Some(2.5),
+            Some(1.0),
+            None,
+            None,
+            Some(1e30),
+            None
+        ]
+    );
+
+    let v = parse(br#"[true, 0, 1, 2, "TRUE", "no", " 1 ", "maybe", null]"#).unwrap();
+    let JSONValue::Array(items) = v else {
+        panic!("not an array")
+    };
+    let bools: Vec<_> = items.iter().map(|v| v.coerce_bool()).collect();
+    assert_eq!(
+        bools,
+        [
+            Some(true),
+            Some(false),
+            Some(true),
+            None,
+            Some(true),
+            Some(false),
+            Some(true),
+            None,
+            None
+        ]
+    );
+    // the strict accessors don't coerce
+    assert!(!items[4].as_bool_or(false));
+}
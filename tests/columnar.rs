@@ -0,0 +1,33 @@
+use json_parser::{parse, ColumnBatch, ColumnValues, JSONValue};
+
+#[test]
+fn columnar() {
+    let records = br#"{"id": 1, "name": "a", "score": 1.5, "tags": ["x"]}
+{"id": 2, "score": 2, "ok": true, "tags": null}
+{"id": 3, "name": "c", "score": null, "ok": false, "tags": {"y": 1}}"#;
+    let records = records
+        .split(|b| *b == b'\n')
+        .map(|line| parse(line).unwrap());
+    let batch = ColumnBatch::from_records(records).unwrap();
+    assert_eq!(batch.len(), 3);
+    let names: Vec<_> = batch.columns().iter().map(|c| c.name()).collect();
+    assert_eq!(names, ["id", "name", "ok", "score", "tags"]);
+    let column = |name| batch.column(name).unwrap();
+    assert_eq!(column("id").values(), &ColumnValues::Int(vec![1, 2, 3]));
+    assert_eq!(
+        column("name").values(),
+        &ColumnValues::Str(vec!["a".into(), "".into(), "c".into()])
+    );
+    assert_eq!(column("name").validity(), [true, false, true]);
+    assert_eq!(
+        column("ok").values(),
+        &ColumnValues::Bool(vec![false, true, false])
+    );
+    assert_eq!(
+        column("score").values(),
+        &ColumnValues::Float(vec![1.5, 2.0, 0.0])
+    );
+    assert_eq!(column("score").null_count(), 1);
+    assert!(matches!(column("tags").values(), ColumnValues::Json(v) if v.len() == 3));
+    assert!(ColumnBatch::from_records([JSONValue::Num(1.0)]).is_err());
+}
@@ -0,0 +1,66 @@
+use json_parser::{
+    parse, parse_with_comments, parse_with_options, Comment, CommentPlacement, ParseOptions,
+};
+
+#[test]
+fn comments_placed() {
+    let json = br#"{
+    // the name
+    "name": "x", // short
+    "tags": [ /* none yet */ ],
+    "n": [1, /* two */ 2]
+}
+/* end */"#;
+    let (v, comments) = parse_with_comments(json, &ParseOptions::default()).unwrap();
+    assert_eq!(
+        v,
+        parse(br#"{"name": "x", "tags": [], "n": [1, 2]}"#).unwrap()
+    );
+    let placed: Vec<_> = comments
+        .iter()
+        .map(|c| (c.text.as_str(), c.is_block(), c.placement.clone()))
+        .collect();
+    assert_eq!(
+        placed,
+        [
+            (
+                "// the name",
+                false,
+                CommentPlacement::Leading("/name".to_string())
+            ),
+            (
+                "// short",
+                false,
+                CommentPlacement::Trailing("/name".to_string())
+            ),
+            (
+                "/* none yet */",
+                true,
+                CommentPlacement::Dangling("/tags".to_string())
+            ),
+            (
+                "/* two */",
+                true,
+                CommentPlacement::Trailing("/n/0".to_string())
+            ),
+            ("/* end */", true, CommentPlacement::Trailing(String::new())),
+        ]
+    );
+    let Comment { span, text, .. } = &comments[0];
+    assert_eq!(&json[span.clone()], text.as_bytes());
+}
+
+#[test]
+fn comments_allowed() {
+    let json = b"[1, // one\n 2 /* two */]";
+    assert!(parse(json).is_err());
+    let options = ParseOptions {
+        allow_comments: true,
+        ..Default::default()
+    };
+    assert_eq!(
+        parse_with_options(json, &options).unwrap(),
+        parse(b"[1, 2]").unwrap()
+    );
+    assert!(parse_with_comments(b"[1 /* open", &ParseOptions::default()).is_err());
+}
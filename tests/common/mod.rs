@@ -0,0 +1,21 @@
+//! Helpers shared by the test targets, included with `mod common;`.
+
+use json_parser::JSONValue;
+use proptest::prelude::*;
+
+pub fn arb_value() -> impl Strategy<Value = JSONValue> {
+    let leaf = prop_oneof![
+        Just(JSONValue::Null),
+        any::<bool>().prop_map(JSONValue::Bool),
+        any::<f64>()
+            .prop_filter("JSON has no NaN or infinities", |n| n.is_finite())
+            .prop_map(JSONValue::Num),
+        any::<String>().prop_map(JSONValue::Str),
+    ];
+    leaf.prop_recursive(4, 64, 8, |inner| {
+        prop_oneof![
+            prop::collection::vec(inner.clone(), 0..8).prop_map(JSONValue::Array),
+            prop::collection::hash_map(any::<String>(), inner, 0..8).prop_map(JSONValue::Dict),
+        ]
+    })
+}
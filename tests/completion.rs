@@ -0,0 +1,130 @@
+use json_parser::lexer::TokenKind;
+use json_parser::{expected_next, Completion};
+
+use TokenKind::*;
+
+const VALUE: [TokenKind; 6] = [LeftBrace, LeftBracket, String, Number, Bool, Null];
+
+fn completion(prefix: &str) -> Completion {
+    expected_next(prefix.as_bytes()).unwrap()
+}
+
+// `expected`, `partial`, `description` and `depth` after `prefix`.
+fn check(
+    prefix: &str,
+    expected: &[TokenKind],
+    partial: Option<TokenKind>,
+    description: &str,
+    depth: usize,
+) {
+    let c = completion(prefix);
+    assert_eq!(c.expected, expected, "{:?}", prefix);
+    assert_eq!(c.partial, partial, "{:?}", prefix);
+    assert_eq!(c.description, description, "{:?}", prefix);
+    assert_eq!(c.depth, depth, "{:?}", prefix);
+}
+
+#[test]
+fn expected_in_each_state() {
+    let value_or_end = [&VALUE[..], &[RightBracket]].concat();
+    check("", &VALUE, None, "expected value", 0);
+    check("[", &value_or_end, None, "expected value or `]`", 1);
+    check(
+        "[1 ",
+        &[Comma, RightBracket],
+        None,
+        "expected `,` or `]`",
+        1,
+    );
+    check("[1,", &VALUE, None, "expected value", 1);
+    check("{", &[String, RightBrace], None, "expected key or `}`", 1);
+    check(r#"{"a""#, &[Colon], None, "expected `:`", 1);
+    check(r#"{"a":"#, &VALUE, None, "expected value", 1);
+    check(
+        r#"{"a": [] "#,
+        &[Comma, RightBrace],
+        None,
+        "expected `,` or `}`",
+        1,
+    );
+    check(r#"{"a": [],"#, &[String], None, "expected key", 1);
+    check(r#"{"a": [{}]} "#, &[], None, "expected end of input", 0);
+    // comments are skipped
+    check(
+        "[ /* one */ 1 // two\n",
+        &[Comma, RightBracket],
+        None,
+        "expected `,` or `]`",
+        1,
+    );
+}
+
+#[test]
+fn partial_tokens() {
+    let value_or_end = [&VALUE[..], &[RightBracket]].concat();
+    check("tru", &VALUE, Some(Bool), "expected value", 0);
+    check("[nu", &value_or_end, Some(Null), "expected value or `]`", 1);
+    check(
+        r#"{"ke"#,
+        &[String, RightBrace],
+        Some(String),
+        "expected key or `}`",
+        1,
+    );
+    check(
+        r#"["a\"#,
+        &value_or_end,
+        Some(String),
+        "expected value or `]`",
+        1,
+    );
+    check(
+        "[1e",
+        &value_or_end,
+        Some(Number),
+        "expected value or `]`",
+        1,
+    );
+    check(
+        "[-",
+        &value_or_end,
+        Some(Number),
+        "expected value or `]`",
+        1,
+    );
+}
+
+#[test]
+fn trailing_numbers() {
+    // `2` may go on to `25`, so it's partial, but could also end the element
+    check(
+        "[1, 2",
+        &[Comma, RightBracket],
+        Some(Number),
+        "expected `,` or `]`",
+        1,
+    );
+    check("12", &[], Some(Number), "expected end of input", 0);
+    check(
+        "[1.5",
+        &[Comma, RightBracket],
+        Some(Number),
+        "expected `,` or `]`",
+        1,
+    );
+    // once something follows, the number is over
+    check(
+        "[1, 2 ",
+        &[Comma, RightBracket],
+        None,
+        "expected `,` or `]`",
+        1,
+    );
+}
+
+#[test]
+fn invalid_prefixes() {
+    for prefix in ["[}", "[1 2", "{1", r#"{"a" 1"#, "[@", "tx", "01"] {
+        assert!(expected_next(prefix.as_bytes()).is_err(), "{:?}", prefix);
+    }
+}
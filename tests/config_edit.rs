@@ -0,0 +1,45 @@
+use json_parser::{parse, ConfigEditor, JSONValue};
+
+#[test]
+fn config_edits() {
+    let original = "{\n  // the package\n  \"name\": \"app\", // npm name\n  \"scripts\": {\n    \"test\": \"jest\"\n  },\n  \"files\": [\"dist\"]\n}\n";
+    let mut pkg = ConfigEditor::new(original).unwrap();
+    pkg.set_field_preserving("scripts.build", "tsc").unwrap();
+    pkg.set_field_preserving("name", "web").unwrap();
+    pkg.set_field_preserving("files.-", "lib").unwrap();
+    pkg.set_field_preserving("engines.node", ">=18").unwrap();
+    assert_eq!(
+        pkg.as_str(),
+        "{\n  // the package\n  \"name\": \"web\", // npm name\n  \"scripts\": {\n    \"test\": \"jest\",\n    \"build\": \"tsc\"\n  },\n  \"files\": [\"dist\", \"lib\"],\n  \"engines\": {\n    \"node\": \">=18\"\n  }\n}\n"
+    );
+    assert_eq!(
+        pkg.get("/scripts/build").unwrap(),
+        Some(JSONValue::Str("tsc".to_string()))
+    );
+
+    assert_eq!(
+        pkg.remove_field_preserving("engines").unwrap(),
+        Some(parse(br#"{"node": ">=18"}"#).unwrap())
+    );
+    pkg.remove_field_preserving("scripts.build").unwrap();
+    pkg.set_field_preserving("name", "app").unwrap();
+    pkg.set_field_preserving(
+        "files",
+        JSONValue::Array(vec![JSONValue::Str("dist".to_string())]),
+    )
+    .unwrap();
+    assert_eq!(pkg.as_str(), original);
+
+    pkg.remove_field_preserving("name").unwrap();
+    assert!(pkg
+        .as_str()
+        .starts_with("{\n  // the package\n  \"scripts\""));
+    assert_eq!(pkg.remove_field_preserving("missing.key").unwrap(), None);
+    assert!(pkg.set_field_preserving("files.5", 1.0).is_err());
+    assert!(pkg.set_field_preserving("scripts.test.x", 1.0).is_err());
+
+    let mut minified = ConfigEditor::new(r#"{"a":{}}"#).unwrap();
+    minified.set_field_preserving("a.b", true).unwrap();
+    minified.set_field_preserving("c", 1.0).unwrap();
+    assert_eq!(minified.as_str(), r#"{"a":{"b":true},"c":1}"#);
+}
@@ -0,0 +1,479 @@
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
+
+// Writes `contents` to a file named `name` in a fresh temp directory.
+fn temp_file(name: &str, contents: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("json_parser-cli-{}", std::process::id()));
+    let path = dir.join(name);
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(&path, contents).unwrap();
+    path
+}
+
+fn json_parser(args: &[&str], stdin: &str) -> Output {
+    json_parser_in(Path::new("."), args, stdin)
+}
+
+// Runs the tool from `dir`, where it looks for a config file.
+fn json_parser_in(dir: &Path, args: &[&str], stdin: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_json_parser"))
+        .args(args)
+        .current_dir(dir)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    // commands that fail early exit without reading their input
+    let _ = child.stdin.take().unwrap().write_all(stdin.as_bytes());
+    child.wait_with_output().unwrap()
+}
+
+#[test]
+fn validate() {
+    let schema = temp_file(
+        "schema.json",
+        r#"{"type": "object", "required": ["id"], "properties": {"id": {"type": "integer"}}}"#,
+    );
+    let schema = schema.to_str().unwrap();
+
+    let out = json_parser(&["validate", "--schema", schema], r#"{"id": 1}"#);
+    assert_eq!(out.status.code(), Some(0));
+    assert!(out.stdout.is_empty());
+
+    let out = json_parser(&["validate", "--schema", schema], "{\n  \"id\": 1.5\n}");
+    assert_eq!(out.status.code(), Some(1));
+    assert_eq!(
+        String::from_utf8(out.stdout).unwrap(),
+        "-:2:9: /id: Expected integer, found number\n"
+    );
+
+    let ndjson = "{\"id\": 1}\n\n[]\n{\"id\":";
+    let out = json_parser(&["validate", "--schema", schema, "--ndjson", "-"], ndjson);
+    assert_eq!(out.status.code(), Some(1));
+    assert_eq!(
+        String::from_utf8(out.stdout).unwrap(),
+        "-:3:1: Expected object, found array\n\
+         -:4: Unexpected end of input, expected value\n"
+    );
+
+    let out = json_parser(&["validate"], "{}");
+    assert_eq!(out.status.code(), Some(2));
+}
+
+#[test]
+fn codegen_and_sample() {
+    let schema = temp_file(
+        "tools.json",
+        r#"{"type": "object", "required": ["id"], "properties": {
+            "id": {"type": "integer", "minimum": 3},
+            "home-page": {"type": "string", "maxLength": 5}
+        }}"#,
+    );
+    let schema = schema.to_str().unwrap();
+
+    let out = json_parser(&["codegen", "--schema", schema, "--name", "user"], "");
+    assert_eq!(out.status.code(), Some(0));
+    assert_eq!(
+        String::from_utf8(out.stdout).unwrap(),
+        "#[derive(Debug, Clone, PartialEq, json_parser::FromJson)]\n\
+         pub struct User {\n    \
+             #[json(rename = \"home-page\")]\n    \
+             pub home_page: Option<String>,\n    \
+             pub id: i64,\n\
+         }\n"
+    );
+
+    let out = json_parser(&["sample", "--schema", schema], "");
+    assert_eq!(out.status.code(), Some(0));
+    let sample = String::from_utf8(out.stdout).unwrap();
+    let out = json_parser(&["validate", "--schema", schema], &sample);
+    assert_eq!(out.status.code(), Some(0), "{}", sample);
+}
+
+#[test]
+fn query() {
+    let users = r#"{"users": {"ann": {"id": 1, "tags": ["a"]}, "bob": {"id": 2}}}"#;
+    let out = json_parser(&["query", "/users/*/id"], users);
+    assert_eq!(out.status.code(), Some(0));
+    assert_eq!(String::from_utf8(out.stdout).unwrap(), "1\n2\n");
+
+    let out = json_parser(&["query", "--arg", "who", "bob", "/users/$who"], users);
+    assert_eq!(String::from_utf8(out.stdout).unwrap(), "{\"id\":2}\n");
+    let out = json_parser(
+        &["query", "--argjson", "i", "0", "/users/ann/tags/$i", "-"],
+        users,
+    );
+    assert_eq!(String::from_utf8(out.stdout).unwrap(), "\"a\"\n");
+
+    let out = json_parser(&["query", "/users/carl"], users);
+    assert_eq!(out.status.code(), Some(1));
+    let out = json_parser(&["query", "/users/$who"], users);
+    assert_eq!(out.status.code(), Some(2));
+    let out = json_parser(&["query", "--argjson", "who", "[1]", "/users/$who"], users);
+    assert_eq!(out.status.code(), Some(2));
+}
+
+#[test]
+fn output_formats() {
+    let people = r#"[{"name": "Ann", "age": 31, "tags": ["a"]}, {"name": "O'Neil, Bob"}]"#;
+    let stdout = |args: &[&str]| {
+        let out = json_parser(args, people);
+        assert_eq!(out.status.code(), Some(0));
+        String::from_utf8(out.stdout).unwrap()
+    };
+    assert_eq!(
+        stdout(&["query", "/*/name", "--output", "raw"]),
+        "Ann\nO'Neil, Bob\n"
+    );
+    assert_eq!(
+        stdout(&["query", "/0", "--output", "ndjson"]),
+        "{\"age\":31,\"name\":\"Ann\",\"tags\":[\"a\"]}\n"
+    );
+    assert_eq!(
+        stdout(&["query", "", "--output", "csv"]),
+        "age,name,tags\n31,Ann,\"[\"\"a\"\"]\"\n,\"O'Neil, Bob\",\n"
+    );
+    assert_eq!(
+        stdout(&["query", "", "--output", "yaml"]),
+        "-\n  age: 31\n  name: Ann\n  tags:\n    - a\n-\n  name: O'Neil, Bob\n"
+    );
+    assert_eq!(stdout(&["query", "/*/age", "--output", "json"]), "31\n");
+
+    let out = json_parser(&["query", "", "--output", "xml"], people);
+    assert_eq!(out.status.code(), Some(2));
+}
+
+#[test]
+fn in_place_edits() {
+    let out = json_parser(&["fmt", "--indent", "4"], r#"{"b": [1, 2.50], "a": {}}"#);
+    assert_eq!(
+        String::from_utf8(out.stdout).unwrap(),
+        "{\n    \"b\": [\n        1,\n        2.50\n    ],\n    \"a\": {}\n}\n"
+    );
+    let out = json_parser(&["sort-keys"], r#"{"b": 1, "a": {"d": 2, "c": 3}}"#);
+    assert_eq!(
+        String::from_utf8(out.stdout).unwrap(),
+        "{\n  \"a\": {\n    \"c\": 3,\n    \"d\": 2\n  },\n  \"b\": 1\n}\n"
+    );
+
+    let original = "{\n  // build\n  \"scripts\": {\n    \"test\": \"jest\"\n  }\n}\n";
+    let file = temp_file("package.json", original);
+    let path = file.to_str().unwrap();
+
+    let out = json_parser(&["set", "--dry-run", "scripts.build", "\"tsc\"", path], "");
+    assert_eq!(out.status.code(), Some(1));
+    assert_eq!(
+        String::from_utf8(out.stdout).unwrap(),
+        format!(
+            "--- {0}\n+++ {0}\n@@ -1,6 +1,7 @@\n {{\n   // build\n   \"scripts\": {{\n\
+             -    \"test\": \"jest\"\n+    \"test\": \"jest\",\n+    \"build\": \"tsc\"\n   }}\n }}\n",
+            path
+        )
+    );
+    assert_eq!(std::fs::read_to_string(&file).unwrap(), original);
+
+    let out = json_parser(
+        &["set", "--in-place=.bak", "scripts.build", "\"tsc\"", path],
+        "",
+    );
+    assert_eq!(out.status.code(), Some(0));
+    assert!(out.stdout.is_empty());
+    assert!(std::fs::read_to_string(&file)
+        .unwrap()
+        .contains("\"build\": \"tsc\""));
+    assert_eq!(
+        std::fs::read_to_string(format!("{}.bak", path)).unwrap(),
+        original
+    );
+
+    let out = json_parser(&["del", "--in-place", "scripts.build", path], "");
+    assert_eq!(out.status.code(), Some(0));
+    assert_eq!(std::fs::read_to_string(&file).unwrap(), original);
+    let out = json_parser(&["del", "--dry-run", "scripts.build", path], "");
+    assert_eq!(out.status.code(), Some(0));
+    assert!(out.stdout.is_empty());
+
+    let out = json_parser(&["set", "a", "not json"], "{}");
+    assert_eq!(out.status.code(), Some(2));
+    let out = json_parser(&["fmt", "--in-place", "-"], "{}");
+    assert_eq!(out.status.code(), Some(2));
+}
+
+#[test]
+fn pipelines() {
+    let events = "{\"id\": 1}\n\n{\"id\": 2}\n";
+    let out = json_parser(&["query", "--slurp", "/*/id", "-"], events);
+    assert_eq!(String::from_utf8(out.stdout).unwrap(), "1\n2\n");
+    let out = json_parser(&["query", "-s", "", "--output", "ndjson"], events);
+    assert_eq!(
+        String::from_utf8(out.stdout).unwrap(),
+        "[{\"id\":1},{\"id\":2}]\n"
+    );
+    let out = json_parser(&["query", "/id"], events);
+    assert_eq!(out.status.code(), Some(1));
+
+    let out = json_parser(
+        &[
+            "query",
+            "--null-input",
+            "--argjson",
+            "cfg",
+            r#"{"db": {"port": 5432}}"#,
+            "$cfg/db",
+            "--output",
+            "yaml",
+        ],
+        "",
+    );
+    assert_eq!(out.status.code(), Some(0));
+    assert_eq!(String::from_utf8(out.stdout).unwrap(), "port: 5432\n");
+    let out = json_parser(&["query", "-n", ""], "");
+    assert_eq!(String::from_utf8(out.stdout).unwrap(), "null\n");
+    let out = json_parser(&["query", "-n", "", "file.json"], "");
+    assert_eq!(out.status.code(), Some(2));
+}
+
+#[test]
+fn parallel_files() {
+    let schema = temp_file("jobs-schema.json", r#"{"type": "object"}"#);
+    let good = temp_file("jobs-good.json", "{\n  \"a\": [\n    1\n  ]\n}\n");
+    let bad = temp_file("jobs-bad.json", "[1,2]");
+    let (schema, good, bad) = (
+        schema.to_str().unwrap(),
+        good.to_str().unwrap(),
+        bad.to_str().unwrap(),
+    );
+    let mut files: Vec<&str> = [good, bad].repeat(20);
+    files.push("missing.json");
+
+    let mut args = vec!["validate", "--schema", schema, "--jobs", "4"];
+    args.extend(&files);
+    let out = json_parser(&args, "");
+    assert_eq!(out.status.code(), Some(3));
+    let stdout = String::from_utf8(out.stdout).unwrap();
+    let expected = format!("{}:1:1: Expected object, found array\n", bad).repeat(20);
+    assert_eq!(stdout, expected);
+    let stderr = String::from_utf8(out.stderr).unwrap();
+    assert!(
+        stderr.ends_with("41 files: 20 invalid, 1 unreadable\n"),
+        "{}",
+        stderr
+    );
+
+    let out = json_parser(&["fmt", "--check", "--jobs", "0", good, bad], "");
+    assert_eq!(out.status.code(), Some(1));
+    assert_eq!(String::from_utf8(out.stdout).unwrap(), format!("{}\n", bad));
+    let out = json_parser(&["fmt", "--check", good], "");
+    assert_eq!(out.status.code(), Some(0));
+
+    let out = json_parser(&["stats", "--jobs", "2", good, bad], "");
+    assert_eq!(out.status.code(), Some(0));
+    assert_eq!(
+        String::from_utf8(out.stdout).unwrap(),
+        format!(
+            "{}: 23 bytes, 3 values, depth 2\n{}: 5 bytes, 3 values, depth 1\n\
+             total: 28 bytes, 6 values, depth 2\n",
+            good, bad
+        )
+    );
+}
+
+#[test]
+fn watch_missing_path() {
+    // fails before waiting for changes, with or without the `watch` feature
+    let missing = temp_file("watched.json", "{}").with_file_name("not-there");
+    let out = json_parser(&["watch", "--validate", missing.to_str().unwrap()], "");
+    assert_eq!(out.status.code(), Some(2));
+    assert!(!out.stderr.is_empty());
+}
+
+#[test]
+fn config_file() {
+    let config = temp_file(
+        "project/.jsonparser.toml",
+        "# shared settings\nindent = 4\nsort-keys = true # fmt too\nallow_comments = true\n\
+         ignore = [\n  'build/**',\n  \"*.gen.json\",\n]\n",
+    );
+    let project = config.parent().unwrap();
+    let sub = project.join("sub");
+    std::fs::create_dir_all(&sub).unwrap();
+
+    // found from a subdirectory
+    let out = json_parser_in(&sub, &["fmt"], r#"{"b": 1, "a": [true]}"#);
+    assert_eq!(out.status.code(), Some(0));
+    assert_eq!(
+        String::from_utf8(out.stdout).unwrap(),
+        "{\n    \"a\": [\n        true\n    ],\n    \"b\": 1\n}\n"
+    );
+    let out = json_parser_in(&sub, &["fmt", "--indent", "1"], "[1]");
+    assert_eq!(String::from_utf8(out.stdout).unwrap(), "[\n 1\n]\n");
+
+    let out = json_parser_in(&sub, &["stats"], "// counted\n[1]");
+    assert_eq!(out.status.code(), Some(0));
+    // fmt would lose the comment
+    let out = json_parser_in(&sub, &["fmt"], "// kept\n[1]");
+    assert_eq!(out.status.code(), Some(1));
+
+    let other = temp_file(
+        "other/json_parser.config.json",
+        r#"{"indent": 2, "colour": true}"#,
+    );
+    let out = json_parser_in(other.parent().unwrap(), &["stats"], "[]");
+    assert_eq!(out.status.code(), Some(2));
+    let stderr = String::from_utf8(out.stderr).unwrap();
+    assert!(stderr.contains("unknown setting colour"), "{}", stderr);
+}
+
+#[test]
+fn directories() {
+    let ignore = temp_file("repo/.gitignore", "node_modules/\n*.log.json\n");
+    let repo = ignore.parent().unwrap();
+    std::fs::create_dir_all(repo.join(".git")).unwrap();
+    temp_file("repo/a.json", r#"{"b":1}"#);
+    temp_file("repo/notes.jsonc", "[]");
+    temp_file("repo/node_modules/pkg.json", "[");
+    temp_file("repo/x.log.json", "[");
+    temp_file("repo/vendor/v.json", "[");
+    temp_file("repo/sub/.jsonparserignore", "generated.json\n");
+    temp_file("repo/sub/generated.json", "[");
+    temp_file("repo/sub/y.log.json", "[");
+    temp_file("repo/sub/c.json", "[]\n");
+
+    let out = json_parser_in(repo, &["fmt", "--check", "--exclude", "vendor", "."], "");
+    assert_eq!(out.status.code(), Some(1));
+    assert_eq!(String::from_utf8(out.stdout).unwrap(), "./a.json\n");
+
+    let out = json_parser_in(repo, &["stats", "--include", "*.jsonc", "."], "");
+    assert_eq!(out.status.code(), Some(0));
+    assert_eq!(
+        String::from_utf8(out.stdout).unwrap(),
+        "./notes.jsonc: 2 bytes, 1 values, depth 0\n"
+    );
+
+    // the .gitignore above still applies
+    let sub = repo.join("sub");
+    let out = json_parser_in(&sub, &["stats", "."], "");
+    assert_eq!(out.status.code(), Some(0));
+    assert_eq!(
+        String::from_utf8(out.stdout).unwrap(),
+        "./c.json: 3 bytes, 1 values, depth 0\n"
+    );
+
+    // files named on the command line are always used
+    let out = json_parser_in(&sub, &["stats", "generated.json"], "");
+    assert_eq!(out.status.code(), Some(1));
+}
+
+#[test]
+fn exit_codes_and_summary() {
+    let good = temp_file("summary-good.json", "[]\n");
+    let changes = temp_file("summary-changes.json", "[ ]");
+    let missing = good.with_file_name("summary-missing.json");
+    let (good, changes, missing) = (
+        good.to_str().unwrap(),
+        changes.to_str().unwrap(),
+        missing.to_str().unwrap(),
+    );
+
+    let out = json_parser(&["fmt", "--check", good], "");
+    assert_eq!(out.status.code(), Some(0));
+    let out = json_parser(&["fmt", "--check", good, changes], "");
+    assert_eq!(out.status.code(), Some(1));
+    let out = json_parser(&["fmt", "--check", "--bogus"], "");
+    assert_eq!(out.status.code(), Some(2));
+    let out = json_parser(&["fmt", "--check", good, changes, missing], "");
+    assert_eq!(out.status.code(), Some(3));
+
+    let out = json_parser(&["fmt", "--check", "--summary", "json", good, changes], "");
+    assert_eq!(out.status.code(), Some(1));
+    let summary = String::from_utf8(out.stderr).unwrap();
+    let expected = format!(
+        r#"{{"command":"fmt","counts":{{"ok":1,"would change":1}},"files":[{{"file":"{}","result":"ok"}},{{"file":"{}","result":"would change"}}],"status":1}}"#,
+        good, changes
+    );
+    assert_eq!(summary, format!("{}\n", expected));
+
+    let out = json_parser(&["validate", "--summary", "json", "--schema", missing], "");
+    assert_eq!(out.status.code(), Some(3));
+    let stderr = String::from_utf8(out.stderr).unwrap();
+    assert!(
+        stderr.ends_with(&format!(
+            "{{\"command\":\"validate\",\"error\":\"Can't read {}: No such file or directory (os error 2)\",\"status\":3}}\n",
+            missing
+        )),
+        "{}",
+        stderr
+    );
+}
+
+#[test]
+fn join() {
+    let a = temp_file(
+        "join/a.json",
+        r#"{"db": {"host": "a", "port": 1}, "tags": [1]}"#,
+    );
+    let b = temp_file("join/b.json", r#"{"db": {"host": "b"}, "tags": [2]}"#);
+    let list = temp_file("join/list.json", "[3]");
+    let (a, b, list) = (
+        a.to_str().unwrap(),
+        b.to_str().unwrap(),
+        list.to_str().unwrap(),
+    );
+
+    let out = json_parser(&["join", "--output", "ndjson", list, "-"], "4");
+    assert_eq!(out.status.code(), Some(0));
+    assert_eq!(String::from_utf8(out.stdout).unwrap(), "[[3],4]\n");
+    let out = json_parser(&["join", "--concat", "--output", "ndjson", list, list], "");
+    assert_eq!(String::from_utf8(out.stdout).unwrap(), "[3,3]\n");
+    let out = json_parser(&["join", "--merge", "--output", "ndjson", a, b], "");
+    assert_eq!(out.status.code(), Some(0));
+    assert_eq!(
+        String::from_utf8(out.stdout).unwrap(),
+        "{\"db\":{\"host\":\"b\",\"port\":1},\"tags\":[1,2]}\n"
+    );
+
+    let out = json_parser(&["join", "--merge", a, list], "");
+    assert_eq!(out.status.code(), Some(1));
+    let stderr = String::from_utf8(out.stderr).unwrap();
+    assert!(
+        stderr.contains(&format!("{} is not an object", list)),
+        "{}",
+        stderr
+    );
+}
+
+#[test]
+fn sort_lines() {
+    let a = temp_file("sort-lines/a.ndjson", "{\"t\": 2}\n{\"t\": 4}");
+    let b = temp_file("sort-lines/b.ndjson", "{\"t\": 3}\n{\"t\": 1}\n");
+    let (a, b) = (a.to_str().unwrap(), b.to_str().unwrap());
+    let out = json_parser(&["sort-lines", "--key", "/t", a, b], "");
+    assert_eq!(out.status.code(), Some(0));
+    assert_eq!(
+        String::from_utf8(out.stdout).unwrap(),
+        "{\"t\": 1}\n{\"t\": 2}\n{\"t\": 3}\n{\"t\": 4}\n"
+    );
+    let out = json_parser(&["sort-lines", "--reverse", "-"], "1\n3\n2\n");
+    assert_eq!(String::from_utf8(out.stdout).unwrap(), "3\n2\n1\n");
+
+    let out = json_parser(&["sort-lines"], "1\n{\n");
+    assert_eq!(out.status.code(), Some(1));
+    let out = json_parser(&["sort-lines", "--memory", "none"], "");
+    assert_eq!(out.status.code(), Some(2));
+}
+
+#[test]
+fn sort_lines_progress() {
+    let file = temp_file("sort-lines/progress.ndjson", "3\n1\n2\n");
+    let out = json_parser(&["sort-lines", "--progress", file.to_str().unwrap()], "");
+    assert_eq!(out.status.code(), Some(0));
+    assert_eq!(String::from_utf8(out.stdout).unwrap(), "1\n2\n3\n");
+    let stderr = String::from_utf8(out.stderr).unwrap();
+    assert!(
+        stderr.ends_with("sort-lines: 6 bytes, 3 records, 100%, 0s left\n"),
+        "{}",
+        stderr
+    );
+}
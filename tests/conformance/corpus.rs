@@ -0,0 +1,130 @@
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::{fs, panic, thread};
+
+use json_parser::parse;
+
+// Cases are named like JSONTestSuite's: `y_` inputs must parse, `n_` inputs
+// must be rejected and `i_` inputs may go either way, but nothing may panic.
+// Set `JSON_TEST_SUITE` to a checkout of https://github.com/nst/JSONTestSuite
+// to run its full corpus as well as the cases kept here.
+
+#[derive(Debug, PartialEq)]
+enum Outcome {
+    Accepted,
+    Rejected,
+    Panicked,
+}
+
+/// Runs `f` on a big stack so deeply nested cases fail a test rather than
+/// abort it, returning `None` if it panicked.
+pub fn catch<T: Send + 'static>(f: impl FnOnce() -> T + Send + 'static) -> Option<T> {
+    thread::Builder::new()
+        .stack_size(512 * 1024 * 1024)
+        .spawn(move || panic::catch_unwind(panic::AssertUnwindSafe(f)).ok())
+        .unwrap()
+        .join()
+        .unwrap()
+}
+
+/// Corpus files, by name, with duplicates from the full suite left out.
+pub fn cases() -> Vec<(String, Vec<u8>)> {
+    let mut dirs =
+        vec![Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/conformance/test_parsing")];
+    if let Some(suite) = std::env::var_os("JSON_TEST_SUITE") {
+        dirs.push(PathBuf::from(suite).join("test_parsing"));
+    }
+    let mut paths = Vec::new();
+    for dir in dirs {
+        for entry in fs::read_dir(&dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.extension().is_some_and(|e| e == "json") {
+                paths.push(path);
+            }
+        }
+    }
+    paths.sort();
+    let mut seen = BTreeSet::new();
+    paths
+        .into_iter()
+        .filter_map(|path| {
+            let name = path.file_name().unwrap().to_string_lossy().into_owned();
+            seen.insert(name.clone())
+                .then(|| (name, fs::read(&path).unwrap()))
+        })
+        .collect()
+}
+
+/// Case names listed in `tests/conformance/<file>`.
+pub fn known(file: &str) -> BTreeSet<String> {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/conformance")
+        .join(file);
+    fs::read_to_string(path)
+        .unwrap()
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty() && !l.starts_with('#'))
+        .map(String::from)
+        .collect()
+}
+
+/// Fails on a case that is wrong but not listed in `file`, or listed but
+/// now right.
+pub fn check(file: &str, results: Vec<(String, Option<String>)>) {
+    let known = known(file);
+    let mut unexpected = Vec::new();
+    let mut fixed = Vec::new();
+    for (name, problem) in results {
+        match (problem, known.contains(&name)) {
+            (Some(problem), false) => unexpected.push(format!("{} ({})", name, problem)),
+            (None, true) => fixed.push(name),
+            _ => {}
+        }
+    }
+    assert!(
+        unexpected.is_empty(),
+        "unexpected results:\n  {}",
+        unexpected.join("\n  ")
+    );
+    assert!(
+        fixed.is_empty(),
+        "now passing, remove from {}:\n  {}",
+        file,
+        fixed.join("\n  ")
+    );
+}
+
+/// Silences the default panic message while `f` runs, since panics are
+/// reported as results.
+pub fn quietly<T>(f: impl FnOnce() -> T) -> T {
+    let hook = panic::take_hook();
+    panic::set_hook(Box::new(|_| {}));
+    let res = f();
+    panic::set_hook(hook);
+    res
+}
+
+#[test]
+fn json_test_suite() {
+    let results = quietly(|| {
+        cases()
+            .into_iter()
+            .map(|(name, input)| {
+                let outcome = match catch(move || parse(&input).is_ok()) {
+                    Some(true) => Outcome::Accepted,
+                    Some(false) => Outcome::Rejected,
+                    None => Outcome::Panicked,
+                };
+                let ok = match &name[..2] {
+                    "y_" => outcome == Outcome::Accepted,
+                    "n_" => outcome == Outcome::Rejected,
+                    _ => outcome != Outcome::Panicked,
+                };
+                let problem = (!ok).then(|| format!("{:?}", outcome));
+                (name, problem)
+            })
+            .collect()
+    });
+    check("known_failures.txt", results);
+}
@@ -0,0 +1,57 @@
+use json_parser::{parse, JSONValue};
+use proptest::prelude::*;
+
+use crate::common::arb_value;
+use crate::corpus::{self, catch};
+
+fn from_serde(v: serde_json::Value) -> JSONValue {
+    match v {
+        serde_json::Value::Null => JSONValue::Null,
+        serde_json::Value::Bool(b) => JSONValue::Bool(b),
+        serde_json::Value::Number(n) => JSONValue::Num(n.as_f64().unwrap_or(f64::NAN)),
+        serde_json::Value::String(s) => JSONValue::Str(s),
+        serde_json::Value::Array(a) => JSONValue::Array(a.into_iter().map(from_serde).collect()),
+        serde_json::Value::Object(o) => {
+            JSONValue::Dict(o.into_iter().map(|(k, v)| (k, from_serde(v))).collect())
+        }
+    }
+}
+
+/// How this crate's result for `input` differs from serde_json's, if it does.
+pub fn divergence(input: &[u8]) -> Option<String> {
+    let owned = input.to_vec();
+    let ours = match catch(move || parse(&owned).map_err(|e| e.to_string())) {
+        Some(res) => res,
+        None => return Some("panicked".into()),
+    };
+    let theirs = serde_json::from_slice(input).map(from_serde);
+    match (ours, theirs) {
+        (Ok(a), Ok(b)) if a == b => None,
+        (Ok(a), Ok(b)) => Some(format!("{} != serde_json's {}", a, b)),
+        (Err(_), Err(_)) => None,
+        (Ok(_), Err(e)) => Some(format!("accepted, serde_json: {}", e)),
+        (Err(e), Ok(_)) => Some(format!("rejected ({}), serde_json accepted", e)),
+    }
+}
+
+#[test]
+fn corpus_against_serde_json() {
+    let results = corpus::quietly(|| {
+        corpus::cases()
+            .into_iter()
+            .map(|(name, input)| {
+                let problem = divergence(&input);
+                (name, problem)
+            })
+            .collect()
+    });
+    corpus::check("known_divergences.txt", results);
+}
+
+proptest! {
+    #[test]
+    fn serialized_against_serde_json(v in arb_value()) {
+        let problem = divergence(v.to_json_pretty().as_bytes());
+        prop_assert!(problem.is_none(), "{:?}", problem);
+    }
+}
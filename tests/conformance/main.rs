@@ -0,0 +1,11 @@
+//! Conformance tests: a JSONTestSuite-style corpus, property-based round
+//! trips through the serializer, differential testing against serde_json,
+//! checks that no input makes the parser panic, and the command line tool.
+
+mod cli;
+#[path = "../common/mod.rs"]
+mod common;
+mod corpus;
+mod differential;
+mod no_panic;
+mod roundtrip;
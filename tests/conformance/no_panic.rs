@@ -0,0 +1,279 @@
+use std::io::Cursor;
+use std::time::Duration;
+use std::{panic, thread};
+
+use json_parser::lexer::{self, TextEdit};
+use json_parser::{
+    anonymize, decode, dedup_lines, expected_next, highlight, merge3, outline, parse,
+    parse_array_of, parse_auto, parse_object, parse_prefix, parse_shards, parse_with_comments,
+    parse_with_deadline, parse_with_hooks, parse_with_metrics, parse_with_options,
+    parse_with_warnings, profile_records, sample_lines, sort_lines, transform_at, truncate,
+    AnonymizeOptions, BTreeMapKind, Bundle, ColumnBatch, ConfigEditor, CrdtValue, Document,
+    EditSession, IncrementalParser, JSONValue, LengthPrefixedReader, MergeStrategy, OpenApi,
+    ParseHooks, ParseMetrics, ParseOptions, Parser, Patch, PathPattern, ProtoAny, Rfc3339, Schema,
+    SharedValue, SortOptions, SseReader, TruncateOptions, Value, ValuePool, DEFAULT_MAX_DEPTH,
+};
+use proptest::prelude::*;
+
+use crate::corpus::quietly;
+
+// Fragments that combine into inputs close enough to JSON to get past the
+// lexer and exercise the parser.
+const FRAGMENTS: &[&str] = &[
+    "{", "}", "[", "]", ",", ":", " ", "\n", "\"", "\"a\"", "\\", "\\u", "d83d", "\\n", "0", "-",
+    "1", ".", "e", "E", "+", "true", "fals", "null", "//", "/*", "*/", "\u{e9}", "\u{0}",
+];
+
+fn json_ish() -> impl Strategy<Value = Vec<u8>> {
+    prop::collection::vec(prop::sample::select(FRAGMENTS), 0..512)
+        .prop_map(|parts| parts.concat().into_bytes())
+}
+
+// Around 100k levels of nesting built from a few openers, closed part of
+// the way and followed by more fragments.
+fn deeply_nested() -> impl Strategy<Value = Vec<u8>> {
+    (
+        prop::collection::vec(
+            prop::sample::select(&["[", "{\"a\":", " [ ", "[{\"\":"][..]),
+            1..4,
+        ),
+        90_000..110_000usize,
+        0..1_000usize,
+        json_ish(),
+    )
+        .prop_map(|(openers, depth, closed, tail)| {
+            let mut input = Vec::new();
+            for i in 0..depth {
+                input.extend_from_slice(openers[i % openers.len()].as_bytes());
+            }
+            input.extend(b"]}".iter().cycle().take(closed));
+            input.extend(tail);
+            input
+        })
+}
+
+struct NoMetrics;
+
+impl ParseMetrics for NoMetrics {}
+
+// Runs every public entry point that takes untrusted text over `input`, and
+// everything that takes a parsed value over what it parses to.
+fn parse_everything(input: Vec<u8>) {
+    let text = String::from_utf8_lossy(&input).into_owned();
+    let lenient = ParseOptions {
+        allow_comments: true,
+        allow_control_chars: true,
+        allow_non_finite: true,
+        strip_bom: true,
+        ..ParseOptions::default()
+    };
+
+    if let Ok(v) = parse(&input) {
+        use_value(v);
+    }
+    let _ = parse_with_options(&input, &lenient);
+    let mut hooks = ParseHooks::new()
+        .on_key(Ok)
+        .on_string(|s| Ok(JSONValue::Str(s)))
+        .on_number(|n| Ok(JSONValue::Num(n)))
+        .on_warning(drop);
+    let _ = parse_with_hooks(&input, &mut hooks);
+    let _ = parse_with_warnings(&input, &lenient);
+    let _ = parse_with_metrics(&input, &lenient, &NoMetrics);
+    let _ = parse_with_deadline(&input, Duration::from_secs(60));
+    let _ = ValuePool::new().parse(&input);
+    let _ = Value::<String, String, BTreeMapKind>::parse(&input);
+    let _ = Value::<String, String, BTreeMapKind>::parse_with_options(&input, &lenient);
+    let _ = decode::<JSONValue>(&input);
+    let _ = decode::<Vec<Option<String>>>(&input);
+    let _ = parse_object(&input);
+    let _ = parse_array_of::<JSONValue>(&input);
+    let schema = br#"{"items": {"enum": [[1], {"a": null}]}, "additionalProperties": false}"#;
+    let _ = Schema::parse(schema).unwrap().validate(&input);
+    let _ = Schema::parse(&input);
+    let _ = json_parser::decode_jwt_claims(&text);
+    let _ = json_parser::decode_jwt_header(&text);
+    if let Ok(payload) = json_parser::jsonrpc::Payload::parse(&input) {
+        let _ = payload.to_json();
+    }
+    let mut framed = b"Content-Length: ".to_vec();
+    framed.extend(input.len().to_string().bytes());
+    framed.extend(b"\r\n\r\n");
+    framed.extend(&input);
+    for message in json_parser::jsonrpc::MessageReader::new(framed.as_slice()).take(4) {
+        let _ = message;
+    }
+    for event in SseReader::new(input.as_slice()).take(64) {
+        let _ = event;
+    }
+    for frame in LengthPrefixedReader::new(input.as_slice()).take(64) {
+        let _ = frame;
+    }
+    if let Ok(bundle) = Bundle::open(input.clone()) {
+        for name in bundle.names() {
+            let _ = bundle.get(name);
+        }
+    }
+    if let Ok(patch) = Patch::parse(&input) {
+        let _ = patch.apply(&mut JSONValue::Array(vec![JSONValue::Null]));
+    }
+    if let Ok(doc) = Document::parse(input.clone()) {
+        let _ = doc.root().to_value();
+    }
+    if let Ok(api) = OpenApi::parse(&input) {
+        let _ = api.operations();
+        let _ = api.dereference(&JSONValue::Null);
+    }
+    let _ = parse_with_comments(&input, &lenient);
+    if let Ok(mut config) = ConfigEditor::new(text.clone()) {
+        for path in ["a", "0", "a.0", "-", "a.b.c"] {
+            let _ = config.set_field_preserving(path, JSONValue::Null);
+        }
+        for path in ["a.0", "0", "a"] {
+            let _ = config.remove_field_preserving(path);
+        }
+    }
+    let _ = JSONValue::from_env_vars("app", [(format!("APP_{}", text), text.clone())]);
+    let _ = Rfc3339::parse(&text);
+    let _ = json_parser::unescape(&input, Default::default());
+    if let Ok(pattern) = PathPattern::new(&text) {
+        transform_at(&mut JSONValue::Array(vec![]), &pattern, |_| {});
+    }
+
+    let _ = parse_prefix(&input);
+    let _ = json_parser::parse_prefix_with_options(&input, &lenient);
+    let _ = expected_next(&input);
+    let _ = outline(&input);
+    let _ = highlight(&input);
+    let mut tokens: Vec<_> = lexer::tokens(&input)
+        .take_while(Result::is_ok)
+        .flatten()
+        .collect();
+    let edit = TextEdit {
+        range: 0..input.len().min(1),
+        new_len: 0,
+    };
+    let _ = lexer::Lexer::new().relex(&mut tokens, input.get(1..).unwrap_or(&[]), &edit);
+    let mut parser = Parser::new();
+    for t in lexer::tokens(&input) {
+        let Ok(t) = t else { break };
+        if parser.step(t.token, &input).is_err() {
+            break;
+        }
+    }
+    // compact: indenting 100k levels writes output quadratic in the depth
+    let compact = json_parser::ReformatStyle { indent: None };
+    let _ = json_parser::reformat(input.as_slice(), std::io::sink(), &compact);
+    if let Err(e) = parse(&input) {
+        let _ = json_parser::explain_error(&input, &e);
+    }
+    let _ = json_parser::split(input.as_slice(), "", 2, |_, _| Ok(()));
+
+    if let Ok(records) = parse_auto(input.as_slice()) {
+        records.take(64).for_each(drop);
+    }
+    let _ = sample_lines(input.as_slice(), 2, 7);
+    if let Ok(dedup) = dedup_lines(input.as_slice(), Some("/a")) {
+        dedup.take(64).for_each(drop);
+    }
+    let _ = profile_records(input.as_slice());
+    let _ = sort_lines(input.as_slice(), std::io::sink(), &SortOptions::default());
+    let _ = parse_shards(&[&input, &input], MergeStrategy::Concat, &lenient);
+    let mut records = json_parser::spawn_records(Cursor::new(input.clone()), 4);
+    records.by_ref().take(64).for_each(drop);
+
+    if let Ok(state) = parse(&input) {
+        if let Ok(mut resumed) = IncrementalParser::resume(&state) {
+            let _ = resumed.feed(b"1]}]");
+            let _ = resumed.current();
+        }
+        let _ = CrdtValue::from_json(&state);
+        let _ = ProtoAny::from_json(&state);
+    }
+    let mut incremental = IncrementalParser::new();
+    for chunk in input.chunks(input.len() / 8 + 1) {
+        if incremental.feed(chunk).is_err() {
+            break;
+        }
+        let _ = incremental.current();
+    }
+    let _ = incremental.checkpoint();
+}
+
+// Everything that walks a value, over one `parse` accepted (so nested up to
+// `DEFAULT_MAX_DEPTH`).
+fn use_value(v: JSONValue) {
+    let _ = v.to_json_string();
+    let _ = v.to_json_pretty();
+    let _ = v.to_tree_string();
+    let _ = format!("{:?}", v);
+    let _ = truncate(&v, &TruncateOptions::default());
+    let _ = merge3(&v, &v, &JSONValue::Null);
+    let _ = v.pointer("/0/a");
+    let _ = v.as_vec_of::<JSONValue>();
+    let mut anonymized = v.clone();
+    anonymize(&mut anonymized, &AnonymizeOptions::default());
+    let _ = ColumnBatch::from_records([v.clone()]);
+    let shared = SharedValue::from(v.clone());
+    let _ = shared.fork().to_value();
+    let mut session = EditSession::new(v.clone());
+    let _ = session.undo();
+    let _ = CrdtValue::new(&v, 1).to_json();
+    let _ = v.to_env_vars("app");
+}
+
+// Runs `f` on the 2 MiB stack threads get by default (`catch` uses a big one
+// for the corpus), so unbounded recursion aborts the test instead of
+// passing. `None` if it panicked.
+fn on_default_stack(f: impl FnOnce() + Send + 'static) -> Option<()> {
+    thread::Builder::new()
+        .stack_size(2 * 1024 * 1024)
+        .spawn(move || panic::catch_unwind(panic::AssertUnwindSafe(f)).ok())
+        .unwrap()
+        .join()
+        .unwrap()
+}
+
+fn assert_no_panic(input: Vec<u8>) -> Result<(), TestCaseError> {
+    let shown: String = String::from_utf8_lossy(&input).chars().take(200).collect();
+    let finished = quietly(|| on_default_stack(move || parse_everything(input)));
+    prop_assert!(finished.is_some(), "panicked on {:?}", shown);
+    Ok(())
+}
+
+proptest! {
+    #[test]
+    fn arbitrary_bytes(input in prop::collection::vec(any::<u8>(), 0..4096)) {
+        assert_no_panic(input)?;
+    }
+
+    #[test]
+    fn json_fragments(input in json_ish()) {
+        assert_no_panic(input)?;
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(8))]
+
+    #[test]
+    fn deeply_nested_fragments(input in deeply_nested()) {
+        assert_no_panic(input)?;
+    }
+}
+
+#[test]
+fn deep_nesting() {
+    for (open, close) in [("[", "]"), ("{\"a\":", "}"), ("[{\"a\":", "}]")] {
+        let levels = DEFAULT_MAX_DEPTH / close.len();
+        let inputs = [
+            open.repeat(100_000),
+            // as deep as `parse` allows, then closed
+            format!("{}1{}", open.repeat(levels), close.repeat(levels)),
+        ];
+        for input in inputs {
+            let input = input.into_bytes();
+            assert!(on_default_stack(move || parse_everything(input)).is_some());
+        }
+    }
+}
@@ -0,0 +1,123 @@
+use std::collections::HashMap;
+
+use json_parser::{
+    decode, merge3, parse, parse_shards, parse_with_options, BigNumbers, Document,
+    IncrementalParser, JSONValue, MergeStrategy, ParseOptions, Schema, SerializeOptions,
+};
+use proptest::prelude::*;
+
+use crate::common::arb_value;
+
+proptest! {
+    #[test]
+    fn compact(v in arb_value()) {
+        prop_assert_eq!(parse(v.to_json_string().as_bytes()).unwrap(), v);
+    }
+
+    #[test]
+    fn pretty(v in arb_value()) {
+        prop_assert_eq!(parse(v.to_json_pretty().as_bytes()).unwrap(), v);
+    }
+
+    #[test]
+    fn escaped(v in arb_value()) {
+        let options = SerializeOptions {
+            ascii_only: true,
+            html_safe: true,
+            ..SerializeOptions::default()
+        };
+        prop_assert_eq!(parse(v.to_json_string_with(&options).as_bytes()).unwrap(), v);
+    }
+
+    #[test]
+    fn exact(v in arb_value()) {
+        // what the serializer writes for an f64 always parses back exactly
+        let options = ParseOptions {
+            big_numbers: BigNumbers::Error,
+            ..ParseOptions::default()
+        };
+        let parsed = parse_with_options(v.to_json_string().as_bytes(), &options).unwrap();
+        prop_assert_eq!(parsed, v);
+    }
+
+    #[test]
+    fn document(v in arb_value()) {
+        let doc = Document::parse(v.to_json_string()).unwrap();
+        prop_assert_eq!(doc.root().to_value(), v);
+    }
+
+    #[test]
+    fn generic(v in arb_value()) {
+        prop_assert_eq!(JSONValue::parse(v.to_json_string().as_bytes()).unwrap(), v);
+    }
+
+    #[test]
+    fn validated(v in arb_value()) {
+        let mut schema = HashMap::new();
+        schema.insert("const".to_string(), v.clone());
+        let schema = Schema::compile(&JSONValue::Dict(schema)).unwrap();
+        prop_assert_eq!(schema.validate(v.to_json_string().as_bytes()).unwrap(), vec![]);
+    }
+
+
+
+
+
+
+    #[test]
+    fn decoded(v in arb_value()) {
+        prop_assert_eq!(decode::<JSONValue>(v.to_json_string().as_bytes()).unwrap(), v);
+    }
+
+    #[test]
+    fn merged_one_side(base in arb_value(), edited in arb_value()) {
+        let merged = merge3(&base, &edited, &base);
+        prop_assert!(merged.is_clean());
+        prop_assert_eq!(&merged.value, &edited);
+        prop_assert_eq!(merge3(&base, &base, &edited), merged);
+    }
+
+
+    #[test]
+    fn resumed(v in arb_value(), cut in any::<prop::sample::Index>()) {
+        let text = v.to_json_pretty();
+        let (first, rest) = text.as_bytes().split_at(cut.index(text.len() + 1));
+        let mut parser = IncrementalParser::new();
+        parser.feed(first).unwrap();
+        let saved = parser.checkpoint().to_json_string();
+        let mut resumed = IncrementalParser::resume(&parse(saved.as_bytes()).unwrap()).unwrap();
+        prop_assert_eq!(resumed.offset(), parser.offset());
+        resumed.feed(rest).unwrap();
+        prop_assert_eq!(resumed.finish(), Some(v));
+    }
+
+
+    #[test]
+    fn sharded(entries in prop::collection::vec(arb_value(), 0..16), size in 1..4usize) {
+        let shards: Vec<_> = entries
+            .chunks(size)
+            .map(|c| JSONValue::Array(c.to_vec()).to_json_string())
+            .collect();
+        let merged = parse_shards(&shards, MergeStrategy::Concat, &ParseOptions::default());
+        prop_assert_eq!(merged.unwrap(), JSONValue::Array(entries));
+    }
+}
+
+#[cfg(feature = "i128")]
+proptest! {
+    #[test]
+    fn wide_integers(i in any::<i128>(), u in any::<u128>()) {
+        use json_parser::{Number, Value};
+
+        let v = Value::<String, Number>::Array(vec![
+            Value::Num(Number::I128(i)),
+            Value::Num(Number::from(u)),
+        ]);
+        let parsed = Value::<String, Number>::parse(v.to_json_string().as_bytes()).unwrap();
+        let Value::Array(entries) = parsed else {
+            panic!("expected an array");
+        };
+        prop_assert_eq!(entries[0].to_json_string(), i.to_string());
+        prop_assert_eq!(entries[1].to_json_string(), u.to_string());
+    }
+}
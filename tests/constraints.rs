@@ -0,0 +1,61 @@
+use json_parser::{parse, parse_with_hooks, JSONValue, NumberConstraint, ParseHooks};
+
+#[test]
+fn constrained_numbers() {
+    let json = br#"{"items": [{"price": 9.5, "qty": 2}, {"price": 120, "qty": 1}]}"#;
+    let parse_with = |hooks: ParseHooks| parse_with_hooks(json, &mut { hooks });
+
+    let positive = NumberConstraint::new().min(0.0);
+    assert_eq!(
+        parse_with(ParseHooks::new().constrain_numbers(positive)).unwrap(),
+        parse(json).unwrap()
+    );
+    assert_eq!(
+        parse_with(ParseHooks::new().constrain_numbers(NumberConstraint::new().max(100.0)))
+            .unwrap_err()
+            .to_string(),
+        "Number 120 at byte 47 is above the maximum of 100"
+    );
+    assert_eq!(
+        parse_with(ParseHooks::new().constrain_numbers(NumberConstraint::new().integer_only()))
+            .unwrap_err()
+            .to_string(),
+        "Number 9.5 at byte 21 is not an integer"
+    );
+    assert_eq!(
+        parse_with(ParseHooks::new().constrain_numbers(NumberConstraint::new().min(1.5)))
+            .unwrap_err()
+            .to_string(),
+        "Number 1 at byte 59 is below the minimum of 1.5"
+    );
+
+    // a pointer-scoped constraint only checks the number there, and reports
+    // where it is
+    let cheap_second = NumberConstraint::new().max(100.0);
+    assert_eq!(
+        parse_with(ParseHooks::new().constrain_number_at("/items/0/price", cheap_second)).unwrap(),
+        parse(json).unwrap()
+    );
+    assert_eq!(
+        parse_with(ParseHooks::new().constrain_number_at("/items/1/price", cheap_second))
+            .unwrap_err()
+            .to_string(),
+        "Number 120 at /items/1/price (byte 47) is above the maximum of 100"
+    );
+    let quantities = NumberConstraint::new().integer_only().min(1.0).max(10.0);
+    assert!(parse_with(
+        ParseHooks::new()
+            .constrain_number_at("/items/0/qty", quantities)
+            .constrain_number_at("/items/1/qty", quantities)
+    )
+    .is_ok());
+
+    // constraints see the number as written, before `on_number`
+    let mut hooks = ParseHooks::new()
+        .constrain_numbers(NumberConstraint::new().max(10.0))
+        .on_number(|n| Ok(JSONValue::Num(n * 100.0)));
+    assert_eq!(
+        parse_with_hooks(b"[1, 2]", &mut hooks).unwrap(),
+        parse(b"[100, 200]").unwrap()
+    );
+}
@@ -0,0 +1,124 @@
+use std::collections::HashMap;
+
+use json_parser::{parse, CrdtValue, JSONValue};
+use proptest::prelude::*;
+
+mod common;
+use common::arb_value;
+
+fn json(text: &str) -> JSONValue {
+    parse(text.as_bytes()).unwrap()
+}
+
+#[test]
+fn concurrent_edits() {
+    let base = json(r#"{"title": "draft", "items": ["a", "c"], "meta": {"k": 1}}"#);
+    let mut a = CrdtValue::new(&base, 1);
+    let mut b = a.fork(2);
+
+    a.set("/title", &json(r#""from a""#)).unwrap();
+    a.insert("/items/1", &json(r#""b""#)).unwrap();
+    a.set("/meta/owner", &json(r#""ann""#)).unwrap();
+    a.remove("/meta/k").unwrap();
+
+    b.set("/title", &json(r#""from b""#)).unwrap();
+    b.set("/items/-", &json(r#""d""#)).unwrap();
+    b.remove("/items/0").unwrap();
+    b.set("/meta/k", &json("2")).unwrap();
+
+    let mut ab = a.clone();
+    ab.merge(&b);
+    let mut ba = b.clone();
+    ba.merge(&a);
+    // both titles were written at the same clock, so the higher replica
+    // wins; both inserts into `items` survive; and `a`'s removal of `k` is
+    // stamped after `b`'s write
+    let expected =
+        json(r#"{"title": "from b", "items": ["b", "c", "d"], "meta": {"owner": "ann"}}"#);
+    assert_eq!(ab.to_value(), expected);
+    assert_eq!(ba.to_value(), expected);
+
+    // an edit made after merging wins over the ones it has seen
+    ba.set("/title", &json(r#""final""#)).unwrap();
+    ab.merge(&ba);
+    assert_eq!(
+        ab.to_value(),
+        json(r#"{"title": "final", "items": ["b", "c", "d"], "meta": {"owner": "ann"}}"#)
+    );
+}
+
+#[test]
+fn concurrent_array_inserts() {
+    let base = json("[1, 4]");
+    let mut a = CrdtValue::new(&base, 1);
+    let mut b = a.fork(2);
+    a.insert("/1", &json("2")).unwrap();
+    b.insert("/1", &json("3")).unwrap();
+    b.set("/-", &json("5")).unwrap();
+    a.merge(&b);
+    // inserts at the same place are ordered by replica
+    assert_eq!(a.to_value(), json("[1, 2, 3, 4, 5]"));
+}
+
+#[test]
+fn state_exchange() {
+    let mut a = CrdtValue::new(&json(r#"{"list": [1]}"#), 7);
+    a.remove("/list/0").unwrap();
+    let state = a.to_json();
+    let restored = CrdtValue::from_json(&json(&state.to_json_string())).unwrap();
+    assert_eq!(restored, a);
+    assert_eq!(restored.replica(), 7);
+    assert_eq!(restored.to_value(), json(r#"{"list": []}"#));
+
+    assert_eq!(
+        a.remove("/list/0").unwrap_err().to_string(),
+        "No value at \"/list/0\""
+    );
+    assert!(CrdtValue::from_json(&json(r#"{"replica": 1}"#)).is_err());
+}
+
+proptest! {
+    #[test]
+    fn replicated(
+        base in arb_value(),
+        edits in prop::collection::vec((any::<bool>(), 0..5u8, any::<u8>(), arb_value()), 0..24),
+    ) {
+        let base = JSONValue::Dict(HashMap::from([
+            ("list".to_string(), JSONValue::Array(vec![base.clone(), base])),
+            ("map".to_string(), JSONValue::Dict(HashMap::new())),
+        ]));
+        let mut a = CrdtValue::new(&base, 1);
+        let mut b = a.fork(2);
+        for (on_a, kind, n, v) in edits {
+            let replica = if on_a { &mut a } else { &mut b };
+            let len = match replica.to_value() {
+                JSONValue::Dict(d) => match &d["list"] {
+                    JSONValue::Array(list) => list.len(),
+                    _ => 0,
+                },
+                _ => 0,
+            };
+            let at = |modulo: usize| format!("/list/{}", n as usize % modulo.max(1));
+            let key = format!("/map/k{}", n % 4);
+            // edits of things that aren't there fail, which is fine
+            let _ = match kind {
+                0 => replica.insert(&at(len + 1), &v),
+                1 => replica.set(&at(len), &v),
+                2 => replica.remove(&at(len)),
+                3 => replica.set(&key, &v),
+                _ => replica.remove(&key),
+            };
+        }
+        let mut ab = a.clone();
+        ab.merge(&b);
+        let mut ba = b.clone();
+        ba.merge(&a);
+        prop_assert_eq!(ab.to_value(), ba.to_value());
+        let merged = ab.to_value();
+        ab.merge(&a);
+        prop_assert_eq!(ab.to_value(), merged);
+        let restored = CrdtValue::from_json(&parse(ab.to_json().to_json_string().as_bytes()).unwrap());
+        prop_assert_eq!(restored.unwrap(), ab);
+    }
+
+}
@@ -0,0 +1,110 @@
+use json_parser::{parse, JSONValue};
+
+fn json(text: &str) -> JSONValue {
+    parse(text.as_bytes()).unwrap()
+}
+
+#[test]
+fn into_and_out_of_containers() {
+    let mut v = json(r#"{"b": [10, {"x": true}], "a": null}"#);
+    let mut c = v.cursor();
+    assert_eq!((c.pointer(), c.depth()), (String::new(), 0));
+
+    // entries are visited in key order
+    assert!(c.down());
+    assert_eq!((c.pointer().as_str(), c.key()), ("/a", Some("a")));
+    assert_eq!(c.focus(), &JSONValue::Null);
+    assert!(c.next_sibling());
+    assert_eq!(c.pointer(), "/b");
+
+    assert!(c.down());
+    assert_eq!(
+        (c.pointer().as_str(), c.index(), c.key()),
+        ("/b/0", Some(0), None)
+    );
+    assert_eq!(c.focus(), &JSONValue::Num(10.0));
+    assert!(c.next_sibling());
+    assert!(c.down_key("x"));
+    assert_eq!((c.pointer().as_str(), c.depth()), ("/b/1/x", 3));
+    assert_eq!(c.focus(), &JSONValue::Bool(true));
+
+    assert!(c.up());
+    assert!(c.up());
+    assert_eq!(c.pointer(), "/b");
+    assert!(c.down_index(1));
+    assert_eq!(c.pointer(), "/b/1");
+    assert!(c.up() && c.up());
+    assert_eq!(c.depth(), 0);
+}
+
+#[test]
+fn siblings() {
+    let mut v = json(r#"{"c": 3, "a": 1, "b": 2}"#);
+    let mut c = v.cursor();
+    assert!(c.down_key("c"));
+    assert!(c.prev_sibling());
+    assert_eq!(c.key(), Some("b"));
+    assert!(c.prev_sibling());
+    assert_eq!(c.key(), Some("a"));
+
+    let mut v = json("[1, 2, 3]");
+    let mut c = v.cursor();
+    assert!(c.down_index(2));
+    assert!(c.prev_sibling() && c.prev_sibling());
+    assert_eq!(c.index(), Some(0));
+    assert!(c.next_sibling());
+    assert_eq!(c.focus(), &JSONValue::Num(2.0));
+}
+
+#[test]
+fn boundaries() {
+    let mut v = json(r#"{"list": [1, 2], "empty": [], "map": {}}"#);
+    let mut c = v.cursor();
+    // the root has no parent or siblings
+    assert!(!c.up());
+    assert!(!c.next_sibling() && !c.prev_sibling());
+
+    assert!(c.down_key("list") && c.down_index(1));
+    // past the end of the array
+    assert!(!c.next_sibling());
+    assert_eq!(c.pointer(), "/list/1");
+    assert!(c.prev_sibling());
+    assert!(!c.prev_sibling());
+    assert_eq!(c.pointer(), "/list/0");
+    // scalars have no children
+    assert!(!c.down());
+    assert!(c.up());
+    assert!(!c.down_index(2));
+    assert!(!c.down_key("list"));
+    assert_eq!(c.pointer(), "/list");
+
+    // empty containers have no children, and the last key no next sibling
+    assert!(c.prev_sibling());
+    assert_eq!(c.key(), Some("empty"));
+    assert!(!c.down());
+    assert!(c.next_sibling() && c.next_sibling());
+    assert_eq!(c.key(), Some("map"));
+    assert!(!c.next_sibling());
+    assert!(!c.down());
+}
+
+#[test]
+fn editing() {
+    let mut v = json(r#"{"users": [{"name": "ann", "pw": "x"}, {"name": "bob"}]}"#);
+    let mut c = v.cursor();
+    assert!(c.down_key("users") && c.down_index(0) && c.down_key("pw"));
+    assert_eq!(c.remove(), Some(JSONValue::Str("x".to_string())));
+    assert_eq!(c.pointer(), "/users/0");
+    assert!(c.next_sibling() && c.down_key("name"));
+    let old = c.replace(JSONValue::Str("BOB".to_string()));
+    assert_eq!(old, JSONValue::Str("bob".to_string()));
+    *c.focus_mut() = JSONValue::Str("Bob".to_string());
+    assert!(c.up() && c.up());
+    assert!(c.down_index(0));
+    assert_eq!(c.remove(), Some(json(r#"{"name": "ann"}"#)));
+
+    let root = c.into_root();
+    assert_eq!(root, &json(r#"{"users": [{"name": "Bob"}]}"#));
+    // the root itself can't be removed
+    assert_eq!(root.cursor().remove(), None);
+}
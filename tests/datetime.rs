@@ -0,0 +1,94 @@
+use json_parser::{parse, parse_with_hooks, JSONValue, ParseHooks, Rfc3339};
+
+#[test]
+fn rfc3339() {
+    let t = Rfc3339::parse("2024-01-31T12:00:00.5+02:00").unwrap();
+    assert_eq!((t.year, t.month, t.day, t.hour), (2024, 1, 31, 12));
+    assert_eq!((t.nanosecond, t.offset_minutes), (500_000_000, 120));
+    assert_eq!(t.to_string(), "2024-01-31T12:00:00.5+02:00");
+    assert_eq!(t.unix_timestamp(), 1_706_695_200);
+    assert_eq!(t.to_utc().unwrap().to_string(), "2024-01-31T10:00:00.5Z");
+    for bad in [
+        "2024-02-30T00:00:00Z",
+        "2024-13-01T00:00:00Z",
+        "2024-01-31T12:00:00",
+    ] {
+        assert_eq!(Rfc3339::parse(bad), None, "{}", bad);
+    }
+
+    let epoch = Rfc3339::from_unix_timestamp(0, 0).unwrap();
+    assert_eq!(epoch.to_string(), "1970-01-01T00:00:00Z");
+    let before = Rfc3339::from_unix_timestamp(-1, 250_000_000).unwrap();
+    assert_eq!(before.to_string(), "1969-12-31T23:59:59.25Z");
+    assert_eq!(
+        Rfc3339::from_unix_timestamp(253_402_300_799, 0)
+            .unwrap()
+            .to_string(),
+        "9999-12-31T23:59:59Z"
+    );
+}
+
+#[test]
+fn out_of_range() {
+    // one second past 9999-12-31T23:59:59Z, and far enough to wrap a u16 year
+    for secs in [253_402_300_800, -62_167_219_201, i64::MAX / 2, i64::MIN / 2] {
+        let err = Rfc3339::from_unix_timestamp(secs, 0).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!("Timestamp {} is outside the years 0000 to 9999", secs)
+        );
+    }
+    assert!(Rfc3339::from_unix_timestamp(0, 1_000_000_000).is_err());
+    let early = Rfc3339::parse("0000-01-01T00:00:00+01:00").unwrap();
+    assert!(early.to_utc().is_err());
+}
+
+#[test]
+fn tagged() {
+    let mut hooks = ParseHooks::new().tag_rfc3339();
+    let v = parse_with_hooks(
+        br#"["2024-01-31T12:00:00+02:00", "0000-01-01T00:00:00+01:00", "noon"]"#,
+        &mut hooks,
+    )
+    .unwrap();
+    assert_eq!(
+        v,
+        parse(br#"[{"$date": "2024-01-31T10:00:00Z"}, "0000-01-01T00:00:00+01:00", "noon"]"#)
+            .unwrap()
+    );
+    let JSONValue::Array(entries) = &v else {
+        panic!("expected an array")
+    };
+    assert_eq!(entries[0].as_unix_timestamp(), Some(1_706_695_200));
+    assert_eq!(entries[2].as_unix_timestamp(), None);
+    assert_eq!(JSONValue::Num(-5.0).as_unix_timestamp(), Some(-5));
+}
+
+#[cfg(feature = "chrono")]
+#[test]
+fn chrono_conversions() {
+    let t = Rfc3339::parse("2024-01-31T12:00:00.5+02:00").unwrap();
+    let utc = chrono::DateTime::<chrono::Utc>::try_from(t).unwrap();
+    assert_eq!(utc.to_rfc3339(), "2024-01-31T10:00:00.500+00:00");
+    assert_eq!(
+        JSONValue::from(t).as_chrono().unwrap().to_rfc3339(),
+        "2024-01-31T12:00:00.500+02:00"
+    );
+    let bad = Rfc3339 {
+        nanosecond: 2_000_000_000,
+        ..t
+    };
+    assert!(chrono::DateTime::<chrono::Utc>::try_from(bad).is_err());
+}
+
+#[cfg(feature = "time")]
+#[test]
+fn time_conversions() {
+    let t = time::OffsetDateTime::from_unix_timestamp(1_706_695_200).unwrap();
+    assert_eq!(
+        JSONValue::try_from(t).unwrap(),
+        JSONValue::Str("2024-01-31T10:00:00Z".to_string())
+    );
+    let v = JSONValue::try_from(t).unwrap();
+    assert_eq!(v.as_offset_datetime(), Some(t));
+}
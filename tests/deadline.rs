@@ -0,0 +1,16 @@
+use std::time::Duration;
+
+use json_parser::{parse, parse_with_deadline, TimedOut};
+
+#[test]
+fn deadline() {
+    let big = format!("[{}0]", "0,".repeat(100_000));
+    let err = parse_with_deadline(big.as_bytes(), Duration::ZERO).unwrap_err();
+    assert!(err.downcast_ref::<TimedOut>().is_some(), "{}", err);
+    let small = br#"{"a": [1, 2]}"#;
+    assert_eq!(
+        parse_with_deadline(small, Duration::from_secs(60)).unwrap(),
+        parse(small).unwrap()
+    );
+    assert!(parse_with_deadline(big.as_bytes(), Duration::MAX).is_ok());
+}
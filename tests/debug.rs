@@ -0,0 +1,18 @@
+use json_parser::{parse, JSONValue};
+
+#[test]
+fn debug_elided() {
+    let v = parse(br#"[1, [2, [3]], "(x)"]"#).unwrap();
+    assert_eq!(
+        format!("{:?}", v),
+        r#"Array([Num(1.0), Array([Num(2.0), Array([Num(3.0)])]), Str("(x)")])"#
+    );
+    assert_eq!(
+        format!("{:.2?}", v),
+        r#"Array([Num(1.0), Array([Num(2.0), Array([..])]), Str("(x)")])"#
+    );
+    assert_eq!(format!("{:1?}", v), "Array([Num(1.0), ..])");
+    assert_eq!(format!("{:#1.0?}", v), "Array(\n    [..],\n)");
+    let long = JSONValue::Str("a".repeat(5000));
+    assert!(format!("{:?}", long).len() < 1100);
+}
@@ -0,0 +1,82 @@
+#![cfg(feature = "rust_decimal")]
+
+use json_parser::{SerializeOptions, Value};
+use rust_decimal::Decimal;
+
+type DecimalValue = Value<String, Decimal>;
+
+#[test]
+fn exact_decimals() {
+    for (input, output) in [
+        ("0.1", "0.1"),
+        ("-12.50", "-12.50"),
+        (
+            "79228162514264337593543950335",
+            "79228162514264337593543950335",
+        ),
+        (
+            "0.1234567890123456789012345678",
+            "0.1234567890123456789012345678",
+        ),
+        ("1e3", "1000"),
+        ("1.5E-2", "0.015"),
+        ("12.34e1", "123.4"),
+        ("-1.25e+2", "-125"),
+        ("1e-28", "0.0000000000000000000000000001"),
+        ("1e28", "10000000000000000000000000000"),
+        ("0e-40", "0"),
+    ] {
+        let v = DecimalValue::parse(input.as_bytes()).unwrap();
+        assert_eq!(v.to_json_string(), output, "{}", input);
+    }
+    // sums that binary floats get wrong stay exact
+    let v = DecimalValue::parse(b"[0.1, 0.2]").unwrap();
+    let Value::Array(entries) = &v else {
+        panic!("expected an array")
+    };
+    let sum: Decimal = entries
+        .iter()
+        .map(|e| match e {
+            Value::Num(d) => *d,
+            _ => panic!("expected a number"),
+        })
+        .sum();
+    assert_eq!(sum.to_string(), "0.3");
+    let text = r#"{"price":19.990,"qty":[3,1E2]}"#;
+    let sorted = SerializeOptions {
+        sort_keys: true,
+        ..Default::default()
+    };
+    assert_eq!(
+        DecimalValue::parse(text.as_bytes())
+            .unwrap()
+            .to_json_string_with(&sorted),
+        r#"{"price":19.990,"qty":[3,100]}"#
+    );
+}
+
+#[test]
+fn too_precise() {
+    for input in [
+        "79228162514264337593543950336",
+        "0.12345678901234567890123456789",
+        "1.23456789012345678901234567891",
+        // rounded by `Decimal::from_scientific`, but not here
+        "1.23456789012345678901234567891e0",
+        "1.00000000000000000000000000001e2",
+        "1e-29",
+        "1.5e-30",
+        "1e29",
+        "7.9228162514264337593543950336e28",
+        "1e99999999999999999999",
+    ] {
+        let err = DecimalValue::parse(input.as_bytes()).unwrap_err();
+        assert!(
+            err.to_string()
+                .starts_with(&format!("Number {} is not a decimal: ", input)),
+            "{}: {}",
+            input,
+            err
+        );
+    }
+}
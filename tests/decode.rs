@@ -0,0 +1,20 @@
+use json_parser::{parse_array_of, parse_object, JSONValue};
+
+#[test]
+fn parsed_shapes() {
+    let object = parse_object(br#"{"b": [1], "a": {"c": null}}"#).unwrap();
+    assert_eq!(object.keys().collect::<Vec<_>>(), ["a", "b"]);
+    assert_eq!(object["b"], JSONValue::Array(vec![JSONValue::Num(1.0)]));
+    assert!(parse_object(b"[1]")
+        .unwrap_err()
+        .to_string()
+        .contains("object"));
+
+    assert_eq!(parse_array_of::<i64>(b"[1, 2, 3]").unwrap(), [1, 2, 3]);
+    assert_eq!(
+        parse_array_of::<Option<String>>(br#"["a", null]"#).unwrap(),
+        [Some("a".to_string()), None]
+    );
+    assert!(parse_array_of::<i64>(b"[1, 2.5]").is_err());
+    assert!(parse_array_of::<i64>(br#"{"a": 1}"#).is_err());
+}
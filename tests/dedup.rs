@@ -0,0 +1,36 @@
+use json_parser::{dedup_lines, JSONValue};
+
+#[test]
+fn deduplicated() {
+    let input = br#"{"id": 1, "at": "a", "tags": {"x": 1, "y": 2}}
+{"id": 2, "at": "b"}
+{"at": "a", "id": 1, "tags": {"y": 2, "x": 1}}
+{"id": 1, "at": "d"}
+{"at": "e"}
+{"at": "e"}
+"#;
+    let flags = |key, window| -> Vec<bool> {
+        let mut dedup = dedup_lines(&input[..], key).unwrap();
+        if let Some(n) = window {
+            dedup = dedup.window(n);
+        }
+        dedup.map(|r| r.unwrap().duplicate).collect()
+    };
+    assert_eq!(flags(None, None), [false, false, true, false, false, true]);
+    assert_eq!(
+        flags(Some("/id"), None),
+        [false, false, true, true, false, false]
+    );
+    assert_eq!(
+        flags(Some("/id"), Some(1)),
+        [false, false, false, true, false, false]
+    );
+
+    let unique: Vec<_> = dedup_lines(&b"[3, 1, 3, 2, 1]"[..], None)
+        .unwrap()
+        .unique()
+        .collect::<eyre::Result<_>>()
+        .unwrap();
+    assert_eq!(unique, [3.0, 1.0, 2.0].map(JSONValue::Num));
+    assert!(dedup_lines(&input[..], Some("id")).is_err());
+}
@@ -0,0 +1,298 @@
+#![cfg(feature = "derive")]
+
+use json_parser::{decode, JSONValue, ParseOptions};
+
+#[test]
+fn derived_enums() {
+    use json_parser::FromJson;
+
+    #[derive(FromJson, Debug, PartialEq)]
+    struct Point {
+        x: f64,
+        y: f64,
+    }
+
+    #[derive(FromJson, Debug, PartialEq)]
+    enum External {
+        Empty,
+        Circle(f64),
+        Line(Point, Point),
+        #[json(rename = "rect")]
+        Rect {
+            w: f64,
+            h: f64,
+        },
+    }
+
+    assert_eq!(decode::<External>(br#""Empty""#).unwrap(), External::Empty);
+    assert_eq!(
+        decode::<External>(br#"{"Empty": null}"#).unwrap(),
+        External::Empty
+    );
+    assert_eq!(
+        decode::<External>(br#"{"Circle": 2}"#).unwrap(),
+        External::Circle(2.0)
+    );
+    assert_eq!(
+        decode::<External>(br#"{"Line": [{"x": 0, "y": 0}, {"x": 1, "y": 1}]}"#).unwrap(),
+        External::Line(Point { x: 0.0, y: 0.0 }, Point { x: 1.0, y: 1.0 })
+    );
+    assert_eq!(
+        decode::<External>(br#"{"rect": {"w": 1, "h": 2}}"#).unwrap(),
+        External::Rect { w: 1.0, h: 2.0 }
+    );
+    assert_eq!(
+        decode::<External>(br#"{"Square": 1}"#)
+            .unwrap_err()
+            .to_string(),
+        "Unknown variant `Square` of External at byte 0"
+    );
+    assert!(decode::<External>(br#"{"Line": [{"x": 0, "y": 0}]}"#).is_err());
+    assert!(decode::<External>(br#"{"Circle": 1, "Empty": null}"#).is_err());
+
+    #[derive(FromJson, Debug, PartialEq)]
+    #[json(tag = "type")]
+    enum Internal {
+        #[json(rename = "circle")]
+        Circle { radius: f64 },
+        #[json(rename = "point")]
+        Point(Point),
+        #[json(rename = "none")]
+        Nothing,
+    }
+
+    assert_eq!(
+        decode::<Internal>(br#"{"radius": 1.5, "type": "circle"}"#).unwrap(),
+        Internal::Circle { radius: 1.5 }
+    );
+    assert_eq!(
+        decode::<Internal>(br#"{"type": "point", "x": 1, "y": 2}"#).unwrap(),
+        Internal::Point(Point { x: 1.0, y: 2.0 })
+    );
+    assert_eq!(
+        decode::<Internal>(br#"{"type": "none", "extra": [1]}"#).unwrap(),
+        Internal::Nothing
+    );
+    assert_eq!(
+        decode::<Vec<Internal>>(br#"[{"type": "none"}, {"radius": 1}]"#)
+            .unwrap_err()
+            .to_string(),
+        "Missing tag `type` of Internal at byte 19"
+    );
+
+    #[derive(FromJson, Debug, PartialEq)]
+    #[json(tag = "t", content = "c")]
+    enum Adjacent {
+        Ping,
+        Move(i64, i64),
+        Say { text: String },
+    }
+
+    assert_eq!(
+        decode::<Adjacent>(br#"{"t": "Ping"}"#).unwrap(),
+        Adjacent::Ping
+    );
+    assert_eq!(
+        decode::<Adjacent>(br#"{"c": [1, -2], "t": "Move"}"#).unwrap(),
+        Adjacent::Move(1, -2)
+    );
+    assert_eq!(
+        decode::<Adjacent>(br#"{"t": "Say", "c": {"text": "hi"}}"#).unwrap(),
+        Adjacent::Say {
+            text: "hi".to_string()
+        }
+    );
+    assert_eq!(
+        decode::<Adjacent>(br#"{"t": "Say"}"#)
+            .unwrap_err()
+            .to_string(),
+        "Missing content `c` of Adjacent at byte 0"
+    );
+
+    #[derive(FromJson, Debug, PartialEq)]
+    #[json(untagged)]
+    enum Untagged {
+        Nothing,
+        Number(f64),
+        Point(Point),
+        Words(Vec<String>),
+    }
+
+    assert_eq!(
+        decode::<Vec<Untagged>>(br#"[null, 3, {"x": 1, "y": 2}, ["a"]]"#).unwrap(),
+        [
+            Untagged::Nothing,
+            Untagged::Number(3.0),
+            Untagged::Point(Point { x: 1.0, y: 2.0 }),
+            Untagged::Words(vec!["a".to_string()])
+        ]
+    );
+    assert_eq!(
+        decode::<Vec<Untagged>>(b"[1, true]")
+            .unwrap_err()
+            .to_string(),
+        "No variant of Untagged matches the value at byte 4"
+    );
+}
+
+#[test]
+fn derived_field_attrs() {
+    use json_parser::{decode_with_options, FromJson, UnknownFields};
+    use std::collections::BTreeMap;
+
+    fn default_retries() -> u32 {
+        3
+    }
+
+    #[derive(FromJson, Debug, PartialEq)]
+    struct Paging {
+        page: u32,
+        #[json(default)]
+        per_page: u32,
+    }
+
+    #[derive(FromJson, Debug, PartialEq)]
+    struct Request {
+        #[json(rename = "type")]
+        kind: String,
+        #[json(default = "default_retries")]
+        retries: u32,
+        #[json(skip)]
+        seen: bool,
+        #[json(flatten)]
+        paging: Paging,
+        #[json(flatten)]
+        rest: BTreeMap<String, JSONValue>,
+    }
+
+    let json = br#"{"type": "list", "page": 2, "seen": true, "trace": "x"}"#;
+    let request = decode::<Request>(json).unwrap();
+    assert_eq!(
+        request,
+        Request {
+            kind: "list".to_string(),
+            retries: 3,
+            seen: false,
+            paging: Paging {
+                page: 2,
+                per_page: 0
+            },
+            // the flattened map sees the keys neither `Request` nor
+            // `Paging` reads
+            rest: [
+                ("seen".to_string(), JSONValue::Bool(true)),
+                ("trace".to_string(), JSONValue::Str("x".to_string())),
+            ]
+            .into_iter()
+            .collect(),
+        }
+    );
+    assert_eq!(
+        decode::<Request>(br#"{"page": 1}"#)
+            .unwrap_err()
+            .to_string(),
+        "Missing field `type` of Request"
+    );
+
+    #[derive(FromJson, Debug, PartialEq)]
+    struct Strict {
+        id: u32,
+        #[json(flatten)]
+        paging: Paging,
+    }
+
+    // keys read by the outer struct are hidden from the flattened one, so
+    // they aren't unknown to it
+    let options = ParseOptions {
+        unknown_fields: UnknownFields::Error,
+        ..Default::default()
+    };
+    assert_eq!(
+        decode_with_options::<Strict>(br#"{"id": 1, "page": 4, "per_page": 9}"#, &options).unwrap(),
+        Strict {
+            id: 1,
+            paging: Paging {
+                page: 4,
+                per_page: 9
+            }
+        }
+    );
+    assert!(decode_with_options::<Strict>(br#"{"id": 1, "page": 4, "x": 0}"#, &options).is_err());
+}
+
+#[test]
+fn derived_flatten_siblings() {
+    use json_parser::{decode_with_options, FromJson, UnknownFields};
+    use std::collections::HashMap;
+
+    #[derive(FromJson, Debug, PartialEq)]
+    struct Paging {
+        page: u32,
+    }
+
+    #[derive(FromJson, Debug, PartialEq)]
+    struct Sorting {
+        #[json(rename = "sort")]
+        by: String,
+        #[json(flatten)]
+        order: Order,
+    }
+
+    #[derive(FromJson, Debug, PartialEq)]
+    struct Order {
+        descending: bool,
+    }
+
+    #[derive(FromJson, Debug, PartialEq)]
+    struct Query {
+        q: String,
+        #[json(flatten)]
+        paging: Paging,
+        #[json(flatten)]
+        sorting: Sorting,
+    }
+
+    let strict = ParseOptions {
+        unknown_fields: UnknownFields::Error,
+        ..Default::default()
+    };
+    // each flattened struct passes over the keys the other reads, even
+    // through its own flattened field
+    let json = br#"{"q": "x", "page": 2, "sort": "date", "descending": true}"#;
+    assert_eq!(
+        decode_with_options::<Query>(json, &strict).unwrap(),
+        Query {
+            q: "x".to_string(),
+            paging: Paging { page: 2 },
+            sorting: Sorting {
+                by: "date".to_string(),
+                order: Order { descending: true }
+            }
+        }
+    );
+    let json = br#"{"q": "x", "page": 2, "sort": "date", "descending": true, "y": 0}"#;
+    assert_eq!(
+        decode_with_options::<Query>(json, &strict)
+            .unwrap_err()
+            .to_string(),
+        "Unknown field `y` at byte 58"
+    );
+    assert_eq!(
+        <Query as FromJson>::flattened_keys(),
+        Some(vec!["q", "page", "sort", "descending"])
+    );
+
+    #[derive(FromJson, Debug, PartialEq)]
+    struct Open {
+        #[json(flatten)]
+        paging: Paging,
+        #[json(flatten)]
+        rest: HashMap<String, u32>,
+    }
+
+    // the keys a flattened map takes aren't unknown to the struct beside it
+    let open = decode_with_options::<Open>(br#"{"page": 1, "a": 2}"#, &strict).unwrap();
+    assert_eq!(open.paging, Paging { page: 1 });
+    assert_eq!(open.rest, [("a".to_string(), 2)].into_iter().collect());
+    assert_eq!(<Open as FromJson>::flattened_keys(), None);
+}
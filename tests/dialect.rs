@@ -0,0 +1,83 @@
+use json_parser::{parse, parse_with_comments, JSONValue, Json5Format, JsoncFormat, ParseOptions};
+
+#[test]
+fn json5() {
+    let v = parse(br#"{"name": "it's\n", "a-b": [1, 2], "_ok": {}, "n": null}"#).unwrap();
+    assert_eq!(
+        v.to_json5(),
+        "\
+{
+  _ok: {},
+  'a-b': [
+    1,
+    2,
+  ],
+  n: null,
+  name: 'it\\'s\\n',
+}"
+    );
+    let mut compact = Json5Format {
+        indent: None,
+        trailing_commas: true,
+    };
+    // no trailing commas without line breaks
+    assert_eq!(
+        v.to_string_formatted(&mut compact),
+        r#"{_ok:{},'a-b':[1,2],n:null,name:'it\'s\n'}"#
+    );
+    let mut no_commas = Json5Format {
+        trailing_commas: false,
+        ..Json5Format::default()
+    };
+    assert_eq!(
+        parse(b"[1]").unwrap().to_string_formatted(&mut no_commas),
+        "[\n  1\n]"
+    );
+    let numbers = JSONValue::Array(vec![
+        JSONValue::Num(f64::NAN),
+        JSONValue::Num(f64::INFINITY),
+        JSONValue::Num(f64::NEG_INFINITY),
+        JSONValue::Num(-0.5),
+    ]);
+    assert_eq!(
+        numbers.to_string_formatted(&mut compact),
+        "[NaN,Infinity,-Infinity,-0.5]"
+    );
+}
+
+#[test]
+fn jsonc() {
+    let json = br#"// top
+{
+    // the name
+    "name": "x", // short
+    "tags": [ /* none yet */ ],
+    "n": [1, /* two */ 2]
+} /* end */"#;
+    let (v, comments) = parse_with_comments(json, &ParseOptions::default()).unwrap();
+    let jsonc = v.to_jsonc(&comments);
+    assert_eq!(
+        jsonc,
+        r#"// top
+{
+  "n": [
+    1, /* two */
+    2
+  ],
+  // the name
+  "name": "x", // short
+  "tags": [
+    /* none yet */
+  ]
+} /* end */"#
+    );
+    // the output keeps every comment and reads back the same
+    let (again, kept) = parse_with_comments(jsonc.as_bytes(), &ParseOptions::default()).unwrap();
+    assert_eq!(again, v);
+    assert_eq!(kept.len(), comments.len());
+
+    assert_eq!(
+        v.to_string_formatted(&mut JsoncFormat::new(&[]).indent(4)),
+        "{\n    \"n\": [\n        1,\n        2\n    ],\n    \"name\": \"x\",\n    \"tags\": []\n}"
+    );
+}
@@ -0,0 +1,147 @@
+use std::borrow::Cow;
+
+use json_parser::{parse, Document, NodeKind, ParseOptions};
+
+const CONFIG: &str = r#"{
+    // service settings
+    "name": "api",
+    "port": 8080,
+    "debug": false,
+    "proxy": null,
+    "hosts": ["a.example", "b\u002eexample"],
+    "limits": {"rps": 1.5e2, "burst": []}
+}
+"#;
+
+fn config() -> Document {
+    let options = ParseOptions {
+        allow_comments: true,
+        ..ParseOptions::default()
+    };
+    Document::parse_with_options(CONFIG, &options).unwrap()
+}
+
+// The document's text with `range` replaced by `text`.
+fn splice(doc: &Document, range: std::ops::Range<usize>, text: &str) -> String {
+    let src = std::str::from_utf8(doc.as_bytes()).unwrap();
+    format!("{}{}{}", &src[..range.start], text, &src[range.end..])
+}
+
+#[test]
+fn scalar_accessors() {
+    let doc = config();
+    let root = doc.root();
+    assert_eq!(root.kind(), NodeKind::Object);
+    assert_eq!(root.len(), 6);
+
+    let name = root.get("name").unwrap();
+    assert_eq!(name.kind(), NodeKind::String);
+    assert_eq!(name.raw(), br#""api""#);
+    // unescaped strings borrow from the input
+    assert!(matches!(name.as_str(), Some(Cow::Borrowed("api"))));
+    assert_eq!(name.as_f64(), None);
+
+    let port = root.get("port").unwrap();
+    assert_eq!(
+        (port.kind(), port.as_f64()),
+        (NodeKind::Number, Some(8080.0))
+    );
+    assert_eq!(port.as_str(), None);
+    let debug = root.get("debug").unwrap();
+    assert_eq!(
+        (debug.kind(), debug.as_bool()),
+        (NodeKind::Bool, Some(false))
+    );
+    let proxy = root.get("proxy").unwrap();
+    assert!(proxy.is_null() && !debug.is_null());
+    assert_eq!(proxy.kind(), NodeKind::Null);
+    assert!(root.get("missing").is_none());
+    // scalars have no children
+    assert_eq!((port.len(), port.elements().count()), (0, 0));
+    assert!(port.get("x").is_none() && port.index(0).is_none());
+}
+
+#[test]
+fn container_accessors() {
+    let doc = config();
+    let root = doc.root();
+    let keys: Vec<_> = root.entries().map(|(k, _)| k).collect();
+    assert_eq!(keys, ["name", "port", "debug", "proxy", "hosts", "limits"]);
+
+    let hosts = root.get("hosts").unwrap();
+    assert_eq!((hosts.kind(), hosts.len()), (NodeKind::Array, 2));
+    assert_eq!(hosts.raw(), br#"["a.example", "b\u002eexample"]"#);
+    let second = hosts.index(1).unwrap();
+    // escaped strings are decoded into an owned copy
+    assert!(matches!(second.as_str(), Some(Cow::Owned(s)) if s == "b.example"));
+    assert_eq!(second.raw(), br#""b\u002eexample""#);
+    assert!(hosts.index(2).is_none());
+    assert_eq!(hosts.entries().count(), 0);
+
+    let limits = root.get("limits").unwrap();
+    assert_eq!(limits.get("rps").unwrap().raw(), b"1.5e2");
+    let burst = limits.get("burst").unwrap();
+    assert!(burst.is_empty() && burst.kind() == NodeKind::Array);
+    assert_eq!(
+        limits.to_value(),
+        parse(br#"{"rps": 150, "burst": []}"#).unwrap()
+    );
+
+    // the last of duplicate keys wins, as with `parse`
+    let doc = Document::parse(r#"{"a": 1, "a": 2}"#).unwrap();
+    assert_eq!(doc.root().get("a").unwrap().as_f64(), Some(2.0));
+    assert_eq!(doc.root().len(), 2);
+}
+
+#[test]
+fn spans_locate_values() {
+    let doc = config();
+    let port = doc.root().get("port").unwrap();
+    let span = port.span();
+    assert_eq!(&CONFIG[span.range()], "8080");
+    assert_eq!(doc.as_bytes(), CONFIG.as_bytes());
+}
+
+#[test]
+fn edits_keep_formatting() {
+    // replacing a value's span leaves the comment, indentation and every
+    // other value as written
+    let doc = config();
+    let port = doc.root().get("port").unwrap().span().range();
+    let edited = splice(&doc, port, "9090");
+    assert_eq!(
+        edited,
+        r#"{
+    // service settings
+    "name": "api",
+    "port": 9090,
+    "debug": false,
+    "proxy": null,
+    "hosts": ["a.example", "b\u002eexample"],
+    "limits": {"rps": 1.5e2, "burst": []}
+}
+"#
+    );
+
+    // nested containers can be swapped whole, and the result reparsed
+    let options = ParseOptions {
+        allow_comments: true,
+        ..ParseOptions::default()
+    };
+    let doc = Document::parse_with_options(edited, &options).unwrap();
+    let burst = doc.root().get("limits").unwrap().get("burst").unwrap();
+    let edited = splice(&doc, burst.span().range(), "[1, 2]");
+    assert!(edited.contains(r#""limits": {"rps": 1.5e2, "burst": [1, 2]}"#));
+    assert!(edited.starts_with("{\n    // service settings\n"));
+    let doc = Document::parse_with_options(edited, &options).unwrap();
+    assert_eq!(
+        doc.root()
+            .get("limits")
+            .unwrap()
+            .get("burst")
+            .unwrap()
+            .len(),
+        2
+    );
+    assert_eq!(doc.into_bytes().len(), CONFIG.len() + 4);
+}
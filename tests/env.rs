@@ -0,0 +1,104 @@
+use json_parser::{parse, JSONValue};
+
+fn pairs(vars: &[(&str, &str)]) -> Vec<(String, String)> {
+    vars.iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect()
+}
+
+#[test]
+fn env_vars() {
+    let config = parse(
+        br#"{"db": {"host": "localhost", "port": 5432, "replicas": ["a", "b"]},
+            "debug": false, "version": "2", "log-level": "info"}"#,
+    )
+    .unwrap();
+    let vars = config.to_env_vars("app").unwrap();
+    let expected = pairs(&[
+        ("APP_DB__HOST", "localhost"),
+        ("APP_DB__PORT", "5432"),
+        ("APP_DB__REPLICAS__0", "a"),
+        ("APP_DB__REPLICAS__1", "b"),
+        ("APP_DEBUG", "false"),
+        ("APP_LOG_LEVEL", "info"),
+        ("APP_VERSION", "\"2\""),
+    ]);
+    assert_eq!(vars, expected);
+
+    let mut env = vars;
+    env.push(("PATH".to_string(), "/usr/bin".to_string()));
+    env.push(("APPLE".to_string(), "1".to_string()));
+    let back = JSONValue::from_env_vars("app", env).unwrap();
+    // the `-` in `log-level` isn't kept
+    let expected = parse(
+        br#"{"db": {"host": "localhost", "port": 5432, "replicas": ["a", "b"]},
+            "debug": false, "version": "2", "log_level": "info"}"#,
+    )
+    .unwrap();
+    assert_eq!(back, expected);
+
+    let conflicting = pairs(&[("APP_DB", "x"), ("APP_DB__PORT", "1")]);
+    assert!(JSONValue::from_env_vars("APP", conflicting).is_err());
+    let sparse = pairs(&[("APP_0", "x"), ("APP_2", "y")]);
+    assert_eq!(
+        JSONValue::from_env_vars("APP", sparse).unwrap(),
+        parse(br#"{"0": "x", "2": "y"}"#).unwrap()
+    );
+}
+
+#[test]
+fn underscores_in_keys() {
+    let config = parse(br#"{"db": {"max_connections": 5, "pool_size": {"min_idle": 1}}}"#).unwrap();
+    let vars = config.to_env_vars("x").unwrap();
+    assert_eq!(
+        vars,
+        pairs(&[
+            ("X_DB__MAX_CONNECTIONS", "5"),
+            ("X_DB__POOL_SIZE__MIN_IDLE", "1"),
+        ])
+    );
+    assert_eq!(JSONValue::from_env_vars("x", vars).unwrap(), config);
+}
+
+#[test]
+fn key_case_is_lost() {
+    let config = parse(br#"{"db": {"Host": "x", "PORT": 1}}"#).unwrap();
+    let vars = config.to_env_vars("x").unwrap();
+    assert_eq!(vars, pairs(&[("X_DB__HOST", "x"), ("X_DB__PORT", "1")]));
+    assert_eq!(
+        JSONValue::from_env_vars("x", vars).unwrap(),
+        parse(br#"{"db": {"host": "x", "port": 1}}"#).unwrap()
+    );
+}
+
+#[test]
+fn colliding_names() {
+    let err = |json: &[u8]| {
+        parse(json)
+            .unwrap()
+            .to_env_vars("x")
+            .unwrap_err()
+            .to_string()
+    };
+    // nesting uses `__`, so a key with `_` in it doesn't clash with a path
+    assert_eq!(
+        parse(br#"{"a_b": 1, "a": {"b": 2}}"#)
+            .unwrap()
+            .to_env_vars("x")
+            .unwrap(),
+        pairs(&[("X_A_B", "1"), ("X_A__B", "2")])
+    );
+    assert_eq!(
+        err(br#"{"a_b": 1, "a-b": 2}"#),
+        "More than one value would be named X_A_B"
+    );
+    assert_eq!(
+        err(br#"{"Host": 1, "host": 2}"#),
+        "More than one value would be named X_HOST"
+    );
+    assert!(err(br#"{"--": 1}"#).contains("has no letters or digits"));
+
+    // the same name twice in the environment is a conflict too
+    let twice = pairs(&[("X_A", "1"), ("X_A", "2")]);
+    assert!(JSONValue::from_env_vars("x", twice).is_err());
+}
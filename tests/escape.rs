@@ -0,0 +1,87 @@
+use json_parser::{parse, parse_with_options, unescape, JSONValue, LoneSurrogates, ParseOptions};
+
+#[test]
+fn escapes() {
+    assert_eq!(
+        parse(br#""\" \\ \/ \b \f \n \r \t \u00e9 \ud83d\ude00""#).unwrap(),
+        JSONValue::Str("\" \\ / \u{8} \u{c} \n \r \t \u{e9} \u{1f600}".to_string())
+    );
+    for (bad, message) in [
+        (&br#""\x""#[..], "Invalid escape: '\\x'"),
+        (br#""\u12""#, "Invalid unicode escape digit: '\"'"),
+        (br#""\u12g4""#, "Invalid unicode escape digit: 'g'"),
+    ] {
+        assert_eq!(parse(bad).unwrap_err().to_string(), message);
+    }
+}
+
+#[test]
+fn lone_surrogates() {
+    let json = br#"["\ud800", "\udc00x", "\ud83dA"]"#;
+    assert_eq!(
+        parse(json).unwrap_err().to_string(),
+        "Lone surrogate in escape: '\\uD800'"
+    );
+    let replace = ParseOptions {
+        lone_surrogates: LoneSurrogates::Replace,
+        ..Default::default()
+    };
+    assert_eq!(
+        parse_with_options(json, &replace).unwrap(),
+        JSONValue::Array(vec![
+            JSONValue::Str("\u{fffd}".to_string()),
+            JSONValue::Str("\u{fffd}x".to_string()),
+            // a high surrogate followed by something other than a low one
+            JSONValue::Str("\u{fffd}A".to_string()),
+        ])
+    );
+    // values must be UTF-8, so preserved surrogates can't make it into one
+    let preserve = ParseOptions {
+        lone_surrogates: LoneSurrogates::Preserve,
+        ..Default::default()
+    };
+    assert!(parse_with_options(json, &preserve).is_err());
+
+    // `unescape` keeps them as WTF-8
+    assert_eq!(
+        unescape(br"a\ud800b", LoneSurrogates::Preserve).unwrap(),
+        b"a\xed\xa0\x80b"
+    );
+    assert_eq!(
+        unescape(br"a\ud800b", LoneSurrogates::Replace).unwrap(),
+        "a\u{fffd}b".as_bytes()
+    );
+    assert!(unescape(br"a\ud800b", LoneSurrogates::Reject).is_err());
+    assert_eq!(
+        unescape(br"\ud83d\ude00", LoneSurrogates::Reject).unwrap(),
+        "\u{1f600}".as_bytes()
+    );
+    assert!(unescape(b"\xff", LoneSurrogates::Replace).is_err());
+}
+
+#[test]
+fn control_chars() {
+    assert_eq!(
+        parse(b"[\"a\tb\"]").unwrap_err().to_string(),
+        "Unescaped control character 0x09 in string at byte 3"
+    );
+    assert!(parse(b"\"\x00\"").is_err());
+    // a newline gets its own hint
+    assert!(parse(b"\"a\nb\"")
+        .unwrap_err()
+        .to_string()
+        .starts_with("Unescaped newline at byte 2"));
+    // escaped, or outside strings, they're fine
+    assert!(parse(b"[\"a\\tb\",\t1\n]").is_ok());
+
+    let options = ParseOptions {
+        allow_control_chars: true,
+        ..Default::default()
+    };
+    assert_eq!(
+        parse_with_options(b"{\"k\x01\": \"a\tb\nc\"}", &options).unwrap(),
+        parse(br#"{"k\u0001": "a\tb\nc"}"#).unwrap()
+    );
+    // DEL isn't a control character to JSON
+    assert!(parse(b"\"\x7f\"").is_ok());
+}
@@ -0,0 +1,103 @@
+use json_parser::{explain_error, parse, parse_with_options, JSONValue, ParseOptions};
+
+#[test]
+fn explained_errors() {
+    let explain = |input: &str| {
+        let err = parse(input.as_bytes()).unwrap_err();
+        let explained = explain_error(input.as_bytes(), &err);
+        assert!(
+            explained.starts_with(&format!("{}\n", err)),
+            "{}",
+            explained
+        );
+        explained
+    };
+    let explained = explain("{\n  \"users\": [\n    {\"name\": \"ann\" \"age\": 3}\n  ]\n}");
+    assert!(
+        explained.contains("At line 3, column 20:\n"),
+        "{}",
+        explained
+    );
+    assert!(
+        explained.contains("\n        {\"name\": \"ann\" \"age\": 3}\n                       ^\n"),
+        "{}",
+        explained
+    );
+    assert!(
+        explained.contains(
+            "After `\"name\"`, `:` and `\"ann\"`, inside the object opened at line 3, \
+             column 5, at /users/0/name, the parser expected `,` or `}`, but found the \
+             string \"age\".\nPerhaps a `,` is missing before it.\n"
+        ),
+        "{}",
+        explained
+    );
+
+    let explained = explain("[1, 2,]");
+    assert!(explained.contains("trailing comma"), "{}", explained);
+    let explained = explain("{\"a\": [1, {\"b\": 2}");
+    assert!(explained.contains("but the input ended."), "{}", explained);
+    assert!(
+        explained.contains("2 containers are still open"),
+        "{}",
+        explained
+    );
+    let explained = explain("{'a': 1}");
+    assert!(explained.contains("double quotes"), "{}", explained);
+    let explained = explain("{\"a\": tru}");
+    assert!(explained.contains("but found `tru`"), "{}", explained);
+    let explained = explain("1 2");
+    assert!(explained.contains("at the top level"), "{}", explained);
+
+    let deep = "[".repeat(20) + &"]".repeat(20);
+    let options = ParseOptions {
+        max_depth: Some(10),
+        ..ParseOptions::default()
+    };
+    let err = parse_with_options(deep.as_bytes(), &options).unwrap_err();
+    assert!(explain_error(deep.as_bytes(), &err).contains("well-formed"));
+}
+
+#[test]
+fn suggested_fixes() {
+    let error = |input: &str| parse(input.as_bytes()).unwrap_err().to_string();
+    assert_eq!(
+        error("{'a': 1}"),
+        "Unexpected `'` at byte 1: strings and keys need double quotes"
+    );
+    assert_eq!(
+        error("[True]"),
+        "Unexpected `True` at byte 1, did you mean `true`?"
+    );
+    assert_eq!(
+        error("[1, None]"),
+        "Unexpected `None` at byte 4, did you mean `null`?"
+    );
+    assert_eq!(
+        error("[nul]"),
+        "Unexpected `nul` at byte 1, did you mean `null`?"
+    );
+    assert_eq!(
+        error("{\"a\": \"line\nbreak\"}"),
+        "Unescaped newline at byte 11 in the string starting at byte 6: write it as \\n, \
+         or close the string before the line ends"
+    );
+    assert!(error("[NaN]").contains("set `allow_non_finite`"));
+    assert!(error("[-Infinity]").contains("set `allow_non_finite`"));
+    assert!(error("[1] // note").contains("set `allow_comments`"));
+
+    let options = ParseOptions {
+        allow_non_finite: true,
+        ..ParseOptions::default()
+    };
+    match parse_with_options(b"[NaN, Infinity, -Infinity, 1]", &options).unwrap() {
+        JSONValue::Array(a) => match a[..] {
+            [JSONValue::Num(nan), JSONValue::Num(inf), JSONValue::Num(neg), JSONValue::Num(one)] => {
+                assert!(nan.is_nan());
+                assert_eq!((inf, neg, one), (f64::INFINITY, f64::NEG_INFINITY, 1.0));
+            }
+            _ => panic!("bad array {:?}", a),
+        },
+        v => panic!("bad value {:?}", v),
+    }
+}
@@ -0,0 +1,87 @@
+use json_parser::{parse, JSONValue};
+
+#[test]
+fn typed_arrays() {
+    let v = parse(br#"[1, 2.5, -3]"#).unwrap();
+    assert_eq!(v.as_vec_of::<f64>().unwrap(), [1.0, 2.5, -3.0]);
+    assert_eq!(
+        v.as_vec_of::<i64>().unwrap_err().to_string(),
+        "Element 1 at /1: expected an integer in i64 range, found 2.5"
+    );
+    assert_eq!(
+        v.as_vec_of::<u8>().unwrap_err().to_string(),
+        "Element 1 at /1: expected an integer in u8 range, found 2.5"
+    );
+
+    let v = parse(br#"["a", null, "c"]"#).unwrap();
+    assert_eq!(
+        v.as_vec_of::<Option<String>>().unwrap(),
+        [Some("a".to_string()), None, Some("c".to_string())]
+    );
+    assert_eq!(
+        v.as_vec_of::<String>().unwrap_err().to_string(),
+        "Element 1 at /1: expected a string, found null"
+    );
+
+    let matrix = parse(b"[[1, 2], [3, true]]").unwrap();
+    assert_eq!(
+        matrix.as_vec_of::<Vec<f64>>().unwrap_err().to_string(),
+        "Element 1 at /1/1: expected a number, found true"
+    );
+    assert_eq!(
+        parse(br#"{"a": 1}"#)
+            .unwrap()
+            .as_vec_of::<f64>()
+            .unwrap_err()
+            .to_string(),
+        "Expected an array, found an object"
+    );
+    assert_eq!(
+        parse(b"[[1, 2], [3]]")
+            .unwrap()
+            .as_vec_of::<Vec<u32>>()
+            .unwrap(),
+        [vec![1, 2], vec![3]]
+    );
+}
+
+#[test]
+fn defaults() {
+    let config = parse(
+        br#"{"server": {"host": "example.org", "port": 8443, "tls": null},
+             "workers": [{"name": "a"}, {"threads": 4}], "debug": "yes"}"#,
+    )
+    .unwrap();
+    assert_eq!(config.get_or("missing", 3_u32), 3);
+    assert!(!config.get_or("debug", false));
+    assert_eq!(config.get_or("debug", String::new()), "yes");
+    assert_eq!(config.pointer_or("/server/port", 80_u16), 8443);
+    assert!(config.pointer_or("/server/tls", true));
+    assert_eq!(config.pointer_or("/workers/1/threads", 1_usize), 4);
+    assert_eq!(config.pointer_or("no slash", 1_usize), 1);
+
+    assert_eq!(
+        config.try_get::<String>(&["server", "host"]).as_deref(),
+        Some("example.org")
+    );
+    assert_eq!(config.try_get::<u64>(&["workers", "1", "threads"]), Some(4));
+    assert_eq!(config.try_get::<u64>(&["workers", "2", "threads"]), None);
+    assert_eq!(config.try_get::<u8>(&["server", "host"]), None);
+    assert_eq!(
+        config.try_get::<Option<bool>>(&["server", "tls"]),
+        Some(None)
+    );
+
+    let host = config.get("server").and_then(|s| s.get("host"));
+    assert_eq!(host.map(|h| h.as_str_or("")), Some("example.org"));
+    assert_eq!(
+        config.pointer("/workers/0/name"),
+        Some(&JSONValue::Str("a".to_string()))
+    );
+    let tls = config.pointer("/server/tls").unwrap();
+    assert_eq!(tls.as_str_or("off"), "off");
+    assert!(tls.as_bool_or(true));
+    assert_eq!(tls.as_f64_or(0.5), 0.5);
+    assert_eq!(JSONValue::Num(2.0).as_i64_or(0), 2);
+    assert_eq!(JSONValue::Num(2.5).as_i64_or(0), 0);
+}
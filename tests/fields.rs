@@ -0,0 +1,63 @@
+use json_parser::{field_set, map_into, parse, JSONValue};
+
+#[derive(Debug, Default, PartialEq)]
+struct Limits {
+    max_connections: u32,
+    burst: Option<u32>,
+}
+
+#[derive(Debug, Default, PartialEq)]
+struct ServerConfig {
+    host_name: String,
+    port: u16,
+    tls: bool,
+    limits: Limits,
+    aliases: Vec<String>,
+}
+
+field_set!(Limits {
+    max_connections,
+    burst
+});
+field_set!(ServerConfig {
+    host_name,
+    port,
+    tls,
+    limits,
+    aliases,
+});
+
+#[test]
+fn mapped_structs() {
+    let v = parse(
+        br#"{"hostName": "example.org", "PORT": 8443, "unknown": [1],
+             "limits": {"max-connections": 100}, "aliases": ["www"]}"#,
+    )
+    .unwrap();
+    let config: ServerConfig = map_into(&v).unwrap();
+    assert_eq!(
+        config,
+        ServerConfig {
+            host_name: "example.org".to_string(),
+            port: 8443,
+            tls: false,
+            limits: Limits {
+                max_connections: 100,
+                burst: None,
+            },
+            aliases: vec!["www".to_string()],
+        }
+    );
+
+    let v = parse(br#"{"limits": {"MAX_CONNECTIONS": -1}}"#).unwrap();
+    assert_eq!(
+        map_into::<ServerConfig>(&v).unwrap_err().to_string(),
+        "Member at /limits/MAX_CONNECTIONS: expected an integer in u32 range, found -1"
+    );
+    assert_eq!(
+        map_into::<Limits>(&JSONValue::Array(vec![]))
+            .unwrap_err()
+            .to_string(),
+        "Expected an object, found an array"
+    );
+}
@@ -0,0 +1,95 @@
+use core::fmt::{self, Write};
+
+use json_parser::{parse, CompactFormat, JSONValue, JsonFormat, PrettyFormat};
+
+fn doc() -> JSONValue {
+    parse(br#"{"b": [1, "two", null], "a": {"x": true}, "c": []}"#).unwrap()
+}
+
+#[test]
+fn builtin_formats() {
+    let compact = doc().to_string_formatted(&mut CompactFormat);
+    assert_eq!(parse(compact.as_bytes()).unwrap(), doc());
+    assert!(!compact.contains(' '));
+    assert_eq!(
+        doc().to_string_formatted(&mut PrettyFormat::default()),
+        "\
+{
+  \"a\": {
+    \"x\": true
+  },
+  \"b\": [
+    1,
+    \"two\",
+    null
+  ],
+  \"c\": []
+}"
+    );
+    assert_eq!(
+        parse(b"[{}]")
+            .unwrap()
+            .to_string_formatted(&mut PrettyFormat { indent: 1 }),
+        "[\n {}\n]"
+    );
+}
+
+// Python's `repr` style, to exercise every hook.
+struct PythonFormat {
+    max_depth: usize,
+}
+
+impl JsonFormat for PythonFormat {
+    fn write_null<W: ?Sized + Write>(&mut self, out: &mut W) -> fmt::Result {
+        out.write_str("None")
+    }
+
+    fn write_bool<W: ?Sized + Write>(&mut self, out: &mut W, b: bool) -> fmt::Result {
+        out.write_str(if b { "True" } else { "False" })
+    }
+
+    fn write_number<W: ?Sized + Write>(&mut self, out: &mut W, n: f64) -> fmt::Result {
+        write!(out, "{:?}", n)
+    }
+
+    fn write_string<W: ?Sized + Write>(&mut self, out: &mut W, s: &str) -> fmt::Result {
+        write!(out, "'{}'", s)
+    }
+
+    fn write_comma<W: ?Sized + Write>(&mut self, out: &mut W) -> fmt::Result {
+        out.write_str(", ")
+    }
+
+    fn write_colon<W: ?Sized + Write>(&mut self, out: &mut W) -> fmt::Result {
+        out.write_str(": ")
+    }
+
+    fn after_last_entry<W: ?Sized + Write>(&mut self, out: &mut W) -> fmt::Result {
+        out.write_char(',')
+    }
+
+    fn indent<W: ?Sized + Write>(&mut self, _out: &mut W, depth: usize) -> fmt::Result {
+        self.max_depth = self.max_depth.max(depth);
+        Ok(())
+    }
+
+    fn order_entries(&mut self, entries: &mut [(&String, &JSONValue)]) {
+        entries.sort_by(|a, b| b.0.cmp(a.0));
+    }
+}
+
+#[test]
+fn custom_format() {
+    let mut format = PythonFormat { max_depth: 0 };
+    assert_eq!(
+        doc().to_string_formatted(&mut format),
+        "{'c': [], 'b': [1.0, 'two', None,], 'a': {'x': True,},}"
+    );
+    assert_eq!(format.max_depth, 2);
+
+    let mut out = String::from("x = ");
+    JSONValue::Bool(false)
+        .write_formatted(&mut out, &mut format)
+        .unwrap();
+    assert_eq!(out, "x = False");
+}
@@ -0,0 +1,91 @@
+use json_parser::{parse, LengthPrefix, LengthPrefixedReader, SseReader};
+
+#[test]
+fn sse() {
+    let stream = "\
+: keep-alive
+
+event: delta
+id: 1
+data: {\"text\":
+data:  \"hi\"}
+
+event: ignored
+
+data: [1, 2]\r
+\r
+data: [DONE]
+
+data: {\"after\": true}
+";
+    let events: Vec<_> = SseReader::new(stream.as_bytes())
+        .stop_at("[DONE]")
+        .map(Result::unwrap)
+        .collect();
+    assert_eq!(events.len(), 2);
+    assert_eq!(events[0].event.as_deref(), Some("delta"));
+    assert_eq!(events[0].id.as_deref(), Some("1"));
+    // data lines are joined with a newline
+    assert_eq!(events[0].data, parse(br#"{"text": "hi"}"#).unwrap());
+    // the event and id of an event without data don't carry over
+    assert_eq!(events[1].event, None);
+    assert_eq!(events[1].data, parse(b"[1, 2]").unwrap());
+
+    // without a sentinel `[DONE]` is just bad JSON, which ends the stream
+    let mut events = SseReader::new("data: [DONE]\n\ndata: 1\n\n".as_bytes());
+    assert!(events.next().unwrap().is_err());
+    assert!(events.next().is_none());
+    // the last event needn't end with a blank line
+    let mut events = SseReader::new("data: 1".as_bytes());
+    assert_eq!(events.next().unwrap().unwrap().data, parse(b"1").unwrap());
+    assert!(events.next().is_none());
+
+    // data that starts out like the sentinel is still parsed
+    let mut events = SseReader::new("data: [\ndata: 1]\n\n".as_bytes()).stop_at("[DONE]");
+    assert_eq!(events.next().unwrap().unwrap().data, parse(b"[1]").unwrap());
+    // an event must hold exactly one whole document
+    for data in [
+        "data: [1,\n\n",
+        "data: [1] [2]\n\n",
+        "data: {\ndata: 1}\n\n",
+    ] {
+        let mut events = SseReader::new(data.as_bytes());
+        assert!(events.next().unwrap().is_err(), "{data:?}");
+    }
+}
+
+#[test]
+fn length_prefixed() {
+    let mut stream = Vec::new();
+    for doc in [&br#"{"a": 1}"#[..], b"[true]"] {
+        stream.extend_from_slice(&(doc.len() as u16).to_le_bytes());
+        stream.extend_from_slice(doc);
+    }
+    let docs: Vec<_> = LengthPrefixedReader::new(stream.as_slice())
+        .prefix(LengthPrefix::U16Le)
+        .map(Result::unwrap)
+        .collect();
+    assert_eq!(
+        docs,
+        [parse(br#"{"a": 1}"#).unwrap(), parse(b"[true]").unwrap()]
+    );
+
+    // the default prefix is a big-endian u32
+    let mut frames = LengthPrefixedReader::new(&b"\0\0\0\x041234\0\0"[..]);
+    assert_eq!(frames.next().unwrap().unwrap(), parse(b"1234").unwrap());
+    assert_eq!(
+        frames.next().unwrap().unwrap_err().to_string(),
+        "Truncated length prefix"
+    );
+    assert!(frames.next().is_none());
+
+    let mut frames = LengthPrefixedReader::new(&b"\0\0\x01\0{}"[..]).max_frame_len(16);
+    assert_eq!(
+        frames.next().unwrap().unwrap_err().to_string(),
+        "Frame of 256 bytes exceeds limit of 16"
+    );
+    // a frame cut short is an error too
+    let mut frames = LengthPrefixedReader::new(&b"\0\0\0\x05{}"[..]);
+    assert!(frames.next().unwrap().is_err());
+    assert!(LengthPrefixedReader::new(&b""[..]).next().is_none());
+}
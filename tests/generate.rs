@@ -0,0 +1,134 @@
+use json_parser::{parse, GenerateOptions, Generator, JSONValue, Schema, TypeWeights};
+use proptest::prelude::*;
+
+// Whether `v` nests at most `depth` deep, with at most `len` entries in any
+// array or object and `chars` characters in any string or key.
+fn bounded_by(v: &JSONValue, depth: usize, len: usize, chars: usize) -> bool {
+    let short = |s: &String| s.chars().count() <= chars;
+    match v {
+        JSONValue::Str(s) => short(s),
+        JSONValue::Array(a) => {
+            depth > 0 && a.len() <= len && a.iter().all(|e| bounded_by(e, depth - 1, len, chars))
+        }
+        JSONValue::Dict(d) => {
+            depth > 0
+                && d.len() <= len
+                && d.iter()
+                    .all(|(k, e)| short(k) && bounded_by(e, depth - 1, len, chars))
+        }
+        _ => true,
+    }
+}
+
+#[test]
+fn seeded() {
+    let values = |seed| {
+        let mut generator = Generator::new(GenerateOptions {
+            seed,
+            ..GenerateOptions::default()
+        });
+        (0..8).map(|_| generator.value()).collect::<Vec<_>>()
+    };
+    assert_eq!(values(7), values(7));
+    assert_ne!(values(7), values(8));
+}
+
+#[test]
+fn bounded() {
+    for seed in 0..64 {
+        let options = GenerateOptions {
+            seed,
+            max_depth: 2,
+            max_len: 3,
+            max_string_len: 5,
+            weights: TypeWeights {
+                array: 4,
+                object: 4,
+                ..TypeWeights::default()
+            },
+        };
+        let v = Generator::new(options).value();
+        assert!(bounded_by(&v, 2, 3, 5), "{}", v);
+    }
+}
+
+#[test]
+fn weighted() {
+    let only = |weights: TypeWeights| {
+        let options = GenerateOptions {
+            weights,
+            ..GenerateOptions::default()
+        };
+        Generator::new(options).value()
+    };
+    let none = TypeWeights {
+        null: 0,
+        bool: 0,
+        number: 0,
+        string: 0,
+        array: 0,
+        object: 0,
+    };
+    assert_eq!(only(TypeWeights { null: 1, ..none }), JSONValue::Null);
+    assert!(matches!(
+        only(TypeWeights { bool: 1, ..none }),
+        JSONValue::Bool(_)
+    ));
+    assert!(matches!(
+        only(TypeWeights { string: 1, ..none }),
+        JSONValue::Str(_)
+    ));
+}
+
+#[test]
+fn fixed_by_schema() {
+    let value_for = |schema: &[u8]| {
+        Generator::new(GenerateOptions::default()).value_for(&Schema::parse(schema).unwrap())
+    };
+    assert_eq!(
+        value_for(br#"{"const": {"a": [1]}}"#),
+        parse(br#"{"a": [1]}"#).unwrap()
+    );
+    assert_eq!(
+        value_for(br#"{"enum": ["only"]}"#),
+        JSONValue::Str("only".to_string())
+    );
+    assert_eq!(
+        value_for(br#"{"type": "integer", "minimum": 3, "maximum": 3}"#),
+        JSONValue::Num(3.0)
+    );
+    assert_eq!(
+        value_for(br#"{"type": "array", "maxItems": 0}"#),
+        JSONValue::Array(vec![])
+    );
+    let v = value_for(br#"{"type": "string", "minLength": 4, "maxLength": 4}"#);
+    assert!(
+        matches!(&v, JSONValue::Str(s) if s.chars().count() == 4),
+        "{}",
+        v
+    );
+}
+
+proptest! {
+    #[test]
+    fn generated(seed in any::<u64>()) {
+        let options = GenerateOptions { seed, ..GenerateOptions::default() };
+        let v = Generator::new(options).value();
+        prop_assert_eq!(parse(v.to_json_string().as_bytes()).unwrap(), v);
+    }
+
+    #[test]
+    fn generated_for_schema(seed in any::<u64>()) {
+        let schema = Schema::parse(br#"{"type": "object", "required": ["id", "tags"],
+            "minProperties": 3, "properties": {
+                "id": {"type": "integer", "minimum": 1, "exclusiveMaximum": 100, "multipleOf": 7},
+                "ratio": {"type": "number", "minimum": 0, "maximum": 1},
+                "name": {"type": ["string", "null"], "minLength": 2, "maxLength": 4},
+                "kind": {"enum": ["a", {"b": [1]}]},
+                "tags": {"type": "array", "maxItems": 3, "items": {"type": "string"}}
+            }}"#).unwrap();
+        let options = GenerateOptions { seed, ..GenerateOptions::default() };
+        let v = Generator::new(options).value_for(&schema);
+        prop_assert_eq!(schema.validate(v.to_json_string().as_bytes()).unwrap(), vec![]);
+    }
+}
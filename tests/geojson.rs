@@ -0,0 +1,49 @@
+#![cfg(feature = "geojson")]
+
+use json_parser::{decode, JSONValue};
+
+#[test]
+fn geojson() {
+    use json_parser::{Feature, FeatureCollection, GeoJson, Geometry};
+
+    let json = br#"{
+        "features": [
+            {"properties": {"name": "park"}, "id": 7, "type": "Feature",
+             "geometry": {"coordinates": [[[0, 0], [4, 0], [4, 3], [0, 0]]], "type": "Polygon"}},
+            {"type": "Feature", "geometry": null, "properties": null, "foreign": true}
+        ],
+        "type": "FeatureCollection",
+        "bbox": [0, 0, 4, 3]
+    }"#;
+    let collection: FeatureCollection = decode(json).unwrap();
+    let [park, empty] = collection.features.as_slice() else {
+        panic!("expected two features")
+    };
+    assert_eq!(park.id, Some(JSONValue::Num(7.0)));
+    assert_eq!(park.property("name"), Some(&JSONValue::Str("park".into())));
+    let geometry = park.geometry.as_ref().unwrap();
+    assert_eq!(geometry.type_name(), "Polygon");
+    assert_eq!(geometry.bbox(), Some([0.0, 0.0, 4.0, 3.0]));
+    assert_eq!(empty, &Feature::default());
+    let written = collection.to_json().to_json_string();
+    assert_eq!(
+        decode::<GeoJson>(written.as_bytes()).unwrap(),
+        GeoJson::FeatureCollection(collection)
+    );
+    assert_eq!(
+        decode::<Geometry>(br#"{"type": "Point", "coordinates": [1.5, 2, 30]}"#).unwrap(),
+        Geometry::Point(vec![1.5, 2.0, 30.0])
+    );
+
+    for bad in [
+        r#"{"type": "Point", "coordinates": [1]}"#,
+        r#"{"type": "LineString", "coordinates": [[1, 2]]}"#,
+        r#"{"type": "Polygon", "coordinates": [[[0, 0], [1, 0], [1, 1], [0, 1]]]}"#,
+        r#"{"type": "Circle", "coordinates": [0, 0]}"#,
+        r#"{"coordinates": [0, 0]}"#,
+        r#"{"type": "Feature", "properties": {}}"#,
+    ] {
+        assert!(decode::<GeoJson>(bad.as_bytes()).is_err(), "{}", bad);
+    }
+    assert!(decode::<Geometry>(br#"{"type": "Feature", "geometry": null}"#).is_err());
+}
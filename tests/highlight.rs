@@ -0,0 +1,107 @@
+use json_parser::{highlight, TokenClass};
+
+use TokenClass::*;
+
+// Each span as its text and class.
+fn spans(input: &str) -> Vec<(&str, TokenClass)> {
+    highlight(input.as_bytes())
+        .into_iter()
+        .map(|(range, class)| (&input[range], class))
+        .collect()
+}
+
+#[test]
+fn keys_and_values() {
+    assert_eq!(
+        spans(r#"{"name": "ann", "tags": ["a"]}"#),
+        [
+            ("{", Punctuation),
+            (r#""name""#, Key),
+            (":", Punctuation),
+            (r#""ann""#, String),
+            (",", Punctuation),
+            (r#""tags""#, Key),
+            (":", Punctuation),
+            ("[", Punctuation),
+            (r#""a""#, String),
+            ("]", Punctuation),
+            ("}", Punctuation),
+        ]
+    );
+    // a comment between a key and its colon doesn't change what it is
+    assert_eq!(
+        spans(r#"{"k" /* why */ : "v"}"#),
+        [
+            ("{", Punctuation),
+            (r#""k""#, Key),
+            ("/* why */", Comment),
+            (":", Punctuation),
+            (r#""v""#, String),
+            ("}", Punctuation),
+        ]
+    );
+}
+
+#[test]
+fn keywords_and_numbers() {
+    assert_eq!(
+        spans("[true, false, null, -1.5e3, 0]"),
+        [
+            ("[", Punctuation),
+            ("true", Keyword),
+            (",", Punctuation),
+            ("false", Keyword),
+            (",", Punctuation),
+            ("null", Keyword),
+            (",", Punctuation),
+            ("-1.5e3", Number),
+            (",", Punctuation),
+            ("0", Number),
+            ("]", Punctuation),
+        ]
+    );
+}
+
+#[test]
+fn comments() {
+    assert_eq!(
+        spans("// leading\n[1 /* inline */]"),
+        [
+            ("// leading", Comment),
+            ("[", Punctuation),
+            ("1", Number),
+            ("/* inline */", Comment),
+            ("]", Punctuation),
+        ]
+    );
+}
+
+#[test]
+fn recovery() {
+    // a bad word is an error up to the next delimiter
+    assert_eq!(
+        spans("[tru, 2]"),
+        [
+            ("[", Punctuation),
+            ("tru", Error),
+            (",", Punctuation),
+            ("2", Number),
+            ("]", Punctuation),
+        ]
+    );
+    // an unterminated string runs to the end of its line
+    assert_eq!(
+        spans("{\"a\": \"open,\n \"b\": @x}"),
+        [
+            ("{", Punctuation),
+            (r#""a""#, Key),
+            (":", Punctuation),
+            ("\"open,", Error),
+            (r#""b""#, Key),
+            (":", Punctuation),
+            ("@x", Error),
+            ("}", Punctuation),
+        ]
+    );
+    assert_eq!(spans(""), []);
+}
@@ -0,0 +1,59 @@
+use json_parser::{parse, parse_with_hooks, JSONValue, ParseHooks};
+
+#[test]
+fn hooked() {
+    let mut keys = Vec::new();
+    let mut hooks = ParseHooks::new()
+        .on_key(|k| {
+            keys.push(k.clone());
+            Ok(k.to_uppercase())
+        })
+        .on_string(|s| {
+            Ok(match s.parse::<f64>() {
+                Ok(n) => JSONValue::Num(n),
+                Err(_) => JSONValue::Str(s),
+            })
+        })
+        .on_number(|n| Ok(JSONValue::Num(n * 10.0)));
+    let v = parse_with_hooks(
+        br#"{"a": "1.5", "b": ["x", 2], "c": {"d": null}}"#,
+        &mut hooks,
+    )
+    .unwrap();
+    // strings turned into numbers don't go through `on_number`
+    assert_eq!(
+        v,
+        parse(br#"{"A": 1.5, "B": ["x", 20], "C": {"D": null}}"#).unwrap()
+    );
+    drop(hooks);
+    keys.sort();
+    assert_eq!(keys, ["a", "b", "c", "d"]);
+}
+
+#[test]
+fn hook_errors() {
+    let mut hooks = ParseHooks::new().on_number(|n| {
+        if n < 0.0 {
+            eyre::bail!("negative")
+        }
+        Ok(JSONValue::Num(n))
+    });
+    assert_eq!(
+        parse_with_hooks(b"[1, -2]", &mut hooks)
+            .unwrap_err()
+            .to_string(),
+        "negative"
+    );
+    let mut hooks = ParseHooks::new().on_key(|k| {
+        if k.is_empty() {
+            eyre::bail!("empty key")
+        }
+        Ok(k)
+    });
+    assert!(parse_with_hooks(br#"{"a": {"": 1}}"#, &mut hooks).is_err());
+    // no hooks, no change
+    assert_eq!(
+        parse_with_hooks(br#"{"a": [1, "b"]}"#, &mut ParseHooks::new()).unwrap(),
+        parse(br#"{"a": [1, "b"]}"#).unwrap()
+    );
+}
@@ -0,0 +1,114 @@
+use json_parser::http::{body_options, BodyTooLarge, DEFAULT_BODY_LIMIT, DEFAULT_DEPTH_LIMIT};
+use json_parser::{parse_with_options, ParseOptions};
+
+#[test]
+fn body_limits() {
+    let options = body_options();
+    assert_eq!(options.max_size, Some(DEFAULT_BODY_LIMIT));
+    assert_eq!(options.max_depth, Some(DEFAULT_DEPTH_LIMIT));
+    let deep = format!("{}{}", "[".repeat(129), "]".repeat(129));
+    assert!(parse_with_options(deep.as_bytes(), &options).is_err());
+
+    let small = ParseOptions {
+        max_size: Some(4),
+        ..ParseOptions::default()
+    };
+    assert!(parse_with_options(b"[1]", &small).is_ok());
+    assert_eq!(
+        parse_with_options(b"[1, 2]", &small)
+            .unwrap_err()
+            .to_string(),
+        "Input of 6 bytes exceeds maximum size of 4"
+    );
+    assert_eq!(
+        BodyTooLarge { limit: 4 }.to_string(),
+        "Body exceeds limit of 4 bytes"
+    );
+}
+
+// Runs a future whose body is all in memory, so it never waits.
+#[cfg(feature = "hyper")]
+fn ready<F: std::future::Future>(f: F) -> F::Output {
+    use std::task::{Context, Poll, Waker};
+
+    let mut cx = Context::from_waker(Waker::noop());
+    match std::pin::pin!(f).poll(&mut cx) {
+        Poll::Ready(out) => out,
+        Poll::Pending => panic!("in-memory body wasn't ready"),
+    }
+}
+
+#[cfg(feature = "hyper")]
+#[test]
+fn bodies() {
+    use http_body_util::Full;
+    use json_parser::http::{parse_body, parse_body_document};
+    use json_parser::parse;
+
+    let body = || Full::new(bytes::Bytes::from_static(br#"{"a": [1, "b"]}"#));
+    let options = body_options();
+    assert_eq!(
+        ready(parse_body(body(), &options)).unwrap(),
+        parse(br#"{"a": [1, "b"]}"#).unwrap()
+    );
+    let doc = ready(parse_body_document(body(), &options)).unwrap();
+    assert_eq!(doc.root().to_value(), parse(br#"{"a": [1, "b"]}"#).unwrap());
+
+    let small = ParseOptions {
+        max_size: Some(4),
+        ..ParseOptions::default()
+    };
+    let err = ready(parse_body(body(), &small)).unwrap_err();
+    assert_eq!(err.downcast_ref::<BodyTooLarge>().unwrap().limit, 4);
+    assert!(ready(parse_body(
+        Full::new(bytes::Bytes::from_static(b"[1,")),
+        &options
+    ))
+    .is_err());
+}
+
+#[cfg(feature = "axum")]
+#[test]
+fn extractor() {
+    use axum_core::body::Body;
+    use axum_core::extract::{FromRequest, Request};
+    use http::StatusCode;
+    use json_parser::http::{Json, JsonRejection};
+    use json_parser::{parse, JSONValue};
+
+    fn extract(content_type: &str, body: &'static str) -> Result<JSONValue, JsonRejection> {
+        let req = Request::builder()
+            .header("content-type", content_type)
+            .body(Body::from(body))
+            .unwrap();
+        ready(Json::<JSONValue>::from_request(req, &())).map(|Json(v)| v)
+    }
+
+    assert_eq!(
+        extract("application/json; charset=utf-8", "[1]").unwrap(),
+        parse(b"[1]").unwrap()
+    );
+    assert!(extract("application/problem+json", "{}").is_ok());
+    let status = |r: Result<JSONValue, JsonRejection>| r.unwrap_err().status;
+    assert_eq!(
+        status(extract("text/plain", "[1]")),
+        StatusCode::UNSUPPORTED_MEDIA_TYPE
+    );
+    assert_eq!(
+        status(extract("application/json", "[1,")),
+        StatusCode::BAD_REQUEST
+    );
+
+    // limits come from a `ParseOptions` extension when there is one
+    let mut req = Request::builder()
+        .header("content-type", "application/json")
+        .body(Body::from("[1, 2, 3]"))
+        .unwrap();
+    req.extensions_mut().insert(ParseOptions {
+        max_size: Some(4),
+        ..ParseOptions::default()
+    });
+    let rejection = ready(Json::<JSONValue>::from_request(req, &())).unwrap_err();
+    assert_eq!(rejection.status, StatusCode::PAYLOAD_TOO_LARGE);
+    assert_eq!(rejection.to_string(), "Body exceeds limit of 4 bytes");
+}
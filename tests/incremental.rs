@@ -0,0 +1,75 @@
+use json_parser::{IncrementalParser, JSONValue, ParseOptions, DEFAULT_MAX_DEPTH};
+
+#[test]
+fn nesting_limited() {
+    let mut parser = IncrementalParser::new();
+    let err = parser.feed(&vec![b'['; 200_000]).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        format!("Nesting exceeds maximum depth of {}", DEFAULT_MAX_DEPTH)
+    );
+    // the containers opened before the limit are still there
+    let mut v = parser.current().unwrap();
+    let mut depth = 0;
+    while let JSONValue::Array(mut entries) = v {
+        depth += 1;
+        v = entries.pop().unwrap_or(JSONValue::Null);
+    }
+    assert_eq!(depth, DEFAULT_MAX_DEPTH);
+    drop(parser);
+
+    let options = ParseOptions {
+        max_depth: Some(2),
+        ..Default::default()
+    };
+    let mut parser = IncrementalParser::with_options(&options);
+    parser.feed(b"[[1], {\"a\": ").unwrap();
+    assert!(parser.feed(b"[").is_err());
+    assert_eq!(parser.current().unwrap().to_json_string(), "[[1],{}]");
+
+    let mut parser = IncrementalParser::new();
+    parser.feed(b"[[[").unwrap();
+    let checkpoint = parser.checkpoint();
+    assert!(IncrementalParser::resume_with_options(&checkpoint, &options).is_err());
+    assert!(IncrementalParser::resume(&checkpoint).is_ok());
+}
+
+#[test]
+fn consumed_bytes_dropped() {
+    let mut parser = IncrementalParser::new();
+    parser.feed(b"[").unwrap();
+    for _ in 0..10_000 {
+        parser.feed(b"\"abc\", 12").unwrap();
+        // only the number, which may go on, is kept
+        assert_eq!(parser.buffer(), b" 12");
+        parser.feed(b"3,").unwrap();
+        assert_eq!(parser.buffer(), b"");
+    }
+    parser.feed(b"true]").unwrap();
+    assert!(parser.is_done());
+    assert_eq!(parser.offset(), 1 + 10_000 * 11 + 5);
+    let JSONValue::Array(entries) = parser.finish().unwrap() else {
+        panic!("expected an array")
+    };
+    assert_eq!(entries.len(), 20_001);
+    assert_eq!(entries[1], JSONValue::Num(123.0));
+
+    // a cut-off token stays until it completes
+    let mut parser = IncrementalParser::new();
+    parser.feed(b"{\"key\": \"val").unwrap();
+    assert_eq!(parser.buffer(), b" \"val");
+    assert_eq!(parser.offset(), 7);
+    assert_eq!(
+        parser.current().unwrap().to_json_string(),
+        r#"{"key":"val"}"#
+    );
+    let checkpoint = parser.checkpoint();
+    let mut resumed = IncrementalParser::resume(&checkpoint).unwrap();
+    assert_eq!(resumed.buffer(), b" \"val");
+    resumed.feed(b"ue\"}").unwrap();
+    assert_eq!(resumed.offset(), 16);
+    assert_eq!(
+        resumed.finish().unwrap().to_json_string(),
+        r#"{"key":"value"}"#
+    );
+}
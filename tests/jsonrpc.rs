@@ -0,0 +1,74 @@
+use json_parser::parse;
+
+#[test]
+fn jsonrpc() {
+    use json_parser::jsonrpc::{
+        write_message, Error, Id, Message, MessageReader, Payload, Request, Response,
+        INVALID_REQUEST, PARSE_ERROR,
+    };
+
+    let request = Message::Request(Request {
+        id: Id::Num(1),
+        method: "textDocument/hover".to_string(),
+        params: Some(parse(br#"{"line": 3}"#).unwrap()),
+    });
+    let batch = Payload::parse(
+        br#"[
+            {"jsonrpc": "2.0", "method": "initialized"},
+            {"jsonrpc": "2.0", "id": "a", "result": null},
+            {"jsonrpc": "2.0", "id": 2, "error": {"code": -32601, "message": "Method not found"}},
+            {"jsonrpc": "1.0", "id": 3, "method": "x"},
+            {"jsonrpc": "2.0", "id": 4, "method": "x", "params": 5},
+            {"jsonrpc": "2.0", "id": 5, "result": 1, "error": {"code": 1, "message": ""}}
+        ]"#,
+    )
+    .unwrap();
+    let Payload::Batch(entries) = &batch else {
+        panic!("expected a batch")
+    };
+    assert!(matches!(&entries[0], Ok(Message::Notification(n)) if n.method == "initialized"));
+    assert!(matches!(&entries[1], Ok(Message::Response(r)) if r.id == Id::Str("a".into())));
+    assert!(
+        matches!(&entries[2], Ok(Message::Response(Response { result: Err(e), .. })) if e.code == -32601)
+    );
+    assert!(entries[3..].iter().all(|e| matches!(
+        e,
+        Err(Error {
+            code: INVALID_REQUEST,
+            ..
+        })
+    )));
+    assert_eq!(Payload::parse(b"[]").unwrap_err().code, INVALID_REQUEST);
+    assert_eq!(Payload::parse(b"{").unwrap_err().code, PARSE_ERROR);
+
+    let mut wire = Vec::new();
+    write_message(&mut wire, &request.clone().into()).unwrap();
+    write_message(&mut wire, &batch).unwrap();
+    wire.extend_from_slice(b"Content-Length: 1\r\n\r\n{");
+    let mut reader = MessageReader::new(wire.as_slice());
+    assert_eq!(
+        reader.next().unwrap().unwrap(),
+        Ok(Payload::Single(request))
+    );
+    let Ok(Payload::Batch(read)) = reader.next().unwrap().unwrap() else {
+        panic!("expected a batch")
+    };
+    assert_eq!(&read[..3], &entries[..3]);
+    // invalid elements come back as error responses
+    assert!(read[3..].iter().all(|e| matches!(
+        e,
+        Ok(Message::Response(Response {
+            id: Id::Null,
+            result: Err(_)
+        }))
+    )));
+    assert_eq!(
+        reader.next().unwrap().unwrap().unwrap_err().code,
+        PARSE_ERROR
+    );
+    assert!(reader.next().is_none());
+    assert!(MessageReader::new(&b"Content-Length: 5\r\n\r\n{}"[..])
+        .next()
+        .unwrap()
+        .is_err());
+}
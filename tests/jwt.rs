@@ -0,0 +1,29 @@
+use json_parser::parse;
+
+#[test]
+fn jwt() {
+    use json_parser::{decode_jwt_claims, decode_jwt_header};
+
+    let token = "eyJhbGciOiJIUzI1NiIsInR5cCI6IkpXVCJ9.\
+        eyJzdWIiOiIxMjM0NTY3ODkwIiwibmFtZSI6IkpvaG4gRG9lIiwiaWF0IjoxNTE2MjM5MDIyfQ.\
+        SflKxwRJSMeKKF2QT4fwpMeJf36POk6yJV_adQssw5c";
+    assert_eq!(
+        decode_jwt_claims(token).unwrap(),
+        parse(br#"{"sub": "1234567890", "name": "John Doe", "iat": 1516239022}"#).unwrap()
+    );
+    assert_eq!(
+        decode_jwt_header(token).unwrap(),
+        parse(br#"{"alg": "HS256", "typ": "JWT"}"#).unwrap()
+    );
+    // unsecured tokens have an empty signature
+    assert!(decode_jwt_claims("eyJhbGciOiJub25lIn0.e30.").is_ok());
+    for bad in [
+        "",
+        "a.b",
+        "eyJhbGciOiJub25lIn0.!!!.",
+        "eyJhbGciOiJub25lIn0.MQ.",
+        "a.b.c.d.e",
+    ] {
+        assert!(decode_jwt_claims(bad).is_err(), "{}", bad);
+    }
+}
@@ -0,0 +1,137 @@
+use json_parser::lexer::{tokens, Lexer, Span, TextEdit, Token, TokenKind};
+use json_parser::parse;
+
+#[test]
+fn number_grammar() {
+    for (input, n) in [
+        ("0", 0.0),
+        ("-0", -0.0),
+        ("10", 10.0),
+        ("-2.50", -2.5),
+        ("1e3", 1000.0),
+        ("1E+3", 1000.0),
+        ("0.5e-1", 0.05),
+    ] {
+        let t: Vec<_> = tokens(input.as_bytes()).collect::<Result<_, _>>().unwrap();
+        assert_eq!(t.len(), 1, "{}", input);
+        assert_eq!(t[0].token, Token::NumVal(n), "{}", input);
+    }
+    for input in [
+        "01", "-01", "00", "2.", "-2.", "0.e1", "2.e3", ".5", "-", "1e", "1e+", "1.5e", "+1",
+        "1-2", "1..2", "1ee2",
+    ] {
+        assert!(parse(input.as_bytes()).is_err(), "{}", input);
+    }
+    assert_eq!(
+        parse(b"[1, 01]").unwrap_err().to_string(),
+        "Invalid number `01` at byte 4"
+    );
+}
+
+#[test]
+fn spanned_tokens() {
+    let input = br#"{"a": [1.5, true, null]}"#;
+    let lexed = Lexer::new().lex(input).unwrap();
+    let spans: Vec<_> = lexed.iter().map(|t| (t.kind(), t.span.range())).collect();
+    assert_eq!(
+        spans,
+        [
+            (TokenKind::LeftBrace, 0..1),
+            (TokenKind::String, 1..4),
+            (TokenKind::Colon, 4..5),
+            (TokenKind::LeftBracket, 6..7),
+            (TokenKind::Number, 7..10),
+            (TokenKind::Comma, 10..11),
+            (TokenKind::Bool, 12..16),
+            (TokenKind::Comma, 16..17),
+            (TokenKind::Null, 18..22),
+            (TokenKind::RightBracket, 22..23),
+            (TokenKind::RightBrace, 23..24),
+        ]
+    );
+    // a string token holds the range of its contents, without the quotes
+    assert_eq!(lexed[1].token, Token::StringVal(2, 3));
+    assert_eq!(lexed[4].token, Token::NumVal(1.5));
+    let lazy: Vec<_> = tokens(input).collect::<Result<_, _>>().unwrap();
+    assert_eq!(lazy, lexed);
+    assert_eq!(Span::new(3, 7).len(), 4);
+    assert!(Span::default().is_empty());
+
+    // the iterator stops after the first error, where `position` points
+    let mut iter = tokens(b"[1, @]");
+    assert_eq!(iter.by_ref().filter(Result::is_ok).count(), 3);
+    assert_eq!(iter.position(), 4);
+    assert!(iter.next().is_none());
+}
+
+#[test]
+fn lexer_options() {
+    let input = b"[NaN, /* c */ \"a\tb\"]";
+    assert!(Lexer::new().lex(input).is_err());
+    let lexer = || {
+        Lexer::new()
+            .allow_comments(true)
+            .allow_non_finite(true)
+            .allow_control_chars(true)
+    };
+    let kinds: Vec<_> = lexer().tokens(input).map(|t| t.unwrap().kind()).collect();
+    assert_eq!(
+        kinds,
+        [
+            TokenKind::LeftBracket,
+            TokenKind::Number,
+            TokenKind::Comma,
+            TokenKind::Comment,
+            TokenKind::String,
+            TokenKind::RightBracket,
+        ]
+    );
+    // `lex` leaves comments out
+    assert_eq!(lexer().lex(input).unwrap().len(), 5);
+
+    let limited = Lexer::new().max_tokens(Some(2));
+    assert_eq!(
+        limited.lex(b"[1]").unwrap_err().to_string(),
+        "Input exceeds maximum of 2 tokens"
+    );
+}
+
+#[test]
+fn relexed() {
+    let old = r#"{"name": "x", "n": 12, "tags": [true, null]}"#;
+    // (range replaced, replacement)
+    for (range, with) in [
+        (10..11, "longer"),
+        (20..20, "3"),
+        (19..22, ""),
+        (0..0, " "),
+        (44..44, " "),
+        (32..36, "false"),
+        (12..12, ", \"y\""),
+        (14..14, "\"k\": [], "),
+    ] {
+        let new = format!("{}{}{}", &old[..range.start], with, &old[range.end..]);
+        let mut lexed = Lexer::new().lex(old.as_bytes()).unwrap();
+        let edit = TextEdit {
+            range: range.clone(),
+            new_len: with.len(),
+        };
+        Lexer::new()
+            .relex(&mut lexed, new.as_bytes(), &edit)
+            .unwrap();
+        assert_eq!(lexed, Lexer::new().lex(new.as_bytes()).unwrap(), "{}", new);
+    }
+
+    // an edit that leaves bad input changes nothing
+    let mut lexed = Lexer::new().lex(old.as_bytes()).unwrap();
+    let before = lexed.clone();
+    let new = old.replacen("true", "tru", 1);
+    let edit = TextEdit {
+        range: 32..36,
+        new_len: 3,
+    };
+    assert!(Lexer::new()
+        .relex(&mut lexed, new.as_bytes(), &edit)
+        .is_err());
+    assert_eq!(lexed, before);
+}
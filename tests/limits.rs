@@ -0,0 +1,61 @@
+use json_parser::{
+    parse_with_options, Document, JSONValue, ParseOptions, Profile, DEFAULT_MAX_DEPTH,
+};
+
+#[test]
+fn limit_profiles() {
+    let web = ParseOptions::profile(Profile::WebApi);
+    assert_eq!(
+        (web.max_size, web.max_depth, web.max_tokens),
+        (Some(2 * 1024 * 1024), Some(128), Some(250_000))
+    );
+    let unlimited = ParseOptions::profile(Profile::Unlimited);
+    assert_eq!(
+        (
+            unlimited.max_size,
+            unlimited.max_depth,
+            unlimited.max_tokens
+        ),
+        (None, None, None)
+    );
+    let batch = ParseOptions {
+        allow_comments: true,
+        ..ParseOptions::profile(Profile::Batch)
+    };
+    assert_eq!(batch.max_depth, Some(DEFAULT_MAX_DEPTH));
+    assert!(parse_with_options(b"[1] // ok", &batch).is_ok());
+
+    let deep = format!("{}{}", "[".repeat(129), "]".repeat(129));
+    assert!(parse_with_options(deep.as_bytes(), &web).is_err());
+    assert!(parse_with_options(deep.as_bytes(), &batch).is_ok());
+
+    // small enough, but too many tokens
+    let dense = format!("[{}0]", "0,".repeat(125_000));
+    let error = "Input exceeds maximum of 250000 tokens";
+    assert_eq!(
+        parse_with_options(dense.as_bytes(), &web)
+            .unwrap_err()
+            .to_string(),
+        error
+    );
+    assert_eq!(
+        Document::parse_with_options(dense.clone(), &web)
+            .unwrap_err()
+            .to_string(),
+        error
+    );
+    assert!(parse_with_options(dense.as_bytes(), &batch).is_ok());
+    let options = ParseOptions {
+        max_tokens: Some(3),
+        ..ParseOptions::default()
+    };
+    assert!(parse_with_options(
+        b"[1] /* not counted */",
+        &ParseOptions {
+            allow_comments: true,
+            ..options.clone()
+        }
+    )
+    .is_ok());
+    assert!(JSONValue::parse_with_options(b"[1, 2]", &options).is_err());
+}
@@ -0,0 +1,67 @@
+use json_parser::parse;
+
+#[test]
+fn media_types() {
+    use json_parser::{HalLink, HalResource, Problem};
+
+    assert_eq!(
+        Problem::new(404)
+            .detail("No order 42")
+            .extension("order_id", 42.0)
+            .extension("status", "ignored")
+            .build(),
+        parse(br#"{"title": "Not Found", "status": 404, "detail": "No order 42", "order_id": 42}"#)
+            .unwrap()
+    );
+    assert_eq!(
+        Problem::new(422)
+            .type_uri("https://example.com/probs/out-of-stock")
+            .instance("/orders/42")
+            .build(),
+        parse(br#"{"type": "https://example.com/probs/out-of-stock", "status": 422, "instance": "/orders/42"}"#)
+            .unwrap()
+    );
+
+    let item = |id: f64| {
+        HalResource::new()
+            .link("self", format!("/items/{}", id))
+            .field("id", id)
+    };
+    let order = HalResource::new()
+        .link("self", "/orders/42")
+        .link("next", HalLink::new("/orders{?page}").templated())
+        .link(
+            "curies",
+            HalLink::new("/docs/{rel}").name("doc").templated(),
+        )
+        .link_list("payments")
+        .field("total", 30.0)
+        .embed("customer", HalResource::new().field("name", "Ann"))
+        .embed_list("items", [item(1.0)])
+        .build();
+    let expected = parse(
+        br#"{
+            "total": 30,
+            "_links": {
+                "self": {"href": "/orders/42"},
+                "next": {"href": "/orders{?page}", "templated": true},
+                "curies": [{"href": "/docs/{rel}", "name": "doc", "templated": true}],
+                "payments": []
+            },
+            "_embedded": {
+                "customer": {"name": "Ann"},
+                "items": [{"id": 1, "_links": {"self": {"href": "/items/1"}}}]
+            }
+        }"#,
+    )
+    .unwrap();
+    assert_eq!(order, expected);
+    let two = HalResource::new()
+        .link("item", "/a")
+        .link("item", "/b")
+        .build();
+    assert_eq!(
+        two,
+        parse(br#"{"_links": {"item": [{"href": "/a"}, {"href": "/b"}]}}"#).unwrap()
+    );
+}
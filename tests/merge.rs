@@ -0,0 +1,15 @@
+use json_parser::{merge3, parse};
+
+#[test]
+fn merged() {
+    let base = parse(br#"{"a": 1, "b": [1, 2], "c": {"d": 1}, "e": 1}"#).unwrap();
+    let mine = parse(br#"{"a": 2, "b": [1, 3], "c": {"d": 2}}"#).unwrap();
+    let theirs = parse(br#"{"a": 1, "b": [4, 2], "c": {"d": 3}, "e": 1, "f": 1}"#).unwrap();
+    let merged = merge3(&base, &mine, &theirs);
+    assert_eq!(
+        merged.value,
+        parse(br#"{"a": 2, "b": [4, 3], "c": {"d": 2}, "f": 1}"#).unwrap()
+    );
+    let conflicts: Vec<_> = merged.conflicts.iter().map(|c| c.to_string()).collect();
+    assert_eq!(conflicts, ["/c/d: base 1, mine 2, theirs 3"]);
+}
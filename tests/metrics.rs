@@ -0,0 +1,32 @@
+use std::cell::RefCell;
+
+use json_parser::{parse_with_metrics, ParseMetrics, ParseOptions, ParseStats};
+
+#[test]
+fn metered() {
+    #[derive(Default)]
+    struct Recorder(RefCell<Vec<(ParseStats, bool)>>);
+
+    impl ParseMetrics for Recorder {
+        fn parsed(&self, stats: &ParseStats) {
+            self.0.borrow_mut().push((*stats, true));
+        }
+
+        fn failed(&self, stats: &ParseStats, _: &eyre::Report) {
+            self.0.borrow_mut().push((*stats, false));
+        }
+    }
+
+    let recorder = Recorder::default();
+    let options = ParseOptions::default();
+    assert!(parse_with_metrics(br#"{"a": [1, 2]}"#, &options, &recorder).is_ok());
+    assert!(parse_with_metrics(b"[1,", &options, &recorder).is_err());
+    assert!(parse_with_metrics(b"\"", &options, &recorder).is_err());
+    let seen: Vec<_> = recorder
+        .0
+        .into_inner()
+        .into_iter()
+        .map(|(stats, ok)| (stats.bytes, stats.tokens, ok))
+        .collect();
+    assert_eq!(seen, [(13, 9, true), (3, 3, false), (1, 0, false)]);
+}
@@ -0,0 +1,70 @@
+use json_parser::{parse, parse_with_options, JSONValue, ParseOptions};
+
+#[test]
+fn folded_keys() {
+    let options = ParseOptions {
+        fold_key_case: true,
+        ..Default::default()
+    };
+    let v = parse_with_options(
+        r#"{"Name": "Ada", "INNER": {"ÉTÉ": 1}}"#.as_bytes(),
+        &options,
+    )
+    .unwrap();
+    // only keys are folded
+    assert_eq!(
+        v,
+        parse(r#"{"name": "Ada", "inner": {"été": 1}}"#.as_bytes()).unwrap()
+    );
+
+    let v = parse(br#"{"Content-Type": "a", "x": 1}"#).unwrap();
+    assert_eq!(
+        v.get_ignore_case("content-type"),
+        Some(&JSONValue::Str("a".to_string()))
+    );
+    assert_eq!(v.get_ignore_case("X"), Some(&JSONValue::Num(1.0)));
+    assert_eq!(v.get_ignore_case("y"), None);
+    assert_eq!(JSONValue::Null.get_ignore_case("x"), None);
+}
+
+#[cfg(feature = "unicode-normalization")]
+#[test]
+fn normalized() {
+    use json_parser::UnicodeForm;
+
+    // "é" written precomposed and as `e` plus a combining accent
+    let json = "{\"caf\u{e9}\": \"cafe\u{301}\", \"cafe\u{301}\": \"caf\u{e9}\"}";
+    let composed = ParseOptions {
+        normalize_keys: Some(UnicodeForm::Nfc),
+        normalize_strings: Some(UnicodeForm::Nfc),
+        ..Default::default()
+    };
+    let JSONValue::Dict(d) = parse_with_options(json.as_bytes(), &composed).unwrap() else {
+        panic!("expected an object")
+    };
+    // both keys become the same, so only one is left
+    assert_eq!(d.len(), 1);
+    assert_eq!(d["caf\u{e9}"], JSONValue::Str("caf\u{e9}".to_string()));
+
+    let decomposed = ParseOptions {
+        normalize_strings: Some(UnicodeForm::Nfkd),
+        ..Default::default()
+    };
+    let v = parse_with_options("[\"caf\u{e9}\", \"\u{fb01}\"]".as_bytes(), &decomposed).unwrap();
+    assert_eq!(
+        v,
+        JSONValue::Array(vec![
+            JSONValue::Str("cafe\u{301}".to_string()),
+            JSONValue::Str("fi".to_string()),
+        ])
+    );
+
+    // keys are normalized before they're folded
+    let both = ParseOptions {
+        normalize_keys: Some(UnicodeForm::Nfkc),
+        fold_key_case: true,
+        ..Default::default()
+    };
+    let v = parse_with_options(r#"{"Ｋｅｙ": 1}"#.as_bytes(), &both).unwrap();
+    assert_eq!(v, parse(br#"{"key": 1}"#).unwrap());
+}
@@ -0,0 +1,34 @@
+use std::cell::RefCell;
+
+use json_parser::{parse, JSONValue, ObservableDocument, Patch};
+
+#[test]
+fn observed() {
+    let seen = RefCell::new(Vec::new());
+    let mut doc = ObservableDocument::new(parse(br#"{"a": [1], "b": 2}"#).unwrap());
+    let id = doc.subscribe(|c| {
+        let show = |v: &Option<JSONValue>| v.as_ref().map(|v| v.to_json_string());
+        seen.borrow_mut()
+            .push((c.pointer.clone(), show(&c.old), show(&c.new)))
+    });
+    doc.set("/b", JSONValue::Num(3.0)).unwrap();
+    doc.insert("/a/-", JSONValue::Null).unwrap();
+    assert!(doc.remove("/c").is_err());
+    doc.remove("/a/0").unwrap();
+    let patch = br#"[{"op": "move", "from": "/b", "path": "/c"}]"#;
+    doc.apply(&Patch::parse(patch).unwrap()).unwrap();
+    assert!(doc.unsubscribe(id));
+    doc.set("/d", JSONValue::Null).unwrap();
+    drop(doc);
+    let s = |s: &str| Some(s.to_string());
+    assert_eq!(
+        seen.into_inner(),
+        [
+            ("/b".to_string(), s("2"), s("3")),
+            ("/a/1".to_string(), None, s("null")),
+            ("/a/0".to_string(), s("1"), None),
+            ("/b".to_string(), s("3"), None),
+            ("/c".to_string(), None, s("3")),
+        ]
+    );
+}
@@ -0,0 +1,78 @@
+use json_parser::parse;
+
+#[test]
+fn openapi() {
+    use json_parser::OpenApi;
+
+    let api = OpenApi::parse(
+        br##"{
+            "openapi": "3.1.0",
+            "paths": {
+                "/pets/{id}": {"$ref": "#/components/pathItems/Pet"},
+                "/pets": {
+                    "post": {"operationId": "addPet"},
+                    "get": {"operationId": "listPets",
+                            "responses": {"200": {"$ref": "#/components/responses/Pets"}}},
+                    "parameters": []
+                }
+            },
+            "components": {
+                "pathItems": {"Pet": {"delete": {}, "get": {"operationId": "getPet"}}},
+                "responses": {"Pets": {"content": {"application/json": {"schema": {
+                    "type": "array", "items": {"$ref": "#/components/schemas/Pet"}}}}}},
+                "schemas": {
+                    "Pet": {"type": "object", "properties": {
+                        "name": {"type": "string"},
+                        "children": {"type": "array", "items": {"$ref": "#/components/schemas/Pet"}},
+                        "owner": {"$ref": "#/components/schemas/Owner%20Info"}}},
+                    "Owner Info": {"$ref": "#/components/schemas/Name"},
+                    "Name": {"type": "string"},
+                    "Loop": {"$ref": "#/components/schemas/Loop"}
+                }
+            }
+        }"##,
+    )
+    .unwrap();
+    assert_eq!(api.version(), Some("3.1.0"));
+    let operations: Vec<_> = api
+        .operations()
+        .unwrap()
+        .iter()
+        .map(|op| (op.path(), op.method(), op.operation_id()))
+        .collect();
+    assert_eq!(
+        operations,
+        [
+            ("/pets", "get", Some("listPets")),
+            ("/pets", "post", Some("addPet")),
+            ("/pets/{id}", "get", Some("getPet")),
+            ("/pets/{id}", "delete", None),
+        ]
+    );
+    assert_eq!(api.operations().unwrap()[0].pointer(), "/paths/~1pets/get");
+    let names: Vec<_> = api.schemas().iter().map(|(name, _)| *name).collect();
+    assert_eq!(names, ["Loop", "Name", "Owner Info", "Pet"]);
+    assert_eq!(
+        api.lookup("#/components/schemas/Owner%20Info").unwrap(),
+        &parse(br#"{"type": "string"}"#).unwrap()
+    );
+    assert!(api.lookup("#/components/schemas/Loop").is_err());
+    assert!(api.lookup("#/components/schemas/Missing").is_err());
+    assert!(api.lookup("other.json#/Pet").is_err());
+
+    let pet = api.lookup("#/components/schemas/Pet").unwrap();
+    assert_eq!(
+        api.dereference(pet).unwrap(),
+        parse(
+            br##"{"type": "object", "properties": {
+                "name": {"type": "string"},
+                "children": {"type": "array", "items": {"type": "object", "properties": {
+                    "name": {"type": "string"},
+                    "children": {"type": "array", "items": {"$ref": "#/components/schemas/Pet"}},
+                    "owner": {"type": "string"}}}},
+                "owner": {"type": "string"}}}"##
+        )
+        .unwrap()
+    );
+    assert!(OpenApi::parse(br#"{"paths": {}}"#).is_err());
+}
@@ -0,0 +1,34 @@
+use json_parser::{outline, Region, RegionKind, DEFAULT_MAX_DEPTH};
+
+#[test]
+fn outlined() {
+    let input = br#"{"a": [1, {"b": []}], "c": {}"#;
+    let region = |kind, span: std::ops::Range<usize>, pointer: &str, depth| Region {
+        kind,
+        span,
+        pointer: pointer.to_string(),
+        depth,
+    };
+    assert_eq!(
+        outline(input).unwrap(),
+        [
+            region(RegionKind::Object, 0..input.len(), "", 0),
+            region(RegionKind::Array, 6..20, "/a", 1),
+            region(RegionKind::Object, 10..19, "/a/1", 2),
+            region(RegionKind::Array, 16..18, "/a/1/b", 3),
+            region(RegionKind::Object, 27..29, "/c", 1),
+        ]
+    );
+    assert!(outline(b"[}").is_err());
+}
+
+#[test]
+fn outline_nesting_limited() {
+    let deep = "[".repeat(DEFAULT_MAX_DEPTH);
+    assert_eq!(outline(deep.as_bytes()).unwrap().len(), DEFAULT_MAX_DEPTH);
+    let deeper = "[".repeat(100_000);
+    assert_eq!(
+        outline(deeper.as_bytes()).unwrap_err().to_string(),
+        format!("Nesting exceeds maximum depth of {}", DEFAULT_MAX_DEPTH)
+    );
+}
@@ -0,0 +1,75 @@
+use json_parser::{parse, JSONValue, Patch};
+
+#[test]
+fn patch_inverted() {
+    let base = parse(br#"{"a": [1, 2], "b": {"c": 1}, "d": "x", "e": true}"#).unwrap();
+    let patch = Patch::parse(
+        br#"[
+            {"op": "add", "path": "/a/-", "value": 3},
+            {"op": "add", "path": "/a/0", "value": 0},
+            {"op": "add", "path": "/d", "value": "y"},
+            {"op": "remove", "path": "/b/c"},
+            {"op": "replace", "path": "/e", "value": false},
+            {"op": "move", "from": "/a/1", "path": "/d"},
+            {"op": "move", "from": "/a/0", "path": "/b/f"},
+            {"op": "copy", "from": "/b", "path": "/g"},
+            {"op": "test", "path": "/d", "value": 1}
+        ]"#,
+    )
+    .unwrap();
+    let mut doc = base.clone();
+    patch.apply(&mut doc).unwrap();
+    let undo = patch.invert(&base).unwrap();
+    let mut undone = doc.clone();
+    undo.apply(&mut undone).unwrap();
+    assert_eq!(undone, base);
+
+    let mut round_trip = base.clone();
+    patch.compose(&undo).apply(&mut round_trip).unwrap();
+    assert_eq!(round_trip, base);
+    assert!(patch.invert(&JSONValue::Null).is_err());
+
+    let replaces = Patch::parse(
+        br#"[
+            {"op": "add", "path": "/x", "value": 1},
+            {"op": "replace", "path": "/x", "value": 2},
+            {"op": "replace", "path": "/x", "value": 3}
+        ]"#,
+    )
+    .unwrap();
+    let squashed = replaces.compose(&Patch::default());
+    assert_eq!(squashed.ops().len(), 1);
+    let (mut a, mut b) = (base.clone(), base.clone());
+    replaces.apply(&mut a).unwrap();
+    squashed.apply(&mut b).unwrap();
+    assert_eq!(a, b);
+}
+
+#[test]
+fn composed_appends() {
+    let base = parse(br#"{"a": [1]}"#).unwrap();
+    let add_then_replace = |path: &str| {
+        let ops = format!(
+            r#"[{{"op": "add", "path": "{0}", "value": 2}},
+                {{"op": "replace", "path": "{0}", "value": 3}}]"#,
+            path
+        );
+        Patch::parse(ops.as_bytes()).unwrap()
+    };
+
+    // an add at an index and a replace there squash into one add
+    let at_index = add_then_replace("/a/0");
+    let squashed = at_index.compose(&Patch::default());
+    assert_eq!(squashed.ops().len(), 1);
+    let mut doc = base.clone();
+    squashed.apply(&mut doc).unwrap();
+    assert_eq!(doc, parse(br#"{"a": [3, 1]}"#).unwrap());
+
+    // but `-` after an append is past the end, so the replace fails, and
+    // composing mustn't turn that into a successful add
+    let appended = add_then_replace("/a/-");
+    let composed = appended.compose(&Patch::default());
+    assert_eq!(composed.ops(), appended.ops());
+    assert!(appended.apply(&mut base.clone()).is_err());
+    assert!(composed.apply(&mut base.clone()).is_err());
+}
@@ -0,0 +1,97 @@
+use json_parser::{parse, transform_at, JSONValue, PathPattern};
+
+fn doc() -> JSONValue {
+    parse(
+        br#"{"users": [
+            {"name": "a", "email": "a@x", "auth": {"password": "p1"}},
+            {"name": "b", "email": "b@x", "password": "p2"}
+        ], "password": "root", "a/b": {"c": 1}}"#,
+    )
+    .unwrap()
+}
+
+#[test]
+fn patterns() {
+    let any = PathPattern::new("/users/*/email").unwrap();
+    assert!(any.matches("/users/0/email"));
+    assert!(any.matches("/users/x/email"));
+    assert!(!any.matches("/users/email"));
+    assert!(!any.matches("/users/0/email/more"));
+    assert!(!any.matches("users/0/email"));
+
+    let deep = PathPattern::new("/**/password").unwrap();
+    for ptr in ["/password", "/users/1/password", "/users/0/auth/password"] {
+        assert!(deep.matches(ptr), "{}", ptr);
+    }
+    assert!(!deep.matches("/users/0/passwords"));
+    // `~1` in a pattern stands for `/`, as in a pointer
+    assert!(PathPattern::new("/a~1b/c").unwrap().matches("/a~1b/c"));
+    assert!(PathPattern::new("").unwrap().matches(""));
+    assert_eq!(
+        PathPattern::new("users").unwrap_err().to_string(),
+        "Pattern must start with '/': \"users\""
+    );
+
+    let found: Vec<_> = deep
+        .find(&doc())
+        .into_iter()
+        .map(|(ptr, v)| (ptr, v.clone()))
+        .collect();
+    assert_eq!(
+        found,
+        [
+            ("/password".to_string(), JSONValue::Str("root".to_string())),
+            (
+                "/users/0/auth/password".to_string(),
+                JSONValue::Str("p1".to_string())
+            ),
+            (
+                "/users/1/password".to_string(),
+                JSONValue::Str("p2".to_string())
+            ),
+        ]
+    );
+}
+
+#[test]
+fn transformed() {
+    let mut v = doc();
+    let redact = PathPattern::new("/**/password").unwrap();
+    let n = transform_at(&mut v, &redact, |p| *p = JSONValue::Str("***".to_string()));
+    assert_eq!(n, 3);
+    let emails = PathPattern::new("/users/*/email").unwrap();
+    let n = transform_at(&mut v, &emails, |e| {
+        if let JSONValue::Str(s) = e {
+            s.make_ascii_uppercase();
+        }
+    });
+    assert_eq!(n, 2);
+    assert_eq!(
+        v,
+        parse(
+            br#"{"users": [
+                {"name": "a", "email": "A@X", "auth": {"password": "***"}},
+                {"name": "b", "email": "B@X", "password": "***"}
+            ], "password": "***", "a/b": {"c": 1}}"#
+        )
+        .unwrap()
+    );
+
+    // a match is transformed after the matches inside it
+    let mut v = parse(br#"{"n": {"n": {"n": 1}}}"#).unwrap();
+    let mut seen = Vec::new();
+    let n = transform_at(&mut v, &PathPattern::new("/**/n").unwrap(), |e| {
+        seen.push(e.to_json_string());
+        *e = JSONValue::Num(0.0);
+    });
+    assert_eq!(n, 3);
+    assert_eq!(seen, [r#"1"#, r#"{"n":0}"#, r#"{"n":0}"#]);
+    assert_eq!(v, parse(br#"{"n": 0}"#).unwrap());
+
+    let mut v = doc();
+    assert_eq!(
+        transform_at(&mut v, &PathPattern::new("/nothing/*").unwrap(), |_| {}),
+        0
+    );
+    assert_eq!(v, doc());
+}
@@ -0,0 +1,22 @@
+#![cfg(feature = "im")]
+
+use json_parser::{parse, JSONValue};
+
+#[test]
+fn persistent() {
+    use json_parser::PersistentValue;
+
+    let doc = parse(br#"{"a": {"b": [1, 2]}, "c": "d"}"#).unwrap();
+    let v0 = PersistentValue::from(doc.clone());
+    let v1 = v0.with("/a/b/-", JSONValue::Num(3.0)).unwrap();
+    let v2 = v1.without("/c").unwrap();
+    assert!(v2.with("/x/y", JSONValue::Null).is_err());
+    assert!(v2.without("/c").is_none());
+    assert_eq!(v0.to_value(), doc);
+    assert_eq!(
+        v1.to_value(),
+        parse(br#"{"a": {"b": [1, 2, 3]}, "c": "d"}"#).unwrap()
+    );
+    assert_eq!(v2.to_value(), parse(br#"{"a": {"b": [1, 2, 3]}}"#).unwrap());
+    assert_eq!(v2.get("/a/b/2"), Some(&PersistentValue::Num(3.0)));
+}
@@ -0,0 +1,34 @@
+use json_parser::{parse, JSONValue, ValuePool};
+
+#[test]
+fn pooled() {
+    let json = br#"{"a": [1, "x", null], "b": "y", "c": {}}"#;
+    let mut pool = ValuePool::new();
+    assert!(pool.is_empty());
+    let v = pool.parse(json).unwrap();
+    assert_eq!(v, parse(json).unwrap());
+
+    // three keys, two strings, one array and two objects
+    pool.recycle(v);
+    assert_eq!(pool.len(), 8);
+    // parsing the same document again takes every buffer back, cleared
+    let again = pool.parse(json).unwrap();
+    assert_eq!(again, parse(json).unwrap());
+    assert!(pool.is_empty());
+
+    pool.recycle(again);
+    pool.recycle(JSONValue::Num(1.0));
+    assert_eq!(pool.len(), 8);
+    pool.clear();
+    assert!(pool.is_empty());
+    assert!(pool.parse(b"[1,").is_err());
+}
+
+#[test]
+fn pool_bounded() {
+    let mut pool = ValuePool::with_max_buffers(2);
+    let v = parse(br#"[["a", "b", "c"], [], []]"#).unwrap();
+    pool.recycle(v);
+    // at most two arrays and two strings are kept
+    assert_eq!(pool.len(), 4);
+}
@@ -0,0 +1,61 @@
+use json_parser::{
+    parse_prefix, parse_prefix_with_options, ParseOptions, PrefixStatus, DEFAULT_MAX_DEPTH,
+};
+
+#[test]
+fn prefix_nesting_limited() {
+    let input = vec![b'['; 200_000];
+    assert_eq!(
+        parse_prefix(&input).unwrap_err().to_string(),
+        format!("Nesting exceeds maximum depth of {}", DEFAULT_MAX_DEPTH)
+    );
+    let deep = vec![b'['; DEFAULT_MAX_DEPTH];
+    let parsed = parse_prefix(&deep).unwrap();
+    assert_eq!(parsed.status, PrefixStatus::NeedMore);
+    assert_eq!(parsed.consumed, DEFAULT_MAX_DEPTH);
+
+    let options = ParseOptions {
+        max_depth: Some(2),
+        ..Default::default()
+    };
+    let parsed = parse_prefix_with_options(b"[[1], {\"a\": [", &options);
+    assert_eq!(
+        parsed.unwrap_err().to_string(),
+        "Nesting exceeds maximum depth of 2"
+    );
+    let parsed = parse_prefix_with_options(b"[[1], {\"a\": 2", &options).unwrap();
+    assert_eq!(parsed.value.unwrap().to_json_string(), r#"[[1],{"a":2}]"#);
+}
+
+#[test]
+fn prefix_cut_off_tokens() {
+    let value = |input: &str| {
+        let parsed = parse_prefix(input.as_bytes()).unwrap();
+        assert_eq!(parsed.status, PrefixStatus::NeedMore);
+        (parsed.value.map(|v| v.to_json_string()), parsed.consumed)
+    };
+    // a cut-off string is kept as far as it goes, but not consumed
+    assert_eq!(
+        value(r#"{"a": [1, "hel"#),
+        (Some(r#"{"a":[1,"hel"]}"#.to_string()), 9)
+    );
+    // an escape or character only partly there is left out
+    assert_eq!(value(r#"["ab\u00"#), (Some(r#"["ab"]"#.to_string()), 1));
+    assert_eq!(value(r#"["ab\"#), (Some(r#"["ab"]"#.to_string()), 1));
+    assert_eq!(value(r#""x"#), (Some(r#""x""#.to_string()), 0));
+    // as are numbers and keywords
+    assert_eq!(value("[1, 2"), (Some("[1,2]".to_string()), 3));
+    assert_eq!(value("[1, 2."), (Some("[1,2]".to_string()), 3));
+    assert_eq!(value("[tr"), (Some("[true]".to_string()), 1));
+    assert_eq!(value("[-"), (Some("[]".to_string()), 1));
+    // keys without a value are dropped, cut off or not
+    assert_eq!(
+        value(r#"{"a": 1, "ke"#),
+        (Some(r#"{"a":1}"#.to_string()), 8)
+    );
+    assert_eq!(
+        value(r#"{"a": 1, "key":"#),
+        (Some(r#"{"a":1}"#.to_string()), 15)
+    );
+    assert_eq!(value("  "), (None, 0));
+}
@@ -0,0 +1,57 @@
+use json_parser::{parse, profile_records, JSONValue};
+
+#[test]
+fn profiled() {
+    let ndjson: String = (0..5000)
+        .map(|i| match i % 4 {
+            0 => format!("{{\"id\": {}, \"name\": null, \"tags\": []}}\n", i),
+            _ => format!(
+                "{{\"id\": {}, \"name\": \"user{}\", \"tags\": [\"a\", \"b\"]}}\n",
+                i,
+                i % 100
+            ),
+        })
+        .collect();
+    let profile = profile_records(ndjson.as_bytes()).unwrap();
+    assert_eq!(profile.records(), 5000);
+    let paths: Vec<_> = profile.paths().map(|(path, _)| path).collect();
+    assert_eq!(paths, ["", "/id", "/name", "/tags", "/tags/*"]);
+
+    let id = profile.path("/id").unwrap();
+    assert_eq!(id.types.get("integer"), Some(&5000));
+    assert!(
+        (4800.0..5200.0).contains(&id.distinct()),
+        "{}",
+        id.distinct()
+    );
+    let name = profile.path("/name").unwrap();
+    assert_eq!(name.null_rate(), 0.25);
+    assert_eq!((name.min_length, name.max_length), (Some(5), Some(6)));
+    assert!(
+        (73.0..77.0).contains(&name.distinct()),
+        "{}",
+        name.distinct()
+    );
+    let tags = profile.path("/tags").unwrap();
+    assert_eq!((tags.min_length, tags.max_length), (Some(0), Some(2)));
+    assert_eq!(profile.path("/tags/*").unwrap().count, 7500);
+
+    let report = profile.to_json();
+    let JSONValue::Dict(report) = &report else {
+        panic!("bad report {:?}", report)
+    };
+    let JSONValue::Dict(paths) = &report["paths"] else {
+        panic!("bad report {:?}", report)
+    };
+    let JSONValue::Dict(mut name) = paths["/name"].clone() else {
+        panic!("bad report {:?}", report)
+    };
+    // an estimate, checked above
+    assert!(name.remove("distinct").is_some());
+    let expected = parse(
+        br#"{"count": 5000, "types": {"null": 1250, "string": 3750}, "null_rate": 0.25,
+             "min_length": 5, "max_length": 6}"#,
+    )
+    .unwrap();
+    assert_eq!(JSONValue::Dict(name), expected);
+}
@@ -0,0 +1,71 @@
+use std::time::Duration;
+
+use json_parser::{
+    parse_auto_with_progress, profile_records_with_progress, sort_lines_with_progress,
+    split_with_progress, Progress, ProgressReport, SortOptions,
+};
+
+#[test]
+fn progress_reported() {
+    let ndjson: String = (0..100)
+        .map(|i| format!("{{\"i\": {}}}\n", 99 - i))
+        .collect();
+    let total = ndjson.len() as u64;
+    let mut seen = Vec::new();
+    fn report(seen: &mut Vec<Progress>, total: u64) -> ProgressReport<'_> {
+        ProgressReport::new(move |p| seen.push(*p))
+            .total_bytes(total)
+            .interval(Duration::ZERO)
+    }
+
+    let records = parse_auto_with_progress(ndjson.as_bytes(), report(&mut seen, total)).unwrap();
+    assert_eq!(records.count(), 100);
+    // one per record and one at the end
+    assert_eq!(seen.len(), 101);
+    assert!(seen.windows(2).all(|w| w[0].bytes <= w[1].bytes));
+    let last = seen[100];
+    assert_eq!((last.bytes, last.records), (total, 100));
+    assert_eq!(last.fraction(), Some(1.0));
+    assert_eq!(last.eta(), Some(Duration::ZERO));
+
+    seen.clear();
+    let options = SortOptions {
+        key: "/i".to_string(),
+        ..SortOptions::default()
+    };
+    let sorted = sort_lines_with_progress(
+        ndjson.as_bytes(),
+        std::io::sink(),
+        &options,
+        report(&mut seen, total),
+    );
+    assert_eq!(sorted.unwrap(), 100);
+    assert_eq!(
+        seen.last().map(|p| (p.bytes, p.records)),
+        Some((total, 100))
+    );
+
+    seen.clear();
+    let array = format!(
+        "[{}]",
+        (0..10).map(|i| i.to_string()).collect::<Vec<_>>().join(",")
+    );
+    let chunks = split_with_progress(
+        array.as_bytes(),
+        "",
+        3,
+        |_, _| Ok(()),
+        report(&mut seen, total),
+    );
+    assert_eq!(chunks.unwrap(), 4);
+    assert_eq!(seen.last().map(|p| p.records), Some(10));
+
+    seen.clear();
+    let profile =
+        profile_records_with_progress(ndjson.as_bytes(), report(&mut seen, total)).unwrap();
+    assert_eq!(profile.records(), 100);
+    assert_eq!(
+        seen.last().map(|p| (p.bytes, p.records)),
+        Some((total, 100))
+    );
+}
@@ -0,0 +1,45 @@
+use json_parser::{parse, JSONValue, ProtoAny};
+
+#[test]
+fn protojson() {
+    let msg = parse(
+        br#"{"userId": "9007199254740993", "max_size": 12, "ratio": "NaN", "note": null,
+            "detail": {"@type": "type.googleapis.com/google.protobuf.Duration", "value": "1.5s"},
+            "extra": {"@type": "example.com/acme.Tag", "name": "x"}}"#,
+    )
+    .unwrap();
+    let field = |name| msg.proto_field(name);
+    assert_eq!(
+        field("user_id").and_then(JSONValue::as_proto_int64),
+        Some(9007199254740993)
+    );
+    assert_eq!(
+        field("max_size").and_then(JSONValue::as_proto_uint64),
+        Some(12)
+    );
+    assert!(field("ratio")
+        .and_then(JSONValue::as_proto_double)
+        .is_some_and(f64::is_nan));
+    assert_eq!(field("note"), None);
+    assert_eq!(field("missing"), None);
+    assert!(JSONValue::Str(String::new()).is_proto_default());
+    assert!(!JSONValue::Num(1.0).is_proto_default());
+    assert_eq!(
+        JSONValue::from_proto_int64(-5),
+        JSONValue::Str("-5".to_string())
+    );
+    assert_eq!(
+        JSONValue::from_proto_double(f64::NEG_INFINITY).as_proto_double(),
+        Some(f64::NEG_INFINITY)
+    );
+
+    let detail = ProtoAny::from_json(field("detail").unwrap()).unwrap();
+    assert_eq!(detail.type_name(), "google.protobuf.Duration");
+    assert_eq!(detail.message(), &JSONValue::Str("1.5s".to_string()));
+    assert_eq!(&detail.to_json(), field("detail").unwrap());
+    let extra = ProtoAny::from_json(field("extra").unwrap()).unwrap();
+    assert_eq!(extra.type_name(), "acme.Tag");
+    assert_eq!(extra.message(), &parse(br#"{"name": "x"}"#).unwrap());
+    assert_eq!(&extra.to_json(), field("extra").unwrap());
+    assert!(ProtoAny::from_json(&parse(br#"{"name": "x"}"#).unwrap()).is_err());
+}
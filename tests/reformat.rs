@@ -0,0 +1,53 @@
+use json_parser::{parse, reformat, ReformatStyle};
+
+#[test]
+fn reformatted_stream() {
+    // hands over one byte per read, splitting every token
+    struct Trickle<'a>(&'a [u8]);
+    impl std::io::Read for Trickle<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let Some((first, rest)) = self.0.split_first() else {
+                return Ok(0);
+            };
+            buf[0] = *first;
+            self.0 = rest;
+            Ok(1)
+        }
+    }
+    let run = |input: &str, style: &ReformatStyle| {
+        let mut out = Vec::new();
+        reformat(Trickle(input.as_bytes()), &mut out, style)
+            .map(|()| String::from_utf8(out).unwrap())
+    };
+
+    let input = r#" {"z": [1.50, -2e+3, true, null, [], {}], "a": {"b\"}": "x\u0041"}, "e": [[]]}"#;
+    assert_eq!(
+        run(input, &ReformatStyle::default()).unwrap(),
+        "{\n  \"z\": [\n    1.50,\n    -2e+3,\n    true,\n    null,\n    [],\n    {}\n  ],\n  \
+         \"a\": {\n    \"b\\\"}\": \"x\\u0041\"\n  },\n  \"e\": [\n    []\n  ]\n}"
+    );
+    let compact = run(input, &ReformatStyle { indent: None }).unwrap();
+    assert_eq!(
+        compact,
+        r#"{"z":[1.50,-2e+3,true,null,[],{}],"a":{"b\"}":"x\u0041"},"e":[[]]}"#
+    );
+    assert_eq!(
+        parse(compact.as_bytes()).unwrap(),
+        parse(input.as_bytes()).unwrap()
+    );
+    assert_eq!(run("7", &ReformatStyle::default()).unwrap(), "7");
+
+    for bad in [
+        "",
+        "[1,]",
+        "{\"a\" 1}",
+        "[01]",
+        "[tru]",
+        "\"open",
+        "[1] 2",
+        "{}}",
+        "[\"\n\"]",
+    ] {
+        assert!(run(bad, &ReformatStyle::default()).is_err(), "{:?}", bad);
+    }
+}
@@ -0,0 +1,42 @@
+use json_parser::{sample_lines, JSONValue};
+
+#[test]
+fn sampled_lines() {
+    let ndjson: String = (0..1000).map(|i| format!("{{\"id\": {}}}\n", i)).collect();
+    let ids = |sample: Vec<JSONValue>| -> Vec<usize> {
+        sample
+            .iter()
+            .map(|r| match r {
+                JSONValue::Dict(d) => match d["id"] {
+                    JSONValue::Num(n) => n as usize,
+                    _ => panic!("bad record {:?}", r),
+                },
+                _ => panic!("bad record {:?}", r),
+            })
+            .collect()
+    };
+    let sample = ids(sample_lines(ndjson.as_bytes(), 10, 7).unwrap());
+    assert_eq!(sample.len(), 10);
+    assert!(sample.windows(2).all(|w| w[0] < w[1]), "{:?}", sample);
+    assert_eq!(sample, ids(sample_lines(ndjson.as_bytes(), 10, 7).unwrap()));
+    assert_ne!(sample, ids(sample_lines(ndjson.as_bytes(), 10, 8).unwrap()));
+
+    let all = sample_lines(&b"[1, 2, 3]"[..], 5, 0).unwrap();
+    assert_eq!(all, [1.0, 2.0, 3.0].map(JSONValue::Num));
+    assert!(sample_lines(ndjson.as_bytes(), 0, 0).unwrap().is_empty());
+
+    // every record is about as likely to be picked
+    let mut picked = [0; 10];
+    for seed in 0..2000 {
+        let sample = sample_lines(&b"[0, 1, 2, 3, 4, 5, 6, 7, 8, 9]"[..], 1, seed).unwrap();
+        match sample[..] {
+            [JSONValue::Num(n)] => picked[n as usize] += 1,
+            _ => panic!("bad sample {:?}", sample),
+        }
+    }
+    assert!(
+        picked.iter().all(|n| (120..280).contains(n)),
+        "{:?}",
+        picked
+    );
+}
@@ -0,0 +1,191 @@
+use json_parser::Schema;
+
+fn violations(schema: &str, json: &str) -> Vec<String> {
+    let schema = Schema::parse(schema.as_bytes()).unwrap();
+    schema
+        .validate(json.as_bytes())
+        .unwrap()
+        .iter()
+        .map(|v| v.to_string())
+        .collect()
+}
+
+#[test]
+fn types() {
+    let schema = r#"{"type": ["integer", "string"]}"#;
+    assert!(violations(schema, "3").is_empty());
+    assert!(violations(schema, r#""x""#).is_empty());
+    assert_eq!(
+        violations(schema, "3.5"),
+        ["byte 0: Expected integer or string, found number"]
+    );
+    // an integer is also a number
+    assert!(violations(r#"{"type": "number"}"#, "3").is_empty());
+    assert_eq!(
+        violations(r#"{"type": "object"}"#, "[]"),
+        ["byte 0: Expected object, found array"]
+    );
+}
+
+#[test]
+fn enum_and_const() {
+    let schema = r#"{"enum": [1, "a", [true]]}"#;
+    assert!(violations(schema, "[true]").is_empty());
+    assert_eq!(
+        violations(schema, "[false]"),
+        ["byte 0: Value is not one of the allowed values"]
+    );
+    let schema = r#"{"const": {"a": null}}"#;
+    assert!(violations(schema, r#"{"a": null}"#).is_empty());
+    assert_eq!(
+        violations(schema, r#"{"a": 0}"#),
+        ["byte 0: Value is not the required constant"]
+    );
+}
+
+#[test]
+fn numeric_bounds() {
+    let schema = r#"{"minimum": 1, "maximum": 10}"#;
+    assert!(violations(schema, "1").is_empty());
+    assert!(violations(schema, "10").is_empty());
+    assert_eq!(
+        violations(schema, "0.5"),
+        ["byte 0: Number 0.5 is below the minimum of 1"]
+    );
+    assert_eq!(
+        violations(schema, "11"),
+        ["byte 0: Number 11 is above the maximum of 10"]
+    );
+
+    let schema = r#"{"exclusiveMinimum": 1, "exclusiveMaximum": 10}"#;
+    assert!(violations(schema, "1.5").is_empty());
+    assert_eq!(violations(schema, "1"), ["byte 0: Number 1 is not above 1"]);
+    assert_eq!(
+        violations(schema, "10"),
+        ["byte 0: Number 10 is not below 10"]
+    );
+}
+
+#[test]
+fn multiple_of() {
+    let schema = r#"{"multipleOf": 3}"#;
+    assert!(violations(schema, "-9").is_empty());
+    assert_eq!(
+        violations(schema, "10"),
+        ["byte 0: Number 10 is not a multiple of 3"]
+    );
+
+    // 0.3 / 0.1 is 2.9999999999999996 in binary, but 0.3 is a multiple
+    let schema = r#"{"multipleOf": 0.1}"#;
+    for n in ["0.3", "0.7", "1.1", "123.4", "0"] {
+        assert!(violations(schema, n).is_empty(), "{}", n);
+    }
+    assert_eq!(
+        violations(schema, "0.35"),
+        ["byte 0: Number 0.35 is not a multiple of 0.1"]
+    );
+
+    let schema = r#"{"multipleOf": 0.01}"#;
+    for n in ["0.07", "19.99", "1e-2", "4.35"] {
+        assert!(violations(schema, n).is_empty(), "{}", n);
+    }
+    assert_eq!(
+        violations(schema, "19.995"),
+        ["byte 0: Number 19.995 is not a multiple of 0.01"]
+    );
+}
+
+#[test]
+fn string_length() {
+    let schema = r#"{"minLength": 2, "maxLength": 3}"#;
+    // lengths count characters, not bytes
+    assert!(violations(schema, r#""é€""#).is_empty());
+    assert_eq!(
+        violations(schema, r#""a""#),
+        ["byte 0: String has 1 characters, fewer than 2"]
+    );
+    assert_eq!(
+        violations(schema, r#""abcd""#),
+        ["byte 0: String has 4 characters, more than 3"]
+    );
+}
+
+#[test]
+fn array_items() {
+    let schema = r#"{"minItems": 1, "maxItems": 2, "items": {"type": "string"}}"#;
+    assert!(violations(schema, r#"["a", "b"]"#).is_empty());
+    assert_eq!(
+        violations(schema, "[]"),
+        ["byte 0: Array has 0 items, fewer than 1"]
+    );
+    assert_eq!(
+        violations(schema, r#"["a", 2, "c"]"#),
+        [
+            "/1 (byte 6): Expected string, found integer",
+            "byte 0: Array has 3 items, more than 2"
+        ]
+    );
+}
+
+#[test]
+fn object_properties() {
+    let schema = r#"{"minProperties": 1, "maxProperties": 2}"#;
+    assert!(violations(schema, r#"{"a": 1}"#).is_empty());
+    assert_eq!(
+        violations(schema, "{}"),
+        ["byte 0: Object has 0 properties, fewer than 1"]
+    );
+    assert_eq!(
+        violations(schema, r#"{"a": 1, "b": 2, "c": 3}"#),
+        ["byte 0: Object has 3 properties, more than 2"]
+    );
+
+    let schema = r#"{
+        "properties": {"id": {"type": "integer"}},
+        "required": ["id", "name"],
+        "additionalProperties": {"type": "string"}
+    }"#;
+    assert!(violations(schema, r#"{"id": 1, "name": "x"}"#).is_empty());
+    assert_eq!(
+        violations(schema, r#"{"id": "1", "name": 2}"#),
+        [
+            "/id (byte 7): Expected integer, found string",
+            "/name (byte 20): Expected string, found integer"
+        ]
+    );
+    assert_eq!(
+        violations(schema, r#"{"id": 1}"#),
+        ["byte 0: Missing required property \"name\""]
+    );
+}
+
+#[test]
+fn boolean_schemas() {
+    assert!(violations("true", r#"{"anything": [1]}"#).is_empty());
+    assert_eq!(
+        violations("false", "null"),
+        ["byte 0: Value is not allowed here"]
+    );
+    let schema = r#"{"additionalProperties": false}"#;
+    assert!(violations(schema, "{}").is_empty());
+    assert_eq!(
+        violations(schema, r#"{"extra": 1}"#),
+        ["/extra (byte 10): Value is not allowed here"]
+    );
+}
+
+#[test]
+fn bad_schemas() {
+    let err = |schema: &str| Schema::parse(schema.as_bytes()).unwrap_err().to_string();
+    assert_eq!(
+        err(r#"{"anyOf": []}"#),
+        "Unsupported schema keyword `anyOf` at \"\""
+    );
+    assert_eq!(err(r#"{"type": "thing"}"#), "Unknown type at \"/type\"");
+    assert_eq!(
+        err(r#"{"minLength": -1}"#),
+        "Expected a non-negative integer at \"/minLength\""
+    );
+    // annotations are allowed, and ignored
+    assert!(Schema::parse(br#"{"title": "T", "description": "D"}"#).is_ok());
+}
@@ -0,0 +1,123 @@
+use json_parser::{parse, JSONValue, SerializeOptions};
+
+fn doc() -> JSONValue {
+    parse(br#"{"b": [1, 2, 3], "a": {"y": "<&>", "x": []}, "d": [11111, 22222, 33333, 44444, 55555, 66666]}"#)
+        .unwrap()
+}
+
+#[test]
+fn sorted_and_escaped() {
+    let sorted = SerializeOptions {
+        sort_keys: true,
+        ..Default::default()
+    };
+    assert_eq!(
+        doc().to_json_string_with(&sorted),
+        r#"{"a":{"x":[],"y":"<&>"},"b":[1,2,3],"d":[11111,22222,33333,44444,55555,66666]}"#
+    );
+    let safe = SerializeOptions {
+        ascii_only: true,
+        html_safe: true,
+        ..Default::default()
+    };
+    let v = JSONValue::Str("<&> \u{e9}\u{2028}\u{1f600}".to_string());
+    assert_eq!(
+        v.to_json_string_with(&safe),
+        r#""\u003c\u0026\u003e \u00e9\u2028\ud83d\ude00""#
+    );
+    let html_only = SerializeOptions {
+        html_safe: true,
+        ..Default::default()
+    };
+    assert_eq!(
+        v.to_json_string_with(&html_only),
+        "\"\\u003c\\u0026\\u003e \u{e9}\\u2028\u{1f600}\""
+    );
+    assert_eq!(parse(v.to_json_string_with(&safe).as_bytes()).unwrap(), v);
+}
+
+#[test]
+fn pretty_layout() {
+    let pretty = "\
+{
+  \"a\": {\"x\": [], \"y\": \"<&>\"},
+  \"b\": [1, 2, 3],
+  \"d\": [11111, 22222, 33333, 44444, 55555, 66666]
+}";
+    assert_eq!(doc().to_json_pretty(), pretty);
+    assert_eq!(format!("{:#}", doc()), pretty);
+
+    // without an inline width every container is broken up
+    let indented = SerializeOptions {
+        sort_keys: true,
+        indent: Some(4),
+        ..Default::default()
+    };
+    assert_eq!(
+        parse(b"{\"a\": [1, {}], \"b\": {\"c\": null}}")
+            .unwrap()
+            .to_json_string_with(&indented),
+        "\
+{
+    \"a\": [
+        1,
+        {}
+    ],
+    \"b\": {
+        \"c\": null
+    }
+}"
+    );
+
+    let narrow = SerializeOptions {
+        sort_keys: true,
+        indent: Some(2),
+        max_inline_width: Some(20),
+        ..Default::default()
+    };
+    assert_eq!(
+        doc().to_json_string_with(&narrow),
+        "\
+{
+  \"a\": {
+    \"x\": [],
+    \"y\": \"<&>\"
+  },
+  \"b\": [1, 2, 3],
+  \"d\": [
+    11111,
+    22222,
+    33333,
+    44444,
+    55555,
+    66666
+  ]
+}"
+    );
+    let packed = SerializeOptions {
+        pack_arrays: true,
+        ..narrow
+    };
+    assert_eq!(
+        doc().to_json_string_with(&packed),
+        "\
+{
+  \"a\": {
+    \"x\": [],
+    \"y\": \"<&>\"
+  },
+  \"b\": [1, 2, 3],
+  \"d\": [
+    11111, 22222,
+    33333, 44444,
+    55555, 66666
+  ]
+}"
+    );
+    for options in [indented, packed, SerializeOptions::pretty()] {
+        assert_eq!(
+            parse(doc().to_json_string_with(&options).as_bytes()).unwrap(),
+            doc()
+        );
+    }
+}
@@ -0,0 +1,28 @@
+use json_parser::{parse, EditSession, JSONValue, Patch};
+
+#[test]
+fn edited() {
+    let doc = parse(br#"{"name": "app", "ports": [80]}"#).unwrap();
+    let mut session = EditSession::new(doc.clone());
+    session.insert("/ports/0", JSONValue::Num(443.0)).unwrap();
+    session.set("/name", JSONValue::Str("web".into())).unwrap();
+    session.set("/debug", JSONValue::Bool(true)).unwrap();
+    assert!(session.remove("/missing").is_err());
+    assert!(session.undo());
+    assert!(session.can_redo());
+    session.remove("/ports/1").unwrap();
+    // the removal replaced the undone `/debug` edit
+    assert!(!session.redo());
+    let edited = parse(br#"{"name": "web", "ports": [443]}"#).unwrap();
+    assert_eq!(session.value().to_value(), edited);
+
+    let patch = session.to_patch();
+    let patch = Patch::parse(patch.to_json().to_json_string().as_bytes()).unwrap();
+    let mut patched = doc.clone();
+    patch.apply(&mut patched).unwrap();
+    assert_eq!(patched, edited);
+
+    while session.undo() {}
+    assert_eq!(session.value().to_value(), doc);
+    assert!(session.to_patch().is_empty());
+}
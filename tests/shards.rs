@@ -0,0 +1,23 @@
+use json_parser::{join, parse, JSONValue, MergeStrategy};
+
+#[test]
+fn joined() {
+    let docs = [
+        r#"{"a": {"x": 1}, "l": [1]}"#,
+        r#"{"a": {"y": 2}, "l": [2]}"#,
+    ]
+    .map(|d| parse(d.as_bytes()).unwrap());
+    let merged = join(docs.clone(), MergeStrategy::DeepMerge).unwrap();
+    assert_eq!(
+        merged,
+        parse(br#"{"a": {"x": 1, "y": 2}, "l": [1, 2]}"#).unwrap()
+    );
+    let collected = join(docs.clone(), MergeStrategy::Collect).unwrap();
+    assert_eq!(collected, JSONValue::Array(docs.to_vec()));
+    assert_eq!(
+        join([], MergeStrategy::Concat).unwrap(),
+        JSONValue::Array(vec![])
+    );
+    let err = join(docs, MergeStrategy::Concat).unwrap_err();
+    assert!(err.to_string().contains("document 0"), "{}", err);
+}
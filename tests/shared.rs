@@ -0,0 +1,23 @@
+use json_parser::{parse, JSONValue, SharedValue};
+
+#[test]
+fn forked() {
+    let doc = parse(br#"{"a": {"b": [1, 2]}, "c": {"d": "e"}}"#).unwrap();
+    let base = SharedValue::from(doc.clone());
+    let mut fork = base.fork();
+    fork.set("/a/b/-", JSONValue::Num(3.0)).unwrap();
+    assert_eq!(fork.remove("/c/d"), Some(SharedValue::Str("e".into())));
+    assert!(fork.set("/x/y", JSONValue::Null).is_err());
+    assert_eq!(base.to_value(), doc);
+    assert_eq!(
+        fork.to_value(),
+        parse(br#"{"a": {"b": [1, 2, 3]}, "c": {}}"#).unwrap()
+    );
+
+    let mut fork = base.fork();
+    fork.set("/a/b/0", JSONValue::Num(0.0)).unwrap();
+    // only the path to the change was copied
+    assert!(!fork.shares(&base));
+    assert!(!fork.get("/a").unwrap().shares(base.get("/a").unwrap()));
+    assert!(fork.get("/c").unwrap().shares(base.get("/c").unwrap()));
+}
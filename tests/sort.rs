@@ -0,0 +1,49 @@
+use json_parser::{parse, sort_lines, JSONValue, SortOptions};
+
+#[test]
+fn sorted_lines() {
+    let input = r#"{"t": 3, "n": "a"}
+{"t": "x", "n": "b"}
+
+{"n": "c"}
+{"t": 1, "n": "d"}
+{"t": 3,  "n": "e"}
+{"t": null, "n": "f"}
+"#;
+    let sorted = |options: &SortOptions| -> Vec<String> {
+        let mut out = Vec::new();
+        let n = sort_lines(input.as_bytes(), &mut out, options).unwrap();
+        assert_eq!(n, 6);
+        String::from_utf8(out)
+            .unwrap()
+            .lines()
+            .map(|line| parse(line.as_bytes()).unwrap())
+            .map(|record| match record {
+                JSONValue::Dict(d) => match &d["n"] {
+                    JSONValue::Str(n) => n.clone(),
+                    n => panic!("bad record {:?}", n),
+                },
+                r => panic!("bad record {:?}", r),
+            })
+            .collect()
+    };
+    let mut options = SortOptions {
+        key: "/t".to_string(),
+        ..SortOptions::default()
+    };
+    assert_eq!(sorted(&options), ["c", "f", "d", "a", "e", "b"]);
+    // a run per line, merged back the same, lines copied as they were
+    options.memory = 1;
+    assert_eq!(sorted(&options), ["c", "f", "d", "a", "e", "b"]);
+    let mut out = Vec::new();
+    sort_lines(input.as_bytes(), &mut out, &options).unwrap();
+    assert!(String::from_utf8(out)
+        .unwrap()
+        .contains("{\"t\": 3,  \"n\": \"e\"}\n"));
+    options.descending = true;
+    assert_eq!(sorted(&options), ["b", "a", "e", "d", "c", "f"]);
+
+    assert!(sort_lines(&b"{}\n{"[..], std::io::sink(), &options).is_err());
+    options.key = "t".to_string();
+    assert!(sort_lines(input.as_bytes(), std::io::sink(), &options).is_err());
+}
@@ -0,0 +1,51 @@
+use json_parser::{parse, split};
+
+#[test]
+fn split_records() {
+    let input = br#"{"meta": {"page": 1},
+  "records": [
+    {"id": 1},
+    {"id": 2},
+    {"id": 3}
+  ],
+  "next": null}"#;
+    let mut chunks = Vec::new();
+    let n = split(&input[..], "/records", 2, |i, chunk| {
+        chunks.push((i, String::from_utf8(chunk.to_vec()).unwrap()));
+        Ok(())
+    })
+    .unwrap();
+    assert_eq!(n, 2);
+    assert_eq!(
+        chunks,
+        [
+            (
+                0,
+                "{\"meta\": {\"page\": 1},\n  \"records\": [\n    {\"id\": 1},\n    {\"id\": 2}\n  ],\n  \"next\": null}".to_string()
+            ),
+            (
+                1,
+                "{\"meta\": {\"page\": 1},\n  \"records\": [\n    {\"id\": 3}\n  ],\n  \"next\": null}".to_string()
+            ),
+        ]
+    );
+    for (_, chunk) in &chunks {
+        parse(chunk.as_bytes()).unwrap();
+    }
+
+    let mut sizes = Vec::new();
+    split(&b"[1, 2, 3, 4, 5]"[..], "", 5, |_, chunk| {
+        sizes.push(chunk.len());
+        Ok(())
+    })
+    .unwrap();
+    assert_eq!(sizes, [15]);
+    let none = |_: usize, _: &[u8]| -> eyre::Result<()> { panic!("no chunks expected") };
+    assert_eq!(split(&br#"{"a": []}"#[..], "/a", 3, none).unwrap(), 0);
+    assert!(split(&br#"{"a": {}}"#[..], "/a", 3, none).is_err());
+    assert!(split(&br#"{"a": []}"#[..], "/b", 3, none).is_err());
+    assert!(split(&br#"{"a": []}"#[..], "/a", 0, none).is_err());
+    // the sink can stop the split
+    let stop = |_: usize, _: &[u8]| -> eyre::Result<()> { eyre::bail!("disk full") };
+    assert!(split(&b"[1]"[..], "", 1, stop).is_err());
+}
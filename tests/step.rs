@@ -0,0 +1,98 @@
+use json_parser::lexer::{Lexer, Token, TokenKind};
+use json_parser::{parse, JSONValue, ParseOptions, Parser, StepResult, DEFAULT_MAX_DEPTH};
+
+#[test]
+fn stepped_from_lexer() {
+    let json = br#"{"a": [1, true], "b": null}"#;
+    let mut parser = Parser::new();
+    let mut results = Vec::new();
+    for t in Lexer::new().tokens(json) {
+        results.push(parser.step(t.unwrap().token, json).unwrap());
+    }
+    use StepResult::*;
+    assert_eq!(
+        results[..results.len() - 1],
+        [
+            Open, Key, Continue, Open, Value, Continue, Value, Value, Continue, Key, Continue,
+            Value
+        ]
+    );
+    assert_eq!(results.last(), Some(&Done(parse(json).unwrap())));
+    assert!(parser.is_done());
+    assert_eq!(parser.expected(), []);
+    assert!(parser.step(Token::NullVal, json).is_err());
+}
+
+#[test]
+fn stepped_by_hand() {
+    // tokens from a tokenizer of the caller's own; strings point into `buf`
+    let buf = b"key";
+    let tokens = [
+        Token::LeftBracket,
+        Token::LeftBrace,
+        Token::StringVal(0, 3),
+        Token::Colon,
+        Token::NumVal(2.5),
+        Token::RightBrace,
+        Token::Comma,
+    ];
+    let mut parser = Parser::new();
+    for t in tokens {
+        parser.step(t, buf).unwrap();
+    }
+    assert_eq!(parser.depth(), 1);
+    assert!(!parser.is_done());
+    assert!(parser.expected().contains(&TokenKind::String));
+    assert!(!parser.expected().contains(&TokenKind::RightBracket));
+
+    // a rejected token leaves the parser as it was
+    let err = parser.step(Token::Colon, buf).unwrap_err();
+    assert!(err.to_string().contains("expected value"), "{}", err);
+    assert!(parser.step(Token::StringVal(0, 9), buf).is_err());
+    assert_eq!(
+        parser.step(Token::BoolVal(false), buf).unwrap(),
+        StepResult::Value
+    );
+    let Ok(StepResult::Done(v)) = parser.step(Token::RightBracket, buf) else {
+        panic!("expected the document to end");
+    };
+    assert_eq!(v, parse(br#"[{"key": 2.5}, false]"#).unwrap());
+}
+
+#[test]
+fn step_nesting_limited() {
+    let mut parser = Parser::new();
+    let mut opened = 0;
+    let err = loop {
+        match parser.step(Token::LeftBracket, b"") {
+            Ok(_) => opened += 1,
+            Err(e) => break e,
+        }
+        assert!(opened <= 200_000);
+    };
+    assert_eq!(opened, DEFAULT_MAX_DEPTH);
+    assert_eq!(
+        err.to_string(),
+        format!("Nesting exceeds maximum depth of {}", DEFAULT_MAX_DEPTH)
+    );
+    for _ in 1..DEFAULT_MAX_DEPTH {
+        assert_eq!(
+            parser.step(Token::RightBracket, b"").unwrap(),
+            StepResult::Value
+        );
+    }
+    let Ok(StepResult::Done(JSONValue::Array(_))) = parser.step(Token::RightBracket, b"") else {
+        panic!("expected the document to end");
+    };
+
+    let options = ParseOptions {
+        max_depth: Some(1),
+        ..Default::default()
+    };
+    let mut parser = Parser::with_options(&options);
+    parser.step(Token::LeftBrace, b"").unwrap();
+    parser.step(Token::StringVal(0, 0), b"").unwrap();
+    parser.step(Token::Colon, b"").unwrap();
+    assert!(parser.step(Token::LeftBracket, b"").is_err());
+    assert_eq!(parser.depth(), 1);
+}
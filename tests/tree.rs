@@ -0,0 +1,57 @@
+use json_parser::{parse, JSONValue};
+use proptest::prelude::*;
+
+mod common;
+use common::arb_value;
+
+#[test]
+fn rendered_tree() {
+    let v = parse(
+        br#"{"name": "hello", "ids": [1, 2.5], "meta": {"ok": true, "none": null, "tags": []}}"#,
+    )
+    .unwrap();
+    let expected = "\
+object(3)
+├── \"ids\": array(2)
+│   ├── [0]: number 1
+│   └── [1]: number 2.5
+├── \"meta\": object(3)
+│   ├── \"none\": null
+│   ├── \"ok\": bool true
+│   └── \"tags\": array(0)
+└── \"name\": string(5B) \"hello\"
+";
+    assert_eq!(v.to_tree_string(), expected);
+    assert_eq!(JSONValue::Null.to_tree_string(), "null\n");
+}
+
+#[test]
+fn long_strings() {
+    // sizes are in bytes, previews in characters
+    let v = JSONValue::Array(vec![
+        JSONValue::Str("é".repeat(41)),
+        JSONValue::Str("x".repeat(2048)),
+        JSONValue::Str("line\n\"quoted\"".to_string()),
+    ]);
+    let expected = format!(
+        "array(3)\n├── [0]: string(82B) \"{}…\"\n├── [1]: string(2.0KB) \"{}…\"\n└── [2]: string(13B) \"line\\n\\\"quoted\\\"\"\n",
+        "é".repeat(40),
+        "x".repeat(40)
+    );
+    assert_eq!(v.to_tree_string(), expected);
+}
+
+proptest! {
+    #[test]
+    fn tree(v in arb_value()) {
+        fn count(v: &JSONValue) -> usize {
+            match v {
+                JSONValue::Array(a) => 1 + a.iter().map(count).sum::<usize>(),
+                JSONValue::Dict(d) => 1 + d.values().map(count).sum::<usize>(),
+                _ => 1,
+            }
+        }
+        // one line per value
+        prop_assert_eq!(v.to_tree_string().lines().count(), count(&v));
+    }
+}
@@ -0,0 +1,97 @@
+use json_parser::{parse, truncate, JSONValue, SerializeOptions, TruncateOptions, TRUNCATED};
+use proptest::prelude::*;
+
+mod common;
+use common::arb_value;
+
+// Whether a preview truncated to one element, entry, character and level at
+// depth 0 stays in bounds: one kept item plus the marker, one character
+// plus `…`.
+fn within(v: &JSONValue, depth: usize) -> bool {
+    match v {
+        JSONValue::Str(s) => s == TRUNCATED || s.chars().count() <= 2,
+        JSONValue::Array(a) => depth < 1 && a.len() <= 2 && a.iter().all(|e| within(e, depth + 1)),
+        JSONValue::Dict(d) => depth < 1 && d.len() <= 2 && d.values().all(|e| within(e, depth + 1)),
+        _ => true,
+    }
+}
+
+fn preview(json: &str, options: &TruncateOptions) -> String {
+    let sorted = SerializeOptions {
+        sort_keys: true,
+        ..SerializeOptions::default()
+    };
+    truncate(&parse(json.as_bytes()).unwrap(), options).to_json_string_with(&sorted)
+}
+
+#[test]
+fn truncated_preview() {
+    let json = r#"{"b": [1, 2, 3, 4], "a": "hello world", "c": {"x": {"y": [1]}}, "d": 1, "e": 2}"#;
+    let options = TruncateOptions {
+        max_array_len: Some(2),
+        max_object_len: Some(3),
+        max_string_len: Some(5),
+        max_depth: Some(2),
+    };
+    // objects keep their first entries in key order and count the rest
+    assert_eq!(
+        preview(json, &options),
+        r#"{"a":"hello…","b":[1,2,"…truncated"],"c":{"x":"…truncated"},"…truncated":2}"#
+    );
+    // the defaults leave a small document alone
+    assert_eq!(
+        preview(json, &TruncateOptions::default()),
+        r#"{"a":"hello world","b":[1,2,3,4],"c":{"x":{"y":[1]}},"d":1,"e":2}"#
+    );
+}
+
+#[test]
+fn truncated_limits() {
+    let none = TruncateOptions {
+        max_array_len: None,
+        max_object_len: None,
+        max_string_len: None,
+        max_depth: None,
+    };
+    // strings are cut by characters, not bytes
+    let short = TruncateOptions {
+        max_string_len: Some(4),
+        ..none
+    };
+    assert_eq!(
+        preview(r#"["héllo wörld", "abcd"]"#, &short),
+        r#"["héll…","abcd"]"#
+    );
+    let empty = TruncateOptions {
+        max_array_len: Some(0),
+        ..none
+    };
+    assert_eq!(preview("[[1], []]", &empty), r#"["…truncated"]"#);
+    let flat = TruncateOptions {
+        max_depth: Some(0),
+        ..none
+    };
+    assert_eq!(preview(r#"{"a": 1}"#, &flat), r#""…truncated""#);
+    assert_eq!(preview("7", &flat), "7");
+}
+
+proptest! {
+    #[test]
+    fn truncated(v in arb_value()) {
+        let unlimited = TruncateOptions {
+            max_array_len: None,
+            max_object_len: None,
+            max_string_len: None,
+            max_depth: None,
+        };
+        prop_assert_eq!(&truncate(&v, &unlimited), &v);
+        let tight = TruncateOptions {
+            max_array_len: Some(1),
+            max_object_len: Some(1),
+            max_string_len: Some(1),
+            max_depth: Some(1),
+        };
+        let preview = truncate(&v, &tight);
+        prop_assert!(within(&preview, 0), "{}", preview);
+    }
+}
@@ -0,0 +1,99 @@
+use json_parser::{parse, JSONValue, VisitAction, VisitContext, Visitor};
+
+// Records every call as `enter`/`leave`, depth, pointer and key.
+#[derive(Default)]
+struct Trace {
+    calls: Vec<String>,
+    skip: Option<&'static str>,
+    stop: Option<&'static str>,
+}
+
+impl Visitor for Trace {
+    fn enter(&mut self, _value: &JSONValue, cx: &VisitContext) -> VisitAction {
+        self.calls.push(format!(
+            "enter {} {:?} {:?}",
+            cx.depth(),
+            cx.pointer(),
+            cx.key()
+        ));
+        if Some(cx.pointer()) == self.skip {
+            VisitAction::SkipSubtree
+        } else if Some(cx.pointer()) == self.stop {
+            VisitAction::Stop
+        } else {
+            VisitAction::Continue
+        }
+    }
+
+    fn leave(&mut self, _value: &JSONValue, cx: &VisitContext) {
+        self.calls.push(format!("leave {:?}", cx.pointer()));
+    }
+}
+
+#[test]
+fn visited() {
+    let v = parse(br#"[{"a/b": [1]}, [], 2]"#).unwrap();
+    let mut trace = Trace::default();
+    assert!(v.visit(&mut trace));
+    assert_eq!(
+        trace.calls,
+        [
+            r#"enter 0 "" None"#,
+            r#"enter 1 "/0" None"#,
+            r#"enter 2 "/0/a~1b" Some("a/b")"#,
+            r#"enter 3 "/0/a~1b/0" None"#,
+            r#"leave "/0/a~1b""#,
+            r#"leave "/0""#,
+            r#"enter 1 "/1" None"#,
+            r#"leave "/1""#,
+            r#"enter 1 "/2" None"#,
+            r#"leave """#,
+        ]
+    );
+
+    // a skipped container is neither descended into nor left
+    let mut trace = Trace {
+        skip: Some("/0"),
+        ..Default::default()
+    };
+    assert!(v.visit(&mut trace));
+    assert_eq!(
+        trace.calls,
+        [
+            r#"enter 0 "" None"#,
+            r#"enter 1 "/0" None"#,
+            r#"enter 1 "/1" None"#,
+            r#"leave "/1""#,
+            r#"enter 1 "/2" None"#,
+            r#"leave """#,
+        ]
+    );
+
+    // stopping leaves nothing else, not even the open containers
+    let mut trace = Trace {
+        stop: Some("/0/a~1b/0"),
+        ..Default::default()
+    };
+    assert!(!v.visit(&mut trace));
+    assert_eq!(trace.calls.len(), 4);
+    assert_eq!(trace.calls[3], r#"enter 3 "/0/a~1b/0" None"#);
+}
+
+#[test]
+fn visited_with_closure() {
+    let v = parse(br#"{"keep": [1, 2], "skip": [3, 4], "n": 5}"#).unwrap();
+    let mut sum = 0.0;
+    let mut deepest = 0;
+    v.visit(&mut |value: &JSONValue, cx: &VisitContext| {
+        deepest = deepest.max(cx.depth());
+        if cx.key() == Some("skip") {
+            return VisitAction::SkipSubtree;
+        }
+        if let JSONValue::Num(n) = value {
+            sum += n;
+        }
+        VisitAction::Continue
+    });
+    assert_eq!(sum, 8.0);
+    assert_eq!(deepest, 2);
+}
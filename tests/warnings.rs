@@ -0,0 +1,69 @@
+use json_parser::{
+    parse, parse_with_comments, parse_with_warnings, Document, JSONValue, ParseOptions, WarningKind,
+};
+
+#[test]
+fn warned() {
+    let input = "\u{feff}{\"a\": 1, \"b\": {\"n\": 12345678901234567891}, \"a\": 2}";
+    let options = ParseOptions {
+        strip_bom: true,
+        ..ParseOptions::default()
+    };
+    let (value, warnings) = parse_with_warnings(input.as_bytes(), &options).unwrap();
+    assert_eq!(
+        value,
+        parse(br#"{"a": 2, "b": {"n": 12345678901234567891}}"#).unwrap()
+    );
+    let seen: Vec<_> = warnings
+        .iter()
+        .map(|w| (w.kind(), w.pointer(), w.offset()))
+        .collect();
+    assert_eq!(
+        seen,
+        [
+            (WarningKind::BomStripped, "", 0),
+            (WarningKind::PrecisionLoss, "/b/n", 23),
+            (WarningKind::DuplicateKey, "/a", 46),
+        ]
+    );
+    assert_eq!(
+        warnings[2].to_string(),
+        "/a (byte 46): Duplicate key; the last value is kept"
+    );
+
+    assert!(parse(input.as_bytes()).is_err());
+
+    let options = ParseOptions {
+        max_depth: Some(4),
+        ..ParseOptions::default()
+    };
+    let (_, warnings) = parse_with_warnings(b"[[[0.1, [1]]], [[[]]]]", &options).unwrap();
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].kind(), WarningKind::DeepNesting);
+    assert_eq!((warnings[0].pointer(), warnings[0].offset()), ("/0/0", 2));
+    assert!(parse_with_warnings(b"[[[[[]]]]]", &options).is_err());
+}
+
+#[test]
+fn bom_stripped_everywhere() {
+    let input = "\u{feff}{\"a\": [1]}".as_bytes();
+    let commented = "\u{feff}{\"a\": [1] /* one */}".as_bytes();
+    let options = ParseOptions {
+        strip_bom: true,
+        ..ParseOptions::default()
+    };
+    let expected = parse(br#"{"a": [1]}"#).unwrap();
+    assert_eq!(
+        JSONValue::parse_with_options(input, &options).unwrap(),
+        expected
+    );
+    let (value, comments) = parse_with_comments(commented, &options).unwrap();
+    assert_eq!(value, expected);
+    // spans stay offsets into the whole input
+    assert_eq!(&commented[comments[0].span.clone()], b"/* one */");
+    let doc = Document::parse_with_options(input, &options).unwrap();
+    assert_eq!(doc.root().get("a").unwrap().span().start, 9);
+
+    assert!(JSONValue::parse_with_options(input, &ParseOptions::default()).is_err());
+    assert!(parse_with_comments(commented, &ParseOptions::default()).is_err());
+}